@@ -0,0 +1,9 @@
+-- Per-project settings stored as a JSON document so new policy fields
+-- can be added without a migration for each one.
+CREATE TABLE project_settings (
+    project_id  BLOB PRIMARY KEY,
+    settings    TEXT NOT NULL DEFAULT '{}',
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
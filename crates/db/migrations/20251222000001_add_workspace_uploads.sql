@@ -0,0 +1,15 @@
+-- Files uploaded into a workspace through the API, so the UI can list what
+-- was handed to the agent. Re-uploading a path replaces its row.
+CREATE TABLE workspace_uploads (
+    id            BLOB PRIMARY KEY,
+    workspace_id  BLOB NOT NULL,
+    path          TEXT NOT NULL,
+    size_bytes    INTEGER NOT NULL,
+    hash          TEXT NOT NULL,
+    uploaded_by   TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    UNIQUE (workspace_id, path)
+);
+
+CREATE INDEX idx_workspace_uploads_workspace_id ON workspace_uploads(workspace_id);
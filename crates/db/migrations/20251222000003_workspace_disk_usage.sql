@@ -0,0 +1,8 @@
+-- Last measured size of each workspace directory, refreshed by the cleanup
+-- janitor.
+CREATE TABLE workspace_disk_usage (
+    workspace_id  BLOB PRIMARY KEY,
+    size_bytes    INTEGER NOT NULL,
+    measured_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- "Blocked by" relationships between tasks of the same project. A task is
+-- blocked while any task it depends on isn't done.
+CREATE TABLE task_dependencies (
+    task_id             BLOB NOT NULL,
+    depends_on_task_id  BLOB NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, depends_on_task_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (depends_on_task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    CHECK (task_id != depends_on_task_id)
+);
+
+CREATE INDEX idx_task_dependencies_depends_on_task_id ON task_dependencies(depends_on_task_id);
@@ -0,0 +1,34 @@
+-- Schedules that create a task on a cron expression, optionally starting an
+-- attempt for it, and the history of what each run created.
+CREATE TABLE task_schedules (
+    id               BLOB PRIMARY KEY,
+    project_id       BLOB NOT NULL,
+    title            TEXT NOT NULL,
+    description      TEXT,
+    cron_expression  TEXT NOT NULL,
+    attempt          TEXT,
+    paused           BOOLEAN NOT NULL DEFAULT 0,
+    next_run_at      TEXT NOT NULL,
+    last_run_at      TEXT,
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_schedules_project_id ON task_schedules(project_id);
+CREATE INDEX idx_task_schedules_next_run_at ON task_schedules(next_run_at);
+
+CREATE TABLE task_schedule_runs (
+    id             BLOB PRIMARY KEY,
+    schedule_id    BLOB NOT NULL,
+    scheduled_for  TEXT NOT NULL,
+    task_id        BLOB,
+    workspace_id   BLOB,
+    error          TEXT,
+    created_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (schedule_id) REFERENCES task_schedules(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_task_schedule_runs_schedule_id ON task_schedule_runs(schedule_id);
@@ -0,0 +1,17 @@
+-- Priority of a task and its place within its board column. Positions are
+-- only compared between tasks of the same project and status.
+ALTER TABLE tasks ADD COLUMN priority TEXT NOT NULL DEFAULT 'normal'
+    CHECK (priority IN ('urgent','high','normal','low'));
+ALTER TABLE tasks ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+-- Keep the newest-first order columns had so far
+UPDATE tasks
+   SET position = (
+       SELECT COUNT(*)
+         FROM tasks newer
+        WHERE newer.project_id = tasks.project_id
+          AND newer.status = tasks.status
+          AND newer.created_at > tasks.created_at
+   );
+
+CREATE INDEX idx_tasks_project_status_position ON tasks(project_id, status, position);
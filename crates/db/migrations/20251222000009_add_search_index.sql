@@ -0,0 +1,58 @@
+-- Full-text index over task titles and descriptions and over the prompts and
+-- final summaries of coding agent turns. Rows carry the ids they were built
+-- from and are kept in sync by triggers.
+CREATE VIRTUAL TABLE search_index USING fts5(
+    task_id UNINDEXED,
+    coding_agent_turn_id UNINDEXED,
+    title,
+    body,
+    tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER search_index_task_insert AFTER INSERT ON tasks BEGIN
+    INSERT INTO search_index (task_id, coding_agent_turn_id, title, body)
+    VALUES (new.id, NULL, new.title, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER search_index_task_update AFTER UPDATE OF title, description ON tasks BEGIN
+    DELETE FROM search_index WHERE task_id = old.id AND coding_agent_turn_id IS NULL;
+    INSERT INTO search_index (task_id, coding_agent_turn_id, title, body)
+    VALUES (new.id, NULL, new.title, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER search_index_task_delete AFTER DELETE ON tasks BEGIN
+    DELETE FROM search_index WHERE task_id = old.id;
+END;
+
+CREATE TRIGGER search_index_turn_insert AFTER INSERT ON coding_agent_turns BEGIN
+    INSERT INTO search_index (task_id, coding_agent_turn_id, title, body)
+    SELECT w.task_id, new.id, '', COALESCE(new.prompt, '') || char(10) || COALESCE(new.summary, '')
+      FROM execution_processes ep
+      JOIN sessions s ON s.id = ep.session_id
+      JOIN workspaces w ON w.id = s.workspace_id
+     WHERE ep.id = new.execution_process_id;
+END;
+
+CREATE TRIGGER search_index_turn_update AFTER UPDATE OF prompt, summary ON coding_agent_turns BEGIN
+    DELETE FROM search_index WHERE coding_agent_turn_id = old.id;
+    INSERT INTO search_index (task_id, coding_agent_turn_id, title, body)
+    SELECT w.task_id, new.id, '', COALESCE(new.prompt, '') || char(10) || COALESCE(new.summary, '')
+      FROM execution_processes ep
+      JOIN sessions s ON s.id = ep.session_id
+      JOIN workspaces w ON w.id = s.workspace_id
+     WHERE ep.id = new.execution_process_id;
+END;
+
+CREATE TRIGGER search_index_turn_delete AFTER DELETE ON coding_agent_turns BEGIN
+    DELETE FROM search_index WHERE coding_agent_turn_id = old.id;
+END;
+
+INSERT INTO search_index (task_id, coding_agent_turn_id, title, body)
+SELECT id, NULL, title, COALESCE(description, '') FROM tasks;
+
+INSERT INTO search_index (task_id, coding_agent_turn_id, title, body)
+SELECT w.task_id, cat.id, '', COALESCE(cat.prompt, '') || char(10) || COALESCE(cat.summary, '')
+  FROM coding_agent_turns cat
+  JOIN execution_processes ep ON ep.id = cat.execution_process_id
+  JOIN sessions s ON s.id = ep.session_id
+  JOIN workspaces w ON w.id = s.workspace_id;
@@ -0,0 +1,17 @@
+-- Tasks imported from GitHub issues remember where they came from, so an
+-- issue is imported once and can be closed when its task is done.
+CREATE TABLE github_issue_links (
+    id            BLOB PRIMARY KEY,
+    task_id       BLOB NOT NULL UNIQUE,
+    project_id    BLOB NOT NULL,
+    repo_owner    TEXT NOT NULL,
+    repo_name     TEXT NOT NULL,
+    issue_number  INTEGER NOT NULL,
+    issue_url     TEXT NOT NULL,
+    sync_status   BOOLEAN NOT NULL DEFAULT 0,
+    closed_at     TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, repo_owner, repo_name, issue_number)
+);
@@ -0,0 +1,30 @@
+-- Board columns a project defines on top of the fixed task statuses, such as
+-- "Needs review" and "QA" both mapped to inreview. Projects get one column per
+-- status the first time their columns are read.
+CREATE TABLE board_columns (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    name        TEXT NOT NULL,
+    position    INTEGER NOT NULL,
+    status      TEXT NOT NULL
+                   CHECK (status IN ('todo','inprogress','inreview','done','cancelled')),
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, name)
+);
+
+CREATE INDEX idx_board_columns_project_id ON board_columns(project_id, position);
+
+-- NULL puts the task in the first column of its status
+ALTER TABLE tasks ADD COLUMN column_id BLOB REFERENCES board_columns(id) ON DELETE SET NULL;
+
+-- A task whose status changes outside its column moves to the new status's
+-- first column
+CREATE TRIGGER tasks_leave_column_on_status_change
+AFTER UPDATE OF status ON tasks
+WHEN NEW.column_id IS NOT NULL
+ AND NEW.status != (SELECT status FROM board_columns WHERE id = NEW.column_id)
+BEGIN
+    UPDATE tasks SET column_id = NULL WHERE id = NEW.id;
+END;
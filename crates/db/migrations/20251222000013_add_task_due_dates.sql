@@ -0,0 +1,11 @@
+-- Deadline of a task, and when the reminder about it was sent
+ALTER TABLE tasks ADD COLUMN due_at TEXT;
+ALTER TABLE tasks ADD COLUMN due_reminder_sent_at TEXT;
+
+-- A new deadline gets a reminder of its own
+CREATE TRIGGER tasks_reset_due_reminder
+AFTER UPDATE OF due_at ON tasks
+WHEN NEW.due_at IS NOT OLD.due_at
+BEGIN
+    UPDATE tasks SET due_reminder_sent_at = NULL WHERE id = NEW.id;
+END;
@@ -0,0 +1,14 @@
+-- Time logged on a task by hand, such as reviewing or testing an attempt's
+-- changes. Time spent by the attempts themselves is taken from their
+-- execution processes.
+CREATE TABLE task_time_entries (
+    id          BLOB PRIMARY KEY,
+    task_id     BLOB NOT NULL,
+    seconds     INTEGER NOT NULL CHECK (seconds > 0),
+    note        TEXT,
+    spent_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_time_entries_task_id ON task_time_entries(task_id);
@@ -0,0 +1,17 @@
+-- Typed links between tasks of the same project. A link reads from task_id to
+-- linked_task_id, e.g. task_id follows up linked_task_id. Unlike dependencies
+-- they don't block anything.
+CREATE TABLE task_links (
+    id              BLOB PRIMARY KEY,
+    task_id         BLOB NOT NULL,
+    linked_task_id  BLOB NOT NULL,
+    link_type       TEXT NOT NULL
+                       CHECK (link_type IN ('relates_to','duplicates','follows_up')),
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (linked_task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    UNIQUE (task_id, linked_task_id, link_type),
+    CHECK (task_id != linked_task_id)
+);
+
+CREATE INDEX idx_task_links_linked_task_id ON task_links(linked_task_id);
@@ -0,0 +1,40 @@
+-- Epics group the tasks of a larger piece of work, such as a milestone, so
+-- that its progress can be followed in one place.
+CREATE TABLE epics (
+    id           BLOB PRIMARY KEY,
+    project_id   BLOB NOT NULL,
+    name         TEXT NOT NULL,
+    description  TEXT,
+    target_date  TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_epics_project_id ON epics(project_id);
+
+ALTER TABLE tasks ADD COLUMN epic_id BLOB REFERENCES epics(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tasks_epic_id ON tasks(epic_id);
+
+-- When the task was last marked done, for charting how an epic's work got
+-- finished over time. Cleared when the task is reopened.
+ALTER TABLE tasks ADD COLUMN done_at TEXT;
+
+UPDATE tasks SET done_at = updated_at WHERE status = 'done';
+
+CREATE TRIGGER tasks_set_done_at_on_insert
+AFTER INSERT ON tasks
+WHEN NEW.status = 'done'
+BEGIN
+    UPDATE tasks SET done_at = datetime('now', 'subsec') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER tasks_set_done_at_on_status_change
+AFTER UPDATE OF status ON tasks
+WHEN NEW.status IS NOT OLD.status
+BEGIN
+    UPDATE tasks
+    SET done_at = CASE WHEN NEW.status = 'done' THEN datetime('now', 'subsec') END
+    WHERE id = NEW.id;
+END;
@@ -0,0 +1,5 @@
+-- Executor profile a task's attempts use instead of the configured default,
+-- and the profile each attempt was actually started with. Both hold an
+-- ExecutorProfileId as JSON.
+ALTER TABLE tasks ADD COLUMN executor_profile_id TEXT;
+ALTER TABLE workspaces ADD COLUMN executor_profile_id TEXT;
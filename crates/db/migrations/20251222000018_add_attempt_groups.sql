@@ -0,0 +1,20 @@
+-- Attempts started together for one task, each with its own executor
+-- profile, so that they can be followed as a group and raced against each
+-- other.
+CREATE TABLE attempt_groups (
+    id          BLOB PRIMARY KEY,
+    task_id     BLOB NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_attempt_groups_task_id ON attempt_groups(task_id);
+
+CREATE TABLE attempt_group_workspaces (
+    workspace_id      BLOB PRIMARY KEY,
+    attempt_group_id  BLOB NOT NULL,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (attempt_group_id) REFERENCES attempt_groups(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_attempt_group_workspaces_group_id ON attempt_group_workspaces(attempt_group_id);
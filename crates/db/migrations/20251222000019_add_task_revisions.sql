@@ -0,0 +1,15 @@
+-- Versions of a task's title and description, one per edit, so that a spec
+-- rewritten while work is under way can be looked back on and restored.
+-- author is NULL for the version a task had before its first recorded edit.
+CREATE TABLE task_revisions (
+    id           BLOB PRIMARY KEY,
+    task_id      BLOB NOT NULL,
+    title        TEXT NOT NULL,
+    description  TEXT,
+    author       TEXT CHECK (author IN ('user','agent')),
+    author_name  TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_revisions_task_id ON task_revisions(task_id, created_at);
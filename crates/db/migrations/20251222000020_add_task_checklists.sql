@@ -0,0 +1,25 @@
+-- Checklist items of a task, such as a project's definition of done
+CREATE TABLE task_checklist_items (
+    id          BLOB PRIMARY KEY,
+    task_id     BLOB NOT NULL,
+    label       TEXT NOT NULL,
+    checked     BOOLEAN NOT NULL DEFAULT FALSE,
+    position    INTEGER NOT NULL,
+    checked_at  TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_checklist_items_task_id ON task_checklist_items(task_id, position);
+
+-- Every new task starts with the checklist its project's settings define,
+-- whichever way it was created
+CREATE TRIGGER tasks_attach_default_checklist
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO task_checklist_items (id, task_id, label, position)
+    SELECT randomblob(16), NEW.id, item.value, item.key
+    FROM project_settings ps, json_each(ps.settings, '$.definition_of_done.items') item
+    WHERE ps.project_id = NEW.project_id
+      AND trim(item.value) != '';
+END;
@@ -0,0 +1,75 @@
+-- What happened on a project's board, newest last, for the activity feed.
+-- Rows are written by triggers so that every way of changing the board is
+-- recorded, whichever code path made the change.
+CREATE TABLE activity_events (
+    id           BLOB PRIMARY KEY,
+    project_id   BLOB NOT NULL,
+    task_id      BLOB,
+    workspace_id BLOB,
+    -- JSON tagged by "type", e.g. {"type":"task_moved","from":"todo","to":"inprogress"}
+    activity     TEXT NOT NULL,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_activity_events_project_id ON activity_events(project_id);
+
+CREATE TRIGGER activity_task_created
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO activity_events (id, project_id, task_id, activity)
+    VALUES (
+        randomblob(16), NEW.project_id, NEW.id,
+        json_object('type', 'task_created', 'status', NEW.status)
+    );
+END;
+
+CREATE TRIGGER activity_task_moved
+AFTER UPDATE OF status ON tasks
+WHEN NEW.status IS NOT OLD.status
+BEGIN
+    INSERT INTO activity_events (id, project_id, task_id, activity)
+    VALUES (
+        randomblob(16), NEW.project_id, NEW.id,
+        json_object('type', 'task_moved', 'from', OLD.status, 'to', NEW.status)
+    );
+END;
+
+CREATE TRIGGER activity_attempt_started
+AFTER INSERT ON workspaces
+BEGIN
+    INSERT INTO activity_events (id, project_id, task_id, workspace_id, activity)
+    SELECT randomblob(16), t.project_id, t.id, NEW.id,
+           json_object('type', 'attempt_started', 'branch', NEW.branch)
+    FROM tasks t
+    WHERE t.id = NEW.task_id;
+END;
+
+CREATE TRIGGER activity_attempt_finished
+AFTER UPDATE OF status ON execution_processes
+WHEN NEW.run_reason = 'codingagent'
+ AND OLD.status = 'running'
+ AND NEW.status != 'running'
+BEGIN
+    INSERT INTO activity_events (id, project_id, task_id, workspace_id, activity)
+    SELECT randomblob(16), t.project_id, t.id, w.id,
+           json_object('type', 'attempt_finished', 'status', NEW.status, 'executor', s.executor)
+    FROM sessions s
+    JOIN workspaces w ON w.id = s.workspace_id
+    JOIN tasks t ON t.id = w.task_id
+    WHERE s.id = NEW.session_id;
+END;
+
+CREATE TRIGGER activity_pr_opened
+AFTER INSERT ON merges
+WHEN NEW.merge_type = 'pr'
+BEGIN
+    INSERT INTO activity_events (id, project_id, task_id, workspace_id, activity)
+    SELECT randomblob(16), t.project_id, t.id, w.id,
+           json_object('type', 'pr_opened', 'pr_number', NEW.pr_number, 'pr_url', NEW.pr_url)
+    FROM workspaces w
+    JOIN tasks t ON t.id = w.task_id
+    WHERE w.id = NEW.workspace_id;
+END;
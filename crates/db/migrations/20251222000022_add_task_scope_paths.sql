@@ -0,0 +1,3 @@
+-- Paths an attempt at the task is meant to change, as a JSON array of
+-- gitignore-style patterns. Empty leaves the whole repository in scope.
+ALTER TABLE tasks ADD COLUMN scope_paths TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,16 @@
+-- Tasks in each board column by day, for burndown and cumulative flow
+-- charts. The current day's rows are rewritten until the day is over. Column
+-- names and statuses are copied so that history outlives renamed and removed
+-- columns.
+CREATE TABLE board_snapshots (
+    project_id  BLOB NOT NULL,
+    day         TEXT NOT NULL,
+    column_id   BLOB NOT NULL,
+    column_name TEXT NOT NULL,
+    status      TEXT NOT NULL,
+    position    INTEGER NOT NULL,
+    task_count  INTEGER NOT NULL,
+    recorded_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (project_id, day, column_id),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
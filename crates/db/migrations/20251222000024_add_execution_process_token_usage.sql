@@ -0,0 +1,8 @@
+-- Tokens a coding agent run used, as last reported by its executor. NULL when
+-- the executor reported nothing; cost_usd stays NULL for executors that only
+-- report tokens.
+ALTER TABLE execution_processes ADD COLUMN input_tokens INTEGER;
+ALTER TABLE execution_processes ADD COLUMN output_tokens INTEGER;
+ALTER TABLE execution_processes ADD COLUMN cache_read_tokens INTEGER;
+ALTER TABLE execution_processes ADD COLUMN cache_write_tokens INTEGER;
+ALTER TABLE execution_processes ADD COLUMN cost_usd REAL;
@@ -0,0 +1,17 @@
+-- Automatic retries of failed coding agent runs. Each row links the run that
+-- failed to the run started in its place; the rows of a workspace count
+-- against its retry limit.
+CREATE TABLE execution_process_retries (
+    id                BLOB PRIMARY KEY,
+    workspace_id      BLOB NOT NULL,
+    failed_process_id BLOB NOT NULL,
+    retry_process_id  BLOB NOT NULL,
+    failure_kind      TEXT NOT NULL,
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (failed_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (retry_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_process_retries_workspace_id
+    ON execution_process_retries(workspace_id);
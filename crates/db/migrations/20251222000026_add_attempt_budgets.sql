@@ -0,0 +1,12 @@
+-- Budget limits of attempts. Coding agent runs keep the number of tool calls
+-- they made, and each attempt how often its budget was extended and which
+-- limit stopped it last, if any.
+ALTER TABLE execution_processes ADD COLUMN tool_calls INTEGER;
+
+CREATE TABLE workspace_budgets (
+    workspace_id BLOB PRIMARY KEY,
+    extensions   INTEGER NOT NULL DEFAULT 0,
+    stopped_by   TEXT,
+    stopped_at   TEXT,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
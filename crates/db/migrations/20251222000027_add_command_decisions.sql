@@ -0,0 +1,19 @@
+-- Every answer to an agent's request to run a shell command, whether the
+-- project's command policy gave it or the user did, kept on the attempt for
+-- audit.
+CREATE TABLE command_decisions (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL,
+    tool_call_id         TEXT NOT NULL,
+    command              TEXT NOT NULL,
+    status               TEXT NOT NULL,
+    decided_by           TEXT NOT NULL,
+    rule                 TEXT,
+    reason               TEXT,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_command_decisions_workspace_id ON command_decisions(workspace_id);
@@ -0,0 +1,15 @@
+-- Coding agent runs that were still going when the server stopped. Their
+-- output up to that point is in execution_process_logs; the row stays open
+-- until the attempt is resumed from it.
+CREATE TABLE execution_process_interruptions (
+    execution_process_id BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    resumed_process_id   BLOB,
+    interrupted_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (resumed_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_execution_process_interruptions_workspace_id
+    ON execution_process_interruptions(workspace_id);
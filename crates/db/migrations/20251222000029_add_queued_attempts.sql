@@ -0,0 +1,8 @@
+-- Attempts waiting for a slot under the global or project limit on attempts
+-- running at once. A row is removed when its attempt starts or is cancelled.
+CREATE TABLE queued_attempts (
+    workspace_id        BLOB PRIMARY KEY,
+    executor_profile_id TEXT NOT NULL,
+    enqueued_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+-- Test counts read from the output of execution processes that ran a test
+-- suite; the latest of an attempt is shown on its task card.
+CREATE TABLE test_runs (
+    execution_process_id BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    runner               TEXT NOT NULL,
+    passed               INTEGER NOT NULL,
+    failed               INTEGER NOT NULL,
+    skipped              INTEGER NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_test_runs_workspace_id ON test_runs(workspace_id);
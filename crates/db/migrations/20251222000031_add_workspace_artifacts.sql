@@ -0,0 +1,18 @@
+-- Files agents published under $VK_ARTIFACTS for review beside the diff,
+-- recorded when the run that wrote them ends. A row follows its file: it
+-- moves to the run that last changed the file and goes when the file does.
+CREATE TABLE workspace_artifacts (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL,
+    name                 TEXT NOT NULL,
+    size_bytes           INTEGER NOT NULL,
+    hash                 TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    UNIQUE (workspace_id, name)
+);
+
+CREATE INDEX idx_workspace_artifacts_workspace_id ON workspace_artifacts(workspace_id);
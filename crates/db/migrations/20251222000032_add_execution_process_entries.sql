@@ -0,0 +1,9 @@
+-- Conversation entries of coding agent runs as they stood when the run
+-- ended, with the times they were added at. Log searches read them instead of
+-- normalizing the raw output again, which would lose the times.
+CREATE TABLE execution_process_entries (
+    execution_process_id BLOB PRIMARY KEY,
+    entries              TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
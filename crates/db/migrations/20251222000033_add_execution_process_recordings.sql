@@ -0,0 +1,10 @@
+-- The patches to the conversation of coding agent runs, each with how long
+-- into the run it was made, so a run can be replayed at the pace it happened.
+-- Entries only keep the time they were added, not when they were updated.
+CREATE TABLE execution_process_recordings (
+    execution_process_id BLOB PRIMARY KEY,
+    events               TEXT NOT NULL,
+    duration_ms          INTEGER NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
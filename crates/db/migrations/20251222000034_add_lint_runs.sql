@@ -0,0 +1,16 @@
+-- Results of the project's lint checks, run in the workspace once an
+-- attempt's agent and cleanup script are done. Keyed by the process whose end
+-- started them, with the fix-up run they led to, if any.
+CREATE TABLE lint_runs (
+    execution_process_id     BLOB PRIMARY KEY,
+    workspace_id             BLOB NOT NULL,
+    checks                   TEXT NOT NULL,
+    passed                   BOOLEAN NOT NULL,
+    fix_execution_process_id BLOB,
+    created_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (fix_execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_lint_runs_workspace_id ON lint_runs(workspace_id);
@@ -0,0 +1,22 @@
+-- Reviews of an attempt's changes by a second agent, run in a session of
+-- their own once the attempt's agent is done. Comments are stored as JSON and
+-- the revision run is the follow-up that was handed them.
+CREATE TABLE agent_reviews (
+    id                            BLOB PRIMARY KEY,
+    workspace_id                  BLOB NOT NULL,
+    reviewed_execution_process_id BLOB NOT NULL,
+    review_execution_process_id   BLOB NOT NULL UNIQUE,
+    status                        TEXT NOT NULL DEFAULT 'running'
+                                  CHECK (status IN ('running', 'approved', 'changes_requested', 'failed')),
+    summary                       TEXT,
+    comments                      TEXT NOT NULL DEFAULT '[]',
+    revision_execution_process_id BLOB,
+    created_at                    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (reviewed_execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (review_execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (revision_execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_agent_reviews_workspace_id ON agent_reviews(workspace_id);
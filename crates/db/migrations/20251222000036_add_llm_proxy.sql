@@ -0,0 +1,32 @@
+-- The LLM proxy: the token each attempt's agents use in place of a provider
+-- key, and a log of the calls made through it. Calls stay logged after their
+-- workspace is removed so they keep counting towards the project's spend.
+CREATE TABLE llm_proxy_tokens (
+    token        TEXT PRIMARY KEY,
+    workspace_id BLOB NOT NULL UNIQUE,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+
+CREATE TABLE llm_proxy_requests (
+    id            BLOB PRIMARY KEY,
+    project_id    BLOB NOT NULL,
+    workspace_id  BLOB,
+    provider      TEXT NOT NULL,
+    method        TEXT NOT NULL,
+    path          TEXT NOT NULL,
+    model         TEXT,
+    status_code   INTEGER,
+    input_tokens  INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd      REAL NOT NULL DEFAULT 0,
+    duration_ms   INTEGER NOT NULL,
+    error         TEXT,
+    request_body  TEXT,
+    response_body TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_llm_proxy_requests_project_id_created_at ON llm_proxy_requests(project_id, created_at);
@@ -0,0 +1,17 @@
+-- Runs of the project's lifecycle hooks, kept per attempt so a failing hook
+-- can be looked into.
+CREATE TABLE hook_runs (
+    id           BLOB PRIMARY KEY,
+    workspace_id BLOB NOT NULL,
+    hook_name    TEXT NOT NULL,
+    event        TEXT NOT NULL
+                 CHECK (event IN ('before_start', 'after_finish', 'after_merge')),
+    status       TEXT NOT NULL
+                 CHECK (status IN ('succeeded', 'failed', 'timed_out')),
+    output       TEXT NOT NULL,
+    duration_ms  INTEGER NOT NULL,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_hook_runs_workspace_id ON hook_runs(workspace_id);
@@ -0,0 +1,21 @@
+-- Plan-only runs of an attempt's agent, made in a throwaway copy of the
+-- workspace. The plan is the agent's last message and the patch what it
+-- changed in the copy; approving one starts the real run.
+CREATE TABLE dry_runs (
+    id                            BLOB PRIMARY KEY,
+    workspace_id                  BLOB NOT NULL,
+    execution_process_id          BLOB NOT NULL UNIQUE,
+    prompt                        TEXT NOT NULL,
+    status                        TEXT NOT NULL DEFAULT 'running'
+                                  CHECK (status IN ('running', 'ready', 'failed', 'approved', 'discarded')),
+    plan                          TEXT,
+    patch                         TEXT,
+    approved_execution_process_id BLOB,
+    created_at                    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at                    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (approved_execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_dry_runs_workspace_id ON dry_runs(workspace_id);
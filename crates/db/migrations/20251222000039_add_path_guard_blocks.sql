@@ -0,0 +1,15 @@
+-- Runs whose changes touched the project's protected paths. Their changes
+-- stay uncommitted until the user overrides the block, which also lets the
+-- same paths through for the rest of the attempt.
+CREATE TABLE path_guard_blocks (
+    id                   BLOB PRIMARY KEY,
+    workspace_id         BLOB NOT NULL,
+    execution_process_id BLOB NOT NULL UNIQUE,
+    paths                TEXT NOT NULL DEFAULT '[]',
+    overridden_at        TEXT,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_path_guard_blocks_workspace_id ON path_guard_blocks(workspace_id);
@@ -0,0 +1,12 @@
+-- Attempts based on another attempt's branch instead of the repository's.
+-- Rebasing the parent rebases its children onto it, and their pull requests
+-- target the parent's branch.
+CREATE TABLE workspace_stacks (
+    workspace_id        BLOB PRIMARY KEY,
+    parent_workspace_id BLOB NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_workspace_stacks_parent_workspace_id ON workspace_stacks(parent_workspace_id);
@@ -0,0 +1,6 @@
+-- How each merge was made. Direct merges were always squashed until the
+-- strategy became a project setting; pull requests merged on the code host
+-- itself have none recorded.
+ALTER TABLE merges ADD COLUMN merge_strategy TEXT;
+
+UPDATE merges SET merge_strategy = 'squash' WHERE merge_type = 'direct';
@@ -0,0 +1,18 @@
+-- Files and hunks of an attempt's diff chosen for a partial merge or pull
+-- request, kept for audit with the commits they were chosen between and the
+-- merge they went into.
+CREATE TABLE merge_selections (
+    id           BLOB PRIMARY KEY,
+    workspace_id BLOB NOT NULL,
+    repo_id      BLOB NOT NULL,
+    base_commit  TEXT NOT NULL,
+    head_commit  TEXT NOT NULL,
+    files        TEXT NOT NULL DEFAULT '[]',
+    merge_id     BLOB,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE,
+    FOREIGN KEY (merge_id) REFERENCES merges(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_merge_selections_workspace_id ON merge_selections(workspace_id);
@@ -0,0 +1,16 @@
+-- Last periodic check of each open attempt's repository against its target
+-- branch: how far behind it is, what would conflict, and whether the check
+-- brought it up to date.
+CREATE TABLE branch_sync_statuses (
+    workspace_id     BLOB NOT NULL,
+    repo_id          BLOB NOT NULL,
+    target_branch    TEXT NOT NULL,
+    commits_behind   INTEGER NOT NULL DEFAULT 0,
+    conflicted_files TEXT NOT NULL DEFAULT '[]',
+    action           TEXT,
+    error            TEXT,
+    checked_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (workspace_id, repo_id),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
@@ -0,0 +1,20 @@
+-- CI checks of an attempt's open pull request, as last polled from its code
+-- host: GitHub check runs and commit statuses, or the jobs of the latest
+-- GitLab pipeline. Each poll replaces the pull request's checks.
+CREATE TABLE ci_checks (
+    id           BLOB PRIMARY KEY,
+    merge_id     BLOB NOT NULL,
+    workspace_id BLOB NOT NULL,
+    repo_id      BLOB NOT NULL,
+    head_commit  TEXT NOT NULL,
+    name         TEXT NOT NULL,
+    state        TEXT NOT NULL,
+    url          TEXT,
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (merge_id) REFERENCES merges(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_ci_checks_workspace_id ON ci_checks(workspace_id);
+CREATE INDEX idx_ci_checks_merge_id ON ci_checks(merge_id);
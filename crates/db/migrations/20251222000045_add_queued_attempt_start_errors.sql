@@ -0,0 +1,3 @@
+-- Why a queued attempt failed to start. It is put back in the queue with the
+-- error, and left there without being started again until it is stopped.
+ALTER TABLE queued_attempts ADD COLUMN start_error TEXT;
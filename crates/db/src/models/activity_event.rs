@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{execution_process::ExecutionProcessStatus, task::TaskStatus};
+
+/// Something that happened on a project's board, recorded by the database as
+/// the board changes. An attempt finishes each time one of its coding agent
+/// runs ends, follow-ups included.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum Activity {
+    TaskCreated {
+        status: TaskStatus,
+    },
+    TaskMoved {
+        from: TaskStatus,
+        to: TaskStatus,
+    },
+    AttemptStarted {
+        branch: String,
+    },
+    AttemptFinished {
+        status: ExecutionProcessStatus,
+        executor: Option<String>,
+    },
+    PrOpened {
+        pr_number: i64,
+        pr_url: String,
+    },
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ActivityEvent {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub task_id: Option<Uuid>,
+    pub workspace_id: Option<Uuid>,
+    /// Current title of the task
+    pub task_title: Option<String>,
+    #[ts(type = "Activity")]
+    pub activity: Json<Activity>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ActivityEvent {
+    pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ActivityEvent,
+            r#"SELECT ae.id as "id!: Uuid",
+                      ae.project_id as "project_id!: Uuid",
+                      ae.task_id as "task_id: Uuid",
+                      ae.workspace_id as "workspace_id: Uuid",
+                      t.title as "task_title?",
+                      ae.activity as "activity!: Json<Activity>",
+                      ae.created_at as "created_at!: DateTime<Utc>"
+               FROM activity_events ae
+               LEFT JOIN tasks t ON t.id = ae.task_id
+               WHERE ae.rowid = $1"#,
+            rowid
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Up to `limit` of the project's events, newest first, starting after the
+    /// event `before` when given
+    pub async fn find_page(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        before: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ActivityEvent,
+            r#"SELECT ae.id as "id!: Uuid",
+                      ae.project_id as "project_id!: Uuid",
+                      ae.task_id as "task_id: Uuid",
+                      ae.workspace_id as "workspace_id: Uuid",
+                      t.title as "task_title?",
+                      ae.activity as "activity!: Json<Activity>",
+                      ae.created_at as "created_at!: DateTime<Utc>"
+               FROM activity_events ae
+               LEFT JOIN tasks t ON t.id = ae.task_id
+               WHERE ae.project_id = $1
+                 AND ($2 IS NULL OR ae.rowid < (SELECT rowid FROM activity_events WHERE id = $2))
+               ORDER BY ae.rowid DESC
+               LIMIT $3"#,
+            project_id,
+            before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
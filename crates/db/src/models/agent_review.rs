@@ -0,0 +1,212 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use strum_macros::{Display, EnumString};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AgentReviewStatus {
+    Running,
+    Approved,
+    ChangesRequested,
+    /// The reviewer's run failed or its answer couldn't be read
+    Failed,
+}
+
+/// A remark of the reviewing agent, on a place in the code when it gave one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct AgentReviewComment {
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    pub body: String,
+    /// Whether the change has to be made before the attempt is done, rather
+    /// than being a suggestion
+    #[serde(default)]
+    pub blocking: bool,
+}
+
+/// A second agent's review of an attempt's changes
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AgentReview {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// The run of the attempt's agent, or the cleanup script after it, whose
+    /// end started the review
+    pub reviewed_execution_process_id: Uuid,
+    /// The reviewer's run
+    pub review_execution_process_id: Uuid,
+    pub status: AgentReviewStatus,
+    pub summary: Option<String>,
+    pub comments: Vec<AgentReviewComment>,
+    /// The follow-up run of the attempt's agent handed the comments
+    pub revision_execution_process_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct AgentReviewRow {
+    id: Uuid,
+    workspace_id: Uuid,
+    reviewed_execution_process_id: Uuid,
+    review_execution_process_id: Uuid,
+    status: AgentReviewStatus,
+    summary: Option<String>,
+    comments: Json<Vec<AgentReviewComment>>,
+    revision_execution_process_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<AgentReviewRow> for AgentReview {
+    fn from(row: AgentReviewRow) -> Self {
+        Self {
+            id: row.id,
+            workspace_id: row.workspace_id,
+            reviewed_execution_process_id: row.reviewed_execution_process_id,
+            review_execution_process_id: row.review_execution_process_id,
+            status: row.status,
+            summary: row.summary,
+            comments: row.comments.0,
+            revision_execution_process_id: row.revision_execution_process_id,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl AgentReview {
+    /// Newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            AgentReviewRow,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", reviewed_execution_process_id as "reviewed_execution_process_id!: Uuid", review_execution_process_id as "review_execution_process_id!: Uuid", status as "status!: AgentReviewStatus", summary, comments as "comments!: Json<Vec<AgentReviewComment>>", revision_execution_process_id as "revision_execution_process_id?: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM agent_reviews
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn find_by_review_execution_process_id(
+        pool: &SqlitePool,
+        review_execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query_as!(
+            AgentReviewRow,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", reviewed_execution_process_id as "reviewed_execution_process_id!: Uuid", review_execution_process_id as "review_execution_process_id!: Uuid", status as "status!: AgentReviewStatus", summary, comments as "comments!: Json<Vec<AgentReviewComment>>", revision_execution_process_id as "revision_execution_process_id?: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM agent_reviews
+               WHERE review_execution_process_id = $1"#,
+            review_execution_process_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    pub async fn count_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM agent_reviews WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Whether a reviewer runs in the session, rather than an attempt's agent
+    pub async fn is_review_session(
+        pool: &SqlitePool,
+        session_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM agent_reviews ar
+                   JOIN execution_processes ep ON ep.id = ar.review_execution_process_id
+                   WHERE ep.session_id = $1
+               ) as "exists!: bool""#,
+            session_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        reviewed_execution_process_id: Uuid,
+        review_execution_process_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let row = sqlx::query_as!(
+            AgentReviewRow,
+            r#"INSERT INTO agent_reviews (id, workspace_id, reviewed_execution_process_id, review_execution_process_id)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", reviewed_execution_process_id as "reviewed_execution_process_id!: Uuid", review_execution_process_id as "review_execution_process_id!: Uuid", status as "status!: AgentReviewStatus", summary, comments as "comments!: Json<Vec<AgentReviewComment>>", revision_execution_process_id as "revision_execution_process_id?: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            reviewed_execution_process_id,
+            review_execution_process_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(row.into())
+    }
+
+    /// Record what the reviewer answered
+    pub async fn complete(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: AgentReviewStatus,
+        summary: Option<&str>,
+        comments: &[AgentReviewComment],
+    ) -> Result<(), sqlx::Error> {
+        let comments = Json(comments);
+        sqlx::query!(
+            r#"UPDATE agent_reviews
+               SET status = $1, summary = $2, comments = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $4"#,
+            status,
+            summary,
+            comments,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_revision_execution_process_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        revision_execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE agent_reviews
+               SET revision_execution_process_id = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2"#,
+            revision_execution_process_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
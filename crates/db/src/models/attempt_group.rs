@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::execution_process::ExecutionProcessStatus;
+
+/// Attempts at one task that were started together
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AttemptGroup {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An attempt of a group with its latest coding agent run
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AttemptGroupMember {
+    pub workspace_id: Uuid,
+    #[ts(type = "ExecutorProfileId | null")]
+    pub executor_profile_id: Option<Json<ExecutorProfileId>>,
+    /// None until the coding agent has started
+    pub status: Option<ExecutionProcessStatus>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl AttemptGroup {
+    pub async fn create(pool: &SqlitePool, task_id: Uuid) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            AttemptGroup,
+            r#"INSERT INTO attempt_groups (id, task_id)
+               VALUES ($1, $2)
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptGroup,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM attempt_groups
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Newest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptGroup,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM attempt_groups
+               WHERE task_id = $1
+               ORDER BY created_at DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn add_workspace(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO attempt_group_workspaces (workspace_id, attempt_group_id) VALUES ($1, $2)",
+            workspace_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The group's attempts in the order they were created
+    pub async fn find_members(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Vec<AttemptGroupMember>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptGroupMember,
+            r#"SELECT w.id as "workspace_id!: Uuid",
+                      w.executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>",
+                      ep.status as "status: ExecutionProcessStatus",
+                      ep.completed_at as "completed_at: DateTime<Utc>"
+               FROM attempt_group_workspaces agw
+               JOIN workspaces w ON w.id = agw.workspace_id
+               LEFT JOIN execution_processes ep ON ep.id = (
+                   SELECT ep2.id
+                   FROM execution_processes ep2
+                   JOIN sessions s ON s.id = ep2.session_id
+                   WHERE s.workspace_id = w.id
+                     AND ep2.run_reason = 'codingagent'
+                     AND ep2.dropped = FALSE
+                   ORDER BY ep2.created_at DESC
+                   LIMIT 1
+               )
+               WHERE agw.attempt_group_id = $1
+               ORDER BY w.created_at ASC"#,
+            id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,322 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// Columns a project starts with, one per status
+const DEFAULT_COLUMNS: [(&str, TaskStatus); 5] = [
+    ("To Do", TaskStatus::Todo),
+    ("In Progress", TaskStatus::InProgress),
+    ("In Review", TaskStatus::InReview),
+    ("Done", TaskStatus::Done),
+    ("Cancelled", TaskStatus::Cancelled),
+];
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct BoardColumn {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    /// Place on the board, leftmost first
+    pub position: i64,
+    /// Status of the tasks in the column
+    pub status: TaskStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Tasks currently on the board in a column, archived ones left out
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ColumnTaskCount {
+    pub column_id: Uuid,
+    pub task_count: i64,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateBoardColumn {
+    pub name: String,
+    pub status: TaskStatus,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateBoardColumn {
+    pub name: Option<String>,
+    /// Changing it also changes the status of the column's tasks
+    pub status: Option<TaskStatus>,
+}
+
+impl BoardColumn {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BoardColumn,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      position,
+                      status as "status!: TaskStatus",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM board_columns
+               WHERE project_id = $1
+               ORDER BY position ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BoardColumn,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      position,
+                      status as "status!: TaskStatus",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM board_columns
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Columns of the project, giving it one column per status if it has none
+    /// yet
+    pub async fn find_or_create_defaults(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let columns = Self::find_by_project_id(pool, project_id).await?;
+        if !columns.is_empty() {
+            return Ok(columns);
+        }
+
+        let mut tx = pool.begin().await?;
+        for (position, (name, status)) in DEFAULT_COLUMNS.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let position = position as i64;
+            // Another request may have created them in the meantime
+            sqlx::query!(
+                r#"INSERT OR IGNORE INTO board_columns (id, project_id, name, position, status)
+                   VALUES ($1, $2, $3, $4, $5)"#,
+                id,
+                project_id,
+                name,
+                position,
+                status
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Self::find_by_project_id(pool, project_id).await
+    }
+
+    /// Number of tasks in each column of the project, in board order. Tasks
+    /// without a column count towards the first column of their status.
+    pub async fn task_counts(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<ColumnTaskCount>, sqlx::Error> {
+        sqlx::query_as!(
+            ColumnTaskCount,
+            r#"SELECT bc.id as "column_id!: Uuid",
+                      COUNT(t.id) as "task_count!: i64"
+               FROM board_columns bc
+               LEFT JOIN tasks t
+                 ON t.project_id = bc.project_id
+                AND t.status = bc.status
+                AND t.archived_at IS NULL
+                AND (t.column_id = bc.id
+                     OR (t.column_id IS NULL
+                         AND bc.position = (SELECT MIN(position)
+                                            FROM board_columns
+                                            WHERE project_id = bc.project_id AND status = bc.status)))
+               WHERE bc.project_id = $1
+               GROUP BY bc.id
+               ORDER BY bc.position ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Add a column at the right end of the board
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateBoardColumn,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            BoardColumn,
+            r#"INSERT INTO board_columns (id, project_id, name, position, status)
+               VALUES ($1, $2, $3,
+                       (SELECT COALESCE(MAX(position), -1) + 1 FROM board_columns WHERE project_id = $2),
+                       $4)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name,
+                         position,
+                         status as "status!: TaskStatus",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.name,
+            data.status
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        column: &BoardColumn,
+        data: &UpdateBoardColumn,
+    ) -> Result<Self, sqlx::Error> {
+        let name = data.name.as_ref().unwrap_or(&column.name);
+        let status = data.status.clone().unwrap_or_else(|| column.status.clone());
+
+        let mut tx = pool.begin().await?;
+        let updated = sqlx::query_as!(
+            BoardColumn,
+            r#"UPDATE board_columns
+               SET name = $2, status = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name,
+                         position,
+                         status as "status!: TaskStatus",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            column.id,
+            name,
+            status
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if status != column.status {
+            sqlx::query!(
+                "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE column_id = $1",
+                column.id,
+                status
+            )
+            .execute(&mut *tx)
+            .await?;
+            // The column may now lead its new status
+            Self::release_first_columns(&mut *tx, column.project_id).await?;
+        }
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    /// Remove the column; its tasks move to the first column of their status
+    pub async fn delete(pool: &SqlitePool, column: &BoardColumn) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let result = sqlx::query!("DELETE FROM board_columns WHERE id = $1", column.id)
+            .execute(&mut *tx)
+            .await?;
+        Self::release_first_columns(&mut *tx, column.project_id).await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Place the project's columns in the given order, left to right
+    pub async fn reorder(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        column_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for (position, id) in column_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                "UPDATE board_columns SET position = $3 WHERE id = $1 AND project_id = $2",
+                id,
+                project_id,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        Self::release_first_columns(&mut *tx, project_id).await?;
+        tx.commit().await
+    }
+
+    /// Tasks in the first column of their status are stored without a column,
+    /// so clear the column of tasks whose column has become the first one
+    async fn release_first_columns<'e, E>(executor: E, project_id: Uuid) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"UPDATE tasks
+               SET column_id = NULL
+               WHERE column_id IN (
+                   SELECT bc.id
+                   FROM board_columns bc
+                   WHERE bc.project_id = $1
+                     AND bc.position = (SELECT MIN(position)
+                                        FROM board_columns
+                                        WHERE project_id = bc.project_id AND status = bc.status)
+               )"#,
+            project_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
+
+/// First column of the status; tasks without a column are shown there
+pub fn first_column_for(columns: &[BoardColumn], status: &TaskStatus) -> Option<Uuid> {
+    columns
+        .iter()
+        .filter(|column| &column.status == status)
+        .min_by_key(|column| column.position)
+        .map(|column| column.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, position: i64, status: TaskStatus) -> BoardColumn {
+        BoardColumn {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            name: name.to_string(),
+            position,
+            status,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn tasks_without_a_column_go_to_the_first_of_their_status() {
+        let columns = vec![
+            column("Done", 3, TaskStatus::Done),
+            column("QA", 2, TaskStatus::InReview),
+            column("Needs review", 1, TaskStatus::InReview),
+            column("To Do", 0, TaskStatus::Todo),
+        ];
+
+        assert_eq!(
+            first_column_for(&columns, &TaskStatus::InReview),
+            Some(columns[2].id)
+        );
+        assert_eq!(first_column_for(&columns, &TaskStatus::Cancelled), None);
+    }
+}
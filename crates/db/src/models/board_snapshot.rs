@@ -0,0 +1,90 @@
+use chrono::NaiveDate;
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// Tasks in a board column at the end of a day, or so far for the current
+/// day. Archived tasks aren't counted, as on the board.
+#[derive(Debug, Clone, FromRow)]
+pub struct BoardSnapshot {
+    pub project_id: Uuid,
+    pub day: NaiveDate,
+    pub column_id: Uuid,
+    /// Name of the column that day
+    pub column_name: String,
+    pub status: TaskStatus,
+    pub position: i64,
+    pub task_count: i64,
+}
+
+impl BoardSnapshot {
+    /// Record the boards as they are now as the snapshot of the day, for one
+    /// project or, with none given, for every project with board columns
+    pub async fn record(
+        pool: &SqlitePool,
+        day: NaiveDate,
+        project_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        // Replaced rather than updated so columns removed during the day drop
+        // out of it
+        sqlx::query!(
+            "DELETE FROM board_snapshots WHERE day = $1 AND ($2 IS NULL OR project_id = $2)",
+            day,
+            project_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO board_snapshots
+                   (project_id, day, column_id, column_name, status, position, task_count)
+               SELECT bc.project_id, $1, bc.id, bc.name, bc.status, bc.position, COUNT(t.id)
+               FROM board_columns bc
+               LEFT JOIN tasks t
+                 ON t.project_id = bc.project_id
+                AND t.status = bc.status
+                AND t.archived_at IS NULL
+                AND (t.column_id = bc.id
+                     OR (t.column_id IS NULL
+                         AND bc.position = (SELECT MIN(position)
+                                            FROM board_columns
+                                            WHERE project_id = bc.project_id AND status = bc.status)))
+               WHERE $2 IS NULL OR bc.project_id = $2
+               GROUP BY bc.id"#,
+            day,
+            project_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// The project's snapshots from the first day to the last one included,
+    /// oldest first and in board order within a day
+    pub async fn find_range(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BoardSnapshot,
+            r#"SELECT project_id as "project_id!: Uuid",
+                      day as "day!: NaiveDate",
+                      column_id as "column_id!: Uuid",
+                      column_name,
+                      status as "status!: TaskStatus",
+                      position,
+                      task_count
+               FROM board_snapshots
+               WHERE project_id = $1 AND day >= $2 AND day <= $3
+               ORDER BY day ASC, position ASC"#,
+            project_id,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// What a check did to bring a branch up to date with its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum BranchSyncAction {
+    Rebased,
+    Merged,
+}
+
+/// The last periodic check of an attempt's repository against its target
+/// branch
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct BranchSyncStatus {
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub target_branch: String,
+    /// Commits of the target branch that the attempt's branch doesn't have
+    pub commits_behind: i64,
+    /// Files that conflict between the two, empty when they merge cleanly
+    #[ts(type = "Array<string>")]
+    pub conflicted_files: Json<Vec<String>>,
+    /// How the check brought the branch up to date, if it did
+    pub action: Option<BranchSyncAction>,
+    /// Why the branch couldn't be checked or brought up to date
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpsertBranchSyncStatus {
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    pub target_branch: String,
+    pub commits_behind: i64,
+    pub conflicted_files: Vec<String>,
+    pub action: Option<BranchSyncAction>,
+    pub error: Option<String>,
+}
+
+impl BranchSyncStatus {
+    pub async fn upsert(
+        pool: &SqlitePool,
+        data: &UpsertBranchSyncStatus,
+    ) -> Result<Self, sqlx::Error> {
+        let conflicted_files = Json(data.conflicted_files.clone());
+        sqlx::query_as!(
+            BranchSyncStatus,
+            r#"INSERT INTO branch_sync_statuses (workspace_id, repo_id, target_branch, commits_behind, conflicted_files, action, error)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               ON CONFLICT(workspace_id, repo_id) DO UPDATE
+               SET target_branch = excluded.target_branch,
+                   commits_behind = excluded.commits_behind,
+                   conflicted_files = excluded.conflicted_files,
+                   action = excluded.action,
+                   error = excluded.error,
+                   checked_at = datetime('now', 'subsec')
+               RETURNING workspace_id as "workspace_id!: Uuid", repo_id as "repo_id!: Uuid", target_branch, commits_behind, conflicted_files as "conflicted_files!: Json<Vec<String>>", action as "action: BranchSyncAction", error, checked_at as "checked_at!: DateTime<Utc>""#,
+            data.workspace_id,
+            data.repo_id,
+            data.target_branch,
+            data.commits_behind,
+            conflicted_files,
+            data.action,
+            data.error
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BranchSyncStatus,
+            r#"SELECT workspace_id as "workspace_id!: Uuid", repo_id as "repo_id!: Uuid", target_branch, commits_behind, conflicted_files as "conflicted_files!: Json<Vec<String>>", action as "action: BranchSyncAction", error, checked_at as "checked_at!: DateTime<Utc>"
+               FROM branch_sync_statuses
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A CI check's result, the same across code hosts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum CiCheckState {
+    /// Queued or still running
+    Pending,
+    Passed,
+    /// Failed, errored, timed out or was cancelled
+    Failed,
+    /// Skipped, neutral or left to be started by hand
+    Skipped,
+}
+
+/// A CI check of an attempt's pull request, as last polled
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct CiCheck {
+    pub id: Uuid,
+    /// The pull request's merge record
+    pub merge_id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    /// The pull request's head commit the check ran on
+    pub head_commit: String,
+    pub name: String,
+    pub state: CiCheckState,
+    pub url: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewCiCheck {
+    pub name: String,
+    pub state: CiCheckState,
+    pub url: Option<String>,
+}
+
+impl CiCheck {
+    /// Replace the pull request's checks with those of the latest poll
+    pub async fn replace_for_merge(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        head_commit: &str,
+        checks: &[NewCiCheck],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!("DELETE FROM ci_checks WHERE merge_id = $1", merge_id)
+            .execute(&mut *tx)
+            .await?;
+        for check in checks {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO ci_checks (id, merge_id, workspace_id, repo_id, head_commit, name, state, url)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                id,
+                merge_id,
+                workspace_id,
+                repo_id,
+                head_commit,
+                check.name,
+                check.state,
+                check.url
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CiCheck,
+            r#"SELECT id as "id!: Uuid", merge_id as "merge_id!: Uuid", workspace_id as "workspace_id!: Uuid", repo_id as "repo_id!: Uuid", head_commit, name, state as "state!: CiCheckState", url, updated_at as "updated_at!: DateTime<Utc>"
+               FROM ci_checks
+               WHERE workspace_id = $1
+               ORDER BY name"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_merge_id(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CiCheck,
+            r#"SELECT id as "id!: Uuid", merge_id as "merge_id!: Uuid", workspace_id as "workspace_id!: Uuid", repo_id as "repo_id!: Uuid", head_commit, name, state as "state!: CiCheckState", url, updated_at as "updated_at!: DateTime<Utc>"
+               FROM ci_checks
+               WHERE merge_id = $1
+               ORDER BY name"#,
+            merge_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -45,6 +45,29 @@ impl CodingAgentTurn {
         .await
     }
 
+    /// The summary the workspace's agent last left, from runs still in its
+    /// history
+    pub async fn find_latest_summary_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT cat.summary as "summary!: String"
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON ep.id = cat.execution_process_id
+               JOIN sessions s ON s.id = ep.session_id
+               WHERE s.workspace_id = $1
+                 AND cat.summary IS NOT NULL
+                 AND ep.dropped = FALSE
+               ORDER BY cat.created_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(record.map(|record| record.summary))
+    }
+
     pub async fn find_by_agent_session_id(
         pool: &SqlitePool,
         agent_session_id: &str,
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use strum_macros::{Display, EnumString};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum CommandDecisionStatus {
+    Approved,
+    Denied,
+    TimedOut,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum CommandDecider {
+    /// The project's command policy
+    Policy,
+    User,
+}
+
+/// How an agent's request to run a shell command was answered
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct CommandDecision {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub tool_call_id: String,
+    pub command: String,
+    pub status: CommandDecisionStatus,
+    pub decided_by: CommandDecider,
+    /// Policy patterns the decision was based on
+    pub rule: Option<String>,
+    /// Why the command was denied
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct CreateCommandDecision {
+    pub workspace_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub tool_call_id: String,
+    pub command: String,
+    pub status: CommandDecisionStatus,
+    pub decided_by: CommandDecider,
+    pub rule: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl CommandDecision {
+    /// Newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CommandDecision,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", tool_call_id, command, status as "status!: CommandDecisionStatus", decided_by as "decided_by!: CommandDecider", rule, reason, created_at as "created_at!: DateTime<Utc>"
+               FROM command_decisions
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateCommandDecision,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            CommandDecision,
+            r#"INSERT INTO command_decisions (id, workspace_id, execution_process_id, tool_call_id, command, status, decided_by, rule, reason)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", tool_call_id, command, status as "status!: CommandDecisionStatus", decided_by as "decided_by!: CommandDecider", rule, reason, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.execution_process_id,
+            data.tool_call_id,
+            data.command,
+            data.status,
+            data.decided_by,
+            data.rule,
+            data.reason
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use strum_macros::{Display, EnumString};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum DryRunStatus {
+    Running,
+    /// Waiting for the user to approve or discard it
+    Ready,
+    /// The agent's run failed or was stopped
+    Failed,
+    Approved,
+    Discarded,
+}
+
+/// A plan-only run of an attempt's agent, made in a throwaway copy of the
+/// workspace so nothing it does reaches the attempt until it is approved
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct DryRun {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub execution_process_id: Uuid,
+    /// What the agent was asked to plan
+    pub prompt: String,
+    pub status: DryRunStatus,
+    /// The agent's last message
+    pub plan: Option<String>,
+    /// What the agent changed in the copy, as a diff against the workspace
+    pub patch: Option<String>,
+    /// The real run started when the plan was approved
+    pub approved_execution_process_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DryRun {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DryRun,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", prompt, status as "status!: DryRunStatus", plan, patch, approved_execution_process_id as "approved_execution_process_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM dry_runs
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DryRun,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", prompt, status as "status!: DryRunStatus", plan, patch, approved_execution_process_id as "approved_execution_process_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM dry_runs
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DryRun,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", prompt, status as "status!: DryRunStatus", plan, patch, approved_execution_process_id as "approved_execution_process_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM dry_runs
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        id: Uuid,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+        prompt: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            DryRun,
+            r#"INSERT INTO dry_runs (id, workspace_id, execution_process_id, prompt)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", prompt, status as "status!: DryRunStatus", plan, patch, approved_execution_process_id as "approved_execution_process_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            execution_process_id,
+            prompt
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn complete(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: DryRunStatus,
+        plan: Option<&str>,
+        patch: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE dry_runs
+               SET status = $1, plan = $2, patch = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $4"#,
+            status,
+            plan,
+            patch,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark a ready dry run approved; false if it wasn't ready
+    pub async fn approve(
+        pool: &SqlitePool,
+        id: Uuid,
+        approved_execution_process_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE dry_runs
+               SET status = 'approved', approved_execution_process_id = $1, updated_at = datetime('now', 'subsec')
+               WHERE id = $2 AND status = 'ready'"#,
+            approved_execution_process_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Drop a dry run that ended without being approved; false if it hadn't
+    pub async fn discard(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE dry_runs
+               SET status = 'discarded', updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND status IN ('ready', 'failed')"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct Epic {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// When the epic's tasks should all be done
+    pub target_date: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateEpic {
+    pub name: String,
+    pub description: Option<String>,
+    pub target_date: Option<DateTime<Utc>>,
+}
+
+/// Replaces the epic's fields; leaving out the description or target date
+/// clears it
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateEpic {
+    pub name: String,
+    pub description: Option<String>,
+    pub target_date: Option<DateTime<Utc>>,
+}
+
+/// What the epic's progress is worked out from for one of its tasks
+#[derive(Debug, Clone, FromRow)]
+pub struct EpicTaskTimeline {
+    pub status: TaskStatus,
+    pub created_at: DateTime<Utc>,
+    /// When the task was last marked done, if it still is
+    pub done_at: Option<DateTime<Utc>>,
+}
+
+impl Epic {
+    /// Epics of the project, the ones due soonest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Epic,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      description,
+                      target_date as "target_date: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM epics
+               WHERE project_id = $1
+               ORDER BY target_date IS NULL, datetime(target_date) ASC, name COLLATE NOCASE ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Epic,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      name,
+                      description,
+                      target_date as "target_date: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM epics
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateEpic,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let name = data.name.trim();
+        sqlx::query_as!(
+            Epic,
+            r#"INSERT INTO epics (id, project_id, name, description, target_date)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name,
+                         description,
+                         target_date as "target_date: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            name,
+            data.description,
+            data.target_date
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateEpic,
+    ) -> Result<Self, sqlx::Error> {
+        let name = data.name.trim();
+        sqlx::query_as!(
+            Epic,
+            r#"UPDATE epics
+               SET name = $2, description = $3, target_date = $4,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         name,
+                         description,
+                         target_date as "target_date: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            data.description,
+            data.target_date
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Remove the epic; its tasks stay, without an epic
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM epics WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// The epic's tasks, archived ones included
+    pub async fn find_task_timelines(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Vec<EpicTaskTimeline>, sqlx::Error> {
+        sqlx::query_as!(
+            EpicTaskTimeline,
+            r#"SELECT status as "status!: TaskStatus",
+                      created_at as "created_at!: DateTime<Utc>",
+                      done_at as "done_at: DateTime<Utc>"
+               FROM tasks
+               WHERE epic_id = $1"#,
+            id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
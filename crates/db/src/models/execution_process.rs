@@ -8,6 +8,7 @@ use serde_json::Value;
 use sqlx::{FromRow, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
+use utils::log_msg::TokenUsage;
 use uuid::Uuid;
 
 use super::{
@@ -119,6 +120,35 @@ pub struct MissingBeforeContext {
     pub repo_path: Option<String>,
 }
 
+/// When a process of an attempt ran, for time accounting
+#[derive(Debug, Clone)]
+pub struct ProcessTiming {
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    /// Executor of the process's session
+    pub executor: Option<String>,
+    pub run_reason: ExecutionProcessRunReason,
+    pub started_at: DateTime<Utc>,
+    /// None while the process is running
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Tokens a coding agent process used, for usage accounting
+#[derive(Debug, Clone)]
+pub struct ProcessUsage {
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    /// Executor of the process's session
+    pub executor: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// Token counts are all None when the executor reported none
+    pub input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+    pub cache_read_tokens: Option<i64>,
+    pub cache_write_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+}
+
 impl ExecutionProcess {
     /// Find execution process by ID
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
@@ -516,6 +546,48 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Record the usage the executor reported, replacing any earlier report
+    pub async fn update_token_usage(
+        pool: &SqlitePool,
+        id: Uuid,
+        usage: &TokenUsage,
+    ) -> Result<(), sqlx::Error> {
+        let input_tokens = usage.input_tokens as i64;
+        let output_tokens = usage.output_tokens as i64;
+        let cache_read_tokens = usage.cache_read_tokens as i64;
+        let cache_write_tokens = usage.cache_write_tokens as i64;
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET input_tokens = $1, output_tokens = $2, cache_read_tokens = $3,
+                   cache_write_tokens = $4, cost_usd = $5
+               WHERE id = $6"#,
+            input_tokens,
+            output_tokens,
+            cache_read_tokens,
+            cache_write_tokens,
+            usage.cost_usd,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_tool_calls(
+        pool: &SqlitePool,
+        id: Uuid,
+        tool_calls: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET tool_calls = $1 WHERE id = $2",
+            tool_calls,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub fn executor_action(&self) -> Result<&ExecutorAction, anyhow::Error> {
         match &self.executor_action.0 {
             ExecutorActionField::ExecutorAction(action) => Ok(action),
@@ -679,4 +751,162 @@ impl ExecutionProcess {
             )),
         }
     }
+
+    /// Timings of the processes of the project's attempts that started within
+    /// the range, each end optional. Dev servers are left out since they run
+    /// alongside the work rather than doing it.
+    pub async fn find_timings_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ProcessTiming>, sqlx::Error> {
+        sqlx::query_as!(
+            ProcessTiming,
+            r#"SELECT
+                    w.id as "workspace_id!: Uuid",
+                    w.task_id as "task_id!: Uuid",
+                    s.executor,
+                    ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.completed_at as "completed_at?: DateTime<Utc>"
+               FROM execution_processes ep
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1
+                 AND ep.run_reason != $2
+                 AND ($3 IS NULL OR datetime(ep.started_at) >= datetime($3))
+                 AND ($4 IS NULL OR datetime(ep.started_at) <= datetime($4))
+               ORDER BY ep.started_at ASC"#,
+            project_id,
+            ExecutionProcessRunReason::DevServer,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Timings of the processes of the task's attempts, dev servers left out
+    pub async fn find_timings_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<ProcessTiming>, sqlx::Error> {
+        sqlx::query_as!(
+            ProcessTiming,
+            r#"SELECT
+                    w.id as "workspace_id!: Uuid",
+                    w.task_id as "task_id!: Uuid",
+                    s.executor,
+                    ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.completed_at as "completed_at?: DateTime<Utc>"
+               FROM execution_processes ep
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               WHERE w.task_id = $1 AND ep.run_reason != $2
+               ORDER BY ep.started_at ASC"#,
+            task_id,
+            ExecutionProcessRunReason::DevServer
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Usage of the coding agent processes of the project's attempts that
+    /// started within the range, each end optional
+    pub async fn find_usage_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ProcessUsage>, sqlx::Error> {
+        sqlx::query_as!(
+            ProcessUsage,
+            r#"SELECT
+                    w.id as "workspace_id!: Uuid",
+                    w.task_id as "task_id!: Uuid",
+                    s.executor,
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.input_tokens,
+                    ep.output_tokens,
+                    ep.cache_read_tokens,
+                    ep.cache_write_tokens,
+                    ep.cost_usd
+               FROM execution_processes ep
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1
+                 AND ep.run_reason = $2
+                 AND ($3 IS NULL OR datetime(ep.started_at) >= datetime($3))
+                 AND ($4 IS NULL OR datetime(ep.started_at) <= datetime($4))
+               ORDER BY ep.started_at ASC"#,
+            project_id,
+            ExecutionProcessRunReason::CodingAgent,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Usage of the coding agent processes of the task's attempts
+    pub async fn find_usage_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<ProcessUsage>, sqlx::Error> {
+        sqlx::query_as!(
+            ProcessUsage,
+            r#"SELECT
+                    w.id as "workspace_id!: Uuid",
+                    w.task_id as "task_id!: Uuid",
+                    s.executor,
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.input_tokens,
+                    ep.output_tokens,
+                    ep.cache_read_tokens,
+                    ep.cache_write_tokens,
+                    ep.cost_usd
+               FROM execution_processes ep
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               WHERE w.task_id = $1 AND ep.run_reason = $2
+               ORDER BY ep.started_at ASC"#,
+            task_id,
+            ExecutionProcessRunReason::CodingAgent
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Usage of the coding agent processes of one attempt
+    pub async fn find_usage_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<ProcessUsage>, sqlx::Error> {
+        sqlx::query_as!(
+            ProcessUsage,
+            r#"SELECT
+                    w.id as "workspace_id!: Uuid",
+                    w.task_id as "task_id!: Uuid",
+                    s.executor,
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.input_tokens,
+                    ep.output_tokens,
+                    ep.cache_read_tokens,
+                    ep.cache_write_tokens,
+                    ep.cost_usd
+               FROM execution_processes ep
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               WHERE w.id = $1 AND ep.run_reason = $2
+               ORDER BY ep.started_at ASC"#,
+            workspace_id,
+            ExecutionProcessRunReason::CodingAgent
+        )
+        .fetch_all(pool)
+        .await
+    }
 }
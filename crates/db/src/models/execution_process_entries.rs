@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use executors::logs::NormalizedEntry;
+use sqlx::{SqlitePool, types::Json};
+use uuid::Uuid;
+
+/// The conversation a coding agent run ended with, by entry index
+pub struct ExecutionProcessEntries;
+
+impl ExecutionProcessEntries {
+    pub async fn find_by_execution_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<BTreeMap<usize, NormalizedEntry>>, sqlx::Error> {
+        let entries = sqlx::query_scalar!(
+            r#"SELECT entries as "entries!: Json<BTreeMap<usize, NormalizedEntry>>"
+               FROM execution_process_entries
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(entries.map(|entries| entries.0))
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        entries: &BTreeMap<usize, NormalizedEntry>,
+    ) -> Result<(), sqlx::Error> {
+        let entries = Json(entries);
+        sqlx::query!(
+            r#"INSERT INTO execution_process_entries (execution_process_id, entries)
+               VALUES ($1, $2)
+               ON CONFLICT(execution_process_id) DO UPDATE SET entries = excluded.entries"#,
+            execution_process_id,
+            entries
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A coding agent run cut off by the server stopping while it was going
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ExecutionProcessInterruption {
+    pub execution_process_id: Uuid,
+    pub workspace_id: Uuid,
+    /// Run that picked the work up again, once the attempt is resumed
+    pub resumed_process_id: Option<Uuid>,
+    /// When the restarted server found the run cut off
+    pub interrupted_at: DateTime<Utc>,
+}
+
+impl ExecutionProcessInterruption {
+    /// The attempt's latest interruption, unless it was resumed or the session
+    /// has carried on with another coding agent run since
+    pub async fn find_open_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessInterruption,
+            r#"SELECT epi.execution_process_id as "execution_process_id!: Uuid", epi.workspace_id as "workspace_id!: Uuid", epi.resumed_process_id as "resumed_process_id?: Uuid", epi.interrupted_at as "interrupted_at!: DateTime<Utc>"
+               FROM execution_process_interruptions epi
+               JOIN execution_processes ep ON ep.id = epi.execution_process_id
+               WHERE epi.workspace_id = $1
+                 AND epi.resumed_process_id IS NULL
+                 AND ep.dropped = FALSE
+                 AND NOT EXISTS (
+                     SELECT 1 FROM execution_processes later
+                     WHERE later.session_id = ep.session_id
+                       AND later.run_reason = 'codingagent'
+                       AND later.dropped = FALSE
+                       AND later.created_at > ep.created_at
+                 )
+               ORDER BY epi.interrupted_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO execution_process_interruptions (execution_process_id, workspace_id)
+               VALUES ($1, $2)
+               ON CONFLICT(execution_process_id) DO NOTHING"#,
+            execution_process_id,
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mark_resumed(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        resumed_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_process_interruptions
+               SET resumed_process_id = $2
+               WHERE execution_process_id = $1"#,
+            execution_process_id,
+            resumed_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RecordedEvent {
+    /// Milliseconds from the start of the run
+    pub at_ms: i64,
+    /// JSON patch to the conversation, as sent by the normalized logs stream
+    pub patch: Value,
+}
+
+/// How a coding agent run's conversation was built up over time
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutionProcessRecording {
+    pub execution_process_id: Uuid,
+    pub events: Vec<RecordedEvent>,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExecutionProcessRecording {
+    pub async fn find_by_execution_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT events as "events!: Json<Vec<RecordedEvent>>",
+                      duration_ms,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM execution_process_recordings
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(record.map(|record| Self {
+            execution_process_id,
+            events: record.events.0,
+            duration_ms: record.duration_ms,
+            created_at: record.created_at,
+        }))
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        events: &[RecordedEvent],
+        duration_ms: i64,
+    ) -> Result<(), sqlx::Error> {
+        let events = Json(events);
+        sqlx::query!(
+            r#"INSERT INTO execution_process_recordings (execution_process_id, events, duration_ms)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(execution_process_id) DO UPDATE SET
+                   events = excluded.events,
+                   duration_ms = excluded.duration_ms"#,
+            execution_process_id,
+            events,
+            duration_ms
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use executors::failure::FailureKind;
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A coding agent run started automatically in place of one that failed
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ExecutionProcessRetry {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub failed_process_id: Uuid,
+    pub retry_process_id: Uuid,
+    pub failure_kind: FailureKind,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExecutionProcessRetry {
+    /// Oldest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessRetry,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", failed_process_id as "failed_process_id!: Uuid", retry_process_id as "retry_process_id!: Uuid", failure_kind as "failure_kind!: FailureKind", created_at as "created_at!: DateTime<Utc>"
+               FROM execution_process_retries
+               WHERE workspace_id = $1
+               ORDER BY created_at ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<u32, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM execution_process_retries WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(count as u32)
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        failed_process_id: Uuid,
+        retry_process_id: Uuid,
+        failure_kind: FailureKind,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ExecutionProcessRetry,
+            r#"INSERT INTO execution_process_retries (id, workspace_id, failed_process_id, retry_process_id, failure_kind)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", failed_process_id as "failed_process_id!: Uuid", retry_process_id as "retry_process_id!: Uuid", failure_kind as "failure_kind!: FailureKind", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            failed_process_id,
+            retry_process_id,
+            failure_kind
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,175 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// The GitHub issue a task was imported from
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct GitHubIssueLink {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub issue_number: i64,
+    pub issue_url: String,
+    /// Close the issue once the task is done
+    pub sync_status: bool,
+    /// When the issue was closed from here
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateGitHubIssueLink {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub issue_number: i64,
+    pub issue_url: String,
+    pub sync_status: bool,
+}
+
+impl GitHubIssueLink {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueLink,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      repo_owner,
+                      repo_name,
+                      issue_number,
+                      issue_url,
+                      sync_status as "sync_status!: bool",
+                      closed_at as "closed_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM github_issue_links
+               WHERE project_id = $1
+               ORDER BY repo_owner, repo_name, issue_number"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueLink,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      repo_owner,
+                      repo_name,
+                      issue_number,
+                      issue_url,
+                      sync_status as "sync_status!: bool",
+                      closed_at as "closed_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM github_issue_links
+               WHERE task_id = $1"#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Numbers of the repository's issues already imported into the project
+    pub async fn find_issue_numbers(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        repo_owner: &str,
+        repo_name: &str,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT issue_number
+               FROM github_issue_links
+               WHERE project_id = $1 AND repo_owner = $2 AND repo_name = $3"#,
+            project_id,
+            repo_owner,
+            repo_name
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Links whose task is done and whose issue should now be closed
+    pub async fn find_to_close(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueLink,
+            r#"SELECT l.id as "id!: Uuid",
+                      l.task_id as "task_id!: Uuid",
+                      l.project_id as "project_id!: Uuid",
+                      l.repo_owner,
+                      l.repo_name,
+                      l.issue_number,
+                      l.issue_url,
+                      l.sync_status as "sync_status!: bool",
+                      l.closed_at as "closed_at: DateTime<Utc>",
+                      l.created_at as "created_at!: DateTime<Utc>"
+               FROM github_issue_links l
+               JOIN tasks t ON t.id = l.task_id
+               WHERE l.sync_status = 1 AND l.closed_at IS NULL AND t.status = 'done'"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create<'e, E>(
+        executor: E,
+        data: &CreateGitHubIssueLink,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            GitHubIssueLink,
+            r#"INSERT INTO github_issue_links (id, task_id, project_id, repo_owner, repo_name, issue_number, issue_url, sync_status)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         repo_owner,
+                         repo_name,
+                         issue_number,
+                         issue_url,
+                         sync_status as "sync_status!: bool",
+                         closed_at as "closed_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.task_id,
+            data.project_id,
+            data.repo_owner,
+            data.repo_name,
+            data.issue_number,
+            data.issue_url,
+            data.sync_status
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn mark_closed(
+        pool: &SqlitePool,
+        id: Uuid,
+        closed_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE github_issue_links SET closed_at = $2 WHERE id = $1",
+            id,
+            closed_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use strum_macros::{Display, EnumString};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A point in an attempt's life the project's hooks can run at
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum HookEvent {
+    /// The attempt's workspace is ready and nothing has run in it yet
+    BeforeStart,
+    /// The attempt's last run ended and the task went to review
+    AfterFinish,
+    /// The attempt's branch was merged, directly or through its pull request
+    AfterMerge,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum HookRunStatus {
+    Succeeded,
+    Failed,
+    TimedOut,
+}
+
+/// A project hook as run for one of its attempts
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct HookRun {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub hook_name: String,
+    pub event: HookEvent,
+    pub status: HookRunStatus,
+    /// The end of a command's output, or a webhook's response status and
+    /// body; why the hook didn't run if it couldn't be started
+    pub output: String,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct CreateHookRun {
+    pub workspace_id: Uuid,
+    pub hook_name: String,
+    pub event: HookEvent,
+    pub status: HookRunStatus,
+    pub output: String,
+    pub duration_ms: i64,
+}
+
+impl HookRun {
+    /// Newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            HookRun,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", hook_name, event as "event!: HookEvent", status as "status!: HookRunStatus", output, duration_ms, created_at as "created_at!: DateTime<Utc>"
+               FROM hook_runs
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateHookRun) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            r#"INSERT INTO hook_runs (id, workspace_id, hook_name, event, status, output, duration_ms)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+            id,
+            data.workspace_id,
+            data.hook_name,
+            data.event,
+            data.status,
+            data.output,
+            data.duration_ms
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -149,6 +149,26 @@ impl Image {
         .await
     }
 
+    /// Total size of the distinct images attached to any task in the project.
+    pub async fn total_size_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(size_bytes), 0) AS "total!: i64"
+               FROM images
+               WHERE id IN (
+                   SELECT ti.image_id
+                   FROM task_images ti
+                   JOIN tasks t ON t.id = ti.task_id
+                   WHERE t.project_id = $1
+               )"#,
+            project_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
     pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query!(r#"DELETE FROM images WHERE id = $1"#, id)
             .execute(pool)
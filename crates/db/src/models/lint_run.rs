@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A problem a check reported at a place in the code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct LintIssue {
+    /// As the check printed it, usually relative to where it ran
+    pub path: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: LintSeverity,
+    pub message: String,
+    /// The lint or error code, e.g. `clippy::needless_return`
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum LintCheckStatus {
+    Passed,
+    Failed,
+    TimedOut,
+    /// The command couldn't be started, e.g. the program isn't installed or
+    /// the project's policy doesn't allow it
+    NotRun,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct LintCheckResult {
+    pub name: String,
+    pub command: String,
+    pub status: LintCheckStatus,
+    pub exit_code: Option<i32>,
+    /// The problems read from the output, for the checks whose output format
+    /// is recognized
+    pub issues: Vec<LintIssue>,
+    /// The end of the output, or why the check didn't run
+    pub output: String,
+}
+
+/// The project's lint checks as run once an attempt's agent was done
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LintRun {
+    /// The process whose end started the checks
+    pub execution_process_id: Uuid,
+    pub workspace_id: Uuid,
+    pub checks: Vec<LintCheckResult>,
+    pub passed: bool,
+    /// The follow-up run asked to fix what failed
+    pub fix_execution_process_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LintRun {
+    /// Newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid",
+                      checks as "checks!: Json<Vec<LintCheckResult>>",
+                      passed as "passed!: bool",
+                      fix_execution_process_id as "fix_execution_process_id?: Uuid",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM lint_runs
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records
+            .into_iter()
+            .map(|record| Self {
+                execution_process_id: record.execution_process_id,
+                workspace_id,
+                checks: record.checks.0,
+                passed: record.passed,
+                fix_execution_process_id: record.fix_execution_process_id,
+                created_at: record.created_at,
+            })
+            .collect())
+    }
+
+    /// How many fix-up runs the workspace's failed checks started
+    pub async fn count_fix_runs_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM lint_runs
+               WHERE workspace_id = $1 AND fix_execution_process_id IS NOT NULL"#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        workspace_id: Uuid,
+        checks: &[LintCheckResult],
+        passed: bool,
+    ) -> Result<(), sqlx::Error> {
+        let checks = Json(checks);
+        sqlx::query!(
+            r#"INSERT INTO lint_runs (execution_process_id, workspace_id, checks, passed)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(execution_process_id) DO UPDATE SET
+                   checks = excluded.checks,
+                   passed = excluded.passed"#,
+            execution_process_id,
+            workspace_id,
+            checks,
+            passed
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_fix_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        fix_execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE lint_runs
+               SET fix_execution_process_id = $1
+               WHERE execution_process_id = $2"#,
+            fix_execution_process_id,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// The token an attempt's agents send the LLM proxy in place of a provider
+/// key
+pub struct LlmProxyToken;
+
+impl LlmProxyToken {
+    /// The workspace's token, made on first use
+    pub async fn for_workspace(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<String, sqlx::Error> {
+        let token = format!("vk-proxy-{}", Uuid::new_v4().simple());
+        sqlx::query_scalar!(
+            r#"INSERT INTO llm_proxy_tokens (token, workspace_id)
+               VALUES ($1, $2)
+               ON CONFLICT(workspace_id) DO UPDATE SET workspace_id = excluded.workspace_id
+               RETURNING token as "token!: String""#,
+            token,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_workspace_id(
+        pool: &SqlitePool,
+        token: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT workspace_id as "workspace_id!: Uuid" FROM llm_proxy_tokens WHERE token = $1"#,
+            token
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
+
+/// A call the LLM proxy made to a provider. A call that failed over to
+/// another provider is logged once for each provider tried.
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct LlmProxyRequest {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    /// None once the workspace is removed
+    pub workspace_id: Option<Uuid>,
+    /// The provider's name in the project settings
+    pub provider: String,
+    pub method: String,
+    pub path: String,
+    pub model: Option<String>,
+    /// None if the provider couldn't be reached
+    pub status_code: Option<i64>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+    pub duration_ms: i64,
+    pub error: Option<String>,
+    /// Only kept when the project logs bodies
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct CreateLlmProxyRequest {
+    pub project_id: Uuid,
+    pub workspace_id: Uuid,
+    pub provider: String,
+    pub method: String,
+    pub path: String,
+    pub model: Option<String>,
+    pub status_code: Option<i64>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+    pub duration_ms: i64,
+    pub error: Option<String>,
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
+}
+
+impl LlmProxyRequest {
+    /// Newest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            LlmProxyRequest,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", workspace_id as "workspace_id?: Uuid", provider, method, path, model, status_code, input_tokens, output_tokens, cost_usd, duration_ms, error, request_body, response_body, created_at as "created_at!: DateTime<Utc>"
+               FROM llm_proxy_requests
+               WHERE project_id = $1
+               ORDER BY created_at DESC
+               LIMIT $2"#,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// What the project's calls since `since` cost
+    pub async fn spend_since(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<f64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(cost_usd), 0.0) as "spend!: f64"
+               FROM llm_proxy_requests
+               WHERE project_id = $1 AND datetime(created_at) >= datetime($2)"#,
+            project_id,
+            since
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateLlmProxyRequest,
+    ) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            r#"INSERT INTO llm_proxy_requests (id, project_id, workspace_id, provider, method, path, model, status_code, input_tokens, output_tokens, cost_usd, duration_ms, error, request_body, response_body)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)"#,
+            id,
+            data.project_id,
+            data.workspace_id,
+            data.provider,
+            data.method,
+            data.path,
+            data.model,
+            data.status_code,
+            data.input_tokens,
+            data.output_tokens,
+            data.cost_usd,
+            data.duration_ms,
+            data.error,
+            data.request_body,
+            data.response_body
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
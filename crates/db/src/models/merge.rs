@@ -14,6 +14,31 @@ pub enum MergeStatus {
     Unknown,
 }
 
+/// How an attempt's branch is merged into its target branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// A merge commit with the target and the branch as parents
+    MergeCommit,
+    /// A single commit with all of the branch's changes
+    #[default]
+    Squash,
+    /// The branch's commits replayed on the target
+    Rebase,
+    /// The target moved to the branch's tip, without a new commit
+    FastForward,
+}
+
+impl MergeStrategy {
+    /// Whether the merge makes a commit of its own, worded by the merge
+    /// message template
+    pub fn makes_commit(self) -> bool {
+        matches!(self, Self::MergeCommit | Self::Squash)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Merge {
@@ -28,6 +53,7 @@ pub struct DirectMerge {
     pub repo_id: Uuid,
     pub merge_commit: String,
     pub target_branch_name: String,
+    pub merge_strategy: Option<MergeStrategy>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -40,6 +66,8 @@ pub struct PrMerge {
     pub created_at: DateTime<Utc>,
     pub target_branch_name: String,
     pub pr_info: PullRequestInfo,
+    /// None unless vibe-kanban merged the pull request
+    pub merge_strategy: Option<MergeStrategy>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -71,6 +99,7 @@ struct MergeRow {
     pr_status: Option<MergeStatus>,
     pr_merged_at: Option<DateTime<Utc>>,
     pr_merge_commit_sha: Option<String>,
+    merge_strategy: Option<MergeStrategy>,
     created_at: DateTime<Utc>,
 }
 
@@ -89,6 +118,7 @@ impl Merge {
         repo_id: Uuid,
         target_branch_name: &str,
         merge_commit: &str,
+        merge_strategy: MergeStrategy,
     ) -> Result<DirectMerge, sqlx::Error> {
         let id = Uuid::new_v4();
         let now = Utc::now();
@@ -96,8 +126,8 @@ impl Merge {
         sqlx::query_as!(
             MergeRow,
             r#"INSERT INTO merges (
-                id, workspace_id, repo_id, merge_type, merge_commit, created_at, target_branch_name
-            ) VALUES ($1, $2, $3, 'direct', $4, $5, $6)
+                id, workspace_id, repo_id, merge_type, merge_commit, created_at, target_branch_name, merge_strategy
+            ) VALUES ($1, $2, $3, 'direct', $4, $5, $6, $7)
             RETURNING
                 id as "id!: Uuid",
                 workspace_id as "workspace_id!: Uuid",
@@ -109,6 +139,7 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 created_at as "created_at!: DateTime<Utc>",
                 target_branch_name as "target_branch_name!: String"
             "#,
@@ -117,7 +148,8 @@ impl Merge {
             repo_id,
             merge_commit,
             now,
-            target_branch_name
+            target_branch_name,
+            merge_strategy
         )
         .fetch_one(pool)
         .await
@@ -151,6 +183,7 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 created_at as "created_at!: DateTime<Utc>",
                 target_branch_name as "target_branch_name!: String"
             "#,
@@ -182,6 +215,7 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 created_at as "created_at!: DateTime<Utc>",
                 target_branch_name as "target_branch_name!: String"
                FROM merges
@@ -223,6 +257,22 @@ impl Merge {
 
         Ok(())
     }
+    /// Record the strategy vibe-kanban merged a pull request with
+    pub async fn update_merge_strategy(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+        merge_strategy: MergeStrategy,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE merges SET merge_strategy = $1 WHERE id = $2",
+            merge_strategy,
+            merge_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Find all merges for a workspace (returns both direct and PR merges)
     pub async fn find_by_workspace_id(
         pool: &SqlitePool,
@@ -242,6 +292,7 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 target_branch_name as "target_branch_name!: String",
                 created_at as "created_at!: DateTime<Utc>"
             FROM merges
@@ -275,6 +326,7 @@ impl Merge {
                 pr_status as "pr_status?: MergeStatus",
                 pr_merged_at as "pr_merged_at?: DateTime<Utc>",
                 pr_merge_commit_sha,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 target_branch_name as "target_branch_name!: String",
                 created_at as "created_at!: DateTime<Utc>"
             FROM merges
@@ -301,6 +353,7 @@ impl From<MergeRow> for DirectMerge {
                 .merge_commit
                 .expect("direct merge must have merge_commit"),
             target_branch_name: row.target_branch_name,
+            merge_strategy: row.merge_strategy,
             created_at: row.created_at,
         }
     }
@@ -320,6 +373,7 @@ impl From<MergeRow> for PrMerge {
                 merged_at: row.pr_merged_at,
                 merge_commit_sha: row.pr_merge_commit_sha,
             },
+            merge_strategy: row.merge_strategy,
             created_at: row.created_at,
         }
     }
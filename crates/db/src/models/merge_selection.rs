@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct SelectedFile {
+    pub path: String,
+    /// Indexes of the file's hunks to take, None for the whole file
+    pub hunks: Option<Vec<usize>>,
+}
+
+/// Part of an attempt's diff chosen to be merged, or opened as a pull
+/// request, while the rest stays on the attempt's branch
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct MergeSelection {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub repo_id: Uuid,
+    /// The diff the selection was made from, between these commits
+    pub base_commit: String,
+    pub head_commit: String,
+    #[ts(type = "Array<SelectedFile>")]
+    pub files: Json<Vec<SelectedFile>>,
+    /// The merge or pull request the selection went into, once it's used
+    pub merge_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MergeSelection {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeSelection,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", repo_id as "repo_id!: Uuid", base_commit, head_commit, files as "files!: Json<Vec<SelectedFile>>", merge_id as "merge_id: Uuid", created_at as "created_at!: DateTime<Utc>"
+               FROM merge_selections
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeSelection,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", repo_id as "repo_id!: Uuid", base_commit, head_commit, files as "files!: Json<Vec<SelectedFile>>", merge_id as "merge_id: Uuid", created_at as "created_at!: DateTime<Utc>"
+               FROM merge_selections
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        repo_id: Uuid,
+        base_commit: &str,
+        head_commit: &str,
+        files: &[SelectedFile],
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let files = Json(files.to_vec());
+        sqlx::query_as!(
+            MergeSelection,
+            r#"INSERT INTO merge_selections (id, workspace_id, repo_id, base_commit, head_commit, files)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", repo_id as "repo_id!: Uuid", base_commit, head_commit, files as "files!: Json<Vec<SelectedFile>>", merge_id as "merge_id: Uuid", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            repo_id,
+            base_commit,
+            head_commit,
+            files
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record the merge or pull request the selection went into
+    pub async fn set_merge_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        merge_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE merge_selections SET merge_id = $1 WHERE id = $2",
+            merge_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
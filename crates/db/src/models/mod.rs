@@ -1,15 +1,53 @@
+pub mod activity_event;
+pub mod agent_review;
+pub mod attempt_group;
+pub mod board_column;
+pub mod board_snapshot;
+pub mod branch_sync_status;
+pub mod ci_check;
 pub mod coding_agent_turn;
+pub mod command_decision;
+pub mod dry_run;
+pub mod epic;
 pub mod execution_process;
+pub mod execution_process_entries;
+pub mod execution_process_interruption;
 pub mod execution_process_logs;
+pub mod execution_process_recording;
 pub mod execution_process_repo_state;
+pub mod execution_process_retry;
+pub mod github_issue_link;
+pub mod hook_run;
 pub mod image;
+pub mod label;
+pub mod lint_run;
+pub mod llm_proxy;
 pub mod merge;
+pub mod merge_selection;
+pub mod path_guard_block;
 pub mod project;
+pub mod project_env_var;
 pub mod project_repo;
+pub mod project_secret;
+pub mod project_settings;
+pub mod queued_attempt;
 pub mod repo;
 pub mod scratch;
+pub mod search;
 pub mod session;
 pub mod tag;
 pub mod task;
+pub mod task_checklist_item;
+pub mod task_dependency;
+pub mod task_link;
+pub mod task_revision;
+pub mod task_schedule;
+pub mod task_time_entry;
+pub mod test_run;
 pub mod workspace;
+pub mod workspace_artifact;
+pub mod workspace_budget;
+pub mod workspace_disk_usage;
 pub mod workspace_repo;
+pub mod workspace_stack;
+pub mod workspace_upload;
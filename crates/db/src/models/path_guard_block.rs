@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A run whose changes touched the project's protected paths, held back from
+/// being committed until the user overrides it
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct PathGuardBlock {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub execution_process_id: Uuid,
+    /// The protected paths changed, prefixed with their repository
+    #[ts(type = "Array<string>")]
+    pub paths: Json<Vec<String>>,
+    /// None while the attempt waits for the override
+    pub overridden_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PathGuardBlock {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PathGuardBlock,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", paths as "paths!: Json<Vec<String>>", overridden_at as "overridden_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM path_guard_blocks
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Newest first
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PathGuardBlock,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", paths as "paths!: Json<Vec<String>>", overridden_at as "overridden_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM path_guard_blocks
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The block the attempt waits on, if any
+    pub async fn find_pending_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PathGuardBlock,
+            r#"SELECT id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", paths as "paths!: Json<Vec<String>>", overridden_at as "overridden_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM path_guard_blocks
+               WHERE workspace_id = $1 AND overridden_at IS NULL
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Protected paths the user already let through in the attempt
+    pub async fn find_overridden_paths(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let blocks = Self::find_by_workspace_id(pool, workspace_id).await?;
+        let mut paths: Vec<String> = blocks
+            .into_iter()
+            .filter(|block| block.overridden_at.is_some())
+            .flat_map(|block| block.paths.0)
+            .collect();
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+        paths: &[String],
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let paths = Json(paths.to_vec());
+        sqlx::query_as!(
+            PathGuardBlock,
+            r#"INSERT INTO path_guard_blocks (id, workspace_id, execution_process_id, paths)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", workspace_id as "workspace_id!: Uuid", execution_process_id as "execution_process_id!: Uuid", paths as "paths!: Json<Vec<String>>", overridden_at as "overridden_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            workspace_id,
+            execution_process_id,
+            paths
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Let the block's changes through. False if it already was.
+    pub async fn set_overridden(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE path_guard_blocks
+               SET overridden_at = datetime('now', 'subsec')
+               WHERE id = $1 AND overridden_at IS NULL"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
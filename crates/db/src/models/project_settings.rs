@@ -0,0 +1,1496 @@
+use std::path::Path;
+
+use executors::{
+    executors::BaseCodingAgent, failure::FailureKind, profile::ExecutorProfileId,
+    prompt_additions::PromptAdditions,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    board_column::{self, BoardColumn, ColumnTaskCount},
+    hook_run::HookEvent,
+    image::Image,
+    merge::MergeStrategy,
+    task::TaskStatus,
+    workspace_budget::{BudgetLimit, BudgetUsage},
+    workspace_upload::WorkspaceUpload,
+};
+
+/// Project-level policies. Persisted as a single JSON document so that
+/// fields missing from older rows fall back to their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub upload_policy: UploadPolicy,
+    pub gitignored_uploads: GitignoredUploadPolicy,
+    pub upload_scan: UploadScanSettings,
+    pub container_backend: ContainerBackend,
+    pub retention: RetentionPolicy,
+    pub exec_policy: ExecPolicy,
+    pub workspace_pool: WorkspacePoolSettings,
+    pub resource_limits: ResourceLimits,
+    pub network_policy: NetworkPolicy,
+    pub shared_caches: SharedCacheSettings,
+    pub gpus: GpuSettings,
+    pub wip_limits: WipLimitSettings,
+    pub due_reminders: DueReminderSettings,
+    pub swimlanes: SwimlaneGrouping,
+    pub definition_of_done: DefinitionOfDone,
+    pub retry_policy: RetryPolicy,
+    pub lint_gate: LintGate,
+    pub agent_review: AgentReviewSettings,
+    pub llm_proxy: LlmProxySettings,
+    pub hooks: Vec<LifecycleHook>,
+    pub attempt_limits: AttemptLimits,
+    pub command_policy: CommandPolicy,
+    pub prompts: PromptSettings,
+    pub concurrency: ConcurrencySettings,
+    /// Gitignore-style patterns of paths, such as CI workflows or secrets
+    /// files, whose changes by an agent wait for the user's override before
+    /// they are committed
+    pub protected_paths: Vec<String>,
+    pub code_host: CodeHostSettings,
+    pub commit_messages: CommitMessageSettings,
+    /// How attempts are merged, both here and through the code host's pull
+    /// requests
+    pub merge_strategy: MergeStrategy,
+    pub branch_sync: BranchSyncSettings,
+    /// Merges, here or through the code host, wait until every CI check of
+    /// the attempt's pull request has passed on the branch's tip
+    pub require_green_checks: bool,
+    pub git_provisioning: GitProvisioningSettings,
+    pub sparse_checkout: SparseCheckoutSettings,
+}
+
+/// What a new attempt's worktrees get beyond `git worktree add`'s checkout,
+/// for repositories that use it. It runs ahead of the setup scripts, with its
+/// progress in the setup log.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct GitProvisioningSettings {
+    /// Initialize and update submodules, recursively
+    pub submodules: bool,
+    /// Download the Git LFS objects of the checkout
+    pub lfs: bool,
+}
+
+impl Default for GitProvisioningSettings {
+    fn default() -> Self {
+        Self {
+            submodules: true,
+            lfs: true,
+        }
+    }
+}
+
+/// Sparse checkout of new attempt worktrees, for repositories too large to
+/// check out whole. Patterns are written like task scopes; the files at a
+/// repository's root are always checked out. Worktrees check out everything
+/// when no pattern applies to their repository.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct SparseCheckoutSettings {
+    /// Gitignore-style patterns of the paths to check out
+    pub patterns: Vec<String>,
+    /// Also check out the paths of the task's scope
+    pub include_task_scope: bool,
+}
+
+/// Periodic check of the project's open attempts against their target
+/// branches, so stale attempts are caught early. Attempts that are behind and
+/// would apply cleanly are brought up to date as `mode` says, unless a process
+/// is running in them or they have uncommitted changes.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct BranchSyncSettings {
+    pub mode: BranchSyncMode,
+    /// How often each open attempt is checked
+    pub interval_minutes: u32,
+}
+
+impl Default for BranchSyncSettings {
+    fn default() -> Self {
+        Self {
+            mode: BranchSyncMode::Check,
+            interval_minutes: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum BranchSyncMode {
+    Off,
+    /// Only record how far behind each attempt is and what would conflict
+    #[default]
+    Check,
+    /// Rebase the attempt's branch onto its target
+    Rebase,
+    /// Merge the target into the attempt's branch
+    Merge,
+}
+
+/// How many of the project's attempts may run at once. Attempts started
+/// beyond it wait in the queue, as do those over the global limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct ConcurrencySettings {
+    /// None for no limit of the project's own
+    pub max_concurrent_attempts: Option<u32>,
+}
+
+/// Where the project's pull requests are opened, polled and merged
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct CodeHostSettings {
+    pub provider: CodeHostProvider,
+    pub gitlab: GitLabSettings,
+    pub bitbucket: BitbucketSettings,
+    pub azure_devops: AzureDevOpsSettings,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum CodeHostProvider {
+    /// Through the `gh` CLI and its login
+    #[default]
+    Github,
+    /// Through the API of gitlab.com or a self-managed instance
+    Gitlab,
+    /// Through the API of Bitbucket Cloud
+    Bitbucket,
+    /// Through the API of Azure DevOps, whose token also authenticates pushes
+    AzureDevops,
+}
+
+/// GitLab is reached with a personal, project or OAuth access token with the
+/// `api` scope, kept as a project secret.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct GitLabSettings {
+    /// The instance's address, e.g. https://gitlab.example.com; None to take
+    /// the host of the repository's remote
+    pub base_url: Option<String>,
+    /// Name of the project secret holding the token
+    pub token_secret: String,
+}
+
+impl Default for GitLabSettings {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            token_secret: "GITLAB_TOKEN".to_string(),
+        }
+    }
+}
+
+/// Bitbucket Cloud is reached with an app password with the pull request
+/// scopes, or an OAuth or repository access token, kept as a project secret.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct BitbucketSettings {
+    /// The account the app password belongs to; None when the secret holds
+    /// a token
+    pub username: Option<String>,
+    /// Name of the project secret holding the app password or token
+    pub token_secret: String,
+}
+
+impl Default for BitbucketSettings {
+    fn default() -> Self {
+        Self {
+            username: None,
+            token_secret: "BITBUCKET_TOKEN".to_string(),
+        }
+    }
+}
+
+/// Azure DevOps is reached with a personal access token with the Code (read &
+/// write) scope, and Work Items (read) to link work items, kept as a project
+/// secret.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct AzureDevOpsSettings {
+    /// Name of the project secret holding the token
+    pub token_secret: String,
+    /// Link the work items the task mentions, as `#123` or `AB#123`, to its
+    /// pull requests
+    pub link_work_items: bool,
+}
+
+impl Default for AzureDevOpsSettings {
+    fn default() -> Self {
+        Self {
+            token_secret: "AZURE_DEVOPS_TOKEN".to_string(),
+            link_work_items: true,
+        }
+    }
+}
+
+/// How the commits vibe-kanban makes for an attempt's runs, and the squash
+/// commit made when it's merged, are worded. Templates take the placeholders
+/// `{task_title}`, `{task_description}`, `{task_id}`, `{short_task_id}` and
+/// `{branch}`; the attempt's also takes the agent's `{summary}`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct CommitMessageSettings {
+    /// None for the agent's summary of its turn
+    pub attempt_template: Option<String>,
+    /// None for the task's title and short ID, then its description
+    pub merge_template: Option<String>,
+    pub conventional_commits: ConventionalCommitSettings,
+    /// Name of the trailer carrying the task's ID; None to leave it out
+    pub task_trailer: Option<String>,
+}
+
+impl Default for CommitMessageSettings {
+    fn default() -> Self {
+        Self {
+            attempt_template: None,
+            merge_template: None,
+            conventional_commits: ConventionalCommitSettings::default(),
+            task_trailer: Some("Vibe-Kanban-Task".to_string()),
+        }
+    }
+}
+
+/// Checks the subject reads `type(scope)!: description`. An attempt's commit
+/// that doesn't is given the fallback type; a squash merge that doesn't is
+/// refused.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct ConventionalCommitSettings {
+    pub enabled: bool,
+    /// Types a subject may start with; empty for any
+    pub types: Vec<String>,
+    pub fallback_type: String,
+}
+
+impl Default for ConventionalCommitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            types: [
+                "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert",
+                "style", "test",
+            ]
+            .map(String::from)
+            .to_vec(),
+            fallback_type: "chore".to_string(),
+        }
+    }
+}
+
+/// Text put before and after the prompt of every coding agent run, such as
+/// coding standards or commit conventions. An executor's override replaces
+/// the prefix or suffix it sets and leaves the other to the project's.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct PromptSettings {
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub previous_attempt: PreviousAttemptContext,
+    pub overrides: Vec<ExecutorPromptOverride>,
+}
+
+/// What a new attempt at a task is told of the attempt before it, so the
+/// agent picks up where that one stopped without the user explaining it
+/// again. It goes before the prompt of the attempt's first run only.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct PreviousAttemptContext {
+    pub enabled: bool,
+    /// The last summary the previous attempt's agent gave
+    pub summary: bool,
+    /// Comments on the previous attempt's pull request
+    pub review_comments: bool,
+    /// The failing tests of the previous attempt's last test run
+    pub failing_tests: bool,
+}
+
+impl Default for PreviousAttemptContext {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            summary: true,
+            review_comments: true,
+            failing_tests: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ExecutorPromptOverride {
+    pub executor: BaseCodingAgent,
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub suffix: Option<String>,
+    /// Whether the executor's runs get the previous attempt's context, None
+    /// to follow the project
+    #[serde(default)]
+    pub previous_attempt: Option<bool>,
+}
+
+impl PromptSettings {
+    /// Whether the runs of any executor get the previous attempt's context
+    pub fn wants_previous_attempt(&self) -> bool {
+        self.previous_attempt.enabled
+            || self
+                .overrides
+                .iter()
+                .any(|over| over.previous_attempt == Some(true))
+    }
+
+    /// What runs of `executor` get, None if nothing is set for it. `context`
+    /// is what the previous attempt left, if this is a new attempt's first run.
+    pub fn for_executor(
+        &self,
+        executor: BaseCodingAgent,
+        context: Option<&str>,
+    ) -> Option<PromptAdditions> {
+        let set = |text: &Option<String>| text.clone().filter(|text| !text.trim().is_empty());
+        let over = self.overrides.iter().find(|over| over.executor == executor);
+        let with_context = over
+            .and_then(|over| over.previous_attempt)
+            .unwrap_or(self.previous_attempt.enabled);
+        let additions = PromptAdditions {
+            prefix: over
+                .and_then(|over| set(&over.prefix))
+                .or_else(|| set(&self.prefix)),
+            context: context
+                .filter(|_| with_context)
+                .map(|context| context.to_string()),
+            suffix: over
+                .and_then(|over| set(&over.suffix))
+                .or_else(|| set(&self.suffix)),
+        };
+        (!additions.is_empty()).then_some(additions)
+    }
+}
+
+/// Answers to the approval requests of agents for shell commands, given
+/// without asking anyone. Patterns match a whole command, `*` standing for any
+/// text. Commands chained with `&&`, `||`, `;`, `|` or `&` are judged part by
+/// part: a command is denied if any part matches a deny pattern, and approved
+/// if every part matches an allow pattern and it has no redirections to or
+/// from files and no `$(...)`, backtick or `<(...)` substitutions. Anything
+/// else is left to the user.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct CommandPolicy {
+    pub enabled: bool,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+/// A decision of the command policy and the patterns it was based on
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandVerdict {
+    pub approved: bool,
+    pub rule: String,
+}
+
+impl CommandPolicy {
+    pub fn decide(&self, command: &str) -> Option<CommandVerdict> {
+        if !self.enabled {
+            return None;
+        }
+        let parts = command_parts(command);
+        let matching = |patterns: &[String], part: &str| {
+            patterns
+                .iter()
+                .find(|pattern| wildcard_match(pattern.trim(), part))
+                .cloned()
+        };
+
+        if let Some(rule) = parts.iter().find_map(|part| matching(&self.deny, part)) {
+            return Some(CommandVerdict {
+                approved: false,
+                rule,
+            });
+        }
+        if parts.is_empty() || redirects_or_substitutes(command) {
+            return None;
+        }
+        let mut rules: Vec<String> = Vec::new();
+        for part in &parts {
+            let rule = matching(&self.allow, part)?;
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
+        Some(CommandVerdict {
+            approved: true,
+            rule: rules.join(", "),
+        })
+    }
+}
+
+/// The commands of a command line, split at `&&`, `||`, `;`, `|`, `&` and
+/// line breaks. The `&` of a redirection such as `2>&1` or `&>` doesn't
+/// separate. Quoting isn't taken into account.
+fn command_parts(command: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+    let mut chars = command.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let after_redirect = matches!(previous.replace(c), Some('>' | '<'));
+        let separator_len = match c {
+            ';' | '\n' => 1,
+            '|' if chars.peek().is_some_and(|&(_, next)| next == '|') => 2,
+            '|' => 1,
+            '&' if after_redirect || chars.peek().is_some_and(|&(_, next)| next == '>') => {
+                continue;
+            }
+            '&' if chars.peek().is_some_and(|&(_, next)| next == '&') => 2,
+            '&' => 1,
+            _ => continue,
+        };
+        parts.push(&command[start..index]);
+        if separator_len == 2 {
+            chars.next();
+        }
+        start = index + separator_len;
+    }
+    parts.push(&command[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Whether the command line reads or writes files through redirections, or
+/// runs commands through substitutions. Duplicating one output into another,
+/// as in `2>&1`, only redirects to what's already open.
+fn redirects_or_substitutes(command: &str) -> bool {
+    if command.contains("$(") || command.contains('`') {
+        return true;
+    }
+    command.match_indices(['>', '<']).any(|(index, redirect)| {
+        let target = &command[index + 1..];
+        let duplicated = redirect == ">"
+            && target
+                .strip_prefix('&')
+                .is_some_and(|fd| fd.starts_with(|c: char| c.is_ascii_digit() || c == '-'));
+        !duplicated
+    })
+}
+
+/// Whether `text` matches `pattern`, in which `*` stands for any text
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut pieces = pattern.split('*');
+    let first = pieces.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let pieces: Vec<&str> = pieces.collect();
+    let Some((last, middle)) = pieces.split_last() else {
+        return rest.is_empty();
+    };
+    for piece in middle {
+        match rest.find(piece) {
+            Some(at) => rest = &rest[at + piece.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Most a single attempt may use before its coding agent is stopped. Usage
+/// adds up over all runs of the attempt, and each time its budget is extended
+/// the attempt gets the same amount again.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct AttemptLimits {
+    /// Minutes the coding agents may run
+    pub max_minutes: Option<u32>,
+    /// Input and output tokens; cached input isn't counted
+    pub max_tokens: Option<u32>,
+    /// Cost as the executors report it; runs of executors that don't report
+    /// cost aren't held to it
+    pub max_cost_usd: Option<f64>,
+    pub max_tool_calls: Option<u32>,
+}
+
+impl AttemptLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_minutes.is_none()
+            && self.max_tokens.is_none()
+            && self.max_cost_usd.is_none()
+            && self.max_tool_calls.is_none()
+    }
+
+    /// The limits of an attempt whose budget was extended `extensions` times
+    pub fn extended(&self, extensions: u32) -> Self {
+        let times = extensions.saturating_add(1);
+        Self {
+            max_minutes: self.max_minutes.map(|max| max.saturating_mul(times)),
+            max_tokens: self.max_tokens.map(|max| max.saturating_mul(times)),
+            max_cost_usd: self.max_cost_usd.map(|max| max * f64::from(times)),
+            max_tool_calls: self.max_tool_calls.map(|max| max.saturating_mul(times)),
+        }
+    }
+
+    /// The first limit `used` reached
+    pub fn reached(&self, used: &BudgetUsage) -> Option<BudgetLimit> {
+        if self
+            .max_minutes
+            .is_some_and(|max| used.agent_seconds >= i64::from(max) * 60)
+        {
+            Some(BudgetLimit::Time)
+        } else if self
+            .max_tokens
+            .is_some_and(|max| used.tokens >= i64::from(max))
+        {
+            Some(BudgetLimit::Tokens)
+        } else if self.max_cost_usd.is_some_and(|max| used.cost_usd >= max) {
+            Some(BudgetLimit::Cost)
+        } else if self
+            .max_tool_calls
+            .is_some_and(|max| used.tool_calls >= i64::from(max))
+        {
+            Some(BudgetLimit::ToolCalls)
+        } else {
+            None
+        }
+    }
+}
+
+/// Automatic retries of coding agent runs that failed in a way another try
+/// may get past. A retry runs the failed request again after a delay that
+/// doubles with each retry of the attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub enabled: bool,
+    /// Retries allowed per attempt, over all of its runs
+    pub max_retries: u32,
+    /// Wait before the attempt's first retry
+    pub delay_seconds: u32,
+    /// Failures that are retried
+    pub retry_on: Vec<FailureKind>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_retries: 2,
+            delay_seconds: 30,
+            retry_on: vec![
+                FailureKind::RateLimit,
+                FailureKind::Network,
+                FailureKind::Crash,
+            ],
+        }
+    }
+}
+
+/// Linters, formatters and type checkers run in the workspace once an
+/// attempt's agent and cleanup script are done, such as `cargo clippy` or
+/// `npx eslint .`. Each check runs as a workspace command, so the project's
+/// exec policy applies. No checks means no gate.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct LintGate {
+    pub checks: Vec<LintCheck>,
+    /// Send the agent what the failed checks reported in a follow-up run
+    pub auto_fix: bool,
+    /// Fix-up runs allowed per attempt
+    pub max_fix_runs: u32,
+}
+
+impl Default for LintGate {
+    fn default() -> Self {
+        Self {
+            checks: Vec::new(),
+            auto_fix: false,
+            max_fix_runs: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct LintCheck {
+    pub name: String,
+    /// Run without a shell, like workspace commands
+    pub command: String,
+    /// Directory relative to the workspace root; the root when omitted
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// Defaults to 10 minutes
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// A second agent reviewing what an attempt's agent did once it and the
+/// cleanup script are done. The reviewer runs in a session of its own in the
+/// attempt's workspace and answers with comments, which are kept on the
+/// attempt and, when it asks for changes, can be handed back to the attempt's
+/// agent in a revision run that is reviewed in turn.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct AgentReviewSettings {
+    pub enabled: bool,
+    /// The executor profile reviewing; no reviews without one
+    pub reviewer: Option<ExecutorProfileId>,
+    /// What the reviewer should look at besides correctness, e.g. the
+    /// project's conventions
+    pub instructions: Option<String>,
+    /// Start a revision run with the comments when the reviewer asks for
+    /// changes
+    pub auto_revise: bool,
+    /// Reviews per attempt
+    pub max_iterations: u32,
+}
+
+impl Default for AgentReviewSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            reviewer: None,
+            instructions: None,
+            auto_revise: true,
+            max_iterations: 2,
+        }
+    }
+}
+
+/// Routes the coding agents' model API calls through this server, which adds
+/// the providers' keys, so the keys stay off the machines and containers the
+/// agents run on. Agents get a token of their attempt in place of a key; the
+/// secrets holding the providers' keys are then left out of their
+/// environment. Every call is logged and counted against the spend cap.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct LlmProxySettings {
+    pub enabled: bool,
+    /// Tried in order; when one can't be reached, is rate limited or fails,
+    /// the call goes to the next one with the same API
+    pub providers: Vec<LlmProvider>,
+    /// US dollars per calendar month (UTC) across the project's calls; calls
+    /// are refused once it is reached
+    pub monthly_spend_cap_usd: Option<f64>,
+    /// Keep request and response bodies in the log, with secrets masked
+    pub log_bodies: bool,
+    /// How the agents reach this server, e.g. from containers;
+    /// `http://127.0.0.1:<port>` when omitted
+    pub server_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct LlmProvider {
+    pub name: String,
+    pub api: LlmApi,
+    /// The API's public endpoint when omitted
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Name of the project secret holding the key
+    pub api_key_secret: String,
+    /// Prices per million tokens, to count calls against the spend cap
+    #[serde(default)]
+    pub input_usd_per_mtok: f64,
+    #[serde(default)]
+    pub output_usd_per_mtok: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub enum LlmApi {
+    #[serde(rename = "anthropic")]
+    #[ts(rename = "anthropic")]
+    Anthropic,
+    #[serde(rename = "openai")]
+    #[ts(rename = "openai")]
+    OpenAi,
+}
+
+/// A command or webhook run when one of the project's attempts reaches
+/// `events`, e.g. to start CI, update a ticket or send a notification. Hooks
+/// run one after another in the order they are listed, and get the attempt's
+/// details as JSON: a webhook as its body, a command in `VK_HOOK_PAYLOAD`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct LifecycleHook {
+    pub name: String,
+    pub events: Vec<HookEvent>,
+    pub action: HookAction,
+    /// Defaults to a minute
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Keep the attempt from starting when the hook fails; only applies to
+    /// `before_start`
+    #[serde(default)]
+    pub blocking: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum HookAction {
+    /// Run on this machine in the attempt's workspace, without a shell like
+    /// workspace commands (use `sh -c` for one), with the project's variables
+    /// and secrets and the `VK_*` variables of the attempt
+    Command {
+        command: String,
+        /// Directory relative to the workspace root; the root when omitted
+        #[serde(default)]
+        working_dir: Option<String>,
+    },
+    /// POST the details to `url`; any 2xx answer is a success
+    Webhook {
+        url: String,
+        /// Name of a project secret sent as a bearer token
+        #[serde(default)]
+        token_secret: Option<String>,
+    },
+}
+
+impl RetryPolicy {
+    /// How long to wait before retrying a run that failed with `kind`, given
+    /// the retries the attempt already had; None if it isn't retried
+    pub fn delay_for(&self, kind: FailureKind, retries_so_far: u32) -> Option<u64> {
+        if !self.enabled || retries_so_far >= self.max_retries || !self.retry_on.contains(&kind) {
+            return None;
+        }
+        Some(u64::from(self.delay_seconds) << retries_so_far.min(10))
+    }
+}
+
+/// Checklist every new task of the project starts with, e.g. "tests added" or
+/// "changelog updated". Changing it leaves the checklists of existing tasks as
+/// they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct DefinitionOfDone {
+    pub items: Vec<String>,
+    /// Refuse to move a task to done while items of its checklist are
+    /// unchecked
+    pub block_done_while_unchecked: bool,
+}
+
+/// What the board's rows split the tasks by. Large shared boards read better
+/// with one row per label, epic or parent task.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SwimlaneGrouping {
+    /// A single row holding every task
+    #[default]
+    None,
+    Label,
+    Epic,
+    ParentTask,
+}
+
+/// Notifications about the project's tasks nearing their due date. Each
+/// deadline is reminded of once; setting a new one brings another reminder.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct DueReminderSettings {
+    pub enabled: bool,
+    /// How long before the deadline the reminder is sent
+    pub hours_before: u32,
+}
+
+impl Default for DueReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hours_before: 24,
+        }
+    }
+}
+
+/// Most tasks each board column may hold before work has to move on. Columns
+/// without a limit take any number of tasks, and archived tasks don't count.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct WipLimitSettings {
+    pub enforcement: WipEnforcement,
+    pub limits: Vec<WipLimit>,
+}
+
+impl WipLimitSettings {
+    pub fn limit_for(&self, column_id: Uuid) -> Option<u32> {
+        self.limits
+            .iter()
+            .find(|limit| limit.column_id == column_id)
+            .map(|limit| limit.max_tasks)
+    }
+
+    /// Check tasks joining the board against the limits, returning which
+    /// columns they would overfill. Each task is given by its status and the
+    /// column stored on it, none meaning the first column of the status.
+    pub fn check(
+        &self,
+        columns: &[BoardColumn],
+        counts: &[ColumnTaskCount],
+        added: &[(TaskStatus, Option<Uuid>)],
+    ) -> Result<(), String> {
+        let mut over = Vec::new();
+        for column in columns {
+            let Some(max_tasks) = self.limit_for(column.id) else {
+                continue;
+            };
+            let joining = added
+                .iter()
+                .filter(|(status, column_id)| {
+                    column_id.or_else(|| board_column::first_column_for(columns, status))
+                        == Some(column.id)
+                })
+                .count() as i64;
+            if joining == 0 {
+                continue;
+            }
+            let current = counts
+                .iter()
+                .find(|count| count.column_id == column.id)
+                .map_or(0, |count| count.task_count);
+            if current + joining > i64::from(max_tasks) {
+                over.push(format!(
+                    "'{}' ({} of {max_tasks} tasks)",
+                    column.name,
+                    current + joining
+                ));
+            }
+        }
+        if over.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Over the WIP limit: {}", over.join(", ")))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WipLimit {
+    pub column_id: Uuid,
+    pub max_tasks: u32,
+}
+
+/// Whether a task that takes a column past its limit is still created or moved,
+/// with a warning in the response, or refused.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum WipEnforcement {
+    #[default]
+    Warn,
+    Reject,
+}
+
+/// GPUs handed to the containers of the project's attempts, for training runs
+/// and CUDA test suites. Executors on the host or an SSH host see that
+/// machine's GPUs anyway, and devcontainer projects request them in their own
+/// configuration. A Docker container keeps the GPUs it was created with.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct GpuSettings {
+    /// GPUs per attempt; zero requests none
+    pub count: u32,
+    /// Extended resource requested from Kubernetes, depending on the
+    /// cluster's device plugin
+    pub kubernetes_resource: String,
+}
+
+impl Default for GpuSettings {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            kubernetes_resource: "nvidia.com/gpu".to_string(),
+        }
+    }
+}
+
+/// Package caches kept per project and mounted into the containers of its
+/// attempts, so dependencies are downloaded once instead of by every attempt.
+/// Processes running on the host or an SSH host already share the user's own
+/// caches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct SharedCacheSettings {
+    pub caches: Vec<SharedCache>,
+    /// Empty a cache once it grows past this size and no execution uses it
+    pub max_size_mb: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SharedCache {
+    Cargo,
+    Npm,
+    Pnpm,
+    Yarn,
+    Pip,
+}
+
+/// Network access of the project's executor processes. `Allowlist` only lets
+/// them reach the listed domains and their subdomains; `Offline` blocks every
+/// destination other than the machine itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+#[ts(tag = "mode", rename_all = "snake_case")]
+pub enum NetworkPolicy {
+    #[default]
+    Unrestricted,
+    Allowlist {
+        /// e.g. `github.com`, which also allows `api.github.com`
+        domains: Vec<String>,
+    },
+    Offline,
+}
+
+impl NetworkPolicy {
+    /// Whether processes may connect to `host`, a domain name or IP address
+    pub fn allows_host(&self, host: &str) -> bool {
+        let domains = match self {
+            NetworkPolicy::Unrestricted => return true,
+            NetworkPolicy::Offline => return false,
+            NetworkPolicy::Allowlist { domains } => domains,
+        };
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        domains.iter().any(|domain| {
+            let domain = domain
+                .trim()
+                .trim_start_matches("*.")
+                .trim_end_matches('.')
+                .to_ascii_lowercase();
+            !domain.is_empty()
+                && (host == domain
+                    || host
+                        .strip_suffix(&domain)
+                        .is_some_and(|prefix| prefix.ends_with('.')))
+        })
+    }
+}
+
+/// Limits shared by all processes of a task attempt. Unset limits don't apply.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// CPU time as a number of cores, e.g. `1.5`
+    pub cpu_cores: Option<f64>,
+    pub memory_mb: Option<u32>,
+    pub max_processes: Option<u32>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.cpu_cores.is_none() && self.memory_mb.is_none() && self.max_processes.is_none()
+    }
+}
+
+/// Workspaces prepared ahead of time so new attempts start without waiting for
+/// their repositories to be checked out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct WorkspacePoolSettings {
+    /// Number of workspaces kept ready. Zero disables the pool.
+    pub size: u32,
+}
+
+/// Commands that may be run in the project's workspaces through the exec API,
+/// which is off until enabled. Commands are started without a shell, so the
+/// lists apply to the program actually being run; the project's own hooks and
+/// lint checks are held to the lists too. Terminals open only while the lists
+/// allow the shell they start.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct ExecPolicy {
+    pub enabled: bool,
+    /// Program names that may be run. Empty allows every program not denied.
+    pub allowed_programs: Vec<String>,
+    /// Program names that may not be run. Shells and programs that run
+    /// another one, such as `env` or `xargs`, are denied along with them.
+    pub denied_programs: Vec<String>,
+}
+
+/// Programs that run a command given in their arguments, which a deny list
+/// can't see into
+const COMMAND_RUNNERS: &[&str] = &[
+    "sh", "bash", "dash", "zsh", "ksh", "fish", "csh", "tcsh", "busybox", "env", "xargs", "nohup",
+    "nice", "ionice", "timeout", "time", "sudo", "doas", "su", "setsid", "stdbuf", "chroot",
+    "flock", "watch", "script", "parallel",
+];
+
+impl ExecPolicy {
+    /// Check the program of a command, returning the reason it was rejected.
+    /// Programs are matched by file name, so `/usr/bin/rm` matches `rm`.
+    pub fn check(&self, program: &str) -> Result<(), String> {
+        if !self.enabled {
+            return Err("Running commands is disabled for this project".to_string());
+        }
+        self.check_lists(program)
+    }
+
+    /// The policy for the commands the project configures itself, such as
+    /// lifecycle hooks and lint checks, which run whether or not the exec API
+    /// is enabled
+    pub fn for_configured_commands(&self) -> Self {
+        Self {
+            enabled: true,
+            ..self.clone()
+        }
+    }
+
+    fn check_lists(&self, program: &str) -> Result<(), String> {
+        let name = Path::new(program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(program);
+        if self.denied_programs.iter().any(|denied| denied == name) {
+            return Err(format!("'{name}' may not be run in this project"));
+        }
+        if !self.denied_programs.is_empty() && COMMAND_RUNNERS.contains(&name) {
+            return Err(format!(
+                "'{name}' runs other programs, so it may not be run while programs are denied"
+            ));
+        }
+        if !self.allowed_programs.is_empty()
+            && !self.allowed_programs.iter().any(|allowed| allowed == name)
+        {
+            return Err(format!(
+                "Only these programs may be run: {}",
+                self.allowed_programs.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Automatic removal of worktrees for finished attempts. Each rule is disabled
+/// while unset; the general cleanup of idle workspaces applies regardless.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Remove a workspace this many days after it was merged
+    pub merged_after_days: Option<i64>,
+    /// Remove an unmerged workspace of a done or cancelled task this many days
+    /// after its last activity
+    pub abandoned_after_days: Option<i64>,
+    /// Empty an attempt's scratch directory this many days after its last
+    /// activity, keeping the rest of the workspace
+    pub scratch_after_days: Option<i64>,
+    /// Keep the workspaces of a deleted project in the trash this many days
+    /// before removing them, instead of removing them right away
+    pub trash_after_delete_days: Option<i64>,
+}
+
+/// Where the executors of a project's task attempts run. `Docker` starts one
+/// container per attempt from `image` with the workspace bind-mounted into it.
+/// `Devcontainer` provisions the container from the repository's
+/// devcontainer.json instead. `Ssh` mirrors the workspace to a directory under
+/// `remote_root` on `host` and runs executors there.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum ContainerBackend {
+    #[default]
+    Local,
+    Docker {
+        image: String,
+    },
+    Devcontainer,
+    Ssh {
+        /// Destination passed to `ssh`, e.g. `user@build-server`
+        host: String,
+        remote_root: String,
+    },
+}
+
+/// Optional malware scan run on every file uploaded into a workspace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct UploadScanSettings {
+    pub enabled: bool,
+    pub scanner: Option<UploadScanner>,
+}
+
+/// `Command` runs a shell command that receives the file on stdin and exits
+/// non-zero to reject it. `Clamd` streams the file to a clamd daemon listening on
+/// a unix socket path or `host:port`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum UploadScanner {
+    Command { command: String },
+    Clamd { address: String },
+}
+
+/// What to do when a file is uploaded to a path matched by the repository's
+/// .gitignore: write it and flag it in the response, or reject the upload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum GitignoredUploadPolicy {
+    #[default]
+    Warn,
+    Refuse,
+}
+
+/// Limits applied to files uploaded into a project's tasks and workspaces.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct UploadPolicy {
+    /// Maximum number of bytes uploaded across all tasks of the project
+    pub max_total_bytes: Option<i64>,
+    /// Maximum size of a single uploaded file
+    pub max_file_bytes: Option<i64>,
+    /// Extensions (without the leading dot) that may be uploaded. Empty allows all.
+    pub allowed_extensions: Vec<String>,
+    /// Extensions (without the leading dot) that are always rejected, e.g. "exe"
+    pub denied_extensions: Vec<String>,
+}
+
+impl UploadPolicy {
+    /// Check a single upload against the policy, returning the reason it was rejected.
+    pub fn check(&self, file_name: &str, size_bytes: i64, used_bytes: i64) -> Result<(), String> {
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+        let listed = |list: &[String]| {
+            list.iter().any(|entry| {
+                entry
+                    .trim_start_matches('.')
+                    .eq_ignore_ascii_case(&extension)
+            })
+        };
+
+        if listed(&self.denied_extensions) {
+            return Err(format!(
+                "Files with extension '.{extension}' are not allowed in this project"
+            ));
+        }
+        if !self.allowed_extensions.is_empty() && !listed(&self.allowed_extensions) {
+            return Err(format!(
+                "Only these file types may be uploaded: {}",
+                self.allowed_extensions.join(", ")
+            ));
+        }
+        if let Some(max) = self.max_file_bytes
+            && size_bytes > max
+        {
+            return Err(format!(
+                "File is {size_bytes} bytes, the project limit is {max} bytes per file"
+            ));
+        }
+        if let Some(max) = self.max_total_bytes
+            && used_bytes + size_bytes > max
+        {
+            return Err(format!(
+                "Upload quota exceeded: {} of {max} bytes remaining",
+                (max - used_bytes).max(0)
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn remaining_bytes(&self, used_bytes: i64) -> Option<i64> {
+        self.max_total_bytes.map(|max| (max - used_bytes).max(0))
+    }
+}
+
+impl ProjectSettings {
+    /// Load the settings for a project, falling back to defaults when none were saved.
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT settings as "settings!: Json<ProjectSettings>"
+               FROM project_settings
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(record.map(|r| r.settings.0).unwrap_or_default())
+    }
+
+    /// Bytes counted against the project's upload quota: task images plus files
+    /// uploaded into its workspaces.
+    pub async fn used_upload_bytes(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let images = Image::total_size_by_project_id(pool, project_id).await?;
+        let uploads = WorkspaceUpload::total_size_by_project_id(pool, project_id).await?;
+        Ok(images + uploads)
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        settings: &ProjectSettings,
+    ) -> Result<Self, sqlx::Error> {
+        let settings_json = Json(settings);
+        sqlx::query!(
+            r#"INSERT INTO project_settings (project_id, settings)
+               VALUES ($1, $2)
+               ON CONFLICT(project_id) DO UPDATE
+               SET settings = excluded.settings,
+                   updated_at = datetime('now', 'subsec')"#,
+            project_id,
+            settings_json
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(settings.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_policy_matches_program_names() {
+        let policy = ExecPolicy {
+            enabled: true,
+            allowed_programs: vec!["cargo".to_string(), "rm".to_string()],
+            denied_programs: vec!["rm".to_string()],
+        };
+        assert!(policy.check("cargo").is_ok());
+        assert!(policy.check("/usr/bin/rm").is_err());
+        assert!(policy.check("npm").is_err());
+        assert!(ExecPolicy::default().check("ls").is_err());
+        assert!(
+            ExecPolicy::default()
+                .for_configured_commands()
+                .check("ls")
+                .is_ok()
+        );
+
+        let deny_only = ExecPolicy {
+            enabled: true,
+            allowed_programs: Vec::new(),
+            denied_programs: vec!["rm".to_string()],
+        };
+        assert!(deny_only.check("cargo").is_ok());
+        assert!(deny_only.check("sh").is_err());
+        assert!(deny_only.check("/usr/bin/env").is_err());
+        assert!(deny_only.check("xargs").is_err());
+        let unrestricted = ExecPolicy {
+            denied_programs: Vec::new(),
+            ..deny_only
+        };
+        assert!(unrestricted.check("sh").is_ok());
+    }
+
+    #[test]
+    fn executor_prompt_overrides_replace_what_they_set() {
+        let prompts = PromptSettings {
+            prefix: Some("Follow CONTRIBUTING.md.".to_string()),
+            suffix: Some("Use conventional commits.".to_string()),
+            overrides: vec![ExecutorPromptOverride {
+                executor: BaseCodingAgent::Codex,
+                prefix: Some("Read AGENTS.md first.".to_string()),
+                suffix: Some("  ".to_string()),
+                previous_attempt: Some(false),
+            }],
+            ..PromptSettings::default()
+        };
+        let context = Some("The previous attempt left 2 tests failing.");
+        assert_eq!(
+            prompts.for_executor(BaseCodingAgent::Codex, context),
+            Some(PromptAdditions {
+                prefix: Some("Read AGENTS.md first.".to_string()),
+                context: None,
+                suffix: Some("Use conventional commits.".to_string()),
+            })
+        );
+        assert_eq!(
+            prompts.for_executor(BaseCodingAgent::ClaudeCode, context),
+            Some(PromptAdditions {
+                prefix: Some("Follow CONTRIBUTING.md.".to_string()),
+                context: context.map(str::to_string),
+                suffix: Some("Use conventional commits.".to_string()),
+            })
+        );
+        assert_eq!(
+            PromptSettings::default().for_executor(BaseCodingAgent::Codex, None),
+            None
+        );
+    }
+
+    #[test]
+    fn extended_attempt_limits_scale_with_extensions() {
+        let limits = AttemptLimits {
+            max_minutes: Some(30),
+            max_tool_calls: Some(100),
+            ..AttemptLimits::default()
+        };
+        let used = BudgetUsage {
+            agent_seconds: 45 * 60,
+            tool_calls: 80,
+            ..BudgetUsage::default()
+        };
+        assert_eq!(limits.reached(&used), Some(BudgetLimit::Time));
+        assert_eq!(limits.extended(1).reached(&used), None);
+        assert_eq!(
+            limits.extended(1).reached(&BudgetUsage {
+                tool_calls: 200,
+                ..used
+            }),
+            Some(BudgetLimit::ToolCalls)
+        );
+        assert_eq!(AttemptLimits::default().reached(&used), None);
+    }
+
+    #[test]
+    fn command_policy_judges_each_chained_command() {
+        let policy = CommandPolicy {
+            enabled: true,
+            allow: vec!["cargo *".to_string(), "git status".to_string()],
+            deny: vec!["git push*".to_string(), "rm -rf *".to_string()],
+        };
+        let verdict = |command: &str| policy.decide(command).map(|v| v.approved);
+
+        assert_eq!(verdict("cargo test --workspace"), Some(true));
+        assert_eq!(verdict("git status && cargo build 2>&1"), Some(true));
+        assert_eq!(verdict("cargo fmt; git push --force"), Some(false));
+        assert_eq!(verdict("cargo test | rm -rf /"), Some(false));
+        assert_eq!(verdict("cargo test && npm test"), None);
+        assert_eq!(verdict("cargo run $(cat args)"), None);
+        assert_eq!(verdict("cargo test & rm -rf ~"), Some(false));
+        assert_eq!(verdict("cargo build & cargo test"), Some(true));
+        assert_eq!(verdict("cargo test > ~/.bashrc"), None);
+        assert_eq!(verdict("cargo test &> out.log"), None);
+        assert_eq!(verdict("cargo run < /etc/passwd"), None);
+        assert_eq!(verdict("cargo run <(curl example.com)"), None);
+        assert_eq!(verdict("cargo run >(sh)"), None);
+        assert_eq!(
+            policy.decide("git status || cargo check").unwrap().rule,
+            "git status, cargo *"
+        );
+        assert!(wildcard_match("a*b*c", "a-b-b-c"));
+        assert!(!wildcard_match("a*b", "a-b-c"));
+    }
+
+    #[test]
+    fn retry_delay_doubles_until_the_cap() {
+        let policy = RetryPolicy {
+            enabled: true,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.delay_for(FailureKind::RateLimit, 0), Some(30));
+        assert_eq!(policy.delay_for(FailureKind::Network, 1), Some(60));
+        assert_eq!(policy.delay_for(FailureKind::Network, 2), None);
+        assert_eq!(policy.delay_for(FailureKind::Other, 0), None);
+        assert_eq!(
+            RetryPolicy::default().delay_for(FailureKind::Crash, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn network_allowlist_matches_subdomains() {
+        let policy = NetworkPolicy::Allowlist {
+            domains: vec!["github.com".to_string(), "*.crates.io".to_string()],
+        };
+        assert!(policy.allows_host("github.com"));
+        assert!(policy.allows_host("API.GitHub.com."));
+        assert!(policy.allows_host("static.crates.io"));
+        assert!(!policy.allows_host("evilgithub.com"));
+        assert!(!policy.allows_host("github.com.evil.net"));
+        assert!(!NetworkPolicy::Offline.allows_host("localhost"));
+        assert!(NetworkPolicy::Unrestricted.allows_host("example.com"));
+    }
+
+    #[test]
+    fn denied_extension_wins_over_allow_list() {
+        let policy = UploadPolicy {
+            allowed_extensions: vec!["exe".into(), "png".into()],
+            denied_extensions: vec![".EXE".into()],
+            ..Default::default()
+        };
+        assert!(policy.check("setup.exe", 10, 0).is_err());
+        assert!(policy.check("shot.PNG", 10, 0).is_ok());
+        assert!(policy.check("notes.txt", 10, 0).is_err());
+    }
+
+    #[test]
+    fn enforces_file_and_total_limits() {
+        let policy = UploadPolicy {
+            max_total_bytes: Some(100),
+            max_file_bytes: Some(60),
+            ..Default::default()
+        };
+        assert!(policy.check("a.png", 61, 0).is_err());
+        assert!(policy.check("a.png", 50, 40).is_ok());
+        assert!(policy.check("a.png", 50, 60).is_err());
+        assert_eq!(policy.remaining_bytes(120), Some(0));
+        assert_eq!(UploadPolicy::default().remaining_bytes(120), None);
+    }
+
+    #[test]
+    fn wip_limits_count_tasks_joining_a_column() {
+        let column = |name: &str, position: i64, status: TaskStatus| BoardColumn {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            name: name.to_string(),
+            position,
+            status,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let columns = vec![
+            column("To Do", 0, TaskStatus::Todo),
+            column("Doing", 1, TaskStatus::InProgress),
+            column("Blocked", 2, TaskStatus::InProgress),
+        ];
+        let counts = vec![ColumnTaskCount {
+            column_id: columns[1].id,
+            task_count: 2,
+        }];
+        let settings = WipLimitSettings {
+            enforcement: WipEnforcement::Reject,
+            limits: vec![WipLimit {
+                column_id: columns[1].id,
+                max_tasks: 3,
+            }],
+        };
+
+        assert!(
+            settings
+                .check(&columns, &counts, &[(TaskStatus::InProgress, None)])
+                .is_ok()
+        );
+        assert!(
+            settings
+                .check(
+                    &columns,
+                    &counts,
+                    &[
+                        (TaskStatus::InProgress, None),
+                        (TaskStatus::InProgress, Some(columns[1].id)),
+                    ],
+                )
+                .is_err()
+        );
+        assert!(
+            settings
+                .check(
+                    &columns,
+                    &counts,
+                    &[
+                        (TaskStatus::Todo, None),
+                        (TaskStatus::InProgress, Some(columns[2].id)),
+                    ],
+                )
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskPriority;
+
+/// An attempt waiting for a slot to start in
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct QueuedAttempt {
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub task_title: String,
+    pub priority: TaskPriority,
+    #[ts(type = "ExecutorProfileId")]
+    pub executor_profile_id: Json<ExecutorProfileId>,
+    pub enqueued_at: DateTime<Utc>,
+    /// Why the attempt failed to start when it last left the queue. It stays
+    /// queued, without being started again, until it is stopped.
+    pub start_error: Option<String>,
+}
+
+/// Attempts of a project with a process other than a dev server running
+#[derive(Debug, Clone, FromRow)]
+pub struct RunningAttemptCount {
+    pub project_id: Uuid,
+    pub attempts: i64,
+}
+
+impl QueuedAttempt {
+    /// Oldest first
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            QueuedAttempt,
+            r#"SELECT qa.workspace_id as "workspace_id!: Uuid", t.id as "task_id!: Uuid", t.project_id as "project_id!: Uuid", t.title as "task_title!", t.priority as "priority!: TaskPriority", qa.executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>", qa.enqueued_at as "enqueued_at!: DateTime<Utc>", qa.start_error
+               FROM queued_attempts qa
+               JOIN workspaces w ON w.id = qa.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               ORDER BY qa.enqueued_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            QueuedAttempt,
+            r#"SELECT qa.workspace_id as "workspace_id!: Uuid", t.id as "task_id!: Uuid", t.project_id as "project_id!: Uuid", t.title as "task_title!", t.priority as "priority!: TaskPriority", qa.executor_profile_id as "executor_profile_id!: Json<ExecutorProfileId>", qa.enqueued_at as "enqueued_at!: DateTime<Utc>", qa.start_error
+               FROM queued_attempts qa
+               JOIN workspaces w ON w.id = qa.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE qa.workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        executor_profile_id: &ExecutorProfileId,
+    ) -> Result<(), sqlx::Error> {
+        let executor_profile_id = Json(executor_profile_id);
+        sqlx::query!(
+            r#"INSERT INTO queued_attempts (workspace_id, executor_profile_id)
+               VALUES ($1, $2)
+               ON CONFLICT(workspace_id) DO UPDATE
+               SET executor_profile_id = excluded.executor_profile_id,
+                   start_error = NULL"#,
+            workspace_id,
+            executor_profile_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Put an attempt that was taken off the queue back in its place, with
+    /// the reason it failed to start if it did
+    pub async fn requeue(
+        pool: &SqlitePool,
+        attempt: &QueuedAttempt,
+        start_error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO queued_attempts (workspace_id, executor_profile_id, enqueued_at, start_error)
+               VALUES ($1, $2, datetime($3, 'subsec'), $4)
+               ON CONFLICT(workspace_id) DO NOTHING"#,
+            attempt.workspace_id,
+            attempt.executor_profile_id,
+            attempt.enqueued_at,
+            start_error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether the attempt was still queued
+    pub async fn delete(pool: &SqlitePool, workspace_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM queued_attempts WHERE workspace_id = $1",
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn running_counts(
+        pool: &SqlitePool,
+    ) -> Result<Vec<RunningAttemptCount>, sqlx::Error> {
+        sqlx::query_as!(
+            RunningAttemptCount,
+            r#"SELECT t.project_id as "project_id!: Uuid", COUNT(DISTINCT w.id) as "attempts!: i64"
+               FROM execution_processes ep
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE ep.status = 'running'
+                 AND ep.run_reason != 'devserver'
+               GROUP BY t.project_id"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// Characters from the private use area that `snippet()` puts around matches
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+#[derive(Debug, Default)]
+pub struct SearchFilters {
+    pub project_id: Option<Uuid>,
+    pub status: Option<TaskStatus>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SearchHitKind {
+    /// The task's title or description matched
+    Task,
+    /// What a coding agent was asked or answered in one of the task's attempts
+    AgentTurn,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub project_id: Uuid,
+    pub status: TaskStatus,
+    /// Attempt the agent turn belongs to
+    pub workspace_id: Option<Uuid>,
+    pub snippet: Vec<SnippetPart>,
+    /// When the task or agent turn was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// Search tasks and agent turns, best matches first
+pub async fn search(
+    pool: &SqlitePool,
+    query: &str,
+    filters: &SearchFilters,
+    limit: i64,
+) -> Result<Vec<SearchHit>, sqlx::Error> {
+    let Some(query) = fts_query(query) else {
+        return Ok(Vec::new());
+    };
+    let records = sqlx::query!(
+        r#"SELECT
+  si.task_id                AS "task_id!: Uuid",
+  si.coding_agent_turn_id   AS "coding_agent_turn_id: Uuid",
+  t.title                   AS "task_title!",
+  t.project_id              AS "project_id!: Uuid",
+  t.status                  AS "status!: TaskStatus",
+  s.workspace_id            AS "workspace_id: Uuid",
+  snippet(search_index, -1, char(57344), char(57345), '…', 16) AS "snippet!: String",
+  COALESCE(cat.created_at, t.created_at) AS "created_at!: DateTime<Utc>"
+FROM search_index si
+JOIN tasks t ON t.id = si.task_id
+LEFT JOIN coding_agent_turns cat ON cat.id = si.coding_agent_turn_id
+LEFT JOIN execution_processes ep ON ep.id = cat.execution_process_id
+LEFT JOIN sessions s ON s.id = ep.session_id
+WHERE search_index MATCH $1
+  AND ($2 IS NULL OR t.project_id = $2)
+  AND ($3 IS NULL OR t.status = $3)
+  AND ($4 IS NULL OR COALESCE(cat.created_at, t.created_at) >= datetime($4))
+  AND ($5 IS NULL OR COALESCE(cat.created_at, t.created_at) <= datetime($5))
+ORDER BY rank
+LIMIT $6"#,
+        query,
+        filters.project_id,
+        filters.status,
+        filters.from,
+        filters.to,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|rec| SearchHit {
+            kind: if rec.coding_agent_turn_id.is_some() {
+                SearchHitKind::AgentTurn
+            } else {
+                SearchHitKind::Task
+            },
+            task_id: rec.task_id,
+            task_title: rec.task_title,
+            project_id: rec.project_id,
+            status: rec.status,
+            workspace_id: rec.workspace_id,
+            snippet: snippet_parts(&rec.snippet),
+            created_at: rec.created_at,
+        })
+        .collect())
+}
+
+/// Turn free text into an FTS5 query matching all of its words, the last one
+/// as a prefix since it may still be typed. Each word is quoted so that
+/// characters with a meaning in the query syntax are taken literally.
+pub fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
+
+/// Split a snippet into plain and matched text
+pub fn snippet_parts(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut highlighted = false;
+    for c in snippet.chars() {
+        if c == MATCH_START || c == MATCH_END {
+            if !text.is_empty() {
+                parts.push(SnippetPart {
+                    text: std::mem::take(&mut text),
+                    highlighted,
+                });
+            }
+            highlighted = c == MATCH_START;
+        } else {
+            text.push(c);
+        }
+    }
+    if !text.is_empty() {
+        parts.push(SnippetPart { text, highlighted });
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_words_and_matches_prefix_of_the_last() {
+        assert_eq!(fts_query("   "), None);
+        assert_eq!(
+            fts_query("add migration"),
+            Some("\"add\" \"migration\"*".to_string())
+        );
+        assert_eq!(
+            fts_query("say \"hi\" OR"),
+            Some("\"say\" \"\"\"hi\"\"\" \"OR\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn splits_snippets_at_match_markers() {
+        let parts = snippet_parts("…ran the \u{E000}migration\u{E001} twice");
+        let parts: Vec<(&str, bool)> = parts
+            .iter()
+            .map(|part| (part.text.as_str(), part.highlighted))
+            .collect();
+        assert_eq!(
+            parts,
+            [("…ran the ", false), ("migration", true), (" twice", false)]
+        );
+    }
+}
@@ -1,11 +1,14 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
 use serde::{Deserialize, Serialize};
-use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type, types::Json};
 use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, workspace::Workspace};
+use super::{label::TaskLabel, project::Project, test_run::TestRun, workspace::Workspace};
 
 #[derive(
     Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default,
@@ -22,6 +25,20 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+#[derive(
+    Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default,
+)]
+#[sqlx(type_name = "task_priority", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TaskPriority {
+    Urgent,
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Task {
     pub id: Uuid,
@@ -30,6 +47,27 @@ pub struct Task {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub parent_workspace_id: Option<Uuid>, // Foreign key to parent Workspace
+    pub parent_task_id: Option<Uuid>,      // Task this one is a subtask of
+    pub priority: TaskPriority,
+    /// Place within the board column of the task's status, lowest first
+    pub position: i64,
+    /// Set while the task is archived and left off the board
+    pub archived_at: Option<DateTime<Utc>>,
+    /// When the task should be done by
+    pub due_at: Option<DateTime<Utc>>,
+    /// Custom board column holding the task; none means the first column of
+    /// its status
+    pub column_id: Option<Uuid>,
+    /// Epic the task is part of
+    pub epic_id: Option<Uuid>,
+    /// Executor profile for the task's attempts, in place of the configured
+    /// default
+    #[ts(type = "ExecutorProfileId | null")]
+    pub executor_profile_id: Option<Json<ExecutorProfileId>>,
+    /// Gitignore-style patterns of the paths attempts should change; empty
+    /// for the whole repository
+    #[ts(type = "Array<string>")]
+    pub scope_paths: Json<Vec<String>>,
     pub shared_task_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -43,6 +81,40 @@ pub struct TaskWithAttemptStatus {
     pub has_in_progress_attempt: bool,
     pub last_attempt_failed: bool,
     pub executor: String,
+    /// Whether a task this one depends on isn't done yet
+    pub is_blocked: bool,
+    pub subtasks: SubtaskProgress,
+    pub label_ids: Vec<Uuid>,
+    /// Tests the task's attempts ran last, for the red/green badge
+    pub latest_test_run: Option<TestRun>,
+}
+
+/// Roll-up of a task's direct subtasks; cancelled subtasks don't count
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct SubtaskProgress {
+    pub total: u32,
+    pub done: u32,
+}
+
+impl SubtaskProgress {
+    pub fn of(subtasks: &[Task]) -> Self {
+        let counted = subtasks
+            .iter()
+            .filter(|task| task.status != TaskStatus::Cancelled);
+        Self {
+            total: counted.clone().count() as u32,
+            done: counted
+                .filter(|task| task.status == TaskStatus::Done)
+                .count() as u32,
+        }
+    }
+}
+
+impl TaskWithAttemptStatus {
+    /// Whether the task carries any of the labels; no labels matches every task
+    pub fn has_any_label(&self, label_ids: &[Uuid]) -> bool {
+        label_ids.is_empty() || self.label_ids.iter().any(|id| label_ids.contains(id))
+    }
 }
 
 impl std::ops::Deref for TaskWithAttemptStatus {
@@ -72,8 +144,12 @@ pub struct CreateTask {
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub parent_workspace_id: Option<Uuid>,
+    /// Create the task as a subtask of this task
+    pub parent_task_id: Option<Uuid>,
+    pub priority: Option<TaskPriority>,
     pub image_ids: Option<Vec<Uuid>>,
     pub shared_task_id: Option<Uuid>,
+    pub due_at: Option<DateTime<Utc>>,
 }
 
 impl CreateTask {
@@ -88,8 +164,11 @@ impl CreateTask {
             description,
             status: Some(TaskStatus::Todo),
             parent_workspace_id: None,
+            parent_task_id: None,
+            priority: None,
             image_ids: None,
             shared_task_id: None,
+            due_at: None,
         }
     }
 
@@ -106,8 +185,11 @@ impl CreateTask {
             description,
             status: Some(status),
             parent_workspace_id: None,
+            parent_task_id: None,
+            priority: None,
             image_ids: None,
             shared_task_id: Some(shared_task_id),
+            due_at: None,
         }
     }
 }
@@ -118,16 +200,25 @@ pub struct UpdateTask {
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub parent_workspace_id: Option<Uuid>,
+    pub priority: Option<TaskPriority>,
     pub image_ids: Option<Vec<Uuid>>,
 }
 
 impl Task {
     pub fn to_prompt(&self) -> String {
-        if let Some(description) = self.description.as_ref().filter(|d| !d.trim().is_empty()) {
-            format!("{}\n\n{}", &self.title, description)
-        } else {
-            self.title.clone()
+        let mut prompt =
+            if let Some(description) = self.description.as_ref().filter(|d| !d.trim().is_empty()) {
+                format!("{}\n\n{}", &self.title, description)
+            } else {
+                self.title.clone()
+            };
+        if !self.scope_paths.is_empty() {
+            prompt.push_str("\n\nOnly change files matching these paths (gitignore patterns):");
+            for pattern in self.scope_paths.iter() {
+                prompt.push_str(&format!("\n- {pattern}"));
+            }
         }
+        prompt
     }
 
     pub async fn parent_project(&self, pool: &SqlitePool) -> Result<Option<Project>, sqlx::Error> {
@@ -146,6 +237,15 @@ impl Task {
   t.description,
   t.status                        AS "status!: TaskStatus",
   t.parent_workspace_id           AS "parent_workspace_id: Uuid",
+  t.parent_task_id                AS "parent_task_id: Uuid",
+  t.priority                      AS "priority!: TaskPriority",
+  t.position                      AS "position!: i64",
+  t.archived_at                   AS "archived_at: DateTime<Utc>",
+  t.due_at                        AS "due_at: DateTime<Utc>",
+  t.column_id                     AS "column_id: Uuid",
+  t.epic_id                       AS "epic_id: Uuid",
+  t.executor_profile_id           AS "executor_profile_id: Json<ExecutorProfileId>",
+  t.scope_paths                   AS "scope_paths!: Json<Vec<String>>",
   t.shared_task_id                AS "shared_task_id: Uuid",
   t.created_at                    AS "created_at!: DateTime<Utc>",
   t.updated_at                    AS "updated_at!: DateTime<Utc>",
@@ -179,19 +279,52 @@ impl Task {
       WHERE w.task_id = t.id
      ORDER BY s.created_at DESC
       LIMIT 1
-    )                               AS "executor!: String"
+    )                               AS "executor!: String",
+
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM task_dependencies td
+      JOIN tasks dt ON dt.id = td.depends_on_task_id
+     WHERE td.task_id = t.id
+       AND dt.status != 'done'
+  ) THEN 1 ELSE 0 END            AS "is_blocked!: i64",
+
+  ( SELECT COUNT(*)
+      FROM tasks st
+     WHERE st.parent_task_id = t.id
+       AND st.status != 'cancelled'
+  )                               AS "subtasks_total!: i64",
+
+  ( SELECT COUNT(*)
+      FROM tasks st
+     WHERE st.parent_task_id = t.id
+       AND st.status = 'done'
+  )                               AS "subtasks_done!: i64"
 
 FROM tasks t
 WHERE t.project_id = $1
+  AND t.archived_at IS NULL
 ORDER BY t.created_at DESC"#,
             project_id
         )
         .fetch_all(pool)
         .await?;
 
+        let mut labels: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for task_label in TaskLabel::find_by_project_id(pool, project_id).await? {
+            labels
+                .entry(task_label.task_id)
+                .or_default()
+                .push(task_label.label_id);
+        }
+
+        let mut test_runs = TestRun::latest_by_project_id(pool, project_id).await?;
+
         let tasks = records
             .into_iter()
             .map(|rec| TaskWithAttemptStatus {
+                label_ids: labels.remove(&rec.id).unwrap_or_default(),
+                latest_test_run: test_runs.remove(&rec.id),
                 task: Task {
                     id: rec.id,
                     project_id: rec.project_id,
@@ -199,6 +332,15 @@ ORDER BY t.created_at DESC"#,
                     description: rec.description,
                     status: rec.status,
                     parent_workspace_id: rec.parent_workspace_id,
+                    parent_task_id: rec.parent_task_id,
+                    priority: rec.priority,
+                    position: rec.position,
+                    archived_at: rec.archived_at,
+                    due_at: rec.due_at,
+                    column_id: rec.column_id,
+                    epic_id: rec.epic_id,
+                    executor_profile_id: rec.executor_profile_id,
+                    scope_paths: rec.scope_paths,
                     shared_task_id: rec.shared_task_id,
                     created_at: rec.created_at,
                     updated_at: rec.updated_at,
@@ -206,6 +348,11 @@ ORDER BY t.created_at DESC"#,
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
                 last_attempt_failed: rec.last_attempt_failed != 0,
                 executor: rec.executor,
+                is_blocked: rec.is_blocked != 0,
+                subtasks: SubtaskProgress {
+                    total: rec.subtasks_total as u32,
+                    done: rec.subtasks_done as u32,
+                },
             })
             .collect();
 
@@ -215,7 +362,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE id = $1"#,
             id
@@ -227,7 +374,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE rowid = $1"#,
             rowid
@@ -245,7 +392,7 @@ ORDER BY t.created_at DESC"#,
     {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE shared_task_id = $1
                LIMIT 1"#,
@@ -258,7 +405,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_all_shared(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE shared_task_id IS NOT NULL"#
         )
@@ -266,70 +413,363 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
-    pub async fn create(
-        pool: &SqlitePool,
+    pub async fn create<'e, E>(
+        executor: E,
         data: &CreateTask,
         task_id: Uuid,
-    ) -> Result<Self, sqlx::Error> {
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let status = data.status.clone().unwrap_or_default();
+        let priority = data.priority.unwrap_or_default();
+        // New tasks go to the top of their column
         sqlx::query_as!(
             Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_workspace_id, shared_task_id)
-               VALUES ($1, $2, $3, $4, $5, $6, $7)
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_workspace_id, shared_task_id, parent_task_id, priority, due_at, position)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
+                       (SELECT COALESCE(MIN(position), 0) - 1 FROM tasks WHERE project_id = $2))
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             task_id,
             data.project_id,
             data.title,
             data.description,
             status,
             data.parent_workspace_id,
-            data.shared_task_id
+            data.shared_task_id,
+            data.parent_task_id,
+            priority,
+            data.due_at
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
-    pub async fn update(
-        pool: &SqlitePool,
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update<'e, E>(
+        executor: E,
         id: Uuid,
         project_id: Uuid,
         title: String,
         description: Option<String>,
         status: TaskStatus,
         parent_workspace_id: Option<Uuid>,
-    ) -> Result<Self, sqlx::Error> {
+        priority: TaskPriority,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Task,
             r#"UPDATE tasks
-               SET title = $3, description = $4, status = $5, parent_workspace_id = $6
+               SET title = $3, description = $4, status = $5, parent_workspace_id = $6, priority = $7
                WHERE id = $1 AND project_id = $2
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             project_id,
             title,
             description,
             status,
-            parent_workspace_id
+            parent_workspace_id,
+            priority
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
-    pub async fn update_status(
+    /// Move the task to `index` within a board column and number the column
+    /// from the top. The column is given by the status and the custom column,
+    /// none meaning the status's first column. Returns the ids of the column's
+    /// tasks in their new order.
+    pub async fn move_to(
         pool: &SqlitePool,
+        task: &Task,
+        status: TaskStatus,
+        column_id: Option<Uuid>,
+        index: usize,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut column = sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid"
+               FROM tasks
+               WHERE project_id = $1 AND status = $2 AND column_id IS $3 AND id != $4
+               ORDER BY position ASC, created_at DESC"#,
+            task.project_id,
+            status,
+            column_id,
+            task.id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        column.insert(index.min(column.len()), task.id);
+
+        if status != task.status || column_id != task.column_id {
+            sqlx::query!(
+                "UPDATE tasks SET status = $2, column_id = $3, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+                task.id,
+                status,
+                column_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        for (position, id) in column.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                "UPDATE tasks SET position = $2 WHERE id = $1 AND position != $2",
+                id,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(column)
+    }
+
+    pub async fn update_status<'e, E>(
+        executor: E,
         id: Uuid,
         status: TaskStatus,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query!(
             "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
             id,
             status
         )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_priority<'e, E>(
+        executor: E,
+        id: Uuid,
+        priority: TaskPriority,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "UPDATE tasks SET priority = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            priority
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Archive the task, keeping the original archive time if it already is,
+    /// or bring it back to the board
+    pub async fn set_archived<'e, E>(
+        executor: E,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"UPDATE tasks
+               SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, datetime('now', 'subsec')) ELSE NULL END,
+                   updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1"#,
+            id,
+            archived
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Set the task's deadline, or clear it
+    pub async fn set_due_at<'e, E>(
+        executor: E,
+        id: Uuid,
+        due_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "UPDATE tasks SET due_at = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            due_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Put the task in the epic, or take it out of its epic
+    pub async fn set_epic(
+        pool: &SqlitePool,
+        id: Uuid,
+        epic_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET epic_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            epic_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Pin the executor profile the task's attempts start with, or go back to
+    /// the configured default
+    pub async fn set_executor_profile(
+        pool: &SqlitePool,
+        id: Uuid,
+        executor_profile_id: Option<&ExecutorProfileId>,
+    ) -> Result<(), sqlx::Error> {
+        let executor_profile_id = executor_profile_id.map(Json);
+        sqlx::query!(
+            "UPDATE tasks SET executor_profile_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            executor_profile_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_scope_paths(
+        pool: &SqlitePool,
+        id: Uuid,
+        scope_paths: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let scope_paths = Json(scope_paths);
+        sqlx::query!(
+            "UPDATE tasks SET scope_paths = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id,
+            scope_paths
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Open tasks due before `before`, overdue ones included, soonest first.
+    /// Done, cancelled and archived tasks are left out.
+    pub async fn find_due_before(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE due_at IS NOT NULL
+                 AND datetime(due_at) <= datetime($2)
+                 AND status NOT IN ('done', 'cancelled')
+                 AND archived_at IS NULL
+                 AND ($1 IS NULL OR project_id = $1)
+               ORDER BY datetime(due_at) ASC"#,
+            project_id,
+            before
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Open tasks with a deadline that nobody has been reminded of yet
+    pub async fn find_awaiting_due_reminder(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE due_at IS NOT NULL
+                 AND due_reminder_sent_at IS NULL
+                 AND status NOT IN ('done', 'cancelled')
+                 AND archived_at IS NULL
+               ORDER BY datetime(due_at) ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn mark_due_reminder_sent(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET due_reminder_sent_at = datetime('now', 'subsec') WHERE id = $1",
+            id
+        )
         .execute(pool)
         .await?;
         Ok(())
     }
 
+    /// Every task of the project, archived ones included, in board order
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE project_id = $1
+               ORDER BY position ASC, created_at DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// A page of the project's archived tasks, most recently archived first,
+    /// along with the number of archived tasks across all pages. `fts_query`
+    /// narrows both to tasks whose title or description matches it.
+    pub async fn find_archived_page(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        fts_query: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Self>, i64), sqlx::Error> {
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE project_id = $1
+                 AND archived_at IS NOT NULL
+                 AND ($2 IS NULL OR id IN (SELECT task_id
+                                           FROM search_index
+                                           WHERE search_index MATCH $2
+                                             AND coding_agent_turn_id IS NULL))
+               ORDER BY archived_at DESC, created_at DESC
+               LIMIT $3 OFFSET $4"#,
+            project_id,
+            fts_query,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let total = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM tasks
+               WHERE project_id = $1
+                 AND archived_at IS NOT NULL
+                 AND ($2 IS NULL OR id IN (SELECT task_id
+                                           FROM search_index
+                                           WHERE search_index MATCH $2
+                                             AND coding_agent_turn_id IS NULL))"#,
+            project_id,
+            fts_query
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok((tasks, total))
+    }
+
     /// Update the parent_workspace_id field for a task
     pub async fn update_parent_workspace_id(
         pool: &SqlitePool,
@@ -446,7 +886,7 @@ ORDER BY t.created_at DESC"#,
         // Find only child tasks that have this workspace as their parent
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE parent_workspace_id = $1
                ORDER BY created_at DESC"#,
@@ -456,6 +896,22 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    pub async fn find_subtasks(
+        pool: &SqlitePool,
+        parent_task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", position as "position!: i64", archived_at as "archived_at: DateTime<Utc>", due_at as "due_at: DateTime<Utc>", column_id as "column_id: Uuid", epic_id as "epic_id: Uuid", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", scope_paths as "scope_paths!: Json<Vec<String>>", shared_task_id as "shared_task_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE parent_task_id = $1
+               ORDER BY created_at ASC"#,
+            parent_task_id,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn find_relationships_for_workspace(
         pool: &SqlitePool,
         workspace: &Workspace,
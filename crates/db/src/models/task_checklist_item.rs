@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskChecklistItem {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub label: String,
+    pub checked: bool,
+    pub position: i64,
+    pub checked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskChecklistItem {
+    pub label: String,
+}
+
+impl TaskChecklistItem {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskChecklistItem,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      label,
+                      checked as "checked!: bool",
+                      position as "position!: i64",
+                      checked_at as "checked_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_checklist_items
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskChecklistItem,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      label,
+                      checked as "checked!: bool",
+                      position as "position!: i64",
+                      checked_at as "checked_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_checklist_items
+               WHERE task_id = $1
+               ORDER BY position ASC, created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Add an item at the end of the task's checklist
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        data: &CreateTaskChecklistItem,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let label = data.label.trim();
+        sqlx::query_as!(
+            TaskChecklistItem,
+            r#"INSERT INTO task_checklist_items (id, task_id, label, position)
+               VALUES (
+                   $1, $2, $3,
+                   (SELECT COALESCE(MAX(position) + 1, 0) FROM task_checklist_items WHERE task_id = $2)
+               )
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         label,
+                         checked as "checked!: bool",
+                         position as "position!: i64",
+                         checked_at as "checked_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            label
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn set_checked(
+        pool: &SqlitePool,
+        id: Uuid,
+        checked: bool,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TaskChecklistItem,
+            r#"UPDATE task_checklist_items
+               SET checked = $2,
+                   checked_at = CASE WHEN $2 THEN datetime('now', 'subsec') END
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         label,
+                         checked as "checked!: bool",
+                         position as "position!: i64",
+                         checked_at as "checked_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            checked
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_checklist_items WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn count_unchecked(pool: &SqlitePool, task_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM task_checklist_items
+               WHERE task_id = $1 AND checked = FALSE"#,
+            task_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::{Task, TaskPriority, TaskStatus};
+
+/// `task_id` is blocked by `depends_on_task_id` until that task is done
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskDependency {
+    pub task_id: Uuid,
+    pub depends_on_task_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskDependency {
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TaskDependency,
+            r#"INSERT INTO task_dependencies (task_id, depends_on_task_id)
+               VALUES ($1, $2)
+               RETURNING task_id as "task_id!: Uuid",
+                         depends_on_task_id as "depends_on_task_id!: Uuid",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            task_id,
+            depends_on_task_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND depends_on_task_id = $2",
+            task_id,
+            depends_on_task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// All dependencies between tasks of the project
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskDependency,
+            r#"SELECT td.task_id as "task_id!: Uuid",
+                      td.depends_on_task_id as "depends_on_task_id!: Uuid",
+                      td.created_at as "created_at!: DateTime<Utc>"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.task_id
+               WHERE t.project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Tasks the given task depends on
+    pub async fn find_blockers(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.parent_task_id as "parent_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position as "position!: i64", t.archived_at as "archived_at: DateTime<Utc>", t.due_at as "due_at: DateTime<Utc>", t.column_id as "column_id: Uuid", t.epic_id as "epic_id: Uuid", t.executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", t.scope_paths as "scope_paths!: Json<Vec<String>>", t.shared_task_id as "shared_task_id: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.depends_on_task_id
+               WHERE td.task_id = $1
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Tasks that depend on the given task
+    pub async fn find_dependents(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.parent_task_id as "parent_task_id: Uuid", t.priority as "priority!: TaskPriority", t.position as "position!: i64", t.archived_at as "archived_at: DateTime<Utc>", t.due_at as "due_at: DateTime<Utc>", t.column_id as "column_id: Uuid", t.epic_id as "epic_id: Uuid", t.executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", t.scope_paths as "scope_paths!: Json<Vec<String>>", t.shared_task_id as "shared_task_id: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.task_id
+               WHERE td.depends_on_task_id = $1
+               ORDER BY t.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+/// Whether making `task_id` depend on `depends_on_task_id` would close a loop,
+/// i.e. `depends_on_task_id` already depends on `task_id`, directly or not
+pub fn creates_cycle(
+    dependencies: &[TaskDependency],
+    task_id: Uuid,
+    depends_on_task_id: Uuid,
+) -> bool {
+    let mut edges: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for dependency in dependencies {
+        edges
+            .entry(dependency.task_id)
+            .or_default()
+            .push(dependency.depends_on_task_id);
+    }
+
+    let mut seen = HashSet::new();
+    let mut stack = vec![depends_on_task_id];
+    while let Some(current) = stack.pop() {
+        if current == task_id {
+            return true;
+        }
+        if seen.insert(current) {
+            stack.extend(edges.get(&current).into_iter().flatten());
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(task_id: Uuid, depends_on_task_id: Uuid) -> TaskDependency {
+        TaskDependency {
+            task_id,
+            depends_on_task_id,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn detects_direct_and_transitive_cycles() {
+        let (a, b, c, d) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        // a is blocked by b, b by c
+        let dependencies = [dependency(a, b), dependency(b, c)];
+
+        assert!(creates_cycle(&dependencies, a, a));
+        assert!(creates_cycle(&dependencies, b, a));
+        assert!(creates_cycle(&dependencies, c, a));
+        assert!(!creates_cycle(&dependencies, a, c));
+        assert!(!creates_cycle(&dependencies, d, a));
+        assert!(!creates_cycle(&dependencies, c, d));
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::Task;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "task_link_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum TaskLinkType {
+    RelatesTo,
+    /// The task repeats the linked one
+    Duplicates,
+    /// The task continues the linked one, such as work left after reviewing
+    /// its attempt
+    FollowsUp,
+}
+
+/// `task_id` relates to, duplicates or follows up `linked_task_id`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskLink {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub linked_task_id: Uuid,
+    pub link_type: TaskLinkType,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskLink {
+    pub linked_task_id: Uuid,
+    pub link_type: TaskLinkType,
+}
+
+/// A link as seen from one of its tasks
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LinkedTask {
+    pub link_id: Uuid,
+    pub link_type: TaskLinkType,
+    /// Set when the link points at the viewed task, e.g. the other task
+    /// follows up this one
+    pub incoming: bool,
+    pub task: Task,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskWithLinks {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub task: Task,
+    pub links: Vec<LinkedTask>,
+}
+
+impl TaskLink {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskLink,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      linked_task_id as "linked_task_id!: Uuid",
+                      link_type as "link_type!: TaskLinkType",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_links
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Links from or to the task, oldest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskLink,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      linked_task_id as "linked_task_id!: Uuid",
+                      link_type as "link_type!: TaskLinkType",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_links
+               WHERE task_id = $1 OR linked_task_id = $1
+               ORDER BY created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Links of the task along with the task at their other end
+    pub async fn find_linked_tasks(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<LinkedTask>, sqlx::Error> {
+        let mut linked = Vec::new();
+        for link in Self::find_by_task_id(pool, task_id).await? {
+            let incoming = link.linked_task_id == task_id;
+            let other_id = if incoming {
+                link.task_id
+            } else {
+                link.linked_task_id
+            };
+            if let Some(task) = Task::find_by_id(pool, other_id).await? {
+                linked.push(LinkedTask {
+                    link_id: link.id,
+                    link_type: link.link_type,
+                    incoming,
+                    task,
+                });
+            }
+        }
+        Ok(linked)
+    }
+
+    pub async fn create<'e, E>(
+        executor: E,
+        task_id: Uuid,
+        data: &CreateTaskLink,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            TaskLink,
+            r#"INSERT INTO task_links (id, task_id, linked_task_id, link_type)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         linked_task_id as "linked_task_id!: Uuid",
+                         link_type as "link_type!: TaskLinkType",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            data.linked_task_id,
+            data.link_type
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_links WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Whether the link joins the two tasks, in either direction
+    pub fn joins(&self, a: Uuid, b: Uuid) -> bool {
+        (self.task_id == a && self.linked_task_id == b)
+            || (self.task_id == b && self.linked_task_id == a)
+    }
+}
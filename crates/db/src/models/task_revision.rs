@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "task_revision_author", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(rename_all = "lowercase")]
+pub enum TaskRevisionAuthor {
+    User,
+    Agent,
+}
+
+/// The task's title and description as one edit left them
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskRevision {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    /// None for the version the task had before its first recorded edit
+    pub author: Option<TaskRevisionAuthor>,
+    /// Signed-in user who made the edit
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct CreateTaskRevision<'a> {
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub author: Option<TaskRevisionAuthor>,
+    pub author_name: Option<&'a str>,
+    /// Defaults to now
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl TaskRevision {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskRevision,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      title,
+                      description,
+                      author as "author: TaskRevisionAuthor",
+                      author_name,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_revisions
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Oldest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskRevision,
+            r#"SELECT id as "id!: Uuid",
+                      task_id as "task_id!: Uuid",
+                      title,
+                      description,
+                      author as "author: TaskRevisionAuthor",
+                      author_name,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_revisions
+               WHERE task_id = $1
+               ORDER BY created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn exists_for_task(pool: &SqlitePool, task_id: Uuid) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM task_revisions WHERE task_id = $1) as "exists!: bool""#,
+            task_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(exists)
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        data: &CreateTaskRevision<'_>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            TaskRevision,
+            r#"INSERT INTO task_revisions (id, task_id, title, description, author, author_name, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, datetime('now', 'subsec')))
+               RETURNING id as "id!: Uuid",
+                         task_id as "task_id!: Uuid",
+                         title,
+                         description,
+                         author as "author: TaskRevisionAuthor",
+                         author_name,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            data.title,
+            data.description,
+            data.author,
+            data.author_name,
+            data.created_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
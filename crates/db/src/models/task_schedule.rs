@@ -0,0 +1,313 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::workspace_repo::CreateWorkspaceRepo;
+
+/// Attempt started for every task a schedule creates
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ScheduledAttempt {
+    pub executor_profile_id: ExecutorProfileId,
+    pub repos: Vec<CreateWorkspaceRepo>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskSchedule {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    /// Five-field cron expression, evaluated in UTC
+    pub cron_expression: String,
+    /// Attempt to start for each created task; without one the task is only created
+    #[ts(type = "ScheduledAttempt | null")]
+    pub attempt: Option<Json<ScheduledAttempt>>,
+    pub paused: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateTaskSchedule {
+    pub title: String,
+    pub description: Option<String>,
+    pub cron_expression: String,
+    pub attempt: Option<ScheduledAttempt>,
+}
+
+/// What one run of a schedule created
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskScheduleRun {
+    pub id: Uuid,
+    pub schedule_id: Uuid,
+    /// Time the run was due, which can be earlier than when it happened
+    pub scheduled_for: DateTime<Utc>,
+    pub task_id: Option<Uuid>,
+    pub workspace_id: Option<Uuid>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskSchedule {
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateTaskSchedule,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let attempt = data.attempt.as_ref().map(Json);
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"INSERT INTO task_schedules (id, project_id, title, description, cron_expression, attempt, next_run_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         title,
+                         description,
+                         cron_expression,
+                         attempt as "attempt: Json<ScheduledAttempt>",
+                         paused as "paused!: bool",
+                         next_run_at as "next_run_at!: DateTime<Utc>",
+                         last_run_at as "last_run_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.title,
+            data.description,
+            data.cron_expression,
+            attempt,
+            next_run_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      title,
+                      description,
+                      cron_expression,
+                      attempt as "attempt: Json<ScheduledAttempt>",
+                      paused as "paused!: bool",
+                      next_run_at as "next_run_at!: DateTime<Utc>",
+                      last_run_at as "last_run_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_schedules
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      title,
+                      description,
+                      cron_expression,
+                      attempt as "attempt: Json<ScheduledAttempt>",
+                      paused as "paused!: bool",
+                      next_run_at as "next_run_at!: DateTime<Utc>",
+                      last_run_at as "last_run_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_schedules
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Schedules that aren't paused and whose next run is due at `now`
+    pub async fn find_due(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      title,
+                      description,
+                      cron_expression,
+                      attempt as "attempt: Json<ScheduledAttempt>",
+                      paused as "paused!: bool",
+                      next_run_at as "next_run_at!: DateTime<Utc>",
+                      last_run_at as "last_run_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_schedules
+               WHERE paused = 0 AND next_run_at <= $1
+               ORDER BY next_run_at ASC"#,
+            now
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &CreateTaskSchedule,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let attempt = data.attempt.as_ref().map(Json);
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"UPDATE task_schedules
+               SET title = $2, description = $3, cron_expression = $4, attempt = $5,
+                   next_run_at = $6, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         title,
+                         description,
+                         cron_expression,
+                         attempt as "attempt: Json<ScheduledAttempt>",
+                         paused as "paused!: bool",
+                         next_run_at as "next_run_at!: DateTime<Utc>",
+                         last_run_at as "last_run_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.title,
+            data.description,
+            data.cron_expression,
+            attempt,
+            next_run_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Pause or resume the schedule. Resuming moves the next run past the time
+    /// spent paused rather than catching up.
+    pub async fn set_paused(
+        pool: &SqlitePool,
+        id: Uuid,
+        paused: bool,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"UPDATE task_schedules
+               SET paused = $2, next_run_at = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         title,
+                         description,
+                         cron_expression,
+                         attempt as "attempt: Json<ScheduledAttempt>",
+                         paused as "paused!: bool",
+                         next_run_at as "next_run_at!: DateTime<Utc>",
+                         last_run_at as "last_run_at: DateTime<Utc>",
+                         created_at as "created_at!: DateTime<Utc>",
+                         updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            paused,
+            next_run_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn mark_run(
+        pool: &SqlitePool,
+        id: Uuid,
+        ran_at: DateTime<Utc>,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE task_schedules SET last_run_at = $2, next_run_at = $3 WHERE id = $1",
+            id,
+            ran_at,
+            next_run_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_schedules WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl TaskScheduleRun {
+    pub async fn create(
+        pool: &SqlitePool,
+        schedule_id: Uuid,
+        scheduled_for: DateTime<Utc>,
+        task_id: Option<Uuid>,
+        workspace_id: Option<Uuid>,
+        error: Option<String>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            TaskScheduleRun,
+            r#"INSERT INTO task_schedule_runs (id, schedule_id, scheduled_for, task_id, workspace_id, error)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid",
+                         schedule_id as "schedule_id!: Uuid",
+                         scheduled_for as "scheduled_for!: DateTime<Utc>",
+                         task_id as "task_id: Uuid",
+                         workspace_id as "workspace_id: Uuid",
+                         error,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            schedule_id,
+            scheduled_for,
+            task_id,
+            workspace_id,
+            error
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Latest runs of the schedule, newest first
+    pub async fn find_by_schedule_id(
+        pool: &SqlitePool,
+        schedule_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskScheduleRun,
+            r#"SELECT id as "id!: Uuid",
+                      schedule_id as "schedule_id!: Uuid",
+                      scheduled_for as "scheduled_for!: DateTime<Utc>",
+                      task_id as "task_id: Uuid",
+                      workspace_id as "workspace_id: Uuid",
+                      error,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_schedule_runs
+               WHERE schedule_id = $1
+               ORDER BY created_at DESC
+               LIMIT $2"#,
+            schedule_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskTimeEntry {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub seconds: i64,
+    pub note: Option<String>,
+    /// When the work was done
+    pub spent_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskTimeEntry {
+    pub seconds: i64,
+    pub note: Option<String>,
+    /// Defaults to now
+    pub spent_at: Option<DateTime<Utc>>,
+}
+
+impl TaskTimeEntry {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTimeEntry,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", seconds, note, spent_at as "spent_at!: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM task_time_entries
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTimeEntry,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", seconds, note, spent_at as "spent_at!: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM task_time_entries
+               WHERE task_id = $1
+               ORDER BY datetime(spent_at) DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Entries of the project's tasks spent within the range, each end
+    /// optional
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskTimeEntry,
+            r#"SELECT e.id as "id!: Uuid", e.task_id as "task_id!: Uuid", e.seconds, e.note, e.spent_at as "spent_at!: DateTime<Utc>", e.created_at as "created_at!: DateTime<Utc>"
+               FROM task_time_entries e
+               JOIN tasks t ON t.id = e.task_id
+               WHERE t.project_id = $1
+                 AND ($2 IS NULL OR datetime(e.spent_at) >= datetime($2))
+                 AND ($3 IS NULL OR datetime(e.spent_at) <= datetime($3))
+               ORDER BY datetime(e.spent_at) DESC"#,
+            project_id,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        data: &CreateTaskTimeEntry,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let spent_at = data.spent_at.unwrap_or_else(Utc::now);
+        sqlx::query_as!(
+            TaskTimeEntry,
+            r#"INSERT INTO task_time_entries (id, task_id, seconds, note, spent_at)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", seconds, note, spent_at as "spent_at!: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            data.seconds,
+            data.note,
+            spent_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_time_entries WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use executors::test_output::{TestRunner, TestSummary};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Tests an execution process ran, as its output reported them
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct TestRun {
+    pub execution_process_id: Uuid,
+    pub workspace_id: Uuid,
+    pub runner: TestRunner,
+    pub passed: i64,
+    pub failed: i64,
+    pub skipped: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TestRun {
+    /// Newest first, leaving out processes dropped from the history
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TestRun,
+            r#"SELECT tr.execution_process_id as "execution_process_id!: Uuid", tr.workspace_id as "workspace_id!: Uuid", tr.runner as "runner!: TestRunner", tr.passed, tr.failed, tr.skipped, tr.created_at as "created_at!: DateTime<Utc>"
+               FROM test_runs tr
+               JOIN execution_processes ep ON ep.id = tr.execution_process_id
+               WHERE tr.workspace_id = $1
+                 AND ep.dropped = FALSE
+               ORDER BY tr.created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The latest test run of each of the project's tasks that had one, by
+    /// task id
+    pub async fn latest_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<Uuid, Self>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT w.task_id as "task_id!: Uuid", tr.execution_process_id as "execution_process_id!: Uuid", tr.workspace_id as "workspace_id!: Uuid", tr.runner as "runner!: TestRunner", tr.passed, tr.failed, tr.skipped, tr.created_at as "created_at!: DateTime<Utc>"
+               FROM test_runs tr
+               JOIN workspaces w ON w.id = tr.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               JOIN execution_processes ep ON ep.id = tr.execution_process_id
+               WHERE t.project_id = $1
+                 AND ep.dropped = FALSE
+               ORDER BY tr.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // Later runs replace earlier ones of the same task
+        Ok(records
+            .into_iter()
+            .map(|rec| {
+                (
+                    rec.task_id,
+                    TestRun {
+                        execution_process_id: rec.execution_process_id,
+                        workspace_id: rec.workspace_id,
+                        runner: rec.runner,
+                        passed: rec.passed,
+                        failed: rec.failed,
+                        skipped: rec.skipped,
+                        created_at: rec.created_at,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        workspace_id: Uuid,
+        summary: &TestSummary,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO test_runs (execution_process_id, workspace_id, runner, passed, failed, skipped)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT(execution_process_id) DO UPDATE SET
+                   runner = excluded.runner,
+                   passed = excluded.passed,
+                   failed = excluded.failed,
+                   skipped = excluded.skipped"#,
+            execution_process_id,
+            workspace_id,
+            summary.runner,
+            summary.passed,
+            summary.failed,
+            summary.skipped
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,13 +1,15 @@
 use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
 use super::{
+    execution_process::ExecutionProcessRunReason,
     project::Project,
-    task::Task,
+    task::{Task, TaskStatus},
     workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
 };
 
@@ -52,10 +54,48 @@ pub struct Workspace {
     pub branch: String,
     pub agent_working_dir: Option<String>,
     pub setup_completed_at: Option<DateTime<Utc>>,
+    /// Executor profile the attempt was started with
+    #[ts(type = "ExecutorProfileId | null")]
+    pub executor_profile_id: Option<Json<ExecutorProfileId>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// What the retention janitor needs to know about a workspace that still has a
+/// directory on disk.
+#[derive(Debug, Clone)]
+pub struct WorkspaceActivity {
+    pub workspace_id: Uuid,
+    pub project_id: Uuid,
+    pub container_ref: String,
+    pub task_status: TaskStatus,
+    /// When the workspace was last merged, directly or through a merged PR
+    pub merged_at: Option<DateTime<Utc>>,
+    /// Latest of the last completed execution and the workspace's own update
+    pub last_activity_at: DateTime<Utc>,
+    pub has_running_processes: bool,
+}
+
+/// How an attempt went, for comparing the executor profiles attempts are
+/// started with
+#[derive(Debug, Clone)]
+pub struct WorkspaceOutcome {
+    pub workspace_id: Uuid,
+    pub task_status: TaskStatus,
+    pub executor_profile_id: Option<Json<ExecutorProfileId>>,
+    /// Executor of the attempt's first session, for attempts started before
+    /// the profile was kept on the workspace
+    pub session_executor: Option<String>,
+    /// Merged directly or through a merged PR
+    pub merged: bool,
+    /// The task has a newer attempt
+    pub superseded: bool,
+    pub coding_agent_runs: i64,
+    /// Sum of the costs the coding agent runs reported
+    pub cost_usd: Option<f64>,
+    pub runs_without_cost: i64,
+}
+
 /// GitHub PR creation parameters
 pub struct CreatePrParams<'a> {
     pub workspace_id: Uuid,
@@ -91,6 +131,7 @@ pub struct WorkspaceContext {
 pub struct CreateWorkspace {
     pub branch: String,
     pub agent_working_dir: Option<String>,
+    pub executor_profile_id: Option<ExecutorProfileId>,
 }
 
 impl Workspace {
@@ -112,6 +153,7 @@ impl Workspace {
                               branch,
                               agent_working_dir,
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              executor_profile_id AS "executor_profile_id: Json<ExecutorProfileId>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM workspaces
@@ -130,6 +172,7 @@ impl Workspace {
                               branch,
                               agent_working_dir,
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              executor_profile_id AS "executor_profile_id: Json<ExecutorProfileId>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM workspaces
@@ -143,6 +186,31 @@ impl Workspace {
         Ok(workspaces)
     }
 
+    /// Workspaces of all tasks in a project
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Workspace,
+            r#"SELECT w.id AS "id!: Uuid",
+                      w.task_id AS "task_id!: Uuid",
+                      w.container_ref,
+                      w.branch,
+                      w.agent_working_dir,
+                      w.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                      w.executor_profile_id AS "executor_profile_id: Json<ExecutorProfileId>",
+                      w.created_at AS "created_at!: DateTime<Utc>",
+                      w.updated_at AS "updated_at!: DateTime<Utc>"
+               FROM workspaces w
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Load workspace with full validation - ensures workspace belongs to task and task belongs to project
     pub async fn load_context(
         pool: &SqlitePool,
@@ -158,6 +226,7 @@ impl Workspace {
                        w.branch,
                        w.agent_working_dir,
                        w.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       w.executor_profile_id AS "executor_profile_id: Json<ExecutorProfileId>",
                        w.created_at        AS "created_at!: DateTime<Utc>",
                        w.updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    workspaces w
@@ -244,6 +313,7 @@ impl Workspace {
                        branch,
                        agent_working_dir,
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       executor_profile_id AS "executor_profile_id: Json<ExecutorProfileId>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    workspaces
@@ -254,6 +324,31 @@ impl Workspace {
         .await
     }
 
+    /// The workspaces that have the repository
+    pub async fn find_by_repo_id(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Workspace,
+            r#"SELECT  w.id                AS "id!: Uuid",
+                       w.task_id           AS "task_id!: Uuid",
+                       w.container_ref,
+                       w.branch,
+                       w.agent_working_dir,
+                       w.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       w.executor_profile_id AS "executor_profile_id: Json<ExecutorProfileId>",
+                       w.created_at        AS "created_at!: DateTime<Utc>",
+                       w.updated_at        AS "updated_at!: DateTime<Utc>"
+               FROM    workspaces w
+               JOIN    workspace_repos wr ON wr.workspace_id = w.id
+               WHERE   wr.repo_id = $1"#,
+            repo_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Workspace,
@@ -263,6 +358,7 @@ impl Workspace {
                        branch,
                        agent_working_dir,
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       executor_profile_id AS "executor_profile_id: Json<ExecutorProfileId>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    workspaces
@@ -287,6 +383,76 @@ impl Workspace {
         Ok(result.exists)
     }
 
+    /// Outcome of each attempt at the project's tasks created within the
+    /// range, each end optional
+    pub async fn find_outcomes_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<WorkspaceOutcome>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceOutcome,
+            r#"
+            SELECT
+                w.id as "workspace_id!: Uuid",
+                t.status as "task_status!: TaskStatus",
+                w.executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>",
+                (
+                    SELECT s.executor
+                    FROM sessions s
+                    WHERE s.workspace_id = w.id
+                    ORDER BY s.created_at ASC
+                    LIMIT 1
+                ) as "session_executor: String",
+                EXISTS (
+                    SELECT 1
+                    FROM merges m
+                    WHERE m.workspace_id = w.id
+                        AND (m.merge_type = 'direct' OR m.pr_status = 'merged')
+                ) as "merged!: bool",
+                EXISTS (
+                    SELECT 1
+                    FROM workspaces later
+                    WHERE later.task_id = w.task_id
+                        AND datetime(later.created_at) > datetime(w.created_at)
+                ) as "superseded!: bool",
+                (
+                    SELECT COUNT(*)
+                    FROM sessions s
+                    JOIN execution_processes ep ON ep.session_id = s.id
+                    WHERE s.workspace_id = w.id AND ep.run_reason = $2
+                ) as "coding_agent_runs!: i64",
+                (
+                    SELECT SUM(ep.cost_usd)
+                    FROM sessions s
+                    JOIN execution_processes ep ON ep.session_id = s.id
+                    WHERE s.workspace_id = w.id AND ep.run_reason = $2
+                ) as "cost_usd: f64",
+                (
+                    SELECT COUNT(*)
+                    FROM sessions s
+                    JOIN execution_processes ep ON ep.session_id = s.id
+                    WHERE s.workspace_id = w.id
+                        AND ep.run_reason = $2
+                        AND ep.cost_usd IS NULL
+                ) as "runs_without_cost!: i64"
+            FROM workspaces w
+            JOIN tasks t ON t.id = w.task_id
+            WHERE t.project_id = $1
+                AND ($3 IS NULL OR datetime(w.created_at) >= datetime($3))
+                AND ($4 IS NULL OR datetime(w.created_at) <= datetime($4))
+            ORDER BY w.created_at ASC
+            "#,
+            project_id,
+            ExecutionProcessRunReason::CodingAgent,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Find workspaces that are expired (72+ hours since last activity) and eligible for cleanup
     pub async fn find_expired_for_cleanup(
         pool: &SqlitePool,
@@ -301,6 +467,7 @@ impl Workspace {
                 w.branch as "branch!",
                 w.agent_working_dir,
                 w.setup_completed_at as "setup_completed_at: DateTime<Utc>",
+                w.executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>",
                 w.created_at as "created_at!: DateTime<Utc>",
                 w.updated_at as "updated_at!: DateTime<Utc>"
             FROM workspaces w
@@ -334,23 +501,70 @@ impl Workspace {
         .await
     }
 
+    /// Activity summary of every workspace that still has a directory on disk.
+    pub async fn find_activity_with_container(
+        pool: &SqlitePool,
+    ) -> Result<Vec<WorkspaceActivity>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceActivity,
+            r#"
+            SELECT
+                w.id as "workspace_id!: Uuid",
+                t.project_id as "project_id!: Uuid",
+                w.container_ref as "container_ref!",
+                t.status as "task_status!: TaskStatus",
+                (
+                    SELECT MAX(COALESCE(m.pr_merged_at, m.created_at))
+                    FROM merges m
+                    WHERE m.workspace_id = w.id
+                        AND (m.merge_type = 'direct' OR m.pr_status = 'merged')
+                ) as "merged_at: DateTime<Utc>",
+                MAX(
+                    w.updated_at,
+                    COALESCE(
+                        (
+                            SELECT MAX(ep.completed_at)
+                            FROM sessions s
+                            JOIN execution_processes ep ON ep.session_id = s.id
+                            WHERE s.workspace_id = w.id
+                        ),
+                        w.updated_at
+                    )
+                ) as "last_activity_at!: DateTime<Utc>",
+                EXISTS (
+                    SELECT 1
+                    FROM sessions s
+                    JOIN execution_processes ep ON ep.session_id = s.id
+                    WHERE s.workspace_id = w.id AND ep.completed_at IS NULL
+                ) as "has_running_processes!: bool"
+            FROM workspaces w
+            JOIN tasks t ON t.id = w.task_id
+            WHERE w.container_ref IS NOT NULL
+            "#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn create(
         pool: &SqlitePool,
         data: &CreateWorkspace,
         id: Uuid,
         task_id: Uuid,
     ) -> Result<Self, WorkspaceError> {
+        let executor_profile_id = data.executor_profile_id.as_ref().map(Json);
         Ok(sqlx::query_as!(
             Workspace,
-            r#"INSERT INTO workspaces (id, task_id, container_ref, branch, agent_working_dir, setup_completed_at)
-               VALUES ($1, $2, $3, $4, $5, $6)
-               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, agent_working_dir, setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO workspaces (id, task_id, container_ref, branch, agent_working_dir, setup_completed_at, executor_profile_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, agent_working_dir, setup_completed_at as "setup_completed_at: DateTime<Utc>", executor_profile_id as "executor_profile_id: Json<ExecutorProfileId>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             task_id,
             Option::<String>::None,
             data.branch,
             data.agent_working_dir,
-            Option::<DateTime<Utc>>::None
+            Option::<DateTime<Utc>>::None,
+            executor_profile_id
         )
         .fetch_one(pool)
         .await?)
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A file an agent published for review
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct WorkspaceArtifact {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    /// Run that last wrote the file
+    pub execution_process_id: Uuid,
+    /// Path below the artifacts directory, using forward slashes
+    pub name: String,
+    pub size_bytes: i64,
+    /// SHA256 of the contents
+    pub hash: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A file found in the artifacts directory
+#[derive(Debug, Clone)]
+pub struct CreateWorkspaceArtifact {
+    pub name: String,
+    pub size_bytes: i64,
+    pub hash: String,
+}
+
+impl WorkspaceArtifact {
+    /// Ordered by name
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceArtifact,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      execution_process_id as "execution_process_id!: Uuid",
+                      name,
+                      size_bytes,
+                      hash,
+                      created_at as "created_at!: DateTime<Utc>",
+                      updated_at as "updated_at!: DateTime<Utc>"
+               FROM workspace_artifacts
+               WHERE workspace_id = $1
+               ORDER BY name ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Make the workspace's artifacts match the files found after a run.
+    /// Files the run changed are credited to it, and rows of files that are
+    /// gone are removed.
+    pub async fn sync(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+        files: &[CreateWorkspaceArtifact],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let recorded = sqlx::query_scalar!(
+            "SELECT name FROM workspace_artifacts WHERE workspace_id = $1",
+            workspace_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for name in recorded {
+            if !files.iter().any(|file| file.name == name) {
+                sqlx::query!(
+                    "DELETE FROM workspace_artifacts WHERE workspace_id = $1 AND name = $2",
+                    workspace_id,
+                    name
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        for file in files {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO workspace_artifacts (id, workspace_id, execution_process_id, name, size_bytes, hash)
+                   VALUES ($1, $2, $3, $4, $5, $6)
+                   ON CONFLICT(workspace_id, name) DO UPDATE
+                   SET execution_process_id = excluded.execution_process_id,
+                       size_bytes = excluded.size_bytes,
+                       hash = excluded.hash,
+                       updated_at = datetime('now', 'subsec')
+                   WHERE workspace_artifacts.hash != excluded.hash"#,
+                id,
+                workspace_id,
+                execution_process_id,
+                file.name,
+                file.size_bytes,
+                file.hash
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use strum_macros::{Display, EnumString};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Which of the project's attempt limits was reached
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum BudgetLimit {
+    Time,
+    Tokens,
+    Cost,
+    ToolCalls,
+}
+
+/// What the coding agent runs of an attempt used so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct BudgetUsage {
+    /// Time the coding agents ran, counting running ones up to now
+    pub agent_seconds: i64,
+    /// Input and output tokens; cached input isn't counted
+    pub tokens: i64,
+    pub cost_usd: f64,
+    pub tool_calls: i64,
+}
+
+/// How far an attempt's budget was extended and what stopped it last
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct WorkspaceBudget {
+    pub workspace_id: Uuid,
+    /// Each extension grants the project's limits once more
+    pub extensions: i64,
+    /// Cleared when the budget is extended
+    pub stopped_by: Option<BudgetLimit>,
+    pub stopped_at: Option<DateTime<Utc>>,
+}
+
+impl WorkspaceBudget {
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceBudget,
+            r#"SELECT workspace_id as "workspace_id!: Uuid", extensions, stopped_by as "stopped_by?: BudgetLimit", stopped_at as "stopped_at?: DateTime<Utc>"
+               FROM workspace_budgets
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn record_stop(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        limit: BudgetLimit,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"INSERT INTO workspace_budgets (workspace_id, stopped_by, stopped_at)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(workspace_id) DO UPDATE SET stopped_by = excluded.stopped_by, stopped_at = excluded.stopped_at"#,
+            workspace_id,
+            limit,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Grant the attempt the project's limits once more
+    pub async fn extend(pool: &SqlitePool, workspace_id: Uuid) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceBudget,
+            r#"INSERT INTO workspace_budgets (workspace_id, extensions)
+               VALUES ($1, 1)
+               ON CONFLICT(workspace_id) DO UPDATE SET extensions = extensions + 1, stopped_by = NULL, stopped_at = NULL
+               RETURNING workspace_id as "workspace_id!: Uuid", extensions, stopped_by as "stopped_by?: BudgetLimit", stopped_at as "stopped_at?: DateTime<Utc>""#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// What the attempt's coding agent runs used, leaving out tool calls of
+    /// runs still going, which are only counted once they end
+    pub async fn usage(pool: &SqlitePool, workspace_id: Uuid) -> Result<BudgetUsage, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"SELECT
+                    COALESCE(SUM(CAST((julianday(COALESCE(ep.completed_at, datetime('now', 'subsec'))) - julianday(ep.started_at)) * 86400 AS INTEGER)), 0) as "agent_seconds!: i64",
+                    COALESCE(SUM(COALESCE(ep.input_tokens, 0) + COALESCE(ep.output_tokens, 0)), 0) as "tokens!: i64",
+                    COALESCE(SUM(ep.cost_usd), 0.0) as "cost_usd!: f64",
+                    COALESCE(SUM(ep.tool_calls), 0) as "tool_calls!: i64"
+               FROM execution_processes ep
+               JOIN sessions s ON ep.session_id = s.id
+               WHERE s.workspace_id = $1
+                 AND ep.run_reason = 'codingagent'"#,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(BudgetUsage {
+            agent_seconds: row.agent_seconds,
+            tokens: row.tokens,
+            cost_usd: row.cost_usd,
+            tool_calls: row.tool_calls,
+        })
+    }
+}
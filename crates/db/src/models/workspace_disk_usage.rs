@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceDiskUsage {
+    pub workspace_id: Uuid,
+    pub size_bytes: i64,
+    pub measured_at: DateTime<Utc>,
+}
+
+impl WorkspaceDiskUsage {
+    pub async fn upsert(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        size_bytes: i64,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceDiskUsage,
+            r#"INSERT INTO workspace_disk_usage (workspace_id, size_bytes)
+               VALUES ($1, $2)
+               ON CONFLICT(workspace_id) DO UPDATE
+               SET size_bytes = excluded.size_bytes,
+                   measured_at = datetime('now', 'subsec')
+               RETURNING workspace_id as "workspace_id!: Uuid",
+                         size_bytes,
+                         measured_at as "measured_at!: DateTime<Utc>""#,
+            workspace_id,
+            size_bytes,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceDiskUsage,
+            r#"SELECT workspace_id as "workspace_id!: Uuid",
+                      size_bytes,
+                      measured_at as "measured_at!: DateTime<Utc>"
+               FROM workspace_disk_usage
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Usage of the project's workspaces that still exist on disk, largest first.
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceDiskUsage,
+            r#"SELECT du.workspace_id as "workspace_id!: Uuid",
+                      du.size_bytes,
+                      du.measured_at as "measured_at!: DateTime<Utc>"
+               FROM workspace_disk_usage du
+               JOIN workspaces w ON w.id = du.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1 AND w.container_ref IS NOT NULL
+               ORDER BY du.size_bytes DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM workspace_disk_usage WHERE workspace_id = $1",
+            workspace_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
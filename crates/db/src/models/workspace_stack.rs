@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+/// An attempt based on another attempt's branch rather than the repository's
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WorkspaceStack {
+    pub workspace_id: Uuid,
+    pub parent_workspace_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WorkspaceStack {
+    pub async fn create(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+        parent_workspace_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceStack,
+            r#"INSERT INTO workspace_stacks (workspace_id, parent_workspace_id)
+               VALUES ($1, $2)
+               RETURNING workspace_id as "workspace_id!: Uuid", parent_workspace_id as "parent_workspace_id!: Uuid", created_at as "created_at!: DateTime<Utc>""#,
+            workspace_id,
+            parent_workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The attempt this one is stacked on, if any
+    pub async fn find_parent_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT parent_workspace_id as "parent_workspace_id!: Uuid"
+               FROM workspace_stacks
+               WHERE workspace_id = $1"#,
+            workspace_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The attempts stacked directly on this one, oldest first
+    pub async fn find_child_ids(
+        pool: &SqlitePool,
+        parent_workspace_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT workspace_id as "workspace_id!: Uuid"
+               FROM workspace_stacks
+               WHERE parent_workspace_id = $1
+               ORDER BY created_at ASC"#,
+            parent_workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceUpload {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub path: String, // relative to the workspace root
+    pub size_bytes: i64,
+    pub hash: String, // SHA256 of the uploaded contents
+    pub uploaded_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateWorkspaceUpload {
+    pub workspace_id: Uuid,
+    pub path: String,
+    pub size_bytes: i64,
+    pub hash: String,
+    pub uploaded_by: Option<String>,
+}
+
+impl WorkspaceUpload {
+    /// Record an upload, replacing any earlier upload to the same path.
+    pub async fn upsert(
+        pool: &SqlitePool,
+        data: &CreateWorkspaceUpload,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WorkspaceUpload,
+            r#"INSERT INTO workspace_uploads (id, workspace_id, path, size_bytes, hash, uploaded_by)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT(workspace_id, path) DO UPDATE
+               SET size_bytes = excluded.size_bytes,
+                   hash = excluded.hash,
+                   uploaded_by = excluded.uploaded_by,
+                   created_at = datetime('now', 'subsec')
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         path,
+                         size_bytes,
+                         hash,
+                         uploaded_by,
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.path,
+            data.size_bytes,
+            data.hash,
+            data.uploaded_by,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceUpload,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      path,
+                      size_bytes,
+                      hash,
+                      uploaded_by,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_uploads
+               WHERE workspace_id = $1
+               ORDER BY created_at DESC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Total size of files uploaded into any workspace of the project.
+    pub async fn total_size_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(wu.size_bytes), 0) AS "total!: i64"
+               FROM workspace_uploads wu
+               JOIN workspaces w ON w.id = wu.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1"#,
+            project_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
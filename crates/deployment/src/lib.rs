@@ -32,6 +32,8 @@ use services::services::{
     queued_message::QueuedMessageService,
     repo::RepoService,
     share::SharePublisher,
+    terminal::TerminalService,
+    workspace_pool::WorkspacePool,
     worktree_manager::WorktreeError,
 };
 use sqlx::Error as SqlxError;
@@ -109,6 +111,10 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn queued_message_service(&self) -> &QueuedMessageService;
 
+    fn terminals(&self) -> &TerminalService;
+
+    fn workspace_pool(&self) -> &WorkspacePool;
+
     fn auth_context(&self) -> &AuthContext;
 
     fn share_publisher(&self) -> Result<SharePublisher, RemoteClientNotConfigured>;
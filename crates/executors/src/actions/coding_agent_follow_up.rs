@@ -10,6 +10,7 @@ use crate::{
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
     profile::{ExecutorConfigs, ExecutorProfileId},
+    prompt_additions::PromptAdditions,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -24,6 +25,9 @@ pub struct CodingAgentFollowUpRequest {
     /// If None, uses the container_ref directory directly.
     #[serde(default)]
     pub working_dir: Option<String>,
+    /// The project's prompt additions as they were when the run started
+    #[serde(default)]
+    pub prompt_additions: Option<PromptAdditions>,
 }
 
 impl CodingAgentFollowUpRequest {
@@ -32,6 +36,14 @@ impl CodingAgentFollowUpRequest {
         self.executor_profile_id.clone()
     }
 
+    /// The prompt with the project's additions put around it
+    pub fn agent_prompt(&self) -> String {
+        match &self.prompt_additions {
+            Some(additions) => additions.apply(&self.prompt),
+            None => self.prompt.clone(),
+        }
+    }
+
     pub fn effective_dir(&self, current_dir: &Path) -> std::path::PathBuf {
         match &self.working_dir {
             Some(rel_path) => current_dir.join(rel_path),
@@ -64,7 +76,7 @@ impl Executable for CodingAgentFollowUpRequest {
         agent.use_approvals(approvals.clone());
 
         agent
-            .spawn_follow_up(&effective_dir, &self.prompt, &self.session_id, env)
+            .spawn_follow_up(&effective_dir, &self.agent_prompt(), &self.session_id, env)
             .await
     }
 }
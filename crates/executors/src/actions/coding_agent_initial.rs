@@ -10,6 +10,7 @@ use crate::{
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
     profile::{ExecutorConfigs, ExecutorProfileId},
+    prompt_additions::PromptAdditions,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -23,6 +24,9 @@ pub struct CodingAgentInitialRequest {
     /// If None, uses the container_ref directory directly.
     #[serde(default)]
     pub working_dir: Option<String>,
+    /// The project's prompt additions as they were when the run started
+    #[serde(default)]
+    pub prompt_additions: Option<PromptAdditions>,
 }
 
 impl CodingAgentInitialRequest {
@@ -30,6 +34,14 @@ impl CodingAgentInitialRequest {
         self.executor_profile_id.executor
     }
 
+    /// The prompt with the project's additions put around it
+    pub fn agent_prompt(&self) -> String {
+        match &self.prompt_additions {
+            Some(additions) => additions.apply(&self.prompt),
+            None => self.prompt.clone(),
+        }
+    }
+
     pub fn effective_dir(&self, current_dir: &Path) -> std::path::PathBuf {
         match &self.working_dir {
             Some(rel_path) => current_dir.join(rel_path),
@@ -57,6 +69,6 @@ impl Executable for CodingAgentInitialRequest {
 
         agent.use_approvals(approvals.clone());
 
-        agent.spawn(&effective_dir, &self.prompt, env).await
+        agent.spawn(&effective_dir, &self.agent_prompt(), env).await
     }
 }
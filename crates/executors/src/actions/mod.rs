@@ -13,6 +13,7 @@ use crate::{
     approvals::ExecutorApprovalService,
     env::ExecutionEnv,
     executors::{BaseCodingAgent, ExecutorError, SpawnedChild},
+    prompt_additions::PromptAdditions,
 };
 pub mod coding_agent_follow_up;
 pub mod coding_agent_initial;
@@ -46,6 +47,27 @@ impl ExecutorAction {
         self
     }
 
+    /// Give each coding agent request in the chain the additions
+    /// `additions_for` returns for its executor, replacing earlier ones
+    pub fn with_prompt_additions(
+        mut self,
+        additions_for: &impl Fn(BaseCodingAgent) -> Option<PromptAdditions>,
+    ) -> Self {
+        match &mut self.typ {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                request.prompt_additions = additions_for(request.base_executor());
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                request.prompt_additions = additions_for(request.base_executor());
+            }
+            ExecutorActionType::ScriptRequest(_) => {}
+        }
+        self.next_action = self
+            .next_action
+            .map(|next| Box::new(next.with_prompt_additions(additions_for)));
+        self
+    }
+
     pub fn typ(&self) -> &ExecutorActionType {
         &self.typ
     }
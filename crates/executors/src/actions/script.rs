@@ -3,7 +3,6 @@ use std::{path::Path, sync::Arc};
 use async_trait::async_trait;
 use command_group::AsyncCommandGroup;
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::shell::get_shell_command;
 
@@ -52,19 +51,18 @@ impl Executable for ScriptRequest {
             None => current_dir.to_path_buf(),
         };
 
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let mut command = Command::new(shell_cmd);
+        // The host's login shell may not exist inside a container image
+        let (shell_cmd, shell_arg) = if env.container.is_some() {
+            ("sh".to_string(), "-c")
+        } else {
+            get_shell_command()
+        };
+        let mut command = env.command(shell_cmd, [shell_arg, self.script.as_str()], &effective_dir);
         command
             .kill_on_drop(true)
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .arg(shell_arg)
-            .arg(&self.script)
-            .current_dir(&effective_dir);
-
-        // Apply environment variables
-        env.apply_to_command(&mut command);
+            .stderr(std::process::Stdio::piped());
 
         let child = command.group_spawn()?;
 
@@ -54,3 +54,77 @@ impl ExecutorApprovalService for NoopExecutorApprovalService {
 pub struct ToolCallMetadata {
     pub tool_call_id: String,
 }
+
+/// The shell command an approval request is for, if it is for one. Claude
+/// Code asks for its Bash tool, Codex for exec calls with the command as an
+/// argument list, and ACP agents for tool calls of the execute kind.
+pub fn requested_command(tool_name: &str, tool_input: &Value) -> Option<String> {
+    if let Some(tool_call) = tool_input.get("tool_call") {
+        if tool_call.get("kind").and_then(Value::as_str) != Some("execute") {
+            return None;
+        }
+        return tool_call
+            .pointer("/rawInput/command")
+            .or_else(|| tool_call.get("title"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+    }
+    if !tool_name.eq_ignore_ascii_case("bash") {
+        return None;
+    }
+    match tool_input.get("command")? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(args) => {
+            let args: Vec<&str> = args.iter().filter_map(Value::as_str).collect();
+            match args.as_slice() {
+                // The script of a `bash -lc <script>` wrapper is the command
+                [shell, "-c" | "-lc", script]
+                    if shell.ends_with("sh") || shell.ends_with("sh.exe") =>
+                {
+                    Some(script.to_string())
+                }
+                _ => Some(args.join(" ")),
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn commands_are_found_in_each_executors_requests() {
+        assert_eq!(
+            requested_command("Bash", &json!({"command": "cargo test"})).as_deref(),
+            Some("cargo test")
+        );
+        assert_eq!(
+            requested_command(
+                "bash",
+                &json!({"callId": "1", "command": ["/bin/bash", "-lc", "git push"]})
+            )
+            .as_deref(),
+            Some("git push")
+        );
+        assert_eq!(
+            requested_command(
+                "Shell",
+                &json!({"tool_call": {"kind": "execute", "title": "ls", "rawInput": {"command": "ls -la"}}})
+            )
+            .as_deref(),
+            Some("ls -la")
+        );
+        assert_eq!(
+            requested_command("edit", &json!({"tool_call": {"kind": "edit"}})),
+            None
+        );
+        assert_eq!(
+            requested_command("Write", &json!({"file_path": "a.txt"})),
+            None
+        );
+    }
+}
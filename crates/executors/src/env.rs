@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
 
 use tokio::process::Command;
 
@@ -8,12 +12,108 @@ use crate::command::CmdOverrides;
 #[derive(Debug, Clone, Default)]
 pub struct ExecutionEnv {
     pub vars: HashMap<String, String>,
+    /// Run executor processes inside this container instead of on the host
+    pub container: Option<ContainerExec>,
+    /// cgroup (v2) directory that host processes join before they start, so
+    /// the attempt's resource limits apply to them and their children
+    pub cgroup: Option<PathBuf>,
+}
+
+/// A running container that has the workspace mounted at the same path as on
+/// the host, or a remote machine holding a copy of it.
+#[derive(Debug, Clone)]
+pub struct ContainerExec {
+    /// Container name, pod name or SSH destination
+    pub name: String,
+    pub user: Option<String>,
+    pub runtime: ContainerRuntime,
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum ContainerRuntime {
+    #[default]
+    Docker,
+    /// A pod reached through `kubectl exec`, which can't pass environment
+    /// variables. Variables named in `env_file_vars` have been written to
+    /// `env_file` in the pod and are sourced from there; any others are passed
+    /// on the command line.
+    Kubernetes {
+        namespace: String,
+        env_file: String,
+        env_file_vars: Vec<String>,
+    },
+    /// A remote machine reached with `ssh`, where the workspace at `local_dir`
+    /// is mirrored to `remote_dir`. Variables are provided the same way as for
+    /// Kubernetes.
+    Ssh {
+        local_dir: PathBuf,
+        remote_dir: String,
+        env_file: String,
+        env_file_vars: Vec<String>,
+    },
+}
+
+impl ContainerRuntime {
+    /// Where `dir` on the host is found by commands run in this runtime. Only
+    /// remote machines keep the workspace at a different path.
+    pub fn path_for(&self, dir: &Path) -> String {
+        match self {
+            ContainerRuntime::Ssh {
+                local_dir,
+                remote_dir,
+                ..
+            } => match dir.strip_prefix(local_dir) {
+                Ok(relative) if !relative.as_os_str().is_empty() => {
+                    format!("{remote_dir}/{}", relative.to_string_lossy())
+                }
+                _ => remote_dir.clone(),
+            },
+            _ => dir.to_string_lossy().into_owned(),
+        }
+    }
+}
+
+/// Have the child move itself into `cgroup` between fork and exec, so nothing
+/// it starts can escape the cgroup's limits
+#[cfg(target_os = "linux")]
+fn join_cgroup_before_exec(command: &mut Command, cgroup: &Path) {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let Ok(procs) = CString::new(cgroup.join("cgroup.procs").as_os_str().as_bytes()) else {
+        return;
+    };
+    // SAFETY: the hook only makes async-signal-safe calls and doesn't allocate
+    unsafe {
+        command.pre_exec(move || {
+            let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            // Writing 0 moves the writing process
+            let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+            let result = if written == 1 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            };
+            libc::close(fd);
+            result
+        });
+    }
+}
+
+fn quote(word: &str) -> String {
+    shlex::try_quote(word)
+        .map(|quoted| quoted.into_owned())
+        .unwrap_or_else(|_| "''".to_string())
 }
 
 impl ExecutionEnv {
     pub fn new() -> Self {
         Self {
             vars: HashMap::new(),
+            container: None,
+            cgroup: None,
         }
     }
 
@@ -50,6 +150,110 @@ impl ExecutionEnv {
         }
     }
 
+    /// Build a command that runs `program` with `args` in `current_dir` with these
+    /// variables. With a container set this becomes a `docker exec` (or the
+    /// runtime's equivalent), and the program is looked up by name on the
+    /// container's PATH.
+    pub fn command<I, S>(&self, program: impl AsRef<Path>, args: I, current_dir: &Path) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let program = program.as_ref();
+        let Some(container) = &self.container else {
+            let mut command = Command::new(program);
+            command.args(args).current_dir(current_dir);
+            self.apply_to_command(&mut command);
+            #[cfg(target_os = "linux")]
+            if let Some(cgroup) = &self.cgroup {
+                join_cgroup_before_exec(&mut command, cgroup);
+            }
+            return command;
+        };
+        let program = program.file_name().unwrap_or(program.as_os_str());
+
+        match &container.runtime {
+            ContainerRuntime::Docker => {
+                let mut command = Command::new("docker");
+                command.arg("exec").arg("-i").arg("-w").arg(current_dir);
+                if let Some(user) = &container.user {
+                    command.arg("-u").arg(user);
+                }
+                // Pass variables by name so their values never show up in the process list
+                for key in self.vars.keys() {
+                    command.arg("-e").arg(key);
+                }
+                self.apply_to_command(&mut command);
+                command.arg(&container.name).arg(program).args(args);
+                command
+            }
+            ContainerRuntime::Kubernetes {
+                namespace,
+                env_file,
+                env_file_vars,
+            } => {
+                let mut command = Command::new("kubectl");
+                command
+                    .args(["exec", "-i", "-n", namespace, &container.name, "--"])
+                    .arg("sh")
+                    .arg("-c")
+                    .arg(format!(
+                        "set -a; [ -f {env_file} ] && . {env_file}; set +a; cd \"$0\" && exec \"$@\""
+                    ))
+                    .arg(current_dir);
+                let extra = self.vars_outside(env_file_vars);
+                if !extra.is_empty() {
+                    command.arg("env").args(extra);
+                }
+                command.arg(program).args(args);
+                command
+            }
+            ContainerRuntime::Ssh {
+                env_file,
+                env_file_vars,
+                ..
+            } => {
+                // ssh hands the remote shell a single command line, so every
+                // part of it is quoted here
+                let remote_cwd = container.runtime.path_for(current_dir);
+                let mut line = format!(
+                    "set -a; [ -f {env_file} ] && . {env_file}; set +a; cd {} && exec env",
+                    quote(&remote_cwd),
+                    env_file = quote(env_file),
+                );
+                let words = self
+                    .vars_outside(env_file_vars)
+                    .into_iter()
+                    .chain(std::iter::once(program.to_string_lossy().into_owned()))
+                    .chain(
+                        args.into_iter()
+                            .map(|arg| arg.as_ref().to_string_lossy().into_owned()),
+                    );
+                for word in words {
+                    line.push(' ');
+                    line.push_str(&quote(&word));
+                }
+
+                let mut command = Command::new("ssh");
+                command
+                    .args(["-T", "-o", "BatchMode=yes"])
+                    .arg(&container.name)
+                    .arg("--")
+                    .arg(line);
+                command
+            }
+        }
+    }
+
+    /// `KEY=value` pairs for the variables not already provided by an env file
+    fn vars_outside(&self, env_file_vars: &[String]) -> Vec<String> {
+        self.vars
+            .iter()
+            .filter(|(key, _)| !env_file_vars.contains(key))
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect()
+    }
+
     pub fn contains_key(&self, key: &str) -> bool {
         self.vars.contains_key(key)
     }
@@ -75,4 +279,95 @@ mod tests {
         assert_eq!(merged.vars.get("FOO").unwrap(), "profile"); // overrides
         assert_eq!(merged.vars.get("BAR").unwrap(), "profile");
     }
+
+    #[test]
+    fn container_command_wraps_docker_exec() {
+        let mut env = ExecutionEnv::new();
+        env.insert("VK_TASK_ID", "123");
+        env.container = Some(ContainerExec {
+            name: "vk-abc".to_string(),
+            user: None,
+            runtime: ContainerRuntime::Docker,
+        });
+
+        let command = env.command("/usr/local/bin/claude", ["-p"], Path::new("/work/repo"));
+        let std = command.as_std();
+        let args: Vec<_> = std.get_args().map(|a| a.to_string_lossy()).collect();
+
+        assert_eq!(std.get_program(), "docker");
+        assert_eq!(
+            args,
+            [
+                "exec",
+                "-i",
+                "-w",
+                "/work/repo",
+                "-e",
+                "VK_TASK_ID",
+                "vk-abc",
+                "claude",
+                "-p"
+            ]
+        );
+    }
+
+    #[test]
+    fn kubernetes_command_sources_env_file() {
+        let mut env = ExecutionEnv::new();
+        env.insert("API_TOKEN", "secret");
+        env.insert("FOO", "bar");
+        env.container = Some(ContainerExec {
+            name: "vk-abc".to_string(),
+            user: None,
+            runtime: ContainerRuntime::Kubernetes {
+                namespace: "vk".to_string(),
+                env_file: "/vk/env.sh".to_string(),
+                env_file_vars: vec!["API_TOKEN".to_string()],
+            },
+        });
+
+        let command = env.command("/usr/local/bin/claude", ["-p"], Path::new("/work/repo"));
+        let std = command.as_std();
+        let args: Vec<_> = std.get_args().map(|a| a.to_string_lossy()).collect();
+
+        assert_eq!(std.get_program(), "kubectl");
+        assert_eq!(args[..6], ["exec", "-i", "-n", "vk", "vk-abc", "--"]);
+        assert_eq!(args[9..], ["/work/repo", "env", "FOO=bar", "claude", "-p"]);
+        assert!(!args.iter().any(|arg| arg.contains("secret")));
+    }
+
+    #[test]
+    fn ssh_command_quotes_remote_line() {
+        let mut env = ExecutionEnv::new();
+        env.insert("API_TOKEN", "secret");
+        env.container = Some(ContainerExec {
+            name: "dev@builder".to_string(),
+            user: None,
+            runtime: ContainerRuntime::Ssh {
+                local_dir: PathBuf::from("/work/repo"),
+                remote_dir: "/srv/vk/abc".to_string(),
+                env_file: "/srv/vk/abc.env".to_string(),
+                env_file_vars: vec!["API_TOKEN".to_string()],
+            },
+        });
+
+        let command = env.command(
+            "/usr/local/bin/claude",
+            ["-p", "fix the user's bug"],
+            Path::new("/work/repo/frontend"),
+        );
+        let std = command.as_std();
+        let args: Vec<_> = std.get_args().map(|a| a.to_string_lossy()).collect();
+
+        assert_eq!(std.get_program(), "ssh");
+        assert_eq!(
+            args[..5],
+            ["-T", "-o", "BatchMode=yes", "dev@builder", "--"]
+        );
+        let line = &args[5];
+        assert!(line.contains("cd /srv/vk/abc/frontend && exec env claude -p"));
+        assert!(!line.contains("secret"));
+        let words = shlex::split(line).unwrap();
+        assert_eq!(words.last().unwrap(), "fix the user's bug");
+    }
 }
@@ -9,15 +9,17 @@ use agent_client_protocol as proto;
 use agent_client_protocol::Agent as _;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
-use tokio::{io::AsyncWriteExt, process::Command, sync::mpsc};
+use tokio::{io::AsyncWriteExt, sync::mpsc};
 use tokio_util::{
     compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt},
     io::ReaderStream,
 };
 use tracing::error;
-use workspace_utils::{approvals::ApprovalStatus, stream_lines::LinesStreamExt};
+use workspace_utils::{
+    approvals::ApprovalStatus, log_msg::TokenUsage, stream_lines::LinesStreamExt,
+};
 
-use super::{AcpClient, SessionManager};
+use super::{AcpClient, SessionManager, prompt_usage};
 use crate::{
     approvals::ExecutorApprovalService,
     command::{CmdOverrides, CommandParts},
@@ -78,19 +80,15 @@ impl AcpAgentHarness {
         approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
     ) -> Result<SpawnedChild, ExecutorError> {
         let (program_path, args) = command_parts.into_resolved().await?;
-        let mut command = Command::new(program_path);
+        let mut env = env.clone();
+        env.insert("NODE_NO_WARNINGS", "1");
+        let env = env.with_profile(cmd_overrides);
+        let mut command = env.command(program_path, &args, current_dir);
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
-
-        env.clone()
-            .with_profile(cmd_overrides)
-            .apply_to_command(&mut command);
+            .stderr(Stdio::piped());
 
         let mut child = command.group_spawn()?;
 
@@ -112,6 +110,7 @@ impl AcpAgentHarness {
             child,
             exit_signal: Some(exit_rx),
             interrupt_sender: None,
+            interjection_sender: None,
         })
     }
 
@@ -127,19 +126,15 @@ impl AcpAgentHarness {
         approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
     ) -> Result<SpawnedChild, ExecutorError> {
         let (program_path, args) = command_parts.into_resolved().await?;
-        let mut command = Command::new(program_path);
+        let mut env = env.clone();
+        env.insert("NODE_NO_WARNINGS", "1");
+        let env = env.with_profile(cmd_overrides);
+        let mut command = env.command(program_path, &args, current_dir);
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
-
-        env.clone()
-            .with_profile(cmd_overrides)
-            .apply_to_command(&mut command);
+            .stderr(Stdio::piped());
 
         let mut child = command.group_spawn()?;
 
@@ -161,6 +156,7 @@ impl AcpAgentHarness {
             child,
             exit_signal: Some(exit_rx),
             interrupt_sender: None,
+            interjection_sender: None,
         })
     }
 
@@ -435,6 +431,8 @@ impl AcpAgentHarness {
                         );
 
                         let mut current_req = Some(initial_req);
+                        // Summed over the prompts of this run
+                        let mut usage: Option<TokenUsage> = None;
 
                         while let Some(req) = current_req.take() {
                             tracing::trace!(?req, "sending ACP prompt request");
@@ -444,6 +442,19 @@ impl AcpAgentHarness {
                                     // Emit done with stop_reason
                                     let stop_reason = serde_json::to_string(&resp.stop_reason)
                                         .unwrap_or_default();
+                                    if let Some(prompt) = serde_json::to_value(&resp)
+                                        .ok()
+                                        .as_ref()
+                                        .and_then(prompt_usage)
+                                    {
+                                        let total = usage.get_or_insert_with(Default::default);
+                                        total.input_tokens += prompt.input_tokens;
+                                        total.output_tokens += prompt.output_tokens;
+                                        total.cache_read_tokens += prompt.cache_read_tokens;
+                                        total.cache_write_tokens += prompt.cache_write_tokens;
+                                        let _ =
+                                            log_tx.send(AcpEvent::Usage(total.clone()).to_string());
+                                    }
                                     let _ = log_tx.send(AcpEvent::Done(stop_reason).to_string());
                                 }
                                 Err(e) => {
@@ -9,8 +9,9 @@ pub use client::AcpClient;
 pub use harness::AcpAgentHarness;
 pub use normalize_logs::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 pub use session::SessionManager;
-use workspace_utils::approvals::ApprovalStatus;
+use workspace_utils::{approvals::ApprovalStatus, log_msg::TokenUsage};
 
 /// Parsed event types for internal processing
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +29,8 @@ pub enum AcpEvent {
     ApprovalResponse(ApprovalResponse),
     Error(String),
     Done(String),
+    /// Tokens used by the session's prompts so far
+    Usage(TokenUsage),
     Other(agent_client_protocol::SessionNotification),
 }
 
@@ -50,3 +53,46 @@ pub struct ApprovalResponse {
     pub tool_call_id: String,
     pub status: ApprovalStatus,
 }
+
+/// Token usage of a prompt, for agents that put it in their prompt response,
+/// either as `usage` or under `_meta`
+pub fn prompt_usage(response: &Value) -> Option<TokenUsage> {
+    let usage = response
+        .get("usage")
+        .or_else(|| response.pointer("/_meta/usage"))?;
+    let count = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| usage.get(key).and_then(Value::as_u64))
+            .unwrap_or(0)
+    };
+    Some(TokenUsage {
+        input_tokens: count(&["inputTokens", "input_tokens"]),
+        // Reasoning is billed as output
+        output_tokens: count(&["outputTokens", "output_tokens"])
+            + count(&["thoughtTokens", "thought_tokens"]),
+        cache_read_tokens: count(&["cachedReadTokens", "cached_read_tokens"]),
+        cache_write_tokens: count(&["cachedWriteTokens", "cached_write_tokens"]),
+        cost_usd: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_usage_is_read_from_usage_or_meta() {
+        let response = serde_json::json!({
+            "stopReason": "end_turn",
+            "usage": {"inputTokens": 1200, "outputTokens": 300, "thoughtTokens": 50, "cachedReadTokens": 800}
+        });
+        let usage = prompt_usage(&response).unwrap();
+        assert_eq!(usage.input_tokens, 1200);
+        assert_eq!(usage.output_tokens, 350);
+        assert_eq!(usage.cache_read_tokens, 800);
+
+        let response = serde_json::json!({"_meta": {"usage": {"input_tokens": 10}}});
+        assert_eq!(prompt_usage(&response).unwrap().input_tokens, 10);
+        assert!(prompt_usage(&serde_json::json!({"stopReason": "end_turn"})).is_none());
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    fs,
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::oneshot,
+};
+use ts_rs::TS;
+use uuid::Uuid;
+use workspace_utils::{
+    msg_store::MsgStore, path::get_vibe_kanban_temp_dir, shell::get_shell_command,
+};
+
+use crate::{
+    command::CmdOverrides,
+    env::ExecutionEnv,
+    executors::{
+        AppendPrompt, AvailabilityInfo, ExecutorError, ExecutorExitResult, SpawnedChild,
+        StandardCodingAgentExecutor,
+    },
+    logs::utils::EntryIndexProvider,
+    stdout_dup::create_stdout_pipe_writer,
+};
+
+pub mod chat;
+pub mod normalize_logs;
+pub mod tools;
+
+use chat::{ChatClient, ChatMessage};
+use normalize_logs::{ApiAgentEvent, normalize_logs};
+use tools::Toolbox;
+
+/// Ollama's OpenAI-compatible endpoint
+const DEFAULT_BASE_URL: &str = "http://localhost:11434/v1";
+const DEFAULT_MAX_TURNS: u32 = 50;
+
+/// Built-in agent for OpenAI-compatible chat completion APIs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct ApiAgent {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Base URL",
+        description = "Base URL of the API, including the version path (default: http://localhost:11434/v1, Ollama; vLLM serves http://localhost:8000/v1)"
+    )]
+    pub base_url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Model",
+        description = "Model name as the server knows it (e.g., qwen3-coder, Qwen/Qwen3-Coder-30B-A3B-Instruct)"
+    )]
+    pub model: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "API Key Variable",
+        description = "Environment variable holding the API key, sent as a bearer token; local servers usually need none"
+    )]
+    pub api_key_env: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Max Turns",
+        description = "Model replies allowed before the run is stopped (default: 50)"
+    )]
+    pub max_turns: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(title = "Temperature", description = "Sampling temperature")]
+    pub temperature: Option<f32>,
+
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl ApiAgent {
+    /// Where the conversation of a session is kept between runs
+    fn session_file(session_id: &str) -> PathBuf {
+        get_vibe_kanban_temp_dir()
+            .join("api_agent_sessions")
+            .join(format!("{session_id}.json"))
+    }
+
+    fn system_prompt(current_dir: &Path) -> String {
+        format!(
+            "You are a coding agent working in the repository at {}. Use the tools to \
+             read and change files and to run commands such as builds and tests; paths \
+             are relative to the repository root. Work until the task is done, then \
+             reply with a short summary of what you changed and call no more tools.",
+            current_dir.display()
+        )
+    }
+
+    /// The conversation runs in this process, but executions are tracked as
+    /// child processes. The child is a shell waiting on stdin, whose stdout
+    /// is replaced with a pipe the tool loop writes its events to. It exits
+    /// when the loop drops its stdin, or is killed once the loop reports the
+    /// run's result.
+    async fn spawn_agent(
+        &self,
+        current_dir: &Path,
+        session_id: String,
+        messages: Vec<ChatMessage>,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let env = env.clone().with_profile(&self.cmd);
+        let (shell, shell_arg) = get_shell_command();
+        let wait_for_stdin = if cfg!(windows) { "set /p _=" } else { "read _" };
+        let mut command = env.command(shell, [shell_arg, wait_for_stdin], current_dir);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command.group_spawn()?;
+        let child_stdin = child.inner().stdin.take().ok_or_else(|| {
+            ExecutorError::Io(std::io::Error::other("API agent host missing stdin"))
+        })?;
+        let log_writer = create_stdout_pipe_writer(&mut child)?;
+
+        let (exit_tx, exit_rx) = oneshot::channel::<ExecutorExitResult>();
+        let (interrupt_tx, interrupt_rx) = oneshot::channel::<()>();
+
+        let api_key = self.api_key_env.as_deref().and_then(|name| {
+            env.vars
+                .get(name)
+                .cloned()
+                .or_else(|| std::env::var(name).ok())
+        });
+        let run = AgentRun {
+            base_url: self
+                .base_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            model: self.model.clone(),
+            api_key,
+            temperature: self.temperature,
+            max_turns: self.max_turns.unwrap_or(DEFAULT_MAX_TURNS),
+            toolbox: Toolbox::new(current_dir.to_path_buf(), env),
+            session_id,
+            messages,
+            events: EventLog::new(log_writer),
+        };
+        tokio::spawn(async move {
+            let result = run.run(interrupt_rx).await;
+            let _ = exit_tx.send(result);
+            drop(child_stdin);
+        });
+
+        Ok(SpawnedChild {
+            child,
+            exit_signal: Some(exit_rx),
+            interrupt_sender: Some(interrupt_tx),
+            interjection_sender: None,
+        })
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for ApiAgent {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let messages = vec![
+            ChatMessage::System {
+                content: Self::system_prompt(current_dir),
+            },
+            ChatMessage::User {
+                content: self.append_prompt.combine_prompt(prompt),
+            },
+        ];
+        self.spawn_agent(current_dir, Uuid::new_v4().to_string(), messages, env)
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let saved = fs::read_to_string(Self::session_file(session_id))
+            .await
+            .map_err(|_| {
+                ExecutorError::FollowUpNotSupported(format!(
+                    "the conversation of session {session_id} is no longer available"
+                ))
+            })?;
+        let mut messages: Vec<ChatMessage> = serde_json::from_str(&saved)?;
+        messages.push(ChatMessage::User {
+            content: self.append_prompt.combine_prompt(prompt),
+        });
+        self.spawn_agent(current_dir, session_id.to_string(), messages, env)
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
+        normalize_logs(
+            msg_store.clone(),
+            current_dir,
+            EntryIndexProvider::start_from(&msg_store),
+        );
+    }
+
+    fn default_mcp_config_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Needs nothing installed; whether the server is up shows on the first run
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        AvailabilityInfo::InstallationFound
+    }
+}
+
+/// Writes the loop's events to the host process's stdout, one per line
+struct EventLog {
+    writer: Box<dyn AsyncWrite + Send + Unpin>,
+}
+
+impl EventLog {
+    fn new(writer: impl AsyncWrite + Send + Unpin + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    async fn emit(&mut self, event: ApiAgentEvent) {
+        let Ok(mut line) = serde_json::to_string(&event) else {
+            return;
+        };
+        line.push('\n');
+        if let Err(e) = self.writer.write_all(line.as_bytes()).await {
+            tracing::warn!("Failed to write API agent event: {e}");
+            return;
+        }
+        let _ = self.writer.flush().await;
+    }
+}
+
+struct AgentRun {
+    base_url: String,
+    model: Option<String>,
+    api_key: Option<String>,
+    temperature: Option<f32>,
+    max_turns: u32,
+    toolbox: Toolbox,
+    session_id: String,
+    messages: Vec<ChatMessage>,
+    events: EventLog,
+}
+
+impl AgentRun {
+    async fn run(mut self, interrupt_rx: oneshot::Receiver<()>) -> ExecutorExitResult {
+        self.events
+            .emit(ApiAgentEvent::Session {
+                session_id: self.session_id.clone(),
+            })
+            .await;
+
+        let result = tokio::select! {
+            result = self.converse() => result,
+            _ = interrupt_rx => Err("Interrupted".to_string()),
+        };
+        // Kept even after a failure, so a follow-up can pick up from there
+        self.save().await;
+
+        match result {
+            Ok(()) => ExecutorExitResult::Success,
+            Err(message) => {
+                self.events.emit(ApiAgentEvent::Error { message }).await;
+                ExecutorExitResult::Failure
+            }
+        }
+    }
+
+    /// Ask the model for its next step and carry out the tools it calls,
+    /// until it answers without calling any
+    async fn converse(&mut self) -> Result<(), String> {
+        let Some(model) = self.model.clone() else {
+            return Err("Set a model in the API agent's configuration".to_string());
+        };
+        let client = ChatClient::new(
+            &self.base_url,
+            self.api_key.clone(),
+            model,
+            self.temperature,
+        )
+        .map_err(|e| e.to_string())?;
+        let definitions: Vec<Value> = tools::definitions();
+
+        for turn in 0..self.max_turns {
+            let reply = client
+                .complete(&self.messages, &definitions)
+                .await
+                .map_err(|e| format!("{}: {e}", self.base_url))?;
+
+            if let Some(reasoning) = reply.reasoning {
+                self.events
+                    .emit(ApiAgentEvent::Thinking { content: reasoning })
+                    .await;
+            }
+            if let Some(content) = &reply.content {
+                self.events
+                    .emit(ApiAgentEvent::Message {
+                        content: content.clone(),
+                    })
+                    .await;
+            }
+
+            let mut tool_calls = reply.tool_calls;
+            for (index, call) in tool_calls.iter_mut().enumerate() {
+                if call.id.is_empty() {
+                    call.id = format!("call_{turn}_{index}");
+                }
+            }
+            self.messages.push(ChatMessage::Assistant {
+                content: reply.content,
+                tool_calls: tool_calls.clone(),
+            });
+            if tool_calls.is_empty() {
+                return Ok(());
+            }
+
+            for call in tool_calls {
+                self.events
+                    .emit(ApiAgentEvent::ToolCall {
+                        id: call.id.clone(),
+                        name: call.function.name.clone(),
+                        arguments: call.function.arguments.clone(),
+                    })
+                    .await;
+                let output = self
+                    .toolbox
+                    .run(&call.function.name, &call.function.arguments)
+                    .await;
+                self.events
+                    .emit(ApiAgentEvent::ToolResult {
+                        id: call.id.clone(),
+                        output: output.output.clone(),
+                        success: output.success,
+                        exit_code: output.exit_code,
+                    })
+                    .await;
+                self.messages.push(ChatMessage::Tool {
+                    tool_call_id: call.id,
+                    content: output.output,
+                });
+            }
+            self.save().await;
+        }
+
+        Err(format!(
+            "Stopped after {} turns without the model finishing",
+            self.max_turns
+        ))
+    }
+
+    async fn save(&self) {
+        let path = ApiAgent::session_file(&self.session_id);
+        let saved = async {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            let json = serde_json::to_string(&self.messages).map_err(std::io::Error::other)?;
+            fs::write(&path, json).await
+        };
+        if let Err(e) = saved.await {
+            tracing::warn!("Failed to save API agent session {}: {e}", self.session_id);
+        }
+    }
+}
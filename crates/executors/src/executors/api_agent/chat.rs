@@ -0,0 +1,185 @@
+//! Minimal client for the chat completions endpoint of OpenAI-compatible
+//! servers (OpenAI, Ollama, vLLM, LM Studio, llama.cpp, ...).
+
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// Local models can take minutes to answer on modest hardware
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// One message of the conversation, as sent to and kept for the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "lowercase")]
+pub enum ChatMessage {
+    System {
+        content: String,
+    },
+    User {
+        content: String,
+    },
+    Assistant {
+        #[serde(default)]
+        content: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tool_calls: Vec<ToolCall>,
+    },
+    Tool {
+        tool_call_id: String,
+        content: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Some servers leave it out; filled in before the call is answered
+    #[serde(default)]
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON text of the arguments. Ollama sends an object rather than a string.
+    #[serde(default, deserialize_with = "arguments_as_text")]
+    pub arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+fn arguments_as_text<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(text) => text,
+        Value::Null => "{}".to_string(),
+        other => other.to_string(),
+    })
+}
+
+/// What the model answered with
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub content: Option<String>,
+    /// Reasoning some servers return next to the answer (vLLM, DeepSeek)
+    pub reasoning: Option<String>,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Debug, Error)]
+pub enum ChatError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("server answered {status}: {body}")]
+    Status {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+    #[error("response had no choices")]
+    NoChoices,
+}
+
+#[derive(Serialize)]
+struct CompletionRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    #[serde(skip_serializing_if = "<[Value]>::is_empty")]
+    tools: &'a [Value],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct CompletionResponse {
+    #[serde(default)]
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ResponseMessage {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default, alias = "reasoning")]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCall>,
+}
+
+pub struct ChatClient {
+    http: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+    temperature: Option<f32>,
+}
+
+impl ChatClient {
+    pub fn new(
+        base_url: &str,
+        api_key: Option<String>,
+        model: String,
+        temperature: Option<f32>,
+    ) -> Result<Self, ChatError> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            http,
+            url: format!("{}/chat/completions", base_url.trim_end_matches('/')),
+            api_key,
+            model,
+            temperature,
+        })
+    }
+
+    pub async fn complete(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[Value],
+    ) -> Result<Reply, ChatError> {
+        let mut request = self.http.post(&self.url).json(&CompletionRequest {
+            model: &self.model,
+            messages,
+            tools,
+            temperature: self.temperature,
+        });
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ChatError::Status { status, body });
+        }
+
+        let completion: CompletionResponse = response.json().await?;
+        let message = completion
+            .choices
+            .into_iter()
+            .next()
+            .ok_or(ChatError::NoChoices)?
+            .message;
+        Ok(Reply {
+            content: message.content.filter(|text| !text.trim().is_empty()),
+            reasoning: message
+                .reasoning_content
+                .filter(|text| !text.trim().is_empty()),
+            tool_calls: message.tool_calls,
+        })
+    }
+}
@@ -0,0 +1,295 @@
+//! The events the API agent's tool loop prints, one JSON object per stdout
+//! line, and their translation into normalized log entries.
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use futures::{StreamExt, future::ready};
+use serde::{Deserialize, Serialize};
+use workspace_utils::{diff::create_unified_diff, msg_store::MsgStore, path::make_path_relative};
+
+use super::tools::{EDIT_FILE, EditFileArgs, READ_FILE, RUN_COMMAND, ReadFileArgs, RunCommandArgs};
+use crate::logs::{
+    ActionType, CommandExitStatus, CommandRunResult, FileChange, NormalizedEntry,
+    NormalizedEntryError, NormalizedEntryType, ToolResult, ToolStatus,
+    stderr_processor::normalize_stderr_logs,
+    utils::{
+        EntryIndexProvider,
+        patch::{add_normalized_entry, replace_normalized_entry},
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiAgentEvent {
+    /// First event of every run
+    Session {
+        session_id: String,
+    },
+    Thinking {
+        content: String,
+    },
+    Message {
+        content: String,
+    },
+    ToolCall {
+        id: String,
+        name: String,
+        /// JSON text, as the model wrote it
+        arguments: String,
+    },
+    ToolResult {
+        id: String,
+        output: String,
+        success: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+pub fn normalize_logs(
+    msg_store: Arc<MsgStore>,
+    worktree_path: &Path,
+    entry_index_provider: EntryIndexProvider,
+) {
+    normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+    let worktree_path = worktree_path.to_string_lossy().to_string();
+    tokio::spawn(async move {
+        // Tool use entries waiting for their result, by tool call id
+        let mut pending: HashMap<String, (usize, NormalizedEntry)> = HashMap::new();
+
+        let add = |entry_type: NormalizedEntryType, content: String| {
+            add_normalized_entry(
+                &msg_store,
+                &entry_index_provider,
+                NormalizedEntry {
+                    timestamp: None,
+                    entry_type,
+                    content,
+                    metadata: None,
+                },
+            )
+        };
+
+        let mut lines_stream = msg_store
+            .stdout_lines_stream()
+            .filter_map(|res| ready(res.ok()));
+
+        while let Some(line) = lines_stream.next().await {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let Ok(event) = serde_json::from_str::<ApiAgentEvent>(trimmed) else {
+                add(NormalizedEntryType::SystemMessage, trimmed.to_string());
+                continue;
+            };
+
+            match event {
+                ApiAgentEvent::Session { session_id } => msg_store.push_session_id(session_id),
+                ApiAgentEvent::Thinking { content } => {
+                    add(NormalizedEntryType::Thinking, content);
+                }
+                ApiAgentEvent::Message { content } => {
+                    add(NormalizedEntryType::AssistantMessage, content);
+                }
+                ApiAgentEvent::ToolCall {
+                    id,
+                    name,
+                    arguments,
+                } => {
+                    let (action_type, content) = tool_action(&name, &arguments, &worktree_path);
+                    let entry = NormalizedEntry {
+                        timestamp: None,
+                        entry_type: NormalizedEntryType::ToolUse {
+                            tool_name: name,
+                            action_type,
+                            status: ToolStatus::Created,
+                        },
+                        content,
+                        metadata: None,
+                    };
+                    let index =
+                        add_normalized_entry(&msg_store, &entry_index_provider, entry.clone());
+                    pending.insert(id, (index, entry));
+                }
+                ApiAgentEvent::ToolResult {
+                    id,
+                    output,
+                    success,
+                    exit_code,
+                } => {
+                    if let Some((index, entry)) = pending.remove(&id) {
+                        replace_normalized_entry(
+                            &msg_store,
+                            index,
+                            finished_entry(entry, output, success, exit_code),
+                        );
+                    }
+                }
+                ApiAgentEvent::Error { message } => {
+                    add(
+                        NormalizedEntryType::ErrorMessage {
+                            error_type: NormalizedEntryError::Other,
+                        },
+                        message,
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// What a tool call does and the entry text describing it
+fn tool_action(name: &str, arguments: &str, worktree_path: &str) -> (ActionType, String) {
+    match name {
+        READ_FILE => {
+            if let Ok(args) = serde_json::from_str::<ReadFileArgs>(arguments) {
+                let path = make_path_relative(&args.path, worktree_path);
+                return (ActionType::FileRead { path: path.clone() }, path);
+            }
+        }
+        EDIT_FILE => {
+            if let Ok(args) = serde_json::from_str::<EditFileArgs>(arguments) {
+                let path = make_path_relative(&args.path, worktree_path);
+                let change = match args.old_text.filter(|text| !text.is_empty()) {
+                    Some(old_text) => FileChange::Edit {
+                        unified_diff: create_unified_diff(&path, &old_text, &args.new_text),
+                        has_line_numbers: false,
+                    },
+                    None => FileChange::Write {
+                        content: args.new_text,
+                    },
+                };
+                return (
+                    ActionType::FileEdit {
+                        path: path.clone(),
+                        changes: vec![change],
+                    },
+                    path,
+                );
+            }
+        }
+        RUN_COMMAND => {
+            if let Ok(args) = serde_json::from_str::<RunCommandArgs>(arguments) {
+                return (
+                    ActionType::CommandRun {
+                        command: args.command.clone(),
+                        result: None,
+                    },
+                    args.command,
+                );
+            }
+        }
+        _ => {}
+    }
+    (
+        ActionType::Tool {
+            tool_name: name.to_string(),
+            arguments: serde_json::from_str(arguments).ok(),
+            result: None,
+        },
+        name.to_string(),
+    )
+}
+
+/// The tool use entry once its result came back
+fn finished_entry(
+    entry: NormalizedEntry,
+    output: String,
+    success: bool,
+    exit_code: Option<i32>,
+) -> NormalizedEntry {
+    let NormalizedEntryType::ToolUse {
+        tool_name,
+        action_type,
+        ..
+    } = entry.entry_type
+    else {
+        return entry;
+    };
+    let action_type = match action_type {
+        ActionType::CommandRun { command, .. } => ActionType::CommandRun {
+            command,
+            result: Some(CommandRunResult {
+                exit_status: exit_code.map(|code| CommandExitStatus::ExitCode { code }),
+                output: Some(output),
+            }),
+        },
+        ActionType::Tool {
+            tool_name,
+            arguments,
+            ..
+        } => ActionType::Tool {
+            tool_name,
+            arguments,
+            result: Some(ToolResult::markdown(output)),
+        },
+        other => other,
+    };
+    NormalizedEntry {
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            status: if success {
+                ToolStatus::Success
+            } else {
+                ToolStatus::Failed
+            },
+        },
+        ..entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_calls_map_to_actions() {
+        let (action, content) = tool_action(
+            EDIT_FILE,
+            r#"{"path": "/work/repo/src/main.rs", "new_text": "fn main() {}"}"#,
+            "/work/repo",
+        );
+        assert_eq!(content, "src/main.rs");
+        assert!(matches!(
+            action,
+            ActionType::FileEdit { ref changes, .. }
+                if matches!(changes[0], FileChange::Write { .. })
+        ));
+
+        let (action, _) = tool_action(RUN_COMMAND, r#"{"command": "cargo test"}"#, "/work/repo");
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: RUN_COMMAND.to_string(),
+                action_type: action,
+                status: ToolStatus::Created,
+            },
+            content: "cargo test".to_string(),
+            metadata: None,
+        };
+        let finished = finished_entry(entry, "1 failed".to_string(), false, Some(101));
+        let NormalizedEntryType::ToolUse {
+            action_type: ActionType::CommandRun { result, .. },
+            status,
+            ..
+        } = finished.entry_type
+        else {
+            panic!("expected a command run");
+        };
+        assert!(matches!(status, ToolStatus::Failed));
+        assert!(matches!(
+            result.and_then(|result| result.exit_status),
+            Some(CommandExitStatus::ExitCode { code: 101 })
+        ));
+
+        let (action, content) = tool_action("list_files", "{}", "/work/repo");
+        assert_eq!(content, "list_files");
+        assert!(matches!(action, ActionType::Tool { .. }));
+    }
+}
@@ -0,0 +1,328 @@
+//! The tools the model is offered: reading and editing files in the working
+//! directory and running shell commands there.
+//!
+//! Files are read and written directly; commands go through the execution
+//! environment, so they run in the attempt's container or cgroup if it has one.
+
+use std::{
+    path::{Component, Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::fs;
+use workspace_utils::shell::get_shell_command;
+
+use crate::env::ExecutionEnv;
+
+pub const READ_FILE: &str = "read_file";
+pub const EDIT_FILE: &str = "edit_file";
+pub const RUN_COMMAND: &str = "run_command";
+
+/// Tool output longer than this is cut before it goes back to the model
+const MAX_OUTPUT_CHARS: usize = 30_000;
+const DEFAULT_READ_LINES: usize = 1_000;
+const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 120;
+
+/// Function definitions in the chat completions `tools` format
+pub fn definitions() -> Vec<Value> {
+    vec![
+        json!({
+            "type": "function",
+            "function": {
+                "name": READ_FILE,
+                "description": "Read a text file, returned with line numbers.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path relative to the working directory"},
+                        "offset": {"type": "integer", "description": "First line to read, starting at 1"},
+                        "limit": {"type": "integer", "description": "Number of lines to read"}
+                    },
+                    "required": ["path"]
+                }
+            }
+        }),
+        json!({
+            "type": "function",
+            "function": {
+                "name": EDIT_FILE,
+                "description": "Replace the one place in a file matching old_text with new_text. Without old_text, the file is created or overwritten with new_text.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path relative to the working directory"},
+                        "old_text": {"type": "string", "description": "Exact text to replace, with enough context to match only once"},
+                        "new_text": {"type": "string", "description": "Text to put in its place"}
+                    },
+                    "required": ["path", "new_text"]
+                }
+            }
+        }),
+        json!({
+            "type": "function",
+            "function": {
+                "name": RUN_COMMAND,
+                "description": "Run a shell command in the working directory and return its exit code and combined output.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "command": {"type": "string"},
+                        "timeout_secs": {"type": "integer", "description": "Defaults to 120"}
+                    },
+                    "required": ["command"]
+                }
+            }
+        }),
+    ]
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadFileArgs {
+    pub path: String,
+    #[serde(default)]
+    pub offset: Option<usize>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditFileArgs {
+    pub path: String,
+    #[serde(default)]
+    pub old_text: Option<String>,
+    pub new_text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunCommandArgs {
+    pub command: String,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Result of a tool call, sent back to the model as the tool message
+#[derive(Debug, Clone)]
+pub struct ToolOutput {
+    pub output: String,
+    pub success: bool,
+    /// Set for commands that ran to completion
+    pub exit_code: Option<i32>,
+}
+
+impl ToolOutput {
+    fn ok(output: String) -> Self {
+        Self {
+            output,
+            success: true,
+            exit_code: None,
+        }
+    }
+
+    fn failed(output: String) -> Self {
+        Self {
+            output,
+            success: false,
+            exit_code: None,
+        }
+    }
+}
+
+pub struct Toolbox {
+    worktree: PathBuf,
+    env: ExecutionEnv,
+}
+
+impl Toolbox {
+    pub fn new(worktree: PathBuf, env: ExecutionEnv) -> Self {
+        Self { worktree, env }
+    }
+
+    pub async fn run(&self, name: &str, arguments: &str) -> ToolOutput {
+        let arguments = if arguments.trim().is_empty() {
+            "{}"
+        } else {
+            arguments
+        };
+        let output = match name {
+            READ_FILE => match serde_json::from_str(arguments) {
+                Ok(args) => self.read_file(args).await,
+                Err(e) => Err(format!("Invalid arguments for {name}: {e}")),
+            },
+            EDIT_FILE => match serde_json::from_str(arguments) {
+                Ok(args) => self.edit_file(args).await,
+                Err(e) => Err(format!("Invalid arguments for {name}: {e}")),
+            },
+            RUN_COMMAND => match serde_json::from_str(arguments) {
+                Ok(args) => return self.run_command(args).await,
+                Err(e) => Err(format!("Invalid arguments for {name}: {e}")),
+            },
+            _ => Err(format!(
+                "Unknown tool {name}; use {READ_FILE}, {EDIT_FILE} or {RUN_COMMAND}"
+            )),
+        };
+        match output {
+            Ok(output) => ToolOutput::ok(truncate(output)),
+            Err(error) => ToolOutput::failed(error),
+        }
+    }
+
+    async fn read_file(&self, args: ReadFileArgs) -> Result<String, String> {
+        let path = resolve(&self.worktree, &args.path)?;
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("Can't read {}: {e}", args.path))?;
+
+        let offset = args.offset.unwrap_or(1).max(1);
+        let limit = args.limit.unwrap_or(DEFAULT_READ_LINES);
+        let numbered: Vec<String> = content
+            .lines()
+            .enumerate()
+            .skip(offset - 1)
+            .take(limit)
+            .map(|(index, line)| format!("{:>6}\t{line}", index + 1))
+            .collect();
+        if numbered.is_empty() {
+            return Ok(format!("{} has no lines from {offset} on", args.path));
+        }
+        Ok(numbered.join("\n"))
+    }
+
+    async fn edit_file(&self, args: EditFileArgs) -> Result<String, String> {
+        let path = resolve(&self.worktree, &args.path)?;
+        let old_text = args.old_text.filter(|text| !text.is_empty());
+
+        let Some(old_text) = old_text else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| format!("Can't create {}: {e}", parent.display()))?;
+            }
+            fs::write(&path, &args.new_text)
+                .await
+                .map_err(|e| format!("Can't write {}: {e}", args.path))?;
+            return Ok(format!("Wrote {}", args.path));
+        };
+
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("Can't read {}: {e}", args.path))?;
+        let updated = replace_once(&content, &old_text, &args.new_text)
+            .map_err(|e| format!("{e} in {}", args.path))?;
+        fs::write(&path, updated)
+            .await
+            .map_err(|e| format!("Can't write {}: {e}", args.path))?;
+        Ok(format!("Edited {}", args.path))
+    }
+
+    async fn run_command(&self, args: RunCommandArgs) -> ToolOutput {
+        let (shell, shell_arg) = get_shell_command();
+        let mut command =
+            self.env
+                .command(shell, [shell_arg, args.command.as_str()], &self.worktree);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let timeout =
+            Duration::from_secs(args.timeout_secs.unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS));
+        let output = match tokio::time::timeout(timeout, command.output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return ToolOutput::failed(format!("Can't run the command: {e}")),
+            Err(_) => {
+                return ToolOutput::failed(format!(
+                    "The command was stopped after {} seconds",
+                    timeout.as_secs()
+                ));
+            }
+        };
+
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.trim().is_empty() {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&stderr);
+        }
+        let exit_code = output.status.code();
+        ToolOutput {
+            output: truncate(text),
+            success: output.status.success(),
+            exit_code,
+        }
+    }
+}
+
+/// `path` within the working directory, refusing paths that lead out of it
+fn resolve(worktree: &Path, path: &str) -> Result<PathBuf, String> {
+    let mut resolved = PathBuf::new();
+    for component in worktree.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+    if resolved.starts_with(worktree) {
+        Ok(resolved)
+    } else {
+        Err(format!("{path} is outside the working directory"))
+    }
+}
+
+/// `content` with its single occurrence of `old` replaced by `new`
+fn replace_once(content: &str, old: &str, new: &str) -> Result<String, String> {
+    match content.matches(old).count() {
+        0 => Err("old_text was not found".to_string()),
+        1 => Ok(content.replacen(old, new, 1)),
+        count => Err(format!(
+            "old_text matches {count} places; include more surrounding lines"
+        )),
+    }
+}
+
+fn truncate(mut text: String) -> String {
+    if let Some((cut, _)) = text.char_indices().nth(MAX_OUTPUT_CHARS) {
+        text.truncate(cut);
+        text.push_str("\n[output truncated]");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_stay_in_the_working_directory() {
+        let worktree = Path::new("/work/repo");
+        assert_eq!(
+            resolve(worktree, "src/./lib.rs").unwrap(),
+            PathBuf::from("/work/repo/src/lib.rs")
+        );
+        assert_eq!(
+            resolve(worktree, "/work/repo/a/../b.rs").unwrap(),
+            PathBuf::from("/work/repo/b.rs")
+        );
+        assert!(resolve(worktree, "../other/file").is_err());
+        assert!(resolve(worktree, "/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn edits_need_a_single_match() {
+        let content = "let a = 1;\nlet b = 1;\n";
+        assert_eq!(
+            replace_once(content, "a = 1", "a = 2").unwrap(),
+            "let a = 2;\nlet b = 1;\n"
+        );
+        assert!(replace_once(content, "= 1", "= 2").is_err());
+        assert!(replace_once(content, "c = 1", "c = 2").is_err());
+    }
+}
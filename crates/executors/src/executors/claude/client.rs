@@ -179,6 +179,16 @@ impl ClaudeAgentClient {
         }
     }
 
+    /// Note an instruction sent to the running session in the logs, where
+    /// the stdin it went to doesn't show up
+    pub async fn on_interjection(&self, content: String) -> Result<(), ExecutorError> {
+        self.log_writer
+            .log_raw(&serde_json::to_string(&ClaudeJson::Interjection {
+                content,
+            })?)
+            .await
+    }
+
     pub async fn on_non_control(&self, line: &str) -> Result<(), ExecutorError> {
         // Forward all non-control messages to stdout
         self.log_writer.log_raw(line).await
@@ -1,10 +1,11 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use futures::FutureExt;
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
     process::{ChildStdin, ChildStdout},
-    sync::{Mutex, oneshot},
+    sync::{Mutex, mpsc, oneshot},
+    time::Instant,
 };
 
 use super::types::{CLIMessage, ControlRequestType, ControlResponseMessage, ControlResponseType};
@@ -16,10 +17,14 @@ use crate::executors::{
     },
 };
 
+/// How long Claude may stay quiet after finishing a turn, when instructions
+/// sent during it may still get a turn of their own, before the session ends
+const NEXT_TURN_GRACE: Duration = Duration::from_secs(5);
+
 /// Handles bidirectional control protocol communication
 #[derive(Clone)]
 pub struct ProtocolPeer {
-    stdin: Arc<Mutex<ChildStdin>>,
+    stdin: Arc<Mutex<Box<dyn AsyncWrite + Send + Unpin>>>,
 }
 
 impl ProtocolPeer {
@@ -28,14 +33,16 @@ impl ProtocolPeer {
         stdout: ChildStdout,
         client: Arc<ClaudeAgentClient>,
         interrupt_rx: oneshot::Receiver<()>,
+        interjection_rx: mpsc::UnboundedReceiver<String>,
     ) -> Self {
-        let peer = Self {
-            stdin: Arc::new(Mutex::new(stdin)),
-        };
+        let peer = Self::new(stdin);
 
         let reader_peer = peer.clone();
         tokio::spawn(async move {
-            if let Err(e) = reader_peer.read_loop(stdout, client, interrupt_rx).await {
+            if let Err(e) = reader_peer
+                .read_loop(stdout, client, interrupt_rx, interjection_rx)
+                .await
+            {
                 tracing::error!("Protocol reader loop error: {}", e);
             }
         });
@@ -43,19 +50,31 @@ impl ProtocolPeer {
         peer
     }
 
+    fn new(stdin: impl AsyncWrite + Send + Unpin + 'static) -> Self {
+        Self {
+            stdin: Arc::new(Mutex::new(Box::new(stdin))),
+        }
+    }
+
     async fn read_loop(
         &self,
-        stdout: ChildStdout,
+        stdout: impl AsyncRead + Unpin,
         client: Arc<ClaudeAgentClient>,
         interrupt_rx: oneshot::Receiver<()>,
+        mut interjection_rx: mpsc::UnboundedReceiver<String>,
     ) -> Result<(), ExecutorError> {
         let mut reader = BufReader::new(stdout);
         let mut buffer = String::new();
         // Fuse the receiver so it returns Pending forever after completing
         let mut interrupt_rx = interrupt_rx.fuse();
+        // Claude may answer instructions sent during a turn in that turn or
+        // in one after it, so the session only ends once it stays quiet
+        let mut interjections_in_turn = 0usize;
+        let mut next_turn_deadline: Option<Instant> = None;
 
         loop {
             buffer.clear();
+            let deadline = next_turn_deadline;
             tokio::select! {
                 line_result = reader.read_line(&mut buffer) => {
                     match line_result {
@@ -65,6 +84,7 @@ impl ProtocolPeer {
                             if line.is_empty() {
                                 continue;
                             }
+                            next_turn_deadline = None;
                             // Parse message using typed enum
                             match serde_json::from_str::<CLIMessage>(line) {
                                 Ok(CLIMessage::ControlRequest {
@@ -77,7 +97,11 @@ impl ProtocolPeer {
                                 Ok(CLIMessage::ControlResponse { .. }) => {}
                                 Ok(CLIMessage::Result(_)) => {
                                     client.on_non_control(line).await?;
-                                    break;
+                                    if interjections_in_turn == 0 {
+                                        break;
+                                    }
+                                    interjections_in_turn = 0;
+                                    next_turn_deadline = Some(Instant::now() + NEXT_TURN_GRACE);
                                 }
                                 _ => {
                                     client.on_non_control(line).await?;
@@ -95,6 +119,17 @@ impl ProtocolPeer {
                         tracing::debug!("Failed to send interrupt to Claude: {e}");
                     }
                 }
+                Some(content) = interjection_rx.recv() => {
+                    if let Err(e) = self.send_user_message(content.clone()).await {
+                        tracing::error!("Failed to send interjection to Claude: {e}");
+                        continue;
+                    }
+                    client.on_interjection(content).await?;
+                    interjections_in_turn += 1;
+                }
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    break;
+                }
             }
         }
         Ok(())
@@ -211,3 +246,96 @@ impl ProtocolPeer {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{DuplexStream, duplex};
+
+    use super::*;
+    use crate::executors::codex::client::LogWriter;
+
+    const RESULT: &str = r#"{"type":"result","subtype":"success","is_error":false}"#;
+
+    /// The CLI's ends of a session run by `read_loop`
+    struct Cli {
+        stdout: DuplexStream,
+        stdin: BufReader<DuplexStream>,
+        _log: DuplexStream,
+        _interrupt_tx: oneshot::Sender<()>,
+        interjection_tx: mpsc::UnboundedSender<String>,
+        session: tokio::task::JoinHandle<Result<(), ExecutorError>>,
+    }
+
+    impl Cli {
+        fn start() -> Self {
+            let (stdout, peer_stdout) = duplex(64 * 1024);
+            let (peer_stdin, stdin) = duplex(64 * 1024);
+            let (log_writer, log) = duplex(64 * 1024);
+            let (interrupt_tx, interrupt_rx) = oneshot::channel();
+            let (interjection_tx, interjection_rx) = mpsc::unbounded_channel();
+            let peer = ProtocolPeer::new(peer_stdin);
+            let client = ClaudeAgentClient::new(LogWriter::new(log_writer), None);
+            let session = tokio::spawn(async move {
+                peer.read_loop(peer_stdout, client, interrupt_rx, interjection_rx)
+                    .await
+            });
+            Self {
+                stdout,
+                stdin: BufReader::new(stdin),
+                _log: log,
+                _interrupt_tx: interrupt_tx,
+                interjection_tx,
+                session,
+            }
+        }
+
+        async fn emit(&mut self, line: &str) {
+            self.stdout.write_all(line.as_bytes()).await.unwrap();
+            self.stdout.write_all(b"\n").await.unwrap();
+        }
+
+        /// Send an instruction and wait until it was passed on to Claude
+        async fn interject(&mut self, content: &str) {
+            self.interjection_tx.send(content.to_string()).unwrap();
+            let mut line = String::new();
+            self.stdin.read_line(&mut line).await.unwrap();
+            assert!(line.contains(content));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn result_ends_session_without_interjections() {
+        let mut cli = Cli::start();
+        cli.emit(RESULT).await;
+        cli.session.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn result_after_interjection_keeps_session_open_for_next_turn() {
+        let mut cli = Cli::start();
+        cli.interject("also update the docs").await;
+        cli.emit(RESULT).await;
+
+        tokio::time::sleep(NEXT_TURN_GRACE / 2).await;
+        assert!(!cli.session.is_finished());
+
+        // The next turn answers the instruction; its result ends the session
+        cli.emit(r#"{"type":"assistant","message":{"role":"assistant","content":[]}}"#)
+            .await;
+        tokio::time::sleep(NEXT_TURN_GRACE).await;
+        assert!(!cli.session.is_finished());
+        cli.emit(RESULT).await;
+        cli.session.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn quiet_grace_period_after_interjection_ends_session() {
+        let mut cli = Cli::start();
+        cli.interject("also update the docs").await;
+        cli.emit(RESULT).await;
+
+        tokio::time::sleep(NEXT_TURN_GRACE + Duration::from_secs(1)).await;
+        assert!(cli.session.is_finished());
+        cli.session.await.unwrap().unwrap();
+    }
+}
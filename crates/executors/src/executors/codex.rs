@@ -19,7 +19,6 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use strum_macros::AsRefStr;
-use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::msg_store::MsgStore;
 
@@ -36,7 +35,10 @@ use crate::{
     executors::{
         AppendPrompt, AvailabilityInfo, ExecutorError, ExecutorExitResult, SpawnedChild,
         StandardCodingAgentExecutor,
-        codex::{jsonrpc::ExitSignalSender, normalize_logs::Error},
+        codex::{
+            jsonrpc::ExitSignalSender,
+            normalize_logs::{Error, Interjection},
+        },
     },
     stdout_dup::create_stdout_pipe_writer,
 };
@@ -302,21 +304,17 @@ impl Codex {
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
         let (program_path, args) = command_parts.into_resolved().await?;
 
-        let mut process = Command::new(program_path);
+        let mut env = env.clone();
+        env.insert("NODE_NO_WARNINGS", "1");
+        env.insert("NO_COLOR", "1");
+        env.insert("RUST_LOG", "error");
+        let env = env.with_profile(&self.cmd);
+        let mut process = env.command(program_path, &args, current_dir);
         process
             .kill_on_drop(true)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("NO_COLOR", "1")
-            .env("RUST_LOG", "error");
-
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut process);
+            .stderr(std::process::Stdio::piped());
 
         let mut child = process.group_spawn()?;
 
@@ -329,6 +327,7 @@ impl Codex {
 
         let new_stdout = create_stdout_pipe_writer(&mut child)?;
         let (exit_signal_tx, exit_signal_rx) = tokio::sync::oneshot::channel();
+        let (interjection_tx, interjection_rx) = tokio::sync::mpsc::unbounded_channel();
 
         let params = self.build_new_conversation_params(current_dir);
         let resume_session = resume_session.map(|s| s.to_string());
@@ -350,6 +349,7 @@ impl Codex {
                 exit_signal_tx.clone(),
                 approvals,
                 auto_approve,
+                interjection_rx,
             )
             .await
             {
@@ -390,6 +390,7 @@ impl Codex {
             child,
             exit_signal: Some(exit_signal_rx),
             interrupt_sender: None,
+            interjection_sender: Some(interjection_tx),
         })
     }
 
@@ -404,8 +405,9 @@ impl Codex {
         exit_signal_tx: ExitSignalSender,
         approvals: Option<Arc<dyn ExecutorApprovalService>>,
         auto_approve: bool,
+        mut interjection_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
     ) -> Result<(), ExecutorError> {
-        let client = AppServerClient::new(log_writer, approvals, auto_approve);
+        let client = AppServerClient::new(log_writer.clone(), approvals, auto_approve);
         let rpc_peer =
             JsonRpcPeer::spawn(child_stdin, child_stdout, client.clone(), exit_signal_tx);
         client.connect(rpc_peer);
@@ -416,7 +418,7 @@ impl Codex {
                 "Codex authentication required".to_string(),
             ));
         }
-        match resume_session {
+        let conversation_id = match resume_session {
             None => {
                 let params = conversation_params;
                 let response = client.new_conversation(params).await?;
@@ -426,6 +428,7 @@ impl Codex {
                 client
                     .send_user_message(conversation_id, combined_prompt)
                     .await?;
+                conversation_id
             }
             Some(session_id) => {
                 let (rollout_path, _forked_session_id) =
@@ -446,7 +449,22 @@ impl Codex {
                 client
                     .send_user_message(conversation_id, combined_prompt)
                     .await?;
+                conversation_id
+            }
+        };
+
+        // Messages sent while a task runs are added to that task's input
+        while let Some(content) = interjection_rx.recv().await {
+            if let Err(err) = client
+                .send_user_message(conversation_id, content.clone())
+                .await
+            {
+                tracing::error!("Failed to send interjection to Codex: {err}");
+                break;
             }
+            log_writer
+                .log_raw(&Interjection::Interjection { content }.raw())
+                .await?;
         }
         Ok(())
     }
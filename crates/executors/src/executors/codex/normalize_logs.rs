@@ -17,8 +17,8 @@ use codex_protocol::{
         ErrorEvent, EventMsg, ExecApprovalRequestEvent, ExecCommandBeginEvent, ExecCommandEndEvent,
         ExecCommandOutputDeltaEvent, ExecOutputStream, FileChange as CodexProtoFileChange,
         McpInvocation, McpToolCallBeginEvent, McpToolCallEndEvent, PatchApplyBeginEvent,
-        PatchApplyEndEvent, StreamErrorEvent, TokenUsageInfo, ViewImageToolCallEvent, WarningEvent,
-        WebSearchBeginEvent, WebSearchEndEvent,
+        PatchApplyEndEvent, StreamErrorEvent, TokenUsage as CodexTokenUsage, TokenUsageInfo,
+        ViewImageToolCallEvent, WarningEvent, WebSearchBeginEvent, WebSearchEndEvent,
     },
 };
 use futures::StreamExt;
@@ -26,8 +26,8 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use workspace_utils::{
-    approvals::ApprovalStatus, diff::normalize_unified_diff, msg_store::MsgStore,
-    path::make_path_relative,
+    approvals::ApprovalStatus, diff::normalize_unified_diff, log_msg::TokenUsage,
+    msg_store::MsgStore, path::make_path_relative,
 };
 
 use crate::{
@@ -207,6 +207,49 @@ impl ToNormalizedEntry for PatchEntry {
     }
 }
 
+/// Token counts Codex reports, which run over the whole conversation. A
+/// resumed conversation starts from the totals of its earlier runs, so a
+/// run's own usage is what was added since its first report.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct TokenTotals {
+    /// Includes the cached input
+    input: i64,
+    cached_input: i64,
+    output: i64,
+}
+
+impl From<&CodexTokenUsage> for TokenTotals {
+    fn from(usage: &CodexTokenUsage) -> Self {
+        Self {
+            input: usage.input_tokens,
+            cached_input: usage.cached_input_tokens,
+            output: usage.output_tokens,
+        }
+    }
+}
+
+impl TokenTotals {
+    fn minus(self, other: TokenTotals) -> Self {
+        Self {
+            input: (self.input - other.input).max(0),
+            cached_input: (self.cached_input - other.cached_input).max(0),
+            output: (self.output - other.output).max(0),
+        }
+    }
+}
+
+impl From<TokenTotals> for TokenUsage {
+    fn from(totals: TokenTotals) -> Self {
+        TokenUsage {
+            input_tokens: (totals.input - totals.cached_input).max(0) as u64,
+            output_tokens: totals.output as u64,
+            cache_read_tokens: totals.cached_input as u64,
+            cache_write_tokens: 0,
+            cost_usd: None,
+        }
+    }
+}
+
 struct LogState {
     entry_index: EntryIndexProvider,
     assistant: Option<StreamingText>,
@@ -216,6 +259,8 @@ struct LogState {
     patches: HashMap<String, PatchState>,
     web_searches: HashMap<String, WebSearchState>,
     token_usage_info: Option<TokenUsageInfo>,
+    /// Conversation totals from before this run
+    token_usage_start: Option<TokenTotals>,
 }
 
 enum StreamingTextKind {
@@ -234,6 +279,7 @@ impl LogState {
             patches: HashMap::new(),
             web_searches: HashMap::new(),
             token_usage_info: None,
+            token_usage_start: None,
         }
     }
 
@@ -379,6 +425,22 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                 continue;
             }
 
+            if let Ok(Interjection::Interjection { content }) =
+                serde_json::from_str::<Interjection>(&line)
+            {
+                add_normalized_entry(
+                    &msg_store,
+                    &entry_index,
+                    NormalizedEntry {
+                        timestamp: None,
+                        entry_type: NormalizedEntryType::UserMessage,
+                        content,
+                        metadata: None,
+                    },
+                );
+                continue;
+            }
+
             if let Ok(response) = serde_json::from_str::<JSONRPCResponse>(&line) {
                 handle_jsonrpc_response(response, &msg_store, &entry_index);
                 continue;
@@ -971,6 +1033,11 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                 }
                 EventMsg::TokenCount(payload) => {
                     if let Some(info) = payload.info {
+                        let total = TokenTotals::from(&info.total_token_usage);
+                        let start = *state.token_usage_start.get_or_insert_with(|| {
+                            total.minus(TokenTotals::from(&info.last_token_usage))
+                        });
+                        msg_store.push_token_usage(total.minus(start).into());
                         state.token_usage_info = Some(info);
                     }
                 }
@@ -1133,6 +1200,18 @@ impl ToNormalizedEntry for Error {
     }
 }
 
+/// A message the user sent while the task was running
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Interjection {
+    Interjection { content: String },
+}
+
+impl Interjection {
+    pub fn raw(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Approval {
     ApprovalResponse {
@@ -1204,3 +1283,40 @@ impl ToNormalizedEntryOpt for Approval {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use workspace_utils::log_msg::LogMsg;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn interjections_become_user_messages() {
+        let msg_store = Arc::new(MsgStore::new());
+        let interjection = Interjection::Interjection {
+            content: "Also update the changelog".to_string(),
+        };
+        msg_store.push_stdout(format!("{}\n", interjection.raw()));
+        msg_store.push_finished();
+
+        normalize_logs(msg_store.clone(), Path::new("/tmp/test-worktree"));
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let entries: Vec<Value> = msg_store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::JsonPatch(patch) => serde_json::to_value(patch).ok(),
+                _ => None,
+            })
+            .flat_map(|patch| patch.as_array().cloned().unwrap_or_default())
+            .filter_map(|op| op.pointer("/value/content").cloned())
+            .collect();
+        let user_messages: Vec<&Value> = entries
+            .iter()
+            .filter(|entry| entry["entry_type"]["type"] == "user_message")
+            .collect();
+        assert_eq!(user_messages.len(), 1);
+        assert_eq!(user_messages[0]["content"], "Also update the changelog");
+    }
+}
@@ -14,7 +14,6 @@ use serde::{Deserialize, Serialize};
 use tokio::{
     fs,
     io::AsyncWriteExt,
-    process::Command,
     time::{interval, timeout},
 };
 use ts_rs::TS;
@@ -55,8 +54,12 @@ pub struct Copilot {
 }
 
 impl Copilot {
+    pub fn base_command() -> &'static str {
+        "npx -y @github/copilot@0.0.367"
+    }
+
     fn build_command_builder(&self, log_dir: &str) -> CommandBuilder {
-        let mut builder = CommandBuilder::new("npx -y @github/copilot@0.0.367").params([
+        let mut builder = CommandBuilder::new(Self::base_command()).params([
             "--no-color",
             "--log-level",
             "debug",
@@ -112,19 +115,15 @@ impl StandardCodingAgentExecutor for Copilot {
 
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
+        let mut env = env.clone();
+        env.insert("NODE_NO_WARNINGS", "1");
+        let env = env.with_profile(&self.cmd);
+        let mut command = env.command(program_path, &args, current_dir);
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
-
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .stderr(Stdio::piped());
 
         let mut child = command.group_spawn()?;
 
@@ -155,20 +154,15 @@ impl StandardCodingAgentExecutor for Copilot {
 
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
-
+        let mut env = env.clone();
+        env.insert("NODE_NO_WARNINGS", "1");
+        let env = env.with_profile(&self.cmd);
+        let mut command = env.command(program_path, &args, current_dir);
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
-
-        env.clone()
-            .with_profile(&self.cmd)
-            .apply_to_command(&mut command);
+            .stderr(Stdio::piped());
 
         let mut child = command.group_spawn()?;
 
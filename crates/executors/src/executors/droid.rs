@@ -5,7 +5,7 @@ use command_group::AsyncCommandGroup;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum_macros::AsRefStr;
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use workspace_utils::msg_store::MsgStore;
 
@@ -83,10 +83,14 @@ pub struct Droid {
 }
 
 impl Droid {
+    pub fn base_command() -> &'static str {
+        "droid exec"
+    }
+
     pub fn build_command_builder(&self) -> crate::command::CommandBuilder {
         use crate::command::{CommandBuilder, apply_overrides};
         let mut builder =
-            CommandBuilder::new("droid exec").params(["--output-format", "stream-json"]);
+            CommandBuilder::new(Self::base_command()).params(["--output-format", "stream-json"]);
         builder = match &self.autonomy {
             Autonomy::Normal => builder,
             Autonomy::Low => builder.extend_params(["--auto", "low"]),
@@ -114,18 +118,13 @@ async fn spawn_droid(
 ) -> Result<SpawnedChild, ExecutorError> {
     let (program_path, args) = command_parts.into_resolved().await?;
 
-    let mut command = Command::new(program_path);
+    let env = env.clone().with_profile(cmd_overrides);
+    let mut command = env.command(program_path, args, current_dir);
     command
         .kill_on_drop(true)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .current_dir(current_dir)
-        .args(args);
-
-    env.clone()
-        .with_profile(cmd_overrides)
-        .apply_to_command(&mut command);
+        .stderr(Stdio::piped());
 
     let mut child = command.group_spawn()?;
 
@@ -0,0 +1,208 @@
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use ts_rs::TS;
+use uuid::Uuid;
+use workspace_utils::msg_store::MsgStore;
+
+use crate::{
+    command::{CmdOverrides, CommandBuilder, CommandParts, apply_overrides},
+    env::ExecutionEnv,
+    executors::{
+        AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+    },
+    logs::utils::EntryIndexProvider,
+    stdout_dup,
+};
+
+pub mod normalize_logs;
+
+use normalize_logs::normalize_logs;
+
+/// Goose executor configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct Goose {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Provider",
+        description = "Goose provider (e.g., anthropic, openai, ollama); overrides the provider from `goose configure`"
+    )]
+    pub provider: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Model",
+        description = "Model name for the provider (e.g., claude-sonnet-4-5, gpt-5)"
+    )]
+    pub model: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Max Turns",
+        description = "Turns the agent may take without user input before it stops"
+    )]
+    pub max_turns: Option<u32>,
+
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl Goose {
+    /// Prefix of the line naming the session, written into the run's stdout
+    /// because Goose doesn't print the name itself
+    pub(crate) const SESSION_PREFIX: &'static str = "[goose-session] ";
+
+    pub fn base_command() -> &'static str {
+        "goose"
+    }
+
+    /// Instructions are read from stdin and events printed as JSON lines
+    fn build_command_builder(&self) -> CommandBuilder {
+        let mut builder = CommandBuilder::new(Self::base_command()).params([
+            "run",
+            "--output-format",
+            "stream-json",
+            "--instructions",
+            "-",
+        ]);
+        if let Some(max_turns) = self.max_turns {
+            builder = builder.extend_params(["--max-turns".to_string(), max_turns.to_string()]);
+        }
+        apply_overrides(builder, &self.cmd)
+    }
+
+    /// Environment variables Goose takes its settings from. Headless runs
+    /// can't ask before acting, so every tool call is approved.
+    fn goose_env(&self) -> Vec<(&'static str, &str)> {
+        let mut vars = vec![("GOOSE_MODE", "auto")];
+        if let Some(provider) = &self.provider {
+            vars.push(("GOOSE_PROVIDER", provider.as_str()));
+        }
+        if let Some(model) = &self.model {
+            vars.push(("GOOSE_MODEL", model.as_str()));
+        }
+        vars
+    }
+
+    async fn spawn_goose(
+        &self,
+        command_parts: CommandParts,
+        current_dir: &Path,
+        prompt: &str,
+        session_name: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let (program_path, args) = command_parts.into_resolved().await?;
+
+        let mut env = env.clone();
+        for (key, value) in self.goose_env() {
+            env.insert(key, value);
+        }
+        let env = env.with_profile(&self.cmd);
+        let mut command = env.command(program_path, &args, current_dir);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command.group_spawn()?;
+
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            stdin.write_all(prompt.as_bytes()).await?;
+            stdin.shutdown().await?;
+        }
+
+        let (_, appender) = stdout_dup::tee_stdout_with_appender(&mut child)?;
+        appender.append_line(format!("{}{session_name}", Self::SESSION_PREFIX));
+
+        Ok(child.into())
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Goose {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let session_name = format!("vk-{}", Uuid::new_v4());
+        let command_parts = self
+            .build_command_builder()
+            .build_follow_up(&["--name".to_string(), session_name.clone()])?;
+
+        self.spawn_goose(
+            command_parts,
+            current_dir,
+            &combined_prompt,
+            &session_name,
+            env,
+        )
+        .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let command_parts = self.build_command_builder().build_follow_up(&[
+            "--name".to_string(),
+            session_id.to_string(),
+            "--resume".to_string(),
+        ])?;
+
+        self.spawn_goose(
+            command_parts,
+            current_dir,
+            &combined_prompt,
+            session_id,
+            env,
+        )
+        .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
+        normalize_logs(
+            msg_store.clone(),
+            current_dir,
+            EntryIndexProvider::start_from(&msg_store),
+        );
+    }
+
+    // Goose keeps its extensions in a YAML config, which the MCP settings
+    // can't edit
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        let config_found = dirs::home_dir()
+            .map(|home| {
+                home.join(".config")
+                    .join("goose")
+                    .join("config.yaml")
+                    .exists()
+            })
+            .unwrap_or(false);
+
+        if config_found {
+            AvailabilityInfo::InstallationFound
+        } else {
+            AvailabilityInfo::NotFound
+        }
+    }
+}
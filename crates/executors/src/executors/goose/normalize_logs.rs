@@ -0,0 +1,447 @@
+//! Translates the events `goose run --output-format stream-json` prints into
+//! normalized log entries.
+//!
+//! Each stdout line is one event. Messages carry a list of content parts:
+//! text and thinking from the assistant, tool requests, and the tool
+//! responses Goose sends back under the user role, which mark the request
+//! with the same id as done. Text streams in as several messages sharing an
+//! id, so those are joined into one entry.
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use futures::{StreamExt, future::ready};
+use serde::Deserialize;
+use serde_json::Value;
+use workspace_utils::{diff::create_unified_diff, msg_store::MsgStore, path::make_path_relative};
+
+use crate::{
+    executors::goose::Goose,
+    logs::{
+        ActionType, CommandRunResult, FileChange, NormalizedEntry, NormalizedEntryError,
+        NormalizedEntryType, ToolResult, ToolStatus,
+        stderr_processor::normalize_stderr_logs,
+        utils::{
+            EntryIndexProvider,
+            patch::{add_normalized_entry, replace_normalized_entry},
+        },
+    },
+};
+
+pub fn normalize_logs(
+    msg_store: Arc<MsgStore>,
+    worktree_path: &Path,
+    entry_index_provider: EntryIndexProvider,
+) {
+    normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+    let worktree_path = worktree_path.to_string_lossy().to_string();
+    tokio::spawn(async move {
+        // Tool use entries waiting for their response, by tool call id
+        let mut pending: HashMap<String, (usize, NormalizedEntry)> = HashMap::new();
+        // The entry text of the message being streamed is added to
+        let mut streaming: Option<Streaming> = None;
+
+        let add = |entry_type: NormalizedEntryType, content: String| {
+            add_normalized_entry(
+                &msg_store,
+                &entry_index_provider,
+                NormalizedEntry {
+                    timestamp: None,
+                    entry_type,
+                    content,
+                    metadata: None,
+                },
+            )
+        };
+
+        let mut lines_stream = msg_store
+            .stdout_lines_stream()
+            .filter_map(|res| ready(res.ok()));
+
+        while let Some(line) = lines_stream.next().await {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(session_name) = trimmed.strip_prefix(Goose::SESSION_PREFIX) {
+                msg_store.push_session_id(session_name.trim().to_string());
+                continue;
+            }
+            let Ok(event) = serde_json::from_str::<GooseEvent>(trimmed) else {
+                add(
+                    NormalizedEntryType::SystemMessage,
+                    strip_ansi_escapes::strip_str(trimmed),
+                );
+                continue;
+            };
+
+            match event {
+                GooseEvent::Message { message } => {
+                    for part in message.content {
+                        match part {
+                            MessageContent::Text { text } if message.role == "assistant" => {
+                                match &mut streaming {
+                                    Some(current)
+                                        if message.id.is_some()
+                                            && current.message_id == message.id =>
+                                    {
+                                        current.entry.content.push_str(&text);
+                                        replace_normalized_entry(
+                                            &msg_store,
+                                            current.index,
+                                            current.entry.clone(),
+                                        );
+                                    }
+                                    _ => {
+                                        if text.trim().is_empty() {
+                                            continue;
+                                        }
+                                        let entry = NormalizedEntry {
+                                            timestamp: None,
+                                            entry_type: NormalizedEntryType::AssistantMessage,
+                                            content: text,
+                                            metadata: None,
+                                        };
+                                        let index = add_normalized_entry(
+                                            &msg_store,
+                                            &entry_index_provider,
+                                            entry.clone(),
+                                        );
+                                        streaming = Some(Streaming {
+                                            message_id: message.id.clone(),
+                                            index,
+                                            entry,
+                                        });
+                                    }
+                                }
+                                continue;
+                            }
+                            // The instructions come back as the first user
+                            // message, which the conversation already shows
+                            MessageContent::Text { .. } => {}
+                            MessageContent::Thinking { thinking } => {
+                                if !thinking.trim().is_empty() {
+                                    add(NormalizedEntryType::Thinking, thinking);
+                                }
+                            }
+                            MessageContent::ToolRequest { id, tool_call } => {
+                                let entry = requested_entry(&tool_call, &worktree_path);
+                                let index = add_normalized_entry(
+                                    &msg_store,
+                                    &entry_index_provider,
+                                    entry.clone(),
+                                );
+                                pending.insert(id, (index, entry));
+                            }
+                            MessageContent::ToolResponse { id, tool_result } => {
+                                if let Some((index, entry)) = pending.remove(&id) {
+                                    replace_normalized_entry(
+                                        &msg_store,
+                                        index,
+                                        responded_entry(entry, &tool_result),
+                                    );
+                                }
+                            }
+                            MessageContent::Other => {}
+                        }
+                        streaming = None;
+                    }
+                }
+
+                GooseEvent::Error { error } => {
+                    streaming = None;
+                    add(
+                        NormalizedEntryType::ErrorMessage {
+                            error_type: NormalizedEntryError::Other,
+                        },
+                        error,
+                    );
+                }
+
+                GooseEvent::Other => {}
+            }
+        }
+    });
+}
+
+struct Streaming {
+    message_id: Option<String>,
+    index: usize,
+    entry: NormalizedEntry,
+}
+
+/// The tool use entry for a tool request, before its response came back
+fn requested_entry(tool_call: &Value, worktree_path: &str) -> NormalizedEntry {
+    let call = tool_call.get("value").unwrap_or(tool_call);
+    let full_name = call
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("tool")
+        .to_string();
+    let arguments = call.get("arguments").cloned().unwrap_or(Value::Null);
+    let (action_type, content) = tool_action(&full_name, arguments, worktree_path);
+    // Requests Goose couldn't parse fail without a response
+    let status = if tool_call.get("status").and_then(Value::as_str) == Some("error") {
+        ToolStatus::Failed
+    } else {
+        ToolStatus::Created
+    };
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name: tool_name(&full_name).to_string(),
+            action_type,
+            status,
+        },
+        content,
+        metadata: None,
+    }
+}
+
+/// Tools are named `<extension>__<tool>`, such as `developer__shell`
+fn tool_name(full_name: &str) -> &str {
+    full_name
+        .rsplit_once("__")
+        .map_or(full_name, |(_, name)| name)
+}
+
+/// What a tool call does and the entry text describing it
+fn tool_action(full_name: &str, arguments: Value, worktree_path: &str) -> (ActionType, String) {
+    let arg = |key: &str| arguments.get(key).and_then(Value::as_str);
+    match (tool_name(full_name), arg("command")) {
+        ("shell", Some(command)) => (
+            ActionType::CommandRun {
+                command: command.to_string(),
+                result: None,
+            },
+            command.to_string(),
+        ),
+        ("text_editor", Some(command)) if arg("path").is_some() => {
+            let path = make_path_relative(arg("path").unwrap_or_default(), worktree_path);
+            let edit = |old: &str, new: &str| ActionType::FileEdit {
+                path: path.clone(),
+                changes: vec![FileChange::Edit {
+                    unified_diff: create_unified_diff(&path, old, new),
+                    has_line_numbers: false,
+                }],
+            };
+            let action_type = match command {
+                "view" => ActionType::FileRead { path: path.clone() },
+                "write" => ActionType::FileEdit {
+                    path: path.clone(),
+                    changes: vec![FileChange::Write {
+                        content: arg("file_text").unwrap_or_default().to_string(),
+                    }],
+                },
+                "str_replace" => edit(
+                    arg("old_str").unwrap_or_default(),
+                    arg("new_str").unwrap_or_default(),
+                ),
+                "insert" => edit("", arg("new_str").unwrap_or_default()),
+                _ => ActionType::Other {
+                    description: format!("{command} {path}"),
+                },
+            };
+            (action_type, path)
+        }
+        _ => (
+            ActionType::Tool {
+                tool_name: full_name.to_string(),
+                arguments: Some(arguments),
+                result: None,
+            },
+            tool_name(full_name).to_string(),
+        ),
+    }
+}
+
+/// The tool use entry once its response came back
+fn responded_entry(entry: NormalizedEntry, tool_result: &Value) -> NormalizedEntry {
+    let value = tool_result.get("value");
+    // Newer versions wrap the content parts in an MCP call result
+    let parts = value
+        .and_then(|value| value.get("content"))
+        .or(value)
+        .and_then(Value::as_array);
+    let output = match parts {
+        Some(parts) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => tool_result
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+    };
+    let failed = tool_result.get("status").and_then(Value::as_str) == Some("error")
+        || value
+            .and_then(|value| value.get("isError"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+    let status = if failed {
+        ToolStatus::Failed
+    } else {
+        ToolStatus::Success
+    };
+
+    let NormalizedEntryType::ToolUse {
+        tool_name,
+        action_type,
+        ..
+    } = entry.entry_type
+    else {
+        return entry;
+    };
+    let action_type = match action_type {
+        ActionType::CommandRun { command, .. } => ActionType::CommandRun {
+            command,
+            result: Some(CommandRunResult {
+                exit_status: None,
+                output: Some(output),
+            }),
+        },
+        ActionType::Tool {
+            tool_name,
+            arguments,
+            ..
+        } => ActionType::Tool {
+            tool_name,
+            arguments,
+            result: Some(ToolResult::markdown(output)),
+        },
+        other => other,
+    };
+    NormalizedEntry {
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            status,
+        },
+        ..entry
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum GooseEvent {
+    Message {
+        message: GooseMessage,
+    },
+    Error {
+        error: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct GooseMessage {
+    #[serde(default)]
+    id: Option<String>,
+    role: String,
+    #[serde(default)]
+    content: Vec<MessageContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum MessageContent {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolRequest {
+        id: String,
+        tool_call: Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolResponse {
+        id: String,
+        tool_result: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_responses_fill_in_the_command_output() {
+        let event: GooseEvent = serde_json::from_str(
+            r#"{"type":"message","message":{"id":"msg_1","role":"assistant","created":1766394000,"content":[{"type":"toolRequest","id":"call_1","toolCall":{"status":"success","value":{"name":"developer__shell","arguments":{"command":"cargo test"}}}}]}}"#,
+        )
+        .unwrap();
+        let GooseEvent::Message { message } = event else {
+            panic!("expected a message");
+        };
+        let [MessageContent::ToolRequest { tool_call, .. }] = &message.content[..] else {
+            panic!("expected a tool request");
+        };
+        let entry = requested_entry(tool_call, "/repo");
+        assert_eq!(entry.content, "cargo test");
+
+        let response = serde_json::json!({
+            "status": "success",
+            "value": [{"type": "text", "text": "test result: ok"}],
+        });
+        let NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type:
+                ActionType::CommandRun {
+                    result: Some(result),
+                    ..
+                },
+            status: ToolStatus::Success,
+        } = responded_entry(entry, &response).entry_type
+        else {
+            panic!("expected a finished command");
+        };
+        assert_eq!(tool_name, "shell");
+        assert_eq!(result.output.as_deref(), Some("test result: ok"));
+    }
+
+    #[test]
+    fn editor_calls_become_file_changes_relative_to_the_worktree() {
+        let (action_type, content) = tool_action(
+            "developer__text_editor",
+            serde_json::json!({
+                "command": "str_replace",
+                "path": "/repo/src/lib.rs",
+                "old_str": "fn a() {}",
+                "new_str": "fn b() {}",
+            }),
+            "/repo",
+        );
+        assert_eq!(content, "src/lib.rs");
+        let ActionType::FileEdit { path, changes } = action_type else {
+            panic!("expected a file edit");
+        };
+        assert_eq!(path, "src/lib.rs");
+        assert!(matches!(
+            &changes[..],
+            [FileChange::Edit { unified_diff, .. }] if unified_diff.contains("+fn b() {}")
+        ));
+
+        let failed = responded_entry(
+            requested_entry(
+                &serde_json::json!({"status": "success", "value": {"name": "memory__remember", "arguments": {}}}),
+                "/repo",
+            ),
+            &serde_json::json!({"status": "error", "error": "no memory extension"}),
+        );
+        assert!(matches!(
+            failed.entry_type,
+            NormalizedEntryType::ToolUse {
+                status: ToolStatus::Failed,
+                ..
+            }
+        ));
+    }
+}
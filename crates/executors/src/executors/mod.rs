@@ -18,21 +18,26 @@ use crate::{
     command::CommandBuildError,
     env::ExecutionEnv,
     executors::{
-        amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
-        droid::Droid, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
+        amp::Amp, api_agent::ApiAgent, claude::ClaudeCode, codex::Codex, copilot::Copilot,
+        cursor::CursorAgent, droid::Droid, gemini::Gemini, goose::Goose, opencode::Opencode,
+        openhands::Openhands, plugin::Plugin, qwen::QwenCode,
     },
     mcp_config::McpConfig,
 };
 
 pub mod acp;
 pub mod amp;
+pub mod api_agent;
 pub mod claude;
 pub mod codex;
 pub mod copilot;
 pub mod cursor;
 pub mod droid;
 pub mod gemini;
+pub mod goose;
 pub mod opencode;
+pub mod openhands;
+pub mod plugin;
 pub mod qwen;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
@@ -70,6 +75,8 @@ pub enum ExecutorError {
     SetupHelperNotSupported,
     #[error("Auth required: {0}")]
     AuthRequired(String),
+    #[error("Executor plugin not found: {0}")]
+    PluginNotFound(String),
 }
 
 #[enum_dispatch]
@@ -100,6 +107,10 @@ pub enum CodingAgent {
     QwenCode,
     Copilot,
     Droid,
+    Openhands,
+    ApiAgent,
+    Goose,
+    Plugin,
 }
 
 impl CodingAgent {
@@ -166,7 +177,11 @@ impl CodingAgent {
                 BaseAgentCapability::SetupHelper,
             ],
             Self::CursorAgent(_) => vec![BaseAgentCapability::SetupHelper],
-            Self::Copilot(_) => vec![],
+            Self::Copilot(_)
+            | Self::Openhands(_)
+            | Self::ApiAgent(_)
+            | Self::Goose(_)
+            | Self::Plugin(_) => vec![],
         }
     }
 }
@@ -248,6 +263,10 @@ pub type ExecutorExitSignal = tokio::sync::oneshot::Receiver<ExecutorExitResult>
 /// When sent, the executor should attempt to interrupt gracefully before being killed.
 pub type InterruptSender = tokio::sync::oneshot::Sender<()>;
 
+/// Sender for instructions the user gives a session while it is running.
+/// Sending fails once the executor no longer takes them.
+pub type InterjectionSender = tokio::sync::mpsc::UnboundedSender<String>;
+
 #[derive(Debug)]
 pub struct SpawnedChild {
     pub child: AsyncGroupChild,
@@ -255,6 +274,8 @@ pub struct SpawnedChild {
     pub exit_signal: Option<ExecutorExitSignal>,
     /// Container → Executor: signals when container wants to interrupt
     pub interrupt_sender: Option<InterruptSender>,
+    /// Container → Executor: instructions from the user for the running session
+    pub interjection_sender: Option<InterjectionSender>,
 }
 
 impl From<AsyncGroupChild> for SpawnedChild {
@@ -263,6 +284,7 @@ impl From<AsyncGroupChild> for SpawnedChild {
             child,
             exit_signal: None,
             interrupt_sender: None,
+            interjection_sender: None,
         }
     }
 }
@@ -38,8 +38,12 @@ pub struct Opencode {
 }
 
 impl Opencode {
+    pub fn base_command() -> &'static str {
+        "npx -y opencode-ai@1.1.3"
+    }
+
     fn build_command_builder(&self) -> CommandBuilder {
-        let builder = CommandBuilder::new("npx -y opencode-ai@1.1.3").extend_params(["acp"]);
+        let builder = CommandBuilder::new(Self::base_command()).extend_params(["acp"]);
         apply_overrides(builder, &self.cmd)
     }
 
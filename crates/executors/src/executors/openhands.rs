@@ -0,0 +1,187 @@
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use ts_rs::TS;
+use uuid::Uuid;
+use workspace_utils::{msg_store::MsgStore, path::get_vibe_kanban_temp_dir};
+
+use crate::{
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, CommandParts, apply_overrides},
+    env::ExecutionEnv,
+    executors::{
+        AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+    },
+    logs::utils::EntryIndexProvider,
+};
+
+pub mod normalize_logs;
+
+use normalize_logs::normalize_logs;
+
+/// OpenHands executor configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct Openhands {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Model",
+        description = "LiteLLM model name (e.g., anthropic/claude-sonnet-4-5-20250929, openai/gpt-5); overrides the model saved in the OpenHands settings"
+    )]
+    pub model: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Base URL",
+        description = "Base URL of the LLM API, for self-hosted or proxied models"
+    )]
+    pub base_url: Option<String>,
+
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl Openhands {
+    pub fn base_command() -> &'static str {
+        "uvx --python 3.12 openhands"
+    }
+
+    /// Headless runs can't ask before acting, so every action is approved;
+    /// events are printed as JSON lines
+    fn build_command_builder(&self) -> CommandBuilder {
+        let mut builder = CommandBuilder::new(Self::base_command()).params([
+            "--headless",
+            "--json",
+            "--always-approve",
+        ]);
+        // The CLI only reads LLM settings from the environment when asked to
+        if self.model.is_some() || self.base_url.is_some() {
+            builder = builder.extend_params(["--override-with-envs"]);
+        }
+        apply_overrides(builder, &self.cmd)
+    }
+
+    /// Environment variables the CLI takes LLM settings from
+    fn llm_env(&self) -> Vec<(&'static str, &str)> {
+        let mut vars = Vec::new();
+        if let Some(model) = &self.model {
+            vars.push(("LLM_MODEL", model.as_str()));
+        }
+        if let Some(base_url) = &self.base_url {
+            vars.push(("LLM_BASE_URL", base_url.as_str()));
+        }
+        vars
+    }
+
+    /// The CLI takes the task from a file rather than stdin. Returns the
+    /// argument giving the file, quoted for the command line.
+    async fn write_task_file(current_dir: &Path, prompt: &str) -> Result<String, ExecutorError> {
+        let task_dir = get_vibe_kanban_temp_dir()
+            .join("openhands_tasks")
+            .join(current_dir.file_name().unwrap_or_default());
+        fs::create_dir_all(&task_dir)
+            .await
+            .map_err(ExecutorError::Io)?;
+
+        let task_file = task_dir.join(format!("{}.md", Uuid::new_v4()));
+        fs::write(&task_file, prompt)
+            .await
+            .map_err(ExecutorError::Io)?;
+        let task_file = task_file.to_string_lossy();
+        let quoted = shlex::try_quote(&task_file).map_err(CommandBuildError::from)?;
+        Ok(quoted.into_owned())
+    }
+
+    async fn spawn_openhands(
+        &self,
+        command_parts: CommandParts,
+        current_dir: &Path,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let (program_path, args) = command_parts.into_resolved().await?;
+
+        let mut env = env.clone();
+        for (key, value) in self.llm_env() {
+            env.insert(key, value);
+        }
+        let env = env.with_profile(&self.cmd);
+        let mut command = env.command(program_path, &args, current_dir);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        Ok(command.group_spawn()?.into())
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Openhands {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let task_file = Self::write_task_file(current_dir, &combined_prompt).await?;
+        let command_parts = self
+            .build_command_builder()
+            .build_follow_up(&["--file".to_string(), task_file])?;
+
+        self.spawn_openhands(command_parts, current_dir, env).await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let task_file = Self::write_task_file(current_dir, &combined_prompt).await?;
+        let command_parts = self.build_command_builder().build_follow_up(&[
+            "--resume".to_string(),
+            session_id.to_string(),
+            "--file".to_string(),
+            task_file,
+        ])?;
+
+        self.spawn_openhands(command_parts, current_dir, env).await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
+        normalize_logs(
+            msg_store.clone(),
+            current_dir,
+            EntryIndexProvider::start_from(&msg_store),
+        );
+    }
+
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        dirs::home_dir().map(|home| home.join(".openhands").join("mcp.json"))
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        let settings_found = dirs::home_dir()
+            .map(|home| home.join(".openhands").join("agent_settings.json").exists())
+            .unwrap_or(false);
+        let mcp_config_found = self
+            .default_mcp_config_path()
+            .map(|path| path.exists())
+            .unwrap_or(false);
+
+        if settings_found || mcp_config_found {
+            AvailabilityInfo::InstallationFound
+        } else {
+            AvailabilityInfo::NotFound
+        }
+    }
+}
@@ -0,0 +1,563 @@
+//! Translates the events the OpenHands CLI prints in JSON mode into normalized
+//! log entries.
+//!
+//! Each stdout line is one event of the conversation, named by its `kind`. An
+//! agent action becomes a tool use entry, which the observation answering the
+//! same tool call later marks as done. The CLI names the conversation in a
+//! plain text line, which is where the session id for follow-ups comes from.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, LazyLock},
+};
+
+use futures::{StreamExt, future::ready};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use workspace_utils::{diff::create_unified_diff, msg_store::MsgStore, path::make_path_relative};
+
+use crate::logs::{
+    ActionType, CommandExitStatus, CommandRunResult, FileChange, NormalizedEntry,
+    NormalizedEntryError, NormalizedEntryType, TodoItem, ToolResult, ToolStatus,
+    stderr_processor::normalize_stderr_logs,
+    utils::{
+        EntryIndexProvider,
+        patch::{add_normalized_entry, replace_normalized_entry},
+    },
+};
+
+pub fn normalize_logs(
+    msg_store: Arc<MsgStore>,
+    worktree_path: &Path,
+    entry_index_provider: EntryIndexProvider,
+) {
+    normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+    let worktree_path = worktree_path.to_string_lossy().to_string();
+    tokio::spawn(async move {
+        // Tool use entries waiting for their observation, by tool call id
+        let mut pending: HashMap<String, (usize, NormalizedEntry)> = HashMap::new();
+        let mut session_id_extracted = false;
+
+        let add = |entry_type: NormalizedEntryType, content: String| {
+            add_normalized_entry(
+                &msg_store,
+                &entry_index_provider,
+                NormalizedEntry {
+                    timestamp: None,
+                    entry_type,
+                    content,
+                    metadata: None,
+                },
+            )
+        };
+
+        let mut lines_stream = msg_store
+            .stdout_lines_stream()
+            .filter_map(|res| ready(res.ok()));
+
+        while let Some(line) = lines_stream.next().await {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let Ok(event) = serde_json::from_str::<OpenHandsEvent>(trimmed) else {
+                let text = strip_ansi_escapes::strip_str(trimmed);
+                if !session_id_extracted && let Some(session_id) = conversation_id(&text) {
+                    msg_store.push_session_id(session_id);
+                    session_id_extracted = true;
+                    continue;
+                }
+                add(NormalizedEntryType::SystemMessage, text);
+                continue;
+            };
+
+            match event {
+                OpenHandsEvent::MessageEvent {
+                    source,
+                    llm_message,
+                } => {
+                    let text = join_text(&llm_message.content);
+                    if text.trim().is_empty() {
+                        continue;
+                    }
+                    let entry_type = match source.as_str() {
+                        "agent" => NormalizedEntryType::AssistantMessage,
+                        "user" => NormalizedEntryType::UserMessage,
+                        _ => NormalizedEntryType::SystemMessage,
+                    };
+                    add(entry_type, text);
+                }
+
+                OpenHandsEvent::ActionEvent {
+                    thought,
+                    reasoning_content,
+                    action,
+                    tool_name,
+                    tool_call_id,
+                } => {
+                    let thought = join_text(&thought);
+                    let thought = if thought.trim().is_empty() {
+                        reasoning_content.unwrap_or_default()
+                    } else {
+                        thought
+                    };
+                    if !thought.trim().is_empty() {
+                        add(NormalizedEntryType::Thinking, thought);
+                    }
+
+                    match serde_json::from_value::<OpenHandsAction>(action.clone()) {
+                        Ok(OpenHandsAction::FinishAction { message }) => {
+                            if !message.trim().is_empty() {
+                                add(NormalizedEntryType::AssistantMessage, message);
+                            }
+                        }
+                        Ok(OpenHandsAction::ThinkAction { thought }) => {
+                            add(NormalizedEntryType::Thinking, thought);
+                        }
+                        parsed => {
+                            let (action_type, content) =
+                                tool_action(&tool_name, parsed.ok(), action, &worktree_path);
+                            let entry = NormalizedEntry {
+                                timestamp: None,
+                                entry_type: NormalizedEntryType::ToolUse {
+                                    tool_name,
+                                    action_type,
+                                    status: ToolStatus::Created,
+                                },
+                                content,
+                                metadata: None,
+                            };
+                            let index = add_normalized_entry(
+                                &msg_store,
+                                &entry_index_provider,
+                                entry.clone(),
+                            );
+                            pending.insert(tool_call_id, (index, entry));
+                        }
+                    }
+                }
+
+                OpenHandsEvent::ObservationEvent {
+                    tool_call_id,
+                    observation,
+                } => {
+                    if let Some((index, entry)) = pending.remove(&tool_call_id) {
+                        replace_normalized_entry(
+                            &msg_store,
+                            index,
+                            observed_entry(entry, &observation),
+                        );
+                    }
+                }
+
+                OpenHandsEvent::UserRejectObservation {
+                    tool_call_id,
+                    rejection_reason,
+                } => {
+                    if let Some((index, entry)) = pending.remove(&tool_call_id)
+                        && let Some(entry) = entry.with_tool_status(ToolStatus::Denied {
+                            reason: rejection_reason,
+                        })
+                    {
+                        replace_normalized_entry(&msg_store, index, entry);
+                    }
+                }
+
+                OpenHandsEvent::AgentErrorEvent {
+                    error,
+                    tool_call_id,
+                } => {
+                    if let Some((index, entry)) =
+                        tool_call_id.and_then(|tool_call_id| pending.remove(&tool_call_id))
+                        && let Some(entry) = entry.with_tool_status(ToolStatus::Failed)
+                    {
+                        replace_normalized_entry(&msg_store, index, entry);
+                    }
+                    add(
+                        NormalizedEntryType::ErrorMessage {
+                            error_type: NormalizedEntryError::Other,
+                        },
+                        error,
+                    );
+                }
+
+                OpenHandsEvent::ConversationErrorEvent { detail } => {
+                    add(
+                        NormalizedEntryType::ErrorMessage {
+                            error_type: NormalizedEntryError::Other,
+                        },
+                        detail,
+                    );
+                }
+
+                OpenHandsEvent::Other => {}
+            }
+        }
+    });
+}
+
+static CONVERSATION_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)conversation id:\s*([0-9a-f][0-9a-f-]{31,35})").expect("valid regex")
+});
+
+/// Conversation id from the CLI's "Conversation ID: ..." line
+fn conversation_id(line: &str) -> Option<String> {
+    CONVERSATION_ID
+        .captures(line)
+        .and_then(|caps| caps.get(1))
+        .map(|id| id.as_str().to_string())
+}
+
+fn join_text(content: &[TextContent]) -> String {
+    content
+        .iter()
+        .filter_map(|part| part.text.as_deref())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// What a tool call does and the entry text describing it
+fn tool_action(
+    tool_name: &str,
+    parsed: Option<OpenHandsAction>,
+    raw: Value,
+    worktree_path: &str,
+) -> (ActionType, String) {
+    match parsed {
+        Some(OpenHandsAction::TerminalAction { command }) => (
+            ActionType::CommandRun {
+                command: command.clone(),
+                result: None,
+            },
+            command,
+        ),
+        Some(OpenHandsAction::FileEditorAction {
+            command,
+            path,
+            file_text,
+            old_str,
+            new_str,
+        }) => {
+            let path = make_path_relative(&path, worktree_path);
+            let edit = |old: &str, new: &str| ActionType::FileEdit {
+                path: path.clone(),
+                changes: vec![FileChange::Edit {
+                    unified_diff: create_unified_diff(&path, old, new),
+                    has_line_numbers: false,
+                }],
+            };
+            let action_type = match command.as_str() {
+                "view" => ActionType::FileRead { path: path.clone() },
+                "create" => ActionType::FileEdit {
+                    path: path.clone(),
+                    changes: vec![FileChange::Write {
+                        content: file_text.unwrap_or_default(),
+                    }],
+                },
+                "str_replace" => edit(
+                    old_str.as_deref().unwrap_or(""),
+                    new_str.as_deref().unwrap_or(""),
+                ),
+                "insert" => edit("", new_str.as_deref().unwrap_or("")),
+                _ => ActionType::Other {
+                    description: format!("{command} {path}"),
+                },
+            };
+            (action_type, path)
+        }
+        Some(OpenHandsAction::TaskTrackerAction { command, task_list }) => (
+            ActionType::TodoManagement {
+                todos: task_list
+                    .into_iter()
+                    .map(|task| TodoItem {
+                        content: task.title,
+                        status: task.status,
+                        priority: None,
+                    })
+                    .collect(),
+                operation: command,
+            },
+            "TODO list updated".to_string(),
+        ),
+        Some(OpenHandsAction::GlobAction { pattern } | OpenHandsAction::GrepAction { pattern }) => {
+            (
+                ActionType::Search {
+                    query: pattern.clone(),
+                },
+                pattern,
+            )
+        }
+        Some(OpenHandsAction::BrowserNavigateAction { url }) => {
+            (ActionType::WebFetch { url: url.clone() }, url)
+        }
+        _ => (
+            ActionType::Tool {
+                tool_name: tool_name.to_string(),
+                arguments: Some(raw),
+                result: None,
+            },
+            tool_name.to_string(),
+        ),
+    }
+}
+
+/// The tool use entry once its observation came back
+fn observed_entry(entry: NormalizedEntry, observation: &Value) -> NormalizedEntry {
+    let failed = observation
+        .get("is_error")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let exit_code = observation
+        .get("exit_code")
+        .or_else(|| observation.pointer("/metadata/exit_code"))
+        .and_then(Value::as_i64)
+        .filter(|code| *code >= 0)
+        .map(|code| code as i32);
+    let output = match observation.get("content") {
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(Value::String(text)) => text.clone(),
+        _ => observation
+            .get("output")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+    };
+    let status = if failed || exit_code.is_some_and(|code| code != 0) {
+        ToolStatus::Failed
+    } else {
+        ToolStatus::Success
+    };
+
+    let NormalizedEntryType::ToolUse {
+        tool_name,
+        action_type,
+        ..
+    } = entry.entry_type
+    else {
+        return entry;
+    };
+    let action_type = match action_type {
+        ActionType::CommandRun { command, .. } => ActionType::CommandRun {
+            command,
+            result: Some(CommandRunResult {
+                exit_status: exit_code.map(|code| CommandExitStatus::ExitCode { code }),
+                output: Some(output),
+            }),
+        },
+        ActionType::Tool {
+            tool_name,
+            arguments,
+            ..
+        } => ActionType::Tool {
+            tool_name,
+            arguments,
+            result: Some(ToolResult::markdown(output)),
+        },
+        other => other,
+    };
+    NormalizedEntry {
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            status,
+        },
+        ..entry
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind")]
+enum OpenHandsEvent {
+    MessageEvent {
+        source: String,
+        llm_message: LlmMessage,
+    },
+    ActionEvent {
+        #[serde(default)]
+        thought: Vec<TextContent>,
+        #[serde(default)]
+        reasoning_content: Option<String>,
+        #[serde(default)]
+        action: Value,
+        #[serde(default)]
+        tool_name: String,
+        #[serde(default)]
+        tool_call_id: String,
+    },
+    ObservationEvent {
+        tool_call_id: String,
+        observation: Value,
+    },
+    UserRejectObservation {
+        tool_call_id: String,
+        #[serde(default)]
+        rejection_reason: Option<String>,
+    },
+    AgentErrorEvent {
+        error: String,
+        #[serde(default)]
+        tool_call_id: Option<String>,
+    },
+    ConversationErrorEvent {
+        detail: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmMessage {
+    #[serde(default)]
+    content: Vec<TextContent>,
+}
+
+/// Images and other content parts have no text
+#[derive(Debug, Deserialize)]
+struct TextContent {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind")]
+enum OpenHandsAction {
+    #[serde(alias = "ExecuteBashAction")]
+    TerminalAction {
+        command: String,
+    },
+    #[serde(alias = "StrReplaceEditorAction")]
+    FileEditorAction {
+        command: String,
+        path: String,
+        #[serde(default)]
+        file_text: Option<String>,
+        #[serde(default)]
+        old_str: Option<String>,
+        #[serde(default)]
+        new_str: Option<String>,
+    },
+    TaskTrackerAction {
+        command: String,
+        #[serde(default)]
+        task_list: Vec<TrackedTask>,
+    },
+    GlobAction {
+        pattern: String,
+    },
+    GrepAction {
+        pattern: String,
+    },
+    BrowserNavigateAction {
+        url: String,
+    },
+    FinishAction {
+        #[serde(default)]
+        message: String,
+    },
+    ThinkAction {
+        thought: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackedTask {
+    title: String,
+    status: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_observations_fill_in_the_result() {
+        let event: OpenHandsEvent = serde_json::from_str(
+            r#"{"kind":"ActionEvent","source":"agent","thought":[{"type":"text","text":"Run the tests"}],"action":{"kind":"TerminalAction","command":"cargo test"},"tool_name":"terminal","tool_call_id":"call_1"}"#,
+        )
+        .unwrap();
+        let OpenHandsEvent::ActionEvent {
+            thought, action, ..
+        } = event
+        else {
+            panic!("expected an action event");
+        };
+        assert_eq!(join_text(&thought), "Run the tests");
+
+        let parsed = serde_json::from_value(action.clone()).ok();
+        let (action_type, content) = tool_action("terminal", parsed, action, "/repo");
+        assert_eq!(content, "cargo test");
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "terminal".to_string(),
+                action_type,
+                status: ToolStatus::Created,
+            },
+            content,
+            metadata: None,
+        };
+
+        let observation = serde_json::json!({
+            "kind": "TerminalObservation",
+            "content": [{"type": "text", "text": "1 failed"}],
+            "metadata": {"exit_code": 101},
+        });
+        let NormalizedEntryType::ToolUse {
+            action_type:
+                ActionType::CommandRun {
+                    result: Some(result),
+                    ..
+                },
+            status: ToolStatus::Failed,
+            ..
+        } = observed_entry(entry, &observation).entry_type
+        else {
+            panic!("expected a failed command");
+        };
+        assert_eq!(result.output.as_deref(), Some("1 failed"));
+        assert!(matches!(
+            result.exit_status,
+            Some(CommandExitStatus::ExitCode { code: 101 })
+        ));
+    }
+
+    #[test]
+    fn file_edits_become_diffs_relative_to_the_worktree() {
+        let action = serde_json::json!({
+            "kind": "FileEditorAction",
+            "command": "str_replace",
+            "path": "/repo/src/lib.rs",
+            "old_str": "fn a() {}",
+            "new_str": "fn b() {}",
+        });
+        let parsed = serde_json::from_value(action.clone()).ok();
+        let (action_type, content) = tool_action("file_editor", parsed, action, "/repo");
+        assert_eq!(content, "src/lib.rs");
+        let ActionType::FileEdit { path, changes } = action_type else {
+            panic!("expected a file edit");
+        };
+        assert_eq!(path, "src/lib.rs");
+        assert!(matches!(
+            &changes[..],
+            [FileChange::Edit { unified_diff, .. }] if unified_diff.contains("+fn b() {}")
+        ));
+    }
+
+    #[test]
+    fn conversation_id_comes_from_the_cli_banner() {
+        assert_eq!(
+            conversation_id("Conversation ID: 3f2c9a1e0b7d4c2a9e5f6a7b8c9d0e1f").as_deref(),
+            Some("3f2c9a1e0b7d4c2a9e5f6a7b8c9d0e1f")
+        );
+        assert_eq!(conversation_id("Agent is working"), None);
+    }
+}
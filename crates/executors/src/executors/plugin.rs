@@ -0,0 +1,179 @@
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::AsyncWriteExt;
+use ts_rs::TS;
+use workspace_utils::msg_store::MsgStore;
+
+use crate::{
+    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    env::ExecutionEnv,
+    executors::{
+        AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+    },
+    logs::utils::EntryIndexProvider,
+};
+
+pub mod manifest;
+pub mod normalize_logs;
+
+use manifest::{PluginManifest, find_plugin, plugins_dir};
+use normalize_logs::normalize_logs;
+
+/// Version of the stdio protocol spoken with plugins
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Executor running a third-party agent installed as a plugin
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct Plugin {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+
+    #[serde(default)]
+    #[schemars(
+        title = "Plugin",
+        description = "Name of the plugin, as in its plugin.json; the first installed plugin is used when empty"
+    )]
+    pub plugin: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        title = "Options",
+        description = "Settings sent to the plugin with each run, which the plugin defines"
+    )]
+    pub options: Option<Value>,
+
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl Plugin {
+    pub fn new(plugin: String) -> Self {
+        Self {
+            append_prompt: AppendPrompt::default(),
+            plugin,
+            options: None,
+            cmd: CmdOverrides::default(),
+        }
+    }
+
+    pub fn manifest(&self) -> Result<(std::path::PathBuf, PluginManifest), ExecutorError> {
+        find_plugin(&self.plugin).ok_or_else(|| {
+            let dir = plugins_dir();
+            ExecutorError::PluginNotFound(if self.plugin.is_empty() {
+                format!("none installed in {}", dir.display())
+            } else {
+                format!("`{}` in {}", self.plugin, dir.display())
+            })
+        })
+    }
+
+    /// The command line starting the plugin, if it's installed
+    pub fn base_command(&self) -> Option<String> {
+        self.manifest()
+            .ok()
+            .map(|(dir, manifest)| manifest.command_in(&dir))
+    }
+
+    /// Starts the plugin and writes the run request to its stdin, which is
+    /// then closed. The plugin answers with notifications on stdout and
+    /// exits when the run is over.
+    async fn spawn_plugin(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: Option<&str>,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let (dir, manifest) = self.manifest()?;
+        if session_id.is_some() && !manifest.follow_ups {
+            return Err(ExecutorError::FollowUpNotSupported(format!(
+                "plugin `{}` can't continue sessions",
+                manifest.name
+            )));
+        }
+        let command_parts =
+            apply_overrides(CommandBuilder::new(manifest.command_in(&dir)), &self.cmd)
+                .build_initial()?;
+        let (program_path, args) = command_parts.into_resolved().await?;
+
+        let mut env = env.clone();
+        env.insert("VK_PLUGIN_PROTOCOL", PROTOCOL_VERSION.to_string());
+        let env = env.with_profile(&self.cmd);
+        let mut command = env.command(program_path, &args, current_dir);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command.group_spawn()?;
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "run",
+            "params": {
+                "protocol": PROTOCOL_VERSION,
+                "prompt": self.append_prompt.combine_prompt(prompt),
+                "cwd": current_dir,
+                "session_id": session_id,
+                "options": self.options,
+            },
+        });
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            stdin.write_all(format!("{request}\n").as_bytes()).await?;
+            stdin.shutdown().await?;
+        }
+
+        Ok(child.into())
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Plugin {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_plugin(current_dir, prompt, None, env).await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_plugin(current_dir, prompt, Some(session_id), env)
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
+        normalize_logs(
+            msg_store.clone(),
+            current_dir,
+            EntryIndexProvider::start_from(&msg_store),
+        );
+    }
+
+    // Plugins configure their own MCP servers, if any
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        if self.manifest().is_ok() {
+            AvailabilityInfo::InstallationFound
+        } else {
+            AvailabilityInfo::NotFound
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Finding the executor plugins installed in the asset directory.
+//!
+//! Each plugin is a directory under `executor-plugins` holding a
+//! `plugin.json` manifest that names the plugin and gives the command
+//! starting it. Directories without a usable manifest are listed with the
+//! reason, so a broken install shows up instead of silently missing.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::executors::plugin::PROTOCOL_VERSION;
+
+const MANIFEST_FILE: &str = "plugin.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct PluginManifest {
+    /// Lowercase letters, digits, `-` and `_`; the profile variant is this
+    /// name in SCREAMING_SNAKE_CASE
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Version of the stdio protocol the plugin speaks
+    pub protocol: u32,
+    /// Command line starting the plugin. A program starting with `./` is
+    /// relative to the plugin's directory.
+    pub command: String,
+    /// Whether the plugin can continue a session it reported
+    #[serde(default)]
+    pub follow_ups: bool,
+    /// Environment variables the plugin takes an API key from
+    #[serde(default)]
+    pub api_key_env: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DiscoveredPlugin {
+    /// Directory holding the manifest
+    pub dir: PathBuf,
+    pub manifest: Option<PluginManifest>,
+    /// Why the plugin can't be used
+    pub error: Option<String>,
+}
+
+pub fn plugins_dir() -> PathBuf {
+    workspace_utils::assets::asset_dir().join("executor-plugins")
+}
+
+impl PluginManifest {
+    fn validate(&self) -> Result<(), String> {
+        let valid_name = !self.name.is_empty()
+            && !self.name.eq_ignore_ascii_case("default")
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(format!(
+                "`{}` is not a valid plugin name; use lowercase letters, digits, `-` and `_`",
+                self.name
+            ));
+        }
+        if self.protocol != PROTOCOL_VERSION {
+            return Err(format!(
+                "The plugin speaks protocol {}, but this version of vibe-kanban speaks protocol {PROTOCOL_VERSION}",
+                self.protocol
+            ));
+        }
+        if self.command.trim().is_empty() {
+            return Err("The plugin has no command".to_string());
+        }
+        Ok(())
+    }
+
+    /// The command line starting the plugin installed in `dir`
+    pub fn command_in(&self, dir: &Path) -> String {
+        let command = self.command.trim();
+        let (program, rest) = command.split_once(' ').unwrap_or((command, ""));
+        let Some(relative) = program.strip_prefix("./") else {
+            return command.to_string();
+        };
+        let program = dir.join(relative).to_string_lossy().to_string();
+        let quoted = shlex::try_quote(&program)
+            .map(|quoted| quoted.into_owned())
+            .unwrap_or(program);
+        format!("{quoted} {rest}").trim_end().to_string()
+    }
+}
+
+fn parse_manifest(content: &str) -> Result<PluginManifest, String> {
+    let manifest = serde_json::from_str::<PluginManifest>(content)
+        .map_err(|e| format!("Invalid {MANIFEST_FILE}: {e}"))?;
+    manifest.validate()?;
+    Ok(manifest)
+}
+
+/// The plugins under `dir`, ordered by directory name. Of plugins sharing a
+/// name, the first one is used.
+pub fn discover_plugins_in(dir: &Path) -> Vec<DiscoveredPlugin> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    let mut names = HashSet::new();
+    dirs.into_iter()
+        .map(|dir| {
+            let manifest = fs::read_to_string(dir.join(MANIFEST_FILE))
+                .map_err(|e| format!("Can't read {MANIFEST_FILE}: {e}"))
+                .and_then(|content| parse_manifest(&content))
+                .and_then(|manifest| {
+                    if names.insert(manifest.name.clone()) {
+                        Ok(manifest)
+                    } else {
+                        Err(format!(
+                            "Another plugin is already named `{}`",
+                            manifest.name
+                        ))
+                    }
+                });
+            match manifest {
+                Ok(manifest) => DiscoveredPlugin {
+                    dir,
+                    manifest: Some(manifest),
+                    error: None,
+                },
+                Err(error) => DiscoveredPlugin {
+                    dir,
+                    manifest: None,
+                    error: Some(error),
+                },
+            }
+        })
+        .collect()
+}
+
+pub fn discover_plugins() -> Vec<DiscoveredPlugin> {
+    discover_plugins_in(&plugins_dir())
+}
+
+/// The usable plugin with this name and the directory it's installed in,
+/// or the first usable plugin when no name is given
+pub fn find_plugin(name: &str) -> Option<(PathBuf, PluginManifest)> {
+    discover_plugins()
+        .into_iter()
+        .filter_map(|plugin| Some((plugin.dir, plugin.manifest?)))
+        .find(|(_, manifest)| name.is_empty() || manifest.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifests_are_checked_and_relative_commands_resolved() {
+        let manifest = parse_manifest(
+            r#"{"name": "my-agent", "protocol": 1, "command": "./bin/agent --stdio"}"#,
+        )
+        .unwrap();
+        assert!(!manifest.follow_ups);
+        assert_eq!(
+            manifest.command_in(Path::new("/plugins/my-agent")),
+            "/plugins/my-agent/bin/agent --stdio"
+        );
+        assert_eq!(
+            manifest.command_in(Path::new("/Application Support/my-agent")),
+            "'/Application Support/my-agent/bin/agent' --stdio"
+        );
+
+        assert!(parse_manifest(r#"{"name": "My Agent", "protocol": 1, "command": "x"}"#).is_err());
+        assert!(parse_manifest(r#"{"name": "default", "protocol": 1, "command": "x"}"#).is_err());
+        assert!(parse_manifest(r#"{"name": "old", "protocol": 0, "command": "x"}"#).is_err());
+        assert!(parse_manifest(r#"{"name": "none", "protocol": 1}"#).is_err());
+    }
+}
@@ -0,0 +1,523 @@
+//! Turns the JSON-RPC notifications a plugin prints into normalized log
+//! entries.
+//!
+//! Plugins normalize their own output: they send conversation entries and
+//! tool calls rather than raw agent logs. What they send isn't trusted.
+//! Unknown methods are ignored, text is stripped of escape codes and cut to
+//! a maximum length, and file changes outside the worktree are shown without
+//! being presented as edits of the project.
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use futures::{StreamExt, future::ready};
+use serde::Deserialize;
+use serde_json::Value;
+use workspace_utils::{msg_store::MsgStore, path::make_path_relative};
+
+use crate::logs::{
+    ActionType, CommandExitStatus, CommandRunResult, FileChange, NormalizedEntry,
+    NormalizedEntryError, NormalizedEntryType, ToolResult, ToolStatus,
+    stderr_processor::normalize_stderr_logs,
+    utils::{
+        EntryIndexProvider,
+        patch::{add_normalized_entry, replace_normalized_entry},
+    },
+};
+
+/// Longest text kept from a single field a plugin sends
+const MAX_TEXT_LEN: usize = 64 * 1024;
+/// Tool calls and streamed messages remembered for later updates
+const MAX_OPEN_IDS: usize = 1000;
+
+pub fn normalize_logs(
+    msg_store: Arc<MsgStore>,
+    worktree_path: &Path,
+    entry_index_provider: EntryIndexProvider,
+) {
+    normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+    let worktree_path = worktree_path.to_string_lossy().to_string();
+    tokio::spawn(async move {
+        // Entries the plugin may update, by the id it gave them
+        let mut messages: HashMap<String, usize> = HashMap::new();
+        let mut tool_calls: HashMap<String, (usize, NormalizedEntry)> = HashMap::new();
+
+        let mut lines_stream = msg_store
+            .stdout_lines_stream()
+            .filter_map(|res| ready(res.ok()));
+
+        while let Some(line) = lines_stream.next().await {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let Ok(message) = serde_json::from_str::<RpcMessage>(trimmed) else {
+                add_normalized_entry(
+                    &msg_store,
+                    &entry_index_provider,
+                    text_entry(NormalizedEntryType::SystemMessage, trimmed),
+                );
+                continue;
+            };
+
+            match PluginEvent::parse(message) {
+                Ok(PluginEvent::Session { session_id }) => {
+                    msg_store.push_session_id(clean_text(&session_id));
+                }
+                Ok(PluginEvent::Message(params)) => {
+                    let entry = text_entry(params.kind.entry_type(), &params.text);
+                    match params.id {
+                        Some(id) if messages.contains_key(&id) => {
+                            replace_normalized_entry(&msg_store, messages[&id], entry);
+                        }
+                        id => {
+                            let index =
+                                add_normalized_entry(&msg_store, &entry_index_provider, entry);
+                            if let Some(id) = id
+                                && messages.len() < MAX_OPEN_IDS
+                            {
+                                messages.insert(id, index);
+                            }
+                        }
+                    }
+                }
+                Ok(PluginEvent::ToolCall(params)) => {
+                    let id = params.id.clone();
+                    let entry = tool_call_entry(params, &worktree_path);
+                    let index =
+                        add_normalized_entry(&msg_store, &entry_index_provider, entry.clone());
+                    if tool_calls.len() < MAX_OPEN_IDS {
+                        tool_calls.insert(id, (index, entry));
+                    }
+                }
+                Ok(PluginEvent::ToolResult(params)) => {
+                    if let Some((index, entry)) = tool_calls.remove(&params.id) {
+                        replace_normalized_entry(
+                            &msg_store,
+                            index,
+                            tool_result_entry(entry, params),
+                        );
+                    }
+                }
+                Ok(PluginEvent::Failed { message }) => {
+                    add_normalized_entry(
+                        &msg_store,
+                        &entry_index_provider,
+                        text_entry(
+                            NormalizedEntryType::ErrorMessage {
+                                error_type: NormalizedEntryError::Other,
+                            },
+                            &message,
+                        ),
+                    );
+                }
+                Ok(PluginEvent::Ignored) => {}
+                Err(error) => {
+                    add_normalized_entry(
+                        &msg_store,
+                        &entry_index_provider,
+                        text_entry(NormalizedEntryType::SystemMessage, &error),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// A JSON-RPC notification from the plugin, or the response to the run
+/// request
+#[derive(Debug, Deserialize)]
+struct RpcMessage {
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Debug)]
+enum PluginEvent {
+    Session {
+        session_id: String,
+    },
+    Message(MessageParams),
+    ToolCall(ToolCallParams),
+    ToolResult(ToolResultParams),
+    /// The plugin answered the run request with an error
+    Failed {
+        message: String,
+    },
+    Ignored,
+}
+
+impl PluginEvent {
+    fn parse(message: RpcMessage) -> Result<Self, String> {
+        let Some(method) = message.method else {
+            return Ok(match message.error {
+                Some(error) => PluginEvent::Failed {
+                    message: error.message,
+                },
+                None => PluginEvent::Ignored,
+            });
+        };
+        let params = message.params;
+        let invalid = |e: serde_json::Error| format!("The plugin sent an invalid `{method}`: {e}");
+        match method.as_str() {
+            "session" => {
+                #[derive(Deserialize)]
+                struct SessionParams {
+                    session_id: String,
+                }
+                let params: SessionParams = serde_json::from_value(params).map_err(invalid)?;
+                Ok(PluginEvent::Session {
+                    session_id: params.session_id,
+                })
+            }
+            "message" => serde_json::from_value(params)
+                .map(PluginEvent::Message)
+                .map_err(invalid),
+            "tool_call" => serde_json::from_value(params)
+                .map(PluginEvent::ToolCall)
+                .map_err(invalid),
+            "tool_result" => serde_json::from_value(params)
+                .map(PluginEvent::ToolResult)
+                .map_err(invalid),
+            // Methods of later protocol versions
+            _ => Ok(PluginEvent::Ignored),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MessageKind {
+    Assistant,
+    Thinking,
+    System,
+    Error,
+    User,
+}
+
+impl MessageKind {
+    fn entry_type(&self) -> NormalizedEntryType {
+        match self {
+            MessageKind::Assistant => NormalizedEntryType::AssistantMessage,
+            MessageKind::Thinking => NormalizedEntryType::Thinking,
+            MessageKind::System => NormalizedEntryType::SystemMessage,
+            MessageKind::Error => NormalizedEntryType::ErrorMessage {
+                error_type: NormalizedEntryError::Other,
+            },
+            MessageKind::User => NormalizedEntryType::UserMessage,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageParams {
+    /// A later message with the same id replaces this one, for streaming
+    #[serde(default)]
+    id: Option<String>,
+    kind: MessageKind,
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolCallParams {
+    id: String,
+    tool: String,
+    #[serde(default)]
+    action: Option<PluginAction>,
+    #[serde(default)]
+    arguments: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PluginAction {
+    Command {
+        command: String,
+    },
+    FileRead {
+        path: String,
+    },
+    FileEdit {
+        path: String,
+        /// Unified diff of the change
+        #[serde(default)]
+        diff: Option<String>,
+        /// The whole new content, for a written file
+        #[serde(default)]
+        content: Option<String>,
+    },
+    Search {
+        query: String,
+    },
+    WebFetch {
+        url: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ResultStatus {
+    Success,
+    Failed,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolResultParams {
+    id: String,
+    status: ResultStatus,
+    #[serde(default)]
+    output: Option<String>,
+    #[serde(default)]
+    exit_code: Option<i32>,
+}
+
+/// Text without escape codes, at most `MAX_TEXT_LEN` bytes long
+fn clean_text(text: &str) -> String {
+    let mut text = strip_ansi_escapes::strip_str(text);
+    if text.len() > MAX_TEXT_LEN {
+        let mut end = MAX_TEXT_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("\n… (truncated)");
+    }
+    text
+}
+
+fn text_entry(entry_type: NormalizedEntryType, text: &str) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: None,
+        entry_type,
+        content: clean_text(text),
+        metadata: None,
+    }
+}
+
+/// The path relative to the worktree, or None for a path outside it
+fn worktree_relative(path: &str, worktree_path: &str) -> Option<String> {
+    let relative = make_path_relative(path, worktree_path);
+    let escapes = Path::new(&relative).is_absolute()
+        || Path::new(&relative)
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir));
+    (!escapes).then_some(relative)
+}
+
+fn tool_call_entry(params: ToolCallParams, worktree_path: &str) -> NormalizedEntry {
+    let tool = clean_text(&params.tool);
+    let generic = |arguments: Option<Value>| ActionType::Tool {
+        tool_name: tool.clone(),
+        arguments,
+        result: None,
+    };
+    let (action_type, content) = match params.action {
+        Some(PluginAction::Command { command }) => {
+            let command = clean_text(&command);
+            (
+                ActionType::CommandRun {
+                    command: command.clone(),
+                    result: None,
+                },
+                command,
+            )
+        }
+        Some(PluginAction::FileRead { path }) => {
+            let path = make_path_relative(&clean_text(&path), worktree_path);
+            (ActionType::FileRead { path: path.clone() }, path)
+        }
+        Some(PluginAction::FileEdit {
+            path,
+            diff,
+            content,
+        }) => {
+            let path = clean_text(&path);
+            match worktree_relative(&path, worktree_path) {
+                Some(relative) => {
+                    let changes = match (diff, content) {
+                        (Some(diff), _) => vec![FileChange::Edit {
+                            unified_diff: clean_text(&diff),
+                            has_line_numbers: true,
+                        }],
+                        (None, Some(content)) => vec![FileChange::Write {
+                            content: clean_text(&content),
+                        }],
+                        (None, None) => Vec::new(),
+                    };
+                    (
+                        ActionType::FileEdit {
+                            path: relative.clone(),
+                            changes,
+                        },
+                        relative,
+                    )
+                }
+                None => (
+                    ActionType::Other {
+                        description: format!("Edit outside the worktree: {path}"),
+                    },
+                    path,
+                ),
+            }
+        }
+        Some(PluginAction::Search { query }) => {
+            let query = clean_text(&query);
+            (
+                ActionType::Search {
+                    query: query.clone(),
+                },
+                query,
+            )
+        }
+        Some(PluginAction::WebFetch { url }) => {
+            let url = clean_text(&url);
+            (ActionType::WebFetch { url: url.clone() }, url)
+        }
+        Some(PluginAction::Other) | None => (generic(params.arguments), tool.clone()),
+    };
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name: tool,
+            action_type,
+            status: ToolStatus::Created,
+        },
+        content,
+        metadata: None,
+    }
+}
+
+fn tool_result_entry(entry: NormalizedEntry, params: ToolResultParams) -> NormalizedEntry {
+    let NormalizedEntryType::ToolUse {
+        tool_name,
+        action_type,
+        ..
+    } = entry.entry_type
+    else {
+        return entry;
+    };
+    let output = params.output.as_deref().map(clean_text);
+    let action_type = match action_type {
+        ActionType::CommandRun { command, .. } => ActionType::CommandRun {
+            command,
+            result: Some(CommandRunResult {
+                exit_status: params
+                    .exit_code
+                    .map(|code| CommandExitStatus::ExitCode { code }),
+                output,
+            }),
+        },
+        ActionType::Tool {
+            tool_name,
+            arguments,
+            ..
+        } => ActionType::Tool {
+            tool_name,
+            arguments,
+            result: output.map(ToolResult::markdown),
+        },
+        other => other,
+    };
+    let status = match params.status {
+        ResultStatus::Success => ToolStatus::Success,
+        ResultStatus::Failed => ToolStatus::Failed,
+    };
+    NormalizedEntry {
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            status,
+        },
+        ..entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(line: &str) -> PluginEvent {
+        PluginEvent::parse(serde_json::from_str(line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn commands_are_finished_by_their_result() {
+        let PluginEvent::ToolCall(call) = event(
+            r#"{"jsonrpc":"2.0","method":"tool_call","params":{"id":"1","tool":"bash","action":{"kind":"command","command":"cargo test"}}}"#,
+        ) else {
+            panic!("expected a tool call");
+        };
+        let entry = tool_call_entry(call, "/repo");
+        assert_eq!(entry.content, "cargo test");
+
+        let PluginEvent::ToolResult(result) = event(
+            r#"{"jsonrpc":"2.0","method":"tool_result","params":{"id":"1","status":"failed","output":"\u001b[31mFAILED\u001b[0m","exit_code":101}}"#,
+        ) else {
+            panic!("expected a tool result");
+        };
+        let NormalizedEntryType::ToolUse {
+            action_type:
+                ActionType::CommandRun {
+                    result: Some(result),
+                    ..
+                },
+            status: ToolStatus::Failed,
+            ..
+        } = tool_result_entry(entry, result).entry_type
+        else {
+            panic!("expected a failed command");
+        };
+        assert_eq!(result.output.as_deref(), Some("FAILED"));
+    }
+
+    #[test]
+    fn untrusted_output_is_contained() {
+        let PluginEvent::ToolCall(call) = event(
+            r#"{"jsonrpc":"2.0","method":"tool_call","params":{"id":"2","tool":"edit","action":{"kind":"file_edit","path":"/etc/passwd","content":"x"}}}"#,
+        ) else {
+            panic!("expected a tool call");
+        };
+        assert!(matches!(
+            tool_call_entry(call, "/repo").entry_type,
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::Other { .. },
+                ..
+            }
+        ));
+        assert_eq!(
+            worktree_relative("/repo/src/lib.rs", "/repo").as_deref(),
+            Some("src/lib.rs")
+        );
+        assert_eq!(worktree_relative("../other/lib.rs", "/repo"), None);
+
+        let long = "é".repeat(MAX_TEXT_LEN);
+        assert!(clean_text(&long).len() < MAX_TEXT_LEN + 20);
+
+        assert!(matches!(
+            event(r#"{"jsonrpc":"2.0","method":"progress","params":{}}"#),
+            PluginEvent::Ignored
+        ));
+        assert!(matches!(
+            event(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"no model"}}"#),
+            PluginEvent::Failed { .. }
+        ));
+        assert!(
+            PluginEvent::parse(
+                serde_json::from_str(r#"{"jsonrpc":"2.0","method":"message","params":{}}"#)
+                    .unwrap()
+            )
+            .is_err()
+        );
+    }
+}
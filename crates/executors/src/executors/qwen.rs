@@ -33,8 +33,12 @@ pub struct QwenCode {
 }
 
 impl QwenCode {
+    pub fn base_command() -> &'static str {
+        "npx -y @qwen-code/qwen-code@0.2.1"
+    }
+
     fn build_command_builder(&self) -> CommandBuilder {
-        let mut builder = CommandBuilder::new("npx -y @qwen-code/qwen-code@0.2.1");
+        let mut builder = CommandBuilder::new(Self::base_command());
 
         if self.yolo.unwrap_or(false) {
             builder = builder.extend_params(["--yolo"]);
@@ -0,0 +1,156 @@
+//! Why a coding agent run failed, as far as the end of its output tells.
+//!
+//! Rate limits, network trouble and crashes of the CLI itself tend to go away
+//! when the run is tried again a little later; anything else (a bad prompt,
+//! missing credentials, a spent budget) would most likely fail the same way.
+
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+use strum_macros::{Display, EnumString};
+use ts_rs::TS;
+
+/// Lines from the end of the output looked at. Earlier output is mostly the
+/// agent's own work, where tool output can mention anything.
+const TAIL_LINES: usize = 40;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum FailureKind {
+    /// The provider refused the request for now: rate limited or overloaded
+    RateLimit,
+    /// The provider couldn't be reached or the connection dropped
+    Network,
+    /// The CLI itself died: killed by a signal, a panic or a segfault
+    Crash,
+    Other,
+}
+
+/// Signs that trying again won't help, which win over everything else
+const PERMANENT: &[&str] = &[
+    "insufficient_quota",
+    "usage limit",
+    "credit balance",
+    "invalid api key",
+    "invalid_api_key",
+    "authentication_error",
+    "unauthorized",
+    "exceeded its memory limit",
+];
+
+const RATE_LIMIT: &[&str] = &[
+    "rate limit",
+    "rate_limit",
+    "ratelimit",
+    "too many requests",
+    "overloaded",
+    "resource_exhausted",
+    "resource exhausted",
+];
+
+const NETWORK: &[&str] = &[
+    "econnreset",
+    "econnrefused",
+    "etimedout",
+    "enotfound",
+    "eai_again",
+    "socket hang up",
+    "network error",
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "connection error",
+    "stream disconnected",
+    "fetch failed",
+    "bad gateway",
+    "service unavailable",
+    "gateway timeout",
+    "request timed out",
+];
+
+const CRASH: &[&str] = &[
+    "panicked at",
+    "segmentation fault",
+    "core dumped",
+    "sigsegv",
+    "sigabrt",
+    "sigbus",
+];
+
+/// Classify a failed run from its combined output and exit code. The exit code
+/// is -1 (or missing) when the process was killed by a signal.
+pub fn classify(output: &str, exit_code: Option<i64>) -> FailureKind {
+    let lines: Vec<&str> = output.lines().collect();
+    let tail = lines[lines.len().saturating_sub(TAIL_LINES)..]
+        .join("\n")
+        .to_lowercase();
+    let mentions = |patterns: &[&str]| patterns.iter().any(|pattern| tail.contains(pattern));
+
+    if mentions(PERMANENT) || has_status(&tail, "401") || has_status(&tail, "403") {
+        FailureKind::Other
+    } else if mentions(RATE_LIMIT) || has_status(&tail, "429") || has_status(&tail, "529") {
+        FailureKind::RateLimit
+    } else if mentions(NETWORK)
+        || has_status(&tail, "502")
+        || has_status(&tail, "503")
+        || has_status(&tail, "504")
+    {
+        FailureKind::Network
+    } else if mentions(CRASH) || matches!(exit_code, None | Some(-1) | Some(134) | Some(139)) {
+        FailureKind::Crash
+    } else {
+        FailureKind::Other
+    }
+}
+
+/// Whether `code` appears as a number of its own, not as part of a longer one
+fn has_status(text: &str, code: &str) -> bool {
+    text.match_indices(code).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + code.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric())
+            && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_classified_by_the_end_of_the_output() {
+        assert_eq!(
+            classify("API Error: 529 {\"type\":\"overloaded_error\"}", Some(1)),
+            FailureKind::RateLimit
+        );
+        assert_eq!(
+            classify("error: status 429 Too Many Requests", Some(1)),
+            FailureKind::RateLimit
+        );
+        assert_eq!(
+            classify("stream disconnected before completion", Some(1)),
+            FailureKind::Network
+        );
+        assert_eq!(
+            classify("thread 'main' panicked at src/main.rs:3:5", Some(101)),
+            FailureKind::Crash
+        );
+        assert_eq!(classify("", Some(-1)), FailureKind::Crash);
+        assert_eq!(
+            classify("429 Too Many Requests: insufficient_quota", Some(1)),
+            FailureKind::Other
+        );
+        assert_eq!(
+            classify("error: 14290 tests failed", Some(1)),
+            FailureKind::Other
+        );
+
+        let mut output = "connection refused\n".to_string();
+        output.push_str(&"test output\n".repeat(TAIL_LINES));
+        assert_eq!(classify(&output, Some(1)), FailureKind::Other);
+    }
+}
@@ -0,0 +1,347 @@
+//! Checks of whether an executor can start, for diagnosing an agent that
+//! won't before creating attempts with it.
+//!
+//! The CLI check runs the configured base command with `--version`, in the
+//! profile's environment, which is as far as an executor can be started
+//! without giving it a task. Credentials count as present when the CLI's
+//! login file exists or one of the API key variables it reads is set.
+
+use std::{process::Stdio, time::Duration};
+
+use serde::Serialize;
+use serde_json::Value;
+use ts_rs::TS;
+
+use crate::{
+    command::{CmdOverrides, CommandBuilder},
+    env::ExecutionEnv,
+    executors::{
+        AvailabilityInfo, BaseCodingAgent, CodingAgent, ExecutorError, StandardCodingAgentExecutor,
+        amp::Amp, claude, codex::Codex, copilot::Copilot, cursor::CursorAgent, droid::Droid,
+        gemini::Gemini, goose::Goose, opencode::Opencode, openhands::Openhands, qwen::QwenCode,
+    },
+    mcp_config::read_agent_config,
+};
+
+/// Long enough for `npx` to download the package on the first run
+const VERSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutorHealth {
+    pub executor: BaseCodingAgent,
+    pub cli: CliCheck,
+    pub auth: AuthCheck,
+    pub mcp_config: McpConfigCheck,
+    /// Nothing found that keeps the executor from starting
+    pub ready: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "status", rename_all = "snake_case")]
+#[ts(tag = "status", rename_all = "snake_case")]
+pub enum CliCheck {
+    /// The CLI started, printing this version
+    Ok {
+        version: Option<String>,
+    },
+    NotInstalled {
+        program: String,
+    },
+    /// The CLI started but exited with an error
+    Failed {
+        error: String,
+    },
+    TimedOut,
+    /// The executor doesn't run a CLI
+    NotNeeded,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "status", rename_all = "snake_case")]
+#[ts(tag = "status", rename_all = "snake_case")]
+pub enum AuthCheck {
+    /// The CLI's login was saved at this time (seconds since the epoch)
+    LoggedIn {
+        last_auth_timestamp: i64,
+    },
+    ApiKey {
+        variable: String,
+    },
+    /// The CLI's settings exist, which may hold the credentials
+    ConfigFound,
+    Missing,
+    /// The executor needs no credentials as configured
+    NotNeeded,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "status", rename_all = "snake_case")]
+#[ts(tag = "status", rename_all = "snake_case")]
+pub enum McpConfigCheck {
+    Valid {
+        path: String,
+        servers: usize,
+    },
+    /// No config file yet, which the CLI treats as no servers
+    Missing {
+        path: String,
+    },
+    Invalid {
+        path: String,
+        error: String,
+    },
+    /// The executor's MCP servers can't be configured here
+    Unsupported,
+}
+
+impl ExecutorHealth {
+    pub async fn check(agent: &CodingAgent) -> Self {
+        let (cli, mcp_config) = tokio::join!(check_cli(agent), check_mcp_config(agent));
+        let auth = check_auth(agent);
+        let ready = matches!(cli, CliCheck::Ok { .. } | CliCheck::NotNeeded)
+            && !matches!(auth, AuthCheck::Missing)
+            && !matches!(mcp_config, McpConfigCheck::Invalid { .. });
+        Self {
+            executor: BaseCodingAgent::from(agent),
+            cli,
+            auth,
+            mcp_config,
+            ready,
+        }
+    }
+}
+
+impl CodingAgent {
+    fn cmd_overrides(&self) -> &CmdOverrides {
+        match self {
+            CodingAgent::ClaudeCode(agent) => &agent.cmd,
+            CodingAgent::Amp(agent) => &agent.cmd,
+            CodingAgent::Gemini(agent) => &agent.cmd,
+            CodingAgent::Codex(agent) => &agent.cmd,
+            CodingAgent::Opencode(agent) => &agent.cmd,
+            CodingAgent::CursorAgent(agent) => &agent.cmd,
+            CodingAgent::QwenCode(agent) => &agent.cmd,
+            CodingAgent::Copilot(agent) => &agent.cmd,
+            CodingAgent::Droid(agent) => &agent.cmd,
+            CodingAgent::Openhands(agent) => &agent.cmd,
+            CodingAgent::ApiAgent(agent) => &agent.cmd,
+            CodingAgent::Goose(agent) => &agent.cmd,
+            CodingAgent::Plugin(agent) => &agent.cmd,
+        }
+    }
+
+    /// The command the executor's CLI is started with, before its arguments
+    fn cli_base_command(&self) -> Option<String> {
+        let base = match self {
+            CodingAgent::ClaudeCode(agent) => {
+                claude::base_command(agent.claude_code_router.unwrap_or(false))
+            }
+            CodingAgent::Amp(_) => Amp::base_command(),
+            CodingAgent::Gemini(_) => Gemini::base_command(),
+            CodingAgent::Codex(_) => Codex::base_command(),
+            CodingAgent::Opencode(_) => Opencode::base_command(),
+            CodingAgent::CursorAgent(_) => CursorAgent::base_command(),
+            CodingAgent::QwenCode(_) => QwenCode::base_command(),
+            CodingAgent::Copilot(_) => Copilot::base_command(),
+            CodingAgent::Droid(_) => Droid::base_command(),
+            CodingAgent::Openhands(_) => Openhands::base_command(),
+            CodingAgent::Goose(_) => Goose::base_command(),
+            CodingAgent::ApiAgent(_) => return None,
+            CodingAgent::Plugin(agent) => return agent.base_command(),
+        };
+        Some(base.to_string())
+    }
+
+    /// Variables the CLI takes an API key from instead of a login
+    fn api_key_variables(&self) -> Vec<String> {
+        let names: &[&str] = match self {
+            CodingAgent::ClaudeCode(_) => &["ANTHROPIC_API_KEY"],
+            CodingAgent::Amp(_) => &["AMP_API_KEY"],
+            CodingAgent::Gemini(_) => &["GEMINI_API_KEY", "GOOGLE_API_KEY"],
+            CodingAgent::Codex(_) => &["OPENAI_API_KEY", "CODEX_API_KEY"],
+            CodingAgent::Opencode(_) | CodingAgent::Goose(_) => {
+                &["ANTHROPIC_API_KEY", "OPENAI_API_KEY", "GOOGLE_API_KEY"]
+            }
+            CodingAgent::CursorAgent(_) => &["CURSOR_API_KEY"],
+            CodingAgent::QwenCode(_) => &["OPENAI_API_KEY", "DASHSCOPE_API_KEY"],
+            CodingAgent::Copilot(_) => &["GH_TOKEN", "GITHUB_TOKEN"],
+            CodingAgent::Droid(_) => &["FACTORY_API_KEY"],
+            CodingAgent::Openhands(_) => &["LLM_API_KEY"],
+            CodingAgent::ApiAgent(agent) => {
+                return agent.api_key_env.iter().cloned().collect();
+            }
+            CodingAgent::Plugin(agent) => {
+                return agent
+                    .manifest()
+                    .map(|(_, manifest)| manifest.api_key_env)
+                    .unwrap_or_default();
+            }
+        };
+        names.iter().map(|name| name.to_string()).collect()
+    }
+}
+
+async fn check_cli(agent: &CodingAgent) -> CliCheck {
+    let Some(base) = agent.cli_base_command() else {
+        return match agent {
+            // The plugin isn't installed
+            CodingAgent::Plugin(plugin) => CliCheck::NotInstalled {
+                program: plugin.plugin.clone(),
+            },
+            _ => CliCheck::NotNeeded,
+        };
+    };
+    let cmd = agent.cmd_overrides();
+    let base = cmd.base_command_override.clone().unwrap_or(base);
+    let parts = match CommandBuilder::new(base).build_follow_up(&["--version".to_string()]) {
+        Ok(parts) => parts,
+        Err(e) => {
+            return CliCheck::Failed {
+                error: e.to_string(),
+            };
+        }
+    };
+    let (program, args) = match parts.into_resolved().await {
+        Ok(resolved) => resolved,
+        Err(ExecutorError::ExecutableNotFound { program }) => {
+            return CliCheck::NotInstalled { program };
+        }
+        Err(e) => {
+            return CliCheck::Failed {
+                error: e.to_string(),
+            };
+        }
+    };
+
+    let env = ExecutionEnv::new().with_profile(cmd);
+    let mut command = env.command(program, &args, &std::env::temp_dir());
+    command
+        .kill_on_drop(true)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let output = match tokio::time::timeout(VERSION_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            return CliCheck::Failed {
+                error: e.to_string(),
+            };
+        }
+        Err(_) => return CliCheck::TimedOut,
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.success() {
+        CliCheck::Ok {
+            version: first_line(&stdout).or_else(|| first_line(&stderr)),
+        }
+    } else {
+        CliCheck::Failed {
+            error: last_line(&stderr)
+                .or_else(|| last_line(&stdout))
+                .unwrap_or_else(|| format!("exited with {}", output.status)),
+        }
+    }
+}
+
+fn first_line(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn last_line(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn check_auth(agent: &CodingAgent) -> AuthCheck {
+    let variables = agent.api_key_variables();
+    // These executors say which keys they need, so none means none
+    let keys_listed = matches!(agent, CodingAgent::ApiAgent(_) | CodingAgent::Plugin(_));
+    if keys_listed && variables.is_empty() {
+        return AuthCheck::NotNeeded;
+    }
+    let profile_env = agent.cmd_overrides().env.as_ref();
+    let is_set = |name: &str| {
+        profile_env
+            .and_then(|env| env.get(name))
+            .is_some_and(|value| !value.is_empty())
+            || std::env::var(name).is_ok_and(|value| !value.is_empty())
+    };
+
+    match agent.get_availability_info() {
+        AvailabilityInfo::LoginDetected {
+            last_auth_timestamp,
+        } => AuthCheck::LoggedIn {
+            last_auth_timestamp,
+        },
+        availability => match variables.into_iter().find(|name| is_set(name)) {
+            Some(variable) => AuthCheck::ApiKey { variable },
+            None if availability.is_available() && !keys_listed => AuthCheck::ConfigFound,
+            None => AuthCheck::Missing,
+        },
+    }
+}
+
+async fn check_mcp_config(agent: &CodingAgent) -> McpConfigCheck {
+    let Some(path) = agent.default_mcp_config_path() else {
+        return McpConfigCheck::Unsupported;
+    };
+    let shown = path.to_string_lossy().to_string();
+    if !path.exists() {
+        return McpConfigCheck::Missing { path: shown };
+    }
+    let mcp_config = agent.get_mcp_config();
+    match read_agent_config(&path, &mcp_config).await {
+        Ok(config) => match servers_at(&config, &mcp_config.servers_path) {
+            Ok(servers) => McpConfigCheck::Valid {
+                path: shown,
+                servers,
+            },
+            Err(error) => McpConfigCheck::Invalid { path: shown, error },
+        },
+        Err(e) => McpConfigCheck::Invalid {
+            path: shown,
+            error: e.to_string(),
+        },
+    }
+}
+
+/// How many servers the config lists under `servers_path`, where a missing
+/// key means none
+fn servers_at(config: &Value, servers_path: &[String]) -> Result<usize, String> {
+    let mut current = config;
+    for key in servers_path {
+        match current.get(key) {
+            Some(next) => current = next,
+            None => return Ok(0),
+        }
+    }
+    current
+        .as_object()
+        .map(|servers| servers.len())
+        .ok_or_else(|| format!("`{}` is not an object", servers_path.join(".")))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn servers_are_counted_under_the_agents_key() {
+        let path = vec!["mcpServers".to_string()];
+        let config = json!({"mcpServers": {"a": {"command": "x"}, "b": {"url": "y"}}});
+        assert_eq!(servers_at(&config, &path), Ok(2));
+        assert_eq!(servers_at(&json!({}), &path), Ok(0));
+        assert!(servers_at(&json!({"mcpServers": []}), &path).is_err());
+    }
+}
@@ -3,7 +3,11 @@ pub mod approvals;
 pub mod command;
 pub mod env;
 pub mod executors;
+pub mod failure;
+pub mod health;
 pub mod logs;
 pub mod mcp_config;
 pub mod profile;
+pub mod prompt_additions;
 pub mod stdout_dup;
+pub mod test_output;
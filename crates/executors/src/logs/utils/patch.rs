@@ -1,6 +1,7 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
-use json_patch::Patch;
+use chrono::{SecondsFormat, Utc};
+use json_patch::{Patch, PatchOperation as JsonPatchOperation};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_value, json, to_value};
 use ts_rs::TS;
@@ -43,6 +44,7 @@ pub struct ConversationPatch;
 impl ConversationPatch {
     /// Create an ADD patch for a new conversation entry at the given index
     pub fn add_normalized_entry(entry_index: usize, entry: NormalizedEntry) -> Patch {
+        let entry = stamped(entry);
         let patch_entry = PatchEntry {
             op: PatchOperation::Add,
             path: format!("/entries/{entry_index}"),
@@ -107,6 +109,7 @@ impl ConversationPatch {
 
     /// Create a REPLACE patch for updating an existing conversation entry at the given index
     pub fn replace(entry_index: usize, entry: NormalizedEntry) -> Patch {
+        let entry = stamped(entry);
         let patch_entry = PatchEntry {
             op: PatchOperation::Replace,
             path: format!("/entries/{entry_index}"),
@@ -125,6 +128,43 @@ impl ConversationPatch {
     }
 }
 
+/// The entry with the current time unless the executor gave it one
+fn stamped(mut entry: NormalizedEntry) -> NormalizedEntry {
+    entry
+        .timestamp
+        .get_or_insert_with(|| Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true));
+    entry
+}
+
+/// The conversation the patches build up, by entry index. An entry that is
+/// replaced keeps the time it was first added at.
+pub fn normalized_entries_from_patches<'a>(
+    patches: impl IntoIterator<Item = &'a Patch>,
+) -> BTreeMap<usize, NormalizedEntry> {
+    let mut entries: BTreeMap<usize, NormalizedEntry> = BTreeMap::new();
+    for patch in patches {
+        for operation in &patch.0 {
+            if let JsonPatchOperation::Remove(remove) = operation
+                && let Some(index) = remove
+                    .path
+                    .strip_prefix("/entries/")
+                    .and_then(|index| index.parse::<usize>().ok())
+            {
+                entries.remove(&index);
+            }
+        }
+        if let Some((index, mut entry)) = extract_normalized_entry_from_patch(patch) {
+            if let Some(previous) = entries.get(&index)
+                && previous.timestamp.is_some()
+            {
+                entry.timestamp = previous.timestamp.clone();
+            }
+            entries.insert(index, entry);
+        }
+    }
+    entries
+}
+
 /// Extract the entry index and `NormalizedEntry` from a JsonPatch if it contains one
 pub fn extract_normalized_entry_from_patch(patch: &Patch) -> Option<(usize, NormalizedEntry)> {
     let value = to_value(patch).ok()?;
@@ -12,6 +12,7 @@ use ts_rs::TS;
 
 use crate::executors::{
     AvailabilityInfo, BaseCodingAgent, CodingAgent, StandardCodingAgentExecutor,
+    plugin::{Plugin, manifest::discover_plugins},
 };
 
 /// Return the canonical form for variant keys.
@@ -206,6 +207,23 @@ impl ExecutorConfigs {
         *cache = Self::load();
     }
 
+    /// Add a PLUGIN variant for each installed plugin. They count as
+    /// defaults, so they aren't saved as overrides.
+    fn register_plugins(&mut self) {
+        let Some(profile) = self.executors.get_mut(&BaseCodingAgent::Plugin) else {
+            return;
+        };
+        for manifest in discover_plugins()
+            .into_iter()
+            .filter_map(|plugin| plugin.manifest)
+        {
+            profile
+                .configurations
+                .entry(canonical_variant_key(&manifest.name))
+                .or_insert_with(|| CodingAgent::Plugin(Plugin::new(manifest.name)));
+        }
+    }
+
     /// Load executor profiles from file or defaults
     pub fn load() -> Self {
         let profiles_path = workspace_utils::assets::profiles_path();
@@ -213,6 +231,7 @@ impl ExecutorConfigs {
         // Load defaults first
         let mut defaults = Self::from_defaults();
         defaults.canonicalise();
+        defaults.register_plugins();
 
         // Try to load user overrides
         let content = match fs::read_to_string(&profiles_path) {
@@ -245,10 +264,13 @@ impl ExecutorConfigs {
         let profiles_path = workspace_utils::assets::profiles_path();
         let mut defaults = Self::from_defaults();
         defaults.canonicalise();
+        defaults.register_plugins();
 
         // Canonicalise current config before computing overrides
         let mut self_clone = self.clone();
         self_clone.canonicalise();
+        // Plugins installed since the caller read the profiles aren't missing
+        self_clone.register_plugins();
 
         // Compute differences from defaults
         let overrides = Self::compute_overrides(&defaults, &self_clone)?;
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Project text put around the prompt of a coding agent run. It is kept on the
+/// run's action apart from the prompt, which stays what the user wrote.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+pub struct PromptAdditions {
+    pub prefix: Option<String>,
+    /// What earlier attempts at the task left, put between the prefix and the
+    /// prompt
+    pub context: Option<String>,
+    pub suffix: Option<String>,
+}
+
+impl PromptAdditions {
+    pub fn is_empty(&self) -> bool {
+        self.prefix.is_none() && self.context.is_none() && self.suffix.is_none()
+    }
+
+    /// The prompt the agent is given
+    pub fn apply(&self, prompt: &str) -> String {
+        let mut combined = String::new();
+        if let Some(prefix) = &self.prefix {
+            combined.push_str(prefix.trim_end());
+            combined.push_str("\n\n");
+        }
+        if let Some(context) = &self.context {
+            combined.push_str(context.trim_end());
+            combined.push_str("\n\n");
+        }
+        combined.push_str(prompt);
+        if let Some(suffix) = &self.suffix {
+            combined.push_str("\n\n");
+            combined.push_str(suffix.trim_start());
+        }
+        combined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn additions_surround_the_prompt() {
+        let additions = PromptAdditions {
+            prefix: Some("Follow the style guide.\n".to_string()),
+            context: Some("An earlier attempt left the tests failing.".to_string()),
+            suffix: Some("Use conventional commits.".to_string()),
+        };
+        assert_eq!(
+            additions.apply("Fix the login bug"),
+            "Follow the style guide.\n\nAn earlier attempt left the tests failing.\n\nFix the login bug\n\nUse conventional commits."
+        );
+        assert_eq!(
+            PromptAdditions::default().apply("Fix the login bug"),
+            "Fix the login bug"
+        );
+    }
+}
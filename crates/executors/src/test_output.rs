@@ -0,0 +1,241 @@
+//! Test counts read from the summary lines test runners print.
+//!
+//! `cargo test` prints a summary per test binary, which are added up for the
+//! last invocation in the output. For pytest and jest the last summary counts.
+//! When several runners ran, the one that finished last wins.
+
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+use strum_macros::{Display, EnumString};
+use ts_rs::TS;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum TestRunner {
+    Cargo,
+    Pytest,
+    Jest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct TestSummary {
+    pub runner: TestRunner,
+    pub passed: u32,
+    /// Failed tests, and for pytest also errors in setup or collection
+    pub failed: u32,
+    /// Skipped or ignored tests, and for pytest expected failures
+    pub skipped: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Counts {
+    passed: u32,
+    failed: u32,
+    skipped: u32,
+}
+
+impl Counts {
+    fn add(&mut self, other: Counts) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.skipped += other.skipped;
+    }
+}
+
+/// The tests the output reports on, if a runner's summary is in it
+pub fn parse(output: &str) -> Option<TestSummary> {
+    // Latest summary per runner, with the line it ended on
+    let mut cargo: Option<(usize, Counts)> = None;
+    let mut cargo_invocation_ended = false;
+    let mut pytest: Option<(usize, Counts)> = None;
+    let mut jest: Option<(usize, Counts)> = None;
+
+    for (index, line) in output.lines().enumerate() {
+        let line = strip_ansi(line);
+        let line = line.trim();
+        if line.starts_with("Finished ") && line.contains("test") {
+            // `cargo test` is about to run another set of binaries
+            cargo_invocation_ended = true;
+        } else if let Some(counts) = cargo_summary(line) {
+            match &mut cargo {
+                Some((last, total)) if !cargo_invocation_ended => {
+                    *last = index;
+                    total.add(counts);
+                }
+                _ => cargo = Some((index, counts)),
+            }
+            cargo_invocation_ended = false;
+        } else if let Some(counts) = pytest_summary(line) {
+            pytest = Some((index, counts));
+        } else if let Some(counts) = jest_summary(line) {
+            jest = Some((index, counts));
+        }
+    }
+
+    [
+        (TestRunner::Cargo, cargo),
+        (TestRunner::Pytest, pytest),
+        (TestRunner::Jest, jest),
+    ]
+    .into_iter()
+    .filter_map(|(runner, found)| found.map(|(index, counts)| (index, runner, counts)))
+    .max_by_key(|(index, _, _)| *index)
+    .map(|(_, runner, counts)| TestSummary {
+        runner,
+        passed: counts.passed,
+        failed: counts.failed,
+        skipped: counts.skipped,
+    })
+}
+
+/// `test result: FAILED. 3 passed; 1 failed; 2 ignored; 0 measured; ...`
+fn cargo_summary(line: &str) -> Option<Counts> {
+    let (_, items) = line.strip_prefix("test result: ")?.split_once(". ")?;
+    let mut counts = Counts::default();
+    for item in items.split(';').map(str::trim) {
+        if item.starts_with("finished in") {
+            break;
+        }
+        let (number, kind) = item.split_once(' ')?;
+        let number: u32 = number.parse().ok()?;
+        match kind {
+            "passed" => counts.passed += number,
+            "failed" => counts.failed += number,
+            "ignored" => counts.skipped += number,
+            "measured" | "filtered out" => {}
+            _ => return None,
+        }
+    }
+    Some(counts)
+}
+
+/// `===== 1 failed, 10 passed, 2 skipped, 3 warnings in 0.42s =====`, the
+/// equal signs missing with `-q`
+fn pytest_summary(line: &str) -> Option<Counts> {
+    let line = line.trim_matches('=').trim();
+    let (items, duration) = line.rsplit_once(" in ")?;
+    if !duration.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut counts = Counts::default();
+    for item in items.split(", ") {
+        let (number, kind) = item.split_once(' ')?;
+        let number: u32 = number.parse().ok()?;
+        match kind {
+            "passed" | "xpassed" => counts.passed += number,
+            "failed" | "error" | "errors" => counts.failed += number,
+            "skipped" | "xfailed" => counts.skipped += number,
+            "warning" | "warnings" | "deselected" | "rerun" => {}
+            _ => return None,
+        }
+    }
+    Some(counts)
+}
+
+/// `Tests:       1 failed, 2 skipped, 10 passed, 13 total`
+fn jest_summary(line: &str) -> Option<Counts> {
+    let items = line.strip_prefix("Tests:")?;
+    let mut counts = Counts::default();
+    for item in items.split(',').map(str::trim) {
+        let (number, kind) = item.split_once(' ')?;
+        let number: u32 = number.parse().ok()?;
+        match kind {
+            "passed" => counts.passed += number,
+            "failed" => counts.failed += number,
+            "skipped" | "todo" => counts.skipped += number,
+            "total" => {}
+            _ => return None,
+        }
+    }
+    Some(counts)
+}
+
+/// The line without the color codes runners add when they think they write
+/// to a terminal
+pub fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip `ESC [ params final-byte`
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(runner: TestRunner, passed: u32, failed: u32, skipped: u32) -> Option<TestSummary> {
+        Some(TestSummary {
+            runner,
+            passed,
+            failed,
+            skipped,
+        })
+    }
+
+    #[test]
+    fn summaries_of_each_runner_are_read() {
+        let cargo = "\
+   Compiling app v0.1.0
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 3.1s
+     Running unittests src/lib.rs
+test result: FAILED. 4 passed; 1 failed; 2 ignored; 0 measured; 0 filtered out; finished in 0.01s
+     Running tests/api.rs
+test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.20s
+";
+        assert_eq!(parse(cargo), summary(TestRunner::Cargo, 7, 1, 2));
+
+        let rerun = format!(
+            "{cargo}    Finished `test` profile [unoptimized] target(s) in 0.1s\n\
+             test result: ok. 8 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s\n"
+        );
+        assert_eq!(parse(&rerun), summary(TestRunner::Cargo, 8, 0, 0));
+
+        assert_eq!(
+            parse(
+                "\u{1b}[31m==== 2 failed, 10 passed, 1 skipped, 3 warnings in 0.42s ====\u{1b}[0m"
+            ),
+            summary(TestRunner::Pytest, 10, 2, 1)
+        );
+        assert_eq!(
+            parse("12 passed in 1.03s"),
+            summary(TestRunner::Pytest, 12, 0, 0)
+        );
+        assert_eq!(
+            parse(
+                "Test Suites: 1 failed, 3 passed, 4 total\n\
+                 Tests:       1 failed, 2 skipped, 10 passed, 13 total\n\
+                 Time:        2.1 s"
+            ),
+            summary(TestRunner::Jest, 10, 1, 2)
+        );
+    }
+
+    #[test]
+    fn the_runner_that_finished_last_wins() {
+        let output = "\
+test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+Tests:       3 passed, 3 total
+";
+        assert_eq!(parse(output), summary(TestRunner::Jest, 3, 0, 0));
+        assert_eq!(parse("Checked in 2 files"), None);
+        assert_eq!(parse("cargo build finished in 3s"), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Package caches shared by the containers of a project's attempts.
+//!
+//! Each enabled cache is a directory below `{root}/{project_id}`, bind-mounted
+//! into Docker and devcontainer workspaces at its host path and pointed to by
+//! the tool's cache variable. With the Kubernetes backend the caches live on
+//! the shared claim, which every pod already mounts.
+//!
+//! Running executions hold the project's caches shared. Emptying a cache, on
+//! request or once it outgrows the project's limit, needs it exclusively, so a
+//! cache is never removed under an install that is writing to it.
+
+use std::{
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use db::models::project_settings::{SharedCache, SharedCacheSettings};
+use executors::env::ExecutionEnv;
+use services::services::{container::SharedCacheUsage, workspace_janitor::directory_size};
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
+use uuid::Uuid;
+
+use crate::kubernetes;
+
+static CACHE_LOCKS: LazyLock<Mutex<HashMap<(Uuid, SharedCache), Arc<RwLock<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Caches held by running executions, by execution id
+static LEASES: LazyLock<Mutex<HashMap<Uuid, Lease>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct Lease {
+    project_id: Uuid,
+    _guards: Vec<OwnedRwLockReadGuard<()>>,
+}
+
+fn root() -> PathBuf {
+    match kubernetes::config() {
+        Some(config) => config.shared_mount.join(".vk-caches"),
+        None => utils::assets::asset_dir().join("caches"),
+    }
+}
+
+fn cache_dir(project_id: Uuid, cache: SharedCache) -> PathBuf {
+    let name = match cache {
+        SharedCache::Cargo => "cargo",
+        SharedCache::Npm => "npm",
+        SharedCache::Pnpm => "pnpm",
+        SharedCache::Yarn => "yarn",
+        SharedCache::Pip => "pip",
+    };
+    root().join(project_id.to_string()).join(name)
+}
+
+/// Variable that points the tool at its cache
+fn env_var(cache: SharedCache) -> &'static str {
+    match cache {
+        // Toolchains stay under RUSTUP_HOME, so only what cargo downloads
+        // itself ends up in the cache
+        SharedCache::Cargo => "CARGO_HOME",
+        SharedCache::Npm => "npm_config_cache",
+        SharedCache::Pnpm => "npm_config_store_dir",
+        SharedCache::Yarn => "YARN_CACHE_FOLDER",
+        SharedCache::Pip => "PIP_CACHE_DIR",
+    }
+}
+
+fn lock(project_id: Uuid, cache: SharedCache) -> Arc<RwLock<()>> {
+    CACHE_LOCKS
+        .lock()
+        .unwrap()
+        .entry((project_id, cache))
+        .or_default()
+        .clone()
+}
+
+/// Create the project's cache directories and point `env` at them, returning
+/// the directories to mount
+pub fn prepare(
+    project_id: Uuid,
+    settings: &SharedCacheSettings,
+    env: &mut ExecutionEnv,
+) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for cache in &settings.caches {
+        let dir = cache_dir(project_id, *cache);
+        std::fs::create_dir_all(&dir)?;
+        env.insert(env_var(*cache), dir.to_string_lossy());
+        dirs.push(dir);
+    }
+    Ok(dirs)
+}
+
+/// Hold the project's caches until the execution is released
+pub async fn acquire(execution_id: Uuid, project_id: Uuid, settings: &SharedCacheSettings) {
+    if settings.caches.is_empty() {
+        return;
+    }
+    let mut guards = Vec::new();
+    for cache in &settings.caches {
+        guards.push(lock(project_id, *cache).read_owned().await);
+    }
+    LEASES.lock().unwrap().insert(
+        execution_id,
+        Lease {
+            project_id,
+            _guards: guards,
+        },
+    );
+}
+
+/// Let go of the execution's caches, emptying those that outgrew the limit
+pub async fn release(execution_id: Uuid, settings: &SharedCacheSettings) {
+    let Some(Lease { project_id, .. }) = LEASES.lock().unwrap().remove(&execution_id) else {
+        return;
+    };
+    let Some(max_mb) = settings.max_size_mb else {
+        return;
+    };
+    for cache in &settings.caches {
+        let dir = cache_dir(project_id, *cache);
+        let size = tokio::task::spawn_blocking(move || directory_size(&dir))
+            .await
+            .unwrap_or_default();
+        if size <= u64::from(max_mb) * 1024 * 1024 {
+            continue;
+        }
+        match clear(project_id, *cache).await {
+            Ok(true) => tracing::info!(
+                "Emptied {:?} cache of project {}, it had grown to {} bytes",
+                cache,
+                project_id,
+                size
+            ),
+            // Another execution still uses it and tries again when it ends
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to empty {:?} cache: {}", cache, e),
+        }
+    }
+}
+
+/// Empty a cache unless an execution is using it, returning whether it was
+/// emptied
+pub async fn clear(project_id: Uuid, cache: SharedCache) -> io::Result<bool> {
+    let Ok(_guard) = lock(project_id, cache).try_write_owned() else {
+        return Ok(false);
+    };
+    // The directory itself stays, containers have it mounted
+    let mut entries = match tokio::fs::read_dir(cache_dir(project_id, cache)).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+        Err(e) => return Err(e),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_dir() {
+            tokio::fs::remove_dir_all(entry.path()).await?;
+        } else {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+    Ok(true)
+}
+
+/// Remove all caches of a deleted project
+pub async fn remove_project(project_id: Uuid) -> io::Result<()> {
+    CACHE_LOCKS
+        .lock()
+        .unwrap()
+        .retain(|(id, _), _| *id != project_id);
+    match tokio::fs::remove_dir_all(root().join(project_id.to_string())).await {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+pub async fn usage(project_id: Uuid, settings: &SharedCacheSettings) -> Vec<SharedCacheUsage> {
+    let mut usage = Vec::new();
+    for cache in &settings.caches {
+        let dir = cache_dir(project_id, *cache);
+        let size_bytes = tokio::task::spawn_blocking(move || directory_size(&dir))
+            .await
+            .unwrap_or_default();
+        usage.push(SharedCacheUsage {
+            cache: *cache,
+            size_bytes,
+            in_use: lock(project_id, *cache).try_write().is_err(),
+        });
+    }
+    usage
+}
@@ -6,6 +6,8 @@ use nix::{
 };
 use services::services::container::ContainerError;
 #[cfg(unix)]
+use services::services::process_registry;
+#[cfg(unix)]
 use tokio::time::Duration;
 
 pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), ContainerError> {
@@ -13,6 +15,9 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
     #[cfg(unix)]
     {
         if let Some(pid) = child.inner().id() {
+            // Processes that moved to a group of their own lose their link to
+            // the execution once its group is gone, so find them first
+            let strays = process_registry::descendants(pid);
             let pgid = getpgid(Some(Pid::from_raw(pid as i32)))
                 .map_err(|e| ContainerError::KillFailed(std::io::Error::other(e)))?;
 
@@ -35,6 +40,7 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
                     break;
                 }
             }
+            process_registry::kill_descendants(&strays);
         }
     }
 
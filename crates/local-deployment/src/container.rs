@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io,
     path::{Path, PathBuf},
     sync::Arc,
@@ -12,17 +12,34 @@ use command_group::AsyncGroupChild;
 use db::{
     DBService,
     models::{
+        agent_review::{AgentReview, AgentReviewStatus},
         coding_agent_turn::CodingAgentTurn,
+        dry_run::{DryRun, DryRunStatus},
         execution_process::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
         },
+        execution_process_entries::ExecutionProcessEntries,
         execution_process_repo_state::ExecutionProcessRepoState,
+        execution_process_retry::ExecutionProcessRetry,
+        lint_run::LintRun,
+        llm_proxy::LlmProxyToken,
+        path_guard_block::PathGuardBlock,
         project_repo::ProjectRepo,
+        project_settings::{
+            AgentReviewSettings, ContainerBackend, LlmProxySettings, ProjectSettings, RetryPolicy,
+            SharedCache, SharedCacheSettings,
+        },
+        queued_attempt::QueuedAttempt,
         repo::Repo,
         scratch::{DraftFollowUpData, Scratch, ScratchType},
+        session::{CreateSession, Session},
         task::{Task, TaskStatus},
+        test_run::TestRun,
         workspace::Workspace,
-        workspace_repo::WorkspaceRepo,
+        workspace_artifact::WorkspaceArtifact,
+        workspace_budget::WorkspaceBudget,
+        workspace_disk_usage::WorkspaceDiskUsage,
+        workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
     },
 };
 use deployment::{DeploymentError, RemoteClientNotConfigured};
@@ -33,42 +50,79 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
     },
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
-    env::ExecutionEnv,
-    executors::{BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender},
-    logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
+    env::{ContainerExec, ContainerRuntime, ExecutionEnv},
+    executors::{
+        BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterjectionSender,
+        InterruptSender,
+    },
+    failure,
+    logs::{
+        ActionType, NormalizedEntryType,
+        utils::patch::{extract_normalized_entry_from_patch, normalized_entries_from_patches},
+    },
     profile::ExecutorProfileId,
+    test_output::{self, TestSummary},
 };
 use futures::{FutureExt, TryStreamExt, stream::select};
 use serde_json::json;
 use services::services::{
+    agent_review,
     analytics::AnalyticsContext,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
-    config::Config,
-    container::{ContainerError, ContainerRef, ContainerService},
+    attempt_budget,
+    attempt_queue::StartingAttempt,
+    board_history, branch_sync, code_server, commit_message,
+    config::{AttemptQueueConfig, CommitSigningConfig, Config},
+    container::{
+        ContainerError, ContainerRef, ContainerService, ContainerStatus, PreviewTarget,
+        ResourceUsage, SharedCacheUsage, WorkspaceEnv,
+    },
     diff_stream::{self, DiffStreamHandle},
+    dry_run, due_reminders,
     git::{Commit, GitCli, GitService},
     image::ImageService,
+    lint_gate, llm_proxy,
     notification::NotificationService,
+    path_guard,
+    process_registry::{ProcessInfo, ProcessKind, ProcessRegistry},
+    project_cleanup::{self, DeletedProject, ProjectCleanupState},
     queued_message::QueuedMessageService,
+    secrets::{ProjectEnv, mask_stream},
     share::SharePublisher,
+    sparse_checkout::SparseCheckout,
+    task_scheduler,
+    terminal::TerminalService,
+    workspace_files::{self, ARTIFACTS_DIR, SCRATCH_DIR},
+    workspace_janitor,
     workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
+    workspace_pool::WorkspacePool,
+};
+use tokio::{
+    sync::{RwLock, mpsc},
+    task::JoinHandle,
 };
-use tokio::{sync::RwLock, task::JoinHandle};
 use tokio_util::io::ReaderStream;
 use utils::{
     log_msg::LogMsg,
     msg_store::MsgStore,
+    port_file::read_port_file,
     text::{git_branch_id, short_uuid, truncate_to_char_boundary},
 };
 use uuid::Uuid;
 
-use crate::{command, copy};
+use crate::{
+    caches, command, copy, devcontainer, docker, egress, kubernetes, limits, preview, ssh,
+};
+
+/// How often running coding agents are checked against the attempt limits
+const BUDGET_CHECK_INTERVAL: Duration = Duration::from_secs(10);
 
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
     interrupt_senders: Arc<RwLock<HashMap<Uuid, InterruptSender>>>,
+    interjection_senders: Arc<RwLock<HashMap<Uuid, InterjectionSender>>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
     config: Arc<RwLock<Config>>,
     git: GitService,
@@ -78,6 +132,9 @@ pub struct LocalContainerService {
     queued_message_service: QueuedMessageService,
     publisher: Result<SharePublisher, RemoteClientNotConfigured>,
     notification_service: NotificationService,
+    workspace_pool: WorkspacePool,
+    terminals: TerminalService,
+    project_cleanups: mpsc::UnboundedSender<DeletedProject>,
 }
 
 impl LocalContainerService {
@@ -92,15 +149,21 @@ impl LocalContainerService {
         approvals: Approvals,
         queued_message_service: QueuedMessageService,
         publisher: Result<SharePublisher, RemoteClientNotConfigured>,
+        workspace_pool: WorkspacePool,
+        terminals: TerminalService,
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
         let interrupt_senders = Arc::new(RwLock::new(HashMap::new()));
+        let interjection_senders = Arc::new(RwLock::new(HashMap::new()));
         let notification_service = NotificationService::new(config.clone());
+        let (project_cleanups, queue) = mpsc::unbounded_channel();
+        Self::spawn_project_cleanup(queue);
 
         let container = LocalContainerService {
             db,
             child_store,
             interrupt_senders,
+            interjection_senders,
             msg_stores,
             config,
             git,
@@ -110,9 +173,15 @@ impl LocalContainerService {
             queued_message_service,
             publisher,
             notification_service,
+            workspace_pool,
+            terminals,
+            project_cleanups,
         };
 
         container.spawn_workspace_cleanup().await;
+        container.spawn_task_scheduler();
+        container.spawn_board_snapshots();
+        container.spawn_branch_sync();
 
         container
     }
@@ -142,11 +211,35 @@ impl LocalContainerService {
         map.remove(id)
     }
 
-    pub async fn cleanup_workspace(db: &DBService, workspace: &Workspace) {
+    async fn add_interjection_sender(&self, id: Uuid, sender: InterjectionSender) {
+        let mut map = self.interjection_senders.write().await;
+        map.insert(id, sender);
+    }
+
+    async fn remove_interjection_sender(&self, id: &Uuid) {
+        let mut map = self.interjection_senders.write().await;
+        map.remove(id);
+    }
+
+    pub async fn cleanup_workspace(
+        db: &DBService,
+        terminals: &TerminalService,
+        workspace: &Workspace,
+    ) {
         let Some(container_ref) = &workspace.container_ref else {
             return;
         };
         let workspace_dir = PathBuf::from(container_ref);
+        terminals.close_workspace(workspace.id);
+
+        let backend = match workspace.parent_task(&db.pool).await {
+            Ok(Some(task)) => ProjectSettings::find_by_project_id(&db.pool, task.project_id)
+                .await
+                .ok()
+                .map(|settings| settings.container_backend),
+            _ => None,
+        };
+        Self::remove_workspace_runtime(&workspace.id, backend.as_ref()).await;
 
         let repositories = WorkspaceRepo::find_repos_for_workspace(&db.pool, workspace.id)
             .await
@@ -176,9 +269,134 @@ impl LocalContainerService {
 
         // Clear container_ref so this workspace won't be picked up again
         let _ = Workspace::clear_container_ref(&db.pool, workspace.id).await;
+        let _ = WorkspaceDiskUsage::delete(&db.pool, workspace.id).await;
+    }
+
+    /// Tear down what the workspace's processes run in
+    async fn remove_workspace_runtime(workspace_id: &Uuid, backend: Option<&ContainerBackend>) {
+        // Projects can switch backends at any time, so always try to remove a container
+        docker::remove_container(workspace_id).await;
+        if let Some(config) = kubernetes::config() {
+            kubernetes::remove_workspace(config, workspace_id).await;
+        }
+        limits::remove_workspace(workspace_id);
+        egress::forget(workspace_id);
+        if let Some(ContainerBackend::Ssh { host, remote_root }) = backend {
+            ssh::remove_workspace(host, remote_root, workspace_id).await;
+        }
+    }
+
+    /// Work through deleted projects one at a time
+    fn spawn_project_cleanup(mut queue: mpsc::UnboundedReceiver<DeletedProject>) {
+        tokio::spawn(async move {
+            while let Some(deleted) = queue.recv().await {
+                Self::cleanup_deleted_project(deleted).await;
+            }
+        });
+    }
+
+    async fn cleanup_deleted_project(deleted: DeletedProject) {
+        let project_id = deleted.project_id;
+        tracing::info!(
+            "Cleaning up {} workspaces of deleted project {}",
+            deleted.workspaces.len(),
+            deleted.project_name
+        );
+        project_cleanup::update(project_id, |progress| {
+            progress.state = ProjectCleanupState::Running
+        });
+
+        let trash_dir = match deleted.purge_after() {
+            Some(purge_after) => match project_cleanup::create_trash(&deleted, purge_after).await {
+                Ok(dir) => {
+                    project_cleanup::update(project_id, |progress| {
+                        progress.trash_dir = Some(dir.to_string_lossy().into_owned());
+                        progress.purge_after = Some(purge_after);
+                    });
+                    Some(dir)
+                }
+                Err(e) => {
+                    // Nothing is removed while the trash can't be created
+                    project_cleanup::update(project_id, |progress| {
+                        progress.state = ProjectCleanupState::Failed;
+                        progress
+                            .errors
+                            .push(format!("Failed to create the trash: {e}"));
+                    });
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        for (workspace, repos) in &deleted.workspaces {
+            ProcessRegistry::global()
+                .kill_matching(|process| process.workspace_id == Some(workspace.id))
+                .await;
+            Self::remove_workspace_runtime(
+                &workspace.id,
+                Some(&deleted.settings.container_backend),
+            )
+            .await;
+
+            if let Some(container_ref) = &workspace.container_ref {
+                let workspace_dir = PathBuf::from(container_ref);
+                let trashed = match &trash_dir {
+                    Some(trash_dir) if workspace_dir.exists() => {
+                        project_cleanup::move_to_trash(trash_dir, &workspace_dir)
+                            .await
+                            .map_err(|e| e.to_string())
+                    }
+                    _ => Ok(()),
+                };
+                // Once moved, this only drops the worktrees' git metadata
+                let result = match trashed {
+                    Ok(()) => WorkspaceManager::cleanup_workspace(&workspace_dir, repos)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    project_cleanup::update(project_id, |progress| {
+                        progress
+                            .errors
+                            .push(format!("{}: {}", workspace_dir.display(), e))
+                    });
+                }
+
+                code_server::remove_workspace_data(&workspace_dir).await;
+                for repo in repos {
+                    code_server::remove_workspace_data(&workspace_dir.join(&repo.name)).await;
+                }
+            }
+            project_cleanup::update(project_id, |progress| progress.cleaned_workspaces += 1);
+        }
+
+        if let Err(e) = caches::remove_project(project_id).await {
+            project_cleanup::update(project_id, |progress| {
+                progress
+                    .errors
+                    .push(format!("Failed to remove shared caches: {e}"))
+            });
+        }
+
+        project_cleanup::update(project_id, |progress| {
+            progress.state = if progress.errors.is_empty() {
+                ProjectCleanupState::Completed
+            } else {
+                ProjectCleanupState::Failed
+            };
+        });
+        tracing::info!(
+            "Finished cleaning up deleted project {}",
+            deleted.project_name
+        );
     }
 
-    pub async fn cleanup_expired_workspaces(db: &DBService) -> Result<(), DeploymentError> {
+    pub async fn cleanup_expired_workspaces(
+        db: &DBService,
+        terminals: &TerminalService,
+    ) -> Result<(), DeploymentError> {
         let expired_workspaces = Workspace::find_expired_for_cleanup(&db.pool).await?;
         if expired_workspaces.is_empty() {
             tracing::debug!("No expired workspaces found");
@@ -189,24 +407,124 @@ impl LocalContainerService {
             expired_workspaces.len()
         );
         for workspace in &expired_workspaces {
-            Self::cleanup_workspace(db, workspace).await;
+            Self::cleanup_workspace(db, terminals, workspace).await;
+        }
+        Ok(())
+    }
+
+    /// Remove workspaces that their project's retention policy no longer keeps.
+    pub async fn cleanup_retained_workspaces(
+        db: &DBService,
+        terminals: &TerminalService,
+    ) -> Result<(), DeploymentError> {
+        for candidate in workspace_janitor::find_reclaimable(&db.pool, None).await? {
+            let Some(workspace) = Workspace::find_by_id(&db.pool, candidate.workspace_id).await?
+            else {
+                continue;
+            };
+            tracing::info!(
+                "Removing {:?} workspace {} under its project's retention policy",
+                candidate.reason,
+                workspace.id
+            );
+            Self::cleanup_workspace(db, terminals, &workspace).await;
+        }
+        Ok(())
+    }
+
+    /// Empty scratch directories that their project's retention policy no
+    /// longer keeps
+    pub async fn cleanup_stale_scratch(db: &DBService) -> Result<(), DeploymentError> {
+        for dir in workspace_janitor::find_stale_scratch(&db.pool).await? {
+            tracing::info!("Emptying idle scratch directory {}", dir.display());
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                tracing::warn!("Failed to empty {}: {}", dir.display(), e);
+            }
         }
         Ok(())
     }
 
     pub async fn spawn_workspace_cleanup(&self) {
         let db = self.db.clone();
+        let terminals = self.terminals.clone();
         let mut cleanup_interval = tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
         WorkspaceManager::cleanup_orphan_workspaces(&self.db.pool).await;
         tokio::spawn(async move {
             loop {
                 cleanup_interval.tick().await;
                 tracing::info!("Starting periodic workspace cleanup...");
-                Self::cleanup_expired_workspaces(&db)
+                Self::cleanup_expired_workspaces(&db, &terminals)
                     .await
                     .unwrap_or_else(|e| {
                         tracing::error!("Failed to clean up expired workspaces: {}", e)
                     });
+                Self::cleanup_retained_workspaces(&db, &terminals)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to apply workspace retention policies: {}", e)
+                    });
+                Self::cleanup_stale_scratch(&db).await.unwrap_or_else(|e| {
+                    tracing::error!("Failed to empty idle scratch directories: {}", e)
+                });
+                if let Err(e) = workspace_janitor::refresh_disk_usage(&db.pool).await {
+                    tracing::error!("Failed to measure workspace disk usage: {}", e);
+                }
+                project_cleanup::purge_expired_trash().await;
+            }
+        });
+    }
+
+    pub fn spawn_task_scheduler(&self) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                if let Err(e) = task_scheduler::run_due(&container).await {
+                    tracing::error!("Failed to run task schedules: {}", e);
+                }
+                if let Err(e) =
+                    due_reminders::send_due(&container.db.pool, &container.notification_service)
+                        .await
+                {
+                    tracing::error!("Failed to send due date reminders: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Refresh today's snapshot of every board each hour; the last refresh of a
+    /// day stands for the end of it
+    pub fn spawn_board_snapshots(&self) {
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                if let Err(e) = board_history::record_snapshots(&db.pool).await {
+                    tracing::error!("Failed to snapshot project boards: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Check open attempts against their target branches each minute; each
+    /// project's settings say how often an attempt is due
+    pub fn spawn_branch_sync(&self) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let signing = container.config.read().await.commit_signing.clone();
+                if let Err(e) =
+                    branch_sync::run_due(&container.db.pool, &container.git, &signing).await
+                {
+                    tracing::error!(
+                        "Failed to check attempts against their target branches: {}",
+                        e
+                    );
+                }
             }
         });
     }
@@ -232,9 +550,10 @@ impl LocalContainerService {
         }
     }
 
-    /// Get the commit message based on the execution run reason.
+    /// Get the commit message based on the execution run reason, worded by
+    /// the project's commit message settings.
     async fn get_commit_message(&self, ctx: &ExecutionContext) -> String {
-        match ctx.execution_process.run_reason {
+        let summary = match ctx.execution_process.run_reason {
             ExecutionProcessRunReason::CodingAgent => {
                 // Try to retrieve the task summary from the coding agent turn
                 // otherwise fallback to default message
@@ -275,7 +594,24 @@ impl LocalContainerService {
                 "Changes from execution process {}",
                 ctx.execution_process.id
             ),
-        }
+        };
+
+        let settings = ProjectSettings::find_by_project_id(&self.db().pool, ctx.project.id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Failed to load the settings of project {}: {}",
+                    ctx.project.id,
+                    e
+                );
+                ProjectSettings::default()
+            });
+        commit_message::attempt_message(
+            &settings.commit_messages,
+            &ctx.task,
+            &ctx.workspace.branch,
+            &summary,
+        )
     }
 
     /// Check which repos have uncommitted changes. Fails if any repo is inaccessible.
@@ -311,7 +647,12 @@ impl LocalContainerService {
     }
 
     /// Commit changes to each repo. Logs failures but continues with other repos.
-    fn commit_repos(&self, repos_with_changes: Vec<(Repo, PathBuf)>, message: &str) -> bool {
+    fn commit_repos(
+        &self,
+        repos_with_changes: Vec<(Repo, PathBuf)>,
+        message: &str,
+        signing: &CommitSigningConfig,
+    ) -> bool {
         let mut any_committed = false;
 
         for (repo, worktree_path) in repos_with_changes {
@@ -321,7 +662,10 @@ impl LocalContainerService {
                 &worktree_path
             );
 
-            match self.git().commit(&worktree_path, message) {
+            match self
+                .git()
+                .commit_with_signing(&worktree_path, message, signing)
+            {
                 Ok(true) => {
                     any_committed = true;
                     tracing::info!("Committed changes in repo '{}'", repo.name);
@@ -411,6 +755,44 @@ impl LocalContainerService {
             }
 
             if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
+                let settings = ProjectSettings::find_by_project_id(&db.pool, ctx.project.id)
+                    .await
+                    .unwrap_or_default();
+                let backend = &settings.container_backend;
+
+                // Say why the execution died if the memory limit killed it
+                if let Some(leaf) = limits::leaf_path(&ctx.workspace.id, &exec_id.to_string()) {
+                    if let Some(message) = limits::oom_message(&leaf, &settings.resource_limits)
+                        && let Some(store) = msg_stores.read().await.get(&exec_id)
+                    {
+                        store.push_stderr(format!("{message}\n"));
+                    }
+                    limits::remove_leaf(&leaf);
+                }
+                caches::release(exec_id, &settings.shared_caches).await;
+
+                // Bring back what the execution changed on the remote host
+                // before anything looks at the worktree
+                if let ContainerBackend::Ssh { host, remote_root } = backend
+                    && kubernetes::config().is_none()
+                    && let Some(container_ref) = &ctx.workspace.container_ref
+                    && let Err(e) = ssh::pull(
+                        host,
+                        &ssh::remote_dir(remote_root, &ctx.workspace.id),
+                        Path::new(container_ref),
+                    )
+                    .await
+                {
+                    tracing::error!("Failed to copy workspace back from {}: {}", host, e);
+                }
+
+                container.persist_tool_calls(&ctx.execution_process).await;
+                container.persist_entries(&ctx.execution_process).await;
+                container.persist_test_run(&ctx.execution_process).await;
+                container
+                    .persist_artifacts(&ctx.execution_process, &ctx.workspace)
+                    .await;
+
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
                     tracing::warn!("Failed to update executor session summary: {}", e);
@@ -421,6 +803,12 @@ impl LocalContainerService {
                     ExecutionProcessStatus::Completed
                 ) && exit_code == Some(0);
 
+                // A dry run's changes stay in its copy, which goes now
+                let dry_run = container.finish_dry_run(&ctx).await;
+                // Changes to protected paths wait for the user's override
+                let guarded =
+                    !dry_run && success && container.run_path_guard(&ctx, &settings).await;
+
                 let cleanup_done = matches!(
                     ctx.execution_process.run_reason,
                     ExecutionProcessRunReason::CleanupScript
@@ -429,7 +817,7 @@ impl LocalContainerService {
                     ExecutionProcessStatus::Running
                 );
 
-                if success || cleanup_done {
+                if !dry_run && !guarded && (success || cleanup_done) {
                     // Commit changes (if any) and get feedback about whether changes were made
                     let changes_committed = match container.try_commit_changes(&ctx).await {
                         Ok(committed) => committed,
@@ -465,7 +853,22 @@ impl LocalContainerService {
                     }
                 }
 
-                if container.should_finalize(&ctx) {
+                let retry_scheduled = !dry_run
+                    && !success
+                    && container
+                        .try_schedule_retry(&ctx, &settings.retry_policy)
+                        .await;
+
+                // Failed lint checks may send the agent back to fix them, and a
+                // reviewing agent may be asked for its opinion, which keeps the
+                // attempt going
+                if !dry_run
+                    && !guarded
+                    && !retry_scheduled
+                    && container.should_finalize(&ctx)
+                    && !container.run_lint_gate(&ctx, &settings).await
+                    && !container.run_agent_review(&ctx, &settings).await
+                {
                     // Only execute queued messages if the execution succeeded
                     // If it failed or was killed, just clear the queue and finalize
                     let should_execute_queued = !matches!(
@@ -517,6 +920,24 @@ impl LocalContainerService {
                     } else {
                         container.finalize_task(publisher.as_ref().ok(), &ctx).await;
                     }
+
+                    // Tear down the attempt's container once nothing is left running in it
+                    let k8s = kubernetes::config();
+                    let containerized = matches!(
+                        backend,
+                        ContainerBackend::Docker { .. } | ContainerBackend::Devcontainer
+                    );
+                    if (containerized || k8s.is_some())
+                        && !container
+                            .has_running_processes(ctx.task.id)
+                            .await
+                            .unwrap_or(true)
+                    {
+                        match k8s {
+                            Some(config) => kubernetes::remove_pod(config, &ctx.workspace.id).await,
+                            None => docker::remove_container(&ctx.workspace.id).await,
+                        }
+                    }
                 }
 
                 // Fire analytics event when CodingAgent execution has finished
@@ -558,9 +979,24 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+            container.remove_interjection_sender(&exec_id).await;
+            ProcessRegistry::global().unregister_execution(exec_id);
+
+            container.spawn_start_queued_attempts();
         })
     }
 
+    /// Start what the queue holds now that a process ended and may have
+    /// freed a slot
+    fn spawn_start_queued_attempts(&self) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = container.start_queued_attempts().await {
+                tracing::error!("Failed to start queued attempts: {}", e);
+            }
+        });
+    }
+
     pub fn spawn_os_exit_watcher(
         &self,
         exec_id: Uuid,
@@ -603,19 +1039,35 @@ impl LocalContainerService {
         format!("{}-{}", short_uuid(workspace_id), task_title_id)
     }
 
-    async fn track_child_msgs_in_store(&self, id: Uuid, child: &mut AsyncGroupChild) {
+    /// What the task's project checks out of each of these repositories
+    async fn sparse_checkout(
+        &self,
+        task: &Task,
+        repos: &[Repo],
+    ) -> Result<SparseCheckout, ContainerError> {
+        let settings = ProjectSettings::find_by_project_id(&self.db.pool, task.project_id).await?;
+        Ok(SparseCheckout::new(&settings.sparse_checkout, task, repos))
+    }
+
+    async fn track_child_msgs_in_store(
+        &self,
+        id: Uuid,
+        child: &mut AsyncGroupChild,
+        secret_values: Vec<String>,
+    ) {
         let store = Arc::new(MsgStore::new());
 
         let out = child.inner().stdout.take().expect("no stdout");
         let err = child.inner().stderr.take().expect("no stderr");
 
+        // Secret values are masked before anything reaches the store, and with it
+        // the database and the log streams
+
         // Map stdout bytes -> LogMsg::Stdout
-        let out = ReaderStream::new(out)
-            .map_ok(|chunk| LogMsg::Stdout(String::from_utf8_lossy(&chunk).into_owned()));
+        let out = mask_stream(ReaderStream::new(out), secret_values.clone()).map_ok(LogMsg::Stdout);
 
         // Map stderr bytes -> LogMsg::Stderr
-        let err = ReaderStream::new(err)
-            .map_ok(|chunk| LogMsg::Stderr(String::from_utf8_lossy(&chunk).into_owned()));
+        let err = mask_stream(ReaderStream::new(err), secret_values).map_ok(LogMsg::Stderr);
 
         // If you have a JSON Patch source, map it to LogMsg::JsonPatch too, then select all three.
 
@@ -790,100 +1242,966 @@ impl LocalContainerService {
         Ok(())
     }
 
-    /// Start a follow-up execution from a queued message
-    async fn start_queued_follow_up(
-        &self,
-        ctx: &ExecutionContext,
-        queued_data: &DraftFollowUpData,
-    ) -> Result<ExecutionProcess, ContainerError> {
-        // Get executor profile from the latest CodingAgent process in this session
-        let initial_executor_profile_id =
-            ExecutionProcess::latest_executor_profile_for_session(&self.db.pool, ctx.session.id)
-                .await
-                .map_err(|e| {
-                    ContainerError::Other(anyhow!("Failed to get executor profile: {e}"))
-                })?;
-
-        let executor_profile_id = ExecutorProfileId {
-            executor: initial_executor_profile_id.executor,
-            variant: queued_data.variant.clone(),
+    /// Output of an execution still held in memory, stdout and stderr in the
+    /// order they arrived
+    async fn raw_output(&self, exec_id: &Uuid) -> String {
+        let Some(store) = self.msg_stores.read().await.get(exec_id).cloned() else {
+            return String::new();
         };
-
-        // Get latest agent session ID for session continuity (from coding agent turns)
-        let latest_agent_session_id = ExecutionProcess::find_latest_coding_agent_turn_session_id(
-            &self.db.pool,
-            ctx.session.id,
-        )
-        .await?;
-
-        let project_repos =
-            ProjectRepo::find_by_project_id_with_names(&self.db.pool, ctx.project.id).await?;
-        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
-
-        let working_dir = ctx
-            .workspace
-            .agent_working_dir
-            .as_ref()
-            .filter(|dir| !dir.is_empty())
-            .cloned();
-
-        let action_type = if let Some(agent_session_id) = latest_agent_session_id {
-            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
-                prompt: queued_data.message.clone(),
-                session_id: agent_session_id,
-                executor_profile_id: executor_profile_id.clone(),
-                working_dir: working_dir.clone(),
+        store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::Stdout(text) | LogMsg::Stderr(text) => Some(text),
+                _ => None,
             })
-        } else {
-            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
-                prompt: queued_data.message.clone(),
-                executor_profile_id: executor_profile_id.clone(),
-                working_dir,
-            })
-        };
-
-        let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
-
-        self.start_execution(
-            &ctx.workspace,
-            &ctx.session,
-            &action,
-            &ExecutionProcessRunReason::CodingAgent,
-        )
-        .await
+            .collect()
     }
-}
 
-fn failure_exit_status() -> std::process::ExitStatus {
-    #[cfg(unix)]
-    {
-        use std::os::unix::process::ExitStatusExt;
-        ExitStatusExt::from_raw(256) // Exit code 1 (shifted by 8 bits)
-    }
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::ExitStatusExt;
-        ExitStatusExt::from_raw(1)
+    /// Tool calls the execution made so far, if its output is still held
+    async fn count_tool_calls(&self, exec_id: &Uuid) -> Option<i64> {
+        let store = self.msg_stores.read().await.get(exec_id).cloned()?;
+        let indices: HashSet<usize> = store
+            .get_history()
+            .iter()
+            .filter_map(|msg| match msg {
+                LogMsg::JsonPatch(patch) => extract_normalized_entry_from_patch(patch),
+                _ => None,
+            })
+            .filter(|(_, entry)| matches!(entry.entry_type, NormalizedEntryType::ToolUse { .. }))
+            .map(|(index, _)| index)
+            .collect();
+        Some(indices.len() as i64)
     }
-}
 
-#[async_trait]
-impl ContainerService for LocalContainerService {
-    fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>> {
-        &self.msg_stores
+    /// Keep the coding agent's tool call count before its output is dropped
+    async fn persist_tool_calls(&self, execution_process: &ExecutionProcess) {
+        if !matches!(
+            execution_process.run_reason,
+            ExecutionProcessRunReason::CodingAgent
+        ) {
+            return;
+        }
+        if let Some(count) = self.count_tool_calls(&execution_process.id).await
+            && let Err(e) =
+                ExecutionProcess::update_tool_calls(&self.db.pool, execution_process.id, count)
+                    .await
+        {
+            tracing::warn!(
+                "Failed to save tool calls of {}: {}",
+                execution_process.id,
+                e
+            );
+        }
     }
 
-    fn db(&self) -> &DBService {
-        &self.db
+    /// Keep the coding agent's conversation with the times of its entries,
+    /// which normalizing the stored output again can't recover
+    async fn persist_entries(&self, execution_process: &ExecutionProcess) {
+        if !matches!(
+            execution_process.run_reason,
+            ExecutionProcessRunReason::CodingAgent
+        ) {
+            return;
+        }
+        let Some(store) = self
+            .msg_stores
+            .read()
+            .await
+            .get(&execution_process.id)
+            .cloned()
+        else {
+            return;
+        };
+        let history = store.get_history();
+        let entries = normalized_entries_from_patches(history.iter().filter_map(|msg| match msg {
+            LogMsg::JsonPatch(patch) => Some(patch),
+            _ => None,
+        }));
+        if let Err(e) =
+            ExecutionProcessEntries::upsert(&self.db.pool, execution_process.id, &entries).await
+        {
+            tracing::warn!("Failed to save entries of {}: {}", execution_process.id, e);
+        }
     }
 
-    fn git(&self) -> &GitService {
-        &self.git
+    /// Tests the execution ran, from the output of the commands a coding agent
+    /// ran or from what a script printed, if its output is still held
+    async fn test_summary(&self, execution_process: &ExecutionProcess) -> Option<TestSummary> {
+        match execution_process.run_reason {
+            ExecutionProcessRunReason::CodingAgent => {
+                let store = self
+                    .msg_stores
+                    .read()
+                    .await
+                    .get(&execution_process.id)
+                    .cloned()?;
+                let history = store.get_history();
+                let entries =
+                    normalized_entries_from_patches(history.iter().filter_map(|msg| match msg {
+                        LogMsg::JsonPatch(patch) => Some(patch),
+                        _ => None,
+                    }));
+                entries
+                    .values()
+                    .rev()
+                    .find_map(|entry| match &entry.entry_type {
+                        NormalizedEntryType::ToolUse {
+                            action_type:
+                                ActionType::CommandRun {
+                                    result: Some(result),
+                                    ..
+                                },
+                            ..
+                        } => result.output.as_deref().and_then(test_output::parse),
+                        _ => None,
+                    })
+            }
+            ExecutionProcessRunReason::SetupScript | ExecutionProcessRunReason::CleanupScript => {
+                test_output::parse(&self.raw_output(&execution_process.id).await)
+            }
+            ExecutionProcessRunReason::DevServer => None,
+        }
     }
 
-    fn share_publisher(&self) -> Option<&SharePublisher> {
-        self.publisher.as_ref().ok()
-    }
+    /// Keep the test counts of the execution before its output is dropped
+    async fn persist_test_run(&self, execution_process: &ExecutionProcess) {
+        let Some(summary) = self.test_summary(execution_process).await else {
+            return;
+        };
+        let result = async {
+            let session = Session::find_by_id(&self.db.pool, execution_process.session_id)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?;
+            TestRun::create(
+                &self.db.pool,
+                execution_process.id,
+                session.workspace_id,
+                &summary,
+            )
+            .await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to save test results of {}: {}",
+                execution_process.id,
+                e
+            );
+        }
+    }
+
+    /// Record what the run left in the artifacts directory
+    async fn persist_artifacts(&self, execution_process: &ExecutionProcess, workspace: &Workspace) {
+        let Some(container_ref) = &workspace.container_ref else {
+            return;
+        };
+        let result = async {
+            let artifacts = workspace_files::collect_artifacts(Path::new(container_ref)).await?;
+            WorkspaceArtifact::sync(
+                &self.db.pool,
+                workspace.id,
+                execution_process.id,
+                &artifacts,
+            )
+            .await?;
+            Ok::<_, ContainerError>(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to record artifacts of {}: {}",
+                execution_process.id,
+                e
+            );
+        }
+    }
+
+    /// Stop a coding agent run once its attempt reaches one of the project's
+    /// limits. The run is interrupted as when the user stops it, and the
+    /// limit is noted on the attempt until its budget is extended.
+    fn spawn_budget_watch(&self, exec_id: Uuid, workspace_id: Uuid, project_id: Uuid) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(BUDGET_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if container.get_child_from_store(&exec_id).await.is_none() {
+                    break;
+                }
+                let pool = &container.db.pool;
+                let running_tool_calls = container.count_tool_calls(&exec_id).await.unwrap_or(0);
+                let budget = match attempt_budget::attempt_budget(
+                    pool,
+                    workspace_id,
+                    project_id,
+                    running_tool_calls,
+                )
+                .await
+                {
+                    Ok(budget) => budget,
+                    Err(e) => {
+                        tracing::warn!("Failed to check budget of {}: {}", workspace_id, e);
+                        continue;
+                    }
+                };
+                let Some(limit) = budget.reached else {
+                    continue;
+                };
+
+                let Ok(Some(process)) = ExecutionProcess::find_by_id(pool, exec_id).await else {
+                    break;
+                };
+                if process.status != ExecutionProcessStatus::Running {
+                    break;
+                }
+                if let Some(store) = container.msg_stores.read().await.get(&exec_id) {
+                    store.push_stderr(format!(
+                        "Stopped: the attempt reached its limit of {}. Extend its budget to continue.\n",
+                        attempt_budget::describe_limit(limit, &budget.limits)
+                    ));
+                }
+                if let Err(e) = WorkspaceBudget::record_stop(pool, workspace_id, limit).await {
+                    tracing::error!("Failed to record budget stop of {}: {}", workspace_id, e);
+                }
+                if let Err(e) = container
+                    .stop_execution(&process, ExecutionProcessStatus::Killed)
+                    .await
+                {
+                    tracing::error!("Failed to stop {} at its budget: {}", exec_id, e);
+                }
+                break;
+            }
+        });
+    }
+
+    /// Run a failed coding agent request again after a delay, if the project's
+    /// retry policy covers the failure. Returns whether a retry was scheduled;
+    /// the task then stays in progress rather than going to review.
+    async fn try_schedule_retry(&self, ctx: &ExecutionContext, policy: &RetryPolicy) -> bool {
+        if !policy.enabled
+            || !matches!(ctx.execution_process.status, ExecutionProcessStatus::Failed)
+            || !matches!(
+                ctx.execution_process.run_reason,
+                ExecutionProcessRunReason::CodingAgent
+            )
+        {
+            return false;
+        }
+
+        let exec_id = ctx.execution_process.id;
+        let kind = failure::classify(
+            &self.raw_output(&exec_id).await,
+            ctx.execution_process.exit_code,
+        );
+        let retries =
+            match ExecutionProcessRetry::count_by_workspace_id(&self.db.pool, ctx.workspace.id)
+                .await
+            {
+                Ok(count) => count,
+                Err(e) => {
+                    tracing::error!("Failed to count retries of {}: {}", ctx.workspace.id, e);
+                    return false;
+                }
+            };
+        let store = self.msg_stores.read().await.get(&exec_id).cloned();
+        let reason = kind.to_string().replace('_', " ");
+
+        let Some(delay) = policy.delay_for(kind, retries) else {
+            if policy.retry_on.contains(&kind)
+                && let Some(store) = store
+            {
+                store.push_stderr(format!(
+                    "Not retrying the {reason} failure: the attempt used its {} retries\n",
+                    policy.max_retries
+                ));
+            }
+            return false;
+        };
+        if let Some(store) = store {
+            store.push_stderr(format!(
+                "The run failed ({reason}); retrying in {delay}s, retry {} of {}\n",
+                retries + 1,
+                policy.max_retries
+            ));
+        }
+
+        let container = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            let pool = &container.db.pool;
+            let Ok(ctx) = ExecutionProcess::load_context(pool, exec_id).await else {
+                return;
+            };
+
+            // Leave the attempt alone if someone carried on with it meanwhile
+            let latest = ExecutionProcess::find_latest_by_session_and_run_reason(
+                pool,
+                ctx.session.id,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await
+            .ok()
+            .flatten();
+            let busy = ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+                pool,
+                ctx.workspace.id,
+            )
+            .await
+            .unwrap_or(true);
+            if latest.is_none_or(|latest| latest.id != exec_id) || busy {
+                tracing::info!("Skipping retry of {}: the attempt has moved on", exec_id);
+                return;
+            }
+
+            let started = match ctx.execution_process.executor_action() {
+                Ok(action) => {
+                    container
+                        .start_execution(
+                            &ctx.workspace,
+                            &ctx.session,
+                            action,
+                            &ExecutionProcessRunReason::CodingAgent,
+                        )
+                        .await
+                }
+                Err(e) => Err(ContainerError::Other(e)),
+            };
+            match started {
+                Ok(retry) => {
+                    if let Err(e) = ExecutionProcessRetry::create(
+                        pool,
+                        ctx.workspace.id,
+                        exec_id,
+                        retry.id,
+                        kind,
+                    )
+                    .await
+                    {
+                        tracing::error!("Failed to record retry of {}: {}", exec_id, e);
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to retry execution {}: {}", exec_id, e);
+                    container
+                        .finalize_task(container.publisher.as_ref().ok(), &ctx)
+                        .await;
+                }
+            }
+        });
+        true
+    }
+
+    /// Run the project's lint checks once the attempt's agent and cleanup
+    /// script are done, and send the agent what failed if the gate asks for
+    /// fix-ups. Returns whether a fix-up run was started; the task then stays
+    /// in progress rather than going to review.
+    async fn run_lint_gate(&self, ctx: &ExecutionContext, settings: &ProjectSettings) -> bool {
+        let gate = &settings.lint_gate;
+        if gate.checks.is_empty()
+            || !matches!(
+                ctx.execution_process.status,
+                ExecutionProcessStatus::Completed
+            )
+            || !matches!(
+                ctx.execution_process.run_reason,
+                ExecutionProcessRunReason::CodingAgent | ExecutionProcessRunReason::CleanupScript
+            )
+        {
+            return false;
+        }
+        let Some(container_ref) = &ctx.workspace.container_ref else {
+            return false;
+        };
+        // A reviewing agent's run isn't the attempt's work
+        match AgentReview::is_review_session(&self.db.pool, ctx.session.id).await {
+            Ok(false) => {}
+            Ok(true) => return false,
+            Err(e) => {
+                tracing::error!("Failed to look up reviews of {}: {}", ctx.session.id, e);
+                return false;
+            }
+        }
+        let WorkspaceEnv { env, secret_values } = match self.workspace_env(&ctx.workspace).await {
+            Ok(env) => env,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to prepare the lint checks of {}: {}",
+                    ctx.workspace.id,
+                    e
+                );
+                return false;
+            }
+        };
+
+        let pool = &self.db.pool;
+        let exec_id = ctx.execution_process.id;
+        let results = lint_gate::run_checks(
+            ctx.workspace.id,
+            Path::new(container_ref),
+            &env,
+            &settings.exec_policy.for_configured_commands(),
+            &secret_values,
+            &gate.checks,
+        )
+        .await;
+        let passed = lint_gate::passed(&results);
+        if let Err(e) = LintRun::create(pool, exec_id, ctx.workspace.id, &results, passed).await {
+            tracing::error!("Failed to save the lint checks of {}: {}", exec_id, e);
+        }
+        if passed
+            || !gate.auto_fix
+            || !lint_gate::needs_fix(&results)
+            || self.queued_message_service.has_queued(ctx.session.id)
+        {
+            return false;
+        }
+
+        match LintRun::count_fix_runs_by_workspace_id(pool, ctx.workspace.id).await {
+            Ok(fix_runs) if fix_runs < gate.max_fix_runs as i64 => {}
+            Ok(_) => {
+                tracing::info!(
+                    "Not fixing the lint checks of {}: the attempt used its {} fix-up runs",
+                    ctx.workspace.id,
+                    gate.max_fix_runs
+                );
+                return false;
+            }
+            Err(e) => {
+                tracing::error!("Failed to count fix-up runs of {}: {}", ctx.workspace.id, e);
+                return false;
+            }
+        }
+        let executor_profile_id =
+            match ExecutionProcess::latest_executor_profile_for_session(pool, ctx.session.id).await
+            {
+                Ok(profile) => profile,
+                Err(e) => {
+                    tracing::error!("Failed to get executor profile for a fix-up run: {}", e);
+                    return false;
+                }
+            };
+        match self
+            .start_follow_up(ctx, executor_profile_id, lint_gate::fix_prompt(&results))
+            .await
+        {
+            Ok(fix) => {
+                if let Err(e) = LintRun::set_fix_execution_process_id(pool, exec_id, fix.id).await {
+                    tracing::error!("Failed to record the fix-up run of {}: {}", exec_id, e);
+                }
+                true
+            }
+            Err(e) => {
+                tracing::error!("Failed to start a fix-up run for {}: {}", exec_id, e);
+                false
+            }
+        }
+    }
+
+    /// Hand the attempt's changes to the project's reviewing agent once its
+    /// agent and cleanup script are done, and once the reviewer is done, send
+    /// its comments back to the attempt's agent if it asked for changes.
+    /// Returns whether a review or revision run was started; the task then
+    /// stays in progress rather than going to review.
+    async fn run_agent_review(&self, ctx: &ExecutionContext, settings: &ProjectSettings) -> bool {
+        let review_settings = &settings.agent_review;
+        let Some(reviewer) = review_settings
+            .reviewer
+            .as_ref()
+            .filter(|_| review_settings.enabled)
+        else {
+            return false;
+        };
+        if !matches!(
+            ctx.execution_process.run_reason,
+            ExecutionProcessRunReason::CodingAgent | ExecutionProcessRunReason::CleanupScript
+        ) {
+            return false;
+        }
+
+        let pool = &self.db.pool;
+        let exec_id = ctx.execution_process.id;
+        match AgentReview::find_by_review_execution_process_id(pool, exec_id).await {
+            Ok(Some(review)) => {
+                return self.finish_agent_review(ctx, review_settings, review).await;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!("Failed to look up the review run {}: {}", exec_id, e);
+                return false;
+            }
+        }
+
+        if !matches!(
+            ctx.execution_process.status,
+            ExecutionProcessStatus::Completed
+        ) || self.queued_message_service.has_queued(ctx.session.id)
+        {
+            return false;
+        }
+        match AgentReview::is_review_session(pool, ctx.session.id).await {
+            Ok(false) => {}
+            // The reviewer's cleanup script
+            Ok(true) => return false,
+            Err(e) => {
+                tracing::error!("Failed to look up reviews of {}: {}", ctx.session.id, e);
+                return false;
+            }
+        }
+        match AgentReview::count_by_workspace_id(pool, ctx.workspace.id).await {
+            Ok(reviews) if reviews < review_settings.max_iterations as i64 => {}
+            Ok(_) => {
+                tracing::info!(
+                    "Not reviewing {} again: the attempt had its {} reviews",
+                    ctx.workspace.id,
+                    review_settings.max_iterations
+                );
+                return false;
+            }
+            Err(e) => {
+                tracing::error!("Failed to count reviews of {}: {}", ctx.workspace.id, e);
+                return false;
+            }
+        }
+
+        let diff = match agent_review::attempt_diff(pool, &self.git, &ctx.workspace).await {
+            Ok(diff) if diff.trim().is_empty() => return false,
+            Ok(diff) => diff,
+            Err(e) => {
+                tracing::error!("Failed to get the changes of {}: {}", ctx.workspace.id, e);
+                return false;
+            }
+        };
+        match self
+            .start_agent_review(ctx, reviewer, review_settings, &diff)
+            .await
+        {
+            Ok(review) => {
+                tracing::info!(
+                    "Started review {} of {} with {}",
+                    review.id,
+                    ctx.workspace.id,
+                    reviewer
+                );
+                true
+            }
+            Err(e) => {
+                tracing::error!("Failed to start a review of {}: {}", ctx.workspace.id, e);
+                false
+            }
+        }
+    }
+
+    /// Run the reviewer in a session of its own, so the attempt's agent keeps
+    /// its conversation
+    async fn start_agent_review(
+        &self,
+        ctx: &ExecutionContext,
+        reviewer: &ExecutorProfileId,
+        review_settings: &AgentReviewSettings,
+        diff: &str,
+    ) -> Result<AgentReview, ContainerError> {
+        let pool = &self.db.pool;
+        let session = Session::create(
+            pool,
+            &CreateSession {
+                executor: Some(reviewer.executor.to_string()),
+            },
+            Uuid::new_v4(),
+            ctx.workspace.id,
+        )
+        .await?;
+        let working_dir = ctx
+            .workspace
+            .agent_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+        let action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: agent_review::review_prompt(
+                    &ctx.task,
+                    diff,
+                    review_settings.instructions.as_deref(),
+                ),
+                executor_profile_id: reviewer.clone(),
+                working_dir,
+                prompt_additions: None,
+            }),
+            None,
+        );
+        let review_process = self
+            .start_execution(
+                &ctx.workspace,
+                &session,
+                &action,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await?;
+        Ok(AgentReview::create(
+            pool,
+            ctx.workspace.id,
+            ctx.execution_process.id,
+            review_process.id,
+        )
+        .await?)
+    }
+
+    /// Record the reviewer's verdict and start the revision it asks for.
+    /// Returns whether a revision run was started.
+    async fn finish_agent_review(
+        &self,
+        ctx: &ExecutionContext,
+        review_settings: &AgentReviewSettings,
+        review: AgentReview,
+    ) -> bool {
+        let pool = &self.db.pool;
+        let feedback = if matches!(
+            ctx.execution_process.status,
+            ExecutionProcessStatus::Completed
+        ) {
+            match CodingAgentTurn::find_by_execution_process_id(pool, ctx.execution_process.id)
+                .await
+            {
+                Ok(turn) => turn
+                    .and_then(|turn| turn.summary)
+                    .as_deref()
+                    .and_then(agent_review::parse_feedback),
+                Err(e) => {
+                    tracing::error!("Failed to read the verdict of review {}: {}", review.id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let (status, summary, comments) = match &feedback {
+            Some(feedback) => (
+                feedback.status(),
+                feedback.summary.as_deref(),
+                feedback.comments.as_slice(),
+            ),
+            None => (AgentReviewStatus::Failed, None, &[][..]),
+        };
+        if let Err(e) = AgentReview::complete(pool, review.id, status, summary, comments).await {
+            tracing::error!("Failed to save review {}: {}", review.id, e);
+        }
+
+        let Some(feedback) = feedback.filter(|feedback| {
+            feedback.status() == AgentReviewStatus::ChangesRequested && review_settings.auto_revise
+        }) else {
+            return false;
+        };
+        let author_ctx = match ExecutionProcess::load_context(
+            pool,
+            review.reviewed_execution_process_id,
+        )
+        .await
+        {
+            Ok(author_ctx) => author_ctx,
+            Err(e) => {
+                tracing::error!("Failed to load the run review {} was of: {}", review.id, e);
+                return false;
+            }
+        };
+        if self
+            .queued_message_service
+            .has_queued(author_ctx.session.id)
+        {
+            return false;
+        }
+        let executor_profile_id = match ExecutionProcess::latest_executor_profile_for_session(
+            pool,
+            author_ctx.session.id,
+        )
+        .await
+        {
+            Ok(profile) => profile,
+            Err(e) => {
+                tracing::error!("Failed to get executor profile for a revision run: {}", e);
+                return false;
+            }
+        };
+        match self
+            .start_follow_up(
+                &author_ctx,
+                executor_profile_id,
+                agent_review::revision_prompt(&feedback),
+            )
+            .await
+        {
+            Ok(revision) => {
+                if let Err(e) =
+                    AgentReview::set_revision_execution_process_id(pool, review.id, revision.id)
+                        .await
+                {
+                    tracing::error!("Failed to record the revision run of {}: {}", review.id, e);
+                }
+                true
+            }
+            Err(e) => {
+                tracing::error!("Failed to start a revision run for {}: {}", review.id, e);
+                false
+            }
+        }
+    }
+
+    /// Keep what a dry run's agent proposed and remove its copy of the
+    /// workspace. Returns whether the process was a dry run's, which ends
+    /// there: nothing is committed, retried or finalized.
+    async fn finish_dry_run(&self, ctx: &ExecutionContext) -> bool {
+        let pool = &self.db.pool;
+        let dry_run =
+            match DryRun::find_by_execution_process_id(pool, ctx.execution_process.id).await {
+                Ok(Some(dry_run)) => dry_run,
+                Ok(None) => return false,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to look up the dry run of {}: {}",
+                        ctx.execution_process.id,
+                        e
+                    );
+                    return false;
+                }
+            };
+
+        let repo_names: Vec<String> =
+            match WorkspaceRepo::find_repos_for_workspace(pool, ctx.workspace.id).await {
+                Ok(repos) => repos.into_iter().map(|repo| repo.name).collect(),
+                Err(e) => {
+                    tracing::error!("Failed to load the repos of {}: {}", ctx.workspace.id, e);
+                    Vec::new()
+                }
+            };
+        let workspace_root = self.workspace_to_current_dir(&ctx.workspace);
+        let patch = match dry_run::capture_patch(&workspace_root, &repo_names, &dry_run.id).await {
+            Ok(patch) => Some(patch),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to read the changes of dry run {}: {}",
+                    dry_run.id,
+                    e
+                );
+                None
+            }
+        };
+        dry_run::remove_copy(&workspace_root, &repo_names, &dry_run.id).await;
+
+        let plan =
+            match CodingAgentTurn::find_by_execution_process_id(pool, ctx.execution_process.id)
+                .await
+            {
+                Ok(turn) => turn.and_then(|turn| turn.summary),
+                Err(e) => {
+                    tracing::error!("Failed to read the plan of dry run {}: {}", dry_run.id, e);
+                    None
+                }
+            };
+        let status = if matches!(
+            ctx.execution_process.status,
+            ExecutionProcessStatus::Completed
+        ) {
+            DryRunStatus::Ready
+        } else {
+            DryRunStatus::Failed
+        };
+        if let Err(e) =
+            DryRun::complete(pool, dry_run.id, status, plan.as_deref(), patch.as_deref()).await
+        {
+            tracing::error!("Failed to save dry run {}: {}", dry_run.id, e);
+        }
+        // The attempt waits for the plan to be looked at
+        if let Err(e) = Task::update_status(pool, ctx.task.id, TaskStatus::InReview).await {
+            tracing::error!("Failed to update task status to InReview: {e}");
+        }
+        true
+    }
+
+    /// Hold back a coding agent run's changes if they touch the project's
+    /// protected paths. Returns whether they were; they then stay uncommitted
+    /// and the attempt waits in review for the user's override.
+    async fn run_path_guard(&self, ctx: &ExecutionContext, settings: &ProjectSettings) -> bool {
+        if settings.protected_paths.is_empty()
+            || !matches!(
+                ctx.execution_process.run_reason,
+                ExecutionProcessRunReason::CodingAgent
+            )
+        {
+            return false;
+        }
+        let pool = &self.db.pool;
+        let paths = match path_guard::guarded_changes(
+            pool,
+            &self.git,
+            &ctx.workspace,
+            &settings.protected_paths,
+        )
+        .await
+        {
+            Ok(paths) => paths,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to check the protected paths of {}: {}",
+                    ctx.workspace.id,
+                    e
+                );
+                return false;
+            }
+        };
+        if paths.is_empty() {
+            return false;
+        }
+
+        tracing::warn!(
+            "Holding back the changes of {} to protected paths: {}",
+            ctx.execution_process.id,
+            paths.join(", ")
+        );
+        if let Err(e) =
+            PathGuardBlock::create(pool, ctx.workspace.id, ctx.execution_process.id, &paths).await
+        {
+            tracing::error!(
+                "Failed to save the path guard block of {}: {}",
+                ctx.workspace.id,
+                e
+            );
+        }
+        if let Err(e) = Task::update_status(pool, ctx.task.id, TaskStatus::InReview).await {
+            tracing::error!("Failed to update task status to InReview: {e}");
+        }
+        self.notification_service
+            .notify(
+                &format!("Protected Paths Changed: {}", ctx.task.title),
+                &format!(
+                    "The agent changed {}; override the guard to keep the changes",
+                    paths.join(", ")
+                ),
+            )
+            .await;
+        true
+    }
+
+    /// Start a follow-up execution from a queued message
+    async fn start_queued_follow_up(
+        &self,
+        ctx: &ExecutionContext,
+        queued_data: &DraftFollowUpData,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        // Get executor profile from the latest CodingAgent process in this session
+        let initial_executor_profile_id =
+            ExecutionProcess::latest_executor_profile_for_session(&self.db.pool, ctx.session.id)
+                .await
+                .map_err(|e| {
+                    ContainerError::Other(anyhow!("Failed to get executor profile: {e}"))
+                })?;
+
+        let executor_profile_id = ExecutorProfileId {
+            executor: initial_executor_profile_id.executor,
+            variant: queued_data.variant.clone(),
+        };
+        self.start_follow_up(ctx, executor_profile_id, queued_data.message.clone())
+            .await
+    }
+
+    /// Continue the session's agent with `prompt`, or start one if the
+    /// session has none to continue
+    async fn start_follow_up(
+        &self,
+        ctx: &ExecutionContext,
+        executor_profile_id: ExecutorProfileId,
+        prompt: String,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        // Get latest agent session ID for session continuity (from coding agent turns)
+        let latest_agent_session_id = ExecutionProcess::find_latest_coding_agent_turn_session_id(
+            &self.db.pool,
+            ctx.session.id,
+        )
+        .await?;
+
+        let project_repos =
+            ProjectRepo::find_by_project_id_with_names(&self.db.pool, ctx.project.id).await?;
+        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
+
+        let working_dir = ctx
+            .workspace
+            .agent_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+
+        let action_type = if let Some(agent_session_id) = latest_agent_session_id {
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt,
+                session_id: agent_session_id,
+                executor_profile_id,
+                working_dir: working_dir.clone(),
+                prompt_additions: None,
+            })
+        } else {
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt,
+                executor_profile_id,
+                working_dir,
+                prompt_additions: None,
+            })
+        };
+
+        let action = ExecutorAction::new(action_type, cleanup_action.map(Box::new));
+
+        self.start_execution(
+            &ctx.workspace,
+            &ctx.session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await
+    }
+
+    /// Environment pointing the workspace's agents at the LLM proxy
+    async fn llm_proxy_env(
+        &self,
+        workspace: &Workspace,
+        proxy: &LlmProxySettings,
+    ) -> anyhow::Result<Vec<(&'static str, String)>> {
+        let server_url = match &proxy.server_url {
+            Some(server_url) => server_url.clone(),
+            None => format!("http://127.0.0.1:{}", read_port_file("vibe-kanban").await?),
+        };
+        let token = LlmProxyToken::for_workspace(&self.db.pool, workspace.id).await?;
+        Ok(llm_proxy::agent_env(&server_url, &token))
+    }
+}
+
+fn failure_exit_status() -> std::process::ExitStatus {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatusExt::from_raw(256) // Exit code 1 (shifted by 8 bits)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatusExt::from_raw(1)
+    }
+}
+
+#[async_trait]
+impl ContainerService for LocalContainerService {
+    fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>> {
+        &self.msg_stores
+    }
+
+    fn db(&self) -> &DBService {
+        &self.db
+    }
+
+    fn git(&self) -> &GitService {
+        &self.git
+    }
+
+    fn share_publisher(&self) -> Option<&SharePublisher> {
+        self.publisher.as_ref().ok()
+    }
 
     fn notification_service(&self) -> &NotificationService {
         &self.notification_service
@@ -893,6 +2211,16 @@ impl ContainerService for LocalContainerService {
         self.config.read().await.git_branch_prefix.clone()
     }
 
+    async fn attempt_queue_config(&self) -> AttemptQueueConfig {
+        self.config.read().await.attempt_queue.clone()
+    }
+
+    async fn send_interjection(&self, exec_id: &Uuid, message: String) -> bool {
+        let map = self.interjection_senders.read().await;
+        map.get(exec_id)
+            .is_some_and(|sender| sender.send(message).is_ok())
+    }
+
     fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf {
         PathBuf::from(workspace.container_ref.clone().unwrap_or_default())
     }
@@ -930,13 +2258,30 @@ impl ContainerService for LocalContainerService {
                 RepoWorkspaceInput::new(repo.clone(), target_branch)
             })
             .collect();
-
-        let created_workspace = WorkspaceManager::create_workspace(
-            &workspace_dir,
-            &workspace_inputs,
-            &workspace.branch,
-        )
-        .await?;
+        let sparse = self.sparse_checkout(&task, &repositories).await?;
+
+        let created_workspace = match self
+            .workspace_pool
+            .claim(
+                task.project_id,
+                &workspace_dir,
+                &workspace_inputs,
+                &workspace.branch,
+                &sparse,
+            )
+            .await
+        {
+            Some(pooled) => pooled,
+            None => {
+                WorkspaceManager::create_workspace(
+                    &workspace_dir,
+                    &workspace_inputs,
+                    &workspace.branch,
+                    &sparse,
+                )
+                .await?
+            }
+        };
 
         // Copy project files and images to workspace
         self.copy_files_and_images(&created_workspace.workspace_dir, workspace)
@@ -960,10 +2305,26 @@ impl ContainerService for LocalContainerService {
 
     async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError> {
         self.try_stop(workspace, true).await;
-        Self::cleanup_workspace(&self.db, workspace).await;
+        Self::cleanup_workspace(&self.db, &self.terminals, workspace).await;
         Ok(())
     }
 
+    fn spawn_queued_attempt_start(&self, attempt: QueuedAttempt, starting: StartingAttempt) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            container.start_or_requeue_attempt(attempt, starting).await;
+        });
+    }
+
+    fn queue_project_cleanup(&self, deleted: DeletedProject) {
+        project_cleanup::queued(&deleted);
+        if self.project_cleanups.send(deleted).is_err() {
+            tracing::error!("Project cleanup queue is closed");
+        }
+        // Pooled workspaces of the project go with the next pass
+        self.workspace_pool.replenish_now();
+    }
+
     async fn ensure_container_exists(
         &self,
         workspace: &Workspace,
@@ -978,20 +2339,26 @@ impl ContainerService for LocalContainerService {
             )));
         }
 
+        let task = workspace
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
         let workspace_dir = if let Some(container_ref) = &workspace.container_ref {
             PathBuf::from(container_ref)
         } else {
-            let task = workspace
-                .parent_task(&self.db.pool)
-                .await?
-                .ok_or(sqlx::Error::RowNotFound)?;
             let workspace_dir_name =
                 LocalContainerService::dir_name_from_workspace(&workspace.id, &task.title);
             WorkspaceManager::get_workspace_base_dir().join(&workspace_dir_name)
         };
 
-        WorkspaceManager::ensure_workspace_exists(&workspace_dir, &repositories, &workspace.branch)
-            .await?;
+        let sparse = self.sparse_checkout(&task, &repositories).await?;
+        WorkspaceManager::ensure_workspace_exists(
+            &workspace_dir,
+            &repositories,
+            &workspace.branch,
+            &sparse,
+        )
+        .await?;
 
         if workspace.container_ref.is_none() {
             Workspace::update_container_ref(
@@ -1034,6 +2401,309 @@ impl ContainerService for LocalContainerService {
         Ok(true)
     }
 
+    async fn add_workspace_repo(
+        &self,
+        workspace: &Workspace,
+        repo: &Repo,
+        target_branch: &str,
+    ) -> Result<(), ContainerError> {
+        // Workspaces that were never created, or have been cleaned up, get the
+        // worktree along with the others when they are next created
+        let workspace_dir = workspace
+            .container_ref
+            .as_ref()
+            .map(PathBuf::from)
+            .filter(|dir| dir.exists());
+        if let Some(workspace_dir) = &workspace_dir {
+            let task = workspace
+                .parent_task(&self.db.pool)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?;
+            let mut repositories =
+                WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+            repositories.push(repo.clone());
+            let sparse = self.sparse_checkout(&task, &repositories).await?;
+            WorkspaceManager::add_repo(
+                workspace_dir,
+                &RepoWorkspaceInput::new(repo.clone(), target_branch.to_string()),
+                &workspace.branch,
+                &sparse,
+            )
+            .await?;
+        }
+
+        WorkspaceRepo::create_many(
+            &self.db.pool,
+            workspace.id,
+            &[CreateWorkspaceRepo {
+                repo_id: repo.id,
+                target_branch: target_branch.to_string(),
+            }],
+        )
+        .await?;
+
+        if let Some(workspace_dir) = &workspace_dir {
+            self.copy_files_and_images(workspace_dir, workspace).await?;
+            let repositories =
+                WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+            Self::create_workspace_config_files(workspace_dir, &repositories).await?;
+        }
+        Ok(())
+    }
+
+    async fn workspace_env(&self, workspace: &Workspace) -> Result<WorkspaceEnv, ContainerError> {
+        let container_ref = workspace
+            .container_ref
+            .as_ref()
+            .ok_or(ContainerError::Other(anyhow!(
+                "Container ref not found for workspace"
+            )))?;
+        let workspace_dir = PathBuf::from(container_ref);
+
+        // Build ExecutionEnv with VK_* variables
+        let mut env = ExecutionEnv::new();
+
+        // Load task and project context for environment variables
+        let task = workspace
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!(
+                "Task not found for workspace"
+            )))?;
+        let project = task
+            .parent_project(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Project not found for task")))?;
+
+        let settings = ProjectSettings::find_by_project_id(&self.db.pool, project.id).await?;
+
+        // Project variables and secrets first, so they can't shadow the VK_* ones
+        let project_env = ProjectEnv::load(&self.db.pool, project.id)
+            .await
+            .map_err(|e| ContainerError::Other(e.into()))?;
+        let proxy = &settings.llm_proxy;
+        let proxy_keys: Vec<&str> = if proxy.enabled {
+            proxy
+                .providers
+                .iter()
+                .map(|provider| provider.api_key_secret.as_str())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        for (name, value) in &project_env.vars {
+            // Keys the proxy adds stay with the server
+            if !proxy_keys.contains(&name.as_str()) {
+                env.insert(name, value);
+            }
+        }
+        if !proxy_keys.is_empty() {
+            match self.llm_proxy_env(workspace, proxy).await {
+                Ok(vars) => {
+                    for (name, value) in vars {
+                        env.insert(name, value);
+                    }
+                }
+                Err(e) => tracing::error!(
+                    "Failed to point the agents of {} at the LLM proxy: {}",
+                    workspace.id,
+                    e
+                ),
+            }
+        }
+
+        env.insert("VK_PROJECT_NAME", &project.name);
+        env.insert("VK_PROJECT_ID", project.id.to_string());
+        env.insert("VK_TASK_ID", task.id.to_string());
+        env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
+        env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
+        // Executors run in the workspace root, with each repository checked out
+        // in a directory of its name
+        let repo_names: Vec<String> =
+            WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id)
+                .await?
+                .into_iter()
+                .map(|repo| repo.name)
+                .collect();
+        env.insert("VK_WORKSPACE_REPOS", repo_names.join(","));
+        let scratch = workspace_files::scratch_dir(&workspace_dir);
+        tokio::fs::create_dir_all(&scratch).await?;
+        env.insert("VK_SCRATCH", scratch.to_string_lossy());
+        let artifacts = workspace_files::artifacts_dir(&workspace_dir);
+        tokio::fs::create_dir_all(&artifacts).await?;
+        env.insert("VK_ARTIFACTS", artifacts.to_string_lossy());
+
+        let backend = settings.container_backend;
+        let resource_limits = settings.resource_limits;
+        let network_policy = settings.network_policy;
+        let shared_caches = settings.shared_caches;
+        let gpus = settings.gpus;
+        if let Some(config) = kubernetes::config() {
+            // The deployment's backend wins; projects can still pick the image
+            let image = match &backend {
+                ContainerBackend::Docker { image } => Some(image.as_str()),
+                ContainerBackend::Local => None,
+                ContainerBackend::Devcontainer | ContainerBackend::Ssh { .. } => {
+                    return Err(ContainerError::Other(anyhow!(
+                        "Devcontainers and SSH hosts are not supported with the Kubernetes backend"
+                    )));
+                }
+            };
+            caches::prepare(project.id, &shared_caches, &mut env)?;
+            let container =
+                kubernetes::ensure_pod(config, &workspace.id, image, &workspace_dir, &env, &gpus)
+                    .await?;
+            env.container = Some(container);
+            egress::apply(&workspace.id, &network_policy, &mut env).await?;
+            return Ok(WorkspaceEnv {
+                env,
+                secret_values: project_env.secret_values,
+            });
+        }
+
+        env.container = match backend {
+            ContainerBackend::Local => {
+                if let Some(cgroup) = limits::workspace_cgroup(&workspace.id, &resource_limits)? {
+                    env.cgroup = Some(limits::create_leaf(&cgroup, limits::SHARED_LEAF)?);
+                }
+                None
+            }
+            ContainerBackend::Docker { image } => {
+                let repos =
+                    WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+                let mut mounts: Vec<PathBuf> = repos.into_iter().map(|repo| repo.path).collect();
+                mounts.extend(caches::prepare(project.id, &shared_caches, &mut env)?);
+                let name = docker::ensure_container(
+                    &workspace.id,
+                    &image,
+                    &workspace_dir,
+                    &mounts,
+                    gpus.count,
+                )
+                .await?;
+                limits::apply_to_container(&name, &resource_limits).await?;
+                Some(ContainerExec {
+                    name,
+                    user: None,
+                    runtime: ContainerRuntime::Docker,
+                })
+            }
+            ContainerBackend::Devcontainer => {
+                let repos =
+                    WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+                let cache_dirs = caches::prepare(project.id, &shared_caches, &mut env)?;
+                let container =
+                    devcontainer::up(&workspace.id, &workspace_dir, &repos, &cache_dirs).await?;
+                limits::apply_to_container(&container.name, &resource_limits).await?;
+                Some(container)
+            }
+            ContainerBackend::Ssh { host, remote_root } => {
+                let remote_dir = ssh::remote_dir(&remote_root, &workspace.id);
+                env.insert("VK_SCRATCH", format!("{remote_dir}/{SCRATCH_DIR}"));
+                env.insert(
+                    "VK_ARTIFACTS",
+                    format!("{remote_dir}/{SCRATCH_DIR}/{ARTIFACTS_DIR}"),
+                );
+                Some(ssh::prepare(&host, &remote_root, &workspace.id, &workspace_dir, &env).await?)
+            }
+        };
+        egress::apply(&workspace.id, &network_policy, &mut env).await?;
+
+        Ok(WorkspaceEnv {
+            env,
+            secret_values: project_env.secret_values,
+        })
+    }
+
+    async fn preview_targets(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Vec<PreviewTarget>, ContainerError> {
+        // Dev servers of containerized workspaces run in the container, which
+        // is found by label so devcontainers are covered as well
+        if let Some(config) = kubernetes::config() {
+            return Ok(kubernetes::preview_targets(config, &workspace.id).await);
+        }
+        if let Some(container) = docker::find_running_container(&workspace.id).await {
+            return Ok(preview::container_targets(&container).await);
+        }
+        let Some(container_ref) = &workspace.container_ref else {
+            return Ok(Vec::new());
+        };
+        Ok(preview::local_targets(Path::new(container_ref)).await)
+    }
+
+    async fn resource_usage(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Option<ResourceUsage>, ContainerError> {
+        if kubernetes::config().is_some() {
+            return Ok(None);
+        }
+        if let Some(container) = docker::find_running_container(&workspace.id).await {
+            return Ok(limits::container_usage(&container).await);
+        }
+        if let Some(usage) = limits::cgroup_usage(&workspace.id) {
+            return Ok(Some(usage));
+        }
+        // Processes of SSH workspaces run on the remote host
+        if let Some(task) = workspace.parent_task(&self.db.pool).await?
+            && let ContainerBackend::Ssh { .. } =
+                ProjectSettings::find_by_project_id(&self.db.pool, task.project_id)
+                    .await?
+                    .container_backend
+        {
+            return Ok(None);
+        }
+        let Some(container_ref) = workspace.container_ref.clone() else {
+            return Ok(None);
+        };
+        Ok(
+            tokio::task::spawn_blocking(move || limits::process_usage(Path::new(&container_ref)))
+                .await
+                .unwrap_or_default(),
+        )
+    }
+
+    async fn container_status(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<ContainerStatus, ContainerError> {
+        if let Some(config) = kubernetes::config() {
+            return Ok(kubernetes::pod_status(config, &workspace.id).await);
+        }
+        let task = workspace
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let backend = ProjectSettings::find_by_project_id(&self.db.pool, task.project_id)
+            .await?
+            .container_backend;
+        Ok(match backend {
+            ContainerBackend::Local => ContainerStatus::Host,
+            ContainerBackend::Ssh { host, .. } => ContainerStatus::Remote { host },
+            ContainerBackend::Docker { .. } | ContainerBackend::Devcontainer => {
+                docker::container_status(&workspace.id).await
+            }
+        })
+    }
+
+    async fn shared_cache_usage(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<SharedCacheUsage>, ContainerError> {
+        let settings = ProjectSettings::find_by_project_id(&self.db.pool, project_id).await?;
+        Ok(caches::usage(project_id, &settings.shared_caches).await)
+    }
+
+    async fn clear_shared_cache(
+        &self,
+        project_id: Uuid,
+        cache: SharedCache,
+    ) -> Result<bool, ContainerError> {
+        Ok(caches::clear(project_id, cache).await?)
+    }
+
     async fn start_execution_inner(
         &self,
         workspace: &Workspace,
@@ -1066,29 +2736,69 @@ impl ContainerService for LocalContainerService {
                 _ => Arc::new(NoopExecutorApprovalService {}),
             };
 
-        // Build ExecutionEnv with VK_* variables
-        let mut env = ExecutionEnv::new();
-
-        // Load task and project context for environment variables
-        let task = workspace
-            .parent_task(&self.db.pool)
-            .await?
-            .ok_or(ContainerError::Other(anyhow!(
-                "Task not found for workspace"
-            )))?;
-        let project = task
-            .parent_project(&self.db.pool)
-            .await?
-            .ok_or(ContainerError::Other(anyhow!("Project not found for task")))?;
+        let WorkspaceEnv {
+            mut env,
+            secret_values,
+        } = self.workspace_env(workspace).await?;
+        // Give the execution its own leaf next to the shared one, so running
+        // out of memory only kills this execution
+        if let Some(cgroup) = env
+            .cgroup
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+        {
+            env.cgroup = Some(limits::create_leaf(
+                &cgroup,
+                &execution_process.id.to_string(),
+            )?);
+        }
+        // Executions share the remote copy, so it is only refreshed while
+        // nothing else is running in it
+        if let Some(ContainerExec {
+            name: host,
+            runtime:
+                ContainerRuntime::Ssh {
+                    local_dir,
+                    remote_dir,
+                    ..
+                },
+            ..
+        }) = &env.container
+        {
+            let sessions: HashSet<Uuid> =
+                Session::find_by_workspace_id(&self.db.pool, workspace.id)
+                    .await?
+                    .into_iter()
+                    .map(|session| session.id)
+                    .collect();
+            let busy = ExecutionProcess::find_running(&self.db.pool)
+                .await?
+                .iter()
+                .any(|process| {
+                    process.id != execution_process.id && sessions.contains(&process.session_id)
+                });
+            if !busy {
+                ssh::push(host, remote_dir, local_dir).await?;
+            }
+        }
 
-        env.insert("VK_PROJECT_NAME", &project.name);
-        env.insert("VK_PROJECT_ID", project.id.to_string());
-        env.insert("VK_TASK_ID", task.id.to_string());
-        env.insert("VK_WORKSPACE_ID", workspace.id.to_string());
-        env.insert("VK_WORKSPACE_BRANCH", &workspace.branch);
+        // Hold the project's shared caches before the execution can write to
+        // them; they are released by the exit monitor
+        let task = workspace.parent_task(&self.db.pool).await?;
+        let shared_caches = match &task {
+            Some(task) => {
+                let settings = ProjectSettings::find_by_project_id(&self.db.pool, task.project_id)
+                    .await?
+                    .shared_caches;
+                caches::acquire(execution_process.id, task.project_id, &settings).await;
+                settings
+            }
+            None => SharedCacheSettings::default(),
+        };
 
         // Create the child and stream, add to execution tracker with timeout
-        let mut spawned = tokio::time::timeout(
+        let spawned = tokio::time::timeout(
             Duration::from_secs(30),
             executor_action.spawn(&current_dir, approvals_service, &env),
         )
@@ -1097,11 +2807,30 @@ impl ContainerService for LocalContainerService {
             ContainerError::Other(anyhow!(
                 "Timeout: process took more than 30 seconds to start"
             ))
-        })??;
+        })
+        .and_then(|result| result.map_err(ContainerError::from));
+        let mut spawned = match spawned {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                caches::release(execution_process.id, &shared_caches).await;
+                return Err(e);
+            }
+        };
 
-        self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
+        self.track_child_msgs_in_store(execution_process.id, &mut spawned.child, secret_values)
             .await;
 
+        if let Some(pid) = spawned.child.inner().id() {
+            ProcessRegistry::global().register(
+                ProcessInfo::new(
+                    pid,
+                    ProcessKind::Execution,
+                    format!("{:?}", execution_process.run_reason),
+                )
+                .with_workspace(workspace.id)
+                .with_execution(execution_process.id),
+            );
+        }
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
 
@@ -1111,8 +2840,18 @@ impl ContainerService for LocalContainerService {
                 .await;
         }
 
+        if let Some(interjection_sender) = spawned.interjection_sender {
+            self.add_interjection_sender(execution_process.id, interjection_sender)
+                .await;
+        }
+
         // Spawn unified exit monitor: watches OS exit and optional executor signal
         let _hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal);
+        if execution_process.run_reason == ExecutionProcessRunReason::CodingAgent
+            && let Some(task) = &task
+        {
+            self.spawn_budget_watch(execution_process.id, workspace.id, task.project_id);
+        }
 
         Ok(())
     }
@@ -1136,6 +2875,7 @@ impl ContainerService for LocalContainerService {
 
         ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, exit_code)
             .await?;
+        self.remove_interjection_sender(&execution_process.id).await;
 
         // Try graceful interrupt first, then force kill
         if let Some(interrupt_sender) = self.take_interrupt_sender(&execution_process.id).await {
@@ -1180,6 +2920,9 @@ impl ContainerService for LocalContainerService {
             }
         }
         self.remove_child_from_store(&execution_process.id).await;
+        self.persist_tool_calls(execution_process).await;
+        self.persist_entries(execution_process).await;
+        self.persist_test_run(execution_process).await;
 
         // Mark the process finished in the MsgStore
         if let Some(msg) = self.msg_stores.write().await.remove(&execution_process.id) {
@@ -1193,6 +2936,8 @@ impl ContainerService for LocalContainerService {
                 ExecutionProcessRunReason::DevServer
             )
         {
+            self.persist_artifacts(&ctx.execution_process, &ctx.workspace)
+                .await;
             match Task::update_status(&self.db.pool, ctx.task.id, TaskStatus::InReview).await {
                 Ok(_) => {
                     if let Some(publisher) = self.share_publisher()
@@ -1218,6 +2963,7 @@ impl ContainerService for LocalContainerService {
 
         // Record after-head commit OID (best-effort)
         self.update_after_head_commits(execution_process.id).await;
+        self.spawn_start_queued_attempts();
 
         Ok(())
     }
@@ -1313,7 +3059,8 @@ impl ContainerService for LocalContainerService {
             return Ok(false);
         }
 
-        Ok(self.commit_repos(repos_with_changes, &message))
+        let signing = self.config.read().await.commit_signing.clone();
+        Ok(self.commit_repos(repos_with_changes, &message, &signing))
     }
 
     /// Copy files from the original project directory to the worktree.
@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use db::models::repo::Repo;
+use executors::env::{ContainerExec, ContainerRuntime};
+use serde::Deserialize;
+use services::services::container::ContainerError;
+use tokio::process::Command;
+use utils::shell::resolve_executable_path;
+use uuid::Uuid;
+
+use crate::docker::WORKSPACE_LABEL;
+
+const DEVCONTAINER_CLI_PACKAGE: &str = "@devcontainers/cli@0.80.1";
+
+/// Result line printed by `devcontainer up`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpOutput {
+    outcome: String,
+    container_id: Option<String>,
+    remote_user: Option<String>,
+    message: Option<String>,
+}
+
+/// Find the devcontainer config of the first repository in the workspace that
+/// has one, returning the repository's worktree and the config path.
+pub fn find_config(workspace_dir: &Path, repos: &[Repo]) -> Option<(PathBuf, PathBuf)> {
+    repos.iter().find_map(|repo| {
+        let worktree = workspace_dir.join(&repo.name);
+        [
+            worktree.join(".devcontainer").join("devcontainer.json"),
+            worktree.join(".devcontainer.json"),
+        ]
+        .into_iter()
+        .find(|path| path.is_file())
+        .map(|config| (worktree, config))
+    })
+}
+
+/// Bring up the workspace's dev container with the devcontainer CLI, which
+/// builds or pulls the image, installs features and runs the lifecycle commands
+/// such as `postCreateCommand` on first creation. Existing containers are found
+/// again through the workspace label. The workspace directory and the
+/// repositories are also mounted at their host paths so executors and git work
+/// from the same paths as on the host, as are any extra `mounts`.
+pub async fn up(
+    workspace_id: &Uuid,
+    workspace_dir: &Path,
+    repos: &[Repo],
+    mounts: &[PathBuf],
+) -> Result<ContainerExec, ContainerError> {
+    let (worktree, config) = find_config(workspace_dir, repos).ok_or_else(|| {
+        ContainerError::Other(anyhow!(
+            "No .devcontainer/devcontainer.json found in the workspace repositories"
+        ))
+    })?;
+
+    let mut command = devcontainer_command().await;
+    command
+        .arg("up")
+        .arg("--workspace-folder")
+        .arg(&worktree)
+        .arg("--config")
+        .arg(&config)
+        .arg("--id-label")
+        .arg(format!("{WORKSPACE_LABEL}={workspace_id}"));
+    for path in std::iter::once(workspace_dir)
+        .chain(repos.iter().map(|repo| repo.path.as_path()))
+        .chain(mounts.iter().map(PathBuf::as_path))
+    {
+        let path = path.to_string_lossy();
+        command
+            .arg("--mount")
+            .arg(format!("type=bind,source={path},target={path}"));
+    }
+
+    let output = command.kill_on_drop(true).output().await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let result = stdout
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<UpOutput>(line.trim()).ok());
+
+    match result {
+        Some(UpOutput {
+            outcome,
+            container_id: Some(name),
+            remote_user,
+            ..
+        }) if outcome == "success" => {
+            tracing::info!(
+                "Dev container {} ready for workspace {}",
+                name,
+                workspace_id
+            );
+            Ok(ContainerExec {
+                name,
+                user: remote_user,
+                runtime: ContainerRuntime::Docker,
+            })
+        }
+        result => Err(ContainerError::Other(anyhow!(
+            "devcontainer up failed: {}",
+            result
+                .and_then(|r| r.message)
+                .unwrap_or_else(|| String::from_utf8_lossy(&output.stderr).trim().to_string())
+        ))),
+    }
+}
+
+/// Prefer an installed `devcontainer` CLI, falling back to running it via npx
+async fn devcontainer_command() -> Command {
+    if let Some(path) = resolve_executable_path("devcontainer").await {
+        return Command::new(path);
+    }
+    let mut command = Command::new(
+        resolve_executable_path("npx")
+            .await
+            .unwrap_or_else(|| PathBuf::from("npx")),
+    );
+    command.arg("-y").arg(DEVCONTAINER_CLI_PACKAGE);
+    command
+}
@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use services::services::container::{ContainerError, ContainerStatus};
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// Label attached to every container started for a workspace
+pub(crate) const WORKSPACE_LABEL: &str = "vibe-kanban.workspace";
+
+pub fn container_name(workspace_id: &Uuid) -> String {
+    format!("vk-{workspace_id}")
+}
+
+/// Make sure the workspace's container is running and return its name. The
+/// workspace directory and `mounts` (the repositories its worktrees point back
+/// to) are bind-mounted at their host paths so executors can use them unchanged.
+/// GPUs can only be requested when the container is created.
+pub async fn ensure_container(
+    workspace_id: &Uuid,
+    image: &str,
+    workspace_dir: &Path,
+    mounts: &[PathBuf],
+    gpus: u32,
+) -> Result<String, ContainerError> {
+    let name = container_name(workspace_id);
+
+    match docker(&["inspect", "-f", "{{.State.Running}}", &name]).await {
+        Ok(running) if running.trim() == "true" => return Ok(name),
+        Ok(_) => {
+            docker(&["start", &name]).await?;
+            return Ok(name);
+        }
+        Err(_) => {}
+    }
+
+    let label = format!("{WORKSPACE_LABEL}={workspace_id}");
+    let workdir = workspace_dir.to_string_lossy();
+    let mut args: Vec<String> = vec![
+        "run".into(),
+        "-d".into(),
+        "--init".into(),
+        "--name".into(),
+        name.clone(),
+        "--label".into(),
+        label,
+        "-w".into(),
+        workdir.to_string(),
+    ];
+    for path in std::iter::once(workspace_dir).chain(mounts.iter().map(PathBuf::as_path)) {
+        let path = path.to_string_lossy();
+        args.push("-v".into());
+        args.push(format!("{path}:{path}"));
+    }
+    if gpus > 0 {
+        args.extend(["--gpus".into(), gpus.to_string()]);
+    }
+    args.extend([image.to_string(), "sleep".into(), "infinity".into()]);
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    docker(&args).await?;
+    tracing::info!("Started container {} from {} for workspace", name, image);
+    Ok(name)
+}
+
+/// A running container labelled with the workspace, if any
+pub async fn find_running_container(workspace_id: &Uuid) -> Option<String> {
+    let filter = format!("label={WORKSPACE_LABEL}={workspace_id}");
+    let ids = docker(&["ps", "-q", "--filter", &filter]).await.ok()?;
+    ids.split_whitespace().next().map(str::to_string)
+}
+
+/// State of the container labelled with the workspace, running or not
+pub async fn container_status(workspace_id: &Uuid) -> ContainerStatus {
+    let filter = format!("label={WORKSPACE_LABEL}={workspace_id}");
+    let listing = match docker(&[
+        "ps",
+        "-a",
+        "--filter",
+        &filter,
+        "--format",
+        "{{.Names}} {{.State}}",
+    ])
+    .await
+    {
+        Ok(listing) => listing,
+        Err(e) => {
+            return ContainerStatus::Unavailable {
+                reason: e.to_string(),
+            };
+        }
+    };
+    let Some((name, state)) = listing.lines().find_map(|line| line.trim().split_once(' ')) else {
+        return ContainerStatus::Missing;
+    };
+    if state == "running" {
+        ContainerStatus::Running {
+            name: name.to_string(),
+        }
+    } else {
+        ContainerStatus::Stopped {
+            name: name.to_string(),
+            state: state.to_string(),
+        }
+    }
+}
+
+/// Networks the container is attached to, with their gateway addresses
+async fn networks(name: &str) -> Result<Vec<(String, String)>, ContainerError> {
+    let format = r#"{{range $net, $conf := .NetworkSettings.Networks}}{{$net}} {{$conf.Gateway}}{{"\n"}}{{end}}"#;
+    let output = docker(&["inspect", "-f", format, name]).await?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let (network, gateway) = line.split_once(' ')?;
+            Some((network.to_string(), gateway.trim().to_string()))
+        })
+        .collect())
+}
+
+/// Detach the container from every network, leaving it only its loopback
+/// interface
+pub async fn disconnect_networks(name: &str) -> Result<(), ContainerError> {
+    for (network, _) in networks(name).await? {
+        docker(&["network", "disconnect", "-f", &network, name]).await?;
+        tracing::info!("Disconnected container {} from network {}", name, network);
+    }
+    Ok(())
+}
+
+/// Attach the container to the default bridge network unless it is on some
+/// network already, and return the address it reaches the host at
+pub async fn connect_network(name: &str) -> Result<String, ContainerError> {
+    let mut attached = networks(name).await?;
+    if attached.is_empty() {
+        docker(&["network", "connect", "bridge", name]).await?;
+        attached = networks(name).await?;
+    }
+    // Containers on the host network have no gateway
+    Ok(attached
+        .into_iter()
+        .map(|(_, gateway)| gateway)
+        .find(|gateway| !gateway.is_empty())
+        .unwrap_or_else(|| "127.0.0.1".to_string()))
+}
+
+/// Remove every container labelled with the workspace, killing anything still
+/// running in them. A missing docker CLI is ignored.
+pub async fn remove_container(workspace_id: &Uuid) {
+    let filter = format!("label={WORKSPACE_LABEL}={workspace_id}");
+    let ids = match docker(&["ps", "-aq", "--filter", &filter]).await {
+        Ok(ids) => ids,
+        Err(e) => {
+            tracing::debug!("Failed to list containers for {}: {}", workspace_id, e);
+            return;
+        }
+    };
+    for id in ids.split_whitespace() {
+        if let Err(e) = docker(&["rm", "-f", id]).await {
+            tracing::warn!("Failed to remove container {}: {}", id, e);
+        }
+    }
+}
+
+async fn docker(args: &[&str]) -> Result<String, ContainerError> {
+    let output = Command::new("docker")
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(ContainerError::Other(anyhow!(
+            "docker {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,377 @@
+//! Projects' network policies, enforced for executor processes.
+//!
+//! Workspaces with an allowlist get `HTTP(S)_PROXY` variables pointing at a
+//! filtering proxy run by the server, with credentials identifying the
+//! workspace. Requests for hosts the workspace's policy doesn't allow are
+//! refused; HTTPS is tunnelled with `CONNECT` and filtered by host name. Host
+//! processes of offline workspaces go through the same proxy, which refuses
+//! everything. Docker workspaces are instead detached from every network while
+//! offline, which programs can't get around by ignoring the proxy variables.
+//!
+//! The proxy listens on all interfaces so containers can reach it, on
+//! `VK_EGRESS_PROXY_PORT` or a random port, and is only started once a project
+//! needs it.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::anyhow;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use db::models::project_settings::NetworkPolicy;
+use executors::env::{ContainerExec, ContainerRuntime, ExecutionEnv};
+use services::services::container::ContainerError;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::OnceCell,
+};
+use uuid::Uuid;
+
+use crate::docker;
+
+const PROXY_USER: &str = "vk";
+const PROXY_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+/// Reached without the proxy, so servers running in the workspace stay usable
+const NO_PROXY: &str = "localhost,127.0.0.1,::1";
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// Headers about the client's connection to the proxy, not passed upstream
+const HOP_BY_HOP_HEADERS: [&str; 4] = [
+    "proxy-authorization",
+    "proxy-connection",
+    "connection",
+    "keep-alive",
+];
+
+struct Grant {
+    token: String,
+    policy: NetworkPolicy,
+}
+
+struct EgressProxy {
+    port: u16,
+    grants: Mutex<HashMap<Uuid, Grant>>,
+}
+
+static PROXY: OnceCell<EgressProxy> = OnceCell::const_new();
+
+impl EgressProxy {
+    /// Let the workspace's processes use the proxy under `policy`, returning
+    /// their token
+    fn grant(&self, workspace_id: Uuid, policy: &NetworkPolicy) -> String {
+        let mut grants = self.grants.lock().unwrap();
+        let grant = grants.entry(workspace_id).or_insert_with(|| Grant {
+            token: Uuid::new_v4().simple().to_string(),
+            policy: policy.clone(),
+        });
+        grant.policy = policy.clone();
+        grant.token.clone()
+    }
+
+    fn policy_for(&self, token: &str) -> Option<NetworkPolicy> {
+        self.grants
+            .lock()
+            .unwrap()
+            .values()
+            .find(|grant| grant.token == token)
+            .map(|grant| grant.policy.clone())
+    }
+}
+
+async fn proxy() -> Result<&'static EgressProxy, ContainerError> {
+    PROXY
+        .get_or_try_init(|| async {
+            let port = std::env::var("VK_EGRESS_PROXY_PORT")
+                .ok()
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(0);
+            let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+            let port = listener.local_addr()?.port();
+            tracing::info!("Egress proxy listening on port {}", port);
+            tokio::spawn(async move {
+                loop {
+                    match listener.accept().await {
+                        Ok((stream, _)) => {
+                            tokio::spawn(async move {
+                                if let Err(e) = handle(stream).await {
+                                    tracing::debug!("Egress proxy connection failed: {}", e);
+                                }
+                            });
+                        }
+                        Err(e) => tracing::warn!("Egress proxy failed to accept: {}", e),
+                    }
+                }
+            });
+            Ok::<_, ContainerError>(EgressProxy {
+                port,
+                grants: Mutex::default(),
+            })
+        })
+        .await
+}
+
+/// Apply the project's network policy to the workspace's processes, run by
+/// `env`
+pub async fn apply(
+    workspace_id: &Uuid,
+    policy: &NetworkPolicy,
+    env: &mut ExecutionEnv,
+) -> Result<(), ContainerError> {
+    let proxy_host = match &env.container {
+        None => "127.0.0.1".to_string(),
+        Some(ContainerExec {
+            name,
+            runtime: ContainerRuntime::Docker,
+            ..
+        }) => {
+            if *policy == NetworkPolicy::Offline {
+                docker::disconnect_networks(name).await?;
+                forget(workspace_id);
+                return Ok(());
+            }
+            let gateway = docker::connect_network(name).await?;
+            // Docker Desktop runs containers in a VM, so the gateway isn't
+            // the host there
+            if cfg!(target_os = "linux") {
+                gateway
+            } else {
+                "host.docker.internal".to_string()
+            }
+        }
+        Some(_) if *policy == NetworkPolicy::Unrestricted => return Ok(()),
+        Some(_) => {
+            return Err(ContainerError::Other(anyhow!(
+                "Network policies are only supported for local and Docker workspaces"
+            )));
+        }
+    };
+    if *policy == NetworkPolicy::Unrestricted {
+        forget(workspace_id);
+        return Ok(());
+    }
+
+    let proxy = proxy().await?;
+    let token = proxy.grant(*workspace_id, policy);
+    let url = format!("http://{PROXY_USER}:{token}@{proxy_host}:{}", proxy.port);
+    for name in PROXY_VARS {
+        env.insert(name, &url);
+    }
+    env.insert("NO_PROXY", NO_PROXY);
+    env.insert("no_proxy", NO_PROXY);
+    Ok(())
+}
+
+/// Revoke the workspace's proxy credentials
+pub fn forget(workspace_id: &Uuid) {
+    if let Some(proxy) = PROXY.get() {
+        proxy.grants.lock().unwrap().remove(workspace_id);
+    }
+}
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    host: String,
+    port: u16,
+    /// Origin-form target of a plain HTTP request, empty for `CONNECT`
+    path: String,
+    version: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next()?.split(' ');
+        let (method, target, version) = (
+            request_line.next()?,
+            request_line.next()?,
+            request_line.next()?,
+        );
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+
+        let (authority, path, default_port) = if method.eq_ignore_ascii_case("CONNECT") {
+            (target, "", 443)
+        } else {
+            // Clients tunnel HTTPS with CONNECT, so only plain HTTP URLs arrive here
+            let rest = target.strip_prefix("http://")?;
+            let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            (authority, if path.is_empty() { "/" } else { path }, 80)
+        };
+        let (host, port) = split_authority(authority, default_port)?;
+        Some(Self {
+            method: method.to_string(),
+            host,
+            port,
+            path: path.to_string(),
+            version: version.to_string(),
+            headers,
+        })
+    }
+
+    fn is_connect(&self) -> bool {
+        self.method.eq_ignore_ascii_case("CONNECT")
+    }
+
+    /// Token from the `Proxy-Authorization` header
+    fn token(&self) -> Option<String> {
+        let (_, value) = self
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("proxy-authorization"))?;
+        let encoded = value.strip_prefix("Basic ")?;
+        let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+        let (user, token) = decoded.split_once(':')?;
+        (user == PROXY_USER).then(|| token.to_string())
+    }
+
+    /// Head of the request as sent to the destination server
+    fn upstream_head(&self) -> String {
+        let mut head = format!("{} {} {}\r\n", self.method, self.path, self.version);
+        for (name, value) in &self.headers {
+            if !HOP_BY_HOP_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                head.push_str(&format!("{name}: {value}\r\n"));
+            }
+        }
+        // Another request on the same connection could be for another host
+        head.push_str("Connection: close\r\n\r\n");
+        head
+    }
+}
+
+fn split_authority(authority: &str, default_port: u16) -> Option<(String, u16)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        match rest.strip_prefix(':') {
+            Some(port) => (host, port.parse().ok()?),
+            None if rest.is_empty() => (host, default_port),
+            None => return None,
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (authority, default_port),
+        }
+    };
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+async fn handle(mut client: TcpStream) -> std::io::Result<()> {
+    let (head, body) = read_head(&mut client).await?;
+    let Some(request) = Request::parse(&head) else {
+        return respond(&mut client, "400 Bad Request", "", "Malformed request").await;
+    };
+    let policy = request
+        .token()
+        .and_then(|token| PROXY.get()?.policy_for(&token));
+    let Some(policy) = policy else {
+        return respond(
+            &mut client,
+            "407 Proxy Authentication Required",
+            "Proxy-Authenticate: Basic realm=\"vibe-kanban\"\r\n",
+            "Missing or unknown workspace credentials",
+        )
+        .await;
+    };
+    if !policy.allows_host(&request.host) {
+        let message = format!(
+            "{} is blocked by the project's network policy",
+            request.host
+        );
+        return respond(&mut client, "403 Forbidden", "", &message).await;
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)).await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            let message = format!("Failed to connect to {}: {e}", request.host);
+            return respond(&mut client, "502 Bad Gateway", "", &message).await;
+        }
+    };
+    if request.is_connect() {
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await?;
+    } else {
+        upstream
+            .write_all(request.upstream_head().as_bytes())
+            .await?;
+    }
+    upstream.write_all(&body).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Read up to the end of the request head, returning it and whatever was read
+/// past it
+async fn read_head(stream: &mut TcpStream) -> std::io::Result<(String, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            buf.truncate(end);
+            return Ok((String::from_utf8_lossy(&buf).into_owned(), rest));
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err(std::io::Error::other("Request head too large"));
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\n{headers}Content-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proxy_requests() {
+        let credentials = STANDARD.encode("vk:secret");
+        let connect = Request::parse(&format!(
+            "CONNECT github.com:443 HTTP/1.1\r\nHost: github.com:443\r\nProxy-Authorization: Basic {credentials}"
+        ))
+        .unwrap();
+        assert!(connect.is_connect());
+        assert_eq!((connect.host.as_str(), connect.port), ("github.com", 443));
+        assert_eq!(connect.token().as_deref(), Some("secret"));
+
+        let get = Request::parse(
+            "GET http://[::1]:8080 HTTP/1.1\r\nHost: [::1]:8080\r\nProxy-Connection: keep-alive",
+        )
+        .unwrap();
+        assert_eq!((get.host.as_str(), get.port), ("::1", 8080));
+        assert_eq!(get.token(), None);
+        assert_eq!(
+            get.upstream_head(),
+            "GET / HTTP/1.1\r\nHost: [::1]:8080\r\nConnection: close\r\n\r\n"
+        );
+
+        assert!(Request::parse("GET https://github.com/ HTTP/1.1").is_none());
+        assert!(Request::parse("CONNECT :443 HTTP/1.1").is_none());
+    }
+}
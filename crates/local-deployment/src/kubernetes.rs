@@ -0,0 +1,419 @@
+//! Run workspaces as Kubernetes pods, for deployments where the server itself
+//! runs in a cluster.
+//!
+//! Enabled with `VK_CONTAINER_BACKEND=kubernetes`. Worktrees are still created
+//! by the server, so the directory holding the workspaces and repositories must
+//! live on a shared (`ReadWriteMany`) claim, which every pod mounts at the same
+//! path. Each workspace additionally gets its own claim for its home directory,
+//! so dependency caches survive pod restarts until the workspace is removed.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::OnceLock,
+};
+
+use anyhow::anyhow;
+use db::models::project_settings::GpuSettings;
+use executors::env::{ContainerExec, ContainerRuntime, ExecutionEnv};
+use serde_json::json;
+use services::services::container::{ContainerError, ContainerStatus, PreviewTarget};
+use tokio::{io::AsyncWriteExt, process::Command};
+use uuid::Uuid;
+
+use crate::{docker::WORKSPACE_LABEL, preview};
+
+/// Where each pod mounts its workspace's own claim
+const DATA_MOUNT: &str = "/vk";
+/// Workspace variables, sourced before every command run in the pod
+const ENV_FILE: &str = "/vk/env.sh";
+const CONTAINER_NAME: &str = "workspace";
+const READY_TIMEOUT: &str = "300s";
+
+#[derive(Debug, Clone)]
+pub struct KubernetesConfig {
+    pub namespace: String,
+    /// Image used unless the project configures its own
+    pub image: String,
+    /// Claim holding the workspaces and repositories
+    pub shared_claim: String,
+    /// Path the shared claim is mounted at, on the server and in every pod
+    pub shared_mount: PathBuf,
+    pub storage_class: Option<String>,
+    pub storage_size: String,
+}
+
+impl KubernetesConfig {
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        if var("VK_CONTAINER_BACKEND")? != "kubernetes" {
+            return None;
+        }
+        let (Some(image), Some(shared_claim), Some(shared_mount)) = (
+            var("VK_K8S_IMAGE"),
+            var("VK_K8S_SHARED_CLAIM"),
+            var("VK_K8S_SHARED_MOUNT"),
+        ) else {
+            tracing::error!(
+                "VK_CONTAINER_BACKEND=kubernetes requires VK_K8S_IMAGE, VK_K8S_SHARED_CLAIM and VK_K8S_SHARED_MOUNT"
+            );
+            return None;
+        };
+        Some(Self {
+            namespace: var("VK_K8S_NAMESPACE").unwrap_or_else(|| "default".to_string()),
+            image,
+            shared_claim,
+            shared_mount: PathBuf::from(shared_mount),
+            storage_class: var("VK_K8S_STORAGE_CLASS"),
+            storage_size: var("VK_K8S_STORAGE_SIZE").unwrap_or_else(|| "10Gi".to_string()),
+        })
+    }
+}
+
+/// The deployment's Kubernetes configuration, when that backend is enabled
+pub fn config() -> Option<&'static KubernetesConfig> {
+    static CONFIG: OnceLock<Option<KubernetesConfig>> = OnceLock::new();
+    CONFIG.get_or_init(KubernetesConfig::from_env).as_ref()
+}
+
+fn pod_name(workspace_id: &Uuid) -> String {
+    format!("vk-{workspace_id}")
+}
+
+fn claim_name(workspace_id: &Uuid) -> String {
+    format!("vk-{workspace_id}-data")
+}
+
+/// Make sure the workspace's pod is running with the current workspace
+/// variables, recreating it if it has terminated or requests other GPUs, and
+/// return how to exec into it.
+pub async fn ensure_pod(
+    config: &KubernetesConfig,
+    workspace_id: &Uuid,
+    image: Option<&str>,
+    workspace_dir: &Path,
+    env: &ExecutionEnv,
+    gpus: &GpuSettings,
+) -> Result<ContainerExec, ContainerError> {
+    if !workspace_dir.starts_with(&config.shared_mount) {
+        return Err(ContainerError::Other(anyhow!(
+            "Workspace {} is outside the shared mount {}",
+            workspace_dir.display(),
+            config.shared_mount.display()
+        )));
+    }
+    let name = pod_name(workspace_id);
+    let existing = kubectl(
+        config,
+        &["get", "pod", &name, "--ignore-not-found", "-o", "json"],
+        None,
+    )
+    .await?;
+    if let Ok(pod) = serde_json::from_str::<serde_json::Value>(&existing) {
+        // Pods don't restart once they have terminated, e.g. after an eviction
+        let terminated = matches!(
+            pod["status"]["phase"].as_str(),
+            Some("Succeeded" | "Failed" | "Unknown")
+        );
+        // and their resources can't be changed in place
+        let requested = pod["spec"]["containers"][0]["resources"]["limits"]
+            [&gpus.kubernetes_resource]
+            .as_str()
+            .and_then(|count| count.parse::<u32>().ok())
+            .unwrap_or(0);
+        if terminated || requested != gpus.count {
+            tracing::info!("Recreating pod {}", name);
+            kubectl(config, &["delete", "pod", &name, "--wait=true"], None).await?;
+        }
+    }
+
+    let manifests = json!({
+        "apiVersion": "v1",
+        "kind": "List",
+        "items": [
+            claim_manifest(config, workspace_id),
+            pod_manifest(
+                config,
+                workspace_id,
+                image.unwrap_or(&config.image),
+                workspace_dir,
+                gpus,
+            ),
+        ],
+    });
+    kubectl(
+        config,
+        &["apply", "-f", "-"],
+        Some(manifests.to_string().as_bytes()),
+    )
+    .await?;
+    kubectl(
+        config,
+        &[
+            "wait",
+            "--for=condition=Ready",
+            &format!("pod/{name}"),
+            &format!("--timeout={READY_TIMEOUT}"),
+        ],
+        None,
+    )
+    .await?;
+
+    let env_file = env_file_contents(env)?;
+    kubectl(
+        config,
+        &[
+            "exec",
+            "-i",
+            &name,
+            "-c",
+            CONTAINER_NAME,
+            "--",
+            "sh",
+            "-c",
+            &format!("umask 077 && cat > {ENV_FILE}"),
+        ],
+        Some(env_file.as_bytes()),
+    )
+    .await?;
+
+    Ok(ContainerExec {
+        name,
+        user: None,
+        runtime: ContainerRuntime::Kubernetes {
+            namespace: config.namespace.clone(),
+            env_file: ENV_FILE.to_string(),
+            env_file_vars: env.vars.keys().cloned().collect(),
+        },
+    })
+}
+
+fn claim_manifest(config: &KubernetesConfig, workspace_id: &Uuid) -> serde_json::Value {
+    let mut spec = json!({
+        "accessModes": ["ReadWriteOnce"],
+        "resources": { "requests": { "storage": config.storage_size } },
+    });
+    if let Some(storage_class) = &config.storage_class {
+        spec["storageClassName"] = json!(storage_class);
+    }
+    json!({
+        "apiVersion": "v1",
+        "kind": "PersistentVolumeClaim",
+        "metadata": {
+            "name": claim_name(workspace_id),
+            "namespace": config.namespace,
+            "labels": { WORKSPACE_LABEL: workspace_id.to_string() },
+        },
+        "spec": spec,
+    })
+}
+
+fn pod_manifest(
+    config: &KubernetesConfig,
+    workspace_id: &Uuid,
+    image: &str,
+    workspace_dir: &Path,
+    gpus: &GpuSettings,
+) -> serde_json::Value {
+    let mut manifest = json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": {
+            "name": pod_name(workspace_id),
+            "namespace": config.namespace,
+            "labels": { WORKSPACE_LABEL: workspace_id.to_string() },
+        },
+        "spec": {
+            "restartPolicy": "Never",
+            "containers": [{
+                "name": CONTAINER_NAME,
+                "image": image,
+                "command": ["sleep", "infinity"],
+                "workingDir": workspace_dir,
+                "env": [{ "name": "HOME", "value": format!("{DATA_MOUNT}/home") }],
+                "volumeMounts": [
+                    { "name": "shared", "mountPath": config.shared_mount },
+                    { "name": "data", "mountPath": DATA_MOUNT },
+                ],
+            }],
+            "volumes": [
+                {
+                    "name": "shared",
+                    "persistentVolumeClaim": { "claimName": config.shared_claim },
+                },
+                {
+                    "name": "data",
+                    "persistentVolumeClaim": { "claimName": claim_name(workspace_id) },
+                },
+            ],
+        },
+    });
+    if gpus.count > 0 {
+        // Extended resources are only given as limits, requests follow them
+        manifest["spec"]["containers"][0]["resources"] = json!({
+            "limits": { gpus.kubernetes_resource.clone(): gpus.count.to_string() },
+        });
+    }
+    manifest
+}
+
+pub(crate) fn env_file_contents(env: &ExecutionEnv) -> Result<String, ContainerError> {
+    let mut contents = String::new();
+    for (key, value) in &env.vars {
+        let value = shlex::try_quote(value).map_err(|_| {
+            ContainerError::Other(anyhow!("Variable {key} contains a NUL character"))
+        })?;
+        contents.push_str(&format!("{key}={value}\n"));
+    }
+    Ok(contents)
+}
+
+/// Delete the workspace's pod, killing anything still running in it. The
+/// workspace's claim is kept for its next run.
+pub async fn remove_pod(config: &KubernetesConfig, workspace_id: &Uuid) {
+    let name = pod_name(workspace_id);
+    if let Err(e) = kubectl(
+        config,
+        &["delete", "pod", &name, "--ignore-not-found", "--wait=false"],
+        None,
+    )
+    .await
+    {
+        tracing::warn!("Failed to delete pod {}: {}", name, e);
+    }
+}
+
+/// Delete the workspace's pod and claim
+pub async fn remove_workspace(config: &KubernetesConfig, workspace_id: &Uuid) {
+    remove_pod(config, workspace_id).await;
+    let claim = claim_name(workspace_id);
+    if let Err(e) = kubectl(
+        config,
+        &[
+            "delete",
+            "pvc",
+            &claim,
+            "--ignore-not-found",
+            "--wait=false",
+        ],
+        None,
+    )
+    .await
+    {
+        tracing::warn!("Failed to delete claim {}: {}", claim, e);
+    }
+}
+
+/// Phase of the workspace's pod
+pub async fn pod_status(config: &KubernetesConfig, workspace_id: &Uuid) -> ContainerStatus {
+    let name = pod_name(workspace_id);
+    let phase = match kubectl(
+        config,
+        &[
+            "get",
+            "pod",
+            &name,
+            "--ignore-not-found",
+            "-o",
+            "jsonpath={.status.phase}",
+        ],
+        None,
+    )
+    .await
+    {
+        Ok(phase) => phase.trim().to_string(),
+        Err(e) => {
+            return ContainerStatus::Unavailable {
+                reason: e.to_string(),
+            };
+        }
+    };
+    match phase.as_str() {
+        "" => ContainerStatus::Missing,
+        "Running" => ContainerStatus::Running { name },
+        _ => ContainerStatus::Stopped { name, state: phase },
+    }
+}
+
+/// Ports listened on in the workspace's pod, reached through the pod's IP
+pub async fn preview_targets(config: &KubernetesConfig, workspace_id: &Uuid) -> Vec<PreviewTarget> {
+    let name = pod_name(workspace_id);
+    let Ok(ip) = kubectl(
+        config,
+        &["get", "pod", &name, "-o", "jsonpath={.status.podIP}"],
+        None,
+    )
+    .await
+    else {
+        return Vec::new();
+    };
+    let Ok(tables) = kubectl(
+        config,
+        &[
+            "exec",
+            &name,
+            "-c",
+            CONTAINER_NAME,
+            "--",
+            "sh",
+            "-c",
+            preview::PROC_NET_TCP_SCRIPT,
+        ],
+        None,
+    )
+    .await
+    else {
+        return Vec::new();
+    };
+    preview::remote_targets(ip.trim(), &tables)
+}
+
+async fn kubectl(
+    config: &KubernetesConfig,
+    args: &[&str],
+    stdin: Option<&[u8]>,
+) -> Result<String, ContainerError> {
+    let mut child = Command::new("kubectl")
+        .arg("-n")
+        .arg(&config.namespace)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(ContainerError::Other(anyhow!(
+            "kubectl {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_file_quotes_values() {
+        let mut env = ExecutionEnv::new();
+        env.insert("GREETING", "it's $HOME");
+        let contents = env_file_contents(&env).unwrap();
+        let (key, value) = contents.trim_end().split_once('=').unwrap();
+        assert_eq!(key, "GREETING");
+        assert_eq!(shlex::split(value).unwrap(), ["it's $HOME"]);
+
+        env.insert("BROKEN", "a\0b");
+        assert!(env_file_contents(&env).is_err());
+    }
+}
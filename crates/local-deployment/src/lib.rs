@@ -21,6 +21,8 @@ use services::services::{
     remote_client::{RemoteClient, RemoteClientError},
     repo::RepoService,
     share::{ShareConfig, SharePublisher},
+    terminal::TerminalService,
+    workspace_pool::WorkspacePool,
 };
 use tokio::sync::RwLock;
 use utils::{
@@ -31,9 +33,17 @@ use utils::{
 use uuid::Uuid;
 
 use crate::container::LocalContainerService;
+mod caches;
 mod command;
 pub mod container;
 mod copy;
+mod devcontainer;
+mod docker;
+mod egress;
+mod kubernetes;
+mod limits;
+mod preview;
+mod ssh;
 
 #[derive(Clone)]
 pub struct LocalDeployment {
@@ -51,6 +61,8 @@ pub struct LocalDeployment {
     file_search_cache: Arc<FileSearchCache>,
     approvals: Approvals,
     queued_message_service: QueuedMessageService,
+    terminals: TerminalService,
+    workspace_pool: WorkspacePool,
     share_publisher: Result<SharePublisher, RemoteClientNotConfigured>,
     share_config: Option<ShareConfig>,
     remote_client: Result<RemoteClient, RemoteClientNotConfigured>,
@@ -172,6 +184,8 @@ impl Deployment for LocalDeployment {
             user_id: user_id.clone(),
             analytics_service: s.clone(),
         });
+        let workspace_pool = WorkspacePool::new();
+        let terminals = TerminalService::new();
         let container = LocalContainerService::new(
             db.clone(),
             msg_stores.clone(),
@@ -182,13 +196,18 @@ impl Deployment for LocalDeployment {
             approvals.clone(),
             queued_message_service.clone(),
             share_publisher.clone(),
+            workspace_pool.clone(),
+            terminals.clone(),
         )
         .await;
+        workspace_pool.spawn_replenisher(db.pool.clone());
 
         let events = EventService::new(db.clone(), events_msg_store, events_entry_count);
 
         let file_search_cache = Arc::new(FileSearchCache::new());
 
+        terminals.spawn_idle_reaper();
+
         let deployment = Self {
             config,
             user_id,
@@ -204,6 +223,8 @@ impl Deployment for LocalDeployment {
             file_search_cache,
             approvals,
             queued_message_service,
+            terminals,
+            workspace_pool,
             share_publisher,
             share_config: share_config.clone(),
             remote_client,
@@ -270,6 +291,14 @@ impl Deployment for LocalDeployment {
         &self.queued_message_service
     }
 
+    fn terminals(&self) -> &TerminalService {
+        &self.terminals
+    }
+
+    fn workspace_pool(&self) -> &WorkspacePool {
+        &self.workspace_pool
+    }
+
     fn share_publisher(&self) -> Result<SharePublisher, RemoteClientNotConfigured> {
         self.share_publisher.clone()
     }
@@ -0,0 +1,348 @@
+//! CPU, memory and process-count limits for task attempts, and measuring what
+//! an attempt currently uses.
+//!
+//! Host processes are limited with cgroup v2 when `VK_CGROUP_ROOT` points at a
+//! cgroup delegated to the server's user (e.g. a systemd unit with
+//! `Delegate=yes`). Every workspace gets a cgroup below it holding the limits,
+//! with a leaf per execution and a `shared` leaf for terminals and exec
+//! commands. An execution that runs out of memory is killed as a whole, and
+//! the reason is added to its log. Docker and devcontainer workspaces get the
+//! limits on their container instead. Without either, limits are not enforced
+//! but usage is still measured from `/proc`.
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use db::models::project_settings::ResourceLimits;
+use services::services::container::{ContainerError, ResourceUsage};
+use uuid::Uuid;
+
+/// Leaf for processes that don't belong to an execution
+pub const SHARED_LEAF: &str = "shared";
+const CPU_PERIOD_USEC: u64 = 100_000;
+const CONTROLLERS: &str = "+cpu +memory +pids";
+/// Files read for [`ResourceUsage`], relative to a cgroup directory
+const USAGE_FILES: [&str; 4] = [
+    "memory.current",
+    "cpu.stat",
+    "pids.current",
+    "memory.events",
+];
+
+/// The delegated cgroup that workspace cgroups are created in
+pub fn cgroup_root() -> Option<&'static Path> {
+    static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+    ROOT.get_or_init(|| {
+        let root = PathBuf::from(std::env::var("VK_CGROUP_ROOT").ok()?);
+        match std::fs::write(root.join("cgroup.subtree_control"), CONTROLLERS) {
+            Ok(()) => Some(root),
+            Err(e) => {
+                tracing::error!(
+                    "Can't enable cgroup controllers in {}, resource limits won't be enforced: {}",
+                    root.display(),
+                    e
+                );
+                None
+            }
+        }
+    })
+    .as_deref()
+}
+
+fn workspace_cgroup_path(root: &Path, workspace_id: &Uuid) -> PathBuf {
+    root.join(format!("vk-{workspace_id}"))
+}
+
+/// Path of a leaf of the workspace's cgroup, whether or not it exists
+pub fn leaf_path(workspace_id: &Uuid, leaf: &str) -> Option<PathBuf> {
+    cgroup_root().map(|root| workspace_cgroup_path(root, workspace_id).join(leaf))
+}
+
+/// Create the workspace's cgroup with the current limits, returning `None`
+/// when cgroups aren't configured
+pub fn workspace_cgroup(
+    workspace_id: &Uuid,
+    limits: &ResourceLimits,
+) -> io::Result<Option<PathBuf>> {
+    let Some(root) = cgroup_root() else {
+        return Ok(None);
+    };
+    let cgroup = workspace_cgroup_path(root, workspace_id);
+    std::fs::create_dir_all(&cgroup)?;
+    std::fs::write(cgroup.join("cgroup.subtree_control"), CONTROLLERS)?;
+
+    let memory = limits.memory_mb.map_or("max".to_string(), |mb| {
+        (u64::from(mb) * 1024 * 1024).to_string()
+    });
+    std::fs::write(cgroup.join("memory.max"), &memory)?;
+    // Swapping would only delay hitting the limit
+    let _ = std::fs::write(cgroup.join("memory.swap.max"), &memory);
+    let cpu = limits.cpu_cores.map_or("max".to_string(), |cores| {
+        ((cores * CPU_PERIOD_USEC as f64) as u64)
+            .max(1000)
+            .to_string()
+    });
+    std::fs::write(cgroup.join("cpu.max"), format!("{cpu} {CPU_PERIOD_USEC}"))?;
+    let pids = limits
+        .max_processes
+        .map_or("max".to_string(), |max| max.to_string());
+    std::fs::write(cgroup.join("pids.max"), pids)?;
+    Ok(Some(cgroup))
+}
+
+/// Create a leaf below a workspace cgroup for processes to run in
+pub fn create_leaf(workspace_cgroup: &Path, leaf: &str) -> io::Result<PathBuf> {
+    let path = workspace_cgroup.join(leaf);
+    std::fs::create_dir_all(&path)?;
+    // Kill every process of the leaf together instead of leaving an
+    // execution half alive after one of its processes was picked
+    std::fs::write(path.join("memory.oom.group"), "1")?;
+    Ok(path)
+}
+
+/// Explanation for the log of an execution whose leaf was killed for
+/// exceeding the memory limit
+pub fn oom_message(leaf: &Path, limits: &ResourceLimits) -> Option<String> {
+    let events = std::fs::read_to_string(leaf.join("memory.events")).ok()?;
+    if counter(&events, "oom_kill") == 0 {
+        return None;
+    }
+    let limit = limits
+        .memory_mb
+        .map_or(String::new(), |mb| format!(" of {mb} MB"));
+    Some(format!(
+        "Killed: the attempt exceeded its memory limit{limit}"
+    ))
+}
+
+fn counter(contents: &str, key: &str) -> u64 {
+    contents
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .find(|(name, _)| *name == key)
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Remove a leaf once its processes are gone. Fails harmlessly while some are
+/// still running.
+pub fn remove_leaf(leaf: &Path) {
+    if let Err(e) = std::fs::remove_dir(leaf)
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        tracing::debug!("Failed to remove cgroup {}: {}", leaf.display(), e);
+    }
+}
+
+/// Remove the workspace's cgroup and its leaves
+pub fn remove_workspace(workspace_id: &Uuid) {
+    let Some(root) = cgroup_root() else {
+        return;
+    };
+    let cgroup = workspace_cgroup_path(root, workspace_id);
+    if let Ok(leaves) = std::fs::read_dir(&cgroup) {
+        for leaf in leaves.filter_map(Result::ok) {
+            if leaf.file_type().is_ok_and(|ft| ft.is_dir()) {
+                remove_leaf(&leaf.path());
+            }
+        }
+    }
+    remove_leaf(&cgroup);
+}
+
+/// Usage of all processes in the workspace's cgroup
+pub fn cgroup_usage(workspace_id: &Uuid) -> Option<ResourceUsage> {
+    let cgroup = workspace_cgroup_path(cgroup_root()?, workspace_id);
+    let files: HashMap<String, String> = USAGE_FILES
+        .iter()
+        .filter_map(|name| {
+            let contents = std::fs::read_to_string(cgroup.join(name)).ok()?;
+            Some((name.to_string(), contents))
+        })
+        .collect();
+    parse_usage(&files)
+}
+
+/// Usage of a workspace container, read from the container's own cgroup
+pub async fn container_usage(container: &str) -> Option<ResourceUsage> {
+    let script = format!(
+        "cd /sys/fs/cgroup && for f in {}; do echo \"==> $f\"; cat \"$f\" 2>/dev/null; done",
+        USAGE_FILES.join(" ")
+    );
+    let output = tokio::process::Command::new("docker")
+        .args(["exec", container, "sh", "-c", &script])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_usage(&split_sections(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Split `==> name` headed sections, as printed by the usage script
+fn split_sections(output: &str) -> HashMap<String, String> {
+    let mut files = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix("==> ") {
+            files.extend(current.take());
+            current = Some((name.to_string(), String::new()));
+        } else if let Some((_, contents)) = &mut current {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+    }
+    files.extend(current);
+    files.retain(|_, contents| !contents.is_empty());
+    files
+}
+
+fn parse_usage(files: &HashMap<String, String>) -> Option<ResourceUsage> {
+    let memory_bytes = files.get("memory.current")?.trim().parse().ok()?;
+    Some(ResourceUsage {
+        memory_bytes,
+        cpu_usec: files
+            .get("cpu.stat")
+            .map_or(0, |stat| counter(stat, "usage_usec")),
+        processes: files
+            .get("pids.current")
+            .and_then(|pids| pids.trim().parse().ok())
+            .unwrap_or(0),
+        oom_kills: files
+            .get("memory.events")
+            .map_or(0, |events| counter(events, "oom_kill")),
+    })
+}
+
+/// Limit a workspace container. Limits that were unset are lifted again,
+/// except memory, which Docker can only raise.
+pub async fn apply_to_container(
+    container: &str,
+    limits: &ResourceLimits,
+) -> Result<(), ContainerError> {
+    let mut args = vec!["update".to_string()];
+    if let Some(mb) = limits.memory_mb {
+        args.extend([
+            "--memory".to_string(),
+            format!("{mb}m"),
+            "--memory-swap".to_string(),
+            format!("{mb}m"),
+        ]);
+    }
+    args.extend([
+        "--cpus".to_string(),
+        limits.cpu_cores.unwrap_or(0.0).to_string(),
+        "--pids-limit".to_string(),
+        limits
+            .max_processes
+            .map_or("-1".to_string(), |max| max.to_string()),
+        container.to_string(),
+    ]);
+    let output = tokio::process::Command::new("docker")
+        .args(&args)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(ContainerError::Other(anyhow::anyhow!(
+            "Failed to apply resource limits to {}: {}",
+            container,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Usage of host processes whose working directory is inside
+/// `workspace_dir`, for workspaces without a cgroup
+#[cfg(target_os = "linux")]
+pub fn process_usage(workspace_dir: &Path) -> Option<ResourceUsage> {
+    // SAFETY: sysconf has no preconditions
+    let ticks_per_sec = unsafe { nix::libc::sysconf(nix::libc::_SC_CLK_TCK) };
+    let usec_per_tick = 1_000_000 / u64::try_from(ticks_per_sec).ok().filter(|t| *t > 0)?;
+
+    let mut usage = ResourceUsage::default();
+    for entry in std::fs::read_dir("/proc").ok()?.filter_map(Result::ok) {
+        let proc_dir = entry.path();
+        let in_workspace = std::fs::read_link(proc_dir.join("cwd"))
+            .is_ok_and(|cwd| cwd.starts_with(workspace_dir));
+        if !in_workspace {
+            continue;
+        }
+        let status = std::fs::read_to_string(proc_dir.join("status")).unwrap_or_default();
+        let rss_kb = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))
+            .and_then(|value| {
+                value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+            .unwrap_or(0);
+        // utime and stime are the 14th and 15th fields, counted after the
+        // parenthesised command name which may contain spaces
+        let stat = std::fs::read_to_string(proc_dir.join("stat")).unwrap_or_default();
+        let ticks: u64 = stat
+            .rsplit_once(')')
+            .map(|(_, rest)| {
+                rest.split_whitespace()
+                    .skip(11)
+                    .take(2)
+                    .filter_map(|value| value.parse::<u64>().ok())
+                    .sum()
+            })
+            .unwrap_or(0);
+
+        usage.memory_bytes += rss_kb * 1024;
+        usage.cpu_usec += ticks * usec_per_tick;
+        usage.processes += 1;
+    }
+    Some(usage)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn process_usage(_workspace_dir: &Path) -> Option<ResourceUsage> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_container_usage_output() {
+        let output = "\
+==> memory.current
+52428800
+==> cpu.stat
+usage_usec 1250000
+user_usec 1000000
+system_usec 250000
+==> pids.current
+7
+==> memory.events
+low 0
+high 0
+max 3
+oom 1
+oom_kill 1
+";
+        let usage = parse_usage(&split_sections(output)).unwrap();
+        assert_eq!(usage.memory_bytes, 52_428_800);
+        assert_eq!(usage.cpu_usec, 1_250_000);
+        assert_eq!(usage.processes, 7);
+        assert_eq!(usage.oom_kills, 1);
+
+        // cgroup v1 hosts have none of these files
+        assert!(parse_usage(&split_sections("==> memory.current\n")).is_none());
+    }
+}
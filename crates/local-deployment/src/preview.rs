@@ -0,0 +1,274 @@
+//! Detect TCP ports that processes in a workspace are listening on, so dev
+//! servers can be previewed through the server's proxy.
+
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+};
+
+use services::services::container::PreviewTarget;
+use tokio::process::Command;
+
+/// A listening socket from `/proc/net/tcp` or `/proc/net/tcp6`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ListeningSocket {
+    addr: IpAddr,
+    port: u16,
+    inode: u64,
+}
+
+const TCP_LISTEN: &str = "0A";
+
+fn parse_proc_net_tcp(contents: &str) -> Vec<ListeningSocket> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                return None;
+            }
+            let (addr, port) = fields[1].split_once(':')?;
+            Some(ListeningSocket {
+                addr: parse_proc_addr(addr)?,
+                port: u16::from_str_radix(port, 16).ok()?,
+                inode: fields[9].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Addresses are printed as 32-bit words in host byte order
+fn parse_proc_addr(hex: &str) -> Option<IpAddr> {
+    let words = (0..hex.len() / 8)
+        .map(|i| u32::from_str_radix(&hex[i * 8..i * 8 + 8], 16).map(u32::to_ne_bytes))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    match words.as_slice() {
+        [v4] => Some(IpAddr::V4(Ipv4Addr::from(*v4))),
+        [a, b, c, d] => {
+            let mut octets = [0u8; 16];
+            for (chunk, word) in octets.chunks_mut(4).zip([a, b, c, d]) {
+                chunk.copy_from_slice(word);
+            }
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// Host to connect to for a socket listening on `addr` on this machine
+fn local_host(addr: IpAddr) -> String {
+    match addr {
+        IpAddr::V4(_) => Ipv4Addr::LOCALHOST.to_string(),
+        IpAddr::V6(v6) if v6.is_unspecified() => Ipv4Addr::LOCALHOST.to_string(),
+        IpAddr::V6(_) => format!("[{}]", Ipv6Addr::LOCALHOST),
+    }
+}
+
+fn into_targets(ports: BTreeMap<u16, String>) -> Vec<PreviewTarget> {
+    ports
+        .into_iter()
+        .map(|(port, host)| PreviewTarget { port, host })
+        .collect()
+}
+
+/// Ports listened on by host processes whose working directory is inside
+/// `workspace_dir`
+pub async fn local_targets(workspace_dir: &Path) -> Vec<PreviewTarget> {
+    let workspace_dir = workspace_dir.to_path_buf();
+    #[cfg(target_os = "linux")]
+    let ports = tokio::task::spawn_blocking(move || proc_listening_ports(&workspace_dir))
+        .await
+        .unwrap_or_default();
+    #[cfg(not(target_os = "linux"))]
+    let ports = lsof_listening_ports(&workspace_dir).await;
+    into_targets(ports)
+}
+
+#[cfg(target_os = "linux")]
+fn proc_listening_ports(workspace_dir: &Path) -> BTreeMap<u16, String> {
+    use std::collections::HashMap;
+
+    let sockets: HashMap<u64, ListeningSocket> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|contents| parse_proc_net_tcp(&contents))
+        .map(|socket| (socket.inode, socket))
+        .collect();
+
+    let mut ports = BTreeMap::new();
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return ports;
+    };
+    for entry in procs.filter_map(Result::ok) {
+        let proc_dir = entry.path();
+        let in_workspace = std::fs::read_link(proc_dir.join("cwd"))
+            .is_ok_and(|cwd| cwd.starts_with(workspace_dir));
+        if !in_workspace {
+            continue;
+        }
+        let Ok(fds) = std::fs::read_dir(proc_dir.join("fd")) else {
+            continue;
+        };
+        for fd in fds.filter_map(Result::ok) {
+            let Ok(link) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = link
+                .to_str()
+                .and_then(|link| link.strip_prefix("socket:["))
+                .and_then(|link| link.strip_suffix(']'))
+                .and_then(|inode| inode.parse::<u64>().ok());
+            if let Some(socket) = inode.and_then(|inode| sockets.get(&inode)) {
+                ports
+                    .entry(socket.port)
+                    .or_insert_with(|| local_host(socket.addr));
+            }
+        }
+    }
+    ports
+}
+
+/// `lsof` based lookup for platforms without procfs
+#[cfg(not(target_os = "linux"))]
+async fn lsof_listening_ports(workspace_dir: &Path) -> BTreeMap<u16, String> {
+    use std::collections::HashMap;
+
+    let mut ports = BTreeMap::new();
+    let Some(listening) = lsof(&["-nP", "-iTCP", "-sTCP:LISTEN", "-Fpn"]).await else {
+        return ports;
+    };
+    // Output is a `p<pid>` line followed by `n<address>:<port>` lines
+    let mut by_pid: HashMap<String, Vec<(IpAddr, u16)>> = HashMap::new();
+    let mut pid = String::new();
+    for line in listening.lines() {
+        if let Some(p) = line.strip_prefix('p') {
+            pid = p.to_string();
+        } else if let Some((addr, port)) = line.strip_prefix('n').and_then(|n| n.rsplit_once(':')) {
+            let addr = match addr.trim_matches(['[', ']']) {
+                "*" => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                addr => addr.parse().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            };
+            if let Ok(port) = port.parse() {
+                by_pid.entry(pid.clone()).or_default().push((addr, port));
+            }
+        }
+    }
+    if by_pid.is_empty() {
+        return ports;
+    }
+
+    let pids = by_pid.keys().cloned().collect::<Vec<_>>().join(",");
+    let Some(cwds) = lsof(&["-a", "-d", "cwd", "-Fpn", "-p", &pids]).await else {
+        return ports;
+    };
+    for line in cwds.lines() {
+        if let Some(p) = line.strip_prefix('p') {
+            pid = p.to_string();
+        } else if let Some(cwd) = line.strip_prefix('n')
+            && Path::new(cwd).starts_with(workspace_dir)
+        {
+            for (addr, port) in by_pid.get(&pid).into_iter().flatten() {
+                ports.entry(*port).or_insert_with(|| local_host(*addr));
+            }
+        }
+    }
+    ports
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn lsof(args: &[&str]) -> Option<String> {
+    let output = Command::new("lsof")
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Ports listened on inside a workspace container, reached through the
+/// container's address. Sockets bound only to the container's loopback
+/// interface can't be reached from the host and are skipped.
+pub async fn container_targets(container: &str) -> Vec<PreviewTarget> {
+    let Some(ip) = docker_output(&[
+        "inspect",
+        "-f",
+        "{{range .NetworkSettings.Networks}}{{.IPAddress}} {{end}}",
+        container,
+    ])
+    .await
+    .and_then(|ips| ips.split_whitespace().next().map(str::to_string)) else {
+        return Vec::new();
+    };
+    let Some(tables) = docker_output(&["exec", container, "sh", "-c", PROC_NET_TCP_SCRIPT]).await
+    else {
+        return Vec::new();
+    };
+    remote_targets(&ip, &tables)
+}
+
+/// Prints the IPv4 and IPv6 socket tables as one table
+pub(crate) const PROC_NET_TCP_SCRIPT: &str =
+    "cat /proc/net/tcp; tail -n +2 /proc/net/tcp6 2>/dev/null; true";
+
+/// Targets for the sockets in `tables` (the output of [`PROC_NET_TCP_SCRIPT`])
+/// of a container or pod reachable at `ip`
+pub(crate) fn remote_targets(ip: &str, tables: &str) -> Vec<PreviewTarget> {
+    let ports = parse_proc_net_tcp(tables)
+        .into_iter()
+        .filter(|socket| !socket.addr.is_loopback())
+        .map(|socket| (socket.port, ip.to_string()))
+        .collect();
+    into_targets(ports)
+}
+
+async fn docker_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("docker")
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_listening_sockets() {
+        let tcp = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0
+   1: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4243 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:0BB8 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 4244 1 0000000000000000 20 4 30 10 -1
+";
+        let sockets = parse_proc_net_tcp(tcp);
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].port, 3000);
+        assert_eq!(sockets[0].inode, 4242);
+        assert_eq!(sockets[1].port, 8080);
+        assert!(sockets[1].addr.is_unspecified());
+        if cfg!(target_endian = "little") {
+            assert_eq!(sockets[0].addr, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        }
+
+        let tcp6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:1538 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 5151 1 0000000000000000 100 0 0 10 0
+";
+        let sockets = parse_proc_net_tcp(tcp6);
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].port, 5432);
+        if cfg!(target_endian = "little") {
+            assert_eq!(sockets[0].addr, IpAddr::V6(Ipv6Addr::LOCALHOST));
+        }
+    }
+}
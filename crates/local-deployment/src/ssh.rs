@@ -0,0 +1,165 @@
+//! Run a project's executors on a remote build machine over SSH.
+//!
+//! Worktrees are still created, diffed and committed by the server. Each
+//! workspace directory is mirrored with rsync to `{remote_root}/{workspace_id}`
+//! on the remote host before an execution starts, and copied back once it
+//! finishes. `.git` entries are not synced (a worktree's `.git` only points at
+//! the local repository), so agents see plain files on the remote and changes
+//! only show up locally after each execution. The attempt's scratch directory
+//! stays on the remote host, since it holds large intermediate files.
+//!
+//! Authentication is left to the server user's SSH configuration; connections
+//! run in batch mode and fail instead of prompting.
+
+use std::{path::Path, process::Stdio};
+
+use anyhow::anyhow;
+use executors::env::{ContainerExec, ContainerRuntime, ExecutionEnv};
+use services::services::{container::ContainerError, workspace_files::SCRATCH_DIR};
+use tokio::{io::AsyncWriteExt, process::Command};
+use uuid::Uuid;
+
+use crate::kubernetes::env_file_contents;
+
+/// Where the workspace is mirrored on the remote host
+pub fn remote_dir(remote_root: &str, workspace_id: &Uuid) -> String {
+    format!("{}/{workspace_id}", remote_root.trim_end_matches('/'))
+}
+
+/// Workspace variables, sourced before every command run on the remote host.
+/// Kept next to the workspace so it is not synced back.
+fn env_file(remote_root: &str, workspace_id: &Uuid) -> String {
+    format!("{}.env", remote_dir(remote_root, workspace_id))
+}
+
+fn quote(word: &str) -> Result<String, ContainerError> {
+    shlex::try_quote(word)
+        .map(|quoted| quoted.into_owned())
+        .map_err(|_| ContainerError::Other(anyhow!("Path contains a NUL character")))
+}
+
+/// Write the workspace variables on the remote host and return how to run
+/// commands there. The workspace is copied over the first time.
+pub async fn prepare(
+    host: &str,
+    remote_root: &str,
+    workspace_id: &Uuid,
+    workspace_dir: &Path,
+    env: &ExecutionEnv,
+) -> Result<ContainerExec, ContainerError> {
+    let dir = remote_dir(remote_root, workspace_id);
+    let env_file = env_file(remote_root, workspace_id);
+    let script = format!(
+        "if [ -d {dir} ]; then echo exists; else mkdir -p {dir}; fi; umask 077 && cat > {env_file}",
+        dir = quote(&dir)?,
+        env_file = quote(&env_file)?,
+    );
+    let output = ssh(host, &script, Some(env_file_contents(env)?.as_bytes())).await?;
+    if output.trim() != "exists" {
+        push(host, &dir, workspace_dir).await?;
+    }
+
+    Ok(ContainerExec {
+        name: host.to_string(),
+        user: None,
+        runtime: ContainerRuntime::Ssh {
+            local_dir: workspace_dir.to_path_buf(),
+            remote_dir: dir,
+            env_file,
+            env_file_vars: env.vars.keys().cloned().collect(),
+        },
+    })
+}
+
+/// Copy the workspace to `remote_dir`, replacing what is there
+pub async fn push(
+    host: &str,
+    remote_dir: &str,
+    workspace_dir: &Path,
+) -> Result<(), ContainerError> {
+    let local = format!("{}/", workspace_dir.to_string_lossy());
+    rsync(&local, &format!("{host}:{remote_dir}/")).await
+}
+
+/// Copy the workspace back from `remote_dir`, so its changes can be committed
+/// locally
+pub async fn pull(
+    host: &str,
+    remote_dir: &str,
+    workspace_dir: &Path,
+) -> Result<(), ContainerError> {
+    let local = format!("{}/", workspace_dir.to_string_lossy());
+    rsync(&format!("{host}:{remote_dir}/"), &local).await
+}
+
+/// Delete the workspace's copy and variables from the remote host
+pub async fn remove_workspace(host: &str, remote_root: &str, workspace_id: &Uuid) {
+    let result = async {
+        let script = format!(
+            "rm -rf {} {}",
+            quote(&remote_dir(remote_root, workspace_id))?,
+            quote(&env_file(remote_root, workspace_id))?
+        );
+        ssh(host, &script, None).await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to remove workspace from {}: {}", host, e);
+    }
+}
+
+async fn rsync(source: &str, destination: &str) -> Result<(), ContainerError> {
+    // Excluded entries are also protected from --delete, so local worktree
+    // metadata is never touched by a pull
+    let exclude_scratch = format!("--exclude=/{SCRATCH_DIR}");
+    let output = Command::new("rsync")
+        .args([
+            "-az",
+            "--delete",
+            "--protect-args",
+            "--exclude=.git",
+            exclude_scratch.as_str(),
+            "-e",
+            "ssh -o BatchMode=yes",
+            source,
+            destination,
+        ])
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(ContainerError::Other(anyhow!(
+            "rsync to {} failed: {}",
+            destination,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+async fn ssh(host: &str, script: &str, stdin: Option<&[u8]>) -> Result<String, ContainerError> {
+    let mut child = Command::new("ssh")
+        .args(["-T", "-o", "BatchMode=yes", host, "--", script])
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(ContainerError::Other(anyhow!(
+            "ssh {} failed: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
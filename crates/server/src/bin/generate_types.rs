@@ -24,18 +24,105 @@ fn generate_types_content() -> String {
         db::models::project_repo::ProjectRepo::decl(),
         db::models::project_repo::CreateProjectRepo::decl(),
         db::models::project_repo::UpdateProjectRepo::decl(),
+        db::models::project_env_var::ProjectEnvVar::decl(),
+        db::models::project_env_var::SetProjectEnvVar::decl(),
+        db::models::project_secret::ProjectSecret::decl(),
+        db::models::project_settings::ProjectSettings::decl(),
+        db::models::project_settings::UploadPolicy::decl(),
+        db::models::project_settings::GitignoredUploadPolicy::decl(),
+        db::models::project_settings::UploadScanSettings::decl(),
+        db::models::project_settings::UploadScanner::decl(),
+        db::models::project_settings::ContainerBackend::decl(),
+        db::models::project_settings::RetentionPolicy::decl(),
+        db::models::project_settings::ExecPolicy::decl(),
+        db::models::project_settings::WorkspacePoolSettings::decl(),
+        db::models::project_settings::ResourceLimits::decl(),
+        db::models::project_settings::NetworkPolicy::decl(),
+        db::models::project_settings::SharedCacheSettings::decl(),
+        db::models::project_settings::SharedCache::decl(),
+        db::models::project_settings::GpuSettings::decl(),
+        db::models::project_settings::WipLimitSettings::decl(),
+        db::models::project_settings::WipLimit::decl(),
+        db::models::project_settings::WipEnforcement::decl(),
+        db::models::project_settings::DueReminderSettings::decl(),
+        db::models::project_settings::SwimlaneGrouping::decl(),
+        db::models::project_settings::DefinitionOfDone::decl(),
+        db::models::project_settings::RetryPolicy::decl(),
+        db::models::project_settings::LintGate::decl(),
+        db::models::project_settings::LintCheck::decl(),
+        db::models::project_settings::AgentReviewSettings::decl(),
+        db::models::project_settings::LlmProxySettings::decl(),
+        db::models::project_settings::LlmProvider::decl(),
+        db::models::project_settings::LlmApi::decl(),
+        db::models::project_settings::LifecycleHook::decl(),
+        db::models::project_settings::HookAction::decl(),
+        db::models::project_settings::AttemptLimits::decl(),
+        db::models::project_settings::ConcurrencySettings::decl(),
+        db::models::project_settings::BranchSyncSettings::decl(),
+        db::models::project_settings::BranchSyncMode::decl(),
+        db::models::project_settings::GitProvisioningSettings::decl(),
+        db::models::project_settings::SparseCheckoutSettings::decl(),
+        db::models::project_settings::CodeHostSettings::decl(),
+        db::models::project_settings::CodeHostProvider::decl(),
+        db::models::project_settings::GitLabSettings::decl(),
+        db::models::project_settings::BitbucketSettings::decl(),
+        db::models::project_settings::AzureDevOpsSettings::decl(),
+        db::models::project_settings::CommitMessageSettings::decl(),
+        db::models::project_settings::ConventionalCommitSettings::decl(),
+        db::models::project_settings::PromptSettings::decl(),
+        db::models::project_settings::PreviousAttemptContext::decl(),
+        db::models::project_settings::ExecutorPromptOverride::decl(),
+        db::models::project_settings::CommandPolicy::decl(),
+        db::models::command_decision::CommandDecision::decl(),
+        db::models::command_decision::CommandDecisionStatus::decl(),
+        db::models::command_decision::CommandDecider::decl(),
+        db::models::workspace_budget::BudgetLimit::decl(),
+        db::models::workspace_budget::BudgetUsage::decl(),
+        services::services::attempt_budget::AttemptBudget::decl(),
         db::models::workspace_repo::WorkspaceRepo::decl(),
         db::models::workspace_repo::CreateWorkspaceRepo::decl(),
         db::models::workspace_repo::RepoWithTargetBranch::decl(),
+        db::models::workspace_upload::WorkspaceUpload::decl(),
+        db::models::workspace_artifact::WorkspaceArtifact::decl(),
+        db::models::workspace_disk_usage::WorkspaceDiskUsage::decl(),
+        db::models::branch_sync_status::BranchSyncStatus::decl(),
+        db::models::branch_sync_status::BranchSyncAction::decl(),
+        db::models::ci_check::CiCheck::decl(),
+        db::models::ci_check::CiCheckState::decl(),
         db::models::tag::Tag::decl(),
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
+        db::models::label::Label::decl(),
+        db::models::label::CreateLabel::decl(),
+        db::models::label::UpdateLabel::decl(),
+        db::models::epic::Epic::decl(),
+        db::models::epic::CreateEpic::decl(),
+        db::models::epic::UpdateEpic::decl(),
+        server::routes::epics::SetTaskEpicRequest::decl(),
+        db::models::board_column::BoardColumn::decl(),
+        db::models::board_column::CreateBoardColumn::decl(),
+        db::models::board_column::UpdateBoardColumn::decl(),
+        server::routes::board_columns::ReorderBoardColumns::decl(),
+        server::routes::board_columns::ColumnWipStatus::decl(),
+        server::routes::board_columns::BoardWipStatus::decl(),
         db::models::task::TaskStatus::decl(),
+        db::models::task::TaskPriority::decl(),
         db::models::task::Task::decl(),
         db::models::task::TaskWithAttemptStatus::decl(),
+        db::models::task::SubtaskProgress::decl(),
         db::models::task::TaskRelationships::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
+        db::models::task_dependency::TaskDependency::decl(),
+        db::models::task_link::TaskLinkType::decl(),
+        db::models::task_link::TaskLink::decl(),
+        db::models::task_link::CreateTaskLink::decl(),
+        db::models::task_link::LinkedTask::decl(),
+        db::models::task_link::TaskWithLinks::decl(),
+        db::models::task_schedule::ScheduledAttempt::decl(),
+        db::models::task_schedule::TaskSchedule::decl(),
+        db::models::task_schedule::CreateTaskSchedule::decl(),
+        db::models::task_schedule::TaskScheduleRun::decl(),
         db::models::scratch::DraftFollowUpData::decl(),
         db::models::scratch::ScratchPayload::decl(),
         db::models::scratch::ScratchType::decl(),
@@ -49,7 +136,34 @@ fn generate_types_content() -> String {
         db::models::execution_process::ExecutionProcess::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        db::models::execution_process_retry::ExecutionProcessRetry::decl(),
+        db::models::queued_attempt::QueuedAttempt::decl(),
+        server::routes::attempt_queue::AttemptQueuePosition::decl(),
+        db::models::test_run::TestRun::decl(),
+        db::models::lint_run::LintSeverity::decl(),
+        db::models::lint_run::LintIssue::decl(),
+        db::models::lint_run::LintCheckStatus::decl(),
+        db::models::lint_run::LintCheckResult::decl(),
+        db::models::lint_run::LintRun::decl(),
+        db::models::agent_review::AgentReviewStatus::decl(),
+        db::models::agent_review::AgentReviewComment::decl(),
+        db::models::agent_review::AgentReview::decl(),
+        db::models::hook_run::HookEvent::decl(),
+        db::models::hook_run::HookRunStatus::decl(),
+        db::models::hook_run::HookRun::decl(),
+        services::services::lifecycle_hooks::HookPayload::decl(),
+        services::services::lifecycle_hooks::HookDetails::decl(),
+        db::models::dry_run::DryRunStatus::decl(),
+        db::models::dry_run::DryRun::decl(),
+        server::routes::task_attempts::dry_runs::StartDryRunRequest::decl(),
+        db::models::path_guard_block::PathGuardBlock::decl(),
+        db::models::merge_selection::SelectedFile::decl(),
+        db::models::merge_selection::MergeSelection::decl(),
+        db::models::execution_process_interruption::ExecutionProcessInterruption::decl(),
         db::models::execution_process_repo_state::ExecutionProcessRepoState::decl(),
+        db::models::execution_process_recording::RecordedEvent::decl(),
+        db::models::execution_process_recording::ExecutionProcessRecording::decl(),
+        db::models::merge::MergeStrategy::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -91,9 +205,14 @@ fn generate_types_content() -> String {
         utils::api::projects::RemoteProjectMembersResponse::decl(),
         server::routes::projects::CreateRemoteProjectRequest::decl(),
         server::routes::projects::LinkToExistingRequest::decl(),
+        server::routes::projects::UploadQuota::decl(),
         server::routes::repo::RegisterRepoRequest::decl(),
         server::routes::repo::InitRepoRequest::decl(),
         server::routes::tags::TagSearchParams::decl(),
+        server::routes::search::SearchParams::decl(),
+        db::models::search::SearchHitKind::decl(),
+        db::models::search::SnippetPart::decl(),
+        db::models::search::SearchHit::decl(),
         server::routes::oauth::TokenResponse::decl(),
         server::routes::config::UserSystemInfo::decl(),
         server::routes::config::Environment::decl(),
@@ -113,33 +232,169 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::RenameBranchResponse::decl(),
         server::routes::task_attempts::OpenEditorRequest::decl(),
         server::routes::task_attempts::OpenEditorResponse::decl(),
+        server::routes::task_attempts::PreviewPort::decl(),
+        services::services::container::ResourceUsage::decl(),
+        services::services::log_search::LogLevel::decl(),
+        services::services::log_search::LogSearchQuery::decl(),
+        services::services::log_search::LogSearchEntry::decl(),
+        services::services::log_search::LogSearchPage::decl(),
+        services::services::session_replay::ReplayQuery::decl(),
+        server::routes::task_attempts::AttemptMetrics::decl(),
+        services::services::task_scope::ScopeCheck::decl(),
         server::routes::shared_tasks::AssignSharedTaskRequest::decl(),
         server::routes::tasks::ShareTaskResponse::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
+        server::routes::tasks::FindDuplicatesRequest::decl(),
+        services::services::task_duplicates::DuplicateCandidate::decl(),
+        server::routes::tasks::TaskDependencies::decl(),
+        server::routes::tasks::TaskDependencyRequest::decl(),
+        server::routes::tasks::SetTaskLabelsRequest::decl(),
+        server::routes::tasks::BulkUpdateTasksRequest::decl(),
+        server::routes::tasks::BulkCreateTasksRequest::decl(),
+        server::routes::tasks::ArchivedTaskPage::decl(),
+        server::routes::tasks::TaskSort::decl(),
+        server::routes::tasks::DueTasks::decl(),
+        server::routes::tasks::SetTaskDueDateRequest::decl(),
+        server::routes::tasks::SetTaskExecutorProfileRequest::decl(),
+        server::routes::tasks::SetTaskScopeRequest::decl(),
+        server::routes::tasks::MoveTaskRequest::decl(),
+        server::routes::tasks::TaskOrder::decl(),
+        server::routes::tasks::Subtasks::decl(),
+        server::routes::tasks::StartSubtasksRequest::decl(),
+        server::routes::tasks::SkippedSubtask::decl(),
+        server::routes::tasks::StartSubtasksResponse::decl(),
         server::routes::task_attempts::pr::CreateGitHubPrRequest::decl(),
         server::routes::images::ImageResponse::decl(),
         server::routes::images::ImageMetadata::decl(),
+        server::routes::task_attempts::images::PastedImageResponse::decl(),
         server::routes::task_attempts::CreateTaskAttemptBody::decl(),
+        server::routes::task_attempts::stack::StackedAttempt::decl(),
+        server::routes::task_attempts::stack::AttemptStack::decl(),
+        db::models::attempt_group::AttemptGroup::decl(),
+        db::models::attempt_group::AttemptGroupMember::decl(),
+        server::routes::attempt_groups::FanOutAttemptsRequest::decl(),
+        server::routes::attempt_groups::AttemptGroupStatus::decl(),
+        db::models::task_checklist_item::TaskChecklistItem::decl(),
+        db::models::task_checklist_item::CreateTaskChecklistItem::decl(),
+        db::models::task_revision::TaskRevisionAuthor::decl(),
+        db::models::task_revision::TaskRevision::decl(),
+        server::routes::task_revisions::TaskRevisionEntry::decl(),
+        db::models::llm_proxy::LlmProxyRequest::decl(),
+        server::routes::llm_proxy::LlmProxyLog::decl(),
+        db::models::activity_event::Activity::decl(),
+        db::models::activity_event::ActivityEvent::decl(),
+        server::routes::activity::ActivityPage::decl(),
         server::routes::task_attempts::WorkspaceRepoInput::decl(),
         server::routes::task_attempts::RunAgentSetupRequest::decl(),
         server::routes::task_attempts::RunAgentSetupResponse::decl(),
+        server::routes::task_attempts::InterjectRequest::decl(),
         server::routes::task_attempts::gh_cli_setup::GhCliSetupError::decl(),
         server::routes::task_attempts::RebaseTaskAttemptRequest::decl(),
         server::routes::task_attempts::AbortConflictsRequest::decl(),
+        server::routes::task_attempts::conflicts::ConflictsQuery::decl(),
+        server::routes::task_attempts::conflicts::ResolveConflictRequest::decl(),
+        server::routes::task_attempts::conflicts::ContinueRebaseRequest::decl(),
+        server::routes::task_attempts::conflicts::ResolveConflictsWithAgentRequest::decl(),
+        server::routes::task_attempts::cherry_pick::CherryPickRequest::decl(),
+        server::routes::task_attempts::partial_merge::SelectableChangesQuery::decl(),
+        server::routes::task_attempts::partial_merge::SelectableChanges::decl(),
+        server::routes::task_attempts::partial_merge::CreateMergeSelectionRequest::decl(),
         server::routes::task_attempts::GitOperationError::decl(),
         server::routes::task_attempts::PushError::decl(),
         server::routes::task_attempts::pr::CreatePrError::decl(),
         server::routes::task_attempts::BranchStatus::decl(),
         server::routes::task_attempts::RunScriptError::decl(),
+        server::routes::task_attempts::setup::RetrySetupRequest::decl(),
+        server::routes::task_attempts::setup::StartAgentAnywayRequest::decl(),
         server::routes::task_attempts::pr::AttachPrResponse::decl(),
         server::routes::task_attempts::pr::AttachExistingPrRequest::decl(),
+        server::routes::task_attempts::pr::MergePrRequest::decl(),
         server::routes::task_attempts::pr::PrCommentsResponse::decl(),
         server::routes::task_attempts::pr::GetPrCommentsError::decl(),
         server::routes::task_attempts::pr::GetPrCommentsQuery::decl(),
+        server::routes::task_attempts::review_tasks::ReviewCommentTask::decl(),
+        server::routes::task_attempts::review_tasks::CreateReviewTasksRequest::decl(),
         services::services::github::UnifiedPrComment::decl(),
+        db::models::github_issue_link::GitHubIssueLink::decl(),
+        services::services::github_issue_import::ImportGitHubIssues::decl(),
+        services::services::github_issue_import::GitHubIssueImport::decl(),
+        services::services::board_transfer::BoardFormat::decl(),
+        services::services::board_transfer::BoardExport::decl(),
+        services::services::board_transfer::ExportedTask::decl(),
+        services::services::board_transfer::ExportedAttempt::decl(),
+        services::services::board_transfer::DuplicateStrategy::decl(),
+        services::services::board_transfer::ImportBoard::decl(),
+        services::services::board_transfer::BoardImportResult::decl(),
+        db::models::task_time_entry::TaskTimeEntry::decl(),
+        db::models::task_time_entry::CreateTaskTimeEntry::decl(),
+        server::routes::time_tracking::TimeReportQuery::decl(),
+        services::services::time_report::TimeTotals::decl(),
+        services::services::time_report::AttemptTime::decl(),
+        services::services::time_report::TaskTime::decl(),
+        services::services::time_report::ExecutorTime::decl(),
+        services::services::time_report::TimeReport::decl(),
+        server::routes::usage::UsageReportQuery::decl(),
+        services::services::usage_report::UsageTotals::decl(),
+        services::services::usage_report::AttemptUsage::decl(),
+        services::services::usage_report::TaskUsage::decl(),
+        services::services::usage_report::ExecutorUsage::decl(),
+        services::services::usage_report::UsageReport::decl(),
+        services::services::variant_stats::VariantStats::decl(),
+        services::services::swimlanes::SwimlaneColumn::decl(),
+        services::services::swimlanes::Swimlane::decl(),
+        services::services::swimlanes::BoardSwimlanes::decl(),
+        services::services::epic_progress::StatusCount::decl(),
+        services::services::epic_progress::BurnUpPoint::decl(),
+        services::services::epic_progress::EpicProgress::decl(),
+        server::routes::board_history::BoardHistoryQuery::decl(),
+        services::services::board_history::BurndownPoint::decl(),
+        services::services::board_history::FlowColumn::decl(),
+        services::services::board_history::FlowDay::decl(),
+        services::services::board_history::CumulativeFlow::decl(),
+        services::services::attempt_comparison::DiffStats::decl(),
+        services::services::attempt_comparison::TestRun::decl(),
+        services::services::attempt_comparison::TokenUsage::decl(),
+        services::services::attempt_comparison::AttemptComparison::decl(),
+        services::services::task_plan::PlannedTask::decl(),
+        server::routes::task_plans::PreviewPlanRequest::decl(),
+        server::routes::task_plans::PlanPreview::decl(),
+        server::routes::task_plans::CreatePlanTasksRequest::decl(),
         server::routes::task_attempts::RepoBranchStatus::decl(),
         services::services::filesystem::DirectoryEntry::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
+        services::services::file_events::FileChangeKind::decl(),
+        services::services::file_events::FileChangeEvent::decl(),
+        services::services::workspace_archive::ArchiveFormat::decl(),
+        services::services::workspace_files::UploadedFile::decl(),
+        services::services::workspace_files::FileContent::decl(),
+        services::services::workspace_files::UpdateFileContent::decl(),
+        services::services::workspace_files::FileEdit::decl(),
+        services::services::workspace_files::FileScope::decl(),
+        services::services::workspace_janitor::ReclaimReason::decl(),
+        services::services::workspace_janitor::ReclaimCandidate::decl(),
+        services::services::workspace_janitor::CleanupPreview::decl(),
+        services::services::worktree_maintenance::WorktreeProblem::decl(),
+        services::services::worktree_maintenance::WorktreeStatus::decl(),
+        services::services::worktree_maintenance::WorktreeReport::decl(),
+        services::services::worktree_maintenance::SkippedWorktree::decl(),
+        services::services::worktree_maintenance::WorktreeMaintenance::decl(),
+        services::services::project_cleanup::ProjectCleanupState::decl(),
+        services::services::project_cleanup::ProjectCleanupProgress::decl(),
+        services::services::workspace_pool::WorkspacePoolStatus::decl(),
+        services::services::workspace_setup::SetupStatus::decl(),
+        services::services::workspace_setup::WorkspaceSetup::decl(),
+        services::services::container::SharedCacheUsage::decl(),
+        services::services::container::ContainerStatus::decl(),
+        services::services::workspace_exec::ExecRequest::decl(),
+        services::services::workspace_exec::ExecOutput::decl(),
+        services::services::terminal::TerminalSize::decl(),
+        services::services::terminal::OpenTerminalRequest::decl(),
+        services::services::terminal::TerminalSessionInfo::decl(),
+        services::services::terminal::TerminalClientMessage::decl(),
+        services::services::process_registry::ProcessKind::decl(),
+        services::services::process_registry::ProcessInfo::decl(),
+        server::routes::task_attempts::health::WorktreeHealth::decl(),
+        server::routes::task_attempts::health::WorkspaceHealth::decl(),
         services::services::config::Config::decl(),
         services::services::config::NotificationConfig::decl(),
         services::services::config::ThemeMode::decl(),
@@ -150,11 +405,29 @@ fn generate_types_content() -> String {
         services::services::config::SoundFile::decl(),
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
+        services::services::config::AttemptQueueConfig::decl(),
+        services::services::config::AttemptQueueOrder::decl(),
+        services::services::config::LocalModelEndpoint::decl(),
+        services::services::config::LocalModelServer::decl(),
+        services::services::config::CommitSigningConfig::decl(),
+        services::services::config::CommitSigningMode::decl(),
+        services::services::config::SigningFormat::decl(),
+        services::services::local_models::EndpointHealth::decl(),
+        services::services::local_models::LocalModelBackend::decl(),
+        server::routes::local_models::SaveLocalModelBackend::decl(),
         services::services::git::GitBranch::decl(),
         services::services::share::SharedTaskDetails::decl(),
         services::services::queued_message::QueuedMessage::decl(),
         services::services::queued_message::QueueStatus::decl(),
         services::services::git::ConflictOp::decl(),
+        services::services::git::SignatureStatus::decl(),
+        services::services::rebase_conflicts::ConflictDetails::decl(),
+        services::services::rebase_conflicts::ConflictedFile::decl(),
+        services::services::rebase_conflicts::ConflictHunk::decl(),
+        services::services::cherry_pick::CherryPickStatus::decl(),
+        services::services::partial_merge::SelectableFile::decl(),
+        services::services::partial_merge::DiffHunk::decl(),
+        services::services::rebase_conflicts::ConflictResolution::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
         executors::actions::ExecutorActionType::decl(),
@@ -169,6 +442,7 @@ fn generate_types_content() -> String {
         executors::profile::ExecutorConfig::decl(),
         executors::profile::ExecutorConfigs::decl(),
         executors::executors::BaseAgentCapability::decl(),
+        executors::failure::FailureKind::decl(),
         executors::executors::claude::ClaudeCode::decl(),
         executors::executors::gemini::Gemini::decl(),
         executors::executors::amp::Amp::decl(),
@@ -185,7 +459,20 @@ fn generate_types_content() -> String {
         executors::executors::droid::Droid::decl(),
         executors::executors::droid::Autonomy::decl(),
         executors::executors::droid::ReasoningEffortLevel::decl(),
+        executors::executors::openhands::Openhands::decl(),
+        executors::executors::api_agent::ApiAgent::decl(),
+        executors::executors::goose::Goose::decl(),
+        executors::executors::plugin::Plugin::decl(),
+        executors::executors::plugin::manifest::PluginManifest::decl(),
+        executors::executors::plugin::manifest::DiscoveredPlugin::decl(),
         executors::executors::AppendPrompt::decl(),
+        executors::prompt_additions::PromptAdditions::decl(),
+        executors::test_output::TestRunner::decl(),
+        executors::test_output::TestSummary::decl(),
+        executors::health::ExecutorHealth::decl(),
+        executors::health::CliCheck::decl(),
+        executors::health::AuthCheck::decl(),
+        executors::health::McpConfigCheck::decl(),
         executors::actions::coding_agent_initial::CodingAgentInitialRequest::decl(),
         executors::actions::coding_agent_follow_up::CodingAgentFollowUpRequest::decl(),
         executors::logs::CommandExitStatus::decl(),
@@ -286,6 +573,22 @@ fn generate_schemas() -> Result<HashMap<&'static str, String>, serde_json::Error
             "droid",
             generate_json_schema::<executors::executors::droid::Droid>()?,
         ),
+        (
+            "openhands",
+            generate_json_schema::<executors::executors::openhands::Openhands>()?,
+        ),
+        (
+            "api_agent",
+            generate_json_schema::<executors::executors::api_agent::ApiAgent>()?,
+        ),
+        (
+            "goose",
+            generate_json_schema::<executors::executors::goose::Goose>()?,
+        ),
+        (
+            "plugin",
+            generate_json_schema::<executors::executors::plugin::Plugin>()?,
+        ),
     ]);
     println!(
         "✅ JSON schemas generated. {} schemas created.",
@@ -13,15 +13,25 @@ use deployment::{DeploymentError, RemoteClientNotConfigured};
 use executors::executors::ExecutorError;
 use git2::Error as Git2Error;
 use services::services::{
+    azure_devops::AzureDevOpsServiceError,
+    bitbucket::BitbucketServiceError,
+    board_transfer::BoardTransferError,
     config::{ConfigError, EditorOpenError},
     container::ContainerError,
     git::GitServiceError,
     github::GitHubServiceError,
+    gitlab::GitLabServiceError,
     image::ImageError,
+    partial_merge::SelectionError,
     project::ProjectServiceError,
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
+    secrets::SecretsError,
     share::ShareError,
+    terminal::TerminalError,
+    workspace_archive::ArchiveError,
+    workspace_exec::ExecError,
+    workspace_files::FileUploadError,
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
@@ -47,6 +57,12 @@ pub enum ApiError {
     #[error(transparent)]
     GitHubService(#[from] GitHubServiceError),
     #[error(transparent)]
+    GitLabService(#[from] GitLabServiceError),
+    #[error(transparent)]
+    BitbucketService(#[from] BitbucketServiceError),
+    #[error(transparent)]
+    AzureDevOpsService(#[from] AzureDevOpsServiceError),
+    #[error(transparent)]
     Deployment(#[from] DeploymentError),
     #[error(transparent)]
     Container(#[from] ContainerError),
@@ -60,6 +76,8 @@ pub enum ApiError {
     Config(#[from] ConfigError),
     #[error(transparent)]
     Image(#[from] ImageError),
+    #[error(transparent)]
+    FileUpload(#[from] FileUploadError),
     #[error("Multipart error: {0}")]
     Multipart(#[from] MultipartError),
     #[error("IO error: {0}")]
@@ -121,6 +139,41 @@ impl IntoResponse for ApiError {
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             },
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
+            ApiError::GitLabService(err) => match err {
+                GitLabServiceError::MissingToken(_) | GitLabServiceError::AuthFailed(_) => {
+                    (StatusCode::UNAUTHORIZED, "GitLabServiceError")
+                }
+                GitLabServiceError::InsufficientPermissions(_) => {
+                    (StatusCode::FORBIDDEN, "GitLabServiceError")
+                }
+                GitLabServiceError::NotMergeable(_) => (StatusCode::CONFLICT, "GitLabServiceError"),
+                _ => (StatusCode::BAD_GATEWAY, "GitLabServiceError"),
+            },
+            ApiError::BitbucketService(err) => match err {
+                BitbucketServiceError::MissingToken(_) | BitbucketServiceError::AuthFailed(_) => {
+                    (StatusCode::UNAUTHORIZED, "BitbucketServiceError")
+                }
+                BitbucketServiceError::InsufficientPermissions(_) => {
+                    (StatusCode::FORBIDDEN, "BitbucketServiceError")
+                }
+                BitbucketServiceError::NotMergeable(_) => {
+                    (StatusCode::CONFLICT, "BitbucketServiceError")
+                }
+                _ => (StatusCode::BAD_GATEWAY, "BitbucketServiceError"),
+            },
+            ApiError::AzureDevOpsService(err) => match err {
+                AzureDevOpsServiceError::MissingToken(_)
+                | AzureDevOpsServiceError::AuthFailed(_) => {
+                    (StatusCode::UNAUTHORIZED, "AzureDevOpsServiceError")
+                }
+                AzureDevOpsServiceError::InsufficientPermissions(_) => {
+                    (StatusCode::FORBIDDEN, "AzureDevOpsServiceError")
+                }
+                AzureDevOpsServiceError::NotMergeable(_) => {
+                    (StatusCode::CONFLICT, "AzureDevOpsServiceError")
+                }
+                _ => (StatusCode::BAD_GATEWAY, "AzureDevOpsServiceError"),
+            },
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
@@ -131,8 +184,34 @@ impl IntoResponse for ApiError {
                 ImageError::InvalidFormat => (StatusCode::BAD_REQUEST, "InvalidImageFormat"),
                 ImageError::TooLarge(_, _) => (StatusCode::PAYLOAD_TOO_LARGE, "ImageTooLarge"),
                 ImageError::NotFound => (StatusCode::NOT_FOUND, "ImageNotFound"),
+                ImageError::PolicyViolation(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "UploadPolicyViolation")
+                }
+                ImageError::Decode(_) => (StatusCode::BAD_REQUEST, "ImageDecodeError"),
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "ImageError"),
             },
+            ApiError::FileUpload(err) => match err {
+                FileUploadError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "FileUploadError"),
+                FileUploadError::InvalidPath(_) => (StatusCode::BAD_REQUEST, "InvalidUploadPath"),
+                FileUploadError::GitDirectory => (StatusCode::FORBIDDEN, "GitDirectoryWrite"),
+                FileUploadError::OutsideWorkspace(_) => {
+                    (StatusCode::FORBIDDEN, "PathOutsideWorkspace")
+                }
+                FileUploadError::Gitignored(_) => (StatusCode::CONFLICT, "GitignoredUpload"),
+                FileUploadError::PolicyViolation(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "UploadPolicyViolation")
+                }
+                FileUploadError::ScanRejected(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "UploadScanRejected")
+                }
+                FileUploadError::ScanFailed(_) => (StatusCode::BAD_GATEWAY, "UploadScanFailed"),
+                FileUploadError::NotFound(_) => (StatusCode::NOT_FOUND, "FileNotFound"),
+                FileUploadError::NotText(_) => (StatusCode::BAD_REQUEST, "FileNotText"),
+                FileUploadError::HashMismatch { .. } => (StatusCode::CONFLICT, "FileHashMismatch"),
+                FileUploadError::PatchFailed(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "PatchApplyFailed")
+                }
+            },
             ApiError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IoError"),
             ApiError::EditorOpen(err) => match err {
                 EditorOpenError::LaunchFailed { .. } => {
@@ -188,6 +267,7 @@ impl IntoResponse for ApiError {
                     *max as f64 / 1_048_576.0
                 ),
                 ImageError::NotFound => "Image not found.".to_string(),
+                ImageError::PolicyViolation(reason) => reason.clone(),
                 _ => {
                     "Failed to process image. Please try again.".to_string()
                 }
@@ -199,6 +279,20 @@ impl IntoResponse for ApiError {
                 }
                 _ => format!("{}: {}", error_type, self),
             },
+            ApiError::FileUpload(err) => match err {
+                FileUploadError::Io(_) => "Failed to write the file into the workspace.".to_string(),
+                FileUploadError::Gitignored(path) => format!(
+                    "{path} is ignored by .gitignore and this project refuses uploads to ignored paths."
+                ),
+                FileUploadError::PolicyViolation(reason) => reason.clone(),
+                FileUploadError::ScanRejected(reason) => {
+                    format!("The upload was rejected by the project's scanner: {reason}")
+                }
+                FileUploadError::HashMismatch { .. } => {
+                    "The file changed since it was loaded. Reload it and reapply your edit.".to_string()
+                }
+                _ => err.to_string(),
+            },
             ApiError::Multipart(_) => "Failed to upload file. Please ensure the file is valid and try again.".to_string(),
             ApiError::RemoteClient(err) => match err {
                 RemoteClientError::Auth => "Unauthorized. Please sign in again.".to_string(),
@@ -313,6 +407,76 @@ impl From<ShareError> for ApiError {
     }
 }
 
+impl From<ArchiveError> for ApiError {
+    fn from(err: ArchiveError) -> Self {
+        match err {
+            ArchiveError::Io(io_err) => ApiError::Io(io_err),
+            ArchiveError::InvalidPath(path) => {
+                ApiError::BadRequest(format!("Not a directory inside the workspace: {path}"))
+            }
+            ArchiveError::NotFound(path) => {
+                ApiError::BadRequest(format!("Path does not exist: {path}"))
+            }
+            ArchiveError::Zip(_) | ArchiveError::Walk(_) => {
+                ApiError::Io(std::io::Error::other(err))
+            }
+        }
+    }
+}
+
+impl From<SecretsError> for ApiError {
+    fn from(err: SecretsError) -> Self {
+        match err {
+            SecretsError::Database(db_err) => ApiError::Database(db_err),
+            SecretsError::Io(io_err) => ApiError::Io(io_err),
+            SecretsError::InvalidKey | SecretsError::Crypto => {
+                ApiError::Io(std::io::Error::other(err))
+            }
+        }
+    }
+}
+
+impl From<ExecError> for ApiError {
+    fn from(err: ExecError) -> Self {
+        match err {
+            ExecError::NotAllowed(reason) => ApiError::Forbidden(reason),
+            ExecError::InvalidCommand(_) => ApiError::BadRequest(err.to_string()),
+            ExecError::InvalidWorkingDir(file_err) => ApiError::FileUpload(file_err),
+            ExecError::Io(io_err) => ApiError::Io(io_err),
+        }
+    }
+}
+
+impl From<BoardTransferError> for ApiError {
+    fn from(err: BoardTransferError) -> Self {
+        // Exports always serialize, so errors come from reading an uploaded file
+        ApiError::BadRequest(err.to_string())
+    }
+}
+
+impl From<SelectionError> for ApiError {
+    fn from(err: SelectionError) -> Self {
+        match err {
+            SelectionError::Git(git_err) => ApiError::GitService(git_err),
+            _ => ApiError::BadRequest(err.to_string()),
+        }
+    }
+}
+
+impl From<TerminalError> for ApiError {
+    fn from(err: TerminalError) -> Self {
+        match err {
+            TerminalError::NotAllowed(reason) => ApiError::Forbidden(reason),
+            TerminalError::NotFound => ApiError::BadRequest(err.to_string()),
+            TerminalError::TooManySessions | TerminalError::Exited => {
+                ApiError::Conflict(err.to_string())
+            }
+            TerminalError::Io(io_err) => ApiError::Io(io_err),
+            TerminalError::Pty(e) => ApiError::Io(std::io::Error::other(e)),
+        }
+    }
+}
+
 impl From<ProjectServiceError> for ApiError {
     fn from(err: ProjectServiceError) -> Self {
         match err {
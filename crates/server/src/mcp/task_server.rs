@@ -111,6 +111,8 @@ pub struct TaskSummary {
     pub title: String,
     #[schemars(description = "Current status of the task")]
     pub status: String,
+    #[schemars(description = "Priority: 'urgent', 'high', 'normal' or 'low'")]
+    pub priority: String,
     #[schemars(description = "When the task was created")]
     pub created_at: String,
     #[schemars(description = "When the task was last updated")]
@@ -119,6 +121,8 @@ pub struct TaskSummary {
     pub has_in_progress_attempt: Option<bool>,
     #[schemars(description = "Whether the last execution attempt failed")]
     pub last_attempt_failed: Option<bool>,
+    #[schemars(description = "Whether the task waits for tasks it depends on to be done")]
+    pub is_blocked: Option<bool>,
 }
 
 impl TaskSummary {
@@ -127,10 +131,12 @@ impl TaskSummary {
             id: task.id.to_string(),
             title: task.title.to_string(),
             status: task.status.to_string(),
+            priority: task.priority.to_string(),
             created_at: task.created_at.to_rfc3339(),
             updated_at: task.updated_at.to_rfc3339(),
             has_in_progress_attempt: Some(task.has_in_progress_attempt),
             last_attempt_failed: Some(task.last_attempt_failed),
+            is_blocked: Some(task.is_blocked),
         }
     }
 }
@@ -145,6 +151,8 @@ pub struct TaskDetails {
     pub description: Option<String>,
     #[schemars(description = "Current status of the task")]
     pub status: String,
+    #[schemars(description = "Priority: 'urgent', 'high', 'normal' or 'low'")]
+    pub priority: String,
     #[schemars(description = "When the task was created")]
     pub created_at: String,
     #[schemars(description = "When the task was last updated")]
@@ -162,6 +170,7 @@ impl TaskDetails {
             title: task.title,
             description: task.description,
             status: task.status.to_string(),
+            priority: task.priority.to_string(),
             created_at: task.created_at.to_rfc3339(),
             updated_at: task.updated_at.to_rfc3339(),
             has_in_progress_attempt: None,
@@ -251,6 +260,10 @@ pub struct GetTaskResponse {
     pub task: TaskDetails,
 }
 
+/// User agent of the server's requests, so edits coding agents make through it
+/// can be told apart from the user's
+pub const MCP_USER_AGENT: &str = "vibe-kanban-mcp";
+
 #[derive(Debug, Clone)]
 pub struct TaskServer {
     client: reqwest::Client,
@@ -285,7 +298,10 @@ pub struct McpContext {
 impl TaskServer {
     pub fn new(base_url: &str) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: reqwest::Client::builder()
+                .user_agent(MCP_USER_AGENT)
+                .build()
+                .unwrap_or_default(),
             base_url: base_url.to_string(),
             tool_router: Self::tool_router(),
             context: None,
@@ -699,8 +715,9 @@ impl TaskServer {
 
         let payload = CreateTaskAttemptBody {
             task_id,
-            executor_profile_id,
+            executor_profile_id: Some(executor_profile_id),
             repos: workspace_repos,
+            stacked_on_workspace_id: None,
         };
 
         let url = self.url("/api/task-attempts");
@@ -755,6 +772,7 @@ impl TaskServer {
             description: expanded_description,
             status,
             parent_workspace_id: None,
+            priority: None,
             image_ids: None,
         };
         let url = self.url(&format!("/api/tasks/{}", task_id));
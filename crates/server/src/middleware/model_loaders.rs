@@ -5,8 +5,10 @@ use axum::{
     response::Response,
 };
 use db::models::{
-    execution_process::ExecutionProcess, project::Project, session::Session, tag::Tag, task::Task,
-    workspace::Workspace,
+    attempt_group::AttemptGroup, board_column::BoardColumn, epic::Epic,
+    execution_process::ExecutionProcess, label::Label, project::Project, session::Session,
+    tag::Tag, task::Task, task_checklist_item::TaskChecklistItem, task_link::TaskLink,
+    task_schedule::TaskSchedule, task_time_entry::TaskTimeEntry, workspace::Workspace,
 };
 use deployment::Deployment;
 use uuid::Uuid;
@@ -148,6 +150,160 @@ pub async fn load_tag_middleware(
     Ok(next.run(request).await)
 }
 
+pub async fn load_label_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(label_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let label = match Label::find_by_id(&deployment.db().pool, label_id).await {
+        Ok(Some(label)) => label,
+        Ok(None) => {
+            tracing::warn!("Label {} not found", label_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch label {}: {}", label_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(label);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_board_column_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(column_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let column = match BoardColumn::find_by_id(&deployment.db().pool, column_id).await {
+        Ok(Some(column)) => column,
+        Ok(None) => {
+            tracing::warn!("Board column {} not found", column_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch board column {}: {}", column_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(column);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_time_entry_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(entry_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let entry = match TaskTimeEntry::find_by_id(&deployment.db().pool, entry_id).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => {
+            tracing::warn!("Time entry {} not found", entry_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch time entry {}: {}", entry_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(entry);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_epic_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(epic_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let epic = match Epic::find_by_id(&deployment.db().pool, epic_id).await {
+        Ok(Some(epic)) => epic,
+        Ok(None) => {
+            tracing::warn!("Epic {} not found", epic_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch epic {}: {}", epic_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(epic);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_task_link_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(link_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let link = match TaskLink::find_by_id(&deployment.db().pool, link_id).await {
+        Ok(Some(link)) => link,
+        Ok(None) => {
+            tracing::warn!("Task link {} not found", link_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch task link {}: {}", link_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(link);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_task_checklist_item_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(item_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let item = match TaskChecklistItem::find_by_id(&deployment.db().pool, item_id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => {
+            tracing::warn!("Task checklist item {} not found", item_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch task checklist item {}: {}", item_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(item);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_task_schedule_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(schedule_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let schedule = match TaskSchedule::find_by_id(&deployment.db().pool, schedule_id).await {
+        Ok(Some(schedule)) => schedule,
+        Ok(None) => {
+            tracing::warn!("Task schedule {} not found", schedule_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch task schedule {}: {}", schedule_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(schedule);
+    Ok(next.run(request).await)
+}
+
 pub async fn load_session_middleware(
     State(deployment): State<DeploymentImpl>,
     Path(session_id): Path<Uuid>,
@@ -169,3 +325,25 @@ pub async fn load_session_middleware(
     request.extensions_mut().insert(session);
     Ok(next.run(request).await)
 }
+
+pub async fn load_attempt_group_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(attempt_group_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let group = match AttemptGroup::find_by_id(&deployment.db().pool, attempt_group_id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            tracing::warn!("Attempt group {} not found", attempt_group_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch attempt group {}: {}", attempt_group_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(group);
+    Ok(next.run(request).await)
+}
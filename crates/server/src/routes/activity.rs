@@ -0,0 +1,76 @@
+use axum::{
+    BoxError, Extension,
+    extract::{Query, State},
+    response::{
+        Json as ResponseJson, Sse,
+        sse::{Event, KeepAlive},
+    },
+};
+use db::models::{activity_event::ActivityEvent, project::Project};
+use deployment::Deployment;
+use futures_util::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const ACTIVITY_PAGE_SIZE: u32 = 50;
+const MAX_ACTIVITY_PAGE_SIZE: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    /// Return the events recorded before this one
+    pub before: Option<Uuid>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ActivityPage {
+    /// Newest first
+    pub events: Vec<ActivityEvent>,
+    /// Pass as `before` to get the next page; none on the last page
+    pub next_before: Option<Uuid>,
+}
+
+/// What happened on the project's board, a page at a time
+pub async fn get_activity(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<ResponseJson<ApiResponse<ActivityPage>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(ACTIVITY_PAGE_SIZE)
+        .clamp(1, MAX_ACTIVITY_PAGE_SIZE);
+    let events = ActivityEvent::find_page(
+        &deployment.db().pool,
+        project.id,
+        query.before,
+        i64::from(limit),
+    )
+    .await?;
+    let next_before = (events.len() == limit as usize)
+        .then(|| events.last().map(|event| event.id))
+        .flatten();
+
+    Ok(ResponseJson(ApiResponse::success(ActivityPage {
+        events,
+        next_before,
+    })))
+}
+
+/// The project's activity events as they happen, each one a JSON patch adding
+/// it under `/activity`
+pub async fn stream_activity(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Sse<impl Stream<Item = Result<Event, BoxError>>> {
+    let stream = deployment
+        .events()
+        .stream_activity_raw(project.id)
+        .map_ok(|msg| msg.to_sse_event())
+        .map_err(|e| -> BoxError { e.into() });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,281 @@
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::get,
+};
+use db::models::{
+    attempt_group::{AttemptGroup, AttemptGroupMember},
+    execution_process::ExecutionProcessStatus,
+    task::Task,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::profile::{ExecutorConfigs, ExecutorProfileId};
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_attempt_group_middleware,
+    routes::task_attempts::{WorkspaceRepoInput, create_and_start_attempt, ensure_not_blocked},
+};
+
+/// Most attempts one request can start
+const MAX_FAN_OUT: usize = 8;
+/// Attempts set up at the same time when the request doesn't say
+const DEFAULT_MAX_CONCURRENT: usize = 2;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct FanOutAttemptsRequest {
+    /// One attempt is started per profile; a profile can be listed twice
+    pub executor_profile_ids: Vec<ExecutorProfileId>,
+    pub repos: Vec<WorkspaceRepoInput>,
+    /// Attempts set up at the same time, 2 if not given
+    pub max_concurrent: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptGroupStatus {
+    pub group: AttemptGroup,
+    pub attempts: Vec<AttemptGroupMember>,
+    /// Attempts whose agent hasn't started yet
+    pub pending: usize,
+    pub running: usize,
+    pub completed: usize,
+    /// Failed or stopped
+    pub failed: usize,
+    /// Every agent of the group has stopped
+    pub finished: bool,
+    /// Attempt whose agent completed first
+    pub first_completed: Option<Uuid>,
+}
+
+impl AttemptGroupStatus {
+    fn new(group: AttemptGroup, attempts: Vec<AttemptGroupMember>) -> Self {
+        let count = |has_status: fn(Option<&ExecutionProcessStatus>) -> bool| {
+            attempts
+                .iter()
+                .filter(|attempt| has_status(attempt.status.as_ref()))
+                .count()
+        };
+        let pending = count(|status| status.is_none());
+        let running = count(|status| status == Some(&ExecutionProcessStatus::Running));
+        let completed = count(|status| status == Some(&ExecutionProcessStatus::Completed));
+        let failed = count(|status| {
+            matches!(
+                status,
+                Some(ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed)
+            )
+        });
+        let first_completed = attempts
+            .iter()
+            .filter(|attempt| attempt.status == Some(ExecutionProcessStatus::Completed))
+            .filter_map(|attempt| Some((attempt.completed_at?, attempt.workspace_id)))
+            .min()
+            .map(|(_, workspace_id)| workspace_id);
+
+        Self {
+            finished: !attempts.is_empty() && pending == 0 && running == 0,
+            group,
+            attempts,
+            pending,
+            running,
+            completed,
+            failed,
+            first_completed,
+        }
+    }
+}
+
+async fn group_status(
+    pool: &SqlitePool,
+    group: AttemptGroup,
+) -> Result<AttemptGroupStatus, sqlx::Error> {
+    let attempts = AttemptGroup::find_members(pool, group.id).await?;
+    Ok(AttemptGroupStatus::new(group, attempts))
+}
+
+/// Start an attempt at the task for each profile, as a group of siblings
+pub async fn fan_out_attempts(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<FanOutAttemptsRequest>,
+) -> Result<ResponseJson<ApiResponse<AttemptGroupStatus>>, ApiError> {
+    if payload.executor_profile_ids.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one executor profile is required".to_string(),
+        ));
+    }
+    if payload.executor_profile_ids.len() > MAX_FAN_OUT {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_FAN_OUT} attempts can be started at once"
+        )));
+    }
+    if payload.repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one repository is required".to_string(),
+        ));
+    }
+    let executor_configs = ExecutorConfigs::get_cached();
+    if let Some(unknown) = payload
+        .executor_profile_ids
+        .iter()
+        .find(|executor_profile_id| {
+            executor_configs
+                .get_coding_agent(executor_profile_id)
+                .is_none()
+        })
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown executor profile '{unknown}'"
+        )));
+    }
+
+    let pool = &deployment.db().pool;
+    ensure_not_blocked(pool, &task).await?;
+    let group = AttemptGroup::create(pool, task.id).await?;
+    let max_concurrent = payload
+        .max_concurrent
+        .unwrap_or(DEFAULT_MAX_CONCURRENT)
+        .clamp(1, MAX_FAN_OUT);
+
+    // Every attempt is seen through, so none is left half set up when another
+    // one fails
+    let results: Vec<Result<Workspace, ApiError>> = stream::iter(&payload.executor_profile_ids)
+        .map(|executor_profile_id| {
+            let deployment = &deployment;
+            let task = &task;
+            let repos = &payload.repos;
+            let group_id = group.id;
+            async move {
+                let workspace =
+                    create_and_start_attempt(deployment, task, Some(executor_profile_id), repos)
+                        .await?;
+                AttemptGroup::add_workspace(&deployment.db().pool, group_id, workspace.id).await?;
+                Ok(workspace)
+            }
+        })
+        .buffered(max_concurrent)
+        .collect()
+        .await;
+    let started = results.iter().filter(|result| result.is_ok()).count();
+    if let Some(Err(e)) = results.into_iter().find(Result::is_err) {
+        tracing::error!(
+            "Started {} of {} attempts of group {}",
+            started,
+            payload.executor_profile_ids.len(),
+            group.id
+        );
+        return Err(e);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "attempts_fanned_out",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "attempt_group_id": group.id.to_string(),
+                "attempt_count": started,
+                "executors": payload
+                    .executor_profile_ids
+                    .iter()
+                    .map(|executor_profile_id| executor_profile_id.to_string())
+                    .collect::<Vec<_>>(),
+                "max_concurrent": max_concurrent,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(
+        group_status(pool, group).await?,
+    )))
+}
+
+/// Groups of attempts started together for the task, newest first
+pub async fn get_task_attempt_groups(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptGroupStatus>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let mut statuses = Vec::new();
+    for group in AttemptGroup::find_by_task_id(pool, task.id).await? {
+        statuses.push(group_status(pool, group).await?);
+    }
+    Ok(ResponseJson(ApiResponse::success(statuses)))
+}
+
+pub async fn get_attempt_group(
+    Extension(group): Extension<AttemptGroup>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptGroupStatus>>, ApiError> {
+    let status = group_status(&deployment.db().pool, group).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let group_router = Router::new()
+        .route("/", get(get_attempt_group))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_attempt_group_middleware,
+        ));
+
+    Router::new().nest("/attempt-groups/{attempt_group_id}", group_router)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+
+    fn member(
+        status: Option<ExecutionProcessStatus>,
+        completed_minutes_ago: Option<i64>,
+    ) -> AttemptGroupMember {
+        AttemptGroupMember {
+            workspace_id: Uuid::new_v4(),
+            executor_profile_id: None,
+            status,
+            completed_at: completed_minutes_ago
+                .map(|minutes| Utc::now() - Duration::minutes(minutes)),
+        }
+    }
+
+    #[test]
+    fn counts_attempts_and_picks_the_first_to_complete() {
+        let group = AttemptGroup {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+        };
+        let attempts = vec![
+            member(Some(ExecutionProcessStatus::Completed), Some(2)),
+            member(Some(ExecutionProcessStatus::Completed), Some(5)),
+            member(Some(ExecutionProcessStatus::Killed), Some(9)),
+            member(Some(ExecutionProcessStatus::Running), None),
+        ];
+        let first = attempts[1].workspace_id;
+
+        let status = AttemptGroupStatus::new(group.clone(), attempts);
+        assert_eq!(
+            (
+                status.pending,
+                status.running,
+                status.completed,
+                status.failed
+            ),
+            (0, 1, 2, 1)
+        );
+        assert!(!status.finished);
+        assert_eq!(status.first_completed, Some(first));
+
+        let status = AttemptGroupStatus::new(group, vec![member(None, None)]);
+        assert_eq!(status.pending, 1);
+        assert!(!status.finished);
+    }
+}
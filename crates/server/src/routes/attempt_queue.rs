@@ -0,0 +1,55 @@
+use axum::{Extension, Router, extract::State, response::Json as ResponseJson, routing::get};
+use db::models::{queued_attempt::QueuedAttempt, workspace::Workspace};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::attempt_queue;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptQueuePosition {
+    /// 1 for the attempt that starts next
+    pub position: u32,
+    pub queued: u32,
+    pub attempt: QueuedAttempt,
+}
+
+async fn ordered_queue(deployment: &DeploymentImpl) -> Result<Vec<QueuedAttempt>, ApiError> {
+    let order = deployment.config().read().await.attempt_queue.order;
+    let queue = QueuedAttempt::find_all(&deployment.db().pool).await?;
+    Ok(attempt_queue::in_order(queue, order))
+}
+
+/// Attempts waiting for a slot, the one that starts next first
+pub async fn get_attempt_queue(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<QueuedAttempt>>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(
+        ordered_queue(&deployment).await?,
+    )))
+}
+
+/// Where the attempt is in the queue; None once it has started
+pub async fn get_attempt_queue_position(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<AttemptQueuePosition>>>, ApiError> {
+    let queue = ordered_queue(&deployment).await?;
+    let queued = queue.len() as u32;
+    let position = queue
+        .into_iter()
+        .enumerate()
+        .find(|(_, attempt)| attempt.workspace_id == workspace.id)
+        .map(|(index, attempt)| AttemptQueuePosition {
+            position: index as u32 + 1,
+            queued,
+            attempt,
+        });
+    Ok(ResponseJson(ApiResponse::success(position)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/attempt-queue", get(get_attempt_queue))
+}
@@ -0,0 +1,241 @@
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::put,
+};
+use db::models::{
+    board_column::{BoardColumn, CreateBoardColumn, UpdateBoardColumn},
+    project::Project,
+    project_settings::{ProjectSettings, WipEnforcement},
+    task::TaskStatus,
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_board_column_middleware};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ReorderBoardColumns {
+    /// Every column of the project, left to right
+    pub column_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ColumnWipStatus {
+    pub column_id: Uuid,
+    pub task_count: i64,
+    /// None when the column takes any number of tasks
+    pub max_tasks: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BoardWipStatus {
+    pub enforcement: WipEnforcement,
+    /// Every column of the project, left to right
+    pub columns: Vec<ColumnWipStatus>,
+}
+
+pub async fn get_columns(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<BoardColumn>>>, ApiError> {
+    let columns = BoardColumn::find_or_create_defaults(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(columns)))
+}
+
+pub async fn create_column(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateBoardColumn>,
+) -> Result<ResponseJson<ApiResponse<BoardColumn>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let columns = BoardColumn::find_or_create_defaults(pool, project.id).await?;
+    validate_name(&columns, None, &payload.name)?;
+    let column = BoardColumn::create(pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "board_column_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "status": column.status.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(column)))
+}
+
+pub async fn update_column(
+    Extension(column): Extension<BoardColumn>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateBoardColumn>,
+) -> Result<ResponseJson<ApiResponse<BoardColumn>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let columns = BoardColumn::find_by_project_id(pool, column.project_id).await?;
+    if let Some(name) = &payload.name {
+        validate_name(&columns, Some(column.id), name)?;
+    }
+    if payload
+        .status
+        .as_ref()
+        .is_some_and(|status| *status != column.status)
+    {
+        ensure_not_last_of_status(&columns, &column)?;
+    }
+    let column = BoardColumn::update(pool, &column, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(column)))
+}
+
+/// Remove the column, moving its tasks to the first column of their status
+pub async fn delete_column(
+    Extension(column): Extension<BoardColumn>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let columns = BoardColumn::find_by_project_id(pool, column.project_id).await?;
+    ensure_not_last_of_status(&columns, &column)?;
+    if BoardColumn::delete(pool, &column).await? == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn reorder_columns(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderBoardColumns>,
+) -> Result<ResponseJson<ApiResponse<Vec<BoardColumn>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let columns = BoardColumn::find_or_create_defaults(pool, project.id).await?;
+    let mut given = payload.column_ids.clone();
+    given.sort();
+    given.dedup();
+    let mut expected: Vec<Uuid> = columns.iter().map(|column| column.id).collect();
+    expected.sort();
+    if given.len() != payload.column_ids.len() || given != expected {
+        return Err(ApiError::BadRequest(
+            "Give every column of the project exactly once".to_string(),
+        ));
+    }
+
+    BoardColumn::reorder(pool, project.id, &payload.column_ids).await?;
+    let columns = BoardColumn::find_by_project_id(pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(columns)))
+}
+
+/// Task counts of the columns next to their WIP limits
+pub async fn get_wip_status(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<BoardWipStatus>>, ApiError> {
+    let pool = &deployment.db().pool;
+    BoardColumn::find_or_create_defaults(pool, project.id).await?;
+    let settings = ProjectSettings::find_by_project_id(pool, project.id)
+        .await?
+        .wip_limits;
+    let columns = BoardColumn::task_counts(pool, project.id)
+        .await?
+        .into_iter()
+        .map(|count| ColumnWipStatus {
+            column_id: count.column_id,
+            task_count: count.task_count,
+            max_tasks: settings.limit_for(count.column_id),
+        })
+        .collect();
+
+    Ok(ResponseJson(ApiResponse::success(BoardWipStatus {
+        enforcement: settings.enforcement,
+        columns,
+    })))
+}
+
+/// Check tasks joining the project's board, given by status and stored column,
+/// against the WIP limits. Going over a limit is a conflict when the project
+/// rejects it; otherwise the returned message warns about it.
+pub async fn check_wip_limits(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    added: &[(TaskStatus, Option<Uuid>)],
+) -> Result<Option<String>, ApiError> {
+    let settings = ProjectSettings::find_by_project_id(pool, project_id)
+        .await?
+        .wip_limits;
+    if settings.limits.is_empty() || added.is_empty() {
+        return Ok(None);
+    }
+
+    let columns = BoardColumn::find_or_create_defaults(pool, project_id).await?;
+    let counts = BoardColumn::task_counts(pool, project_id).await?;
+    match settings.check(&columns, &counts, added) {
+        Ok(()) => Ok(None),
+        Err(message) if settings.enforcement == WipEnforcement::Reject => {
+            Err(ApiError::Conflict(message))
+        }
+        Err(message) => Ok(Some(message)),
+    }
+}
+
+/// Names are unique within a project, ignoring case
+fn validate_name(
+    columns: &[BoardColumn],
+    column_id: Option<Uuid>,
+    name: &str,
+) -> Result<(), ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("Column name is required".to_string()));
+    }
+    if columns
+        .iter()
+        .any(|column| Some(column.id) != column_id && column.name.eq_ignore_ascii_case(name))
+    {
+        return Err(ApiError::Conflict(format!(
+            "A column named '{name}' already exists"
+        )));
+    }
+    Ok(())
+}
+
+/// Every status keeps at least one column so that all tasks stay on the board
+fn ensure_not_last_of_status(
+    columns: &[BoardColumn],
+    column: &BoardColumn,
+) -> Result<(), ApiError> {
+    let others = columns
+        .iter()
+        .filter(|other| other.id != column.id && other.status == column.status)
+        .count();
+    if others == 0 {
+        return Err(ApiError::Conflict(format!(
+            "'{}' is the only column for {} tasks",
+            column.name,
+            status_name(&column.status)
+        )));
+    }
+    Ok(())
+}
+
+fn status_name(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "to do",
+        TaskStatus::InProgress => "in progress",
+        TaskStatus::InReview => "in review",
+        TaskStatus::Done => "done",
+        TaskStatus::Cancelled => "cancelled",
+    }
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let column_router = Router::new()
+        .route("/", put(update_column).delete(delete_column))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_board_column_middleware,
+        ));
+
+    Router::new().nest("/board-columns/{column_id}", column_router)
+}
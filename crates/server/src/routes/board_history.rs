@@ -0,0 +1,70 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use db::models::project::Project;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::board_history::{self, BurndownPoint, CumulativeFlow};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Days charted when the request doesn't say
+const DEFAULT_RANGE_DAYS: i64 = 30;
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct BoardHistoryQuery {
+    /// 30 days before `to` if not given
+    pub from: Option<NaiveDate>,
+    /// Today if not given
+    pub to: Option<NaiveDate>,
+}
+
+impl BoardHistoryQuery {
+    fn range(&self) -> Result<(NaiveDate, NaiveDate), ApiError> {
+        let to = self.to.unwrap_or_else(|| Utc::now().date_naive());
+        let from = self
+            .from
+            .unwrap_or(to - Duration::days(DEFAULT_RANGE_DAYS - 1));
+        if from > to {
+            return Err(ApiError::BadRequest(
+                "'from' must not be later than 'to'".to_string(),
+            ));
+        }
+        if (to - from).num_days() >= MAX_RANGE_DAYS {
+            return Err(ApiError::BadRequest(format!(
+                "At most {MAX_RANGE_DAYS} days can be charted at once"
+            )));
+        }
+        Ok((from, to))
+    }
+}
+
+/// Tasks left and done on the project's board, day by day
+pub async fn get_burndown(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<BoardHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<BurndownPoint>>>, ApiError> {
+    let (from, to) = query.range()?;
+    let points =
+        board_history::project_burndown(&deployment.db().pool, project.id, from, to).await?;
+    Ok(ResponseJson(ApiResponse::success(points)))
+}
+
+/// Tasks in each of the project's board columns, day by day
+pub async fn get_cumulative_flow(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<BoardHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<CumulativeFlow>>, ApiError> {
+    let (from, to) = query.range()?;
+    let flow =
+        board_history::project_cumulative_flow(&deployment.db().pool, project.id, from, to).await?;
+    Ok(ResponseJson(ApiResponse::success(flow)))
+}
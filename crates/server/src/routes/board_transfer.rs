@@ -0,0 +1,83 @@
+use axum::{
+    Extension, Json,
+    body::Body,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{Json as ResponseJson, Response},
+};
+use db::models::project::Project;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::board_transfer::{self, BoardFormat, BoardImportResult, ImportBoard};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError, routes::tasks::refresh_board};
+
+/// Uploaded files can be well beyond the default request body limit
+pub const IMPORT_BODY_LIMIT: usize = 20 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: BoardFormat,
+}
+
+/// Download the project's tasks, archived ones included
+pub async fn export_tasks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    let board = board_transfer::export_board(&deployment.db().pool, &project).await?;
+    let content = board_transfer::write_board(&board, query.format)?;
+    let file_name = format!(
+        "{}-tasks.{}",
+        project.name.replace(['/', '\\', '"'], "-"),
+        query.format.extension()
+    );
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, query.format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{file_name}\""),
+        )
+        .body(Body::from(content))
+        .map_err(|e| ApiError::Io(std::io::Error::other(e)))
+}
+
+pub async fn import_tasks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ImportBoard>,
+) -> Result<ResponseJson<ApiResponse<BoardImportResult>>, ApiError> {
+    let tasks = board_transfer::read_board(&payload.content, payload.format)?;
+    let result = board_transfer::import_board(
+        &deployment.db().pool,
+        project.id,
+        &tasks,
+        payload.on_duplicate,
+    )
+    .await?;
+
+    // Labels are stored apart from the task rows, so push the final state
+    for task_id in result.created.iter().chain(&result.updated) {
+        refresh_board(&deployment, *task_id).await;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "tasks_imported",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "format": payload.format.extension(),
+                "created_count": result.created.len(),
+                "updated_count": result.updated.len(),
+                "skipped_count": result.skipped,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(result)))
+}
@@ -6,22 +6,27 @@ use axum::{
     extract::{Path, Query, State},
     http,
     response::{Json as ResponseJson, Response},
-    routing::{get, put},
+    routing::{get, post, put},
 };
 use deployment::{Deployment, DeploymentError};
 use executors::{
     executors::{
         AvailabilityInfo, BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor,
+        plugin::manifest::{DiscoveredPlugin, discover_plugins},
     },
+    health::ExecutorHealth,
     mcp_config::{McpConfig, read_agent_config, write_agent_config},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use services::services::config::{
-    Config, ConfigError, SoundFile,
-    editor::{EditorConfig, EditorType},
-    save_config_to_file,
+use services::services::{
+    config::{
+        Config, ConfigError, SoundFile,
+        editor::{EditorConfig, EditorType},
+        save_config_to_file,
+    },
+    local_models::{self, EndpointHealth},
 };
 use tokio::fs;
 use ts_rs::TS;
@@ -41,6 +46,9 @@ pub fn router() -> Router<DeploymentImpl> {
             get(check_editor_availability),
         )
         .route("/agents/check-availability", get(check_agent_availability))
+        .route("/executors/health", get(get_executors_health))
+        .route("/executors/plugins", get(get_executor_plugins))
+        .route("/executors/plugins/reload", post(reload_executor_plugins))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -383,10 +391,13 @@ fn set_mcp_servers_in_config_path(
 pub struct ProfilesContent {
     pub content: String,
     pub path: String,
+    /// The local model endpoints, with their latency and the models they
+    /// offer the executors
+    pub local_models: Vec<EndpointHealth>,
 }
 
 async fn get_profiles(
-    State(_deployment): State<DeploymentImpl>,
+    State(deployment): State<DeploymentImpl>,
 ) -> ResponseJson<ApiResponse<ProfilesContent>> {
     let profiles_path = utils::assets::profiles_path();
 
@@ -399,9 +410,18 @@ async fn get_profiles(
             .unwrap_or_else(|_| "{}".to_string())
     });
 
+    let endpoints = deployment
+        .config()
+        .read()
+        .await
+        .local_model_endpoints
+        .clone();
+    let local_models = local_models::check_endpoints(&endpoints).await;
+
     ResponseJson(ApiResponse::success(ProfilesContent {
         content,
         path: profiles_path.display().to_string(),
+        local_models,
     }))
 }
 
@@ -485,3 +505,46 @@ async fn check_agent_availability(
 
     ResponseJson(ApiResponse::success(info))
 }
+
+/// Checks the default profile of every executor, slow the first time while
+/// `npx` downloads the CLIs
+async fn get_executors_health(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ExecutorHealth>>> {
+    let profiles = ExecutorConfigs::get_cached();
+    let mut executors: Vec<BaseCodingAgent> = profiles.executors.keys().copied().collect();
+    executors.sort_by_key(|executor| executor.to_string());
+
+    let agents: Vec<_> = executors
+        .into_iter()
+        .filter_map(|executor| profiles.get_coding_agent(&ExecutorProfileId::new(executor)))
+        .collect();
+    let health = futures_util::future::join_all(agents.iter().map(ExecutorHealth::check)).await;
+
+    ResponseJson(ApiResponse::success(health))
+}
+
+/// Plugins installed in the asset directory, including ones that can't be
+/// used and why
+async fn get_executor_plugins(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<DiscoveredPlugin>>> {
+    ResponseJson(ApiResponse::success(discover_plugins()))
+}
+
+/// Registers plugins installed since the profiles were loaded
+async fn reload_executor_plugins(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<DiscoveredPlugin>>> {
+    ExecutorConfigs::reload();
+    let plugins = discover_plugins();
+    deployment
+        .track_if_analytics_allowed(
+            "executor_plugins_reloaded",
+            serde_json::json!({
+                "plugin_count": plugins.iter().filter(|p| p.manifest.is_some()).count(),
+            }),
+        )
+        .await;
+    ResponseJson(ApiResponse::success(plugins))
+}
@@ -0,0 +1,132 @@
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::get,
+};
+use db::models::{
+    epic::{CreateEpic, Epic, UpdateEpic},
+    project::Project,
+    task::Task,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::epic_progress::{self, EpicProgress};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_epic_middleware, routes::tasks::refresh_board,
+};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetTaskEpicRequest {
+    /// None takes the task out of its epic
+    pub epic_id: Option<Uuid>,
+}
+
+pub async fn get_epics(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Epic>>>, ApiError> {
+    let epics = Epic::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(epics)))
+}
+
+pub async fn create_epic(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateEpic>,
+) -> Result<ResponseJson<ApiResponse<Epic>>, ApiError> {
+    validate_name(&payload.name)?;
+    let epic = Epic::create(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "epic_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "epic_id": epic.id.to_string(),
+                "has_target_date": epic.target_date.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(epic)))
+}
+
+pub async fn get_epic(
+    Extension(epic): Extension<Epic>,
+) -> Result<ResponseJson<ApiResponse<Epic>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(epic)))
+}
+
+pub async fn update_epic(
+    Extension(epic): Extension<Epic>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateEpic>,
+) -> Result<ResponseJson<ApiResponse<Epic>>, ApiError> {
+    validate_name(&payload.name)?;
+    let epic = Epic::update(&deployment.db().pool, epic.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(epic)))
+}
+
+/// Remove the epic, keeping its tasks
+pub async fn delete_epic(
+    Extension(epic): Extension<Epic>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if Epic::delete(&deployment.db().pool, epic.id).await? == 0 {
+        return Err(ApiError::Database(SqlxError::RowNotFound));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_epic_progress(
+    Extension(epic): Extension<Epic>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<EpicProgress>>, ApiError> {
+    let progress = epic_progress::epic_progress(&deployment.db().pool, epic).await?;
+    Ok(ResponseJson(ApiResponse::success(progress)))
+}
+
+/// Put the task in an epic of its project, or take it out of its epic
+pub async fn set_task_epic(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetTaskEpicRequest>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if let Some(epic_id) = payload.epic_id
+        && !Epic::find_by_id(pool, epic_id)
+            .await?
+            .is_some_and(|epic| epic.project_id == task.project_id)
+    {
+        return Err(ApiError::BadRequest(
+            "The epic has to be in the task's project".to_string(),
+        ));
+    }
+
+    Task::set_epic(pool, task.id, payload.epic_id).await?;
+    let task = Task::find_by_id(pool, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    refresh_board(&deployment, task.id).await;
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+fn validate_name(name: &str) -> Result<(), ApiError> {
+    if name.trim().is_empty() {
+        return Err(ApiError::BadRequest("Epic name is required".to_string()));
+    }
+    Ok(())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let epic_router = Router::new()
+        .route("/", get(get_epic).put(update_epic).delete(delete_epic))
+        .route("/progress", get(get_epic_progress))
+        .layer(from_fn_with_state(deployment.clone(), load_epic_middleware));
+
+    Router::new().nest("/epics/{epic_id}", epic_router)
+}
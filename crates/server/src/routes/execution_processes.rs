@@ -11,12 +11,17 @@ use axum::{
 };
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
+    execution_process_recording::ExecutionProcessRecording,
     execution_process_repo_state::ExecutionProcessRepoState,
 };
 use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::Deserialize;
-use services::services::container::ContainerService;
+use services::services::{
+    container::ContainerService,
+    log_search::{LogSearchPage, LogSearchQuery},
+    session_replay::ReplayQuery,
+};
 use utils::{log_msg::LogMsg, response::ApiResponse};
 use uuid::Uuid;
 
@@ -243,6 +248,74 @@ pub async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+/// A page of the run's conversation entries matching the query, so long logs
+/// can be searched without loading them into the browser
+pub async fn search_normalized_logs(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<LogSearchPage>>, ApiError> {
+    let entries = deployment
+        .container()
+        .normalized_entries(&execution_process.id)
+        .await
+        .ok_or(ApiError::ExecutionProcess(
+            ExecutionProcessError::ExecutionProcessNotFound,
+        ))?;
+    Ok(ResponseJson(ApiResponse::success(query.run(&entries))))
+}
+
+async fn find_recording(
+    deployment: &DeploymentImpl,
+    execution_process: &ExecutionProcess,
+) -> Result<ExecutionProcessRecording, ApiError> {
+    ExecutionProcessRecording::find_by_execution_id(&deployment.db().pool, execution_process.id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::Conflict(
+                "The run has no recording; coding agent runs are recorded once they finish"
+                    .to_string(),
+            )
+        })
+}
+
+/// Every patch to the run's conversation with its time, to attach to a bug
+/// report or replay elsewhere
+pub async fn get_recording(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcessRecording>>, ApiError> {
+    let recording = find_recording(&deployment, &execution_process).await?;
+    Ok(ResponseJson(ApiResponse::success(recording)))
+}
+
+/// Sends the recorded patches at the pace the run made them, in the format
+/// of the normalized logs stream
+pub async fn replay_recording_ws(
+    ws: WebSocketUpgrade,
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let recording = find_recording(&deployment, &execution_process).await?;
+    deployment
+        .track_if_analytics_allowed(
+            "execution_process_replayed",
+            serde_json::json!({
+                "execution_process_id": execution_process.id.to_string(),
+                "speed": query.speed,
+            }),
+        )
+        .await;
+    let stream = query.stream(recording).map(Ok::<_, anyhow::Error>).boxed();
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_normalized_logs_ws(socket, stream).await {
+            tracing::warn!("replay WS closed: {}", e);
+        }
+    }))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
@@ -250,6 +323,9 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/repo-states", get(get_execution_process_repo_states))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
+        .route("/normalized-logs/search", get(search_normalized_logs))
+        .route("/recording", get(get_recording))
+        .route("/recording/replay/ws", get(replay_recording_ws))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
@@ -0,0 +1,84 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    github_issue_link::GitHubIssueLink,
+    project::Project,
+    project_repo::{ProjectRepo, ProjectRepoError},
+    repo::Repo,
+};
+use deployment::Deployment;
+use services::services::{
+    github::{GitHubService, IssueFilter},
+    github_issue_import::{self, GitHubIssueImport, ImportGitHubIssues},
+};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Most issues looked at by one import
+const MAX_ISSUES: u32 = 200;
+
+/// Issues the project's tasks were imported from
+pub async fn get_issue_links(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<GitHubIssueLink>>>, ApiError> {
+    let links = GitHubIssueLink::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(links)))
+}
+
+/// Create tasks for the open issues of one of the project's repositories
+pub async fn import_issues(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ImportGitHubIssues>,
+) -> Result<ResponseJson<ApiResponse<GitHubIssueImport>>, ApiError> {
+    let pool = &deployment.db().pool;
+    ProjectRepo::find_by_project_and_repo(pool, project.id, payload.repo_id)
+        .await?
+        .ok_or(ProjectRepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(ProjectRepoError::NotFound)?;
+
+    let github_service = GitHubService::new()?;
+    let repo_info = github_service.get_repo_info(&repo.path).await?;
+    let filter = IssueFilter {
+        labels: payload
+            .labels
+            .iter()
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty())
+            .collect(),
+        milestone: payload
+            .milestone
+            .as_deref()
+            .map(str::trim)
+            .filter(|milestone| !milestone.is_empty())
+            .map(str::to_string),
+        limit: MAX_ISSUES,
+    };
+    let issues = github_service.list_issues(&repo_info, &filter).await?;
+
+    let import = github_issue_import::import_issues(
+        pool,
+        project.id,
+        &repo_info,
+        &issues,
+        payload.sync_status,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "github_issues_imported",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "task_count": import.tasks.len(),
+                "skipped_count": import.skipped,
+                "sync_status": payload.sync_status,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(import)))
+}
@@ -95,6 +95,11 @@ pub(crate) async fn process_image_upload(
                 .unwrap_or_else(|| "image.png".to_string());
 
             let data = field.bytes().await?;
+            if let Some(task_id) = link_task_id {
+                image_service
+                    .check_upload_policy(task_id, &filename, data.len() as i64)
+                    .await?;
+            }
             let image = image_service.store_image(&data, &filename).await?;
 
             if let Some(task_id) = link_task_id {
@@ -172,6 +177,31 @@ pub async fn serve_image(
     Ok(response)
 }
 
+/// Serve a PNG thumbnail of an image, generating it on first request
+pub async fn serve_thumbnail(
+    Path(image_id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Response, ApiError> {
+    let image_service = deployment.image();
+    let image = image_service
+        .get_image(image_id)
+        .await?
+        .ok_or_else(|| ApiError::Image(ImageError::NotFound))?;
+    let thumbnail_path = image_service.ensure_thumbnail(&image).await?;
+
+    let file = File::open(&thumbnail_path).await?;
+    let metadata = file.metadata().await?;
+    let body = Body::from_stream(ReaderStream::new(file));
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/png")
+        .header(header::CONTENT_LENGTH, metadata.len())
+        .header(header::CACHE_CONTROL, "public, max-age=31536000")
+        .body(body)
+        .map_err(|e| ApiError::Image(ImageError::ResponseBuildError(e.to_string())))
+}
+
 pub async fn delete_image(
     Path(image_id): Path<Uuid>,
     State(deployment): State<DeploymentImpl>,
@@ -260,6 +290,7 @@ pub fn routes() -> Router<DeploymentImpl> {
             post(upload_image).layer(DefaultBodyLimit::max(20 * 1024 * 1024)), // 20MB limit
         )
         .route("/{id}/file", get(serve_image))
+        .route("/{id}/thumbnail", get(serve_thumbnail))
         .route("/{id}", delete(delete_image))
         .route("/task/{task_id}", get(get_task_images))
         .route("/task/{task_id}/metadata", get(get_task_image_metadata))
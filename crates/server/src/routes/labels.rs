@@ -0,0 +1,117 @@
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::put,
+};
+use db::models::{
+    label::{self, CreateLabel, Label, UpdateLabel},
+    project::Project,
+};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_label_middleware,
+    routes::tasks::refresh_board,
+};
+
+pub async fn get_labels(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Label>>>, ApiError> {
+    let labels = Label::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(labels)))
+}
+
+pub async fn create_label(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateLabel>,
+) -> Result<ResponseJson<ApiResponse<Label>>, ApiError> {
+    validate(&deployment, project.id, None, &payload.name, &payload.color).await?;
+    let label = Label::create(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "label_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "label_id": label.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(label)))
+}
+
+pub async fn update_label(
+    Extension(label): Extension<Label>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateLabel>,
+) -> Result<ResponseJson<ApiResponse<Label>>, ApiError> {
+    validate(
+        &deployment,
+        label.project_id,
+        Some(label.id),
+        payload.name.as_deref().unwrap_or(&label.name),
+        payload.color.as_deref().unwrap_or(&label.color),
+    )
+    .await?;
+    let label = Label::update(&deployment.db().pool, label.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(label)))
+}
+
+pub async fn delete_label(
+    Extension(label): Extension<Label>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task_ids = Label::find_task_ids(pool, label.id).await?;
+    if Label::delete(pool, label.id).await? == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    for task_id in task_ids {
+        refresh_board(&deployment, task_id).await;
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Names are unique within a project, ignoring case
+async fn validate(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    label_id: Option<Uuid>,
+    name: &str,
+    color: &str,
+) -> Result<(), ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("Label name is required".to_string()));
+    }
+    if !label::is_valid_color(color) {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid color '{color}', expected a hex color such as #1f883d"
+        )));
+    }
+    let labels = Label::find_by_project_id(&deployment.db().pool, project_id).await?;
+    if labels
+        .iter()
+        .any(|label| Some(label.id) != label_id && label.name.eq_ignore_ascii_case(name))
+    {
+        return Err(ApiError::Conflict(format!(
+            "A label named '{name}' already exists"
+        )));
+    }
+    Ok(())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let label_router = Router::new()
+        .route("/", put(update_label).delete(delete_label))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_label_middleware,
+        ));
+
+    Router::new().nest("/labels/{label_id}", label_router)
+}
@@ -0,0 +1,357 @@
+//! The LLM proxy: coding agents send their model API calls to
+//! `/api/llm-proxy/{api}/...` with their attempt's token as the key, and the
+//! call is made with the project's key for that API, trying the project's
+//! providers in order.
+//!
+//! Responses are streamed to the agent as they arrive and logged once they
+//! end, with the usage read from them counted against the project's monthly
+//! spend cap. A call over the cap is refused before it reaches a provider, so
+//! the call that crosses it still goes through.
+
+use std::time::Instant;
+
+use axum::{
+    Extension, Router,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::any,
+};
+use chrono::Utc;
+use db::models::{
+    llm_proxy::{CreateLlmProxyRequest, LlmProxyRequest, LlmProxyToken},
+    project::Project,
+    project_settings::{LlmApi, LlmProvider, ProjectSettings},
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    llm_proxy::{self, CallUsage},
+    secrets::ProjectEnv,
+};
+use sqlx::SqlitePool;
+use tokio::sync::mpsc;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use super::preview::forwarded_headers;
+use crate::{DeploymentImpl, error::ApiError};
+
+const MAX_REQUEST_BODY: usize = 32 * 1024 * 1024;
+/// How much of a response is kept to read its usage from
+const MAX_CAPTURED_RESPONSE: usize = 16 * 1024 * 1024;
+const REQUESTS_PAGE_SIZE: u32 = 100;
+const MAX_REQUESTS_PAGE_SIZE: u32 = 500;
+
+/// Where a call came from, to log it with
+#[derive(Clone)]
+struct Call {
+    pool: SqlitePool,
+    project_id: Uuid,
+    workspace_id: Uuid,
+    method: String,
+    path: String,
+    log_bodies: bool,
+    request_body: Option<String>,
+    secret_values: Vec<String>,
+}
+
+impl Call {
+    async fn log(
+        &self,
+        provider: &LlmProvider,
+        status: Option<StatusCode>,
+        response_body: &[u8],
+        error: Option<String>,
+        started: Instant,
+    ) {
+        let usage = CallUsage::from_body(response_body);
+        let data = CreateLlmProxyRequest {
+            project_id: self.project_id,
+            workspace_id: self.workspace_id,
+            provider: provider.name.clone(),
+            method: self.method.clone(),
+            path: self.path.clone(),
+            cost_usd: usage.cost_usd(provider),
+            model: usage.model,
+            status_code: status.map(|status| i64::from(status.as_u16())),
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            duration_ms: started.elapsed().as_millis() as i64,
+            error,
+            request_body: self.request_body.clone(),
+            response_body: self
+                .log_bodies
+                .then(|| llm_proxy::logged_body(response_body, &self.secret_values)),
+        };
+        if let Err(e) = LlmProxyRequest::create(&self.pool, &data).await {
+            tracing::error!(
+                "Failed to log an LLM proxy call of {}: {}",
+                self.workspace_id,
+                e
+            );
+        }
+    }
+}
+
+async fn proxy(
+    State(deployment): State<DeploymentImpl>,
+    Path((api, path)): Path<(LlmApi, String)>,
+    request: Request,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let Some(token) = agent_token(request.headers()) else {
+        return Ok((StatusCode::UNAUTHORIZED, "Missing LLM proxy token").into_response());
+    };
+    let workspace = match LlmProxyToken::find_workspace_id(pool, token).await? {
+        Some(workspace_id) => Workspace::find_by_id(pool, workspace_id).await?,
+        None => None,
+    };
+    let Some(workspace) = workspace else {
+        return Ok((StatusCode::UNAUTHORIZED, "Unknown LLM proxy token").into_response());
+    };
+    let Some(task) = workspace.parent_task(pool).await? else {
+        return Ok((StatusCode::UNAUTHORIZED, "Unknown LLM proxy token").into_response());
+    };
+
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id)
+        .await?
+        .llm_proxy;
+    if !settings.enabled {
+        return Ok((
+            StatusCode::FORBIDDEN,
+            "The LLM proxy is turned off for this project",
+        )
+            .into_response());
+    }
+    if let Some(cap) = settings.monthly_spend_cap_usd {
+        let spend =
+            LlmProxyRequest::spend_since(pool, task.project_id, llm_proxy::month_start(Utc::now()))
+                .await?;
+        if spend >= cap {
+            return Ok((
+                StatusCode::PAYMENT_REQUIRED,
+                format!("The project's monthly LLM spend cap of ${cap:.2} is reached"),
+            )
+                .into_response());
+        }
+    }
+    let providers: Vec<LlmProvider> = settings
+        .providers
+        .into_iter()
+        .filter(|provider| provider.api == api)
+        .collect();
+    if providers.is_empty() {
+        return Ok((
+            StatusCode::BAD_GATEWAY,
+            "No provider is set up for this API",
+        )
+            .into_response());
+    }
+    let project_env = ProjectEnv::load(pool, task.project_id).await?;
+
+    let query = request.uri().query().map(str::to_string);
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_REQUEST_BODY)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let call = Call {
+        pool: pool.clone(),
+        project_id: task.project_id,
+        workspace_id: workspace.id,
+        method: parts.method.to_string(),
+        path: path.clone(),
+        log_bodies: settings.log_bodies,
+        request_body: settings
+            .log_bodies
+            .then(|| llm_proxy::logged_body(&body, &project_env.secret_values)),
+        secret_values: project_env.secret_values.clone(),
+    };
+
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| ApiError::Io(std::io::Error::other(e)))?;
+    let last = providers.len() - 1;
+    for (index, provider) in providers.into_iter().enumerate() {
+        let Some((auth_name, auth_value)) = project_env
+            .vars
+            .iter()
+            .find(|(name, _)| *name == provider.api_key_secret)
+            .and_then(|(_, key)| key_header(provider.api, key))
+        else {
+            tracing::warn!(
+                "LLM proxy provider {} of project {} has no usable key in {}",
+                provider.name,
+                task.project_id,
+                provider.api_key_secret
+            );
+            continue;
+        };
+        let mut headers = forwarded_headers(&parts.headers);
+        headers.remove(header::AUTHORIZATION);
+        headers.remove("x-api-key");
+        // Uncompressed, so the usage can be read from the response
+        headers.remove(header::ACCEPT_ENCODING);
+        headers.insert(auth_name, auth_value);
+
+        let started = Instant::now();
+        let upstream = client
+            .request(
+                parts.method.clone(),
+                llm_proxy::upstream_url(&provider, &path, query.as_deref()),
+            )
+            .headers(headers)
+            .body(body.clone())
+            .send()
+            .await;
+        match upstream {
+            Ok(upstream) if index == last || !llm_proxy::fails_over(upstream.status().as_u16()) => {
+                return Ok(stream_response(call, provider, upstream, started));
+            }
+            Ok(upstream) => {
+                let status = upstream.status();
+                let response_body = upstream.bytes().await.unwrap_or_default();
+                call.log(
+                    &provider,
+                    Some(status),
+                    &response_body,
+                    Some("Tried the next provider".to_string()),
+                    started,
+                )
+                .await;
+            }
+            Err(e) => {
+                tracing::debug!("LLM proxy call to {} failed: {}", provider.name, e);
+                call.log(&provider, None, &[], Some(e.to_string()), started)
+                    .await;
+            }
+        }
+    }
+    Ok((StatusCode::BAD_GATEWAY, "No provider could be reached").into_response())
+}
+
+/// The attempt's token, sent where the agent's SDK puts its key
+fn agent_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+fn key_header(api: LlmApi, key: &str) -> Option<(HeaderName, HeaderValue)> {
+    match api {
+        LlmApi::Anthropic => Some((
+            HeaderName::from_static("x-api-key"),
+            HeaderValue::from_str(key).ok()?,
+        )),
+        LlmApi::OpenAi => Some((
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {key}")).ok()?,
+        )),
+    }
+}
+
+/// Pass the provider's response on as it arrives, and log the call once it
+/// ends
+fn stream_response(
+    call: Call,
+    provider: LlmProvider,
+    upstream: reqwest::Response,
+    started: Instant,
+) -> Response {
+    let status = upstream.status();
+    let mut response = Response::builder().status(status);
+    if let Some(headers) = response.headers_mut() {
+        *headers = forwarded_headers(upstream.headers());
+    }
+
+    let (tx, rx) = mpsc::channel::<Result<Bytes, std::io::Error>>(16);
+    tokio::spawn(async move {
+        let mut captured = Vec::new();
+        let mut error = None;
+        let mut chunks = upstream.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    if captured.len() < MAX_CAPTURED_RESPONSE {
+                        captured.extend_from_slice(&chunk);
+                    }
+                    if tx.send(Ok(chunk)).await.is_err() {
+                        error = Some("The agent closed the connection".to_string());
+                        break;
+                    }
+                }
+                Err(e) => {
+                    error = Some(e.to_string());
+                    let _ = tx.send(Err(std::io::Error::other(e))).await;
+                    break;
+                }
+            }
+        }
+        call.log(&provider, Some(status), &captured, error, started)
+            .await;
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+    response
+        .body(Body::from_stream(body))
+        .unwrap_or_else(|_| StatusCode::BAD_GATEWAY.into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LlmProxyLogQuery {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct LlmProxyLog {
+    pub month_spend_usd: f64,
+    pub monthly_spend_cap_usd: Option<f64>,
+    /// Newest first
+    pub requests: Vec<LlmProxyRequest>,
+}
+
+/// The project's latest calls through the LLM proxy and this month's spend
+pub async fn get_llm_proxy_log(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LlmProxyLogQuery>,
+) -> Result<ResponseJson<ApiResponse<LlmProxyLog>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let limit = query
+        .limit
+        .unwrap_or(REQUESTS_PAGE_SIZE)
+        .clamp(1, MAX_REQUESTS_PAGE_SIZE);
+    let settings = ProjectSettings::find_by_project_id(pool, project.id)
+        .await?
+        .llm_proxy;
+    let month_spend_usd =
+        LlmProxyRequest::spend_since(pool, project.id, llm_proxy::month_start(Utc::now())).await?;
+    let requests = LlmProxyRequest::find_by_project_id(pool, project.id, i64::from(limit)).await?;
+
+    Ok(ResponseJson(ApiResponse::success(LlmProxyLog {
+        month_spend_usd,
+        monthly_spend_cap_usd: settings.monthly_spend_cap_usd,
+        requests,
+    })))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/llm-proxy/{api}/{*path}", any(proxy))
+        .layer(DefaultBodyLimit::max(MAX_REQUEST_BODY))
+}
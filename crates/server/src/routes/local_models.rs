@@ -0,0 +1,147 @@
+use axum::{
+    Router,
+    extract::{Json, Path, State},
+    response::Json as ResponseJson,
+    routing::{delete, get, post},
+};
+use deployment::Deployment;
+use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use serde::Deserialize;
+use services::services::{
+    config::{Config, LocalModelEndpoint, save_config_to_file},
+    local_models::{self, EndpointHealth},
+};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::{assets::config_path, response::ApiResponse};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SaveLocalModelBackend {
+    pub executor: BaseCodingAgent,
+    pub model: String,
+}
+
+async fn update_config(
+    deployment: &DeploymentImpl,
+    update: impl FnOnce(&mut Config),
+) -> Result<(), ApiError> {
+    let mut config = deployment.config().write().await;
+    let mut updated = config.clone();
+    update(&mut updated);
+    save_config_to_file(&updated, &config_path()).await?;
+    *config = updated;
+    Ok(())
+}
+
+async fn find_endpoint(
+    deployment: &DeploymentImpl,
+    name: &str,
+) -> Result<LocalModelEndpoint, ApiError> {
+    deployment
+        .config()
+        .read()
+        .await
+        .local_model_endpoints
+        .iter()
+        .find(|endpoint| endpoint.name == name)
+        .cloned()
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))
+}
+
+/// The registered endpoints, each checked now
+pub async fn get_local_models(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<EndpointHealth>>> {
+    let endpoints = deployment
+        .config()
+        .read()
+        .await
+        .local_model_endpoints
+        .clone();
+    ResponseJson(ApiResponse::success(
+        local_models::check_endpoints(&endpoints).await,
+    ))
+}
+
+pub async fn register_local_model(
+    State(deployment): State<DeploymentImpl>,
+    Json(endpoint): Json<LocalModelEndpoint>,
+) -> Result<ResponseJson<ApiResponse<EndpointHealth>>, ApiError> {
+    let existing = deployment
+        .config()
+        .read()
+        .await
+        .local_model_endpoints
+        .clone();
+    local_models::validate_endpoint(&endpoint, &existing).map_err(ApiError::BadRequest)?;
+    update_config(&deployment, |config| {
+        config.local_model_endpoints.push(endpoint.clone())
+    })
+    .await?;
+
+    let health = local_models::check_endpoints(std::slice::from_ref(&endpoint)).await;
+    deployment
+        .track_if_analytics_allowed(
+            "local_model_endpoint_registered",
+            serde_json::json!({
+                "server": endpoint.server,
+                "available": health.first().is_some_and(|health| health.available),
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(
+        health.into_iter().next().expect("one endpoint was checked"),
+    )))
+}
+
+/// Forget the endpoint; the profile variants saved for its models stay
+pub async fn delete_local_model(
+    State(deployment): State<DeploymentImpl>,
+    Path(name): Path<String>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    find_endpoint(&deployment, &name).await?;
+    update_config(&deployment, |config| {
+        config
+            .local_model_endpoints
+            .retain(|endpoint| endpoint.name != name)
+    })
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Save a model of the endpoint as a profile variant of the executor, to be
+/// picked like any other
+pub async fn save_local_model_backend(
+    State(deployment): State<DeploymentImpl>,
+    Path(name): Path<String>,
+    Json(payload): Json<SaveLocalModelBackend>,
+) -> Result<ResponseJson<ApiResponse<ExecutorProfileId>>, ApiError> {
+    let endpoint = find_endpoint(&deployment, &name).await?;
+    if !local_models::compatible_executors(endpoint.server).contains(&payload.executor) {
+        return Err(ApiError::BadRequest(format!(
+            "{} can't use models of {}",
+            payload.executor, endpoint.name
+        )));
+    }
+    if payload.model.trim().is_empty() {
+        return Err(ApiError::BadRequest("Choose a model".to_string()));
+    }
+    let profile_id = local_models::save_backend(&endpoint, payload.executor, &payload.model)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    Ok(ResponseJson(ApiResponse::success(profile_id)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route(
+            "/local-models",
+            get(get_local_models).post(register_local_model),
+        )
+        .route("/local-models/{name}", delete(delete_local_model))
+        .route(
+            "/local-models/{name}/backends",
+            post(save_local_model_backend),
+        )
+}
@@ -5,26 +5,48 @@ use axum::{
 
 use crate::DeploymentImpl;
 
+pub mod activity;
 pub mod approvals;
+pub mod attempt_groups;
+pub mod attempt_queue;
+pub mod board_columns;
+pub mod board_history;
+pub mod board_transfer;
 pub mod config;
 pub mod containers;
+pub mod epics;
 pub mod filesystem;
 // pub mod github;
 pub mod events;
 pub mod execution_processes;
 pub mod frontend;
+pub mod github_issues;
 pub mod health;
 pub mod images;
+pub mod labels;
+pub mod llm_proxy;
+pub mod local_models;
 pub mod oauth;
 pub mod organizations;
+pub mod preview;
+pub mod processes;
 pub mod projects;
 pub mod repo;
 pub mod scratch;
+pub mod search;
 pub mod sessions;
 pub mod shared_tasks;
 pub mod tags;
 pub mod task_attempts;
+pub mod task_checklists;
+pub mod task_links;
+pub mod task_plans;
+pub mod task_revisions;
+pub mod task_schedules;
 pub mod tasks;
+pub mod time_tracking;
+pub mod usage;
+pub mod webdav;
 
 pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
     // Create routers with different middleware layers
@@ -36,6 +58,15 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(tasks::router(&deployment))
         .merge(shared_tasks::router())
         .merge(task_attempts::router(&deployment))
+        .merge(attempt_groups::router(&deployment))
+        .merge(attempt_queue::router())
+        .merge(task_schedules::router(&deployment))
+        .merge(labels::router(&deployment))
+        .merge(epics::router(&deployment))
+        .merge(board_columns::router(&deployment))
+        .merge(time_tracking::router(&deployment))
+        .merge(task_checklists::router(&deployment))
+        .merge(task_links::router(&deployment))
         .merge(execution_processes::router(&deployment))
         .merge(tags::router(&deployment))
         .merge(oauth::router())
@@ -46,6 +77,12 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(approvals::router())
         .merge(scratch::router(&deployment))
         .merge(sessions::router(&deployment))
+        .merge(webdav::router())
+        .merge(preview::router())
+        .merge(llm_proxy::router())
+        .merge(local_models::router())
+        .merge(processes::router())
+        .merge(search::router())
         .nest("/images", images::routes())
         .with_state(deployment);
 
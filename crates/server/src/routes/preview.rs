@@ -0,0 +1,192 @@
+//! Reverse proxy to dev servers running in task attempt workspaces, so a
+//! preview is reachable at `/api/preview/{attempt_id}/{port}/`.
+//!
+//! Only ports that a process in the workspace is currently listening on are
+//! proxied, and only while the attempt has running processes, so previews go
+//! away when the attempt is stopped. Apps are served under the prefix above,
+//! which is also sent as `X-Forwarded-Prefix` for servers that support a base
+//! path. WebSocket upgrades (e.g. hot reload) are not proxied.
+//!
+//! Previews share the app's origin, so the pages are sandboxed: their scripts
+//! run in an opaque origin and can't call the API with the user's session.
+//! Cookies are not passed in either direction for the same reason.
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{DefaultBodyLimit, Path, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::any,
+};
+use db::models::{execution_process::ExecutionProcess, workspace::Workspace};
+use deployment::Deployment;
+use futures_util::TryStreamExt;
+use services::services::container::ContainerService;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const MAX_REQUEST_BODY: usize = 50 * 1024 * 1024;
+
+const PREVIEW_CSP: &str = "sandbox allow-scripts allow-forms";
+
+/// Headers that describe a single connection and must not be forwarded
+const HOP_BY_HOP: [HeaderName; 7] = [
+    header::CONNECTION,
+    header::PROXY_AUTHENTICATE,
+    header::PROXY_AUTHORIZATION,
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+async fn proxy_root(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, port)): Path<(Uuid, u16)>,
+    request: Request,
+) -> Result<Response, ApiError> {
+    proxy(deployment, workspace_id, port, String::new(), request).await
+}
+
+async fn proxy_path(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, port, path)): Path<(Uuid, u16, String)>,
+    request: Request,
+) -> Result<Response, ApiError> {
+    proxy(deployment, workspace_id, port, path, request).await
+}
+
+async fn proxy(
+    deployment: DeploymentImpl,
+    workspace_id: Uuid,
+    port: u16,
+    path: String,
+    request: Request,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let Some(workspace) = Workspace::find_by_id(pool, workspace_id).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let running =
+        ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+            .await?
+            || !ExecutionProcess::find_running_dev_servers_by_workspace(pool, workspace.id)
+                .await?
+                .is_empty();
+    if !running {
+        return Ok((StatusCode::NOT_FOUND, "Preview is not running").into_response());
+    }
+    let Some(target) = deployment
+        .container()
+        .preview_targets(&workspace)
+        .await?
+        .into_iter()
+        .find(|target| target.port == port)
+    else {
+        return Ok((StatusCode::BAD_GATEWAY, "Nothing is listening on this port").into_response());
+    };
+
+    let mut url = format!("http://{}:{}/{}", target.host, target.port, path);
+    if let Some(query) = request.uri().query() {
+        url.push('?');
+        url.push_str(query);
+    }
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_REQUEST_BODY)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let mut headers = forwarded_headers(&parts.headers);
+    headers.remove(header::COOKIE);
+    headers.insert(
+        "x-forwarded-prefix",
+        format!("/api/preview/{workspace_id}/{port}")
+            .parse()
+            .expect("valid header value"),
+    );
+
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| ApiError::Io(std::io::Error::other(e)))?;
+    let upstream = match client
+        .request(parts.method, url)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await
+    {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            tracing::debug!("Preview proxy to port {} failed: {}", port, e);
+            return Ok(
+                (StatusCode::BAD_GATEWAY, "Preview server is not responding").into_response(),
+            );
+        }
+    };
+
+    let mut response = Response::builder().status(upstream.status());
+    if let Some(headers) = response.headers_mut() {
+        *headers = preview_response_headers(upstream.headers());
+    }
+    let stream = upstream.bytes_stream().map_err(std::io::Error::other);
+    Ok(response
+        .body(Body::from_stream(stream))
+        .unwrap_or_else(|_| StatusCode::BAD_GATEWAY.into_response()))
+}
+
+pub(crate) fn forwarded_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut forwarded = headers.clone();
+    forwarded.remove(header::HOST);
+    for name in &HOP_BY_HOP {
+        forwarded.remove(name);
+    }
+    forwarded
+}
+
+/// Upstream response headers as sent to the browser, sandboxed on top of any
+/// policy the dev server sets itself
+fn preview_response_headers(upstream: &HeaderMap) -> HeaderMap {
+    let mut headers = forwarded_headers(upstream);
+    headers.remove(header::SET_COOKIE);
+    headers.append(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static(PREVIEW_CSP),
+    );
+    headers
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/preview/{workspace_id}/{port}", any(proxy_root))
+        .route("/preview/{workspace_id}/{port}/", any(proxy_root))
+        .route("/preview/{workspace_id}/{port}/{*path}", any(proxy_path))
+        .layer(DefaultBodyLimit::max(MAX_REQUEST_BODY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_responses_are_sandboxed_without_cookies() {
+        let mut upstream = HeaderMap::new();
+        upstream.insert(header::SET_COOKIE, "session=abc".parse().unwrap());
+        upstream.insert(
+            header::CONTENT_SECURITY_POLICY,
+            "default-src 'self'".parse().unwrap(),
+        );
+        upstream.insert(header::CONTENT_TYPE, "text/html".parse().unwrap());
+
+        let headers = preview_response_headers(&upstream);
+        assert!(headers.get(header::SET_COOKIE).is_none());
+        assert_eq!(headers.get(header::CONTENT_TYPE).unwrap(), "text/html");
+        let policies: Vec<_> = headers
+            .get_all(header::CONTENT_SECURITY_POLICY)
+            .iter()
+            .collect();
+        assert_eq!(policies, ["default-src 'self'", PREVIEW_CSP]);
+    }
+}
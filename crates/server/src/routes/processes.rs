@@ -0,0 +1,14 @@
+use axum::{Router, response::Json as ResponseJson, routing::get};
+use services::services::process_registry::{ProcessInfo, ProcessRegistry};
+use utils::response::ApiResponse;
+
+use crate::DeploymentImpl;
+
+/// Every process the server has spawned that is still running
+pub async fn list_processes() -> ResponseJson<ApiResponse<Vec<ProcessInfo>>> {
+    ResponseJson(ApiResponse::success(ProcessRegistry::global().list()))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/processes", get(list_processes))
+}
@@ -4,25 +4,37 @@ use anyhow;
 use axum::{
     Extension, Router,
     extract::{
-        Json, Path, Query, State,
+        DefaultBodyLimit, Json, Path, Query, Request, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
     http::StatusCode,
-    middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get, post, put},
 };
 use db::models::{
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
+    project_env_var::{ProjectEnvVar, SetProjectEnvVar, is_valid_env_name},
     project_repo::{CreateProjectRepo, ProjectRepo, UpdateProjectRepo},
+    project_secret::ProjectSecret,
+    project_settings::{ProjectSettings, SharedCache},
     repo::Repo,
+    workspace_disk_usage::WorkspaceDiskUsage,
 };
 use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use services::services::{
-    file_search_cache::SearchQuery, project::ProjectServiceError,
+    commit_message,
+    container::{ContainerService, SharedCacheUsage},
+    file_search_cache::SearchQuery,
+    project::ProjectServiceError,
+    project_cleanup::{self, DeletedProject, ProjectCleanupProgress},
     remote_client::CreateRemoteProjectPayload,
+    secrets::SecretCipher,
+    task_scope::PathScope,
+    workspace_janitor::{self, CleanupPreview},
+    workspace_pool::WorkspacePoolStatus,
 };
 use ts_rs::TS;
 use utils::{
@@ -31,7 +43,15 @@ use utils::{
 };
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_project_middleware,
+    routes::{
+        activity, board_columns, board_history, board_transfer, epics, github_issues, labels,
+        llm_proxy, task_plans, task_schedules, time_tracking, usage,
+    },
+};
 
 #[derive(Deserialize, TS)]
 pub struct LinkToExistingRequest {
@@ -279,36 +299,61 @@ pub async fn update_project(
     }
 }
 
+/// Delete the project right away and clean up its workspaces in the
+/// background, see `GET /projects/cleanups` for progress
 pub async fn delete_project(
     Extension(project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
-) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
-    match deployment
-        .project()
-        .delete_project(&deployment.db().pool, project.id)
-        .await
-    {
-        Ok(rows_affected) => {
-            if rows_affected == 0 {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                deployment
-                    .track_if_analytics_allowed(
-                        "project_deleted",
-                        serde_json::json!({
-                            "project_id": project.id.to_string(),
-                        }),
-                    )
-                    .await;
-
-                Ok(ResponseJson(ApiResponse::success(())))
-            }
-        }
-        Err(e) => {
-            tracing::error!("Failed to delete project: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+) -> Result<(StatusCode, ResponseJson<ApiResponse<()>>), ApiError> {
+    let pool = &deployment.db().pool;
+    let deleted = DeletedProject::snapshot(pool, &project).await?;
+
+    let mut task_ids: Vec<Uuid> = deleted
+        .workspaces
+        .iter()
+        .map(|(workspace, _)| workspace.task_id)
+        .collect();
+    task_ids.sort();
+    task_ids.dedup();
+    for task_id in task_ids {
+        if deployment
+            .container()
+            .has_running_processes(task_id)
+            .await?
+        {
+            return Err(ApiError::Conflict(
+                "The project has running execution processes. Please wait for them to complete or stop them first.".to_string(),
+            ));
         }
     }
+
+    let rows_affected = deployment
+        .project()
+        .delete_project(pool, project.id)
+        .await?;
+    if rows_affected == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_deleted",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "workspace_count": deleted.workspaces.len(),
+                "trashed": deleted.purge_after().is_some(),
+            }),
+        )
+        .await;
+
+    deployment.container().queue_project_cleanup(deleted);
+
+    // Return 202 Accepted to indicate cleanup was scheduled
+    Ok((StatusCode::ACCEPTED, ResponseJson(ApiResponse::success(()))))
+}
+
+pub async fn get_project_cleanups() -> ResponseJson<ApiResponse<Vec<ProjectCleanupProgress>>> {
+    ResponseJson(ApiResponse::success(project_cleanup::list().await))
 }
 
 #[derive(serde::Deserialize)]
@@ -589,6 +634,211 @@ pub async fn update_project_repository(
     }
 }
 
+#[derive(Debug, Serialize, TS)]
+pub struct UploadQuota {
+    pub used_bytes: i64,
+    pub max_total_bytes: Option<i64>,
+    pub max_file_bytes: Option<i64>,
+    pub remaining_bytes: Option<i64>,
+}
+
+pub async fn get_project_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProjectSettings>>, ApiError> {
+    let settings = ProjectSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn update_project_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ProjectSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectSettings>>, ApiError> {
+    if let Err(e) = PathScope::new(&payload.protected_paths) {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid protected path pattern: {e}"
+        )));
+    }
+    if let Err(e) = commit_message::validate(&payload.commit_messages) {
+        return Err(ApiError::BadRequest(e));
+    }
+    let settings = ProjectSettings::upsert(&deployment.db().pool, project.id, &payload).await?;
+    // Apply a changed pool size without waiting for the next pass
+    deployment.workspace_pool().replenish_now();
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn get_project_upload_quota(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<UploadQuota>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let policy = ProjectSettings::find_by_project_id(pool, project.id)
+        .await?
+        .upload_policy;
+    let used_bytes = ProjectSettings::used_upload_bytes(pool, project.id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(UploadQuota {
+        used_bytes,
+        max_total_bytes: policy.max_total_bytes,
+        max_file_bytes: policy.max_file_bytes,
+        remaining_bytes: policy.remaining_bytes(used_bytes),
+    })))
+}
+
+/// Last measured size of each of the project's workspaces, largest first.
+pub async fn get_project_disk_usage(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceDiskUsage>>>, ApiError> {
+    let usage = WorkspaceDiskUsage::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+/// Dry run of the retention policy: the workspaces the janitor would remove on
+/// its next pass and how much space that frees.
+pub async fn preview_workspace_cleanup(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<CleanupPreview>>, ApiError> {
+    let preview = workspace_janitor::preview_cleanup(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(preview)))
+}
+
+pub async fn get_workspace_pool_status(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WorkspacePoolStatus>>, ApiError> {
+    let status = deployment
+        .workspace_pool()
+        .status(&deployment.db().pool, project.id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn get_shared_caches(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<SharedCacheUsage>>>, ApiError> {
+    let usage = deployment
+        .container()
+        .shared_cache_usage(project.id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+pub async fn clear_shared_cache(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_id, cache)): Path<(Uuid, SharedCache)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !deployment
+        .container()
+        .clear_shared_cache(project.id, cache)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "The cache is in use by a running execution".to_string(),
+        ));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+fn validate_env_name(name: &str) -> Result<(), ApiError> {
+    if is_valid_env_name(name) {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(format!(
+            "Invalid variable name '{name}': use letters, digits and underscores, not starting with a digit or VK_"
+        )))
+    }
+}
+
+pub async fn get_project_env_vars(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectEnvVar>>>, ApiError> {
+    let vars = ProjectEnvVar::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(vars)))
+}
+
+pub async fn set_project_env_var(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_id, name)): Path<(Uuid, String)>,
+    Json(payload): Json<SetProjectEnvVar>,
+) -> Result<ResponseJson<ApiResponse<ProjectEnvVar>>, ApiError> {
+    validate_env_name(&name)?;
+    let var =
+        ProjectEnvVar::upsert(&deployment.db().pool, project.id, &name, &payload.value).await?;
+    Ok(ResponseJson(ApiResponse::success(var)))
+}
+
+pub async fn delete_project_env_var(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_id, name)): Path<(Uuid, String)>,
+) -> Result<(StatusCode, ResponseJson<ApiResponse<()>>), ApiError> {
+    let rows_affected = ProjectEnvVar::delete(&deployment.db().pool, project.id, &name).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok((StatusCode::OK, ResponseJson(ApiResponse::success(()))))
+    }
+}
+
+/// List the project's secrets by name. Values are write-only.
+pub async fn get_project_secrets(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectSecret>>>, ApiError> {
+    let secrets = ProjectSecret::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(secrets)))
+}
+
+pub async fn set_project_secret(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_id, name)): Path<(Uuid, String)>,
+    Json(payload): Json<SetProjectEnvVar>,
+) -> Result<ResponseJson<ApiResponse<ProjectSecret>>, ApiError> {
+    validate_env_name(&name)?;
+    let encrypted = SecretCipher::load()?.encrypt(&payload.value)?;
+    let secret =
+        ProjectSecret::upsert(&deployment.db().pool, project.id, &name, &encrypted).await?;
+    Ok(ResponseJson(ApiResponse::success(secret)))
+}
+
+pub async fn delete_project_secret(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_id, name)): Path<(Uuid, String)>,
+) -> Result<(StatusCode, ResponseJson<ApiResponse<()>>), ApiError> {
+    let rows_affected = ProjectSecret::delete(&deployment.db().pool, project.id, &name).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok((StatusCode::OK, ResponseJson(ApiResponse::success(()))))
+    }
+}
+
+/// Middleware to load the Project for routes with a second path parameter.
+async fn load_project_with_param(
+    State(deployment): State<DeploymentImpl>,
+    Path((id, _param)): Path<(Uuid, String)>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let project = match Project::find_by_id(&deployment.db().pool, id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    request.extensions_mut().insert(project);
+    Ok(next.run(request).await)
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -607,13 +857,77 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/repositories",
             get(get_project_repositories).post(add_project_repository),
         )
+        .route(
+            "/settings",
+            get(get_project_settings).put(update_project_settings),
+        )
+        .route("/upload-quota", get(get_project_upload_quota))
+        .route("/disk-usage", get(get_project_disk_usage))
+        .route("/cleanup-preview", get(preview_workspace_cleanup))
+        .route("/workspace-pool", get(get_workspace_pool_status))
+        .route("/shared-caches", get(get_shared_caches))
+        .route("/env-vars", get(get_project_env_vars))
+        .route("/secrets", get(get_project_secrets))
+        .route(
+            "/labels",
+            get(labels::get_labels).post(labels::create_label),
+        )
+        .route("/epics", get(epics::get_epics).post(epics::create_epic))
+        .route(
+            "/columns",
+            get(board_columns::get_columns).post(board_columns::create_column),
+        )
+        .route("/columns/order", put(board_columns::reorder_columns))
+        .route("/columns/wip", get(board_columns::get_wip_status))
+        .route("/board/burndown", get(board_history::get_burndown))
+        .route(
+            "/board/cumulative-flow",
+            get(board_history::get_cumulative_flow),
+        )
+        .route("/time-report", get(time_tracking::get_time_report))
+        .route("/usage-report", get(usage::get_usage_report))
+        .route("/variant-stats", get(usage::get_variant_stats))
+        .route("/llm-proxy/log", get(llm_proxy::get_llm_proxy_log))
+        .route("/activity", get(activity::get_activity))
+        .route("/activity/stream", get(activity::stream_activity))
+        .route(
+            "/schedules",
+            get(task_schedules::get_schedules).post(task_schedules::create_schedule),
+        )
+        .route("/github-issues", get(github_issues::get_issue_links))
+        .route("/github-issues/import", post(github_issues::import_issues))
+        .route("/tasks/export", get(board_transfer::export_tasks))
+        .route("/tasks/plan", post(task_plans::create_plan_tasks))
+        .route("/tasks/plan/preview", post(task_plans::preview_plan))
+        .route(
+            "/tasks/import",
+            post(board_transfer::import_tasks)
+                .layer(DefaultBodyLimit::max(board_transfer::IMPORT_BODY_LIMIT)),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
         ));
 
+    // Routes with a second path parameter need their own loader
+    let project_item_router = Router::new()
+        .route("/shared-caches/{cache}", delete(clear_shared_cache))
+        .route(
+            "/env-vars/{name}",
+            put(set_project_env_var).delete(delete_project_env_var),
+        )
+        .route(
+            "/secrets/{name}",
+            put(set_project_secret).delete(delete_project_secret),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_project_with_param,
+        ));
+
     let projects_router = Router::new()
         .route("/", get(get_projects).post(create_project))
+        .route("/cleanups", get(get_project_cleanups))
         .route(
             "/{project_id}/repositories/{repo_id}",
             get(get_project_repository)
@@ -621,7 +935,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .delete(delete_project_repository),
         )
         .route("/stream/ws", get(stream_projects_ws))
-        .nest("/{id}", project_id_router);
+        .nest("/{id}", project_id_router.merge(project_item_router));
 
     Router::new().nest("/projects", projects_router).route(
         "/remote-projects/{remote_project_id}",
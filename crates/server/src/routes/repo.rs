@@ -4,10 +4,13 @@ use axum::{
     response::Json as ResponseJson,
     routing::{get, post},
 };
-use db::models::repo::Repo;
+use db::models::{repo::Repo, workspace::Workspace};
 use deployment::Deployment;
 use serde::Deserialize;
-use services::services::git::GitBranch;
+use services::services::{
+    git::GitBranch,
+    worktree_maintenance::{self, WorktreeMaintenance, WorktreeReport},
+};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
@@ -74,9 +77,86 @@ pub async fn get_repo_branches(
     Ok(ResponseJson(ApiResponse::success(branches)))
 }
 
+/// The repository's worktrees, what's wrong with any of them, and the
+/// attempt branches left behind
+pub async fn get_repo_worktrees(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorktreeReport>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+    let workspaces = Workspace::find_by_repo_id(pool, repo.id).await?;
+    let branch_prefix = deployment.config().read().await.git_branch_prefix.clone();
+    let report =
+        worktree_maintenance::inspect(deployment.git(), &repo, &workspaces, &branch_prefix)?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Drop missing worktrees and remove orphaned ones without uncommitted
+/// changes
+pub async fn prune_repo_worktrees(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorktreeMaintenance>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+    let workspaces = Workspace::find_by_repo_id(pool, repo.id).await?;
+    let branch_prefix = deployment.config().read().await.git_branch_prefix.clone();
+    let outcome =
+        worktree_maintenance::prune(deployment.git(), &repo, &workspaces, &branch_prefix).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "repo_worktrees_pruned",
+            serde_json::json!({
+                "repo_id": repo.id.to_string(),
+                "fixed": outcome.fixed.len(),
+                "skipped": outcome.skipped.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(outcome)))
+}
+
+/// Relink worktrees that lost track of the repository, or it of them
+pub async fn repair_repo_worktrees(
+    State(deployment): State<DeploymentImpl>,
+    Path(repo_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WorktreeMaintenance>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repo = deployment.repo().get_by_id(pool, repo_id).await?;
+    let workspaces = Workspace::find_by_repo_id(pool, repo.id).await?;
+    let branch_prefix = deployment.config().read().await.git_branch_prefix.clone();
+    let outcome =
+        worktree_maintenance::repair(deployment.git(), &repo, &workspaces, &branch_prefix)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "repo_worktrees_repaired",
+            serde_json::json!({
+                "repo_id": repo.id.to_string(),
+                "fixed": outcome.fixed.len(),
+                "skipped": outcome.skipped.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(outcome)))
+}
+
 pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/repos", post(register_repo))
         .route("/repos/init", post(init_repo))
         .route("/repos/{repo_id}/branches", get(get_repo_branches))
+        .route("/repos/{repo_id}/worktrees", get(get_repo_worktrees))
+        .route(
+            "/repos/{repo_id}/worktrees/prune",
+            post(prune_repo_worktrees),
+        )
+        .route(
+            "/repos/{repo_id}/worktrees/repair",
+            post(repair_repo_worktrees),
+        )
 }
@@ -0,0 +1,62 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    search::{self, SearchFilters, SearchHit},
+    task::TaskStatus,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SearchParams {
+    pub q: String,
+    pub project_id: Option<Uuid>,
+    pub status: Option<TaskStatus>,
+    /// Only match tasks and agent turns created at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only match tasks and agent turns created at or before this time
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+/// Search task titles, descriptions and what coding agents were asked and
+/// answered
+pub async fn search(
+    State(deployment): State<DeploymentImpl>,
+    Query(params): Query<SearchParams>,
+) -> Result<ResponseJson<ApiResponse<Vec<SearchHit>>>, ApiError> {
+    if let (Some(from), Some(to)) = (params.from, params.to)
+        && from > to
+    {
+        return Err(ApiError::BadRequest(
+            "'from' must not be later than 'to'".to_string(),
+        ));
+    }
+    let filters = SearchFilters {
+        project_id: params.project_id,
+        status: params.status,
+        from: params.from,
+        to: params.to,
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let hits = search::search(&deployment.db().pool, &params.q, &filters, limit as i64).await?;
+    Ok(ResponseJson(ApiResponse::success(hits)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/search", get(search))
+}
@@ -22,7 +22,7 @@ use executors::{
     profile::ExecutorProfileId,
 };
 use serde::Deserialize;
-use services::services::container::ContainerService;
+use services::services::{attempt_budget, container::ContainerService};
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
 use utils::response::ApiResponse;
@@ -136,6 +136,14 @@ pub async fn follow_up(
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
+    let budget = attempt_budget::attempt_budget(pool, workspace.id, project.id, 0).await?;
+    if let Some(limit) = budget.reached {
+        return Err(ApiError::BadRequest(format!(
+            "The attempt reached its limit of {}; extend its budget to continue",
+            attempt_budget::describe_limit(limit, &budget.limits)
+        )));
+    }
+
     // If retry settings provided, perform replace-logic before proceeding
     if let Some(proc_id) = payload.retry_process_id {
         // Validate process belongs to this session
@@ -193,6 +201,7 @@ pub async fn follow_up(
             session_id: agent_session_id,
             executor_profile_id: executor_profile_id.clone(),
             working_dir: working_dir.clone(),
+            prompt_additions: None,
         })
     } else {
         ExecutorActionType::CodingAgentInitialRequest(
@@ -200,6 +209,7 @@ pub async fn follow_up(
                 prompt,
                 executor_profile_id: executor_profile_id.clone(),
                 working_dir,
+                prompt_additions: None,
             },
         )
     };
@@ -1,8 +1,22 @@
+pub mod cherry_pick;
+pub mod ci_checks;
 pub mod codex_setup;
+pub mod conflicts;
 pub mod cursor_setup;
+pub mod dry_runs;
+pub mod exec;
+pub mod files;
 pub mod gh_cli_setup;
+pub mod health;
 pub mod images;
+pub mod partial_merge;
+pub mod path_guard;
 pub mod pr;
+pub mod resume;
+pub mod review_tasks;
+pub mod setup;
+pub mod stack;
+pub mod terminals;
 pub mod util;
 
 use std::{
@@ -22,14 +36,27 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    agent_review::AgentReview,
+    branch_sync_status::BranchSyncStatus,
+    command_decision::CommandDecision,
     execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
-    merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+    execution_process_retry::ExecutionProcessRetry,
+    hook_run::{HookEvent, HookRun},
+    lint_run::LintRun,
+    merge::{Merge, MergeStatus, MergeStrategy, PrMerge, PullRequestInfo},
+    merge_selection::MergeSelection,
     project_repo::ProjectRepo,
+    project_settings::{CodeHostProvider, ProjectSettings, ResourceLimits},
+    queued_attempt::QueuedAttempt,
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{Task, TaskRelationships, TaskStatus},
+    task_dependency::TaskDependency,
+    test_run::TestRun,
     workspace::{CreateWorkspace, Workspace, WorkspaceError},
+    workspace_budget::WorkspaceBudget,
     workspace_repo::{CreateWorkspaceRepo, RepoWithTargetBranch, WorkspaceRepo},
+    workspace_stack::WorkspaceStack,
 };
 use deployment::Deployment;
 use executors::{
@@ -43,18 +70,24 @@ use executors::{
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService,
-    git::{ConflictOp, GitCliError, GitServiceError},
+    attempt_budget::{self, AttemptBudget},
+    commit_message,
+    container::{ContainerService, ResourceUsage},
+    git::{ConflictOp, GitCliError, GitServiceError, SignatureStatus},
     github::GitHubService,
+    lifecycle_hooks::{self, HookDetails},
+    task_scope::{self, ScopeCheck},
 };
-use sqlx::Error as SqlxError;
+use sqlx::{Error as SqlxError, SqlitePool};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_workspace_middleware,
-    routes::task_attempts::gh_cli_setup::GhCliSetupError,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_workspace_middleware,
+    routes::{attempt_queue, task_attempts::gh_cli_setup::GhCliSetupError, usage},
 };
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -67,6 +100,9 @@ pub struct RebaseTaskAttemptRequest {
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct AbortConflictsRequest {
     pub repo_id: Uuid,
+    /// Target branch of a cherry-pick of the attempt's commits to abort, in
+    /// place of the attempt's own operation
+    pub cherry_pick_target: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -106,8 +142,13 @@ pub async fn get_task_attempt(
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
 pub struct CreateTaskAttemptBody {
     pub task_id: Uuid,
-    pub executor_profile_id: ExecutorProfileId,
+    /// Without one the task's own profile is used, or else the configured
+    /// default
+    pub executor_profile_id: Option<ExecutorProfileId>,
     pub repos: Vec<WorkspaceRepoInput>,
+    /// Attempt whose branch this one is based on, in place of the repos'
+    /// target branches
+    pub stacked_on_workspace_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
@@ -129,8 +170,6 @@ pub async fn create_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTaskAttemptBody>,
 ) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
-    let executor_profile_id = payload.executor_profile_id.clone();
-
     if payload.repos.is_empty() {
         return Err(ApiError::BadRequest(
             "At least one repository is required".to_string(),
@@ -138,9 +177,49 @@ pub async fn create_task_attempt(
     }
 
     let pool = &deployment.db().pool;
-    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
+    let task = Task::find_by_id(pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
+    let repos = match payload.stacked_on_workspace_id {
+        Some(parent_id) => stack::stacked_repos(pool, &task, parent_id, &payload.repos).await?,
+        None => payload.repos,
+    };
+    let workspace = create_and_start_attempt(
+        &deployment,
+        &task,
+        payload.executor_profile_id.as_ref(),
+        &repos,
+    )
+    .await?;
+    if let Some(parent_id) = payload.stacked_on_workspace_id {
+        WorkspaceStack::create(pool, workspace.id, parent_id).await?;
+    }
+
+    tracing::info!("Created attempt for task {}", task.id);
+
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+/// Create an attempt for the task and start its agent. Failing to start the
+/// agent still leaves the attempt in place.
+///
+/// Without an explicit `executor_profile_id` the attempt uses the profile
+/// pinned on the task, or else the configured default.
+pub async fn create_and_start_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    executor_profile_id: Option<&ExecutorProfileId>,
+    repos: &[WorkspaceRepoInput],
+) -> Result<Workspace, ApiError> {
+    let pool = &deployment.db().pool;
+    let executor_profile_id = match executor_profile_id.or(task.executor_profile_id.as_deref()) {
+        Some(executor_profile_id) => executor_profile_id.clone(),
+        None => deployment.config().read().await.executor_profile.clone(),
+    };
+
+    // Agents start attempts this way too, so they can't jump ahead of the tasks
+    // this one waits for
+    ensure_not_blocked(pool, task).await?;
 
     let project = task
         .parent_project(pool)
@@ -162,16 +241,16 @@ pub async fn create_task_attempt(
     let workspace = Workspace::create(
         pool,
         &CreateWorkspace {
-            branch: git_branch_name.clone(),
+            branch: git_branch_name,
             agent_working_dir,
+            executor_profile_id: Some(executor_profile_id.clone()),
         },
         attempt_id,
-        payload.task_id,
+        task.id,
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
+    let workspace_repos: Vec<CreateWorkspaceRepo> = repos
         .iter()
         .map(|r| CreateWorkspaceRepo {
             repo_id: r.repo_id,
@@ -182,7 +261,7 @@ pub async fn create_task_attempt(
     WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
     if let Err(err) = deployment
         .container()
-        .start_workspace(&workspace, executor_profile_id.clone())
+        .start_or_queue_workspace(&workspace, executor_profile_id.clone())
         .await
     {
         tracing::error!("Failed to start task attempt: {}", err);
@@ -196,14 +275,29 @@ pub async fn create_task_attempt(
                 "variant": &executor_profile_id.variant,
                 "executor": &executor_profile_id.executor,
                 "workspace_id": workspace.id.to_string(),
-                "repository_count": payload.repos.len(),
+                "repository_count": repos.len(),
             }),
         )
         .await;
 
-    tracing::info!("Created attempt for task {}", task.id);
+    Ok(workspace)
+}
 
-    Ok(ResponseJson(ApiResponse::success(workspace)))
+/// Conflict if a task this one depends on isn't done yet
+pub(crate) async fn ensure_not_blocked(pool: &SqlitePool, task: &Task) -> Result<(), ApiError> {
+    let unfinished: Vec<String> = TaskDependency::find_blockers(pool, task.id)
+        .await?
+        .into_iter()
+        .filter(|blocker| blocker.status != TaskStatus::Done)
+        .map(|blocker| format!("'{}'", blocker.title))
+        .collect();
+    if !unfinished.is_empty() {
+        return Err(ApiError::Conflict(format!(
+            "Task is blocked by {}",
+            unfinished.join(", ")
+        )));
+    }
+    Ok(())
 }
 
 #[axum::debug_handler]
@@ -305,6 +399,9 @@ async fn handle_task_attempt_diff_ws(
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct MergeTaskAttemptRequest {
     pub repo_id: Uuid,
+    /// Merge only the files and hunks of this selection, as a single commit,
+    /// leaving the rest of the changes on the attempt's branch
+    pub selection_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -319,6 +416,7 @@ pub async fn merge_task_attempt(
     Json(request): Json<MergeTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
     let pool = &deployment.db().pool;
+    path_guard::ensure_not_guarded(pool, workspace.id).await?;
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
@@ -334,46 +432,96 @@ pub async fn merge_task_attempt(
         .ensure_container_exists(&workspace)
         .await?;
     let workspace_path = Path::new(&container_ref);
-    let worktree_path = workspace_path.join(repo.name);
+    let worktree_path = workspace_path.join(&repo.name);
 
     let task = workspace
         .parent_task(pool)
         .await?
         .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
-    let task_uuid_str = task.id.to_string();
-    let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
-
-    let mut commit_message = format!("{} (vibe-kanban {})", task.title, first_uuid_section);
-
-    // Add description on next line if it exists
-    if let Some(description) = &task.description
-        && !description.trim().is_empty()
-    {
-        commit_message.push_str("\n\n");
-        commit_message.push_str(description);
-    }
+    let selection = match request.selection_id {
+        Some(selection_id) => Some(
+            partial_merge::selection_patch(&deployment, &workspace, &repo, selection_id).await?,
+        ),
+        None => None,
+    };
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+    ci_checks::ensure_checks_green(&deployment, &workspace, &repo, &settings).await?;
+    // A partial merge is a single commit of the selected changes
+    let strategy = match selection {
+        Some(_) => MergeStrategy::Squash,
+        None => settings.merge_strategy,
+    };
+    // Fast-forwards and rebases keep the branch's own commits
+    let commit_message = if strategy.makes_commit() {
+        commit_message::merge_message(&settings.commit_messages, &task, &workspace.branch).map_err(
+            |e| {
+                ApiError::BadRequest(format!(
+                    "The merge commit's message isn't a conventional commit: {e}"
+                ))
+            },
+        )?
+    } else {
+        String::new()
+    };
 
-    let merge_commit_id = deployment.git().merge_changes(
-        &repo.path,
-        &worktree_path,
-        &workspace.branch,
-        &workspace_repo.target_branch,
-        &commit_message,
-    )?;
+    let signing = deployment.config().read().await.commit_signing.clone();
+    let merge_commit_id = match &selection {
+        Some((_, patch)) => deployment.git().merge_partial(
+            &repo.path,
+            &worktree_path,
+            &workspace.branch,
+            &workspace_repo.target_branch,
+            patch,
+            &commit_message,
+            &signing,
+        )?,
+        None => deployment.git().merge_changes_with_strategy(
+            &repo.path,
+            &worktree_path,
+            &workspace.branch,
+            &workspace_repo.target_branch,
+            &commit_message,
+            strategy,
+            &signing,
+        )?,
+    };
 
-    Merge::create_direct(
+    let merge = Merge::create_direct(
         pool,
         workspace.id,
         workspace_repo.repo_id,
         &workspace_repo.target_branch,
         &merge_commit_id,
+        strategy,
     )
     .await?;
-    Task::update_status(pool, task.id, TaskStatus::Done).await?;
+    // The rest of a partial merge's changes are still to be merged
+    let completed = match &selection {
+        Some((selection, _)) => {
+            MergeSelection::set_merge_id(pool, selection.id, merge.id).await?;
+            false
+        }
+        None => true,
+    };
+    if completed {
+        Task::update_status(pool, task.id, TaskStatus::Done).await?;
+    }
+    lifecycle_hooks::spawn_hooks(
+        pool.clone(),
+        workspace.clone(),
+        HookEvent::AfterMerge,
+        HookDetails {
+            merge_commit: Some(merge_commit_id),
+            ..Default::default()
+        },
+    );
 
     // Stop any running dev servers for this workspace
-    let dev_servers =
-        ExecutionProcess::find_running_dev_servers_by_workspace(pool, workspace.id).await?;
+    let dev_servers = if completed {
+        ExecutionProcess::find_running_dev_servers_by_workspace(pool, workspace.id).await?
+    } else {
+        Vec::new()
+    };
 
     for dev_server in dev_servers {
         tracing::info!(
@@ -418,6 +566,8 @@ pub async fn merge_task_attempt(
             serde_json::json!({
                 "task_id": task.id.to_string(),
                 "workspace_id": workspace.id.to_string(),
+                "merge_strategy": strategy,
+                "partial": !completed,
             }),
         )
         .await;
@@ -431,9 +581,14 @@ pub async fn push_task_attempt_branch(
     Json(request): Json<PushTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
     let pool = &deployment.db().pool;
+    path_guard::ensure_not_guarded(pool, workspace.id).await?;
 
-    let github_service = GitHubService::new()?;
-    github_service.check_token().await?;
+    let (project_id, provider) = pr::code_host(pool, &workspace).await?;
+    if provider == CodeHostProvider::Github {
+        let github_service = GitHubService::new()?;
+        github_service.check_token().await?;
+    }
+    let auth_header = pr::push_auth_header(pool, project_id, provider).await?;
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
@@ -451,10 +606,12 @@ pub async fn push_task_attempt_branch(
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
-    match deployment
-        .git()
-        .push_to_github(&worktree_path, &workspace.branch, false)
-    {
+    match deployment.git().push_to_remote(
+        &worktree_path,
+        &workspace.branch,
+        false,
+        auth_header.as_deref(),
+    ) {
         Ok(_) => Ok(ResponseJson(ApiResponse::success(()))),
         Err(GitServiceError::GitCLI(GitCliError::PushRejected(_))) => Ok(ResponseJson(
             ApiResponse::error_with_data(PushError::ForcePushRequired),
@@ -469,9 +626,14 @@ pub async fn force_push_task_attempt_branch(
     Json(request): Json<PushTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<(), PushError>>, ApiError> {
     let pool = &deployment.db().pool;
+    path_guard::ensure_not_guarded(pool, workspace.id).await?;
 
-    let github_service = GitHubService::new()?;
-    github_service.check_token().await?;
+    let (project_id, provider) = pr::code_host(pool, &workspace).await?;
+    if provider == CodeHostProvider::Github {
+        let github_service = GitHubService::new()?;
+        github_service.check_token().await?;
+    }
+    let auth_header = pr::push_auth_header(pool, project_id, provider).await?;
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
@@ -489,9 +651,12 @@ pub async fn force_push_task_attempt_branch(
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
-    deployment
-        .git()
-        .push_to_github(&worktree_path, &workspace.branch, true)?;
+    deployment.git().push_to_remote(
+        &worktree_path,
+        &workspace.branch,
+        true,
+        auth_header.as_deref(),
+    )?;
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
@@ -602,6 +767,10 @@ pub struct BranchStatus {
     pub conflict_op: Option<ConflictOp>,
     /// List of files currently in conflicted (unmerged) state
     pub conflicted_files: Vec<String>,
+    /// Signature check of the branch's latest commit
+    pub head_signature: Option<SignatureStatus>,
+    /// Commits on the branch but not the target that aren't signed
+    pub unsigned_commits: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
@@ -668,6 +837,12 @@ pub async fn get_task_attempt_branch_status(
             (in_rebase, conflicts, op)
         };
 
+        let head_signature = deployment.git().get_head_signature(&worktree_path).ok();
+        let unsigned_commits = deployment
+            .git()
+            .count_unsigned_commits(&worktree_path, &target_branch)
+            .ok();
+
         let (uncommitted_count, untracked_count) =
             match deployment.git().get_worktree_change_counts(&worktree_path) {
                 Ok((a, b)) => (Some(a), Some(b)),
@@ -736,6 +911,8 @@ pub async fn get_task_attempt_branch_status(
                 is_rebase_in_progress,
                 conflict_op,
                 conflicted_files,
+                head_signature,
+                unsigned_commits,
             },
         });
     }
@@ -1026,6 +1203,11 @@ pub async fn rebase_task_attempt(
     let workspace_path = Path::new(&container_ref);
     let worktree_path = workspace_path.join(&repo.name);
 
+    let old_head = deployment
+        .git()
+        .get_head_info(&worktree_path)
+        .ok()
+        .map(|h| h.oid);
     let result = deployment.git().rebase_branch(
         &repo.path,
         &worktree_path,
@@ -1055,12 +1237,20 @@ pub async fn rebase_task_attempt(
         };
     }
 
+    let restacked = match old_head {
+        Some(old_head) => {
+            stack::restack_children(&deployment, &workspace, &repo, &old_head).await?
+        }
+        None => 0,
+    };
+
     deployment
         .track_if_analytics_allowed(
             "task_attempt_rebased",
             serde_json::json!({
                 "workspace_id": workspace.id.to_string(),
                 "repo_id": payload.repo_id.to_string(),
+                "restacked_children": restacked,
             }),
         )
         .await;
@@ -1085,8 +1275,13 @@ pub async fn abort_conflicts_task_attempt(
         .ensure_container_exists(&workspace)
         .await?;
     let workspace_path = Path::new(&container_ref);
-    let worktree_path = workspace_path.join(&repo.name);
 
+    if let Some(target) = &payload.cherry_pick_target {
+        cherry_pick::abort_cherry_pick(&deployment, &repo, workspace_path, target)?;
+        return Ok(ResponseJson(ApiResponse::success(())));
+    }
+
+    let worktree_path = workspace_path.join(&repo.name);
     deployment.git().abort_conflicts(&worktree_path)?;
 
     Ok(ResponseJson(ApiResponse::success(())))
@@ -1243,6 +1438,8 @@ pub async fn stop_task_attempt_execution(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    // An attempt still waiting for a slot is taken out of the queue
+    QueuedAttempt::delete(&deployment.db().pool, workspace.id).await?;
     deployment.container().try_stop(&workspace, false).await;
 
     deployment
@@ -1482,6 +1679,303 @@ pub async fn get_task_attempt_repos(
     Ok(ResponseJson(ApiResponse::success(repos)))
 }
 
+/// Add another of the project's repositories to the attempt. It is checked out
+/// on the attempt's branch next to the attempt's other repositories.
+pub async fn add_task_attempt_repo(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<WorkspaceRepoInput>,
+) -> Result<ResponseJson<ApiResponse<Vec<RepoWithTargetBranch>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    if ProjectRepo::find_by_project_and_repo(pool, task.project_id, repo.id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "'{}' is not a repository of this project",
+            repo.name
+        )));
+    }
+    // Repositories are checked out in directories of their name
+    let current = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+    if current
+        .iter()
+        .any(|other| other.id == repo.id || other.name == repo.name)
+    {
+        return Err(ApiError::Conflict(format!(
+            "The attempt already has a repository named '{}'",
+            repo.name
+        )));
+    }
+    if !deployment
+        .git()
+        .check_branch_exists(&repo.path, &payload.target_branch)?
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Branch '{}' does not exist in repository '{}'",
+            payload.target_branch, repo.name
+        )));
+    }
+
+    deployment
+        .container()
+        .add_workspace_repo(&workspace, &repo, &payload.target_branch)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_repo_added",
+            serde_json::json!({
+                "repo_id": repo.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "repository_count": current.len() + 1,
+            }),
+        )
+        .await;
+
+    let repos =
+        WorkspaceRepo::find_repos_with_target_branch_for_workspace(pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(repos)))
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct PreviewPort {
+    pub port: u16,
+    /// Path of the preview proxy for this port
+    pub url: String,
+}
+
+/// Ports that processes in the attempt's workspace are listening on, each with
+/// a preview URL served by the preview proxy
+pub async fn get_preview_ports(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<PreviewPort>>>, ApiError> {
+    let ports = deployment
+        .container()
+        .preview_targets(&workspace)
+        .await?
+        .into_iter()
+        .map(|target| PreviewPort {
+            port: target.port,
+            url: format!("/api/preview/{}/{}/", workspace.id, target.port),
+        })
+        .collect();
+    Ok(ResponseJson(ApiResponse::success(ports)))
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptMetrics {
+    pub limits: ResourceLimits,
+    /// Absent when usage can't be measured for the project's backend
+    pub usage: Option<ResourceUsage>,
+}
+
+pub async fn get_attempt_metrics(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptMetrics>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let limits = ProjectSettings::find_by_project_id(pool, task.project_id)
+        .await?
+        .resource_limits;
+    let usage = deployment.container().resource_usage(&workspace).await?;
+    Ok(ResponseJson(ApiResponse::success(AttemptMetrics {
+        limits,
+        usage,
+    })))
+}
+
+/// What the attempt changed outside its task's scope
+pub async fn get_attempt_scope_check(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ScopeCheck>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let check = task_scope::check_attempt(pool, deployment.git(), &task, &workspace).await?;
+    Ok(ResponseJson(ApiResponse::success(check)))
+}
+
+/// Where the attempt stands against the project's attempt limits. Tool calls
+/// of a run still going are counted once it ends.
+pub async fn get_attempt_budget(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptBudget>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let budget = attempt_budget::attempt_budget(pool, workspace.id, task.project_id, 0).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+/// Grant the attempt the project's limits once more, so its coding agent can
+/// carry on after being stopped at one
+pub async fn extend_attempt_budget(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptBudget>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let limits = ProjectSettings::find_by_project_id(pool, task.project_id)
+        .await?
+        .attempt_limits;
+    if limits.is_empty() {
+        return Err(ApiError::BadRequest(
+            "The project sets no attempt limits".to_string(),
+        ));
+    }
+    let extended = WorkspaceBudget::extend(pool, workspace.id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "attempt_budget_extended",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "extensions": extended.extensions,
+            }),
+        )
+        .await;
+
+    let budget = attempt_budget::attempt_budget(pool, workspace.id, task.project_id, 0).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+/// Answers given to the attempt's agents when they asked to run shell
+/// commands, newest first
+pub async fn get_command_decisions(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<CommandDecision>>>, ApiError> {
+    let decisions =
+        CommandDecision::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(decisions)))
+}
+
+/// Test results read from the attempt's runs, newest first
+pub async fn get_attempt_test_results(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TestRun>>>, ApiError> {
+    let test_runs = TestRun::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(test_runs)))
+}
+
+/// The project's lint checks as run on the attempt, newest first
+pub async fn get_attempt_lint_runs(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<LintRun>>>, ApiError> {
+    let lint_runs = LintRun::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(lint_runs)))
+}
+
+/// The last periodic check of each of the attempt's repositories against its
+/// target branch: how far behind it is and whether conflicts are present
+pub async fn get_attempt_branch_sync(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<BranchSyncStatus>>>, ApiError> {
+    let statuses =
+        BranchSyncStatus::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(statuses)))
+}
+
+pub async fn get_attempt_hook_runs(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<HookRun>>>, ApiError> {
+    let hook_runs = HookRun::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(hook_runs)))
+}
+
+pub async fn get_attempt_agent_reviews(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<AgentReview>>>, ApiError> {
+    let reviews = AgentReview::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(reviews)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct InterjectRequest {
+    pub message: String,
+}
+
+/// Sends a message to the attempt's running coding agent, which reads it
+/// before its next step instead of after the run
+pub async fn interject(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<InterjectRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let message = payload.message.trim();
+    if message.is_empty() {
+        return Err(ApiError::BadRequest("The message is empty".to_string()));
+    }
+    let running = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+        &deployment.db().pool,
+        workspace.id,
+        &ExecutionProcessRunReason::CodingAgent,
+    )
+    .await?
+    .filter(|process| process.status == ExecutionProcessStatus::Running)
+    .ok_or_else(|| ApiError::Conflict("No coding agent is running".to_string()))?;
+
+    if !deployment
+        .container()
+        .send_interjection(&running.id, message.to_string())
+        .await
+    {
+        return Err(ApiError::BadRequest(
+            "The running coding agent doesn't take messages until it finishes".to_string(),
+        ));
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "attempt_interjection_sent",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "execution_process_id": running.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Coding agent runs the retry policy started in place of failed ones
+pub async fn get_attempt_retries(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcessRetry>>>, ApiError> {
+    let retries =
+        ExecutionProcessRetry::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(retries)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
         .route("/", get(get_task_attempt))
@@ -1489,23 +1983,68 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/gh-cli-setup", post(gh_cli_setup_handler))
         .route("/start-dev-server", post(start_dev_server))
         .route("/run-setup-script", post(run_setup_script))
+        .route("/setup", get(setup::get_setup))
+        .route("/setup/retry", post(setup::retry_setup))
+        .route("/setup/override", post(setup::start_agent_anyway))
         .route("/run-cleanup-script", post(run_cleanup_script))
         .route("/branch-status", get(get_task_attempt_branch_status))
+        .route("/branch-sync", get(get_attempt_branch_sync))
+        .route("/ci-checks", get(ci_checks::get_ci_checks))
         .route("/diff/ws", get(stream_task_attempt_diff_ws))
         .route("/merge", post(merge_task_attempt))
         .route("/push", post(push_task_attempt_branch))
         .route("/push/force", post(force_push_task_attempt_branch))
         .route("/rebase", post(rebase_task_attempt))
+        .route("/conflicts", get(conflicts::get_conflicts))
+        .route("/conflicts/resolve", post(conflicts::resolve_conflict))
+        .route("/conflicts/continue", post(conflicts::continue_rebase))
+        .route(
+            "/conflicts/agent",
+            post(conflicts::resolve_conflicts_with_agent),
+        )
         .route("/conflicts/abort", post(abort_conflicts_task_attempt))
+        .route("/cherry-pick", post(cherry_pick::cherry_pick_task_attempt))
+        .route(
+            "/merge-selections",
+            get(partial_merge::get_merge_selections).post(partial_merge::create_merge_selection),
+        )
+        .route(
+            "/merge-selections/changes",
+            get(partial_merge::get_selectable_changes),
+        )
+        .route("/stack", get(stack::get_attempt_stack))
         .route("/pr", post(pr::create_github_pr))
         .route("/pr/attach", post(pr::attach_existing_pr))
         .route("/pr/comments", get(pr::get_pr_comments))
+        .route("/pr/merge", post(pr::merge_pr))
         .route("/open-editor", post(open_task_attempt_in_editor))
         .route("/children", get(get_task_attempt_children))
+        .route("/review-tasks", post(review_tasks::create_review_tasks))
         .route("/stop", post(stop_task_attempt_execution))
         .route("/change-target-branch", post(change_target_branch))
         .route("/rename-branch", post(rename_branch))
-        .route("/repos", get(get_task_attempt_repos))
+        .route(
+            "/repos",
+            get(get_task_attempt_repos).post(add_task_attempt_repo),
+        )
+        .route("/exec", post(exec::exec_in_workspace))
+        .route("/preview-ports", get(get_preview_ports))
+        .route("/metrics", get(get_attempt_metrics))
+        .route("/scope", get(get_attempt_scope_check))
+        .route("/usage", get(usage::get_attempt_usage))
+        .route("/retries", get(get_attempt_retries))
+        .route("/budget", get(get_attempt_budget))
+        .route("/budget/extend", post(extend_attempt_budget))
+        .route("/command-decisions", get(get_command_decisions))
+        .route("/interruption", get(resume::get_interruption))
+        .route("/resume", post(resume::resume_attempt))
+        .route("/queue", get(attempt_queue::get_attempt_queue_position))
+        .route("/test-results", get(get_attempt_test_results))
+        .route("/lint-runs", get(get_attempt_lint_runs))
+        .route("/agent-reviews", get(get_attempt_agent_reviews))
+        .route("/hook-runs", get(get_attempt_hook_runs))
+        .route("/interject", post(interject))
+        .route("/workspace/health", get(health::get_workspace_health))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_workspace_middleware,
@@ -1514,7 +2053,11 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempts_router = Router::new()
         .route("/", get(get_task_attempts).post(create_task_attempt))
         .nest("/{id}", task_attempt_id_router)
-        .nest("/{id}/images", images::router(deployment));
+        .nest("/{id}/images", images::router(deployment))
+        .nest("/{id}/files", files::router(deployment))
+        .nest("/{id}/dry-runs", dry_runs::router(deployment))
+        .nest("/{id}/path-guard", path_guard::router(deployment))
+        .nest("/{id}/terminals", terminals::router(deployment));
 
     Router::new().nest("/task-attempts", task_attempts_router)
 }
@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    merge::Merge,
+    repo::{Repo, RepoError},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    cherry_pick::{self, CherryPickStatus},
+    container::ContainerService,
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::task_attempts::path_guard};
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct CherryPickRequest {
+    pub repo_id: Uuid,
+    /// Local branch the attempt's commits are picked onto
+    pub target_branch: String,
+}
+
+/// Cherry-pick the attempt's commits onto another branch. Conflicts are
+/// resolved through the conflicts endpoints, given the target branch.
+pub async fn cherry_pick_task_attempt(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CherryPickRequest>,
+) -> Result<ResponseJson<ApiResponse<CherryPickStatus>>, ApiError> {
+    let pool = &deployment.db().pool;
+    path_guard::ensure_not_guarded(pool, workspace.id).await?;
+
+    let target_branch = payload.target_branch.trim();
+    if !deployment.git().is_branch_name_valid(target_branch) {
+        return Err(ApiError::BadRequest(format!(
+            "'{target_branch}' isn't a valid branch name"
+        )));
+    }
+    if target_branch == workspace.branch {
+        return Err(ApiError::BadRequest(
+            "Cherry-pick onto a branch other than the attempt's own".to_string(),
+        ));
+    }
+
+    let workspace_repo =
+        WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, payload.repo_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, payload.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let worktree_path =
+        cherry_pick::worktree_path(Path::new(&container_ref), &repo.name, target_branch);
+    if worktree_path.exists() {
+        return Err(ApiError::Conflict(format!(
+            "A cherry-pick onto '{target_branch}' is already in progress; continue or abort it"
+        )));
+    }
+
+    let direct_merge = Merge::find_by_workspace_and_repo_id(pool, workspace.id, repo.id)
+        .await?
+        .into_iter()
+        .find_map(|merge| match merge {
+            Merge::Direct(direct) => Some(direct),
+            Merge::Pr(_) => None,
+        });
+    let commits = cherry_pick::attempt_commits(
+        deployment.git(),
+        &repo.path,
+        &workspace.branch,
+        &workspace_repo.target_branch,
+        direct_merge.as_ref(),
+    )?;
+    if commits.is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "The attempt has no commits to cherry-pick onto '{target_branch}'"
+        )));
+    }
+
+    let status = cherry_pick::start(
+        deployment.git(),
+        &repo.path,
+        &worktree_path,
+        target_branch,
+        &commits,
+    )?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_cherry_picked",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "repo_id": repo.id.to_string(),
+                "commits": commits.len(),
+                "conflicted": status.target_commit.is_none(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+/// Abort the cherry-pick onto the target branch and remove its worktree
+pub fn abort_cherry_pick(
+    deployment: &DeploymentImpl,
+    repo: &Repo,
+    attempt_dir: &Path,
+    target_branch: &str,
+) -> Result<(), ApiError> {
+    let worktree_path = cherry_pick::worktree_path(attempt_dir, &repo.name, target_branch);
+    if !worktree_path.exists() {
+        return Err(ApiError::BadRequest(format!(
+            "No cherry-pick onto '{target_branch}' is in progress"
+        )));
+    }
+    cherry_pick::abort(deployment.git(), &repo.path, &worktree_path)?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    ci_check::CiCheck,
+    merge::{Merge, MergeStatus},
+    project_settings::{CodeHostProvider, ProjectSettings},
+    repo::Repo,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use services::services::ci_checks;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Refuse to merge the attempt's repository, here or through its pull
+/// request, until every CI check of the branch's tip has passed, when the
+/// project asks for that
+pub async fn ensure_checks_green(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo: &Repo,
+    settings: &ProjectSettings,
+) -> Result<(), ApiError> {
+    if !settings.require_green_checks {
+        return Ok(());
+    }
+    if matches!(
+        settings.code_host.provider,
+        CodeHostProvider::Bitbucket | CodeHostProvider::AzureDevops
+    ) {
+        return Err(ApiError::Conflict(
+            "CI checks are only polled from GitHub and GitLab; stop waiting for them in the project's settings"
+                .to_string(),
+        ));
+    }
+    let pool = &deployment.db().pool;
+    let pr_merge = Merge::find_by_workspace_and_repo_id(pool, workspace.id, repo.id)
+        .await?
+        .into_iter()
+        .find_map(|merge| match merge {
+            Merge::Pr(pr_merge) if matches!(pr_merge.pr_info.status, MergeStatus::Open) => {
+                Some(pr_merge)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| {
+            ApiError::Conflict(
+                "The project waits for CI checks before merging; open a pull request for them to run on"
+                    .to_string(),
+            )
+        })?;
+    let checks = CiCheck::find_by_merge_id(pool, pr_merge.id).await?;
+    let head_commit = deployment
+        .git()
+        .get_branch_oid(&repo.path, &workspace.branch)?;
+    match ci_checks::unmet_reason(&checks, &head_commit) {
+        Some(reason) => Err(ApiError::Conflict(reason)),
+        None => Ok(()),
+    }
+}
+
+/// The CI checks of the attempt's pull requests, as last polled
+pub async fn get_ci_checks(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<CiCheck>>>, ApiError> {
+    let checks = CiCheck::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(checks)))
+}
@@ -0,0 +1,320 @@
+use std::path::{Path, PathBuf};
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    repo::{Repo, RepoError},
+    session::{CreateSession, Session},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::actions::{
+    ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+    coding_agent_initial::CodingAgentInitialRequest,
+};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    cherry_pick,
+    container::ContainerService,
+    git::ConflictOp,
+    rebase_conflicts::{self, ConflictDetails, ConflictResolution},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::task_attempts::stack};
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct ConflictsQuery {
+    pub repo_id: Uuid,
+    /// Target branch of a cherry-pick of the attempt's commits, for its
+    /// conflicts rather than the attempt's own
+    pub cherry_pick_target: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct ResolveConflictRequest {
+    pub repo_id: Uuid,
+    pub path: String,
+    pub resolution: ConflictResolution,
+    pub cherry_pick_target: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct ContinueRebaseRequest {
+    pub repo_id: Uuid,
+    pub cherry_pick_target: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct ResolveConflictsWithAgentRequest {
+    pub repo_id: Uuid,
+}
+
+/// Worktree of one of the attempt's repositories, or of its cherry-pick onto
+/// the target branch, with the repository
+async fn worktree(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo_id: Uuid,
+    cherry_pick_target: Option<&str>,
+) -> Result<(Repo, WorkspaceRepo, PathBuf), ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace_repo = WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(workspace)
+        .await?;
+    let worktree_path = match cherry_pick_target {
+        Some(target) => {
+            let path = cherry_pick::worktree_path(Path::new(&container_ref), &repo.name, target);
+            if !path.exists() {
+                return Err(ApiError::BadRequest(format!(
+                    "No cherry-pick onto '{target}' is in progress"
+                )));
+            }
+            path
+        }
+        None => PathBuf::from(container_ref).join(&repo.name),
+    };
+    Ok((repo, workspace_repo, worktree_path))
+}
+
+/// The files the repository's rebase, or other operation, stopped on
+pub async fn get_conflicts(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ConflictsQuery>,
+) -> Result<ResponseJson<ApiResponse<ConflictDetails>>, ApiError> {
+    let (_, _, worktree_path) = worktree(
+        &deployment,
+        &workspace,
+        query.repo_id,
+        query.cherry_pick_target.as_deref(),
+    )
+    .await?;
+    let details = rebase_conflicts::conflict_details(deployment.git(), &worktree_path)?;
+    Ok(ResponseJson(ApiResponse::success(details)))
+}
+
+/// Resolve one file of a conflicted rebase and stage it
+pub async fn resolve_conflict(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ResolveConflictRequest>,
+) -> Result<ResponseJson<ApiResponse<ConflictDetails>>, ApiError> {
+    let (_, _, worktree_path) = worktree(
+        &deployment,
+        &workspace,
+        payload.repo_id,
+        payload.cherry_pick_target.as_deref(),
+    )
+    .await?;
+    let conflicted = deployment.git().get_conflicted_files(&worktree_path)?;
+    if !conflicted.contains(&payload.path) {
+        return Err(ApiError::BadRequest(format!(
+            "'{}' has no conflict to resolve",
+            payload.path
+        )));
+    }
+
+    rebase_conflicts::resolve_file(&worktree_path, &payload.path, &payload.resolution)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "rebase_conflict_resolved",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "resolution": match payload.resolution {
+                    ConflictResolution::Ours => "ours",
+                    ConflictResolution::Theirs => "theirs",
+                    ConflictResolution::Content { .. } => "content",
+                    ConflictResolution::Delete => "delete",
+                },
+            }),
+        )
+        .await;
+
+    let details = rebase_conflicts::conflict_details(deployment.git(), &worktree_path)?;
+    Ok(ResponseJson(ApiResponse::success(details)))
+}
+
+/// Carry on with the rebase, or the cherry-pick onto another branch, once
+/// every file is resolved. It may stop again on the conflicts of a later
+/// commit.
+pub async fn continue_rebase(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ContinueRebaseRequest>,
+) -> Result<ResponseJson<ApiResponse<ConflictDetails>>, ApiError> {
+    let cherry_pick_target = payload.cherry_pick_target.as_deref();
+    let (repo, _, worktree_path) =
+        worktree(&deployment, &workspace, payload.repo_id, cherry_pick_target).await?;
+    let (expected_op, name) = match cherry_pick_target {
+        Some(_) => (ConflictOp::CherryPick, "cherry-pick"),
+        None => (ConflictOp::Rebase, "rebase"),
+    };
+    if deployment.git().detect_conflict_op(&worktree_path)? != Some(expected_op) {
+        return Err(ApiError::Conflict(format!(
+            "No {name} is in progress in this repository"
+        )));
+    }
+    let conflicted = deployment.git().get_conflicted_files(&worktree_path)?;
+    if !conflicted.is_empty() {
+        return Err(ApiError::Conflict(format!(
+            "Resolve the remaining conflicts first: {}",
+            conflicted.join(", ")
+        )));
+    }
+
+    if let Some(target) = cherry_pick_target {
+        let status = cherry_pick::continue_cherry_pick(
+            deployment.git(),
+            &repo.path,
+            &worktree_path,
+            target,
+        )?;
+        return Ok(ResponseJson(ApiResponse::success(status.conflicts)));
+    }
+
+    rebase_conflicts::continue_rebase(deployment.git(), &worktree_path)?;
+
+    let details = rebase_conflicts::conflict_details(deployment.git(), &worktree_path)?;
+    if details.op.is_none() {
+        // The rebase records the branch's tip from before it started
+        let restacked = match deployment.git().resolve_commit(&worktree_path, "ORIG_HEAD") {
+            Ok(old_head) => {
+                stack::restack_children(&deployment, &workspace, &repo, &old_head).await?
+            }
+            Err(_) => 0,
+        };
+        deployment
+            .track_if_analytics_allowed(
+                "task_attempt_rebased",
+                serde_json::json!({
+                    "workspace_id": workspace.id.to_string(),
+                    "repo_id": payload.repo_id.to_string(),
+                    "restacked_children": restacked,
+                }),
+            )
+            .await;
+    }
+    Ok(ResponseJson(ApiResponse::success(details)))
+}
+
+/// Hand the rebase's conflicts to the attempt's coding agent, which resolves
+/// them and continues the rebase
+pub async fn resolve_conflicts_with_agent(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ResolveConflictsWithAgentRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let (repo, workspace_repo, worktree_path) =
+        worktree(&deployment, &workspace, payload.repo_id, None).await?;
+    if deployment.git().detect_conflict_op(&worktree_path)? != Some(ConflictOp::Rebase) {
+        return Err(ApiError::Conflict(
+            "No rebase is in progress in this repository".to_string(),
+        ));
+    }
+    let conflicted = deployment.git().get_conflicted_files(&worktree_path)?;
+    if conflicted.is_empty() {
+        return Err(ApiError::Conflict(
+            "The rebase has no conflicts left; continue it instead".to_string(),
+        ));
+    }
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "A process is already running in this attempt".to_string(),
+        ));
+    }
+
+    let prompt = format!(
+        "Rebasing the branch of the `{}` repository onto `{}` stopped on conflicts in:\n{}\n\n\
+         Resolve each conflict, keeping the intent of both sides; the markers include the \
+         common ancestor's lines. Stage the resolved files with `git add`, then run \
+         `GIT_EDITOR=true git rebase --continue`. Later commits may conflict too: resolve \
+         them the same way until the rebase finishes. Don't abort the rebase.",
+        repo.name,
+        workspace_repo.target_branch,
+        conflicted
+            .iter()
+            .map(|path| format!("- {path}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+
+    let session = match Session::find_latest_by_workspace_id(pool, workspace.id).await? {
+        Some(s) => s,
+        None => {
+            Session::create(
+                pool,
+                &CreateSession { executor: None },
+                Uuid::new_v4(),
+                workspace.id,
+            )
+            .await?
+        }
+    };
+    let executor_profile_id =
+        ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?;
+    let agent_session_id =
+        ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await?;
+    let working_dir = workspace
+        .agent_working_dir
+        .as_ref()
+        .filter(|dir| !dir.is_empty())
+        .cloned();
+
+    let action_type = match agent_session_id {
+        Some(session_id) => {
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt,
+                session_id,
+                executor_profile_id,
+                working_dir,
+                prompt_additions: None,
+            })
+        }
+        None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt,
+            executor_profile_id,
+            working_dir,
+            prompt_additions: None,
+        }),
+    };
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &ExecutorAction::new(action_type, None),
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "rebase_conflicts_sent_to_agent",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "files": conflicted.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
@@ -0,0 +1,276 @@
+use std::path::PathBuf;
+
+use axum::{
+    Extension, Router,
+    extract::{Json, Path, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::{
+    dry_run::{DryRun, DryRunStatus},
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    project_repo::ProjectRepo,
+    session::{CreateSession, Session},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+    },
+    profile::ExecutorProfileId,
+};
+use serde::Deserialize;
+use services::services::{attempt_budget, container::ContainerService, dry_run};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_workspace_middleware};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct StartDryRunRequest {
+    /// What to plan; the task itself when omitted
+    pub prompt: Option<String>,
+    /// The profile the attempt was started with when omitted
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+pub async fn get_dry_runs(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<DryRun>>>, ApiError> {
+    let dry_runs = DryRun::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dry_runs)))
+}
+
+/// Have the agent plan the work in a throwaway copy of the workspace, to be
+/// approved before anything changes in the attempt
+pub async fn start_dry_run(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<StartDryRunRequest>,
+) -> Result<ResponseJson<ApiResponse<DryRun>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let Some(executor_profile_id) = payload.executor_profile_id.or_else(|| {
+        workspace
+            .executor_profile_id
+            .clone()
+            .map(|profile| profile.0)
+    }) else {
+        return Err(ApiError::BadRequest(
+            "Choose the executor profile to plan with".to_string(),
+        ));
+    };
+    let budget = attempt_budget::attempt_budget(pool, workspace.id, task.project_id, 0).await?;
+    if let Some(limit) = budget.reached {
+        return Err(ApiError::BadRequest(format!(
+            "The attempt reached its limit of {}; extend its budget to continue",
+            attempt_budget::describe_limit(limit, &budget.limits)
+        )));
+    }
+    let prompt = payload
+        .prompt
+        .filter(|prompt| !prompt.trim().is_empty())
+        .unwrap_or_else(|| task.to_prompt());
+
+    let workspace_root = PathBuf::from(
+        deployment
+            .container()
+            .ensure_container_exists(&workspace)
+            .await?,
+    );
+    let repo_names: Vec<String> = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id)
+        .await?
+        .into_iter()
+        .map(|repo| repo.name)
+        .collect();
+    let dry_run_id = Uuid::new_v4();
+    dry_run::create_copy(&workspace_root, &repo_names, &dry_run_id).await?;
+
+    let started = async {
+        let session = Session::create(
+            pool,
+            &CreateSession {
+                executor: Some(executor_profile_id.executor.to_string()),
+            },
+            Uuid::new_v4(),
+            workspace.id,
+        )
+        .await?;
+        let action = ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: dry_run::dry_run_prompt(&prompt),
+                executor_profile_id: executor_profile_id.clone(),
+                working_dir: Some(dry_run::agent_working_dir(
+                    &dry_run_id,
+                    workspace.agent_working_dir.as_deref(),
+                )),
+                prompt_additions: None,
+            }),
+            None,
+        );
+        let process = deployment
+            .container()
+            .start_execution(
+                &workspace,
+                &session,
+                &action,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await?;
+        Ok::<_, ApiError>(
+            DryRun::create(pool, dry_run_id, workspace.id, process.id, &prompt).await?,
+        )
+    }
+    .await;
+    let dry_run = match started {
+        Ok(dry_run) => dry_run,
+        Err(e) => {
+            dry_run::remove_copy(&workspace_root, &repo_names, &dry_run_id).await;
+            return Err(e);
+        }
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "dry_run_started",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "executor": executor_profile_id.executor.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(dry_run)))
+}
+
+/// Start the real run, handing the agent the approved plan and changes
+pub async fn approve_dry_run(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, dry_run_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let dry_run = DryRun::find_by_id(pool, dry_run_id)
+        .await?
+        .filter(|dry_run| dry_run.workspace_id == workspace_id)
+        .ok_or(SqlxError::RowNotFound)?;
+    if dry_run.status != DryRunStatus::Ready {
+        return Err(ApiError::Conflict(
+            "Only a finished dry run can be approved".to_string(),
+        ));
+    }
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "A process is already running in this attempt".to_string(),
+        ));
+    }
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let budget = attempt_budget::attempt_budget(pool, workspace.id, task.project_id, 0).await?;
+    if let Some(limit) = budget.reached {
+        return Err(ApiError::BadRequest(format!(
+            "The attempt reached its limit of {}; extend its budget to continue",
+            attempt_budget::describe_limit(limit, &budget.limits)
+        )));
+    }
+
+    let planned = ExecutionProcess::find_by_id(pool, dry_run.execution_process_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let executor_profile_id =
+        ExecutionProcess::latest_executor_profile_for_session(pool, planned.session_id).await?;
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let session = Session::create(
+        pool,
+        &CreateSession {
+            executor: Some(executor_profile_id.executor.to_string()),
+        },
+        Uuid::new_v4(),
+        workspace.id,
+    )
+    .await?;
+    let project_repos = ProjectRepo::find_by_project_id_with_names(pool, task.project_id).await?;
+    let cleanup_action = deployment
+        .container()
+        .cleanup_actions_for_repos(&project_repos);
+    let working_dir = workspace
+        .agent_working_dir
+        .as_ref()
+        .filter(|dir| !dir.is_empty())
+        .cloned();
+    let action = ExecutorAction::new(
+        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt: dry_run::approved_prompt(
+                &dry_run.prompt,
+                dry_run.plan.as_deref(),
+                dry_run.patch.as_deref(),
+            ),
+            executor_profile_id,
+            working_dir,
+            prompt_additions: None,
+        }),
+        cleanup_action.map(Box::new),
+    );
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+    DryRun::approve(pool, dry_run.id, execution_process.id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
+pub async fn discard_dry_run(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, dry_run_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let dry_run = DryRun::find_by_id(pool, dry_run_id)
+        .await?
+        .filter(|dry_run| dry_run.workspace_id == workspace_id)
+        .ok_or(SqlxError::RowNotFound)?;
+    if !DryRun::discard(pool, dry_run.id).await? {
+        return Err(ApiError::Conflict(
+            "Only a finished dry run can be discarded".to_string(),
+        ));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let workspace_router = Router::new()
+        .route("/", get(get_dry_runs).post(start_dry_run))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_workspace_middleware,
+        ));
+
+    let dry_run_router = Router::new()
+        .route("/{dry_run_id}/approve", post(approve_dry_run))
+        .route("/{dry_run_id}/discard", post(discard_dry_run));
+
+    workspace_router.merge(dry_run_router)
+}
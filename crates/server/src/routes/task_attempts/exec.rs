@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use axum::{
+    Extension,
+    extract::{Json, State},
+    response::{
+        Sse,
+        sse::{Event, KeepAlive},
+    },
+};
+use db::models::{project_settings::ProjectSettings, task::Task, workspace::Workspace};
+use deployment::Deployment;
+use futures_util::{Stream, StreamExt};
+use services::services::{
+    container::{ContainerService, WorkspaceEnv},
+    workspace_exec::{self, ExecRequest},
+};
+use sqlx::Error as SqlxError;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Run a one-off command in the attempt's workspace, inside its container when
+/// the project uses one, and stream its output as server-sent events. The
+/// command is killed when the client disconnects.
+pub async fn exec_in_workspace(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<ExecRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let WorkspaceEnv { env, secret_values } =
+        deployment.container().workspace_env(&workspace).await?;
+
+    let stream = workspace_exec::spawn_exec(
+        workspace.id,
+        &PathBuf::from(container_ref),
+        &env,
+        &settings.exec_policy,
+        secret_values,
+        &request,
+    )?;
+    let _ = Workspace::touch(pool, workspace.id).await;
+
+    Ok(
+        Sse::new(stream.map(|output| Event::default().json_data(&output)))
+            .keep_alive(KeepAlive::default()),
+    )
+}
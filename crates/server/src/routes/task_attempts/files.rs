@@ -0,0 +1,420 @@
+use std::path::PathBuf;
+
+use axum::{
+    Extension, Router,
+    body::Body,
+    extract::{
+        DefaultBodyLimit, Json, Multipart, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{StatusCode, header},
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{get, post},
+};
+use db::models::{
+    project_settings::ProjectSettings,
+    task::Task,
+    workspace::Workspace,
+    workspace_artifact::WorkspaceArtifact,
+    workspace_upload::{CreateWorkspaceUpload, WorkspaceUpload},
+};
+use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    file_events,
+    workspace_archive::{self, ArchiveFormat},
+    workspace_bundle,
+    workspace_files::{
+        self, FileContent, FileScope, FileUploadError, UpdateFileContent, UploadedFile,
+    },
+};
+use sqlx::Error as SqlxError;
+use tokio_util::io::ReaderStream;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_workspace_middleware};
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveQuery {
+    /// Directory relative to the scope's root; all of it when omitted
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub scope: FileScope,
+    #[serde(default)]
+    pub format: ArchiveFormat,
+    #[serde(default)]
+    pub respect_gitignore: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BundleQuery {
+    #[serde(default)]
+    pub format: ArchiveFormat,
+}
+
+/// Stream create/modify/remove/rename events under the workspace root so the UI
+/// can refresh file trees and diffs without polling.
+pub async fn stream_file_changes_ws(
+    ws: WebSocketUpgrade,
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> impl IntoResponse {
+    let _ = Workspace::touch(&deployment.db().pool, workspace.id).await;
+
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_file_changes_ws(socket, deployment, workspace).await {
+            tracing::warn!("file changes WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_file_changes_ws(
+    socket: WebSocket,
+    deployment: DeploymentImpl,
+    workspace: Workspace,
+) -> anyhow::Result<()> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_path = PathBuf::from(container_ref);
+
+    // Walking the tree to register watches can take a while on large repos
+    let stream =
+        tokio::task::spawn_blocking(move || file_events::watch_file_changes(workspace_path))
+            .await??;
+    let mut stream = Box::pin(stream);
+
+    let (mut sender, mut receiver) = socket.split();
+
+    loop {
+        tokio::select! {
+            batch = stream.next() => {
+                let Some(batch) = batch else { break };
+                let json = serde_json::to_string(&batch)?;
+                if sender.send(Message::Text(json.into())).await.is_err() {
+                    break;
+                }
+            }
+            msg = receiver.next() => {
+                if msg.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadQuery {
+    /// Directory relative to the scope's root to place the files in
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub scope: FileScope,
+}
+
+/// Upload one or more files into the workspace. Files landing on gitignored paths
+/// are flagged, or refused when the project is configured to do so, and the
+/// project's upload scanner runs before anything is written.
+pub async fn upload_files(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<UploadQuery>,
+    mut multipart: Multipart,
+) -> Result<ResponseJson<ApiResponse<Vec<UploadedFile>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+    let mut used_bytes = ProjectSettings::used_upload_bytes(pool, task.project_id).await?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let root = query.scope.root(&PathBuf::from(container_ref)).await?;
+    let target_dir = query.path.trim_matches('/');
+
+    let mut uploaded = Vec::new();
+    while let Some(field) = multipart.next_field().await? {
+        // Only the final component of the client-supplied name is used
+        let Some(file_name) = field
+            .file_name()
+            .and_then(|name| std::path::Path::new(name).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        let data = field.bytes().await?;
+        settings
+            .upload_policy
+            .check(&file_name, data.len() as i64, used_bytes)
+            .map_err(FileUploadError::PolicyViolation)?;
+
+        let relative = if target_dir.is_empty() {
+            file_name
+        } else {
+            format!("{target_dir}/{file_name}")
+        };
+        let file = workspace_files::upload_file(&root, &relative, &data, &settings).await?;
+        // Uploads are recorded relative to the workspace root
+        let recorded_path = match query.scope.prefix() {
+            Some(prefix) => format!("{prefix}/{}", file.path),
+            None => file.path.clone(),
+        };
+        WorkspaceUpload::upsert(
+            pool,
+            &CreateWorkspaceUpload {
+                workspace_id: workspace.id,
+                path: recorded_path,
+                size_bytes: file.size_bytes,
+                hash: file.hash.clone(),
+                uploaded_by: Some(deployment.user_id().to_string()),
+            },
+        )
+        .await?;
+        used_bytes += file.size_bytes;
+        uploaded.push(file);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_files_uploaded",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "file_count": uploaded.len(),
+                "gitignored_count": uploaded.iter().filter(|file| file.gitignored).count(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(uploaded)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileContentQuery {
+    /// Path relative to the scope's root
+    pub path: String,
+    #[serde(default)]
+    pub scope: FileScope,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScopeQuery {
+    #[serde(default)]
+    pub scope: FileScope,
+}
+
+pub async fn get_file_content(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<FileContentQuery>,
+) -> Result<ResponseJson<ApiResponse<FileContent>>, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let root = query.scope.root(&PathBuf::from(container_ref)).await?;
+    let content = workspace_files::read_text_file(&root, &query.path).await?;
+    Ok(ResponseJson(ApiResponse::success(content)))
+}
+
+/// Replace or patch a text file in the workspace, guarded by the hash of the
+/// contents the edit was based on. Edits are held to the project's upload
+/// policy, gitignore rule and scanner like uploads.
+pub async fn update_file_content(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ScopeQuery>,
+    Json(payload): Json<UpdateFileContent>,
+) -> Result<ResponseJson<ApiResponse<FileContent>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let root = query.scope.root(&PathBuf::from(container_ref)).await?;
+    let content = workspace_files::update_text_file(&root, &payload, &settings).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_file_edited",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "patch": matches!(payload.edit, workspace_files::FileEdit::Patch { .. }),
+                "scratch": query.scope == FileScope::Scratch,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(content)))
+}
+
+/// List files previously uploaded into this workspace, newest first.
+pub async fn get_workspace_uploads(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceUpload>>>, ApiError> {
+    let uploads =
+        WorkspaceUpload::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(uploads)))
+}
+
+/// Files the attempt's agents published for review, by name. Download one
+/// with `/raw?scope=artifacts&path=<name>`.
+pub async fn get_workspace_artifacts(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceArtifact>>>, ApiError> {
+    let artifacts =
+        WorkspaceArtifact::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(artifacts)))
+}
+
+/// A file as it is, typed by its extension so the browser can show images and
+/// pages. Pages are sandboxed, so their scripts can't act on the app.
+pub async fn get_raw_file(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<FileContentQuery>,
+) -> Result<Response, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let root = query.scope.root(&PathBuf::from(container_ref)).await?;
+    let (file, length) = workspace_files::open_file(&root, &query.path).await?;
+    let file_name = std::path::Path::new(&query.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('"', "-"))
+        .unwrap_or_default();
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            header::CONTENT_TYPE,
+            mime_guess::from_path(&query.path)
+                .first_or_octet_stream()
+                .as_ref(),
+        )
+        .header(header::CONTENT_LENGTH, length)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{file_name}\""),
+        )
+        .header(header::CONTENT_SECURITY_POLICY, "sandbox")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| ApiError::Io(std::io::Error::other(e)))
+}
+
+/// Download the workspace, or a directory inside it, as a zip or tar.gz archive.
+pub async fn download_archive(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ArchiveQuery>,
+) -> Result<Response, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let scope_root = query.scope.root(&PathBuf::from(container_ref)).await?;
+    let root = workspace_archive::resolve_archive_root(&scope_root, &query.path)?;
+
+    let archive_name = format!(
+        "{}.{}",
+        root.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "workspace".to_string()),
+        query.format.extension()
+    );
+    let (format, respect_gitignore) = (query.format, query.respect_gitignore);
+    let file = tokio::task::spawn_blocking(move || {
+        workspace_archive::build_archive(&root, format, respect_gitignore)
+    })
+    .await
+    .map_err(|e| ApiError::Io(std::io::Error::other(e)))??;
+
+    archive_response(file, format, &archive_name).await
+}
+
+/// Download the whole workspace as an archive that opens as a dev container,
+/// with a configuration describing the attempt's environment. Gitignored files
+/// such as dependencies and build output are left out.
+pub async fn download_bundle(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<BundleQuery>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let root = PathBuf::from(container_ref);
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let generated = workspace_bundle::generated_files(pool, &workspace, &task).await?;
+
+    let archive_name =
+        format!("{}.{}", workspace.branch, query.format.extension()).replace(['/', '"'], "-");
+    let format = query.format;
+    let file = tokio::task::spawn_blocking(move || {
+        workspace_archive::build_archive_with_files(&root, format, true, &generated)
+    })
+    .await
+    .map_err(|e| ApiError::Io(std::io::Error::other(e)))??;
+
+    archive_response(file, format, &archive_name).await
+}
+
+async fn archive_response(
+    file: std::fs::File,
+    format: ArchiveFormat,
+    archive_name: &str,
+) -> Result<Response, ApiError> {
+    let file = tokio::fs::File::from_std(file);
+    let length = file.metadata().await?.len();
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(header::CONTENT_LENGTH, length)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{archive_name}\""),
+        )
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| ApiError::Io(std::io::Error::other(e)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/ws", get(stream_file_changes_ws))
+        .route("/archive", get(download_archive))
+        .route("/bundle", get(download_bundle))
+        .route("/uploads", get(get_workspace_uploads))
+        .route("/artifacts", get(get_workspace_artifacts))
+        .route("/raw", get(get_raw_file))
+        .route("/content", get(get_file_content).put(update_file_content))
+        .route(
+            "/upload",
+            post(upload_files).layer(DefaultBodyLimit::max(20 * 1024 * 1024)),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_workspace_middleware,
+        ))
+}
@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{workspace::Workspace, workspace_repo::WorkspaceRepo};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::{
+    container::{ContainerService, ContainerStatus},
+    process_registry::{ProcessInfo, ProcessRegistry},
+    workspace_manager::WorkspaceManager,
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, TS)]
+pub struct WorktreeHealth {
+    pub repo_id: Uuid,
+    pub repo_name: String,
+    pub exists: bool,
+    /// Whether the worktree has no uncommitted changes; absent when it is
+    /// missing or git can't read it
+    pub clean: Option<bool>,
+}
+
+/// What is known of an attempt's workspace, for explaining why opening it or
+/// running in it fails
+#[derive(Debug, Serialize, TS)]
+pub struct WorkspaceHealth {
+    /// Absent until the workspace is first created
+    pub workspace_dir: Option<String>,
+    pub workspace_dir_exists: bool,
+    pub worktrees: Vec<WorktreeHealth>,
+    /// Space left on the filesystem holding the workspace, where it can be
+    /// measured
+    pub disk_free_bytes: Option<u64>,
+    pub container: ContainerStatus,
+    /// Processes the server runs for the attempt: executions, terminals,
+    /// editors and exec commands
+    pub processes: Vec<ProcessInfo>,
+}
+
+pub async fn get_workspace_health(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceHealth>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace_dir = workspace.container_ref.as_ref().map(PathBuf::from);
+    let workspace_dir_exists = workspace_dir.as_ref().is_some_and(|dir| dir.is_dir());
+
+    let mut worktrees = Vec::new();
+    for repo in WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await? {
+        let path = workspace_dir.as_ref().map(|dir| dir.join(&repo.name));
+        let exists = path.as_ref().is_some_and(|path| path.join(".git").exists());
+        let clean = match path.filter(|_| exists) {
+            Some(path) => deployment.git().is_worktree_clean(&path).ok(),
+            None => None,
+        };
+        worktrees.push(WorktreeHealth {
+            repo_id: repo.id,
+            repo_name: repo.name,
+            exists,
+            clean,
+        });
+    }
+
+    // Before the workspace exists, the base directory is where it will go
+    let disk_dir = workspace_dir
+        .clone()
+        .filter(|_| workspace_dir_exists)
+        .unwrap_or_else(WorkspaceManager::get_workspace_base_dir);
+    let disk_free_bytes = tokio::task::spawn_blocking(move || disk_free(&disk_dir))
+        .await
+        .ok()
+        .flatten();
+
+    let container = deployment.container().container_status(&workspace).await?;
+    let processes = ProcessRegistry::global()
+        .list()
+        .into_iter()
+        .filter(|process| process.workspace_id == Some(workspace.id))
+        .collect();
+
+    Ok(ResponseJson(ApiResponse::success(WorkspaceHealth {
+        workspace_dir: workspace.container_ref,
+        workspace_dir_exists,
+        worktrees,
+        disk_free_bytes,
+        container,
+        processes,
+    })))
+}
+
+#[cfg(unix)]
+// The field types differ between platforms
+#[allow(clippy::unnecessary_cast)]
+fn disk_free(path: &Path) -> Option<u64> {
+    let stat = nix::sys::statvfs::statvfs(path).ok()?;
+    Some(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
+#[cfg(not(unix))]
+fn disk_free(_path: &Path) -> Option<u64> {
+    None
+}
@@ -2,19 +2,20 @@ use std::path::Path;
 
 use axum::{
     Extension, Router,
-    body::Body,
+    body::{Body, Bytes},
     extract::{DefaultBodyLimit, Multipart, Query, Request, State},
-    http::{StatusCode, header},
+    http::{HeaderMap, StatusCode, header},
     middleware::{Next, from_fn_with_state},
     response::{Json as ResponseJson, Response},
     routing::{get, post},
 };
-use db::models::{task::Task, workspace::Workspace};
+use db::models::{image::TaskImage, task::Task, workspace::Workspace};
 use deployment::Deployment;
-use serde::Deserialize;
-use services::services::{container::ContainerService, image::ImageError};
+use serde::{Deserialize, Serialize};
+use services::services::{container::ContainerService, image::ImageError, workspace_files};
 use tokio::fs::File;
 use tokio_util::io::ReaderStream;
+use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
@@ -59,6 +60,71 @@ pub async fn upload_image(
     Ok(ResponseJson(ApiResponse::success(image_response)))
 }
 
+#[derive(Debug, Serialize, TS)]
+pub struct PastedImageResponse {
+    pub image: ImageResponse,
+    /// Markdown image link relative to the worktree root, ready to insert into a description
+    pub markdown: String,
+    pub thumbnail_url: String,
+}
+
+/// Accept raw PNG/JPEG bytes pasted from the clipboard, store them like a regular
+/// upload and place the file in the workspace so the agent can read it.
+pub async fn paste_image(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<ResponseJson<ApiResponse<PastedImageResponse>>, ApiError> {
+    let task = Task::find_by_id(&deployment.db().pool, workspace.task_id)
+        .await?
+        .ok_or_else(|| ApiError::Image(ImageError::NotFound))?;
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let image_service = deployment.image();
+    let image = image_service
+        .store_pasted_image(task.id, &body, content_type)
+        .await?;
+    TaskImage::associate_many_dedup(
+        &deployment.db().pool,
+        task.id,
+        std::slice::from_ref(&image.id),
+    )
+    .await?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_path = std::path::PathBuf::from(container_ref);
+    image_service
+        .copy_images_by_ids_to_worktree(&workspace_path, &[image.id])
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "image_pasted",
+            serde_json::json!({
+                "image_id": image.id.to_string(),
+                "size_bytes": image.size_bytes,
+                "task_id": task.id.to_string(),
+            }),
+        )
+        .await;
+
+    let thumbnail_url = format!("/api/images/{}/thumbnail", image.id);
+    let image = ImageResponse::from_image(image);
+    let markdown = format!("![{}]({})", image.original_name, image.file_path);
+    Ok(ResponseJson(ApiResponse::success(PastedImageResponse {
+        image,
+        markdown,
+        thumbnail_url,
+    })))
+}
+
 /// Get metadata about an image in the workspace's worktree.
 pub async fn get_image_metadata(
     Extension(workspace): Extension<Workspace>,
@@ -96,10 +162,11 @@ pub async fn get_image_metadata(
         .await?;
     let workspace_path = std::path::PathBuf::from(container_ref);
     let full_path = workspace_path.join(&query.path);
+    let inside_workspace = workspace_files::ensure_within_root(&workspace_path, &full_path).is_ok();
 
     // Check if file exists
     let metadata = match tokio::fs::metadata(&full_path).await {
-        Ok(m) if m.is_file() => m,
+        Ok(m) if m.is_file() && inside_workspace => m,
         _ => {
             return Ok(ResponseJson(ApiResponse::success(ImageMetadata {
                 exists: false,
@@ -234,6 +301,10 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/upload",
             post(upload_image).layer(DefaultBodyLimit::max(20 * 1024 * 1024)), // 20MB limit
         )
+        .route(
+            "/paste",
+            post(paste_image).layer(DefaultBodyLimit::max(20 * 1024 * 1024)),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_workspace_middleware,
@@ -0,0 +1,172 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    merge_selection::{MergeSelection, SelectedFile},
+    repo::{Repo, RepoError},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::partial_merge::{self, SelectableFile};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct SelectableChangesQuery {
+    pub repo_id: Uuid,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct SelectableChanges {
+    /// Where the attempt's branch leaves its target branch
+    pub base_commit: String,
+    /// The attempt branch's tip
+    pub head_commit: String,
+    pub files: Vec<SelectableFile>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct CreateMergeSelectionRequest {
+    pub repo_id: Uuid,
+    /// The branch tip the files and hunks were picked at, from the
+    /// selectable changes
+    pub head_commit: String,
+    pub files: Vec<SelectedFile>,
+}
+
+async fn attempt_repo(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo_id: Uuid,
+) -> Result<(Repo, WorkspaceRepo), ApiError> {
+    let pool = &deployment.db().pool;
+    let workspace_repo = WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    let repo = Repo::find_by_id(pool, repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+    Ok((repo, workspace_repo))
+}
+
+/// The files and hunks of the attempt's diff that can be picked for a
+/// partial merge or pull request
+pub async fn get_selectable_changes(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SelectableChangesQuery>,
+) -> Result<ResponseJson<ApiResponse<SelectableChanges>>, ApiError> {
+    let (repo, workspace_repo) = attempt_repo(&deployment, &workspace, query.repo_id).await?;
+    let git = deployment.git();
+    let base_commit = git
+        .get_base_commit(&repo.path, &workspace.branch, &workspace_repo.target_branch)?
+        .to_string();
+    let head_commit = git.get_branch_oid(&repo.path, &workspace.branch)?;
+    let files = partial_merge::selectable_files(git, &repo.path, &base_commit, &head_commit)?;
+    Ok(ResponseJson(ApiResponse::success(SelectableChanges {
+        base_commit,
+        head_commit,
+        files,
+    })))
+}
+
+/// Save a selection of the attempt's files and hunks, to be given to a merge
+/// or pull request. Selections are kept once used, for audit.
+pub async fn create_merge_selection(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateMergeSelectionRequest>,
+) -> Result<ResponseJson<ApiResponse<MergeSelection>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let (repo, workspace_repo) = attempt_repo(&deployment, &workspace, payload.repo_id).await?;
+    let git = deployment.git();
+    let head_commit = git.get_branch_oid(&repo.path, &workspace.branch)?;
+    if head_commit != payload.head_commit {
+        return Err(ApiError::Conflict(
+            "The attempt's branch has changed since its changes were listed; pick them again"
+                .to_string(),
+        ));
+    }
+    let base_commit = git
+        .get_base_commit(&repo.path, &workspace.branch, &workspace_repo.target_branch)?
+        .to_string();
+    // Checks that every file and hunk picked is in the diff
+    partial_merge::selection_patch(git, &repo.path, &base_commit, &head_commit, &payload.files)?;
+
+    let selection = MergeSelection::create(
+        pool,
+        workspace.id,
+        repo.id,
+        &base_commit,
+        &head_commit,
+        &payload.files,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "merge_selection_created",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "repo_id": repo.id.to_string(),
+                "files": payload.files.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(selection)))
+}
+
+/// The attempt's selections, newest first
+pub async fn get_merge_selections(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<MergeSelection>>>, ApiError> {
+    let selections =
+        MergeSelection::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(selections)))
+}
+
+/// A selection of the attempt's repository that wasn't used yet, with the
+/// patch of its files and hunks. The branch can't have moved since it was
+/// made.
+pub async fn selection_patch(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    repo: &Repo,
+    selection_id: Uuid,
+) -> Result<(MergeSelection, String), ApiError> {
+    let selection = MergeSelection::find_by_id(&deployment.db().pool, selection_id)
+        .await?
+        .filter(|s| s.workspace_id == workspace.id && s.repo_id == repo.id)
+        .ok_or_else(|| {
+            ApiError::BadRequest("The selection isn't one of this attempt's repository".to_string())
+        })?;
+    if selection.merge_id.is_some() {
+        return Err(ApiError::Conflict(
+            "The selection was already merged or opened as a pull request".to_string(),
+        ));
+    }
+    let git = deployment.git();
+    if git.get_branch_oid(&repo.path, &workspace.branch)? != selection.head_commit {
+        return Err(ApiError::Conflict(
+            "The attempt's branch has changed since the selection was made; make a new one"
+                .to_string(),
+        ));
+    }
+    let patch = partial_merge::selection_patch(
+        git,
+        &repo.path,
+        &selection.base_commit,
+        &selection.head_commit,
+        &selection.files,
+    )?;
+    Ok((selection, patch))
+}
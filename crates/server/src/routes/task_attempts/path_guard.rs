@@ -0,0 +1,95 @@
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::{
+    execution_process::ExecutionProcess, path_guard_block::PathGuardBlock, workspace::Workspace,
+};
+use deployment::Deployment;
+use services::services::container::ContainerService;
+use sqlx::{Error as SqlxError, SqlitePool};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_workspace_middleware};
+
+/// Refuse to take the attempt's changes anywhere while changes to protected
+/// paths wait for an override
+pub async fn ensure_not_guarded(pool: &SqlitePool, workspace_id: Uuid) -> Result<(), ApiError> {
+    match PathGuardBlock::find_pending_by_workspace_id(pool, workspace_id).await? {
+        Some(block) => Err(ApiError::Conflict(format!(
+            "The attempt changed protected paths ({}); override the guard first",
+            block.paths.join(", ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+pub async fn get_path_guard_blocks(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<PathGuardBlock>>>, ApiError> {
+    let blocks = PathGuardBlock::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(blocks)))
+}
+
+/// Let the held-back changes through: commit them and carry on with the run's
+/// next action, as if they hadn't been stopped
+pub async fn override_path_guard(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, block_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<PathGuardBlock>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let block = PathGuardBlock::find_by_id(pool, block_id)
+        .await?
+        .filter(|block| block.workspace_id == workspace_id)
+        .ok_or(SqlxError::RowNotFound)?;
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace_id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "Wait for the attempt's running process to finish".to_string(),
+        ));
+    }
+    if !PathGuardBlock::set_overridden(pool, block.id).await? {
+        return Err(ApiError::Conflict(
+            "The block was already overridden".to_string(),
+        ));
+    }
+
+    let ctx = ExecutionProcess::load_context(pool, block.execution_process_id).await?;
+    deployment.container().try_commit_changes(&ctx).await?;
+    deployment.container().try_start_next_action(&ctx).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "path_guard_overridden",
+            serde_json::json!({
+                "workspace_id": workspace_id.to_string(),
+                "paths": block.paths.len(),
+            }),
+        )
+        .await;
+
+    let block = PathGuardBlock::find_by_id(pool, block.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(block)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let workspace_router =
+        Router::new()
+            .route("/", get(get_path_guard_blocks))
+            .layer(from_fn_with_state(
+                deployment.clone(),
+                load_workspace_middleware,
+            ));
+
+    let block_router = Router::new().route("/{block_id}/override", post(override_path_guard));
+
+    workspace_router.merge(block_router)
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use axum::{
     Extension, Json,
@@ -7,12 +7,16 @@ use axum::{
 };
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
-    merge::{Merge, MergeStatus},
+    hook_run::HookEvent,
+    merge::{Merge, MergeStatus, PullRequestInfo},
+    merge_selection::MergeSelection,
+    project_settings::{CodeHostProvider, ProjectSettings},
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{Task, TaskStatus},
     workspace::{Workspace, WorkspaceError},
     workspace_repo::WorkspaceRepo,
+    workspace_stack::WorkspaceStack,
 };
 use deployment::Deployment;
 use executors::actions::{
@@ -22,15 +26,24 @@ use executors::actions::{
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
+    azure_devops::{self, AzureDevOpsService, AzureDevOpsServiceError, AzureRepo},
+    bitbucket::{BitbucketRepo, BitbucketService, BitbucketServiceError},
     container::ContainerService,
     git::{GitCliError, GitServiceError},
     github::{CreatePrRequest, GitHubService, GitHubServiceError, UnifiedPrComment},
+    gitlab::{GitLabProject, GitLabService, GitLabServiceError},
+    lifecycle_hooks::{self, HookDetails},
 };
+use sqlx::SqlitePool;
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    routes::task_attempts::{ci_checks, partial_merge, path_guard, stack},
+};
 
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct CreateGitHubPrRequest {
@@ -41,6 +54,9 @@ pub struct CreateGitHubPrRequest {
     pub repo_id: Uuid,
     #[serde(default)]
     pub auto_generate_description: bool,
+    /// Open the pull request with only the files and hunks of this selection,
+    /// from a branch of their own, leaving the rest on the attempt's branch
+    pub selection_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -52,6 +68,9 @@ pub enum CreatePrError {
     GitCliNotLoggedIn,
     GitCliNotInstalled,
     TargetBranchNotFound { branch: String },
+    GitlabNotLoggedIn,
+    BitbucketNotLoggedIn,
+    AzureDevopsNotLoggedIn,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -67,6 +86,11 @@ pub struct AttachExistingPrRequest {
     pub repo_id: Uuid,
 }
 
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct MergePrRequest {
+    pub repo_id: Uuid,
+}
+
 #[derive(Debug, Serialize, TS)]
 pub struct PrCommentsResponse {
     pub comments: Vec<UnifiedPrComment>,
@@ -79,6 +103,9 @@ pub enum GetPrCommentsError {
     NoPrAttached,
     GithubCliNotInstalled,
     GithubCliNotLoggedIn,
+    GitlabNotLoggedIn,
+    BitbucketNotLoggedIn,
+    AzureDevopsNotLoggedIn,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -99,9 +126,110 @@ Analyze the changes in this branch and write:
 
 Use `gh pr edit` to update the PR."#;
 
+pub const DEFAULT_MR_DESCRIPTION_PROMPT: &str = r#"Update the GitLab merge request that was just created with a better title and description.
+The merge request is !{pr_number} and the URL is {pr_url}.
+
+Analyze the changes in this branch and write:
+1. A concise, descriptive title that summarizes the changes, postfixed with "(Vibe Kanban)"
+2. A detailed description that explains:
+   - What changes were made
+   - Why they were made (based on the task context)
+   - Any important implementation details
+   - At the end, include a note: "This merge request was written using [Vibe Kanban](https://vibekanban.com)"
+
+Use `glab mr update` to update the merge request."#;
+
+pub const DEFAULT_BITBUCKET_PR_DESCRIPTION_PROMPT: &str = r#"Update the Bitbucket pull request that was just created with a better title and description.
+The PR number is #{pr_number} and the URL is {pr_url}.
+
+Analyze the changes in this branch and write:
+1. A concise, descriptive title that summarizes the changes, postfixed with "(Vibe Kanban)"
+2. A detailed description that explains:
+   - What changes were made
+   - Why they were made (based on the task context)
+   - Any important implementation details
+   - At the end, include a note: "This PR was written using [Vibe Kanban](https://vibekanban.com)"
+
+Update the PR through the Bitbucket Cloud REST API (`PUT /2.0/repositories/{workspace}/{repo_slug}/pullrequests/{pr_number}`) with the credentials in the environment."#;
+
+pub const DEFAULT_AZURE_PR_DESCRIPTION_PROMPT: &str = r#"Update the Azure DevOps pull request that was just created with a better title and description.
+The PR number is !{pr_number} and the URL is {pr_url}.
+
+Analyze the changes in this branch and write:
+1. A concise, descriptive title that summarizes the changes, postfixed with "(Vibe Kanban)"
+2. A detailed description that explains:
+   - What changes were made
+   - Why they were made (based on the task context)
+   - Any important implementation details
+   - At the end, include a note: "This PR was written using [Vibe Kanban](https://vibekanban.com)"
+Keep the work item mentions (e.g. #123) already in the description.
+
+Use `az repos pr update` to update the PR."#;
+
+/// The workspace's project, and where its pull requests go
+pub async fn code_host(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+) -> Result<(Uuid, CodeHostProvider), ApiError> {
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+    Ok((task.project_id, settings.code_host.provider))
+}
+
+async fn gitlab_for_repo(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    repo_path: &Path,
+) -> Result<(GitLabService, GitLabProject), GitLabServiceError> {
+    let (gitlab, settings) = GitLabService::for_project(&deployment.db().pool, project_id).await?;
+    let project = GitLabService::project_for_repo(deployment.git(), repo_path, &settings)?;
+    Ok((gitlab, project))
+}
+
+async fn bitbucket_for_repo(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    repo_path: &Path,
+) -> Result<(BitbucketService, BitbucketRepo), BitbucketServiceError> {
+    let bitbucket = BitbucketService::for_project(&deployment.db().pool, project_id).await?;
+    let repo = BitbucketService::repo_for_path(deployment.git(), repo_path)?;
+    Ok((bitbucket, repo))
+}
+
+async fn azure_for_repo(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    repo_path: &Path,
+) -> Result<(AzureDevOpsService, bool, AzureRepo), AzureDevOpsServiceError> {
+    let (azure, settings) =
+        AzureDevOpsService::for_project(&deployment.db().pool, project_id).await?;
+    let repo = AzureDevOpsService::repo_for_path(deployment.git(), repo_path)?;
+    Ok((azure, settings.link_work_items, repo))
+}
+
+/// The header git authenticates with, for code hosts whose token is used
+/// rather than the machine's git credentials
+pub async fn push_auth_header(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    provider: CodeHostProvider,
+) -> Result<Option<String>, AzureDevOpsServiceError> {
+    match provider {
+        CodeHostProvider::AzureDevops => {
+            let (azure, _) = AzureDevOpsService::for_project(pool, project_id).await?;
+            Ok(Some(azure.git_auth_header()))
+        }
+        _ => Ok(None),
+    }
+}
+
 async fn trigger_pr_description_follow_up(
     deployment: &DeploymentImpl,
     workspace: &Workspace,
+    provider: CodeHostProvider,
     pr_number: i64,
     pr_url: &str,
 ) -> Result<(), ApiError> {
@@ -110,7 +238,12 @@ async fn trigger_pr_description_follow_up(
     let prompt_template = config
         .pr_auto_description_prompt
         .as_deref()
-        .unwrap_or(DEFAULT_PR_DESCRIPTION_PROMPT);
+        .unwrap_or(match provider {
+            CodeHostProvider::Github => DEFAULT_PR_DESCRIPTION_PROMPT,
+            CodeHostProvider::Gitlab => DEFAULT_MR_DESCRIPTION_PROMPT,
+            CodeHostProvider::Bitbucket => DEFAULT_BITBUCKET_PR_DESCRIPTION_PROMPT,
+            CodeHostProvider::AzureDevops => DEFAULT_AZURE_PR_DESCRIPTION_PROMPT,
+        });
 
     // Replace placeholders in prompt
     let prompt = prompt_template
@@ -159,12 +292,14 @@ async fn trigger_pr_description_follow_up(
             session_id: agent_session_id,
             executor_profile_id: executor_profile_id.clone(),
             working_dir: working_dir.clone(),
+            prompt_additions: None,
         })
     } else {
         ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
             prompt,
             executor_profile_id: executor_profile_id.clone(),
             working_dir,
+            prompt_additions: None,
         })
     };
 
@@ -189,6 +324,17 @@ pub async fn create_github_pr(
     Json(request): Json<CreateGitHubPrRequest>,
 ) -> Result<ResponseJson<ApiResponse<String, CreatePrError>>, ApiError> {
     let pool = &deployment.db().pool;
+    path_guard::ensure_not_guarded(pool, workspace.id).await?;
+    let (project_id, provider) = code_host(pool, &workspace).await?;
+    let auth_header = match push_auth_header(pool, project_id, provider).await {
+        Ok(header) => header,
+        Err(AzureDevOpsServiceError::MissingToken(_)) => {
+            return Ok(ResponseJson(ApiResponse::error_with_data(
+                CreatePrError::AzureDevopsNotLoggedIn,
+            )));
+        }
+        Err(e) => return Err(ApiError::AzureDevOpsService(e)),
+    };
 
     let workspace_repo =
         WorkspaceRepo::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
@@ -199,7 +345,7 @@ pub async fn create_github_pr(
         .await?
         .ok_or(RepoError::NotFound)?;
 
-    let repo_path = repo.path;
+    let repo_path = repo.path.clone();
     let target_branch = if let Some(branch) = request.target_branch {
         branch
     } else {
@@ -211,12 +357,31 @@ pub async fn create_github_pr(
         .ensure_container_exists(&workspace)
         .await?;
     let workspace_path = PathBuf::from(&container_ref);
-    let worktree_path = workspace_path.join(repo.name);
+    let worktree_path = workspace_path.join(&repo.name);
 
-    match deployment
-        .git()
-        .check_remote_branch_exists(&repo_path, &target_branch)
+    // A stacked attempt's pull request targets its parent's branch, which
+    // has to be on the code host first
+    if let Some(parent_id) = WorkspaceStack::find_parent_id(pool, workspace.id).await?
+        && let Some(parent) = Workspace::find_by_id(pool, parent_id).await?
+        && parent.branch == target_branch
     {
+        let parent_ref = deployment
+            .container()
+            .ensure_container_exists(&parent)
+            .await?;
+        deployment.git().push_to_remote(
+            &PathBuf::from(parent_ref).join(&repo.name),
+            &parent.branch,
+            false,
+            auth_header.as_deref(),
+        )?;
+    }
+
+    match deployment.git().check_remote_branch_exists(
+        &repo_path,
+        &target_branch,
+        auth_header.as_deref(),
+    ) {
         Ok(false) => {
             return Ok(ResponseJson(ApiResponse::error_with_data(
                 CreatePrError::TargetBranchNotFound {
@@ -238,12 +403,39 @@ pub async fn create_github_pr(
         Ok(true) => {}
     }
 
-    // Push the branch to GitHub first
-    if let Err(e) = deployment
-        .git()
-        .push_to_github(&worktree_path, &workspace.branch, false)
-    {
-        tracing::error!("Failed to push branch to GitHub: {}", e);
+    // A partial pull request comes from a branch of just the selected changes
+    let selection = match request.selection_id {
+        Some(selection_id) => {
+            let (selection, patch) =
+                partial_merge::selection_patch(&deployment, &workspace, &repo, selection_id)
+                    .await?;
+            let branch = format!("{}-partial", workspace.branch);
+            let signing = deployment.config().read().await.commit_signing.clone();
+            deployment.git().create_partial_branch(
+                &repo_path,
+                &branch,
+                &selection.base_commit,
+                &patch,
+                &request.title,
+                &signing,
+            )?;
+            Some((selection, branch))
+        }
+        None => None,
+    };
+    let head_branch = match &selection {
+        Some((_, branch)) => branch.clone(),
+        None => workspace.branch.clone(),
+    };
+
+    // Push the branch to the code host first
+    if let Err(e) = deployment.git().push_to_remote(
+        &worktree_path,
+        &head_branch,
+        selection.is_some(),
+        auth_header.as_deref(),
+    ) {
+        tracing::error!("Failed to push branch to the code host: {}", e);
         match e {
             GitServiceError::GitCLI(GitCliError::AuthFailed(_)) => {
                 return Ok(ResponseJson(ApiResponse::error_with_data(
@@ -278,81 +470,186 @@ pub async fn create_github_pr(
     } else {
         target_branch
     };
+    let body = match stack::pr_stack_section(pool, &workspace, repo.id).await? {
+        Some(section) => Some(match &request.body {
+            Some(body) if !body.trim().is_empty() => format!("{body}\n\n---\n\n{section}"),
+            _ => section,
+        }),
+        None => request.body.clone(),
+    };
     // Create the PR using GitHub service
     let pr_request = CreatePrRequest {
         title: request.title.clone(),
-        body: request.body.clone(),
-        head_branch: workspace.branch.clone(),
+        body,
+        head_branch,
         base_branch: norm_target_branch_name.clone(),
         draft: request.draft,
     };
-    let github_service = GitHubService::new()?;
-    let repo_info = github_service.get_repo_info(&repo_path).await?;
-    match github_service.create_pr(&repo_info, &pr_request).await {
-        Ok(pr_info) => {
-            // Update the workspace with PR information
-            if let Err(e) = Merge::create_pr(
-                pool,
-                workspace.id,
-                workspace_repo.repo_id,
-                &norm_target_branch_name,
-                pr_info.number,
-                &pr_info.url,
-            )
-            .await
-            {
-                tracing::error!("Failed to update workspace PR status: {}", e);
+    let pr_info = match provider {
+        CodeHostProvider::Github => {
+            let github_service = GitHubService::new()?;
+            let repo_info = github_service.get_repo_info(&repo_path).await?;
+            match github_service.create_pr(&repo_info, &pr_request).await {
+                Ok(pr_info) => pr_info,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to create GitHub PR for attempt {}: {}",
+                        workspace.id,
+                        e
+                    );
+                    return match &e {
+                        GitHubServiceError::GhCliNotInstalled(_) => Ok(ResponseJson(
+                            ApiResponse::error_with_data(CreatePrError::GithubCliNotInstalled),
+                        )),
+                        GitHubServiceError::AuthFailed(_) => Ok(ResponseJson(
+                            ApiResponse::error_with_data(CreatePrError::GithubCliNotLoggedIn),
+                        )),
+                        _ => Err(ApiError::GitHubService(e)),
+                    };
+                }
             }
-
-            // Auto-open PR in browser
-            if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
-                tracing::warn!("Failed to open PR in browser: {}", e);
+        }
+        CodeHostProvider::Gitlab => {
+            let created = async {
+                let (gitlab, project) =
+                    gitlab_for_repo(&deployment, project_id, &repo_path).await?;
+                gitlab.create_merge_request(&project, &pr_request).await
             }
-            deployment
-                .track_if_analytics_allowed(
-                    "github_pr_created",
-                    serde_json::json!({
-                        "workspace_id": workspace.id.to_string(),
-                    }),
-                )
-                .await;
-
-            // Trigger auto-description follow-up if enabled
-            if request.auto_generate_description
-                && let Err(e) = trigger_pr_description_follow_up(
-                    &deployment,
-                    &workspace,
-                    pr_info.number,
-                    &pr_info.url,
-                )
-                .await
-            {
-                tracing::warn!(
-                    "Failed to trigger PR description follow-up for attempt {}: {}",
-                    workspace.id,
-                    e
-                );
+            .await;
+            match created {
+                Ok(pr_info) => pr_info,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to create GitLab merge request for attempt {}: {}",
+                        workspace.id,
+                        e
+                    );
+                    return match &e {
+                        GitLabServiceError::MissingToken(_) | GitLabServiceError::AuthFailed(_) => {
+                            Ok(ResponseJson(ApiResponse::error_with_data(
+                                CreatePrError::GitlabNotLoggedIn,
+                            )))
+                        }
+                        _ => Err(ApiError::GitLabService(e)),
+                    };
+                }
             }
-
-            Ok(ResponseJson(ApiResponse::success(pr_info.url)))
         }
-        Err(e) => {
-            tracing::error!(
-                "Failed to create GitHub PR for attempt {}: {}",
-                workspace.id,
-                e
-            );
-            match &e {
-                GitHubServiceError::GhCliNotInstalled(_) => Ok(ResponseJson(
-                    ApiResponse::error_with_data(CreatePrError::GithubCliNotInstalled),
-                )),
-                GitHubServiceError::AuthFailed(_) => Ok(ResponseJson(
-                    ApiResponse::error_with_data(CreatePrError::GithubCliNotLoggedIn),
-                )),
-                _ => Err(ApiError::GitHubService(e)),
+        CodeHostProvider::Bitbucket => {
+            let created = async {
+                let (bitbucket, bitbucket_repo) =
+                    bitbucket_for_repo(&deployment, project_id, &repo_path).await?;
+                bitbucket.create_pr(&bitbucket_repo, &pr_request).await
+            }
+            .await;
+            match created {
+                Ok(pr_info) => pr_info,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to create Bitbucket PR for attempt {}: {}",
+                        workspace.id,
+                        e
+                    );
+                    return match &e {
+                        BitbucketServiceError::MissingToken(_)
+                        | BitbucketServiceError::AuthFailed(_) => Ok(ResponseJson(
+                            ApiResponse::error_with_data(CreatePrError::BitbucketNotLoggedIn),
+                        )),
+                        _ => Err(ApiError::BitbucketService(e)),
+                    };
+                }
             }
         }
+        CodeHostProvider::AzureDevops => {
+            let created = async {
+                let (azure, link_work_items, azure_repo) =
+                    azure_for_repo(&deployment, project_id, &repo_path).await?;
+                let work_items = match workspace.parent_task(pool).await? {
+                    Some(task) if link_work_items => azure_devops::work_item_ids(&format!(
+                        "{}\n{}",
+                        task.title,
+                        task.description.unwrap_or_default()
+                    )),
+                    _ => Vec::new(),
+                };
+                azure.create_pr(&azure_repo, &pr_request, &work_items).await
+            }
+            .await;
+            match created {
+                Ok(pr_info) => pr_info,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to create Azure DevOps PR for attempt {}: {}",
+                        workspace.id,
+                        e
+                    );
+                    return match &e {
+                        AzureDevOpsServiceError::MissingToken(_)
+                        | AzureDevOpsServiceError::AuthFailed(_) => Ok(ResponseJson(
+                            ApiResponse::error_with_data(CreatePrError::AzureDevopsNotLoggedIn),
+                        )),
+                        _ => Err(ApiError::AzureDevOpsService(e)),
+                    };
+                }
+            }
+        }
+    };
+
+    // Update the workspace with PR information
+    match Merge::create_pr(
+        pool,
+        workspace.id,
+        workspace_repo.repo_id,
+        &norm_target_branch_name,
+        pr_info.number,
+        &pr_info.url,
+    )
+    .await
+    {
+        Ok(pr_merge) => {
+            if let Some((selection, _)) = &selection
+                && let Err(e) = MergeSelection::set_merge_id(pool, selection.id, pr_merge.id).await
+            {
+                tracing::error!("Failed to link selection {} to its PR: {}", selection.id, e);
+            }
+        }
+        Err(e) => tracing::error!("Failed to update workspace PR status: {}", e),
+    }
+
+    // Auto-open PR in browser
+    if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
+        tracing::warn!("Failed to open PR in browser: {}", e);
+    }
+    deployment
+        .track_if_analytics_allowed(
+            "github_pr_created",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "provider": provider,
+                "partial": selection.is_some(),
+            }),
+        )
+        .await;
+
+    // Trigger auto-description follow-up if enabled
+    if request.auto_generate_description
+        && let Err(e) = trigger_pr_description_follow_up(
+            &deployment,
+            &workspace,
+            provider,
+            pr_info.number,
+            &pr_info.url,
+        )
+        .await
+    {
+        tracing::warn!(
+            "Failed to trigger PR description follow-up for attempt {}: {}",
+            workspace.id,
+            e
+        );
     }
+
+    Ok(ResponseJson(ApiResponse::success(pr_info.url)))
 }
 
 pub async fn attach_existing_pr(
@@ -387,13 +684,36 @@ pub async fn attach_existing_pr(
         })));
     }
 
-    let github_service = GitHubService::new()?;
-    let repo_info = github_service.get_repo_info(&repo.path).await?;
-
     // List all PRs for branch (open, closed, and merged)
-    let prs = github_service
-        .list_all_prs_for_branch(&repo_info, &workspace.branch)
-        .await?;
+    let prs = match code_host(pool, &workspace).await? {
+        (_, CodeHostProvider::Github) => {
+            let github_service = GitHubService::new()?;
+            let repo_info = github_service.get_repo_info(&repo.path).await?;
+            github_service
+                .list_all_prs_for_branch(&repo_info, &workspace.branch)
+                .await?
+        }
+        (project_id, CodeHostProvider::Gitlab) => {
+            let (gitlab, project) = gitlab_for_repo(&deployment, project_id, &repo.path).await?;
+            gitlab
+                .list_merge_requests_for_branch(&project, &workspace.branch)
+                .await?
+        }
+        (project_id, CodeHostProvider::Bitbucket) => {
+            let (bitbucket, bitbucket_repo) =
+                bitbucket_for_repo(&deployment, project_id, &repo.path).await?;
+            bitbucket
+                .list_prs_for_branch(&bitbucket_repo, &workspace.branch)
+                .await?
+        }
+        (project_id, CodeHostProvider::AzureDevops) => {
+            let (azure, _, azure_repo) =
+                azure_for_repo(&deployment, project_id, &repo.path).await?;
+            azure
+                .list_prs_for_branch(&azure_repo, &workspace.branch)
+                .await?
+        }
+    };
 
     // Take the first PR (prefer open, but also accept merged/closed)
     if let Some(pr_info) = prs.into_iter().next() {
@@ -486,6 +806,71 @@ pub async fn get_pr_comments(
         }
     };
 
+    let (project_id, provider) = code_host(pool, &workspace).await?;
+    match provider {
+        CodeHostProvider::Github => {}
+        CodeHostProvider::Gitlab => {
+            let comments = async {
+                let (gitlab, project) =
+                    gitlab_for_repo(&deployment, project_id, &repo.path).await?;
+                gitlab.get_mr_comments(&project, pr_info.number).await
+            }
+            .await;
+            return match comments {
+                Ok(comments) => Ok(ResponseJson(ApiResponse::success(PrCommentsResponse {
+                    comments,
+                }))),
+                Err(GitLabServiceError::MissingToken(_) | GitLabServiceError::AuthFailed(_)) => {
+                    Ok(ResponseJson(ApiResponse::error_with_data(
+                        GetPrCommentsError::GitlabNotLoggedIn,
+                    )))
+                }
+                Err(e) => Err(ApiError::GitLabService(e)),
+            };
+        }
+        CodeHostProvider::Bitbucket => {
+            let comments = async {
+                let (bitbucket, bitbucket_repo) =
+                    bitbucket_for_repo(&deployment, project_id, &repo.path).await?;
+                bitbucket
+                    .get_pr_comments(&bitbucket_repo, pr_info.number)
+                    .await
+            }
+            .await;
+            return match comments {
+                Ok(comments) => Ok(ResponseJson(ApiResponse::success(PrCommentsResponse {
+                    comments,
+                }))),
+                Err(
+                    BitbucketServiceError::MissingToken(_) | BitbucketServiceError::AuthFailed(_),
+                ) => Ok(ResponseJson(ApiResponse::error_with_data(
+                    GetPrCommentsError::BitbucketNotLoggedIn,
+                ))),
+                Err(e) => Err(ApiError::BitbucketService(e)),
+            };
+        }
+        CodeHostProvider::AzureDevops => {
+            let comments = async {
+                let (azure, _, azure_repo) =
+                    azure_for_repo(&deployment, project_id, &repo.path).await?;
+                azure.get_pr_comments(&azure_repo, pr_info.number).await
+            }
+            .await;
+            return match comments {
+                Ok(comments) => Ok(ResponseJson(ApiResponse::success(PrCommentsResponse {
+                    comments,
+                }))),
+                Err(
+                    AzureDevOpsServiceError::MissingToken(_)
+                    | AzureDevOpsServiceError::AuthFailed(_),
+                ) => Ok(ResponseJson(ApiResponse::error_with_data(
+                    GetPrCommentsError::AzureDevopsNotLoggedIn,
+                ))),
+                Err(e) => Err(ApiError::AzureDevOpsService(e)),
+            };
+        }
+    }
+
     let github_service = GitHubService::new()?;
     let repo_info = github_service.get_repo_info(&repo.path).await?;
 
@@ -516,3 +901,112 @@ pub async fn get_pr_comments(
         }
     }
 }
+
+/// Merge the attempt's pull request on its code host with the project's
+/// merge strategy
+pub async fn merge_pr(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<MergePrRequest>,
+) -> Result<ResponseJson<ApiResponse<PullRequestInfo>>, ApiError> {
+    let pool = &deployment.db().pool;
+    path_guard::ensure_not_guarded(pool, workspace.id).await?;
+    let (project_id, provider) = code_host(pool, &workspace).await?;
+
+    let pr_merge = match Merge::find_by_workspace_and_repo_id(pool, workspace.id, request.repo_id)
+        .await?
+        .into_iter()
+        .next()
+    {
+        Some(Merge::Pr(pr_merge)) => pr_merge,
+        _ => {
+            return Err(ApiError::BadRequest(
+                "The attempt has no pull request for this repository".to_string(),
+            ));
+        }
+    };
+    if !matches!(pr_merge.pr_info.status, MergeStatus::Open) {
+        return Err(ApiError::Conflict(
+            "The pull request isn't open".to_string(),
+        ));
+    }
+    let repo = Repo::find_by_id(pool, request.repo_id)
+        .await?
+        .ok_or(RepoError::NotFound)?;
+
+    let settings = ProjectSettings::find_by_project_id(pool, project_id).await?;
+    ci_checks::ensure_checks_green(&deployment, &workspace, &repo, &settings).await?;
+    let strategy = settings.merge_strategy;
+    let number = pr_merge.pr_info.number;
+    let pr_info = match provider {
+        CodeHostProvider::Github => {
+            GitHubService::new()?
+                .merge_pr(&pr_merge.pr_info.url, strategy)
+                .await?
+        }
+        CodeHostProvider::Gitlab => {
+            let (gitlab, project) = gitlab_for_repo(&deployment, project_id, &repo.path).await?;
+            gitlab
+                .merge_merge_request(&project, number, strategy)
+                .await?
+        }
+        CodeHostProvider::Bitbucket => {
+            let (bitbucket, bitbucket_repo) =
+                bitbucket_for_repo(&deployment, project_id, &repo.path).await?;
+            bitbucket
+                .merge_pr(&bitbucket_repo, number, strategy)
+                .await?
+        }
+        CodeHostProvider::AzureDevops => {
+            let (azure, _, azure_repo) =
+                azure_for_repo(&deployment, project_id, &repo.path).await?;
+            azure.merge_pr(&azure_repo, number, strategy).await?
+        }
+    };
+    Merge::update_status(
+        pool,
+        pr_merge.id,
+        pr_info.status.clone(),
+        pr_info.merge_commit_sha.clone(),
+    )
+    .await?;
+    Merge::update_merge_strategy(pool, pr_merge.id, strategy).await?;
+
+    if matches!(pr_info.status, MergeStatus::Merged) {
+        Task::update_status(pool, workspace.task_id, TaskStatus::Done).await?;
+        lifecycle_hooks::spawn_hooks(
+            pool.clone(),
+            workspace.clone(),
+            HookEvent::AfterMerge,
+            HookDetails {
+                merge_commit: pr_info.merge_commit_sha.clone(),
+                pr_url: Some(pr_info.url.clone()),
+                ..Default::default()
+            },
+        );
+        if let Ok(publisher) = deployment.share_publisher()
+            && let Err(err) = publisher.update_shared_task_by_id(workspace.task_id).await
+        {
+            tracing::warn!(
+                ?err,
+                "Failed to propagate shared task update for {}",
+                workspace.task_id
+            );
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "pr_merged",
+            serde_json::json!({
+                "task_id": workspace.task_id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "project_id": project_id.to_string(),
+                "provider": provider,
+                "merge_strategy": strategy,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(pr_info)))
+}
@@ -0,0 +1,142 @@
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    execution_process_interruption::ExecutionProcessInterruption,
+    project_repo::ProjectRepo,
+    session::Session,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::actions::{
+    ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+};
+use services::services::{attempt_budget, container::ContainerService};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Sent to the agent when its session is picked up again
+const RESUME_PROMPT: &str = "Your previous run was interrupted because the server stopped. \
+Check the current state of the workspace and continue the task where you left off.";
+
+/// The coding agent run the attempt can be resumed from, if the server
+/// stopped while one was going
+pub async fn get_interruption(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionProcessInterruption>>>, ApiError> {
+    let interruption = ExecutionProcessInterruption::find_open_by_workspace_id(
+        &deployment.db().pool,
+        workspace.id,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(interruption)))
+}
+
+/// Carry on with the interrupted run: a follow-up in its agent session when
+/// the agent reported one, otherwise the same request again
+pub async fn resume_attempt(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let Some(interruption) =
+        ExecutionProcessInterruption::find_open_by_workspace_id(pool, workspace.id).await?
+    else {
+        return Err(ApiError::BadRequest(
+            "No interrupted run to resume in this attempt".to_string(),
+        ));
+    };
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "A process is already running in this attempt".to_string(),
+        ));
+    }
+
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let budget = attempt_budget::attempt_budget(pool, workspace.id, task.project_id, 0).await?;
+    if let Some(limit) = budget.reached {
+        return Err(ApiError::BadRequest(format!(
+            "The attempt reached its limit of {}; extend its budget to continue",
+            attempt_budget::describe_limit(limit, &budget.limits)
+        )));
+    }
+
+    let interrupted = ExecutionProcess::find_by_id(pool, interruption.execution_process_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let session = Session::find_by_id(pool, interrupted.session_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let previous = interrupted
+        .executor_action()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+
+    let agent_session_id =
+        ExecutionProcess::find_latest_coding_agent_turn_session_id(pool, session.id).await?;
+    let action = match agent_session_id {
+        Some(agent_session_id) => {
+            let executor_profile_id =
+                ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?;
+            let project_repos =
+                ProjectRepo::find_by_project_id_with_names(pool, task.project_id).await?;
+            let cleanup_action = deployment
+                .container()
+                .cleanup_actions_for_repos(&project_repos);
+            let working_dir = workspace
+                .agent_working_dir
+                .as_ref()
+                .filter(|dir| !dir.is_empty())
+                .cloned();
+            ExecutorAction::new(
+                ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                    prompt: RESUME_PROMPT.to_string(),
+                    session_id: agent_session_id,
+                    executor_profile_id,
+                    working_dir,
+                    prompt_additions: None,
+                }),
+                cleanup_action.map(Box::new),
+            )
+        }
+        None => previous.clone(),
+    };
+
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+    ExecutionProcessInterruption::mark_resumed(pool, interrupted.id, execution_process.id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "attempt_resumed",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "follow_up": matches!(
+                    action.typ,
+                    ExecutorActionType::CodingAgentFollowUpRequest(_)
+                ),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
@@ -0,0 +1,190 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    task::{CreateTask, Task},
+    task_link::{CreateTaskLink, TaskLink, TaskLinkType},
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    routes::{
+        board_columns::check_wip_limits,
+        tasks::{check_bulk_size, with_wip_warning},
+    },
+};
+
+/// Longest title given to a task made from a comment, in characters
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Markers reviewers start a comment with to say it is work to do
+const TODO_MARKERS: [&str; 4] = ["TODO", "FIXME", "FOLLOW-UP", "FOLLOWUP"];
+
+/// A comment left on a line of the attempt's diff
+#[derive(Debug, Deserialize, TS)]
+pub struct ReviewCommentTask {
+    pub file_path: String,
+    pub line_number: u32,
+    pub text: String,
+    /// The commented line as shown in the diff
+    pub code_line: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateReviewTasksRequest {
+    /// The comments to turn into tasks, one task each
+    pub comments: Vec<ReviewCommentTask>,
+}
+
+/// Create a task for each review comment in the attempt's project. The new
+/// tasks start from the attempt and follow up its task.
+pub async fn create_review_tasks(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateReviewTasksRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    check_bulk_size(payload.comments.len())?;
+    if payload
+        .comments
+        .iter()
+        .any(|comment| task_title(&comment.text).is_empty())
+    {
+        return Err(ApiError::BadRequest(
+            "Every comment needs some text".to_string(),
+        ));
+    }
+
+    let pool = &deployment.db().pool;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let new_tasks: Vec<CreateTask> = payload
+        .comments
+        .iter()
+        .map(|comment| CreateTask {
+            parent_workspace_id: Some(workspace.id),
+            ..CreateTask::from_title_description(
+                task.project_id,
+                task_title(&comment.text),
+                Some(task_description(comment, &task, &workspace)),
+            )
+        })
+        .collect();
+
+    let added: Vec<_> = new_tasks
+        .iter()
+        .map(|data| (data.status.clone().unwrap_or_default(), None))
+        .collect();
+    let wip_warning = check_wip_limits(pool, task.project_id, &added).await?;
+
+    // Created last to first so that they end up in the order of the comments
+    let mut tx = pool.begin().await?;
+    let mut created = Vec::with_capacity(new_tasks.len());
+    for data in new_tasks.iter().rev() {
+        let follow_up = Task::create(&mut *tx, data, Uuid::new_v4()).await?;
+        TaskLink::create(
+            &mut *tx,
+            follow_up.id,
+            &CreateTaskLink {
+                linked_task_id: task.id,
+                link_type: TaskLinkType::FollowsUp,
+            },
+        )
+        .await?;
+        created.push(follow_up);
+    }
+    tx.commit().await?;
+    created.reverse();
+
+    deployment
+        .track_if_analytics_allowed(
+            "review_tasks_created",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "task_count": created.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(with_wip_warning(created, wip_warning)))
+}
+
+/// First line of the comment without its TODO marker, shortened to fit
+fn task_title(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let line = strip_todo_marker(line);
+    if line.chars().count() <= MAX_TITLE_CHARS {
+        return line.to_string();
+    }
+    let shortened: String = line.chars().take(MAX_TITLE_CHARS - 1).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+/// "TODO: rename this" and "fixme - rename this" both become "rename this"
+fn strip_todo_marker(line: &str) -> &str {
+    for marker in TODO_MARKERS {
+        let Some(head) = line.get(..marker.len()) else {
+            continue;
+        };
+        if !head.eq_ignore_ascii_case(marker) {
+            continue;
+        }
+        let rest = &line[marker.len()..];
+        if rest.starts_with(|c: char| c.is_alphanumeric()) {
+            continue;
+        }
+        let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ':' || c == '-');
+        if !rest.is_empty() {
+            return rest;
+        }
+    }
+    line
+}
+
+fn task_description(comment: &ReviewCommentTask, task: &Task, workspace: &Workspace) -> String {
+    let mut description = format!(
+        "Follow-up from reviewing the attempt on `{}` for '{}'.\n\n**{}** (Line {})\n",
+        workspace.branch, task.title, comment.file_path, comment.line_number
+    );
+    if let Some(code_line) = comment.code_line.as_deref().filter(|line| !line.is_empty()) {
+        description.push_str(&format!("```\n{code_line}\n```\n"));
+    }
+    for line in comment.text.trim().lines() {
+        description.push_str(&format!("\n> {line}"));
+    }
+    description.push('\n');
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn titles_drop_the_todo_marker() {
+        assert_eq!(
+            task_title("TODO: handle the empty list"),
+            "handle the empty list"
+        );
+        assert_eq!(task_title("\n  fixme - retry on 503\nmore"), "retry on 503");
+        assert_eq!(task_title("Todos are shown twice"), "Todos are shown twice");
+        assert_eq!(task_title("TODO"), "TODO");
+        assert_eq!(task_title("   "), "");
+
+        let long = task_title(&format!("TODO: {}", "word ".repeat(30)));
+        assert_eq!(long.chars().count(), MAX_TITLE_CHARS);
+        assert!(long.ends_with('…'));
+    }
+}
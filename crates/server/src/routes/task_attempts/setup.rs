@@ -0,0 +1,224 @@
+use std::path::Path;
+
+use axum::{
+    Extension,
+    extract::{Json, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    project_repo::ProjectRepo,
+    project_settings::ProjectSettings,
+    session::{CreateSession, Session},
+    task::Task,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    workspace_setup::{self, SetupStatus, WorkspaceSetup},
+};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RetrySetupRequest {
+    /// Agent to start once the scripts succeed, if it hasn't been started yet
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct StartAgentAnywayRequest {
+    pub executor_profile_id: ExecutorProfileId,
+}
+
+pub async fn get_setup(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WorkspaceSetup>>, ApiError> {
+    let setup = workspace_setup::load(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(setup)))
+}
+
+/// Run the setup scripts again, continuing with the agent when they succeed if
+/// a failed setup kept it from starting
+pub async fn retry_setup(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RetrySetupRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+        .await?
+    {
+        return Err(ApiError::Conflict(
+            "A process is already running in this attempt".to_string(),
+        ));
+    }
+
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project_repos = ProjectRepo::find_by_project_id_with_names(pool, task.project_id).await?;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+
+    // Submodules and LFS objects are brought in again too, in case that's
+    // what failed
+    let provisioning_settings = ProjectSettings::find_by_project_id(pool, task.project_id)
+        .await?
+        .git_provisioning;
+    let provisioning = deployment.container().provisioning_actions_for_repos(
+        Path::new(&container_ref),
+        &project_repos,
+        &provisioning_settings,
+    );
+    let setup_action = match (
+        provisioning,
+        deployment
+            .container()
+            .setup_actions_for_repos(&project_repos),
+    ) {
+        (Some(provisioning), Some(scripts)) => provisioning.append_action(scripts),
+        (Some(action), None) | (None, Some(action)) => action,
+        (None, None) => {
+            return Err(ApiError::BadRequest(
+                "The project has no setup scripts".to_string(),
+            ));
+        }
+    };
+
+    let agent_started = workspace_setup::load(pool, workspace.id)
+        .await?
+        .agent_started;
+    let agent_profile = payload.executor_profile_id.filter(|_| !agent_started);
+    let starts_agent = agent_profile.is_some();
+    let executor = agent_profile
+        .as_ref()
+        .map_or("setup-script".to_string(), |profile| {
+            profile.executor.to_string()
+        });
+    let action = match agent_profile {
+        Some(profile) => setup_action.append_action(deployment.container().initial_agent_action(
+            &task,
+            &workspace,
+            &project_repos,
+            profile,
+        )),
+        None => setup_action,
+    };
+
+    let session = latest_or_new_session(&deployment, &workspace, executor).await?;
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::SetupScript,
+        )
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "setup_script_retried",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+                "starts_agent": starts_agent,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
+/// Start the agent although the setup scripts failed
+pub async fn start_agent_anyway(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<StartAgentAnywayRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let setup = workspace_setup::load(pool, workspace.id).await?;
+    if setup.agent_started {
+        return Err(ApiError::Conflict(
+            "The agent has already been started in this attempt".to_string(),
+        ));
+    }
+    if setup.status != SetupStatus::Failed {
+        return Err(ApiError::Conflict(
+            "Only a failed setup can be overridden".to_string(),
+        ));
+    }
+
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project_repos = ProjectRepo::find_by_project_id_with_names(pool, task.project_id).await?;
+
+    deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+
+    let executor = payload.executor_profile_id.executor.to_string();
+    let action = deployment.container().initial_agent_action(
+        &task,
+        &workspace,
+        &project_repos,
+        payload.executor_profile_id,
+    );
+    let session = latest_or_new_session(&deployment, &workspace, executor).await?;
+    let execution_process = deployment
+        .container()
+        .start_execution(
+            &workspace,
+            &session,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "setup_failure_overridden",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "workspace_id": workspace.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
+async fn latest_or_new_session(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+    executor: String,
+) -> Result<Session, ApiError> {
+    let pool = &deployment.db().pool;
+    if let Some(session) = Session::find_latest_by_workspace_id(pool, workspace.id).await? {
+        return Ok(session);
+    }
+    Ok(Session::create(
+        pool,
+        &CreateSession {
+            executor: Some(executor),
+        },
+        Uuid::new_v4(),
+        workspace.id,
+    )
+    .await?)
+}
@@ -0,0 +1,293 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    execution_process::ExecutionProcess, merge::Merge, repo::Repo, task::Task,
+    workspace::Workspace, workspace_repo::WorkspaceRepo, workspace_stack::WorkspaceStack,
+};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::container::ContainerService;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::task_attempts::WorkspaceRepoInput};
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct StackedAttempt {
+    pub workspace_id: Uuid,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub branch: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptStack {
+    /// The attempts this one is stacked on, the bottom of the stack first
+    pub ancestors: Vec<StackedAttempt>,
+    /// The attempts stacked directly on this one
+    pub children: Vec<StackedAttempt>,
+}
+
+/// The attempts this one is stacked on, the bottom of the stack first
+async fn ancestors(pool: &SqlitePool, workspace: &Workspace) -> Result<Vec<Workspace>, ApiError> {
+    let mut ancestors = Vec::new();
+    let mut seen = HashSet::from([workspace.id]);
+    let mut current = workspace.id;
+    while let Some(parent_id) = WorkspaceStack::find_parent_id(pool, current).await? {
+        if !seen.insert(parent_id) {
+            break;
+        }
+        let Some(parent) = Workspace::find_by_id(pool, parent_id).await? else {
+            break;
+        };
+        current = parent.id;
+        ancestors.push(parent);
+    }
+    ancestors.reverse();
+    Ok(ancestors)
+}
+
+async fn stacked_attempt(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+) -> Result<StackedAttempt, ApiError> {
+    let task_title = workspace
+        .parent_task(pool)
+        .await?
+        .map(|task| task.title)
+        .unwrap_or_default();
+    Ok(StackedAttempt {
+        workspace_id: workspace.id,
+        task_id: workspace.task_id,
+        task_title,
+        branch: workspace.branch.clone(),
+    })
+}
+
+pub async fn get_attempt_stack(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptStack>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let mut stack = AttemptStack {
+        ancestors: Vec::new(),
+        children: Vec::new(),
+    };
+    for ancestor in ancestors(pool, &workspace).await? {
+        stack
+            .ancestors
+            .push(stacked_attempt(pool, &ancestor).await?);
+    }
+    for child_id in WorkspaceStack::find_child_ids(pool, workspace.id).await? {
+        if let Some(child) = Workspace::find_by_id(pool, child_id).await? {
+            stack.children.push(stacked_attempt(pool, &child).await?);
+        }
+    }
+    Ok(ResponseJson(ApiResponse::success(stack)))
+}
+
+/// The repositories of an attempt stacked on another, each targeting the
+/// parent's branch
+pub async fn stacked_repos(
+    pool: &SqlitePool,
+    task: &Task,
+    parent_workspace_id: Uuid,
+    repos: &[WorkspaceRepoInput],
+) -> Result<Vec<WorkspaceRepoInput>, ApiError> {
+    let parent = Workspace::find_by_id(pool, parent_workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("The attempt to stack on doesn't exist".to_string()))?;
+    let parent_task = parent.parent_task(pool).await?;
+    if parent_task.map(|t| t.project_id) != Some(task.project_id) {
+        return Err(ApiError::BadRequest(
+            "Attempts can only be stacked on attempts of the same project".to_string(),
+        ));
+    }
+    let parent_repo_ids: HashSet<Uuid> = WorkspaceRepo::find_by_workspace_id(pool, parent.id)
+        .await?
+        .into_iter()
+        .map(|wr| wr.repo_id)
+        .collect();
+    target_parent_branch(&parent.branch, &parent_repo_ids, repos)
+}
+
+/// Point each repository at the parent's branch, which all of them must have
+fn target_parent_branch(
+    parent_branch: &str,
+    parent_repo_ids: &HashSet<Uuid>,
+    repos: &[WorkspaceRepoInput],
+) -> Result<Vec<WorkspaceRepoInput>, ApiError> {
+    repos
+        .iter()
+        .map(|repo| {
+            if parent_repo_ids.contains(&repo.repo_id) {
+                Ok(WorkspaceRepoInput {
+                    repo_id: repo.repo_id,
+                    target_branch: parent_branch.to_string(),
+                })
+            } else {
+                Err(ApiError::BadRequest(format!(
+                    "The attempt to stack on doesn't include repository {}",
+                    repo.repo_id
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Rebase the attempts stacked on this one onto its rebased branch, and the
+/// ones stacked on them in turn. A child that conflicts is left mid-rebase for
+/// its conflicts to be resolved, and the ones stacked on it aren't touched.
+/// Returns how many were rebased.
+pub async fn restack_children(
+    deployment: &DeploymentImpl,
+    parent: &Workspace,
+    repo: &Repo,
+    old_head: &str,
+) -> Result<usize, ApiError> {
+    let pool = &deployment.db().pool;
+    let mut rebased = 0;
+    let mut pending = vec![(parent.clone(), old_head.to_string())];
+    while let Some((parent, old_head)) = pending.pop() {
+        for child_id in WorkspaceStack::find_child_ids(pool, parent.id).await? {
+            let Some(child) = Workspace::find_by_id(pool, child_id).await? else {
+                continue;
+            };
+            let Some(child_repo) =
+                WorkspaceRepo::find_by_workspace_and_repo_id(pool, child.id, repo.id).await?
+            else {
+                continue;
+            };
+            // The child was moved to another base since
+            if child_repo.target_branch != parent.branch {
+                continue;
+            }
+            if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, child.id)
+                .await?
+            {
+                tracing::warn!(
+                    "Not rebasing stacked attempt {} while a process runs in it",
+                    child.id
+                );
+                continue;
+            }
+
+            let container_ref = deployment
+                .container()
+                .ensure_container_exists(&child)
+                .await?;
+            let worktree_path = PathBuf::from(container_ref).join(&repo.name);
+            let child_old_head = deployment.git().get_head_info(&worktree_path)?.oid;
+            match deployment.git().rebase_branch(
+                &repo.path,
+                &worktree_path,
+                &parent.branch,
+                &old_head,
+                &child.branch,
+            ) {
+                Ok(_) => {
+                    rebased += 1;
+                    pending.push((child, child_old_head));
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to rebase stacked attempt {} onto '{}': {}",
+                        child.id,
+                        parent.branch,
+                        e
+                    );
+                }
+            }
+        }
+    }
+    Ok(rebased)
+}
+
+/// Section added to the pull request of a stacked attempt, listing the stack
+/// in order with the pull requests already opened for it
+pub async fn pr_stack_section(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    repo_id: Uuid,
+) -> Result<Option<String>, ApiError> {
+    let mut stack = Vec::new();
+    for ancestor in ancestors(pool, workspace).await? {
+        let pr_url = Merge::find_by_workspace_and_repo_id(pool, ancestor.id, repo_id)
+            .await?
+            .into_iter()
+            .find_map(|merge| match merge {
+                Merge::Pr(pr) => Some(pr.pr_info.url),
+                Merge::Direct(_) => None,
+            });
+        stack.push((ancestor.branch, pr_url));
+    }
+    Ok(stack_section(&stack))
+}
+
+/// The stack section for the given ancestors, each a branch with the URL of
+/// its pull request if one was opened
+fn stack_section(ancestors: &[(String, Option<String>)]) -> Option<String> {
+    if ancestors.is_empty() {
+        return None;
+    }
+    let mut lines = vec!["This pull request is part of a stack:".to_string()];
+    for (i, (branch, pr_url)) in ancestors.iter().enumerate() {
+        lines.push(match pr_url {
+            Some(url) => format!("{}. {url}", i + 1),
+            None => format!("{}. `{branch}`", i + 1),
+        });
+    }
+    lines.push(format!("{}. This pull request", ancestors.len() + 1));
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_section_lists_ancestors_bottom_first() {
+        assert_eq!(stack_section(&[]), None);
+        let ancestors = [
+            (
+                "vk/1a2b-api".to_string(),
+                Some("https://github.com/acme/app/pull/41".to_string()),
+            ),
+            ("vk/3c4d-ui".to_string(), None),
+        ];
+        assert_eq!(
+            stack_section(&ancestors).unwrap(),
+            "This pull request is part of a stack:\n\
+             1. https://github.com/acme/app/pull/41\n\
+             2. `vk/3c4d-ui`\n\
+             3. This pull request"
+        );
+    }
+
+    #[test]
+    fn stacked_repos_target_the_parent_branch() {
+        let (api, ui) = (Uuid::new_v4(), Uuid::new_v4());
+        let input = |repo_id| WorkspaceRepoInput {
+            repo_id,
+            target_branch: "main".to_string(),
+        };
+
+        let repos =
+            target_parent_branch("vk/1a2b-api", &HashSet::from([api, ui]), &[input(api)]).unwrap();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].repo_id, api);
+        assert_eq!(repos[0].target_branch, "vk/1a2b-api");
+
+        assert!(matches!(
+            target_parent_branch(
+                "vk/1a2b-api",
+                &HashSet::from([api]),
+                &[input(api), input(ui)]
+            ),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use std::{path::PathBuf, sync::Arc};
+
+use axum::{
+    Extension, Router,
+    extract::{
+        Json, Path, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Json as ResponseJson},
+    routing::{delete, get, post},
+};
+use db::models::{project_settings::ProjectSettings, task::Task, workspace::Workspace};
+use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt};
+use services::services::{
+    container::{ContainerService, WorkspaceEnv},
+    terminal::{
+        OpenTerminalRequest, TerminalClientMessage, TerminalEvent, TerminalSession,
+        TerminalSessionInfo, TerminalSize,
+    },
+    workspace_files,
+};
+use sqlx::Error as SqlxError;
+use tokio::sync::broadcast::error::RecvError;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_workspace_middleware};
+
+pub async fn list_terminals(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TerminalSessionInfo>>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(
+        deployment.terminals().list(workspace.id),
+    )))
+}
+
+/// Start a shell in the attempt's workspace, inside its container when the
+/// project uses one. Attach to it through the session's WebSocket. Terminals
+/// are only available when the project's exec policy allows running a shell.
+pub async fn open_terminal(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<OpenTerminalRequest>,
+) -> Result<ResponseJson<ApiResponse<TerminalSessionInfo>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let workspace_path = PathBuf::from(container_ref);
+    let working_dir = match request
+        .working_dir
+        .as_deref()
+        .filter(|dir| !dir.trim_matches('/').is_empty())
+    {
+        Some(dir) => workspace_files::validate_target_path(&workspace_path, dir)?,
+        None => workspace_path,
+    };
+    if !working_dir.is_dir() {
+        return Err(ApiError::BadRequest(
+            "Working directory does not exist".to_string(),
+        ));
+    }
+
+    let WorkspaceEnv { env, secret_values } =
+        deployment.container().workspace_env(&workspace).await?;
+    let size = TerminalSize {
+        cols: request.cols,
+        rows: request.rows,
+    };
+    let info = deployment.terminals().open(
+        workspace.id,
+        &working_dir,
+        &env,
+        &settings.exec_policy,
+        secret_values,
+        size,
+    )?;
+    let _ = Workspace::touch(pool, workspace.id).await;
+    Ok(ResponseJson(ApiResponse::success(info)))
+}
+
+pub async fn resize_terminal(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, session_id)): Path<(Uuid, Uuid)>,
+    Json(size): Json<TerminalSize>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    deployment
+        .terminals()
+        .get(workspace_id, session_id)?
+        .resize(size)?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn close_terminal(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, session_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    deployment.terminals().close(workspace_id, session_id)?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Attach to a session. The scrollback is replayed first so a reloaded page
+/// picks up where it left off; output then follows as binary frames. Clients
+/// send [`TerminalClientMessage`]s as JSON text frames.
+pub async fn terminal_ws(
+    ws: WebSocketUpgrade,
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, session_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let session = deployment.terminals().get(workspace_id, session_id)?;
+    Ok(ws.on_upgrade(move |socket| async move {
+        let (scrollback, events) = session.attach();
+        if let Err(e) = handle_terminal_ws(socket, &session, scrollback, events).await {
+            tracing::debug!("terminal WS closed: {}", e);
+        }
+        session.detach();
+    }))
+}
+
+async fn handle_terminal_ws(
+    socket: WebSocket,
+    session: &Arc<TerminalSession>,
+    scrollback: Vec<u8>,
+    mut events: tokio::sync::broadcast::Receiver<TerminalEvent>,
+) -> anyhow::Result<()> {
+    let (mut sender, mut receiver) = socket.split();
+    if !scrollback.is_empty() {
+        sender.send(Message::Binary(scrollback.into())).await?;
+    }
+    if let Some(code) = session.exit_code() {
+        send_exit(&mut sender, code).await?;
+        return Ok(());
+    }
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(TerminalEvent::Output(data)) => {
+                    sender.send(Message::Binary(data.into())).await?;
+                }
+                Ok(TerminalEvent::Exit(code)) => {
+                    send_exit(&mut sender, code).await?;
+                    break;
+                }
+                // The client fell behind; it only misses output, so keep going
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+            msg = receiver.next() => {
+                let Some(Ok(msg)) = msg else { break };
+                let Message::Text(text) = msg else { continue };
+                match serde_json::from_str::<TerminalClientMessage>(&text)? {
+                    TerminalClientMessage::Input { data } => {
+                        let session = session.clone();
+                        tokio::task::spawn_blocking(move || session.write(data.as_bytes()))
+                            .await??;
+                    }
+                    TerminalClientMessage::Resize { cols, rows } => {
+                        session.resize(TerminalSize { cols, rows })?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn send_exit(
+    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    code: Option<u32>,
+) -> anyhow::Result<()> {
+    let message = serde_json::json!({ "type": "exit", "code": code });
+    sender
+        .send(Message::Text(message.to_string().into()))
+        .await?;
+    sender.close().await?;
+    Ok(())
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let workspace_router = Router::new()
+        .route("/", get(list_terminals).post(open_terminal))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_workspace_middleware,
+        ));
+
+    let session_router = Router::new()
+        .route("/{session_id}", delete(close_terminal))
+        .route("/{session_id}/resize", post(resize_terminal))
+        .route("/{session_id}/ws", get(terminal_ws));
+
+    workspace_router.merge(session_router)
+}
@@ -0,0 +1,107 @@
+use axum::{
+    Extension, Json, Router,
+    extract::State,
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{delete, post},
+};
+use db::models::{
+    project_settings::ProjectSettings,
+    task::{Task, TaskStatus},
+    task_checklist_item::{CreateTaskChecklistItem, TaskChecklistItem},
+};
+use deployment::Deployment;
+use sqlx::SqlitePool;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_task_checklist_item_middleware};
+
+/// Refuse to move the task to done while its checklist has unchecked items,
+/// when the project's definition of done asks for that
+pub(crate) async fn check_definition_of_done(
+    pool: &SqlitePool,
+    task: &Task,
+    status: &TaskStatus,
+) -> Result<(), ApiError> {
+    if *status != TaskStatus::Done || task.status == TaskStatus::Done {
+        return Ok(());
+    }
+    if !ProjectSettings::find_by_project_id(pool, task.project_id)
+        .await?
+        .definition_of_done
+        .block_done_while_unchecked
+    {
+        return Ok(());
+    }
+    match TaskChecklistItem::count_unchecked(pool, task.id).await? {
+        0 => Ok(()),
+        unchecked => Err(ApiError::Conflict(format!(
+            "'{}' has {unchecked} unchecked checklist item(s)",
+            task.title
+        ))),
+    }
+}
+
+pub async fn get_task_checklist(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskChecklistItem>>>, ApiError> {
+    let items = TaskChecklistItem::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(items)))
+}
+
+pub async fn create_task_checklist_item(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskChecklistItem>,
+) -> Result<ResponseJson<ApiResponse<TaskChecklistItem>>, ApiError> {
+    if payload.label.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Checklist items need a label".to_string(),
+        ));
+    }
+    let item = TaskChecklistItem::create(&deployment.db().pool, task.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(item)))
+}
+
+pub async fn toggle_task_checklist_item(
+    Extension(item): Extension<TaskChecklistItem>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskChecklistItem>>, ApiError> {
+    let item =
+        TaskChecklistItem::set_checked(&deployment.db().pool, item.id, !item.checked).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_checklist_item_toggled",
+            serde_json::json!({
+                "task_id": item.task_id.to_string(),
+                "checked": item.checked,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(item)))
+}
+
+pub async fn delete_task_checklist_item(
+    Extension(item): Extension<TaskChecklistItem>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if TaskChecklistItem::delete(&deployment.db().pool, item.id).await? == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let item_router = Router::new()
+        .route("/", delete(delete_task_checklist_item))
+        .route("/toggle", post(toggle_task_checklist_item))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_task_checklist_item_middleware,
+        ));
+
+    Router::new().nest("/checklist-items/{checklist_item_id}", item_router)
+}
@@ -0,0 +1,87 @@
+use axum::{
+    Extension, Json, Router, extract::State, middleware::from_fn_with_state,
+    response::Json as ResponseJson, routing::delete,
+};
+use db::models::{
+    task::Task,
+    task_link::{CreateTaskLink, LinkedTask, TaskLink},
+};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_task_link_middleware};
+
+pub async fn get_task_links(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<LinkedTask>>>, ApiError> {
+    let links = TaskLink::find_linked_tasks(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(links)))
+}
+
+/// Link the task to another task of the same project
+pub async fn create_task_link(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskLink>,
+) -> Result<ResponseJson<ApiResponse<TaskLink>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if payload.linked_task_id == task.id {
+        return Err(ApiError::BadRequest(
+            "A task can't be linked to itself".to_string(),
+        ));
+    }
+    let linked = Task::find_by_id(pool, payload.linked_task_id)
+        .await?
+        .filter(|linked| linked.project_id == task.project_id)
+        .ok_or_else(|| {
+            ApiError::BadRequest("Tasks can only be linked within a project".to_string())
+        })?;
+    // One link of each type between two tasks, whichever way it points
+    if TaskLink::find_by_task_id(pool, task.id)
+        .await?
+        .iter()
+        .any(|link| link.link_type == payload.link_type && link.joins(task.id, linked.id))
+    {
+        return Err(ApiError::Conflict(format!(
+            "The task is already linked to '{}' that way",
+            linked.title
+        )));
+    }
+
+    let link = TaskLink::create(pool, task.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_link_created",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "linked_task_id": linked.id.to_string(),
+                "link_type": link.link_type,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(link)))
+}
+
+pub async fn delete_task_link(
+    Extension(link): Extension<TaskLink>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if TaskLink::delete(&deployment.db().pool, link.id).await? == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let link_router = Router::new()
+        .route("/", delete(delete_task_link))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_task_link_middleware,
+        ));
+
+    Router::new().nest("/task-links/{link_id}", link_router)
+}
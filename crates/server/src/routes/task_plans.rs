@@ -0,0 +1,100 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    project::Project,
+    task::{Task, TaskStatus},
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::task_plan::{self, PlannedTask};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    routes::{
+        board_columns::check_wip_limits,
+        tasks::{check_bulk_size, with_wip_warning},
+    },
+};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct PreviewPlanRequest {
+    pub markdown: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct PlanPreview {
+    pub tasks: Vec<PlannedTask>,
+    /// Tasks that would be created, subtasks included
+    pub task_count: usize,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreatePlanTasksRequest {
+    /// The previewed tasks, edited as needed
+    pub tasks: Vec<PlannedTask>,
+    /// Create the plan's top-level tasks as subtasks of this task
+    pub parent_task_id: Option<Uuid>,
+}
+
+/// Tasks the markdown plan would turn into, without creating them
+pub async fn preview_plan(
+    Json(payload): Json<PreviewPlanRequest>,
+) -> Result<ResponseJson<ApiResponse<PlanPreview>>, ApiError> {
+    let tasks = task_plan::parse_plan(&payload.markdown);
+    let task_count = tasks.iter().map(PlannedTask::count).sum();
+    Ok(ResponseJson(ApiResponse::success(PlanPreview {
+        tasks,
+        task_count,
+    })))
+}
+
+/// Create the tasks of a previewed plan; either all are created or none
+pub async fn create_plan_tasks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreatePlanTasksRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let statuses = task_plan::statuses(&payload.tasks);
+    check_bulk_size(statuses.len())?;
+    if has_blank_title(&payload.tasks) {
+        return Err(ApiError::BadRequest("Every task needs a title".to_string()));
+    }
+    let pool = &deployment.db().pool;
+    if let Some(parent_task_id) = payload.parent_task_id
+        && !Task::find_by_id(pool, parent_task_id)
+            .await?
+            .is_some_and(|parent| parent.project_id == project.id)
+    {
+        return Err(ApiError::BadRequest(
+            "Subtasks must belong to a task of the same project".to_string(),
+        ));
+    }
+
+    let added: Vec<(TaskStatus, Option<Uuid>)> =
+        statuses.into_iter().map(|status| (status, None)).collect();
+    let wip_warning = check_wip_limits(pool, project.id, &added).await?;
+    let created =
+        task_plan::create_tasks(pool, project.id, payload.parent_task_id, &payload.tasks).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "plan_tasks_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "task_count": created.len(),
+                "top_level_count": payload.tasks.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(with_wip_warning(created, wip_warning)))
+}
+
+fn has_blank_title(tasks: &[PlannedTask]) -> bool {
+    tasks
+        .iter()
+        .any(|task| task.title.trim().is_empty() || has_blank_title(&task.subtasks))
+}
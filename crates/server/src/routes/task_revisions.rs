@@ -0,0 +1,221 @@
+use axum::{
+    Extension,
+    extract::{Path, State},
+    http::{HeaderMap, header},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    task::Task,
+    task_revision::{CreateTaskRevision, TaskRevision, TaskRevisionAuthor},
+};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::share::ShareError;
+use ts_rs::TS;
+use utils::{api::oauth::LoginStatus, diff::create_unified_diff, response::ApiResponse};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, mcp::task_server::MCP_USER_AGENT};
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskRevisionEntry {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub revision: TaskRevision,
+    /// Title before this revision, when the revision changed it
+    pub previous_title: Option<String>,
+    /// Unified diff of the description against the revision before, when it
+    /// changed
+    pub description_diff: Option<String>,
+}
+
+/// History entries for revisions given oldest first, returned newest first
+fn revision_entries(revisions: Vec<TaskRevision>) -> Vec<TaskRevisionEntry> {
+    let mut entries = Vec::with_capacity(revisions.len());
+    let mut previous: Option<&TaskRevision> = None;
+    for revision in &revisions {
+        let (previous_title, description_diff) = match previous {
+            Some(previous) => {
+                let old = previous.description.as_deref().unwrap_or("");
+                let new = revision.description.as_deref().unwrap_or("");
+                (
+                    (previous.title != revision.title).then(|| previous.title.clone()),
+                    (old != new).then(|| create_unified_diff("description", old, new)),
+                )
+            }
+            None => (None, None),
+        };
+        entries.push(TaskRevisionEntry {
+            revision: revision.clone(),
+            previous_title,
+            description_diff,
+        });
+        previous = Some(revision);
+    }
+    entries.reverse();
+    entries
+}
+
+/// Who made a request: coding agents edit tasks through the MCP server, which
+/// identifies itself by its user agent
+async fn request_author(
+    deployment: &DeploymentImpl,
+    headers: &HeaderMap,
+) -> (TaskRevisionAuthor, Option<String>) {
+    let from_mcp = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|user_agent| user_agent == MCP_USER_AGENT);
+    if from_mcp {
+        return (TaskRevisionAuthor::Agent, None);
+    }
+    match deployment.get_login_status().await {
+        LoginStatus::LoggedIn { profile } => (
+            TaskRevisionAuthor::User,
+            Some(profile.username.unwrap_or(profile.email)),
+        ),
+        LoginStatus::LoggedOut => (TaskRevisionAuthor::User, None),
+    }
+}
+
+/// Record the task's new title and description if an edit changed them. The
+/// first recorded edit also records what the task said before it.
+pub(crate) async fn record_edit(
+    deployment: &DeploymentImpl,
+    headers: &HeaderMap,
+    before: &Task,
+    after: &Task,
+) -> Result<(), ApiError> {
+    if before.title == after.title && before.description == after.description {
+        return Ok(());
+    }
+    let pool = &deployment.db().pool;
+    if !TaskRevision::exists_for_task(pool, before.id).await? {
+        TaskRevision::create(
+            pool,
+            before.id,
+            &CreateTaskRevision {
+                title: &before.title,
+                description: before.description.as_deref(),
+                author: None,
+                author_name: None,
+                created_at: Some(before.created_at),
+            },
+        )
+        .await?;
+    }
+    let (author, author_name) = request_author(deployment, headers).await;
+    TaskRevision::create(
+        pool,
+        after.id,
+        &CreateTaskRevision {
+            title: &after.title,
+            description: after.description.as_deref(),
+            author: Some(author),
+            author_name: author_name.as_deref(),
+            created_at: None,
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Edit history of the task's title and description, newest first
+pub async fn get_task_revisions(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskRevisionEntry>>>, ApiError> {
+    let revisions = TaskRevision::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(revision_entries(
+        revisions,
+    ))))
+}
+
+/// Put the task's title and description back to what a revision had
+pub async fn restore_task_revision(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, revision_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let revision = TaskRevision::find_by_id(pool, revision_id)
+        .await?
+        .filter(|revision| revision.task_id == task.id)
+        .ok_or_else(|| ApiError::BadRequest("Revision not found for this task".to_string()))?;
+
+    let restored = Task::update(
+        pool,
+        task.id,
+        task.project_id,
+        revision.title.clone(),
+        revision.description.clone(),
+        task.status.clone(),
+        task.parent_workspace_id,
+        task.priority,
+    )
+    .await?;
+    record_edit(&deployment, &headers, &task, &restored).await?;
+
+    if restored.shared_task_id.is_some() {
+        let Ok(publisher) = deployment.share_publisher() else {
+            return Err(ShareError::MissingConfig("share publisher unavailable").into());
+        };
+        publisher.update_shared_task(&restored).await?;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_revision_restored",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "revision_id": revision.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(restored)))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+
+    fn revision(title: &str, description: Option<&str>, minutes_ago: i64) -> TaskRevision {
+        TaskRevision {
+            id: Uuid::new_v4(),
+            task_id: Uuid::nil(),
+            title: title.to_string(),
+            description: description.map(str::to_string),
+            author: Some(TaskRevisionAuthor::User),
+            author_name: None,
+            created_at: Utc::now() - Duration::minutes(minutes_ago),
+        }
+    }
+
+    #[test]
+    fn entries_describe_each_change_newest_first() {
+        let revisions = vec![
+            revision("Add login", Some("Use OAuth"), 30),
+            revision("Add login page", Some("Use OAuth"), 20),
+            revision("Add login page", Some("Use OAuth\nand remember me"), 10),
+        ];
+        let newest = revisions[2].id;
+
+        let entries = revision_entries(revisions);
+        assert_eq!(entries[0].revision.id, newest);
+        assert_eq!(entries[0].previous_title, None);
+        assert!(
+            entries[0]
+                .description_diff
+                .as_deref()
+                .is_some_and(|diff| diff.contains("+and remember me"))
+        );
+        assert_eq!(entries[1].previous_title.as_deref(), Some("Add login"));
+        assert_eq!(entries[1].description_diff, None);
+        assert_eq!(entries[2].previous_title, None);
+        assert_eq!(entries[2].description_diff, None);
+    }
+}
@@ -0,0 +1,148 @@
+use axum::{
+    Extension, Json, Router,
+    extract::State,
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post, put},
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    project::Project,
+    task_schedule::{CreateTaskSchedule, TaskSchedule, TaskScheduleRun},
+};
+use deployment::Deployment;
+use services::services::task_scheduler;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_task_schedule_middleware};
+
+/// Runs listed per schedule
+const RUN_HISTORY_LIMIT: i64 = 50;
+
+pub async fn get_schedules(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskSchedule>>>, ApiError> {
+    let schedules = TaskSchedule::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(schedules)))
+}
+
+pub async fn create_schedule(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskSchedule>,
+) -> Result<ResponseJson<ApiResponse<TaskSchedule>>, ApiError> {
+    validate(&payload)?;
+    let next_run_at = next_run(&payload.cron_expression)?;
+    let schedule =
+        TaskSchedule::create(&deployment.db().pool, project.id, &payload, next_run_at).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_schedule_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "starts_attempt": schedule.attempt.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(schedule)))
+}
+
+pub async fn update_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskSchedule>,
+) -> Result<ResponseJson<ApiResponse<TaskSchedule>>, ApiError> {
+    validate(&payload)?;
+    let next_run_at = next_run(&payload.cron_expression)?;
+    let schedule =
+        TaskSchedule::update(&deployment.db().pool, schedule.id, &payload, next_run_at).await?;
+    Ok(ResponseJson(ApiResponse::success(schedule)))
+}
+
+pub async fn delete_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = TaskSchedule::delete(&deployment.db().pool, schedule.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+pub async fn pause_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskSchedule>>, ApiError> {
+    let schedule = TaskSchedule::set_paused(
+        &deployment.db().pool,
+        schedule.id,
+        true,
+        schedule.next_run_at,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(schedule)))
+}
+
+/// Resume from the next time the expression matches; runs that came due while
+/// paused are skipped
+pub async fn resume_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskSchedule>>, ApiError> {
+    let next_run_at = next_run(&schedule.cron_expression)?;
+    let schedule =
+        TaskSchedule::set_paused(&deployment.db().pool, schedule.id, false, next_run_at).await?;
+    Ok(ResponseJson(ApiResponse::success(schedule)))
+}
+
+pub async fn get_schedule_runs(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskScheduleRun>>>, ApiError> {
+    let runs =
+        TaskScheduleRun::find_by_schedule_id(&deployment.db().pool, schedule.id, RUN_HISTORY_LIMIT)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(runs)))
+}
+
+fn validate(payload: &CreateTaskSchedule) -> Result<(), ApiError> {
+    if payload.title.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Scheduled tasks need a title".to_string(),
+        ));
+    }
+    if payload
+        .attempt
+        .as_ref()
+        .is_some_and(|attempt| attempt.repos.is_empty())
+    {
+        return Err(ApiError::BadRequest(
+            "At least one repository is required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn next_run(cron_expression: &str) -> Result<DateTime<Utc>, ApiError> {
+    task_scheduler::next_run(cron_expression, Utc::now())
+        .map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let schedule_router = Router::new()
+        .route("/", put(update_schedule).delete(delete_schedule))
+        .route("/pause", post(pause_schedule))
+        .route("/resume", post(resume_schedule))
+        .route("/runs", get(get_schedule_runs))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_task_schedule_middleware,
+        ));
+
+    Router::new().nest("/task-schedules/{schedule_id}", schedule_router)
+}
@@ -1,31 +1,48 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use anyhow;
 use axum::{
     Extension, Json, Router,
     extract::{
-        Query, State,
+        Path, Query, Request, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
-    middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
+    http::{HeaderMap, StatusCode},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::{delete, get, post, put},
 };
+use chrono::{DateTime, Duration, Utc};
 use db::models::{
+    board_column::{self, BoardColumn},
     image::TaskImage,
+    label::Label,
     project::{Project, ProjectError},
+    project_settings::{ProjectSettings, SwimlaneGrouping},
     repo::Repo,
-    task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    search,
+    task::{
+        CreateTask, SubtaskProgress, Task, TaskPriority, TaskStatus, TaskWithAttemptStatus,
+        UpdateTask,
+    },
+    task_dependency::{self, TaskDependency},
+    task_link::{TaskLink, TaskWithLinks},
     workspace::{CreateWorkspace, Workspace},
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
 use deployment::Deployment;
-use executors::profile::ExecutorProfileId;
+use executors::profile::{ExecutorConfigs, ExecutorProfileId};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService, share::ShareError, workspace_manager::WorkspaceManager,
+    attempt_comparison::{self, AttemptComparison},
+    attempt_queue::AttemptStart,
+    container::ContainerService,
+    share::ShareError,
+    swimlanes::{self, BoardSwimlanes},
+    task_duplicates::{self, DuplicateCandidate},
+    task_scope::PathScope,
+    workspace_manager::WorkspaceManager,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -33,47 +50,207 @@ use utils::{api::oauth::LoginStatus, response::ApiResponse};
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_task_middleware,
-    routes::task_attempts::WorkspaceRepoInput,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_task_middleware,
+    routes::{
+        attempt_groups,
+        board_columns::check_wip_limits,
+        epics,
+        task_attempts::{WorkspaceRepoInput, create_and_start_attempt},
+        task_checklists::{self, check_definition_of_done},
+        task_links, task_revisions, time_tracking, usage,
+    },
 };
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskQuery {
     pub project_id: Uuid,
+    /// Comma-separated label ids; only tasks with any of them are listed
+    pub labels: Option<String>,
+    #[serde(default)]
+    pub sort: TaskSort,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum TaskSort {
+    /// Order of the board columns
+    #[default]
+    Position,
+    /// Soonest deadline first, tasks without one last
+    DueAt,
+}
+
+impl TaskQuery {
+    fn label_ids(&self) -> Result<Vec<Uuid>, ApiError> {
+        let Some(labels) = &self.labels else {
+            return Ok(Vec::new());
+        };
+        labels
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                Uuid::parse_str(id)
+                    .map_err(|_| ApiError::BadRequest(format!("Invalid label id '{id}'")))
+            })
+            .collect()
+    }
 }
 
 pub async fn get_tasks(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskQuery>,
 ) -> Result<ResponseJson<ApiResponse<Vec<TaskWithAttemptStatus>>>, ApiError> {
-    let tasks =
+    let label_ids = query.label_ids()?;
+    let mut tasks =
         Task::find_by_project_id_with_attempt_status(&deployment.db().pool, query.project_id)
             .await?;
+    tasks.retain(|task| task.has_any_label(&label_ids));
+    if let TaskSort::DueAt = query.sort {
+        tasks.sort_by_key(|task| (task.due_at.is_none(), task.due_at));
+    }
 
     Ok(ResponseJson(ApiResponse::success(tasks)))
 }
 
+const DEFAULT_DUE_WITHIN_HOURS: u32 = 48;
+const MAX_DUE_WITHIN_HOURS: u32 = 24 * 90;
+
+#[derive(Debug, Deserialize)]
+pub struct DueTasksQuery {
+    /// Tasks of every project when unset
+    pub project_id: Option<Uuid>,
+    pub within_hours: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct DueTasks {
+    /// Most overdue first
+    pub overdue: Vec<Task>,
+    /// Tasks due within the requested window, soonest first
+    pub due_soon: Vec<Task>,
+}
+
+/// Open tasks that are overdue or coming due
+pub async fn get_due_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DueTasksQuery>,
+) -> Result<ResponseJson<ApiResponse<DueTasks>>, ApiError> {
+    let within_hours = query
+        .within_hours
+        .unwrap_or(DEFAULT_DUE_WITHIN_HOURS)
+        .min(MAX_DUE_WITHIN_HOURS);
+    let now = Utc::now();
+    let tasks = Task::find_due_before(
+        &deployment.db().pool,
+        query.project_id,
+        now + Duration::hours(within_hours.into()),
+    )
+    .await?;
+    let (overdue, due_soon) = tasks
+        .into_iter()
+        .partition(|task| task.due_at.is_some_and(|due_at| due_at <= now));
+
+    Ok(ResponseJson(ApiResponse::success(DueTasks {
+        overdue,
+        due_soon,
+    })))
+}
+
+const ARCHIVE_PAGE_SIZE: u32 = 50;
+const MAX_ARCHIVE_PAGE_SIZE: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ArchivedTasksQuery {
+    pub project_id: Uuid,
+    /// Words the title or description has to contain
+    pub q: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ArchivedTaskPage {
+    /// Most recently archived first
+    pub tasks: Vec<Task>,
+    /// Archived tasks matching the search, across all pages
+    pub total: i64,
+}
+
+pub async fn get_archived_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ArchivedTasksQuery>,
+) -> Result<ResponseJson<ApiResponse<ArchivedTaskPage>>, ApiError> {
+    let fts_query = query.q.as_deref().and_then(search::fts_query);
+    let limit = query
+        .limit
+        .unwrap_or(ARCHIVE_PAGE_SIZE)
+        .clamp(1, MAX_ARCHIVE_PAGE_SIZE);
+    let (tasks, total) = Task::find_archived_page(
+        &deployment.db().pool,
+        query.project_id,
+        fts_query.as_deref(),
+        limit as i64,
+        query.offset.unwrap_or(0) as i64,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(ArchivedTaskPage {
+        tasks,
+        total,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwimlaneQuery {
+    pub project_id: Uuid,
+    /// Overrides the project's swimlane setting
+    pub group_by: Option<SwimlaneGrouping>,
+}
+
+/// The board's tasks split into rows, grouped as the project is set up to
+pub async fn get_task_swimlanes(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<SwimlaneQuery>,
+) -> Result<ResponseJson<ApiResponse<BoardSwimlanes>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let grouping = match query.group_by {
+        Some(grouping) => grouping,
+        None => {
+            ProjectSettings::find_by_project_id(pool, query.project_id)
+                .await?
+                .swimlanes
+        }
+    };
+    let swimlanes = swimlanes::board_swimlanes(pool, query.project_id, grouping).await?;
+    Ok(ResponseJson(ApiResponse::success(swimlanes)))
+}
+
 pub async fn stream_tasks_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskQuery>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| async move {
-        if let Err(e) = handle_tasks_ws(socket, deployment, query.project_id).await {
+) -> Result<impl IntoResponse, ApiError> {
+    let label_ids = query.label_ids()?;
+    Ok(ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_tasks_ws(socket, deployment, query.project_id, label_ids).await {
             tracing::warn!("tasks WS closed: {}", e);
         }
-    })
+    }))
 }
 
 async fn handle_tasks_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
     project_id: Uuid,
+    label_ids: Vec<Uuid>,
 ) -> anyhow::Result<()> {
     // Get the raw stream and convert LogMsg to WebSocket messages
     let mut stream = deployment
         .events()
-        .stream_tasks_raw(project_id)
+        .stream_tasks_raw(project_id, label_ids)
         .await?
         .map_ok(|msg| msg.to_ws_message_unchecked());
 
@@ -102,9 +279,39 @@ async fn handle_tasks_ws(
 
 pub async fn get_task(
     Extension(task): Extension<Task>,
-    State(_deployment): State<DeploymentImpl>,
-) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
-    Ok(ResponseJson(ApiResponse::success(task)))
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskWithLinks>>, ApiError> {
+    let links = TaskLink::find_linked_tasks(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(TaskWithLinks {
+        task,
+        links,
+    })))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct FindDuplicatesRequest {
+    pub project_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    /// Leave this task out, when checking a task that already exists
+    pub exclude_task_id: Option<Uuid>,
+}
+
+/// Open tasks of the project that look like the task about to be created, to
+/// show before creating it
+pub async fn find_duplicate_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<FindDuplicatesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<DuplicateCandidate>>>, ApiError> {
+    let candidates = task_duplicates::find_duplicates(
+        &deployment.db().pool,
+        payload.project_id,
+        &payload.title,
+        payload.description.as_deref(),
+        payload.exclude_task_id,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(candidates)))
 }
 
 pub async fn create_task(
@@ -119,6 +326,13 @@ pub async fn create_task(
         payload.project_id
     );
 
+    ensure_parent_in_project(&deployment, &payload).await?;
+    let wip_warning = check_wip_limits(
+        &deployment.db().pool,
+        payload.project_id,
+        &[(payload.status.clone().unwrap_or_default(), None)],
+    )
+    .await?;
     let task = Task::create(&deployment.db().pool, &payload, id).await?;
 
     if let Some(image_ids) = &payload.image_ids {
@@ -133,11 +347,20 @@ pub async fn create_task(
             "project_id": payload.project_id,
             "has_description": task.description.is_some(),
             "has_images": payload.image_ids.is_some(),
+            "is_subtask": task.parent_task_id.is_some(),
             }),
         )
         .await;
 
-    Ok(ResponseJson(ApiResponse::success(task)))
+    Ok(ResponseJson(with_wip_warning(task, wip_warning)))
+}
+
+/// Successful response, carrying the WIP limit warning if there is one
+pub(crate) fn with_wip_warning<T>(data: T, wip_warning: Option<String>) -> ApiResponse<T> {
+    match wip_warning {
+        Some(message) => ApiResponse::success_with_message(data, message),
+        None => ApiResponse::success(data),
+    }
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -159,6 +382,13 @@ pub async fn create_task_and_start(
 
     let pool = &deployment.db().pool;
 
+    ensure_parent_in_project(&deployment, &payload.task).await?;
+    let wip_warning = check_wip_limits(
+        pool,
+        payload.task.project_id,
+        &[(payload.task.status.clone().unwrap_or_default(), None)],
+    )
+    .await?;
     let task_id = Uuid::new_v4();
     let task = Task::create(pool, &payload.task, task_id).await?;
 
@@ -174,6 +404,7 @@ pub async fn create_task_and_start(
                 "project_id": task.project_id,
                 "has_description": task.description.is_some(),
                 "has_images": payload.task.image_ids.is_some(),
+                "is_subtask": task.parent_task_id.is_some(),
             }),
         )
         .await;
@@ -199,6 +430,7 @@ pub async fn create_task_and_start(
         &CreateWorkspace {
             branch: git_branch_name,
             agent_working_dir,
+            executor_profile_id: Some(payload.executor_profile_id.clone()),
         },
         attempt_id,
         task.id,
@@ -217,10 +449,10 @@ pub async fn create_task_and_start(
 
     let is_attempt_running = deployment
         .container()
-        .start_workspace(&workspace, payload.executor_profile_id.clone())
+        .start_or_queue_workspace(&workspace, payload.executor_profile_id.clone())
         .await
         .inspect_err(|err| tracing::error!("Failed to start task attempt: {}", err))
-        .is_ok();
+        .is_ok_and(|start| matches!(start, AttemptStart::Started));
     deployment
         .track_if_analytics_allowed(
             "task_attempt_started",
@@ -238,33 +470,70 @@ pub async fn create_task_and_start(
         .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
 
     tracing::info!("Started attempt for task {}", task.id);
-    Ok(ResponseJson(ApiResponse::success(TaskWithAttemptStatus {
-        task,
-        has_in_progress_attempt: is_attempt_running,
-        last_attempt_failed: false,
-        executor: payload.executor_profile_id.executor.to_string(),
-    })))
+    Ok(ResponseJson(with_wip_warning(
+        TaskWithAttemptStatus {
+            task,
+            has_in_progress_attempt: is_attempt_running,
+            last_attempt_failed: false,
+            executor: payload.executor_profile_id.executor.to_string(),
+            is_blocked: false,
+            subtasks: SubtaskProgress::default(),
+            label_ids: Vec::new(),
+            latest_test_run: None,
+        },
+        wip_warning,
+    )))
+}
+
+async fn ensure_parent_in_project(
+    deployment: &DeploymentImpl,
+    payload: &CreateTask,
+) -> Result<(), ApiError> {
+    let Some(parent_task_id) = payload.parent_task_id else {
+        return Ok(());
+    };
+    match Task::find_by_id(&deployment.db().pool, parent_task_id).await? {
+        Some(parent) if parent.project_id == payload.project_id => Ok(()),
+        _ => Err(ApiError::BadRequest(
+            "Subtasks must belong to a task of the same project".to_string(),
+        )),
+    }
 }
 
 pub async fn update_task(
     Extension(existing_task): Extension<Task>,
     State(deployment): State<DeploymentImpl>,
-
+    headers: HeaderMap,
     Json(payload): Json<UpdateTask>,
 ) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
     ensure_shared_task_auth(&existing_task, &deployment).await?;
 
     // Use existing values if not provided in update
-    let title = payload.title.unwrap_or(existing_task.title);
+    let title = payload.title.unwrap_or_else(|| existing_task.title.clone());
     let description = match payload.description {
         Some(s) if s.trim().is_empty() => None, // Empty string = clear description
         Some(s) => Some(s),                     // Non-empty string = update description
-        None => existing_task.description,      // Field omitted = keep existing
+        None => existing_task.description.clone(), // Field omitted = keep existing
+    };
+    let status = payload
+        .status
+        .unwrap_or_else(|| existing_task.status.clone());
+    check_definition_of_done(&deployment.db().pool, &existing_task, &status).await?;
+    // A task changing status joins the first column of the new status
+    let wip_warning = if status != existing_task.status {
+        check_wip_limits(
+            &deployment.db().pool,
+            existing_task.project_id,
+            &[(status.clone(), None)],
+        )
+        .await?
+    } else {
+        None
     };
-    let status = payload.status.unwrap_or(existing_task.status);
     let parent_workspace_id = payload
         .parent_workspace_id
         .or(existing_task.parent_workspace_id);
+    let priority = payload.priority.unwrap_or(existing_task.priority);
 
     let task = Task::update(
         &deployment.db().pool,
@@ -274,8 +543,10 @@ pub async fn update_task(
         description,
         status,
         parent_workspace_id,
+        priority,
     )
     .await?;
+    task_revisions::record_edit(&deployment, &headers, &existing_task, &task).await?;
 
     if let Some(image_ids) = &payload.image_ids {
         TaskImage::delete_by_task_id(&deployment.db().pool, task.id).await?;
@@ -290,7 +561,7 @@ pub async fn update_task(
         publisher.update_shared_task(&task).await?;
     }
 
-    Ok(ResponseJson(ApiResponse::success(task)))
+    Ok(ResponseJson(with_wip_warning(task, wip_warning)))
 }
 
 async fn ensure_shared_task_auth(
@@ -370,6 +641,10 @@ pub async fn delete_task(
     // Commit the transaction - if this fails, all changes are rolled back
     tx.commit().await?;
 
+    if let Some(parent_task_id) = task.parent_task_id {
+        refresh_board(&deployment, parent_task_id).await;
+    }
+
     if total_children_affected > 0 {
         tracing::info!(
             "Nullified {} child task references before deleting task {}",
@@ -460,23 +735,846 @@ pub async fn share_task(
     })))
 }
 
+#[derive(Debug, Serialize, TS)]
+pub struct TaskDependencies {
+    /// Tasks that have to be done before this one can start
+    pub blocked_by: Vec<Task>,
+    /// Tasks waiting for this one
+    pub blocking: Vec<Task>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct TaskDependencyRequest {
+    pub depends_on_task_id: Uuid,
+}
+
+pub async fn get_task_dependencies(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskDependencies>>, ApiError> {
+    let pool = &deployment.db().pool;
+    Ok(ResponseJson(ApiResponse::success(TaskDependencies {
+        blocked_by: TaskDependency::find_blockers(pool, task.id).await?,
+        blocking: TaskDependency::find_dependents(pool, task.id).await?,
+    })))
+}
+
+/// Make the task wait for another task of the same project
+pub async fn add_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<TaskDependencyRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskDependency>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let blocker = Task::find_by_id(pool, payload.depends_on_task_id)
+        .await?
+        .filter(|blocker| blocker.project_id == task.project_id)
+        .ok_or_else(|| {
+            ApiError::BadRequest("Tasks can only depend on tasks of the same project".to_string())
+        })?;
+    let dependencies = TaskDependency::find_by_project_id(pool, task.project_id).await?;
+    if dependencies
+        .iter()
+        .any(|d| d.task_id == task.id && d.depends_on_task_id == blocker.id)
+    {
+        return Err(ApiError::Conflict(format!(
+            "Task already depends on '{}'",
+            blocker.title
+        )));
+    }
+    if task_dependency::creates_cycle(&dependencies, task.id, blocker.id) {
+        return Err(ApiError::Conflict(format!(
+            "'{}' already depends on this task, directly or through other tasks",
+            blocker.title
+        )));
+    }
+
+    let dependency = TaskDependency::create(pool, task.id, blocker.id).await?;
+    refresh_board(&deployment, task.id).await;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_dependency_added",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "depends_on_task_id": blocker.id.to_string(),
+                "blocker_done": blocker.status == TaskStatus::Done,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(dependency)))
+}
+
+pub async fn remove_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskDependencyRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if TaskDependency::delete(pool, task.id, query.depends_on_task_id).await? == 0 {
+        return Err(ApiError::Database(SqlxError::RowNotFound));
+    }
+    refresh_board(&deployment, task.id).await;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct MoveTaskRequest {
+    /// Status to move the task to; defaults to its current status
+    pub status: Option<TaskStatus>,
+    /// Custom column to move the task to, which also sets its status
+    pub column_id: Option<Uuid>,
+    /// Place within the column, 0 being the top
+    pub index: u32,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct TaskOrder {
+    pub status: TaskStatus,
+    /// None for the first column of the status
+    pub column_id: Option<Uuid>,
+    /// Tasks of the column from top to bottom
+    pub task_ids: Vec<Uuid>,
+}
+
+/// Move the task within its column or to another one
+pub async fn move_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<MoveTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskOrder>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let (status, column_id) = match payload.column_id {
+        Some(column_id) => {
+            let columns = BoardColumn::find_or_create_defaults(pool, task.project_id).await?;
+            let column = columns
+                .iter()
+                .find(|column| column.id == column_id)
+                .ok_or_else(|| {
+                    ApiError::BadRequest(format!(
+                        "Column {column_id} doesn't belong to the task's project"
+                    ))
+                })?;
+            if payload
+                .status
+                .as_ref()
+                .is_some_and(|status| *status != column.status)
+            {
+                return Err(ApiError::BadRequest(
+                    "The status doesn't match the column's".to_string(),
+                ));
+            }
+            // Tasks in the first column of their status are stored without one
+            let first = board_column::first_column_for(&columns, &column.status);
+            (
+                column.status.clone(),
+                (first != Some(column.id)).then_some(column.id),
+            )
+        }
+        None => {
+            let status = payload.status.unwrap_or_else(|| task.status.clone());
+            let column_id = if status == task.status {
+                task.column_id
+            } else {
+                None
+            };
+            (status, column_id)
+        }
+    };
+    let status_changed = status != task.status;
+    if status_changed {
+        ensure_shared_task_auth(&task, &deployment).await?;
+        check_definition_of_done(pool, &task, &status).await?;
+    }
+    // Moving within its own column doesn't add to the column
+    let wip_warning = if status_changed || column_id != task.column_id {
+        check_wip_limits(pool, task.project_id, &[(status.clone(), column_id)]).await?
+    } else {
+        None
+    };
+
+    let task_ids = Task::move_to(
+        pool,
+        &task,
+        status.clone(),
+        column_id,
+        payload.index as usize,
+    )
+    .await?;
+
+    if status_changed && task.shared_task_id.is_some() {
+        let Ok(publisher) = deployment.share_publisher() else {
+            return Err(ShareError::MissingConfig("share publisher unavailable").into());
+        };
+        let task = Task::find_by_id(pool, task.id)
+            .await?
+            .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+        publisher.update_shared_task(&task).await?;
+    }
+
+    Ok(ResponseJson(with_wip_warning(
+        TaskOrder {
+            status,
+            column_id,
+            task_ids,
+        },
+        wip_warning,
+    )))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetTaskDueDateRequest {
+    /// None clears the deadline
+    pub due_at: Option<DateTime<Utc>>,
+}
+
+pub async fn set_task_due_date(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetTaskDueDateRequest>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    Task::set_due_at(pool, task.id, payload.due_at).await?;
+    let task = Task::find_by_id(pool, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_due_date_set",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "cleared": task.due_at.is_none(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetTaskExecutorProfileRequest {
+    /// None goes back to the configured default
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+/// Pin the executor profile, and with it the agent and model, that attempts of
+/// the task start with
+pub async fn set_task_executor_profile(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetTaskExecutorProfileRequest>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    if let Some(executor_profile_id) = &payload.executor_profile_id
+        && ExecutorConfigs::get_cached()
+            .get_coding_agent(executor_profile_id)
+            .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown executor profile '{executor_profile_id}'"
+        )));
+    }
+
+    let pool = &deployment.db().pool;
+    Task::set_executor_profile(pool, task.id, payload.executor_profile_id.as_ref()).await?;
+    let task = Task::find_by_id(pool, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_executor_profile_set",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "executor": payload.executor_profile_id.as_ref().map(|id| &id.executor),
+                "variant": payload.executor_profile_id.as_ref().and_then(|id| id.variant.as_ref()),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetTaskScopeRequest {
+    /// Gitignore-style patterns; an empty list puts the whole repository back
+    /// in scope
+    pub paths: Vec<String>,
+}
+
+/// Limit the paths the task's attempts should change. Agents are told about
+/// the scope, and changes outside it are flagged on the attempt.
+pub async fn set_task_scope(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetTaskScopeRequest>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let paths: Vec<String> = payload
+        .paths
+        .iter()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .collect();
+    if let Err(e) = PathScope::new(&paths) {
+        return Err(ApiError::BadRequest(format!("Invalid scope pattern: {e}")));
+    }
+
+    let pool = &deployment.db().pool;
+    Task::set_scope_paths(pool, task.id, &paths).await?;
+    let task = Task::find_by_id(pool, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_scope_set",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "pattern_count": paths.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+/// Take the task off the board, keeping it and its attempts for reference
+pub async fn archive_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    set_task_archived(&deployment, &task, true).await
+}
+
+/// Put an archived task back on the board, in the column it was archived from
+pub async fn unarchive_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    set_task_archived(&deployment, &task, false).await
+}
+
+async fn set_task_archived(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    archived: bool,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let wip_warning = if !archived && task.archived_at.is_some() {
+        check_wip_limits(
+            pool,
+            task.project_id,
+            &[(task.status.clone(), task.column_id)],
+        )
+        .await?
+    } else {
+        None
+    };
+
+    Task::set_archived(pool, task.id, archived).await?;
+    let task = Task::find_by_id(pool, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    deployment
+        .track_if_analytics_allowed(
+            if archived {
+                "task_archived"
+            } else {
+                "task_unarchived"
+            },
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": task.project_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(with_wip_warning(task, wip_warning)))
+}
+
+/// Most tasks a single bulk request may create or change
+const MAX_BULK_TASKS: usize = 500;
+
+pub(crate) fn check_bulk_size(count: usize) -> Result<(), ApiError> {
+    if count == 0 {
+        return Err(ApiError::BadRequest("No tasks given".to_string()));
+    }
+    if count > MAX_BULK_TASKS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_BULK_TASKS} tasks can be handled at once"
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct BulkUpdateTasksRequest {
+    pub task_ids: Vec<Uuid>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    /// Labels added to every task
+    #[serde(default)]
+    pub add_label_ids: Vec<Uuid>,
+    /// Labels removed from every task
+    #[serde(default)]
+    pub remove_label_ids: Vec<Uuid>,
+    /// Archive the tasks, or bring archived tasks back to the board
+    pub archived: Option<bool>,
+}
+
+/// Apply the same changes to tasks of one project; either all tasks change or
+/// none does
+pub async fn bulk_update_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<BulkUpdateTasksRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    check_bulk_size(payload.task_ids.len())?;
+    let pool = &deployment.db().pool;
+
+    let mut tasks = Vec::with_capacity(payload.task_ids.len());
+    for task_id in &payload.task_ids {
+        let task = Task::find_by_id(pool, *task_id)
+            .await?
+            .ok_or_else(|| ApiError::BadRequest(format!("Task {task_id} not found")))?;
+        tasks.push(task);
+    }
+    let project_id = tasks[0].project_id;
+    if tasks.iter().any(|task| task.project_id != project_id) {
+        return Err(ApiError::BadRequest(
+            "All tasks must belong to the same project".to_string(),
+        ));
+    }
+
+    let project_labels = Label::find_by_project_id(pool, project_id).await?;
+    if let Some(unknown) = payload
+        .add_label_ids
+        .iter()
+        .chain(&payload.remove_label_ids)
+        .find(|id| !project_labels.iter().any(|label| label.id == **id))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Label {unknown} doesn't belong to the tasks' project"
+        )));
+    }
+    if let Some(status) = &payload.status {
+        for task in &tasks {
+            ensure_shared_task_auth(task, &deployment).await?;
+            check_definition_of_done(pool, task, status).await?;
+        }
+    }
+    let wip_warning = match &payload.status {
+        Some(status) => {
+            let added: Vec<_> = tasks
+                .iter()
+                .filter(|task| task.status != *status)
+                .map(|_| (status.clone(), None))
+                .collect();
+            check_wip_limits(pool, project_id, &added).await?
+        }
+        None => None,
+    };
+
+    let mut tx = pool.begin().await?;
+    for task in &tasks {
+        if let Some(status) = &payload.status {
+            Task::update_status(&mut *tx, task.id, status.clone()).await?;
+        }
+        if let Some(priority) = payload.priority {
+            Task::update_priority(&mut *tx, task.id, priority).await?;
+        }
+        if let Some(archived) = payload.archived {
+            Task::set_archived(&mut *tx, task.id, archived).await?;
+        }
+        for label_id in &payload.add_label_ids {
+            Label::add_to_task(&mut *tx, task.id, *label_id).await?;
+        }
+        for label_id in &payload.remove_label_ids {
+            Label::remove_from_task(&mut *tx, task.id, *label_id).await?;
+        }
+    }
+    tx.commit().await?;
+
+    let mut updated = Vec::with_capacity(tasks.len());
+    for task in &tasks {
+        if let Some(task) = Task::find_by_id(pool, task.id).await? {
+            refresh_board(&deployment, task.id).await;
+            updated.push(task);
+        }
+    }
+
+    if payload.status.is_some() && updated.iter().any(|task| task.shared_task_id.is_some()) {
+        let Ok(publisher) = deployment.share_publisher() else {
+            return Err(ShareError::MissingConfig("share publisher unavailable").into());
+        };
+        for task in updated.iter().filter(|task| task.shared_task_id.is_some()) {
+            publisher.update_shared_task(task).await?;
+        }
+    }
+
+    let changes_labels = !payload.add_label_ids.is_empty() || !payload.remove_label_ids.is_empty();
+    deployment
+        .track_if_analytics_allowed(
+            "tasks_bulk_updated",
+            serde_json::json!({
+                "project_id": project_id.to_string(),
+                "task_count": updated.len(),
+                "changes_status": payload.status.is_some(),
+                "changes_priority": payload.priority.is_some(),
+                "changes_labels": changes_labels,
+                "changes_archived": payload.archived.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(with_wip_warning(updated, wip_warning)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct BulkCreateTasksRequest {
+    pub tasks: Vec<CreateTask>,
+}
+
+/// Create several tasks at once, such as from an import script; either all
+/// tasks are created or none is
+pub async fn bulk_create_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<BulkCreateTasksRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    check_bulk_size(payload.tasks.len())?;
+    for data in &payload.tasks {
+        if data.title.trim().is_empty() {
+            return Err(ApiError::BadRequest("Every task needs a title".to_string()));
+        }
+        ensure_parent_in_project(&deployment, data).await?;
+    }
+
+    let pool = &deployment.db().pool;
+    let mut wip_warnings = Vec::new();
+    for (project_id, added) in group_by_project(&payload.tasks) {
+        if let Some(warning) = check_wip_limits(pool, project_id, &added).await? {
+            wip_warnings.push(warning);
+        }
+    }
+
+    // Each new task goes to the top of its column, so creating them last to
+    // first keeps the order they were given in
+    let mut tx = pool.begin().await?;
+    let mut created = Vec::with_capacity(payload.tasks.len());
+    for data in payload.tasks.iter().rev() {
+        created.push(Task::create(&mut *tx, data, Uuid::new_v4()).await?);
+    }
+    tx.commit().await?;
+    created.reverse();
+
+    for (task, data) in created.iter().zip(&payload.tasks) {
+        if let Some(image_ids) = &data.image_ids {
+            TaskImage::associate_many_dedup(pool, task.id, image_ids).await?;
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "tasks_bulk_created",
+            serde_json::json!({
+                "task_count": created.len(),
+            }),
+        )
+        .await;
+
+    let wip_warning = (!wip_warnings.is_empty()).then(|| wip_warnings.join("; "));
+    Ok(ResponseJson(with_wip_warning(created, wip_warning)))
+}
+
+/// Status of each new task, grouped by the project it is created in
+fn group_by_project(tasks: &[CreateTask]) -> HashMap<Uuid, Vec<(TaskStatus, Option<Uuid>)>> {
+    let mut projects: HashMap<Uuid, Vec<_>> = HashMap::new();
+    for data in tasks {
+        projects
+            .entry(data.project_id)
+            .or_default()
+            .push((data.status.clone().unwrap_or_default(), None));
+    }
+    projects
+}
+
+pub async fn get_task_labels(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Label>>>, ApiError> {
+    let labels = Label::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(labels)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetTaskLabelsRequest {
+    pub label_ids: Vec<Uuid>,
+}
+
+/// Replace the task's labels with labels of its project
+pub async fn set_task_labels(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetTaskLabelsRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Label>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let project_labels = Label::find_by_project_id(pool, task.project_id).await?;
+    if let Some(unknown) = payload
+        .label_ids
+        .iter()
+        .find(|id| !project_labels.iter().any(|label| label.id == **id))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Label {unknown} doesn't belong to the task's project"
+        )));
+    }
+
+    Label::set_for_task(pool, task.id, &payload.label_ids).await?;
+    refresh_board(&deployment, task.id).await;
+
+    let labels = Label::find_by_task_id(pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(labels)))
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct Subtasks {
+    pub progress: SubtaskProgress,
+    pub subtasks: Vec<Task>,
+}
+
+pub async fn get_subtasks(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Subtasks>>, ApiError> {
+    let subtasks = Task::find_subtasks(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(Subtasks {
+        progress: SubtaskProgress::of(&subtasks),
+        subtasks,
+    })))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct StartSubtasksRequest {
+    /// Profile for every started subtask; without one each subtask uses its
+    /// own or the configured default
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    pub repos: Vec<WorkspaceRepoInput>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct SkippedSubtask {
+    pub task_id: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct StartSubtasksResponse {
+    /// Attempts created, one per started subtask
+    pub started: Vec<Workspace>,
+    /// Subtasks to do that couldn't be started, e.g. because they are blocked
+    pub skipped: Vec<SkippedSubtask>,
+}
+
+/// Start an attempt for every subtask still to do that has none yet
+pub async fn start_subtasks(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<StartSubtasksRequest>,
+) -> Result<ResponseJson<ApiResponse<StartSubtasksResponse>>, ApiError> {
+    if payload.repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one repository is required".to_string(),
+        ));
+    }
+
+    let pool = &deployment.db().pool;
+    let mut started = Vec::new();
+    let mut skipped = Vec::new();
+    for subtask in Task::find_subtasks(pool, task.id).await? {
+        if subtask.status != TaskStatus::Todo
+            || !Workspace::fetch_all(pool, Some(subtask.id))
+                .await?
+                .is_empty()
+        {
+            continue;
+        }
+        match create_and_start_attempt(
+            &deployment,
+            &subtask,
+            payload.executor_profile_id.as_ref(),
+            &payload.repos,
+        )
+        .await
+        {
+            Ok(workspace) => started.push(workspace),
+            Err(ApiError::Conflict(reason)) => skipped.push(SkippedSubtask {
+                task_id: subtask.id,
+                reason,
+            }),
+            Err(e) => return Err(e),
+        }
+    }
+
+    tracing::info!(
+        "Started {} subtasks of task {} ({} skipped)",
+        started.len(),
+        task.id,
+        skipped.len()
+    );
+    Ok(ResponseJson(ApiResponse::success(StartSubtasksResponse {
+        started,
+        skipped,
+    })))
+}
+
+/// The task's attempts side by side, newest first, for picking the one to keep
+pub async fn get_attempt_comparison(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptComparison>>>, ApiError> {
+    let comparisons =
+        attempt_comparison::compare_attempts(&deployment.db().pool, deployment.git(), &task)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(comparisons)))
+}
+
+/// Dependencies and labels aren't watched by the event hooks and a deleted
+/// task's parent isn't refreshed by them, so the board learns of those changes
+/// here
+pub(crate) async fn refresh_board(deployment: &DeploymentImpl, task_id: Uuid) {
+    if let Err(e) = deployment.events().push_task_update(task_id).await {
+        tracing::warn!("Failed to push update for task {}: {}", task_id, e);
+    }
+}
+
+/// Middleware to load the Task for routes with a second path parameter.
+async fn load_task_with_param(
+    State(deployment): State<DeploymentImpl>,
+    Path((task_id, _param)): Path<(Uuid, Uuid)>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    load_task_middleware(State(deployment), Path(task_id), request, next).await
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_actions_router = Router::new()
         .route("/", put(update_task))
         .route("/", delete(delete_task))
-        .route("/share", post(share_task));
+        .route("/share", post(share_task))
+        .route(
+            "/dependencies",
+            get(get_task_dependencies)
+                .post(add_task_dependency)
+                .delete(remove_task_dependency),
+        )
+        .route("/move", post(move_task))
+        .route("/due-date", put(set_task_due_date))
+        .route("/epic", put(epics::set_task_epic))
+        .route("/executor-profile", put(set_task_executor_profile))
+        .route("/scope", put(set_task_scope))
+        .route(
+            "/links",
+            get(task_links::get_task_links).post(task_links::create_task_link),
+        )
+        .route("/time", get(time_tracking::get_task_time))
+        .route("/usage", get(usage::get_task_usage))
+        .route(
+            "/time-entries",
+            get(time_tracking::get_time_entries).post(time_tracking::create_time_entry),
+        )
+        .route("/archive", post(archive_task))
+        .route("/unarchive", post(unarchive_task))
+        .route("/labels", get(get_task_labels).put(set_task_labels))
+        .route("/subtasks", get(get_subtasks))
+        .route("/subtasks/start", post(start_subtasks))
+        .route(
+            "/checklist",
+            get(task_checklists::get_task_checklist)
+                .post(task_checklists::create_task_checklist_item),
+        )
+        .route("/revisions", get(task_revisions::get_task_revisions))
+        .route("/attempts/compare", get(get_attempt_comparison))
+        .route("/attempts/fan-out", post(attempt_groups::fan_out_attempts))
+        .route(
+            "/attempt-groups",
+            get(attempt_groups::get_task_attempt_groups),
+        );
 
     let task_id_router = Router::new()
         .route("/", get(get_task))
         .merge(task_actions_router)
         .layer(from_fn_with_state(deployment.clone(), load_task_middleware));
 
+    // Routes with a second path parameter need their own loader
+    let task_item_router = Router::new()
+        .route(
+            "/revisions/{revision_id}/restore",
+            post(task_revisions::restore_task_revision),
+        )
+        .layer(from_fn_with_state(deployment.clone(), load_task_with_param));
+
     let inner = Router::new()
         .route("/", get(get_tasks).post(create_task))
         .route("/stream/ws", get(stream_tasks_ws))
+        .route("/archived", get(get_archived_tasks))
+        .route("/due", get(get_due_tasks))
+        .route("/swimlanes", get(get_task_swimlanes))
+        .route("/bulk-update", post(bulk_update_tasks))
+        .route("/bulk-create", post(bulk_create_tasks))
+        .route("/duplicates", post(find_duplicate_tasks))
         .route("/create-and-start", post(create_task_and_start))
-        .nest("/{task_id}", task_id_router);
+        .nest("/{task_id}", task_id_router.merge(task_item_router));
 
     // mount under /projects/:project_id/tasks
     Router::new().nest("/tasks", inner)
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request as HttpRequest, middleware::from_fn};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn task_id_only(_: Path<Uuid>, request: Request, next: Next) -> Response {
+        next.run(request).await
+    }
+
+    async fn task_and_revision_id(_: Path<(Uuid, Uuid)>, request: Request, next: Next) -> Response {
+        next.run(request).await
+    }
+
+    async fn restore(Path((_task_id, _revision_id)): Path<(Uuid, Uuid)>) -> StatusCode {
+        StatusCode::OK
+    }
+
+    /// POST a restore through `item_router`, nested as `router` nests it
+    async fn restore_status(item_router: Router) -> StatusCode {
+        let app = Router::new().nest("/tasks", Router::new().nest("/{task_id}", item_router));
+        let uri = format!(
+            "/tasks/{}/revisions/{}/restore",
+            Uuid::new_v4(),
+            Uuid::new_v4()
+        );
+        app.oneshot(HttpRequest::post(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn restore_route_is_reached_through_a_loader_of_both_path_parameters() {
+        let route = || Router::new().route("/revisions/{revision_id}/restore", post(restore));
+
+        // The other task routes' loader only takes the task id
+        let status = restore_status(route().layer(from_fn(task_id_only))).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        // As `load_task_with_param` takes them
+        let status = restore_status(route().layer(from_fn(task_and_revision_id))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -0,0 +1,120 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::delete,
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    project::Project,
+    task::Task,
+    task_time_entry::{CreateTaskTimeEntry, TaskTimeEntry},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::time_report::{self, TaskTime, TimeReport};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_time_entry_middleware};
+
+/// Longest a single manual entry may be
+const MAX_ENTRY_SECONDS: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct TimeReportQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Time spent on the project's tasks, per task and per executor
+pub async fn get_time_report(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TimeReportQuery>,
+) -> Result<ResponseJson<ApiResponse<TimeReport>>, ApiError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(ApiError::BadRequest(
+            "'from' must not be later than 'to'".to_string(),
+        ));
+    }
+    let report =
+        time_report::project_report(&deployment.db().pool, project.id, query.from, query.to)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub async fn get_task_time(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskTime>>, ApiError> {
+    let time = time_report::task_time(&deployment.db().pool, &task).await?;
+    Ok(ResponseJson(ApiResponse::success(time)))
+}
+
+pub async fn get_time_entries(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskTimeEntry>>>, ApiError> {
+    let entries = TaskTimeEntry::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+/// Log time spent on the task by hand
+pub async fn create_time_entry(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskTimeEntry>,
+) -> Result<ResponseJson<ApiResponse<TaskTimeEntry>>, ApiError> {
+    if payload.seconds <= 0 || payload.seconds > MAX_ENTRY_SECONDS {
+        return Err(ApiError::BadRequest(format!(
+            "An entry must be between 1 and {MAX_ENTRY_SECONDS} seconds"
+        )));
+    }
+    if payload
+        .spent_at
+        .is_some_and(|spent_at| spent_at > Utc::now())
+    {
+        return Err(ApiError::BadRequest(
+            "Time can't be logged in the future".to_string(),
+        ));
+    }
+    let entry = TaskTimeEntry::create(&deployment.db().pool, task.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_time_logged",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "seconds": entry.seconds,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(entry)))
+}
+
+pub async fn delete_time_entry(
+    Extension(entry): Extension<TaskTimeEntry>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if TaskTimeEntry::delete(&deployment.db().pool, entry.id).await? == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let entry_router =
+        Router::new()
+            .route("/", delete(delete_time_entry))
+            .layer(from_fn_with_state(
+                deployment.clone(),
+                load_time_entry_middleware,
+            ));
+
+    Router::new().nest("/time-entries/{entry_id}", entry_router)
+}
@@ -0,0 +1,83 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use chrono::{DateTime, Utc};
+use db::models::{project::Project, task::Task, workspace::Workspace};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    usage_report::{self, AttemptUsage, TaskUsage, UsageReport},
+    variant_stats::{self, VariantStats},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UsageReportQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Tokens and cost of the project's coding agent runs, per task and per
+/// executor
+pub async fn get_usage_report(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<UsageReportQuery>,
+) -> Result<ResponseJson<ApiResponse<UsageReport>>, ApiError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(ApiError::BadRequest(
+            "'from' must not be later than 'to'".to_string(),
+        ));
+    }
+    let report =
+        usage_report::project_report(&deployment.db().pool, project.id, query.from, query.to)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Outcomes, follow-ups and cost of the project's attempts per executor
+/// profile variant, for attempts created within the range
+pub async fn get_variant_stats(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<UsageReportQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<VariantStats>>>, ApiError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(ApiError::BadRequest(
+            "'from' must not be later than 'to'".to_string(),
+        ));
+    }
+    let stats = variant_stats::project_variant_stats(
+        &deployment.db().pool,
+        project.id,
+        query.from,
+        query.to,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(stats)))
+}
+
+pub async fn get_task_usage(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskUsage>>, ApiError> {
+    let usage = usage_report::task_usage(&deployment.db().pool, &task).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
+pub async fn get_attempt_usage(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptUsage>>, ApiError> {
+    let usage = usage_report::attempt_usage(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
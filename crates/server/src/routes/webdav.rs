@@ -0,0 +1,586 @@
+//! Minimal WebDAV (class 1, with advisory locks) over workspace directories so a
+//! task attempt's worktree can be mounted as a network drive.
+//!
+//! Every path goes through the same validation as the files router, so `.git`
+//! stays hidden and symlinks cannot be used to reach outside the workspace.
+//! Files written through WebDAV follow the project's upload rules and are
+//! recorded as uploads, like files uploaded through the API.
+
+use std::path::{Path as StdPath, PathBuf};
+
+use axum::{
+    Router,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::any,
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    project_settings::{GitignoredUploadPolicy, ProjectSettings},
+    task::Task,
+    workspace::Workspace,
+    workspace_upload::{CreateWorkspaceUpload, WorkspaceUpload},
+};
+use deployment::Deployment;
+use services::services::{
+    container::ContainerService,
+    upload_scan,
+    workspace_files::{self, FileUploadError},
+};
+use sqlx::SqlitePool;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const ALLOWED_METHODS: &str =
+    "OPTIONS, GET, HEAD, PUT, DELETE, MKCOL, COPY, MOVE, PROPFIND, LOCK, UNLOCK";
+
+async fn handle_root(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    handle(
+        deployment,
+        workspace_id,
+        String::new(),
+        method,
+        headers,
+        body,
+    )
+    .await
+}
+
+async fn handle_path(
+    State(deployment): State<DeploymentImpl>,
+    Path((workspace_id, path)): Path<(Uuid, String)>,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    handle(deployment, workspace_id, path, method, headers, body).await
+}
+
+async fn handle(
+    deployment: DeploymentImpl,
+    workspace_id: Uuid,
+    path: String,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let workspace = Workspace::find_by_id(&deployment.db().pool, workspace_id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&workspace)
+        .await?;
+    let root = PathBuf::from(container_ref);
+    let relative = path.trim_matches('/').to_string();
+    let dav = Dav {
+        root,
+        base_href: format!("/api/webdav/{workspace_id}"),
+    };
+
+    let response = match method.as_str() {
+        "OPTIONS" => Response::builder()
+            .status(StatusCode::OK)
+            .header("DAV", "1, 2")
+            .header(header::ALLOW, ALLOWED_METHODS)
+            .body(Body::empty()),
+        "GET" | "HEAD" => return dav.get(&relative, method == Method::HEAD).await,
+        "PUT" => {
+            let mut rules = UploadRules::load(&deployment, &workspace).await?;
+            rules.check(&dav.root, &relative, &body, true).await?;
+            let response = dav.put(&relative, &body).await?;
+            if response.status().is_success() {
+                rules.record(&relative, &body).await?;
+            }
+            return Ok(response);
+        }
+        "DELETE" => return dav.delete(&relative).await,
+        "MKCOL" => return dav.mkcol(&relative).await,
+        "COPY" | "MOVE" => {
+            let mut rules = UploadRules::load(&deployment, &workspace).await?;
+            return dav
+                .copy_or_move(&relative, &headers, method.as_str() == "MOVE", &mut rules)
+                .await;
+        }
+        "PROPFIND" => return dav.propfind(&relative, &headers).await,
+        "LOCK" => return Ok(lock_response(&dav.href(&relative, false))),
+        "UNLOCK" => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty()),
+        _ => Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .header(header::ALLOW, ALLOWED_METHODS)
+            .body(Body::empty()),
+    };
+    response.map_err(|e| ApiError::Io(std::io::Error::other(e)))
+}
+
+/// The project's upload rules for the files written to a workspace
+struct UploadRules {
+    pool: SqlitePool,
+    workspace_id: Uuid,
+    uploaded_by: String,
+    settings: ProjectSettings,
+    used_bytes: i64,
+}
+
+impl UploadRules {
+    async fn load(deployment: &DeploymentImpl, workspace: &Workspace) -> Result<Self, ApiError> {
+        let pool = &deployment.db().pool;
+        let task = Task::find_by_id(pool, workspace.task_id)
+            .await?
+            .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+        Ok(Self {
+            pool: pool.clone(),
+            workspace_id: workspace.id,
+            uploaded_by: deployment.user_id().to_string(),
+            settings: ProjectSettings::find_by_project_id(pool, task.project_id).await?,
+            used_bytes: ProjectSettings::used_upload_bytes(pool, task.project_id).await?,
+        })
+    }
+
+    /// Check a file about to be written to `relative`. Its size counts against
+    /// the project's quota when `counted`, i.e. when it adds to what is stored.
+    async fn check(
+        &mut self,
+        root: &StdPath,
+        relative: &str,
+        data: &[u8],
+        counted: bool,
+    ) -> Result<(), ApiError> {
+        let size = data.len() as i64;
+        let used_bytes = if counted { self.used_bytes } else { 0 };
+        self.settings
+            .upload_policy
+            .check(relative, size, used_bytes)
+            .map_err(FileUploadError::PolicyViolation)?;
+        if self.settings.gitignored_uploads == GitignoredUploadPolicy::Refuse
+            && workspace_files::is_gitignored(root, StdPath::new(relative), false)
+        {
+            return Err(FileUploadError::Gitignored(relative.to_string()).into());
+        }
+        upload_scan::scan_upload(&self.settings.upload_scan, relative, data).await?;
+        if counted {
+            self.used_bytes += size;
+        }
+        Ok(())
+    }
+
+    async fn record(&self, relative: &str, data: &[u8]) -> Result<(), ApiError> {
+        WorkspaceUpload::upsert(
+            &self.pool,
+            &CreateWorkspaceUpload {
+                workspace_id: self.workspace_id,
+                path: relative.to_string(),
+                size_bytes: data.len() as i64,
+                hash: workspace_files::content_hash(data),
+                uploaded_by: Some(self.uploaded_by.clone()),
+            },
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+struct Dav {
+    root: PathBuf,
+    base_href: String,
+}
+
+impl Dav {
+    /// Resolve a request path. The empty path is the workspace root itself.
+    fn resolve(&self, relative: &str) -> Result<PathBuf, FileUploadError> {
+        if relative.is_empty() {
+            Ok(self.root.clone())
+        } else {
+            workspace_files::validate_target_path(&self.root, relative)
+        }
+    }
+
+    fn href(&self, relative: &str, is_dir: bool) -> String {
+        let mut href = self.base_href.clone();
+        for segment in relative.split('/').filter(|s| !s.is_empty()) {
+            href.push('/');
+            href.push_str(&urlencoding::encode(segment));
+        }
+        if is_dir {
+            href.push('/');
+        }
+        href
+    }
+
+    async fn get(&self, relative: &str, head_only: bool) -> Result<Response, ApiError> {
+        let target = self.resolve(relative)?;
+        let metadata = match tokio::fs::metadata(&target).await {
+            Ok(metadata) => metadata,
+            Err(_) => return status(StatusCode::NOT_FOUND),
+        };
+        if metadata.is_dir() {
+            return status(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        let content_type = mime_guess::from_path(&target).first_or_octet_stream();
+        let builder = Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type.as_ref())
+            .header(header::CONTENT_LENGTH, metadata.len())
+            .header(header::LAST_MODIFIED, http_date(&metadata));
+        let body = if head_only {
+            Body::empty()
+        } else {
+            Body::from_stream(ReaderStream::new(tokio::fs::File::open(&target).await?))
+        };
+        builder
+            .body(body)
+            .map_err(|e| ApiError::Io(std::io::Error::other(e)))
+    }
+
+    async fn put(&self, relative: &str, body: &[u8]) -> Result<Response, ApiError> {
+        let target = self.resolve(relative)?;
+        if target.is_dir() {
+            return status(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let Some(parent) = target.parent() else {
+            return status(StatusCode::CONFLICT);
+        };
+        if !parent.is_dir() {
+            return status(StatusCode::CONFLICT);
+        }
+        let existed = target.exists();
+        tokio::fs::write(&target, body).await?;
+        status(if existed {
+            StatusCode::NO_CONTENT
+        } else {
+            StatusCode::CREATED
+        })
+    }
+
+    async fn delete(&self, relative: &str) -> Result<Response, ApiError> {
+        if relative.is_empty() {
+            return status(StatusCode::FORBIDDEN);
+        }
+        let target = self.resolve(relative)?;
+        match tokio::fs::symlink_metadata(&target).await {
+            Ok(metadata) if metadata.is_dir() => tokio::fs::remove_dir_all(&target).await?,
+            Ok(_) => tokio::fs::remove_file(&target).await?,
+            Err(_) => return status(StatusCode::NOT_FOUND),
+        }
+        status(StatusCode::NO_CONTENT)
+    }
+
+    async fn mkcol(&self, relative: &str) -> Result<Response, ApiError> {
+        let target = self.resolve(relative)?;
+        if target.exists() {
+            return status(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        if !target.parent().is_some_and(StdPath::is_dir) {
+            return status(StatusCode::CONFLICT);
+        }
+        tokio::fs::create_dir(&target).await?;
+        status(StatusCode::CREATED)
+    }
+
+    async fn copy_or_move(
+        &self,
+        relative: &str,
+        headers: &HeaderMap,
+        is_move: bool,
+        rules: &mut UploadRules,
+    ) -> Result<Response, ApiError> {
+        let Some(destination) = headers
+            .get("Destination")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| self.destination_path(value))
+        else {
+            return status(StatusCode::BAD_REQUEST);
+        };
+        if relative.is_empty() || destination.is_empty() {
+            return status(StatusCode::FORBIDDEN);
+        }
+
+        let source = self.resolve(relative)?;
+        let target = self.resolve(&destination)?;
+        if !source.exists() {
+            return status(StatusCode::NOT_FOUND);
+        }
+        if source == target {
+            return status(StatusCode::FORBIDDEN);
+        }
+        if !target.parent().is_some_and(StdPath::is_dir) {
+            return status(StatusCode::CONFLICT);
+        }
+
+        let overwrite = headers
+            .get("Overwrite")
+            .is_none_or(|value| value.as_bytes() != b"F");
+        let existed = target.exists();
+        if existed && !overwrite {
+            return status(StatusCode::PRECONDITION_FAILED);
+        }
+
+        // The files get new names, so they pass the upload rules like a PUT.
+        // A move adds nothing to what is stored, so only copies are counted
+        // and recorded.
+        let incoming = {
+            let source = source.clone();
+            let destination = destination.clone();
+            tokio::task::spawn_blocking(move || files_below(&source, &destination))
+                .await
+                .map_err(|e| ApiError::Io(std::io::Error::other(e)))??
+        };
+        for (relative, path) in &incoming {
+            let data = tokio::fs::read(path).await?;
+            rules.check(&self.root, relative, &data, !is_move).await?;
+        }
+
+        if existed {
+            if target.is_dir() {
+                tokio::fs::remove_dir_all(&target).await?;
+            } else {
+                tokio::fs::remove_file(&target).await?;
+            }
+        }
+
+        if is_move {
+            tokio::fs::rename(&source, &target).await?;
+        } else {
+            tokio::task::spawn_blocking(move || copy_recursive(&source, &target))
+                .await
+                .map_err(|e| ApiError::Io(std::io::Error::other(e)))??;
+            for (relative, _) in &incoming {
+                let data = tokio::fs::read(self.resolve(relative)?).await?;
+                rules.record(relative, &data).await?;
+            }
+        }
+        status(if existed {
+            StatusCode::NO_CONTENT
+        } else {
+            StatusCode::CREATED
+        })
+    }
+
+    /// Turn a Destination header (absolute URL or path) into a workspace-relative path.
+    fn destination_path(&self, destination: &str) -> Option<String> {
+        let path = match url::Url::parse(destination) {
+            Ok(url) => url.path().to_string(),
+            Err(_) => destination.to_string(),
+        };
+        let relative = path.strip_prefix(&self.base_href)?;
+        let decoded = urlencoding::decode(relative).ok()?;
+        Some(decoded.trim_matches('/').to_string())
+    }
+
+    async fn propfind(&self, relative: &str, headers: &HeaderMap) -> Result<Response, ApiError> {
+        let target = self.resolve(relative)?;
+        let metadata = match tokio::fs::metadata(&target).await {
+            Ok(metadata) => metadata,
+            Err(_) => return status(StatusCode::NOT_FOUND),
+        };
+        // "infinity" is treated as 1 to keep listings of large worktrees cheap
+        let depth_zero = headers
+            .get("Depth")
+            .is_some_and(|value| value.as_bytes() == b"0");
+
+        let mut xml =
+            String::from(r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#);
+        push_prop_response(&mut xml, &self.href(relative, metadata.is_dir()), &metadata);
+
+        if metadata.is_dir() && !depth_zero {
+            let mut entries = tokio::fs::read_dir(&target).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name == ".git" {
+                    continue;
+                }
+                let child = if relative.is_empty() {
+                    name
+                } else {
+                    format!("{relative}/{name}")
+                };
+                // Skip entries (e.g. symlinks) that resolve outside the workspace
+                if workspace_files::ensure_within_root(&self.root, &entry.path()).is_err() {
+                    continue;
+                }
+                let Ok(child_metadata) = tokio::fs::metadata(entry.path()).await else {
+                    continue;
+                };
+                push_prop_response(
+                    &mut xml,
+                    &self.href(&child, child_metadata.is_dir()),
+                    &child_metadata,
+                );
+            }
+        }
+        xml.push_str("</D:multistatus>");
+
+        Response::builder()
+            .status(StatusCode::MULTI_STATUS)
+            .header(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/xml; charset=utf-8"),
+            )
+            .body(Body::from(xml))
+            .map_err(|e| ApiError::Io(std::io::Error::other(e)))
+    }
+}
+
+fn push_prop_response(xml: &mut String, href: &str, metadata: &std::fs::Metadata) {
+    let name = href
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .and_then(|segment| urlencoding::decode(segment).ok())
+        .unwrap_or_default();
+    let resource_type = if metadata.is_dir() {
+        "<D:collection/>"
+    } else {
+        ""
+    };
+    xml.push_str(&format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>\
+         <D:displayname>{}</D:displayname>\
+         <D:resourcetype>{}</D:resourcetype>\
+         <D:getlastmodified>{}</D:getlastmodified>",
+        xml_escape(href),
+        xml_escape(&name),
+        resource_type,
+        http_date(metadata),
+    ));
+    if !metadata.is_dir() {
+        xml.push_str(&format!(
+            "<D:getcontentlength>{}</D:getcontentlength>",
+            metadata.len()
+        ));
+    }
+    xml.push_str("</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>");
+}
+
+/// Locks are advisory only: clients such as Finder refuse to write without them,
+/// but nothing else edits the workspace through WebDAV concurrently.
+fn lock_response(href: &str) -> Response {
+    let token = format!("opaquelocktoken:{}", Uuid::new_v4());
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><D:prop xmlns:D="DAV:"><D:lockdiscovery><D:activelock><D:locktype><D:write/></D:locktype><D:lockscope><D:exclusive/></D:lockscope><D:depth>infinity</D:depth><D:timeout>Second-3600</D:timeout><D:locktoken><D:href>{token}</D:href></D:locktoken><D:lockroot><D:href>{}</D:href></D:lockroot></D:activelock></D:lockdiscovery></D:prop>"#,
+        xml_escape(href)
+    );
+    (
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                "application/xml; charset=utf-8".to_string(),
+            ),
+            (
+                header::HeaderName::from_static("lock-token"),
+                format!("<{token}>"),
+            ),
+        ],
+        xml,
+    )
+        .into_response()
+}
+
+fn copy_recursive(source: &StdPath, target: &StdPath) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(source)?;
+    if metadata.is_dir() {
+        std::fs::create_dir(target)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+        }
+    } else if metadata.is_file() {
+        std::fs::copy(source, target)?;
+    }
+    // Symlinks are not copied so a COPY can't duplicate content from outside the workspace
+    Ok(())
+}
+
+/// The regular files at or below `source`, with the paths they get when
+/// `source` is copied or moved to `destination`. Symlinks are skipped like in
+/// [`copy_recursive`].
+fn files_below(source: &StdPath, destination: &str) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let metadata = std::fs::symlink_metadata(source)?;
+    let mut files = Vec::new();
+    if metadata.is_dir() {
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            files.extend(files_below(
+                &entry.path(),
+                &format!("{destination}/{name}"),
+            )?);
+        }
+    } else if metadata.is_file() {
+        files.push((destination.to_string(), source.to_path_buf()));
+    }
+    Ok(files)
+}
+
+fn http_date(metadata: &std::fs::Metadata) -> String {
+    let modified: DateTime<Utc> = metadata
+        .modified()
+        .map(DateTime::from)
+        .unwrap_or_else(|_| Utc::now());
+    modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn status(code: StatusCode) -> Result<Response, ApiError> {
+    Ok(code.into_response())
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/webdav/{workspace_id}", any(handle_root))
+        .route("/webdav/{workspace_id}/", any(handle_root))
+        .route("/webdav/{workspace_id}/{*path}", any(handle_path))
+        .layer(DefaultBodyLimit::max(100 * 1024 * 1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_below_maps_sources_to_destination_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("docs");
+        std::fs::create_dir_all(source.join("nested")).unwrap();
+        std::fs::write(source.join("a.txt"), "a").unwrap();
+        std::fs::write(source.join("nested/b.txt"), "b").unwrap();
+
+        let mut files = files_below(&source, "out/docs").unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                ("out/docs/a.txt".to_string(), source.join("a.txt")),
+                (
+                    "out/docs/nested/b.txt".to_string(),
+                    source.join("nested/b.txt")
+                ),
+            ]
+        );
+        assert_eq!(
+            files_below(&source.join("a.txt"), "a.exe").unwrap(),
+            [("a.exe".to_string(), source.join("a.txt"))]
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Handing an attempt's changes to a second agent for review, and its
+//! feedback back to the attempt's agent.
+//!
+//! The reviewer gets the diff in its prompt rather than being pointed at the
+//! worktree, and answers with a JSON object at the end of its last message;
+//! an answer that can't be read fails the review instead of guessing.
+
+use db::models::{
+    agent_review::{AgentReviewComment, AgentReviewStatus},
+    task::Task,
+    workspace::Workspace,
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utils::diff::create_unified_diff;
+
+use super::{attempt_comparison::attempt_diffs, git::GitService};
+
+const MAX_DIFF_CHARS: usize = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVerdict {
+    Approve,
+    RequestChanges,
+}
+
+/// What the reviewer answered
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReviewFeedback {
+    pub verdict: ReviewVerdict,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub comments: Vec<AgentReviewComment>,
+}
+
+impl ReviewFeedback {
+    pub fn status(&self) -> AgentReviewStatus {
+        match self.verdict {
+            ReviewVerdict::Approve => AgentReviewStatus::Approved,
+            ReviewVerdict::RequestChanges => AgentReviewStatus::ChangesRequested,
+        }
+    }
+}
+
+/// The attempt's changes against its target branches as one unified diff,
+/// cut short when it gets too long for a prompt. Empty if nothing changed.
+pub async fn attempt_diff(
+    pool: &SqlitePool,
+    git: &GitService,
+    workspace: &Workspace,
+) -> Result<String, sqlx::Error> {
+    let mut diff = String::new();
+    for (repo_name, file) in attempt_diffs(pool, git, workspace).await? {
+        let path = format!("{repo_name}/{}", GitService::diff_path(&file));
+        if file.content_omitted {
+            diff.push_str(&format!("--- {path}\n(file too large to show)\n"));
+            continue;
+        }
+        diff.push_str(&create_unified_diff(
+            &path,
+            file.old_content.as_deref().unwrap_or_default(),
+            file.new_content.as_deref().unwrap_or_default(),
+        ));
+        if !diff.ends_with('\n') {
+            diff.push('\n');
+        }
+    }
+    if let Some((end, _)) = diff.char_indices().nth(MAX_DIFF_CHARS) {
+        diff.truncate(end);
+        diff.push_str("\n… the rest of the diff was left out\n");
+    }
+    Ok(diff)
+}
+
+/// The prompt of the reviewer's run
+pub fn review_prompt(task: &Task, diff: &str, instructions: Option<&str>) -> String {
+    let mut prompt = format!(
+        "Review the changes another agent made for this task. Don't modify any files; only report what you find.\n\nTask: {}",
+        task.title
+    );
+    if let Some(description) = task
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty())
+    {
+        prompt.push_str(&format!("\n\n{description}"));
+    }
+    prompt.push_str(&format!("\n\nThe changes:\n```diff\n{diff}```"));
+    if let Some(instructions) = instructions
+        .map(str::trim)
+        .filter(|instructions| !instructions.is_empty())
+    {
+        prompt.push_str(&format!("\n\nWhat to look for:\n{instructions}"));
+    }
+    prompt.push_str(
+        "\n\nEnd your last message with your verdict as a JSON object, and nothing after it:\n\
+         {\"verdict\": \"approve\" or \"request_changes\", \"summary\": \"...\", \"comments\": [{\"path\": \"...\", \"line\": 12, \"body\": \"...\", \"blocking\": true}]}\n\
+         Request changes only for problems that have to be fixed, and mark those comments as blocking; path and line may be left out.",
+    );
+    prompt
+}
+
+/// The last JSON object in the reviewer's message that reads as a verdict
+pub fn parse_feedback(message: &str) -> Option<ReviewFeedback> {
+    message
+        .char_indices()
+        .rev()
+        .filter(|(_, c)| *c == '{')
+        .find_map(|(start, _)| {
+            serde_json::Deserializer::from_str(&message[start..])
+                .into_iter::<ReviewFeedback>()
+                .next()
+                .and_then(Result::ok)
+        })
+}
+
+/// The follow-up prompt handing the reviewer's comments to the attempt's
+/// agent
+pub fn revision_prompt(feedback: &ReviewFeedback) -> String {
+    let mut prompt = String::from(
+        "A reviewer looked at your changes and asked for changes. Address its comments, keeping the rest of your work.",
+    );
+    if let Some(summary) = feedback
+        .summary
+        .as_deref()
+        .map(str::trim)
+        .filter(|summary| !summary.is_empty())
+    {
+        prompt.push_str(&format!("\n\n{summary}"));
+    }
+    if !feedback.comments.is_empty() {
+        prompt.push('\n');
+    }
+    for comment in &feedback.comments {
+        let kind = if comment.blocking {
+            "must fix"
+        } else {
+            "suggestion"
+        };
+        let location = match (&comment.path, comment.line) {
+            (Some(path), Some(line)) => format!(" on {path}:{line}"),
+            (Some(path), None) => format!(" on {path}"),
+            _ => String::new(),
+        };
+        prompt.push_str(&format!("\n- ({kind}){location}: {}", comment.body.trim()));
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdict_is_read_from_the_end_of_the_message() {
+        let message = r#"The handler looks fine, but {"the": "retry"} is unbounded.
+
+```json
+{"verdict": "request_changes", "summary": "Bound the retries.", "comments": [
+  {"path": "src/retry.rs", "line": 40, "body": "Cap the attempts.", "blocking": true},
+  {"body": "Consider logging each retry."}
+]}
+```"#;
+        let feedback = parse_feedback(message).unwrap();
+        assert_eq!(feedback.status(), AgentReviewStatus::ChangesRequested);
+        assert_eq!(feedback.comments.len(), 2);
+        assert!(!feedback.comments[1].blocking);
+
+        assert_eq!(
+            revision_prompt(&feedback),
+            "A reviewer looked at your changes and asked for changes. Address its comments, keeping the rest of your work.\n\n\
+             Bound the retries.\n\n\
+             - (must fix) on src/retry.rs:40: Cap the attempts.\n\
+             - (suggestion): Consider logging each retry."
+        );
+
+        assert_eq!(
+            parse_feedback(r#"All good. {"verdict": "approve"}"#).map(|f| f.status()),
+            Some(AgentReviewStatus::Approved)
+        );
+        assert_eq!(parse_feedback("Looks good to me."), None);
+        assert_eq!(parse_feedback(r#"{"verdict": "maybe"}"#), None);
+    }
+}
@@ -1,8 +1,17 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use db::{self, DBService};
-use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService};
+use db::{
+    self, DBService,
+    models::{
+        command_decision::{
+            CommandDecider, CommandDecision, CommandDecisionStatus, CreateCommandDecision,
+        },
+        execution_process::ExecutionProcess,
+        project_settings::{CommandPolicy, ProjectSettings},
+    },
+};
+use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService, requested_command};
 use serde_json::Value;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
@@ -30,6 +39,47 @@ impl ExecutorApprovalBridge {
             execution_process_id,
         })
     }
+
+    /// The attempt the execution belongs to and its project's command policy
+    async fn command_policy(&self) -> Option<(Uuid, CommandPolicy)> {
+        let ctx = ExecutionProcess::load_context(&self.db.pool, self.execution_process_id)
+            .await
+            .ok()?;
+        let settings = ProjectSettings::find_by_project_id(&self.db.pool, ctx.project.id)
+            .await
+            .ok()?;
+        Some((ctx.workspace.id, settings.command_policy))
+    }
+
+    async fn record_decision(
+        &self,
+        workspace_id: Uuid,
+        tool_call_id: &str,
+        command: &str,
+        status: &ApprovalStatus,
+        decided_by: CommandDecider,
+        rule: Option<String>,
+    ) {
+        let (status, reason) = match status {
+            ApprovalStatus::Approved => (CommandDecisionStatus::Approved, None),
+            ApprovalStatus::Denied { reason } => (CommandDecisionStatus::Denied, reason.clone()),
+            ApprovalStatus::TimedOut => (CommandDecisionStatus::TimedOut, None),
+            ApprovalStatus::Pending => return,
+        };
+        let decision = CreateCommandDecision {
+            workspace_id,
+            execution_process_id: self.execution_process_id,
+            tool_call_id: tool_call_id.to_string(),
+            command: command.to_string(),
+            status,
+            decided_by,
+            rule,
+            reason,
+        };
+        if let Err(e) = CommandDecision::create(&self.db.pool, &decision).await {
+            tracing::error!("Failed to record command decision: {}", e);
+        }
+    }
 }
 
 #[async_trait]
@@ -40,6 +90,38 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         tool_input: Value,
         tool_call_id: &str,
     ) -> Result<ApprovalStatus, ExecutorApprovalError> {
+        // Shell commands go through the project's command policy first, and
+        // every answer to them is kept on the attempt
+        let command = requested_command(tool_name, &tool_input);
+        let policy = match &command {
+            Some(_) => self.command_policy().await,
+            None => None,
+        };
+        if let (Some(command), Some((workspace_id, policy))) = (&command, &policy)
+            && let Some(verdict) = policy.decide(command)
+        {
+            let status = if verdict.approved {
+                ApprovalStatus::Approved
+            } else {
+                ApprovalStatus::Denied {
+                    reason: Some(format!(
+                        "The project's command policy denies `{}`",
+                        verdict.rule
+                    )),
+                }
+            };
+            self.record_decision(
+                *workspace_id,
+                tool_call_id,
+                command,
+                &status,
+                CommandDecider::Policy,
+                Some(verdict.rule),
+            )
+            .await;
+            return Ok(status);
+        }
+
         super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
 
         let request = ApprovalRequest::from_create(
@@ -73,6 +155,18 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             ));
         }
 
+        if let (Some(command), Some((workspace_id, _))) = (&command, &policy) {
+            self.record_decision(
+                *workspace_id,
+                tool_call_id,
+                command,
+                &status,
+                CommandDecider::User,
+                None,
+            )
+            .await;
+        }
+
         Ok(status)
     }
 }
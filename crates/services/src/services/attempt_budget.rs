@@ -0,0 +1,65 @@
+//! Where an attempt stands against the project's attempt limits.
+
+use db::models::{
+    project_settings::{AttemptLimits, ProjectSettings},
+    workspace_budget::{BudgetLimit, BudgetUsage, WorkspaceBudget},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptBudget {
+    /// The project's limits, times the extensions granted
+    pub limits: AttemptLimits,
+    pub used: BudgetUsage,
+    pub extensions: u32,
+    /// Limit that stopped the attempt's coding agent since the last extension
+    pub stopped_by: Option<BudgetLimit>,
+    /// Limit the attempt is at; new runs are refused until it is extended
+    pub reached: Option<BudgetLimit>,
+}
+
+/// The attempt's budget, with tool calls of a run still going added on
+pub async fn attempt_budget(
+    pool: &SqlitePool,
+    workspace_id: Uuid,
+    project_id: Uuid,
+    running_tool_calls: i64,
+) -> Result<AttemptBudget, sqlx::Error> {
+    let settings = ProjectSettings::find_by_project_id(pool, project_id)
+        .await?
+        .attempt_limits;
+    let state = WorkspaceBudget::find_by_workspace_id(pool, workspace_id).await?;
+    let extensions = state
+        .as_ref()
+        .map_or(0, |state| state.extensions.max(0) as u32);
+
+    let mut used = WorkspaceBudget::usage(pool, workspace_id).await?;
+    used.tool_calls += running_tool_calls;
+    let limits = settings.extended(extensions);
+
+    Ok(AttemptBudget {
+        reached: limits.reached(&used),
+        stopped_by: state.and_then(|state| state.stopped_by),
+        limits,
+        used,
+        extensions,
+    })
+}
+
+/// The limit in words, e.g. "30 minutes of agent time"
+pub fn describe_limit(limit: BudgetLimit, limits: &AttemptLimits) -> String {
+    match limit {
+        BudgetLimit::Time => format!(
+            "{} minutes of agent time",
+            limits.max_minutes.unwrap_or_default()
+        ),
+        BudgetLimit::Tokens => format!("{} tokens", limits.max_tokens.unwrap_or_default()),
+        BudgetLimit::Cost => format!("${:.2}", limits.max_cost_usd.unwrap_or_default()),
+        BudgetLimit::ToolCalls => {
+            format!("{} tool calls", limits.max_tool_calls.unwrap_or_default())
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Figures for comparing the attempts at a task side by side: what each one
+//! changed, whether its checks passed, how long it took and what its agent
+//! cost.
+//!
+//! Test results come from the attempt's last cleanup script run, which is
+//! where projects run their tests and linters. Token usage is read back from
+//! the agents' stored output, so it is only known for agents that report it.
+
+use std::{collections::BTreeSet, path::PathBuf};
+
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process_logs::ExecutionProcessLogs,
+    session::Session,
+    task::Task,
+    workspace::{Workspace, WorkspaceError},
+    workspace_repo::WorkspaceRepo,
+};
+use executors::executors::claude::ClaudeJson;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::{diff::Diff, log_msg::LogMsg};
+
+use super::{
+    git::{DiffTarget, GitService},
+    task_scope, time_report,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TestRun {
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct TokenUsage {
+    /// Cache writes and reads included
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Only set when the agent reported a cost for every run
+    pub cost_usd: Option<f64>,
+}
+
+impl TokenUsage {
+    fn plus(self, other: TokenUsage) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            cost_usd: self.cost_usd.zip(other.cost_usd).map(|(a, b)| a + b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptComparison {
+    pub workspace: Workspace,
+    /// Executor of the attempt's first coding agent run
+    pub executor: Option<String>,
+    /// Against the base of the attempt's branch, uncommitted changes included
+    pub diff: DiffStats,
+    /// Paths changed, prefixed with the repository name
+    pub files: Vec<String>,
+    /// Changed paths outside the task's scope
+    pub out_of_scope: Vec<String>,
+    /// Last cleanup script run, if there was one
+    pub test_run: Option<TestRun>,
+    pub wall_clock_seconds: i64,
+    pub agent_seconds: i64,
+    /// None when the agent doesn't report token usage
+    pub tokens: Option<TokenUsage>,
+}
+
+/// The task's attempts, newest first
+pub async fn compare_attempts(
+    pool: &SqlitePool,
+    git: &GitService,
+    task: &Task,
+) -> Result<Vec<AttemptComparison>, WorkspaceError> {
+    let times = time_report::task_time(pool, task).await?;
+    let scope = task_scope::task_scope(task);
+    let workspaces = Workspace::fetch_all(pool, Some(task.id)).await?;
+
+    let mut comparisons = Vec::with_capacity(workspaces.len());
+    for workspace in workspaces {
+        let changed = changed_files(pool, git, &workspace).await?;
+        let (diff, files) = diff_stats(&changed);
+        let out_of_scope = scope
+            .as_ref()
+            .map(|scope| scope.out_of_scope(&changed))
+            .unwrap_or_default();
+        let test_run = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CleanupScript,
+        )
+        .await?
+        .map(|process| TestRun {
+            status: process.status,
+            exit_code: process.exit_code,
+        });
+        let tokens = agent_token_usage(pool, &workspace).await?;
+        let time = times
+            .attempts
+            .iter()
+            .find(|attempt| attempt.workspace_id == workspace.id);
+
+        comparisons.push(AttemptComparison {
+            executor: time.and_then(|time| time.executor.clone()),
+            diff,
+            files,
+            out_of_scope,
+            test_run,
+            wall_clock_seconds: time.map_or(0, |time| time.wall_clock_seconds),
+            agent_seconds: time.map_or(0, |time| time.agent_seconds),
+            tokens,
+            workspace,
+        });
+    }
+    Ok(comparisons)
+}
+
+/// A file an attempt changed
+pub(crate) struct ChangedFile {
+    pub repo_name: String,
+    /// Relative to the repository root
+    pub path: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Files the attempt changed against the base of its branch in each of its
+/// repositories, uncommitted changes included while the worktree exists
+pub(crate) async fn changed_files(
+    pool: &SqlitePool,
+    git: &GitService,
+    workspace: &Workspace,
+) -> Result<Vec<ChangedFile>, sqlx::Error> {
+    Ok(attempt_diffs(pool, git, workspace)
+        .await?
+        .into_iter()
+        .map(|(repo_name, diff)| ChangedFile {
+            path: GitService::diff_path(&diff).to_string(),
+            additions: diff.additions.unwrap_or(0),
+            deletions: diff.deletions.unwrap_or(0),
+            repo_name,
+        })
+        .collect())
+}
+
+/// The attempt's changes against the base of its branch, with the name of
+/// the repository each is in, uncommitted changes included while the
+/// worktree exists
+pub(crate) async fn attempt_diffs(
+    pool: &SqlitePool,
+    git: &GitService,
+    workspace: &Workspace,
+) -> Result<Vec<(String, Diff)>, sqlx::Error> {
+    let workspace_repos = WorkspaceRepo::find_by_workspace_id(pool, workspace.id).await?;
+    let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+
+    let mut changes = Vec::new();
+    for repo in repos {
+        let Some(target_branch) = workspace_repos
+            .iter()
+            .find(|workspace_repo| workspace_repo.repo_id == repo.id)
+            .map(|workspace_repo| workspace_repo.target_branch.as_str())
+        else {
+            continue;
+        };
+
+        // The worktree also holds what the agent hasn't committed; once it is
+        // cleaned up only the branch is left
+        let worktree_path = workspace
+            .container_ref
+            .as_ref()
+            .map(|container_ref| PathBuf::from(container_ref).join(&repo.name))
+            .filter(|path| path.exists());
+        let diffs = match worktree_path {
+            Some(worktree_path) => git
+                .get_base_commit(&repo.path, &workspace.branch, target_branch)
+                .and_then(|base_commit| {
+                    git.get_diffs(
+                        DiffTarget::Worktree {
+                            worktree_path: &worktree_path,
+                            base_commit: &base_commit,
+                        },
+                        None,
+                    )
+                }),
+            None => git.get_diffs(
+                DiffTarget::Branch {
+                    repo_path: &repo.path,
+                    branch_name: &workspace.branch,
+                    base_branch: target_branch,
+                },
+                None,
+            ),
+        };
+        let diffs = match diffs {
+            Ok(diffs) => diffs,
+            Err(e) => {
+                tracing::warn!(
+                    "Leaving out the changes to repo {} of workspace {}: {}",
+                    repo.name,
+                    workspace.id,
+                    e
+                );
+                continue;
+            }
+        };
+
+        changes.extend(diffs.into_iter().map(|diff| (repo.name.clone(), diff)));
+    }
+    Ok(changes)
+}
+
+fn diff_stats(changed: &[ChangedFile]) -> (DiffStats, Vec<String>) {
+    let files: BTreeSet<String> = changed
+        .iter()
+        .map(|file| format!("{}/{}", file.repo_name, file.path))
+        .collect();
+    let stats = DiffStats {
+        files_changed: files.len(),
+        additions: changed.iter().map(|file| file.additions).sum(),
+        deletions: changed.iter().map(|file| file.deletions).sum(),
+    };
+    (stats, files.into_iter().collect())
+}
+
+async fn agent_token_usage(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+) -> Result<Option<TokenUsage>, sqlx::Error> {
+    let mut total: Option<TokenUsage> = None;
+    for session in Session::find_by_workspace_id(pool, workspace.id).await? {
+        for process in ExecutionProcess::find_by_session_id(pool, session.id, true).await? {
+            if process.run_reason != ExecutionProcessRunReason::CodingAgent {
+                continue;
+            }
+            let records = ExecutionProcessLogs::find_by_execution_id(pool, process.id).await?;
+            let Ok(messages) = ExecutionProcessLogs::parse_logs(&records) else {
+                continue;
+            };
+            let stdout: String = messages
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    LogMsg::Stdout(chunk) => Some(chunk),
+                    _ => None,
+                })
+                .collect();
+            if let Some(usage) = token_usage(&stdout) {
+                total = Some(match total {
+                    Some(total) => total.plus(usage),
+                    None => usage,
+                });
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Usage an agent reported in the result lines of its JSON output
+fn token_usage(stdout: &str) -> Option<TokenUsage> {
+    let mut total: Option<TokenUsage> = None;
+    for line in stdout.lines() {
+        let Ok(ClaudeJson::Result {
+            usage: Some(usage),
+            total_cost_usd,
+            ..
+        }) = serde_json::from_str::<ClaudeJson>(line.trim())
+        else {
+            continue;
+        };
+        let usage = TokenUsage {
+            input_tokens: usage.input_tokens.unwrap_or(0)
+                + usage.cache_creation_input_tokens.unwrap_or(0)
+                + usage.cache_read_input_tokens.unwrap_or(0),
+            output_tokens: usage.output_tokens.unwrap_or(0),
+            cost_usd: total_cost_usd,
+        };
+        total = Some(match total {
+            Some(total) => total.plus(usage),
+            None => usage,
+        });
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_the_usage_of_every_result_line() {
+        let stdout = concat!(
+            r#"{"type":"assistant","message":{"role":"assistant","content":[]}}"#,
+            "\n",
+            r#"{"type":"result","subtype":"success","is_error":false,"total_cost_usd":0.25,"usage":{"input_tokens":10,"cache_read_input_tokens":90,"output_tokens":40}}"#,
+            "\n",
+            r#"{"type":"result","subtype":"success","is_error":false,"total_cost_usd":0.5,"usage":{"input_tokens":5,"output_tokens":15}}"#,
+            "\n",
+        );
+
+        assert_eq!(
+            token_usage(stdout),
+            Some(TokenUsage {
+                input_tokens: 105,
+                output_tokens: 55,
+                cost_usd: Some(0.75),
+            })
+        );
+        assert_eq!(token_usage("plain output\n"), None);
+    }
+}
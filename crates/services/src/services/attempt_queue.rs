@@ -0,0 +1,225 @@
+//! Attempts waiting for a slot under the limits on attempts running at once.
+//!
+//! New attempts always join the queue, and the queue is worked off right
+//! away, so an attempt only stays queued while the global limit or its
+//! project's limit is reached. An attempt counts as running while any of its
+//! processes other than a dev server is, or while it is still starting. One
+//! that fails to start goes back in the queue with the error, and isn't
+//! started again until it is stopped.
+
+use std::{collections::HashMap, sync::Mutex as StdMutex};
+
+use db::models::{
+    queued_attempt::{QueuedAttempt, RunningAttemptCount},
+    task::TaskPriority,
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::services::config::AttemptQueueOrder;
+
+/// Held while attempts are taken off the queue, so two callers can't both
+/// fill the last slot
+pub(crate) static ADMISSION: Mutex<()> = Mutex::const_new(());
+
+/// Attempts taken off the queue whose processes haven't started yet, as
+/// (workspace, project)
+static STARTING: StdMutex<Vec<(Uuid, Uuid)>> = StdMutex::new(Vec::new());
+
+/// Counts an attempt taken off the queue as running until it is dropped, by
+/// which time its processes are running or it failed to start
+pub struct StartingAttempt(Uuid);
+
+impl StartingAttempt {
+    pub(crate) fn new(attempt: &QueuedAttempt) -> Self {
+        STARTING
+            .lock()
+            .unwrap()
+            .push((attempt.workspace_id, attempt.project_id));
+        Self(attempt.workspace_id)
+    }
+}
+
+impl Drop for StartingAttempt {
+    fn drop(&mut self) {
+        STARTING
+            .lock()
+            .unwrap()
+            .retain(|(workspace_id, _)| *workspace_id != self.0);
+    }
+}
+
+/// Running attempts per project, including those that are still starting
+pub(crate) fn with_starting(running: Vec<RunningAttemptCount>) -> Vec<RunningAttemptCount> {
+    add_starting(running, &STARTING.lock().unwrap())
+}
+
+fn add_starting(
+    mut running: Vec<RunningAttemptCount>,
+    starting: &[(Uuid, Uuid)],
+) -> Vec<RunningAttemptCount> {
+    for (_, project_id) in starting {
+        match running
+            .iter_mut()
+            .find(|count| count.project_id == *project_id)
+        {
+            Some(count) => count.attempts += 1,
+            None => running.push(RunningAttemptCount {
+                project_id: *project_id,
+                attempts: 1,
+            }),
+        }
+    }
+    running
+}
+
+/// What became of an attempt that was asked to start
+#[derive(Debug)]
+pub enum AttemptStart {
+    Started,
+    Queued(QueuedAttempt),
+}
+
+/// The queue in the order attempts leave it
+pub fn in_order(mut queue: Vec<QueuedAttempt>, order: AttemptQueueOrder) -> Vec<QueuedAttempt> {
+    if order == AttemptQueueOrder::Priority {
+        // Stable, so attempts of equal priority keep the order they queued in
+        queue.sort_by_key(|attempt| priority_rank(attempt.priority));
+    }
+    queue
+}
+
+fn priority_rank(priority: TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::Urgent => 0,
+        TaskPriority::High => 1,
+        TaskPriority::Normal => 2,
+        TaskPriority::Low => 3,
+    }
+}
+
+/// Queued attempts that can start now, in the order to start them. An
+/// attempt held back by its project's limit doesn't hold up those of other
+/// projects, and attempts that failed to start are left where they are.
+pub fn admit(
+    ordered: &[QueuedAttempt],
+    running: &[RunningAttemptCount],
+    global_limit: Option<u32>,
+    project_limits: &HashMap<Uuid, Option<u32>>,
+) -> Vec<Uuid> {
+    let mut per_project: HashMap<Uuid, i64> = running
+        .iter()
+        .map(|count| (count.project_id, count.attempts))
+        .collect();
+    let mut total: i64 = per_project.values().sum();
+    let within = |limit: Option<u32>, count: i64| limit.is_none_or(|max| count < i64::from(max));
+
+    let mut admitted = Vec::new();
+    for attempt in ordered
+        .iter()
+        .filter(|attempt| attempt.start_error.is_none())
+    {
+        if !within(global_limit, total) {
+            break;
+        }
+        let project_count = per_project.entry(attempt.project_id).or_default();
+        let project_limit = project_limits.get(&attempt.project_id).copied().flatten();
+        if !within(project_limit, *project_count) {
+            continue;
+        }
+        *project_count += 1;
+        total += 1;
+        admitted.push(attempt.workspace_id);
+    }
+    admitted
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+    use sqlx::types::Json;
+
+    use super::*;
+
+    fn queued(project_id: Uuid, priority: TaskPriority, minute: u32) -> QueuedAttempt {
+        QueuedAttempt {
+            workspace_id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            project_id,
+            task_title: format!("task {minute}"),
+            priority,
+            executor_profile_id: Json(ExecutorProfileId::new(BaseCodingAgent::ClaudeCode)),
+            enqueued_at: Utc.with_ymd_and_hms(2025, 12, 22, 9, minute, 0).unwrap(),
+            start_error: None,
+        }
+    }
+
+    #[test]
+    fn attempts_start_while_both_limits_leave_room() {
+        let (busy, idle) = (Uuid::new_v4(), Uuid::new_v4());
+        let queue = vec![
+            queued(busy, TaskPriority::Normal, 0),
+            queued(idle, TaskPriority::Low, 1),
+            queued(idle, TaskPriority::Urgent, 2),
+            queued(idle, TaskPriority::Normal, 3),
+        ];
+        let running = vec![RunningAttemptCount {
+            project_id: busy,
+            attempts: 1,
+        }];
+        let limits = HashMap::from([(busy, Some(1)), (idle, None)]);
+
+        let fifo = in_order(queue.clone(), AttemptQueueOrder::Fifo);
+        assert_eq!(
+            admit(&fifo, &running, Some(3), &limits),
+            vec![queue[1].workspace_id, queue[2].workspace_id]
+        );
+
+        let by_priority = in_order(queue.clone(), AttemptQueueOrder::Priority);
+        assert_eq!(
+            admit(&by_priority, &running, Some(3), &limits),
+            vec![queue[2].workspace_id, queue[3].workspace_id]
+        );
+
+        assert_eq!(admit(&fifo, &running, None, &limits).len(), 3);
+        assert!(admit(&fifo, &running, Some(1), &limits).is_empty());
+    }
+
+    #[test]
+    fn starting_attempts_take_their_slots() {
+        let (busy, idle) = (Uuid::new_v4(), Uuid::new_v4());
+        let queue = vec![
+            queued(busy, TaskPriority::Normal, 0),
+            queued(idle, TaskPriority::Normal, 1),
+        ];
+        let limits = HashMap::from([(busy, Some(1)), (idle, None)]);
+        let running = add_starting(
+            vec![RunningAttemptCount {
+                project_id: idle,
+                attempts: 1,
+            }],
+            &[(Uuid::new_v4(), busy), (Uuid::new_v4(), idle)],
+        );
+
+        assert_eq!(
+            admit(&queue, &running, Some(4), &limits),
+            vec![queue[1].workspace_id]
+        );
+        assert!(admit(&queue, &running, Some(3), &limits).is_empty());
+    }
+
+    #[test]
+    fn attempts_that_failed_to_start_stay_queued() {
+        let project = Uuid::new_v4();
+        let mut failed = queued(project, TaskPriority::Urgent, 0);
+        failed.start_error = Some("before-start hook failed".to_string());
+        let queue = vec![failed, queued(project, TaskPriority::Normal, 1)];
+        let limits = HashMap::from([(project, Some(1))]);
+
+        assert_eq!(
+            admit(&queue, &[], None, &limits),
+            vec![queue[1].workspace_id]
+        );
+    }
+}
@@ -0,0 +1,530 @@
+//! Azure DevOps (Azure Repos) pull requests, called through its REST API with
+//! a personal access token from the project's secrets. The same token
+//! authenticates pushes of attempt branches, so the machine needs no Git
+//! credential manager for Azure.
+//!
+//! Work items the task mentions, as `#123` or `AB#123`, are linked to its pull
+//! requests and listed in their description.
+
+use std::path::Path;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use db::models::{
+    merge::{MergeStatus, MergeStrategy, PullRequestInfo},
+    project_settings::{AzureDevOpsSettings, ProjectSettings},
+};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::json;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use url::Url;
+use uuid::Uuid;
+
+use super::{
+    git::{GitService, GitServiceError},
+    github::{CreatePrRequest, UnifiedPrComment},
+    secrets::{ProjectEnv, SecretsError},
+};
+
+const API_VERSION: &str = "7.1";
+
+#[derive(Debug, Error)]
+pub enum AzureDevOpsServiceError {
+    #[error("No Azure DevOps token: the project has no secret named {0}")]
+    MissingToken(String),
+    #[error("Azure DevOps rejected the token: {0}")]
+    AuthFailed(String),
+    #[error("The Azure DevOps token isn't allowed to do this: {0}")]
+    InsufficientPermissions(String),
+    #[error("Azure DevOps repository not found or not accessible: {0}")]
+    RepoNotFound(String),
+    #[error("The pull request can't be merged: {0}")]
+    NotMergeable(String),
+    #[error("{0} isn't the remote of an Azure DevOps repository")]
+    UnsupportedRemote(String),
+    #[error("Azure DevOps request failed: {0}")]
+    Request(String),
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<reqwest::Error> for AzureDevOpsServiceError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Request(err.to_string())
+    }
+}
+
+/// A Git repository of an Azure DevOps project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AzureRepo {
+    pub organization: String,
+    pub project: String,
+    pub repository: String,
+}
+
+impl AzureRepo {
+    fn api_url(&self, endpoint: &str) -> String {
+        format!(
+            "https://dev.azure.com/{}/{}/_apis/git/repositories/{}{endpoint}",
+            self.organization,
+            urlencoding::encode(&self.project),
+            urlencoding::encode(&self.repository)
+        )
+    }
+
+    fn pull_request_url(&self, id: i64) -> String {
+        format!(
+            "https://dev.azure.com/{}/{}/_git/{}/pullrequest/{id}",
+            self.organization,
+            urlencoding::encode(&self.project),
+            urlencoding::encode(&self.repository)
+        )
+    }
+}
+
+/// The Azure DevOps repository a remote points at, from its dev.azure.com,
+/// legacy visualstudio.com or SSH form
+pub fn repo_from_remote(remote_url: &str) -> Option<AzureRepo> {
+    let repo = |organization: &str, project: &str, repository: &str| {
+        let decode = |part: &str| {
+            urlencoding::decode(part)
+                .map(|part| part.into_owned())
+                .unwrap_or_else(|_| part.to_string())
+        };
+        Some(AzureRepo {
+            organization: decode(organization),
+            project: decode(project),
+            repository: decode(repository.trim_end_matches(".git")),
+        })
+    };
+    // git@ssh.dev.azure.com:v3/org/project/repo
+    if let Some((_, path)) = remote_url.split_once("ssh.dev.azure.com:v3/") {
+        return match path.split('/').collect::<Vec<_>>().as_slice() {
+            [organization, project, repository] => repo(organization, project, repository),
+            _ => None,
+        };
+    }
+    let url = Url::parse(remote_url).ok()?;
+    let host = url.host_str()?;
+    let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+    match (host, segments.as_slice()) {
+        ("dev.azure.com", [organization, project, "_git", repository, ..]) => {
+            repo(organization, project, repository)
+        }
+        (host, [.., project, "_git", repository]) if host.ends_with(".visualstudio.com") => {
+            let organization = host.trim_end_matches(".visualstudio.com");
+            repo(organization, project, repository)
+        }
+        _ => None,
+    }
+}
+
+/// The repository and id of a pull request from its web URL
+pub fn parse_pull_request_url(url: &str) -> Option<(AzureRepo, i64)> {
+    let (repo_url, rest) = url.split_once("/pullrequest/")?;
+    let id = rest
+        .split(['/', '#', '?'])
+        .next()
+        .and_then(|id| id.parse().ok())?;
+    Some((repo_from_remote(repo_url)?, id))
+}
+
+/// Work item ids mentioned as `#123` or `AB#123`, in order of appearance
+pub fn work_item_ids(text: &str) -> Vec<u32> {
+    let mut ids = Vec::new();
+    for (index, _) in text.match_indices('#') {
+        let before = text[..index].trim_end_matches("AB");
+        if before.ends_with(|c: char| c.is_alphanumeric() || c == '&' || c == '/') {
+            continue;
+        }
+        let digits: String = text[index + 1..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        if let Ok(id) = digits.parse()
+            && !ids.contains(&id)
+        {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureCommit {
+    commit_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzurePullRequest {
+    pull_request_id: i64,
+    status: String,
+    closed_date: Option<DateTime<Utc>>,
+    last_merge_commit: Option<AzureCommit>,
+    last_merge_source_commit: Option<AzureCommit>,
+}
+
+impl AzurePullRequest {
+    fn into_info(self, repo: &AzureRepo) -> PullRequestInfo {
+        let status = match self.status.as_str() {
+            "active" => MergeStatus::Open,
+            "completed" => MergeStatus::Merged,
+            "abandoned" => MergeStatus::Closed,
+            _ => MergeStatus::Unknown,
+        };
+        let merged = matches!(status, MergeStatus::Merged);
+        PullRequestInfo {
+            number: self.pull_request_id,
+            url: repo.pull_request_url(self.pull_request_id),
+            status,
+            merged_at: self.closed_date.filter(|_| merged),
+            merge_commit_sha: self
+                .last_merge_commit
+                .filter(|_| merged)
+                .map(|commit| commit.commit_id),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureList<T> {
+    value: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureIdentity {
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureThread {
+    thread_context: Option<AzureThreadContext>,
+    #[serde(default)]
+    comments: Vec<AzureComment>,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureThreadContext {
+    file_path: Option<String>,
+    right_file_start: Option<AzureFilePosition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureFilePosition {
+    line: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AzureComment {
+    id: i64,
+    author: AzureIdentity,
+    content: Option<String>,
+    published_date: DateTime<Utc>,
+    comment_type: Option<String>,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AzureDevOpsService {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl AzureDevOpsService {
+    pub fn new(token: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            token,
+        }
+    }
+
+    /// The service with the project's token, and the project's Azure DevOps
+    /// settings
+    pub async fn for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<(Self, AzureDevOpsSettings), AzureDevOpsServiceError> {
+        let settings = ProjectSettings::find_by_project_id(pool, project_id)
+            .await?
+            .code_host
+            .azure_devops;
+        let env = ProjectEnv::load(pool, project_id).await?;
+        let token = env
+            .vars
+            .iter()
+            .find(|(name, _)| *name == settings.token_secret)
+            .map(|(_, value)| value.clone())
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| AzureDevOpsServiceError::MissingToken(settings.token_secret.clone()))?;
+        Ok((Self::new(token), settings))
+    }
+
+    /// The Azure DevOps repository of the repository's remote
+    pub fn repo_for_path(
+        git: &GitService,
+        repo_path: &Path,
+    ) -> Result<AzureRepo, AzureDevOpsServiceError> {
+        let remote_url = git.get_default_remote_url(repo_path)?;
+        repo_from_remote(&remote_url).ok_or(AzureDevOpsServiceError::UnsupportedRemote(remote_url))
+    }
+
+    /// The HTTP header git sends to authenticate with the token
+    pub fn git_auth_header(&self) -> String {
+        format!(
+            "Authorization: Basic {}",
+            STANDARD.encode(format!(":{}", self.token))
+        )
+    }
+
+    fn request(&self, method: Method, url: String) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .basic_auth("", Some(&self.token))
+            .query(&[("api-version", API_VERSION)])
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, AzureDevOpsServiceError> {
+        let response = request.send().await?;
+        let status = response.status();
+        // An expired or wrong token is answered with a sign-in page
+        if status == StatusCode::NON_AUTHORITATIVE_INFORMATION {
+            return Err(AzureDevOpsServiceError::AuthFailed(status.to_string()));
+        }
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+        let message = response.text().await.unwrap_or_default();
+        let message = format!("{status}: {}", message.trim());
+        Err(match status {
+            StatusCode::UNAUTHORIZED => AzureDevOpsServiceError::AuthFailed(message),
+            StatusCode::FORBIDDEN => AzureDevOpsServiceError::InsufficientPermissions(message),
+            StatusCode::NOT_FOUND => AzureDevOpsServiceError::RepoNotFound(message),
+            StatusCode::CONFLICT => AzureDevOpsServiceError::NotMergeable(message),
+            _ => AzureDevOpsServiceError::Request(message),
+        })
+    }
+
+    /// Open a pull request linked to `work_items`, which are listed at the end
+    /// of its description
+    pub async fn create_pr(
+        &self,
+        repo: &AzureRepo,
+        request: &CreatePrRequest,
+        work_items: &[u32],
+    ) -> Result<PullRequestInfo, AzureDevOpsServiceError> {
+        let mut description = request.body.clone().unwrap_or_default();
+        if !work_items.is_empty() {
+            let mentions: Vec<String> = work_items.iter().map(|id| format!("#{id}")).collect();
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            description.push_str(&format!("Work items: {}", mentions.join(", ")));
+        }
+        let body = json!({
+            "sourceRefName": format!("refs/heads/{}", request.head_branch),
+            "targetRefName": format!("refs/heads/{}", request.base_branch),
+            "title": request.title,
+            "description": description,
+            "isDraft": request.draft.unwrap_or(false),
+            "workItemRefs": work_items
+                .iter()
+                .map(|id| json!({ "id": id.to_string() }))
+                .collect::<Vec<_>>(),
+        });
+        let pr: AzurePullRequest = self
+            .send(
+                self.request(Method::POST, repo.api_url("/pullrequests"))
+                    .json(&body),
+            )
+            .await?;
+        Ok(pr.into_info(repo))
+    }
+
+    async fn fetch_pr(
+        &self,
+        repo: &AzureRepo,
+        id: i64,
+    ) -> Result<AzurePullRequest, AzureDevOpsServiceError> {
+        self.send(self.request(Method::GET, repo.api_url(&format!("/pullrequests/{id}"))))
+            .await
+    }
+
+    pub async fn get_pr(
+        &self,
+        repo: &AzureRepo,
+        id: i64,
+    ) -> Result<PullRequestInfo, AzureDevOpsServiceError> {
+        Ok(self.fetch_pr(repo, id).await?.into_info(repo))
+    }
+
+    /// Pull requests from the branch in any state, active ones first
+    pub async fn list_prs_for_branch(
+        &self,
+        repo: &AzureRepo,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, AzureDevOpsServiceError> {
+        let source = format!("refs/heads/{branch}");
+        let list: AzureList<AzurePullRequest> = self
+            .send(
+                self.request(Method::GET, repo.api_url("/pullrequests"))
+                    .query(&[
+                        ("searchCriteria.sourceRefName", source.as_str()),
+                        ("searchCriteria.status", "all"),
+                    ]),
+            )
+            .await?;
+        let mut prs: Vec<PullRequestInfo> = list
+            .value
+            .into_iter()
+            .map(|pr| pr.into_info(repo))
+            .collect();
+        prs.sort_by_key(|pr| !matches!(pr.status, MergeStatus::Open));
+        Ok(prs)
+    }
+
+    /// Complete the pull request at the commit it was last checked at, with
+    /// the strategy. Azure may finish the merge after answering, in which case
+    /// it is still open and the PR monitor picks up the merge.
+    pub async fn merge_pr(
+        &self,
+        repo: &AzureRepo,
+        id: i64,
+        strategy: MergeStrategy,
+    ) -> Result<PullRequestInfo, AzureDevOpsServiceError> {
+        let pr = self.fetch_pr(repo, id).await?;
+        let Some(source) = pr.last_merge_source_commit else {
+            return Err(AzureDevOpsServiceError::NotMergeable(
+                "Azure DevOps hasn't checked the pull request's merge yet".to_string(),
+            ));
+        };
+        // Azure has no plain fast-forward; its rebase fast-forwards a branch
+        // that is up to date with the target
+        let merge_strategy = match strategy {
+            MergeStrategy::MergeCommit => "noFastForward",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase | MergeStrategy::FastForward => "rebase",
+        };
+        let body = json!({
+            "status": "completed",
+            "lastMergeSourceCommit": { "commitId": source.commit_id },
+            "completionOptions": {
+                "deleteSourceBranch": false,
+                "mergeStrategy": merge_strategy,
+            },
+        });
+        let pr: AzurePullRequest = self
+            .send(
+                self.request(Method::PATCH, repo.api_url(&format!("/pullrequests/{id}")))
+                    .json(&body),
+            )
+            .await?;
+        Ok(pr.into_info(repo))
+    }
+
+    /// Comments of the pull request's threads, oldest first; threads on a file
+    /// are review comments
+    pub async fn get_pr_comments(
+        &self,
+        repo: &AzureRepo,
+        id: i64,
+    ) -> Result<Vec<UnifiedPrComment>, AzureDevOpsServiceError> {
+        let list: AzureList<AzureThread> = self
+            .send(self.request(
+                Method::GET,
+                repo.api_url(&format!("/pullrequests/{id}/threads")),
+            ))
+            .await?;
+        let url = repo.pull_request_url(id);
+        let mut comments: Vec<UnifiedPrComment> = list
+            .value
+            .into_iter()
+            .filter(|thread| !thread.is_deleted)
+            .flat_map(|thread| {
+                let context = thread
+                    .thread_context
+                    .and_then(|context| Some((context.file_path?, context.right_file_start)));
+                let url = url.clone();
+                thread
+                    .comments
+                    .into_iter()
+                    .filter(|comment| {
+                        !comment.is_deleted && comment.comment_type.as_deref() != Some("system")
+                    })
+                    .map(move |comment| match &context {
+                        Some((path, start)) => UnifiedPrComment::Review {
+                            id: comment.id,
+                            author: comment.author.display_name,
+                            author_association: "NONE".to_string(),
+                            body: comment.content.unwrap_or_default(),
+                            created_at: comment.published_date,
+                            url: url.clone(),
+                            path: path.trim_start_matches('/').to_string(),
+                            line: start.as_ref().map(|start| start.line),
+                            diff_hunk: String::new(),
+                        },
+                        None => UnifiedPrComment::General {
+                            id: comment.id.to_string(),
+                            author: comment.author.display_name,
+                            author_association: "NONE".to_string(),
+                            body: comment.content.unwrap_or_default(),
+                            created_at: comment.published_date,
+                            url: url.clone(),
+                        },
+                    })
+            })
+            .collect();
+        comments.sort_by_key(|comment| match comment {
+            UnifiedPrComment::General { created_at, .. }
+            | UnifiedPrComment::Review { created_at, .. } => *created_at,
+        });
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repos_are_found_from_remotes_and_work_items_from_text() {
+        let repo = AzureRepo {
+            organization: "contoso".to_string(),
+            project: "Fabrikam Web".to_string(),
+            repository: "app".to_string(),
+        };
+        for remote in [
+            "https://contoso@dev.azure.com/contoso/Fabrikam%20Web/_git/app",
+            "git@ssh.dev.azure.com:v3/contoso/Fabrikam%20Web/app",
+            "https://contoso.visualstudio.com/DefaultCollection/Fabrikam%20Web/_git/app",
+        ] {
+            assert_eq!(repo_from_remote(remote), Some(repo.clone()), "{remote}");
+        }
+        assert_eq!(repo_from_remote("git@github.com:contoso/app.git"), None);
+
+        let url = repo.pull_request_url(12);
+        assert_eq!(parse_pull_request_url(&url), Some((repo, 12)));
+
+        assert_eq!(
+            work_item_ids("Fix login (#42), see AB#7 and #42 again; not issue#9 or &#38;"),
+            vec![42, 7]
+        );
+    }
+}
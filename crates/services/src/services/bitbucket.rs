@@ -0,0 +1,448 @@
+//! Bitbucket Cloud pull requests, called through its REST API. The project's
+//! secret holds an app password, sent with the username it belongs to, or an
+//! OAuth or repository access token, sent as a bearer token.
+//!
+//! A repository's Bitbucket workspace and slug are found from its remote.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    merge::{MergeStatus, MergeStrategy, PullRequestInfo},
+    project_settings::{BitbucketSettings, ProjectSettings},
+};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::json;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use url::Url;
+use uuid::Uuid;
+
+use super::{
+    git::{GitService, GitServiceError},
+    github::{CreatePrRequest, UnifiedPrComment},
+    secrets::{ProjectEnv, SecretsError},
+};
+
+const API_URL: &str = "https://api.bitbucket.org/2.0";
+const HOST: &str = "bitbucket.org";
+
+#[derive(Debug, Error)]
+pub enum BitbucketServiceError {
+    #[error("No Bitbucket credentials: the project has no secret named {0}")]
+    MissingToken(String),
+    #[error("Bitbucket rejected the credentials: {0}")]
+    AuthFailed(String),
+    #[error("The Bitbucket credentials aren't allowed to do this: {0}")]
+    InsufficientPermissions(String),
+    #[error("Bitbucket repository not found or not accessible: {0}")]
+    RepoNotFound(String),
+    #[error("The pull request can't be merged: {0}")]
+    NotMergeable(String),
+    #[error("{0} isn't the remote of a Bitbucket Cloud repository")]
+    UnsupportedRemote(String),
+    #[error("Bitbucket request failed: {0}")]
+    Request(String),
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<reqwest::Error> for BitbucketServiceError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Request(err.to_string())
+    }
+}
+
+/// A repository on Bitbucket Cloud
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitbucketRepo {
+    pub workspace: String,
+    pub slug: String,
+}
+
+impl BitbucketRepo {
+    fn api_url(&self, endpoint: &str) -> String {
+        format!(
+            "{API_URL}/repositories/{}/{}{endpoint}",
+            self.workspace, self.slug
+        )
+    }
+}
+
+/// The Bitbucket Cloud repository a remote points at
+pub fn repo_from_remote(remote_url: &str) -> Option<BitbucketRepo> {
+    let path = match Url::parse(remote_url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https" | "ssh") => {
+            (url.host_str()? == HOST).then(|| url.path().to_string())?
+        }
+        // scp-like syntax, e.g. git@bitbucket.org:team/repo.git
+        _ => {
+            let (user_host, path) = remote_url.split_once(':')?;
+            (user_host.rsplit('@').next()? == HOST).then(|| path.to_string())?
+        }
+    };
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        [workspace, slug] if !workspace.is_empty() && !slug.is_empty() => Some(BitbucketRepo {
+            workspace: workspace.to_string(),
+            slug: slug.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// The repository and number of a pull request from its web URL
+pub fn parse_pull_request_url(url: &str) -> Option<(BitbucketRepo, i64)> {
+    let (repo_url, rest) = url.split_once("/pull-requests/")?;
+    let id = rest
+        .split(['/', '#', '?'])
+        .next()
+        .and_then(|id| id.parse().ok())?;
+    Some((repo_from_remote(repo_url)?, id))
+}
+
+/// Whether the URL is of a Bitbucket Cloud pull request
+pub fn is_pull_request_url(url: &str) -> bool {
+    parse_pull_request_url(url).is_some()
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketLink {
+    href: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketLinks {
+    html: BitbucketLink,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketCommit {
+    hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPullRequest {
+    id: i64,
+    state: String,
+    links: BitbucketLinks,
+    merge_commit: Option<BitbucketCommit>,
+    updated_on: Option<DateTime<Utc>>,
+}
+
+impl From<BitbucketPullRequest> for PullRequestInfo {
+    fn from(pr: BitbucketPullRequest) -> Self {
+        let status = match pr.state.as_str() {
+            "OPEN" => MergeStatus::Open,
+            "MERGED" => MergeStatus::Merged,
+            "DECLINED" | "SUPERSEDED" => MergeStatus::Closed,
+            _ => MergeStatus::Unknown,
+        };
+        let merged_at = pr
+            .updated_on
+            .filter(|_| matches!(status, MergeStatus::Merged));
+        PullRequestInfo {
+            number: pr.id,
+            url: pr.links.html.href,
+            status,
+            merged_at,
+            merge_commit_sha: pr.merge_commit.map(|commit| commit.hash),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPage<T> {
+    values: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketUser {
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketContent {
+    raw: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketComment {
+    id: i64,
+    content: BitbucketContent,
+    user: BitbucketUser,
+    created_on: DateTime<Utc>,
+    #[serde(default)]
+    deleted: bool,
+    inline: Option<BitbucketInline>,
+    links: BitbucketLinks,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketInline {
+    path: String,
+    to: Option<i64>,
+    from: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+enum Credentials {
+    AppPassword { username: String, password: String },
+    Token(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BitbucketService {
+    client: reqwest::Client,
+    credentials: Credentials,
+}
+
+impl BitbucketService {
+    /// With an app password of `username`, or else a bearer token
+    pub fn new(username: Option<String>, secret: String) -> Self {
+        let credentials = match username.filter(|username| !username.trim().is_empty()) {
+            Some(username) => Credentials::AppPassword {
+                username,
+                password: secret,
+            },
+            None => Credentials::Token(secret),
+        };
+        Self {
+            client: reqwest::Client::new(),
+            credentials,
+        }
+    }
+
+    /// The service with the project's credentials
+    pub async fn for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Self, BitbucketServiceError> {
+        let BitbucketSettings {
+            username,
+            token_secret,
+        } = ProjectSettings::find_by_project_id(pool, project_id)
+            .await?
+            .code_host
+            .bitbucket;
+        let env = ProjectEnv::load(pool, project_id).await?;
+        let secret = env
+            .vars
+            .iter()
+            .find(|(name, _)| *name == token_secret)
+            .map(|(_, value)| value.clone())
+            .filter(|value| !value.trim().is_empty())
+            .ok_or(BitbucketServiceError::MissingToken(token_secret))?;
+        Ok(Self::new(username, secret))
+    }
+
+    /// The Bitbucket repository of the repository's remote
+    pub fn repo_for_path(
+        git: &GitService,
+        repo_path: &Path,
+    ) -> Result<BitbucketRepo, BitbucketServiceError> {
+        let remote_url = git.get_default_remote_url(repo_path)?;
+        repo_from_remote(&remote_url).ok_or(BitbucketServiceError::UnsupportedRemote(remote_url))
+    }
+
+    fn request(&self, method: Method, url: String) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.credentials {
+            Credentials::AppPassword { username, password } => {
+                request.basic_auth(username, Some(password))
+            }
+            Credentials::Token(token) => request.bearer_auth(token),
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, BitbucketServiceError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+        let message = response.text().await.unwrap_or_default();
+        let message = format!("{status}: {}", message.trim());
+        Err(match status {
+            StatusCode::UNAUTHORIZED => BitbucketServiceError::AuthFailed(message),
+            StatusCode::FORBIDDEN => BitbucketServiceError::InsufficientPermissions(message),
+            StatusCode::NOT_FOUND => BitbucketServiceError::RepoNotFound(message),
+            _ => BitbucketServiceError::Request(message),
+        })
+    }
+
+    /// Whether Bitbucket accepts the credentials
+    pub async fn check_token(&self) -> Result<(), BitbucketServiceError> {
+        self.send::<BitbucketUser>(self.request(Method::GET, format!("{API_URL}/user")))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_pr(
+        &self,
+        repo: &BitbucketRepo,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, BitbucketServiceError> {
+        let body = json!({
+            "title": request.title,
+            "description": request.body.clone().unwrap_or_default(),
+            "source": { "branch": { "name": request.head_branch } },
+            "destination": { "branch": { "name": request.base_branch } },
+            "draft": request.draft.unwrap_or(false),
+        });
+        let pr: BitbucketPullRequest = self
+            .send(
+                self.request(Method::POST, repo.api_url("/pullrequests"))
+                    .json(&body),
+            )
+            .await?;
+        Ok(pr.into())
+    }
+
+    pub async fn get_pr(
+        &self,
+        repo: &BitbucketRepo,
+        id: i64,
+    ) -> Result<PullRequestInfo, BitbucketServiceError> {
+        let pr: BitbucketPullRequest = self
+            .send(self.request(Method::GET, repo.api_url(&format!("/pullrequests/{id}"))))
+            .await?;
+        Ok(pr.into())
+    }
+
+    /// Pull requests from the branch in any state, open ones first
+    pub async fn list_prs_for_branch(
+        &self,
+        repo: &BitbucketRepo,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, BitbucketServiceError> {
+        let query = format!("source.branch.name = \"{}\"", branch.replace('"', "\\\""));
+        let page: BitbucketPage<BitbucketPullRequest> = self
+            .send(
+                self.request(Method::GET, repo.api_url("/pullrequests"))
+                    .query(&[
+                        ("q", query.as_str()),
+                        ("state", "OPEN"),
+                        ("state", "MERGED"),
+                        ("state", "DECLINED"),
+                        ("state", "SUPERSEDED"),
+                    ]),
+            )
+            .await?;
+        let mut prs: Vec<PullRequestInfo> = page.values.into_iter().map(Into::into).collect();
+        prs.sort_by_key(|pr| !matches!(pr.status, MergeStatus::Open));
+        Ok(prs)
+    }
+
+    /// Merge the pull request with the strategy
+    pub async fn merge_pr(
+        &self,
+        repo: &BitbucketRepo,
+        id: i64,
+        strategy: MergeStrategy,
+    ) -> Result<PullRequestInfo, BitbucketServiceError> {
+        let url = repo.api_url(&format!("/pullrequests/{id}/merge"));
+        let merge_strategy = match strategy {
+            MergeStrategy::MergeCommit => "merge_commit",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase_fast_forward",
+            MergeStrategy::FastForward => "fast_forward",
+        };
+        match self
+            .send::<BitbucketPullRequest>(
+                self.request(Method::POST, url)
+                    .json(&json!({ "merge_strategy": merge_strategy })),
+            )
+            .await
+        {
+            Ok(pr) => Ok(pr.into()),
+            // Bitbucket answers 400 for conflicts and unmet merge checks
+            Err(BitbucketServiceError::Request(message)) if message.starts_with("400") => {
+                Err(BitbucketServiceError::NotMergeable(message))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Comments on the pull request, oldest first; inline ones are review
+    /// comments
+    pub async fn get_pr_comments(
+        &self,
+        repo: &BitbucketRepo,
+        id: i64,
+    ) -> Result<Vec<UnifiedPrComment>, BitbucketServiceError> {
+        let page: BitbucketPage<BitbucketComment> = self
+            .send(
+                self.request(
+                    Method::GET,
+                    repo.api_url(&format!("/pullrequests/{id}/comments")),
+                )
+                .query(&[("pagelen", "100"), ("sort", "created_on")]),
+            )
+            .await?;
+        Ok(page
+            .values
+            .into_iter()
+            .filter(|comment| !comment.deleted)
+            .map(|comment| match comment.inline {
+                Some(inline) => UnifiedPrComment::Review {
+                    id: comment.id,
+                    author: comment.user.display_name,
+                    author_association: "NONE".to_string(),
+                    body: comment.content.raw,
+                    created_at: comment.created_on,
+                    url: comment.links.html.href,
+                    path: inline.path,
+                    line: inline.to.or(inline.from),
+                    diff_hunk: String::new(),
+                },
+                None => UnifiedPrComment::General {
+                    id: comment.id.to_string(),
+                    author: comment.user.display_name,
+                    author_association: "NONE".to_string(),
+                    body: comment.content.raw,
+                    created_at: comment.created_on,
+                    url: comment.links.html.href,
+                },
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repos_are_found_from_remotes_and_pull_request_urls() {
+        let repo = BitbucketRepo {
+            workspace: "team".to_string(),
+            slug: "app".to_string(),
+        };
+        assert_eq!(
+            repo_from_remote("https://someone@bitbucket.org/team/app.git"),
+            Some(repo.clone())
+        );
+        assert_eq!(
+            repo_from_remote("git@bitbucket.org:team/app.git"),
+            Some(repo.clone())
+        );
+        assert_eq!(repo_from_remote("git@github.com:team/app.git"), None);
+        assert_eq!(repo_from_remote("https://bitbucket.org/team"), None);
+
+        let url = "https://bitbucket.org/team/app/pull-requests/7/overview";
+        assert!(is_pull_request_url(url));
+        assert!(!is_pull_request_url(
+            "https://gitlab.com/team/app/-/merge_requests/7"
+        ));
+        assert_eq!(parse_pull_request_url(url), Some((repo, 7)));
+    }
+}
@@ -0,0 +1,199 @@
+//! Burndown and cumulative flow charts of a project's board, read from the
+//! daily snapshots of its columns.
+//!
+//! Snapshots are only taken while the app runs, so days it was closed have no
+//! point. Archived tasks aren't on the board and leave the charts when they
+//! are archived.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use db::models::{board_snapshot::BoardSnapshot, task::TaskStatus};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct BurndownPoint {
+    pub day: NaiveDate,
+    /// Tasks left to do: neither done nor cancelled
+    pub remaining: u32,
+    pub done: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct FlowColumn {
+    pub column_id: Uuid,
+    /// Latest name of the column in the range
+    pub name: String,
+    pub status: TaskStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct FlowDay {
+    pub day: NaiveDate,
+    /// Tasks in each column, in the order of the chart's columns
+    pub counts: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CumulativeFlow {
+    /// Every column seen in the range, in their latest board order
+    pub columns: Vec<FlowColumn>,
+    /// Oldest first
+    pub days: Vec<FlowDay>,
+}
+
+/// Snapshot every project's board as it is now, as the snapshot of the day
+pub async fn record_snapshots(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    BoardSnapshot::record(pool, Utc::now().date_naive(), None).await
+}
+
+/// The project's snapshots over the range, refreshing today's first so the
+/// charts end with the board as it is now
+async fn snapshots(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<BoardSnapshot>, sqlx::Error> {
+    let today = Utc::now().date_naive();
+    if (from..=to).contains(&today) {
+        BoardSnapshot::record(pool, today, Some(project_id)).await?;
+    }
+    BoardSnapshot::find_range(pool, project_id, from, to).await
+}
+
+pub async fn project_burndown(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<BurndownPoint>, sqlx::Error> {
+    Ok(burndown(&snapshots(pool, project_id, from, to).await?))
+}
+
+pub async fn project_cumulative_flow(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<CumulativeFlow, sqlx::Error> {
+    Ok(cumulative_flow(
+        &snapshots(pool, project_id, from, to).await?,
+    ))
+}
+
+/// One point per snapshot day, given snapshots sorted by day
+fn burndown(snapshots: &[BoardSnapshot]) -> Vec<BurndownPoint> {
+    let mut points: Vec<BurndownPoint> = Vec::new();
+    for snapshot in snapshots {
+        if points.last().is_none_or(|point| point.day != snapshot.day) {
+            points.push(BurndownPoint {
+                day: snapshot.day,
+                remaining: 0,
+                done: 0,
+            });
+        }
+        let Some(point) = points.last_mut() else {
+            continue;
+        };
+        let count = snapshot.task_count as u32;
+        match snapshot.status {
+            TaskStatus::Done => point.done += count,
+            TaskStatus::Cancelled => {}
+            _ => point.remaining += count,
+        }
+    }
+    points
+}
+
+/// Given snapshots sorted by day, then board position
+fn cumulative_flow(snapshots: &[BoardSnapshot]) -> CumulativeFlow {
+    // Later snapshots overwrite earlier ones, leaving each column as last seen
+    let mut last_seen: HashMap<Uuid, (usize, &BoardSnapshot)> = HashMap::new();
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        last_seen.insert(snapshot.column_id, (index, snapshot));
+    }
+    let mut latest: Vec<(usize, &BoardSnapshot)> = last_seen.into_values().collect();
+    // Columns removed before the last day keep the place they last had
+    latest.sort_by_key(|(index, snapshot)| (snapshot.position, std::cmp::Reverse(*index)));
+
+    let columns: Vec<FlowColumn> = latest
+        .iter()
+        .map(|(_, snapshot)| FlowColumn {
+            column_id: snapshot.column_id,
+            name: snapshot.column_name.clone(),
+            status: snapshot.status.clone(),
+        })
+        .collect();
+    let column_index: HashMap<Uuid, usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| (column.column_id, index))
+        .collect();
+
+    let mut days: Vec<FlowDay> = Vec::new();
+    for snapshot in snapshots {
+        if days.last().is_none_or(|day| day.day != snapshot.day) {
+            days.push(FlowDay {
+                day: snapshot.day,
+                counts: vec![0; columns.len()],
+            });
+        }
+        if let Some(day) = days.last_mut()
+            && let Some(&index) = column_index.get(&snapshot.column_id)
+        {
+            day.counts[index] = snapshot.task_count as u32;
+        }
+    }
+
+    CumulativeFlow { columns, days }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(
+        day: u32,
+        column_id: Uuid,
+        name: &str,
+        status: TaskStatus,
+        position: i64,
+        task_count: i64,
+    ) -> BoardSnapshot {
+        BoardSnapshot {
+            project_id: Uuid::nil(),
+            day: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            column_id,
+            column_name: name.to_string(),
+            status,
+            position,
+            task_count,
+        }
+    }
+
+    #[test]
+    fn charts_follow_the_columns_day_by_day() {
+        let (todo, review, done) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let snapshots = vec![
+            snapshot(1, todo, "To Do", TaskStatus::Todo, 0, 5),
+            snapshot(1, review, "Review", TaskStatus::InReview, 1, 1),
+            snapshot(1, done, "Done", TaskStatus::Done, 2, 0),
+            snapshot(3, todo, "Backlog", TaskStatus::Todo, 0, 2),
+            snapshot(3, done, "Done", TaskStatus::Done, 1, 4),
+        ];
+
+        let points = burndown(&snapshots);
+        let lines: Vec<(u32, u32)> = points.iter().map(|p| (p.remaining, p.done)).collect();
+        assert_eq!(lines, vec![(6, 0), (2, 4)]);
+
+        let flow = cumulative_flow(&snapshots);
+        let names: Vec<&str> = flow.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Backlog", "Done", "Review"]);
+        let counts: Vec<&Vec<u32>> = flow.days.iter().map(|d| &d.counts).collect();
+        assert_eq!(counts, vec![&vec![5, 0, 1], &vec![2, 4, 0]]);
+    }
+}
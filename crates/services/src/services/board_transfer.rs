@@ -0,0 +1,565 @@
+//! Export a project's tasks to JSON or CSV and import them back, for backups,
+//! moving boards between instances and triaging in a spreadsheet.
+//!
+//! Both formats carry the same fields. Attempts are exported for reference
+//! only; importing recreates tasks and their labels, not their attempts.
+
+use std::{collections::HashMap, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    label::{CreateLabel, Label, TaskLabel},
+    project::Project,
+    session::Session,
+    task::{CreateTask, Task, TaskPriority, TaskStatus},
+    workspace::Workspace,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Color given to labels that an import creates
+const IMPORTED_LABEL_COLOR: &str = "#8b949e";
+
+const CSV_COLUMNS: [&str; 11] = [
+    "id",
+    "title",
+    "description",
+    "status",
+    "priority",
+    "labels",
+    "archived",
+    "attempt_count",
+    "last_attempt_branch",
+    "created_at",
+    "updated_at",
+];
+
+#[derive(Debug, Error)]
+pub enum BoardTransferError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid CSV: {0}")]
+    Csv(String),
+    #[error("Task {0} has no title")]
+    MissingTitle(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(rename_all = "lowercase")]
+pub enum BoardFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl BoardFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BoardFormat::Json => "json",
+            BoardFormat::Csv => "csv",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            BoardFormat::Json => "application/json",
+            BoardFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BoardExport {
+    pub project_name: String,
+    pub exported_at: DateTime<Utc>,
+    pub tasks: Vec<ExportedTask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ExportedTask {
+    /// Id on the instance the board was exported from
+    pub id: Option<Uuid>,
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub priority: TaskPriority,
+    /// Label names
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub archived: bool,
+    /// Newest first; not imported
+    #[serde(default)]
+    pub attempts: Vec<ExportedAttempt>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ExportedAttempt {
+    pub id: Uuid,
+    pub branch: String,
+    pub executor: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What to do with an imported task that matches one already on the board,
+/// either by id or by title
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum DuplicateStrategy {
+    /// Leave the existing task alone
+    #[default]
+    Skip,
+    /// Update the existing task and add the imported labels to it
+    Merge,
+    /// Create the task anyway
+    Create,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct ImportBoard {
+    pub format: BoardFormat,
+    /// Contents of an exported file
+    pub content: String,
+    #[serde(default)]
+    pub on_duplicate: DuplicateStrategy,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct BoardImportResult {
+    pub created: Vec<Uuid>,
+    pub updated: Vec<Uuid>,
+    pub skipped: usize,
+}
+
+/// Only the tasks are read back from an exported JSON file
+#[derive(Deserialize)]
+struct ImportedBoard {
+    tasks: Vec<ExportedTask>,
+}
+
+pub async fn export_board(
+    pool: &SqlitePool,
+    project: &Project,
+) -> Result<BoardExport, sqlx::Error> {
+    let label_names: HashMap<Uuid, String> = Label::find_by_project_id(pool, project.id)
+        .await?
+        .into_iter()
+        .map(|label| (label.id, label.name))
+        .collect();
+    let mut labels_by_task: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for task_label in TaskLabel::find_by_project_id(pool, project.id).await? {
+        if let Some(name) = label_names.get(&task_label.label_id) {
+            labels_by_task
+                .entry(task_label.task_id)
+                .or_default()
+                .push(name.clone());
+        }
+    }
+
+    let mut attempts_by_task: HashMap<Uuid, Vec<ExportedAttempt>> = HashMap::new();
+    for workspace in Workspace::find_by_project_id(pool, project.id).await? {
+        let executor = Session::find_latest_by_workspace_id(pool, workspace.id)
+            .await?
+            .and_then(|session| session.executor);
+        attempts_by_task
+            .entry(workspace.task_id)
+            .or_default()
+            .push(ExportedAttempt {
+                id: workspace.id,
+                branch: workspace.branch,
+                executor,
+                created_at: workspace.created_at,
+            });
+    }
+
+    let tasks = Task::find_by_project_id(pool, project.id)
+        .await?
+        .into_iter()
+        .map(|task| {
+            let mut attempts = attempts_by_task.remove(&task.id).unwrap_or_default();
+            attempts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+            ExportedTask {
+                id: Some(task.id),
+                labels: labels_by_task.remove(&task.id).unwrap_or_default(),
+                archived: task.archived_at.is_some(),
+                attempts,
+                title: task.title,
+                description: task.description,
+                status: task.status,
+                priority: task.priority,
+                created_at: Some(task.created_at),
+                updated_at: Some(task.updated_at),
+            }
+        })
+        .collect();
+
+    Ok(BoardExport {
+        project_name: project.name.clone(),
+        exported_at: Utc::now(),
+        tasks,
+    })
+}
+
+pub fn write_board(board: &BoardExport, format: BoardFormat) -> Result<String, BoardTransferError> {
+    match format {
+        BoardFormat::Json => Ok(serde_json::to_string_pretty(board)?),
+        BoardFormat::Csv => Ok(write_csv(&board.tasks)),
+    }
+}
+
+pub fn read_board(
+    content: &str,
+    format: BoardFormat,
+) -> Result<Vec<ExportedTask>, BoardTransferError> {
+    let tasks = match format {
+        BoardFormat::Json => serde_json::from_str::<ImportedBoard>(content)?.tasks,
+        BoardFormat::Csv => read_csv(content)?,
+    };
+    if let Some(index) = tasks.iter().position(|task| task.title.trim().is_empty()) {
+        return Err(BoardTransferError::MissingTitle(index + 1));
+    }
+    Ok(tasks)
+}
+
+/// Add the tasks to the project in one transaction, keeping their order at the
+/// top of the board. Labels are matched by name, ignoring case, and created
+/// when the project doesn't have them.
+pub async fn import_board(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    tasks: &[ExportedTask],
+    on_duplicate: DuplicateStrategy,
+) -> Result<BoardImportResult, sqlx::Error> {
+    let existing = Task::find_by_project_id(pool, project_id).await?;
+    let mut labels: HashMap<String, Uuid> = Label::find_by_project_id(pool, project_id)
+        .await?
+        .into_iter()
+        .map(|label| (label.name.to_lowercase(), label.id))
+        .collect();
+
+    let mut result = BoardImportResult {
+        created: Vec::new(),
+        updated: Vec::new(),
+        skipped: 0,
+    };
+    let mut tx = pool.begin().await?;
+    // Each new task goes to the top of its column, so go from last to first
+    for imported in tasks.iter().rev() {
+        let duplicate = existing.iter().find(|task| {
+            imported.id == Some(task.id)
+                || task
+                    .title
+                    .trim()
+                    .eq_ignore_ascii_case(imported.title.trim())
+        });
+        let task_id = match (duplicate, on_duplicate) {
+            (Some(_), DuplicateStrategy::Skip) => {
+                result.skipped += 1;
+                continue;
+            }
+            (Some(task), DuplicateStrategy::Merge) => {
+                Task::update(
+                    &mut *tx,
+                    task.id,
+                    project_id,
+                    imported.title.trim().to_string(),
+                    imported
+                        .description
+                        .clone()
+                        .or_else(|| task.description.clone()),
+                    imported.status.clone(),
+                    task.parent_workspace_id,
+                    imported.priority,
+                )
+                .await?;
+                result.updated.push(task.id);
+                task.id
+            }
+            _ => {
+                let create_task = CreateTask {
+                    status: Some(imported.status.clone()),
+                    priority: Some(imported.priority),
+                    ..CreateTask::from_title_description(
+                        project_id,
+                        imported.title.trim().to_string(),
+                        imported.description.clone(),
+                    )
+                };
+                let task = Task::create(&mut *tx, &create_task, Uuid::new_v4()).await?;
+                result.created.push(task.id);
+                task.id
+            }
+        };
+
+        if imported.archived {
+            Task::set_archived(&mut *tx, task_id, true).await?;
+        }
+        for name in imported.labels.iter().map(|name| name.trim()) {
+            if name.is_empty() {
+                continue;
+            }
+            let label_id = match labels.get(&name.to_lowercase()) {
+                Some(id) => *id,
+                None => {
+                    let label = Label::create(
+                        &mut *tx,
+                        project_id,
+                        &CreateLabel {
+                            name: name.to_string(),
+                            color: IMPORTED_LABEL_COLOR.to_string(),
+                        },
+                    )
+                    .await?;
+                    labels.insert(name.to_lowercase(), label.id);
+                    label.id
+                }
+            };
+            Label::add_to_task(&mut *tx, task_id, label_id).await?;
+        }
+    }
+    tx.commit().await?;
+
+    result.created.reverse();
+    result.updated.reverse();
+    Ok(result)
+}
+
+fn write_csv(tasks: &[ExportedTask]) -> String {
+    let mut out = String::new();
+    write_csv_row(
+        &mut out,
+        CSV_COLUMNS.iter().map(|column| column.to_string()),
+    );
+    for task in tasks {
+        let last_attempt = task.attempts.first();
+        write_csv_row(
+            &mut out,
+            [
+                task.id.map(|id| id.to_string()).unwrap_or_default(),
+                task.title.clone(),
+                task.description.clone().unwrap_or_default(),
+                task.status.to_string(),
+                task.priority.to_string(),
+                task.labels.join(";"),
+                task.archived.to_string(),
+                task.attempts.len().to_string(),
+                last_attempt
+                    .map(|attempt| attempt.branch.clone())
+                    .unwrap_or_default(),
+                task.created_at
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_default(),
+                task.updated_at
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_default(),
+            ],
+        );
+    }
+    out
+}
+
+fn write_csv_row(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// Read tasks from CSV with a header row. Only `title` is required; columns
+/// that aren't recognised are ignored.
+fn read_csv(content: &str) -> Result<Vec<ExportedTask>, BoardTransferError> {
+    let mut rows = parse_csv(content.trim_start_matches('\u{FEFF}'))?.into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or_else(|| BoardTransferError::Csv("missing header row".to_string()))?
+        .iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let title = column("title")
+        .ok_or_else(|| BoardTransferError::Csv("missing title column".to_string()))?;
+    let (id, description, status, priority, labels, archived) = (
+        column("id"),
+        column("description"),
+        column("status"),
+        column("priority"),
+        column("labels"),
+        column("archived"),
+    );
+
+    let mut tasks = Vec::new();
+    for (index, row) in rows.enumerate() {
+        // Row 1 is the header
+        let line = index + 2;
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|i| row.get(i))
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        if row.iter().all(|value| value.trim().is_empty()) {
+            continue;
+        }
+        let invalid = |name: &str, value: &str| {
+            BoardTransferError::Csv(format!("row {line}: invalid {name} '{value}'"))
+        };
+
+        tasks.push(ExportedTask {
+            id: cell(id)
+                .map(|value| Uuid::parse_str(value).map_err(|_| invalid("id", value)))
+                .transpose()?,
+            title: cell(Some(title)).unwrap_or_default().to_string(),
+            description: cell(description).map(str::to_string),
+            status: cell(status)
+                .map(|value| TaskStatus::from_str(value).map_err(|_| invalid("status", value)))
+                .transpose()?
+                .unwrap_or_default(),
+            priority: cell(priority)
+                .map(|value| TaskPriority::from_str(value).map_err(|_| invalid("priority", value)))
+                .transpose()?
+                .unwrap_or_default(),
+            labels: cell(labels)
+                .map(|value| {
+                    value
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            archived: cell(archived)
+                .map(|value| {
+                    value
+                        .parse::<bool>()
+                        .map_err(|_| invalid("archived", value))
+                })
+                .transpose()?
+                .unwrap_or(false),
+            attempts: Vec::new(),
+            created_at: None,
+            updated_at: None,
+        });
+    }
+    Ok(tasks)
+}
+
+/// Split CSV into rows of fields. Quoted fields may contain commas, doubled
+/// quotes and line breaks.
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, BoardTransferError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(BoardTransferError::Csv(
+            "unterminated quoted field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_fields() {
+        let rows = parse_csv(
+            "title,description\r\n\"Fix, then ship\",\"Say \"\"hi\"\"\ntwice\"\r\nplain,\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            [
+                vec!["title", "description"],
+                vec!["Fix, then ship", "Say \"hi\"\ntwice"],
+                vec!["plain", ""],
+            ]
+        );
+        assert!(parse_csv("title\n\"open").is_err());
+    }
+
+    #[test]
+    fn csv_round_trips_exported_tasks() {
+        let task = ExportedTask {
+            id: Some(Uuid::new_v4()),
+            title: "Upgrade, \"carefully\"".to_string(),
+            description: Some("Line one\nLine two".to_string()),
+            status: TaskStatus::InReview,
+            priority: TaskPriority::High,
+            labels: vec!["backend".to_string(), "infra".to_string()],
+            archived: true,
+            attempts: Vec::new(),
+            created_at: None,
+            updated_at: None,
+        };
+
+        let tasks = read_csv(&write_csv(std::slice::from_ref(&task))).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, task.id);
+        assert_eq!(tasks[0].title, task.title);
+        assert_eq!(tasks[0].description, task.description);
+        assert_eq!(tasks[0].status, task.status);
+        assert_eq!(tasks[0].priority, task.priority);
+        assert_eq!(tasks[0].labels, task.labels);
+        assert!(tasks[0].archived);
+    }
+
+    #[test]
+    fn csv_needs_only_a_title() {
+        let tasks = read_csv("Title,Owner\nWrite docs,sam\n,\n").unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Write docs");
+        assert_eq!(tasks[0].status, TaskStatus::Todo);
+
+        assert!(read_csv("name\nWrite docs\n").is_err());
+        assert!(read_csv("title,status\nWrite docs,later\n").is_err());
+    }
+}
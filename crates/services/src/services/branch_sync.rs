@@ -0,0 +1,216 @@
+//! Periodic check of open attempts against their target branches. Each of an
+//! attempt's repositories records how far behind its branch is and what would
+//! conflict; where the project allows it and the branch would apply cleanly,
+//! the branch is also rebased onto or merged with its target.
+
+use std::{collections::HashMap, path::Path};
+
+use chrono::{DateTime, Duration, Utc};
+use db::models::{
+    branch_sync_status::{BranchSyncAction, BranchSyncStatus, UpsertBranchSyncStatus},
+    project_settings::{BranchSyncMode, BranchSyncSettings, ProjectSettings},
+    repo::Repo,
+    task::TaskStatus,
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::{
+    config::CommitSigningConfig,
+    git::{GitService, GitServiceError},
+};
+
+/// Whether an attempt last checked at `last_checked` is due another check
+pub fn is_due(
+    settings: &BranchSyncSettings,
+    last_checked: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    settings.mode != BranchSyncMode::Off
+        && last_checked
+            .is_none_or(|at| now - at >= Duration::minutes(settings.interval_minutes.max(1) as i64))
+}
+
+/// Check the open attempts that are due, as their project's settings say.
+/// Attempts with a running process are only checked.
+pub async fn run_due(
+    pool: &SqlitePool,
+    git: &GitService,
+    signing: &CommitSigningConfig,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    let mut settings: HashMap<Uuid, BranchSyncSettings> = HashMap::new();
+
+    for activity in Workspace::find_activity_with_container(pool).await? {
+        if matches!(
+            activity.task_status,
+            TaskStatus::Done | TaskStatus::Cancelled
+        ) {
+            continue;
+        }
+        if !settings.contains_key(&activity.project_id) {
+            let project_settings =
+                ProjectSettings::find_by_project_id(pool, activity.project_id).await?;
+            settings.insert(activity.project_id, project_settings.branch_sync);
+        }
+        let sync = &settings[&activity.project_id];
+        let last_checked = BranchSyncStatus::find_by_workspace_id(pool, activity.workspace_id)
+            .await?
+            .iter()
+            .map(|status| status.checked_at)
+            .min();
+        if !is_due(sync, last_checked, now) {
+            continue;
+        }
+        let Some(workspace) = Workspace::find_by_id(pool, activity.workspace_id).await? else {
+            continue;
+        };
+        let mode = if activity.has_running_processes {
+            BranchSyncMode::Check
+        } else {
+            sync.mode
+        };
+
+        for workspace_repo in WorkspaceRepo::find_by_workspace_id(pool, workspace.id).await? {
+            let Some(repo) = Repo::find_by_id(pool, workspace_repo.repo_id).await? else {
+                continue;
+            };
+            let worktree_path = Path::new(&activity.container_ref).join(&repo.name);
+            if !worktree_path.exists() {
+                continue;
+            }
+            let status = sync_repo(
+                git,
+                mode,
+                &repo.path,
+                &worktree_path,
+                &workspace.branch,
+                &workspace_repo.target_branch,
+                signing,
+            );
+            if let Some(action) = status.action {
+                tracing::info!(
+                    "Brought attempt {} up to date with '{}' ({:?})",
+                    workspace.id,
+                    workspace_repo.target_branch,
+                    action
+                );
+            }
+            BranchSyncStatus::upsert(
+                pool,
+                &UpsertBranchSyncStatus {
+                    workspace_id: workspace.id,
+                    repo_id: repo.id,
+                    target_branch: workspace_repo.target_branch.clone(),
+                    commits_behind: status.commits_behind as i64,
+                    conflicted_files: status.conflicted_files,
+                    action: status.action,
+                    error: status.error,
+                },
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+struct SyncOutcome {
+    commits_behind: usize,
+    conflicted_files: Vec<String>,
+    action: Option<BranchSyncAction>,
+    error: Option<String>,
+}
+
+fn sync_repo(
+    git: &GitService,
+    mode: BranchSyncMode,
+    repo_path: &Path,
+    worktree_path: &Path,
+    branch: &str,
+    target_branch: &str,
+    signing: &CommitSigningConfig,
+) -> SyncOutcome {
+    let (commits_behind, conflicted_files) =
+        match git.check_against_base(repo_path, branch, target_branch) {
+            Ok(check) => check,
+            Err(e) => {
+                return SyncOutcome {
+                    commits_behind: 0,
+                    conflicted_files: Vec::new(),
+                    action: None,
+                    error: Some(e.to_string()),
+                };
+            }
+        };
+    let mut outcome = SyncOutcome {
+        commits_behind,
+        conflicted_files,
+        action: None,
+        error: None,
+    };
+    if commits_behind == 0 || !outcome.conflicted_files.is_empty() {
+        return outcome;
+    }
+
+    let synced = match mode {
+        BranchSyncMode::Off | BranchSyncMode::Check => return outcome,
+        BranchSyncMode::Rebase => git
+            .rebase_branch(
+                repo_path,
+                worktree_path,
+                target_branch,
+                target_branch,
+                branch,
+            )
+            .map(|_| BranchSyncAction::Rebased),
+        BranchSyncMode::Merge => git
+            .merge_base_into_branch(worktree_path, target_branch, signing)
+            .map(|_| BranchSyncAction::Merged),
+    };
+    match synced {
+        Ok(action) => {
+            outcome.commits_behind = 0;
+            outcome.action = Some(action);
+        }
+        Err(GitServiceError::MergeConflicts(msg)) => {
+            // One of the branch's commits conflicts, though the branches as a
+            // whole merge cleanly. The rebase is aborted for the user to redo;
+            // a conflicting merge aborts itself.
+            outcome.conflicted_files = git.get_conflicted_files(worktree_path).unwrap_or_default();
+            outcome.error = Some(match mode {
+                BranchSyncMode::Rebase => match git.abort_rebase(worktree_path) {
+                    Ok(()) => msg,
+                    Err(e) => format!("{msg} ({e})"),
+                },
+                _ => msg,
+            });
+        }
+        Err(e) => outcome.error = Some(e.to_string()),
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_are_due_after_the_interval() {
+        let settings = BranchSyncSettings {
+            mode: BranchSyncMode::Rebase,
+            interval_minutes: 30,
+        };
+        let now = Utc::now();
+        assert!(is_due(&settings, None, now));
+        assert!(is_due(&settings, Some(now - Duration::minutes(31)), now));
+        assert!(!is_due(&settings, Some(now - Duration::minutes(5)), now));
+
+        let off = BranchSyncSettings {
+            mode: BranchSyncMode::Off,
+            ..settings
+        };
+        assert!(!is_due(&off, None, now));
+    }
+}
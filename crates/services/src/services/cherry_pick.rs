@@ -0,0 +1,190 @@
+//! Cherry-picks of an attempt's commits onto another branch, such as a release
+//! branch for a hotfix. Each runs in a worktree of its own under
+//! `.vk-cherry-picks` in the attempt's directory, detached at the target's
+//! tip, so its conflicts are resolved as a rebase's are and the target only
+//! moves once every commit is picked. Being inside the attempt's directory, a
+//! cherry-pick stopped on conflicts lasts as long as the attempt does.
+
+use std::path::{Path, PathBuf};
+
+use db::models::merge::{DirectMerge, MergeStrategy};
+use serde::Serialize;
+use ts_rs::TS;
+
+use super::{
+    git::{GitCli, GitCliError, GitService, GitServiceError},
+    rebase_conflicts::{self, ConflictDetails},
+};
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CherryPickStatus {
+    pub target_branch: String,
+    /// The target's new tip once every commit is picked, None while the
+    /// cherry-pick is stopped on conflicts
+    pub target_commit: Option<String>,
+    pub conflicts: ConflictDetails,
+}
+
+pub const CHERRY_PICK_DIR: &str = ".vk-cherry-picks";
+
+/// Worktree of the cherry-pick of one of the attempt's repositories onto the
+/// target branch
+pub fn worktree_path(attempt_dir: &Path, repo_name: &str, target_branch: &str) -> PathBuf {
+    let target: String = target_branch
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    attempt_dir
+        .join(CHERRY_PICK_DIR)
+        .join(target)
+        .join(repo_name)
+}
+
+/// The commits to pick: those of the attempt's branch that aren't on its base
+/// branch, or once the attempt was merged here, the commits its merge brought
+/// in. Empty when a fast-forward or rebase merged them, which doesn't record
+/// where they start.
+pub fn attempt_commits(
+    git: &GitService,
+    repo_path: &Path,
+    branch: &str,
+    base_branch: &str,
+    direct_merge: Option<&DirectMerge>,
+) -> Result<Vec<String>, GitServiceError> {
+    let own = git.list_commits(repo_path, base_branch, branch)?;
+    let Some(merge) = direct_merge.filter(|_| own.is_empty()) else {
+        return Ok(own);
+    };
+    match merge.merge_strategy {
+        // Merges from before the strategy was recorded were squashed
+        None | Some(MergeStrategy::Squash) => Ok(vec![merge.merge_commit.clone()]),
+        Some(MergeStrategy::MergeCommit) => {
+            match git
+                .commit_parents(repo_path, &merge.merge_commit)?
+                .as_slice()
+            {
+                [base, merged] => git.list_commits(repo_path, base, merged),
+                _ => Ok(Vec::new()),
+            }
+        }
+        Some(MergeStrategy::Rebase | MergeStrategy::FastForward) => Ok(Vec::new()),
+    }
+}
+
+/// Start picking the commits onto the target branch, finishing at once
+/// unless they conflict
+pub fn start(
+    git: &GitService,
+    repo_path: &Path,
+    worktree_path: &Path,
+    target_branch: &str,
+    commits: &[String],
+) -> Result<CherryPickStatus, GitServiceError> {
+    match git.start_cherry_pick(repo_path, worktree_path, target_branch, commits) {
+        Ok(()) => finish(git, repo_path, worktree_path, target_branch),
+        Err(GitServiceError::MergeConflicts(_)) => stopped(git, worktree_path, target_branch),
+        Err(e) => {
+            if worktree_path.exists() {
+                let _ = remove_worktree(repo_path, worktree_path);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Carry on with the cherry-pick once every conflict is resolved. It may stop
+/// again on the conflicts of a later commit.
+pub fn continue_cherry_pick(
+    git: &GitService,
+    repo_path: &Path,
+    worktree_path: &Path,
+    target_branch: &str,
+) -> Result<CherryPickStatus, GitServiceError> {
+    match GitCli::new().continue_cherry_pick(worktree_path) {
+        Ok(()) => finish(git, repo_path, worktree_path, target_branch),
+        Err(GitCliError::CommandFailed(_))
+            if !git.get_conflicted_files(worktree_path)?.is_empty() =>
+        {
+            stopped(git, worktree_path, target_branch)
+        }
+        Err(e) => Err(cli_error(e)),
+    }
+}
+
+/// Give up on the cherry-pick, leaving the target branch as it was
+pub fn abort(
+    git: &GitService,
+    repo_path: &Path,
+    worktree_path: &Path,
+) -> Result<(), GitServiceError> {
+    git.abort_conflicts(worktree_path)?;
+    remove_worktree(repo_path, worktree_path)
+}
+
+fn stopped(
+    git: &GitService,
+    worktree_path: &Path,
+    target_branch: &str,
+) -> Result<CherryPickStatus, GitServiceError> {
+    Ok(CherryPickStatus {
+        target_branch: target_branch.to_string(),
+        target_commit: None,
+        conflicts: rebase_conflicts::conflict_details(git, worktree_path)?,
+    })
+}
+
+/// Move the target branch to the picked commits. The worktree is kept if the
+/// target moved meanwhile, so the picks aren't lost.
+fn finish(
+    git: &GitService,
+    repo_path: &Path,
+    worktree_path: &Path,
+    target_branch: &str,
+) -> Result<CherryPickStatus, GitServiceError> {
+    let head = git.resolve_commit(worktree_path, "HEAD")?;
+    git.fast_forward_branch(repo_path, target_branch, &head)?;
+    remove_worktree(repo_path, worktree_path)?;
+    Ok(CherryPickStatus {
+        target_branch: target_branch.to_string(),
+        target_commit: Some(head),
+        conflicts: ConflictDetails {
+            op: None,
+            files: Vec::new(),
+        },
+    })
+}
+
+fn remove_worktree(repo_path: &Path, worktree_path: &Path) -> Result<(), GitServiceError> {
+    GitCli::new()
+        .worktree_remove(repo_path, worktree_path, true)
+        .map_err(cli_error)?;
+    // The target's directory and the one of all cherry-picks, unless other
+    // cherry-picks still use them
+    for dir in worktree_path.ancestors().skip(1).take(2) {
+        let _ = std::fs::remove_dir(dir);
+    }
+    Ok(())
+}
+
+fn cli_error(e: GitCliError) -> GitServiceError {
+    GitServiceError::InvalidRepository(format!("git failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worktrees_sit_inside_the_attempts() {
+        assert_eq!(
+            worktree_path(Path::new("/tmp/worktrees/1a2b-fix"), "api", "release/2.1"),
+            PathBuf::from("/tmp/worktrees/1a2b-fix/.vk-cherry-picks/release-2.1/api")
+        );
+    }
+}
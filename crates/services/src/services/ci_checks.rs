@@ -0,0 +1,125 @@
+//! CI checks of attempts' pull requests. GitHub check runs and commit
+//! statuses, and the jobs of GitLab pipelines, are polled with the pull
+//! request's status and normalized to one set of states, so a project can have
+//! merges wait until every check of the branch's tip has passed.
+
+use db::models::ci_check::{CiCheck, CiCheckState};
+
+/// A GitHub check run, from its status and, once completed, its conclusion
+pub fn github_check_run_state(status: &str, conclusion: Option<&str>) -> CiCheckState {
+    if !status.eq_ignore_ascii_case("completed") {
+        return CiCheckState::Pending;
+    }
+    match conclusion.map(|c| c.to_ascii_uppercase()).as_deref() {
+        Some("SUCCESS") => CiCheckState::Passed,
+        Some("NEUTRAL" | "SKIPPED") => CiCheckState::Skipped,
+        // FAILURE, CANCELLED, TIMED_OUT, ACTION_REQUIRED, STARTUP_FAILURE, STALE
+        _ => CiCheckState::Failed,
+    }
+}
+
+/// A GitHub commit status, as reported by older integrations
+pub fn github_status_state(state: &str) -> CiCheckState {
+    match state.to_ascii_uppercase().as_str() {
+        "SUCCESS" => CiCheckState::Passed,
+        "FAILURE" | "ERROR" => CiCheckState::Failed,
+        // PENDING, EXPECTED
+        _ => CiCheckState::Pending,
+    }
+}
+
+/// A GitLab job. Failed jobs that are allowed to fail don't hold up a merge.
+pub fn gitlab_job_state(status: &str, allow_failure: bool) -> CiCheckState {
+    match status {
+        "success" => CiCheckState::Passed,
+        "failed" if allow_failure => CiCheckState::Skipped,
+        "failed" | "canceled" => CiCheckState::Failed,
+        "skipped" | "manual" => CiCheckState::Skipped,
+        // created, waiting_for_resource, preparing, pending, running, scheduled
+        _ => CiCheckState::Pending,
+    }
+}
+
+/// Why the checks don't let the branch be merged yet, or None when every one
+/// of them ran on `head_commit` and none failed or is still running
+pub fn unmet_reason(checks: &[CiCheck], head_commit: &str) -> Option<String> {
+    if checks.is_empty() {
+        return Some("No CI checks have reported on the pull request yet".to_string());
+    }
+    if checks.iter().any(|check| check.head_commit != head_commit) {
+        return Some(
+            "The CI checks are of an older commit than the branch's tip; push it and wait for them"
+                .to_string(),
+        );
+    }
+    let failed: Vec<&str> = checks
+        .iter()
+        .filter(|check| check.state == CiCheckState::Failed)
+        .map(|check| check.name.as_str())
+        .collect();
+    if !failed.is_empty() {
+        return Some(format!("CI checks failed: {}", failed.join(", ")));
+    }
+    match checks
+        .iter()
+        .filter(|check| check.state == CiCheckState::Pending)
+        .count()
+    {
+        0 => None,
+        pending => Some(format!("{pending} CI check(s) are still running")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn check(name: &str, head_commit: &str, state: CiCheckState) -> CiCheck {
+        CiCheck {
+            id: Uuid::new_v4(),
+            merge_id: Uuid::nil(),
+            workspace_id: Uuid::nil(),
+            repo_id: Uuid::nil(),
+            head_commit: head_commit.to_string(),
+            name: name.to_string(),
+            state,
+            url: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn merges_wait_for_every_check_of_the_tip_to_pass() {
+        assert_eq!(
+            github_check_run_state("IN_PROGRESS", None),
+            CiCheckState::Pending
+        );
+        assert_eq!(
+            github_check_run_state("COMPLETED", Some("TIMED_OUT")),
+            CiCheckState::Failed
+        );
+        assert_eq!(github_status_state("EXPECTED"), CiCheckState::Pending);
+        assert_eq!(gitlab_job_state("failed", true), CiCheckState::Skipped);
+        assert_eq!(gitlab_job_state("manual", false), CiCheckState::Skipped);
+
+        assert!(unmet_reason(&[], "abc").is_some());
+        let green = [
+            check("build", "abc", CiCheckState::Passed),
+            check("deploy", "abc", CiCheckState::Skipped),
+        ];
+        assert_eq!(unmet_reason(&green, "abc"), None);
+        assert!(unmet_reason(&green, "def").is_some());
+
+        let red = [
+            check("build", "abc", CiCheckState::Failed),
+            check("test", "abc", CiCheckState::Pending),
+        ];
+        assert_eq!(
+            unmet_reason(&red, "abc").as_deref(),
+            Some("CI checks failed: build")
+        );
+    }
+}
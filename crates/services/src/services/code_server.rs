@@ -5,6 +5,8 @@ use std::time::{Duration, Instant};
 use thiserror::Error;
 use tracing::{info, warn};
 
+use super::process_registry::{self, ProcessInfo, ProcessKind, ProcessRegistry};
+
 #[derive(Debug, Error)]
 pub enum CodeServerError {
     #[error("Failed to spawn code-server: {0}")]
@@ -64,6 +66,31 @@ impl Default for CodeServerConfig {
     }
 }
 
+/// Data directory of the instances opened on a folder, named by a hash of its path
+fn workspace_data_dir(data_dir: &str, workspace_path: &Path) -> std::path::PathBuf {
+    // Use a hash of the workspace path to create a unique subdirectory
+    let workspace_hash = {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        workspace_path.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    std::path::Path::new(data_dir).join(format!("workspace-{:x}", workspace_hash))
+}
+
+/// Remove the settings and extension state kept for instances opened on a
+/// folder that is going away
+pub async fn remove_workspace_data(workspace_path: &Path) {
+    let dir = workspace_data_dir(&CodeServerConfig::default().data_dir, workspace_path);
+    if let Err(e) = tokio::fs::remove_dir_all(&dir).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        warn!("Failed to remove code-server data {}: {}", dir.display(), e);
+    }
+}
+
 impl CodeServerService {
     pub fn new(config: CodeServerConfig) -> Self {
         Self {
@@ -106,7 +133,7 @@ impl CodeServerService {
                         instance.workspace_path,
                         workspace_path
                     );
-                    let _ = instance.process.kill();
+                    instance.stop().await;
                     state.instance = None;
                 }
             } else {
@@ -115,7 +142,7 @@ impl CodeServerService {
                     "Code-server on port {} is dead, respawning",
                     instance.port
                 );
-                let _ = instance.process.kill();
+                instance.stop().await;
                 state.instance = None;
             }
         }
@@ -125,6 +152,11 @@ impl CodeServerService {
         info!("Spawning new code-server on port {} for workspace {:?}", port, workspace_path);
 
         let process = self.spawn_process(port, workspace_path)?;
+        ProcessRegistry::global().register(ProcessInfo::new(
+            process.id(),
+            ProcessKind::CodeServer,
+            workspace_path.to_string_lossy(),
+        ));
 
         // Wait for startup
         tokio::time::sleep(Duration::from_millis(500)).await;
@@ -168,17 +200,7 @@ impl CodeServerService {
 
     fn spawn_process(&self, port: u16, workspace_path: &Path) -> Result<Child, CodeServerError> {
         // Create workspace-specific data directory to prevent coder.json conflicts
-        // Use a hash of the workspace path to create a unique subdirectory
-        let workspace_hash = {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-            let mut hasher = DefaultHasher::new();
-            workspace_path.hash(&mut hasher);
-            hasher.finish()
-        };
-
-        let workspace_data_dir = std::path::Path::new(&self.config.data_dir)
-            .join(format!("workspace-{:x}", workspace_hash));
+        let workspace_data_dir = workspace_data_dir(&self.config.data_dir, workspace_path);
 
         if !workspace_data_dir.exists() {
             std::fs::create_dir_all(&workspace_data_dir).map_err(|e| {
@@ -186,7 +208,15 @@ impl CodeServerService {
             })?;
         }
 
-        Command::new(&self.config.executable_path)
+        let mut command = Command::new(&self.config.executable_path);
+        // Lead a group of its own so the extension host and language servers
+        // it starts are stopped along with it
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        command
             .arg("--auth")
             .arg("none")
             .arg("--bind-addr")
@@ -200,11 +230,22 @@ impl CodeServerService {
     }
 }
 
+impl RunningInstance {
+    async fn stop(&mut self) {
+        let pid = self.process.id();
+        process_registry::kill_tree(pid).await;
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        ProcessRegistry::global().unregister(pid);
+    }
+}
+
 impl Drop for CodeServerService {
     fn drop(&mut self) {
         if let Ok(mut state) = self.inner.try_lock() {
             if let Some(mut instance) = state.instance.take() {
                 let _ = instance.process.kill();
+                ProcessRegistry::global().unregister(instance.process.id());
                 info!("Killed code-server on port {}", instance.port);
             }
         }
@@ -0,0 +1,237 @@
+//! Messages of the commits vibe-kanban makes for an attempt, worded by the
+//! project's templates, checked against Conventional Commits when it asks
+//! for them and ending with a trailer naming the task.
+
+use std::sync::LazyLock;
+
+use db::models::{
+    project_settings::{CommitMessageSettings, ConventionalCommitSettings},
+    task::Task,
+};
+use regex::Regex;
+use uuid::Uuid;
+
+const DEFAULT_ATTEMPT_TEMPLATE: &str = "{summary}";
+const DEFAULT_MERGE_TEMPLATE: &str =
+    "{task_title} (vibe-kanban {short_task_id})\n\n{task_description}";
+
+const PLACEHOLDERS: [&str; 6] = [
+    "summary",
+    "task_title",
+    "task_description",
+    "task_id",
+    "short_task_id",
+    "branch",
+];
+
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{([a-z_]+)\}").expect("valid regex"));
+static CONVENTIONAL_SUBJECT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([a-z][a-z0-9-]*)(\([^()\s][^()]*\))?!?: \S").expect("valid regex")
+});
+static TRAILER_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9][A-Za-z0-9-]*$").expect("valid regex"));
+static TRAILER_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9][A-Za-z0-9-]*: ").expect("valid regex"));
+
+/// Values the templates' placeholders are filled with
+struct Vars<'a> {
+    task_id: Uuid,
+    task_title: &'a str,
+    task_description: Option<&'a str>,
+    branch: &'a str,
+    summary: Option<&'a str>,
+}
+
+impl<'a> Vars<'a> {
+    fn new(task: &'a Task, branch: &'a str, summary: Option<&'a str>) -> Self {
+        Self {
+            task_id: task.id,
+            task_title: &task.title,
+            task_description: task.description.as_deref(),
+            branch,
+            summary,
+        }
+    }
+}
+
+/// Message of the commit of an attempt's run. One whose subject isn't a
+/// conventional commit is given the fallback type rather than left
+/// uncommitted.
+pub fn attempt_message(
+    settings: &CommitMessageSettings,
+    task: &Task,
+    branch: &str,
+    summary: &str,
+) -> String {
+    compose_attempt(settings, &Vars::new(task, branch, Some(summary)))
+}
+
+/// Message of the squash commit made when the attempt is merged, or why it
+/// isn't a conventional commit
+pub fn merge_message(
+    settings: &CommitMessageSettings,
+    task: &Task,
+    branch: &str,
+) -> Result<String, String> {
+    compose_merge(settings, &Vars::new(task, branch, None))
+}
+
+fn compose_attempt(settings: &CommitMessageSettings, vars: &Vars) -> String {
+    let template = settings
+        .attempt_template
+        .as_deref()
+        .unwrap_or(DEFAULT_ATTEMPT_TEMPLATE);
+    let mut message = render(template, vars);
+    let conventional = &settings.conventional_commits;
+    if conventional.enabled && check_conventional(&message, conventional).is_err() {
+        message = format!("{}: {}", conventional.fallback_type, message);
+    }
+    with_task_trailer(message, settings, vars.task_id)
+}
+
+fn compose_merge(settings: &CommitMessageSettings, vars: &Vars) -> Result<String, String> {
+    let template = settings
+        .merge_template
+        .as_deref()
+        .unwrap_or(DEFAULT_MERGE_TEMPLATE);
+    let message = render(template, vars);
+    if settings.conventional_commits.enabled {
+        check_conventional(&message, &settings.conventional_commits)?;
+    }
+    Ok(with_task_trailer(message, settings, vars.task_id))
+}
+
+/// Reasons the settings can't be applied, checked when they are saved
+pub fn validate(settings: &CommitMessageSettings) -> Result<(), String> {
+    for (name, template, allowed) in [
+        ("attempt", &settings.attempt_template, &PLACEHOLDERS[..]),
+        ("merge", &settings.merge_template, &PLACEHOLDERS[1..]),
+    ] {
+        let Some(template) = template else {
+            continue;
+        };
+        if template.trim().is_empty() {
+            return Err(format!("The {name} template is empty"));
+        }
+        if let Some(unknown) = PLACEHOLDER
+            .captures_iter(template)
+            .map(|caps| caps.get(1).unwrap().as_str())
+            .find(|placeholder| !allowed.contains(placeholder))
+        {
+            return Err(format!(
+                "The {name} template has an unknown placeholder {{{unknown}}}"
+            ));
+        }
+    }
+    if let Some(trailer) = &settings.task_trailer
+        && !TRAILER_NAME.is_match(trailer)
+    {
+        return Err(format!("'{trailer}' isn't a valid trailer name"));
+    }
+    let conventional = &settings.conventional_commits;
+    if conventional.enabled {
+        check_conventional(
+            &format!("{}: subject", conventional.fallback_type),
+            conventional,
+        )
+        .map_err(|e| format!("Invalid fallback type: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Whether the first line of the message reads `type(scope)!: description`
+/// with an allowed type
+pub fn check_conventional(
+    message: &str,
+    settings: &ConventionalCommitSettings,
+) -> Result<(), String> {
+    let subject = message.lines().next().unwrap_or_default();
+    let Some(caps) = CONVENTIONAL_SUBJECT.captures(subject) else {
+        return Err(format!(
+            "'{subject}' doesn't read 'type(scope): description'"
+        ));
+    };
+    let kind = &caps[1];
+    if !settings.types.is_empty() && !settings.types.iter().any(|t| t == kind) {
+        return Err(format!(
+            "'{kind}' isn't one of the allowed types ({})",
+            settings.types.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn render(template: &str, vars: &Vars) -> String {
+    let task_id = vars.task_id.to_string();
+    let short_task_id = task_id.split('-').next().unwrap_or(&task_id).to_string();
+    let rendered = PLACEHOLDER.replace_all(template, |caps: &regex::Captures| match &caps[1] {
+        "summary" => vars.summary.unwrap_or_default().to_string(),
+        "task_title" => vars.task_title.to_string(),
+        "task_description" => vars.task_description.unwrap_or_default().to_string(),
+        "task_id" => task_id.clone(),
+        "short_task_id" => short_task_id.clone(),
+        "branch" => vars.branch.to_string(),
+        _ => caps[0].to_string(),
+    });
+    // An empty description or summary shouldn't leave blank lines behind
+    rendered.trim().to_string()
+}
+
+fn with_task_trailer(message: String, settings: &CommitMessageSettings, task_id: Uuid) -> String {
+    let Some(name) = &settings.task_trailer else {
+        return message;
+    };
+    let trailer = format!("{name}: {task_id}");
+    if message.lines().any(|line| line == trailer) {
+        return message;
+    }
+    // Join a trailer block the message already ends with
+    let last_paragraph = message.rsplit("\n\n").next().unwrap_or_default();
+    let ends_with_trailers = message.contains("\n\n")
+        && last_paragraph
+            .lines()
+            .all(|line| TRAILER_LINE.is_match(line));
+    let separator = if ends_with_trailers { "\n" } else { "\n\n" };
+    format!("{message}{separator}{trailer}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_follow_the_templates_and_conventions() {
+        let vars = Vars {
+            task_id: Uuid::parse_str("0a1b2c3d-0000-0000-0000-000000000000").unwrap(),
+            task_title: "Add login page",
+            task_description: None,
+            branch: "vk/0a1b-login",
+            summary: Some("Added the form"),
+        };
+        let mut settings = CommitMessageSettings::default();
+        assert_eq!(
+            compose_merge(&settings, &vars).unwrap(),
+            "Add login page (vibe-kanban 0a1b2c3d)\n\n\
+             Vibe-Kanban-Task: 0a1b2c3d-0000-0000-0000-000000000000"
+        );
+
+        settings.conventional_commits.enabled = true;
+        assert_eq!(
+            compose_attempt(&settings, &vars),
+            "chore: Added the form\n\n\
+             Vibe-Kanban-Task: 0a1b2c3d-0000-0000-0000-000000000000"
+        );
+        assert!(compose_merge(&settings, &vars).is_err());
+
+        settings.merge_template = Some("feat(auth): {task_title}\n\nRefs: {branch}".to_string());
+        assert_eq!(
+            compose_merge(&settings, &vars).unwrap(),
+            "feat(auth): Add login page\n\nRefs: vk/0a1b-login\n\
+             Vibe-Kanban-Task: 0a1b2c3d-0000-0000-0000-000000000000"
+        );
+
+        settings.merge_template = Some("{summary}".to_string());
+        assert!(validate(&settings).is_err());
+    }
+}
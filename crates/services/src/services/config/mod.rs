@@ -26,6 +26,13 @@ pub type EditorType = versions::v8::EditorType;
 pub type GitHubConfig = versions::v8::GitHubConfig;
 pub type UiLanguage = versions::v8::UiLanguage;
 pub type ShowcaseState = versions::v8::ShowcaseState;
+pub type AttemptQueueConfig = versions::v8::AttemptQueueConfig;
+pub type AttemptQueueOrder = versions::v8::AttemptQueueOrder;
+pub type LocalModelEndpoint = versions::v8::LocalModelEndpoint;
+pub type LocalModelServer = versions::v8::LocalModelServer;
+pub type CommitSigningConfig = versions::v8::CommitSigningConfig;
+pub type CommitSigningMode = versions::v8::CommitSigningMode;
+pub type SigningFormat = versions::v8::SigningFormat;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
@@ -41,6 +41,96 @@ pub struct Config {
     pub pr_auto_description_enabled: bool,
     #[serde(default)]
     pub pr_auto_description_prompt: Option<String>,
+    #[serde(default)]
+    pub attempt_queue: AttemptQueueConfig,
+    #[serde(default)]
+    pub local_model_endpoints: Vec<LocalModelEndpoint>,
+    #[serde(default)]
+    pub commit_signing: CommitSigningConfig,
+}
+
+/// How many attempts may run at once across all projects, and in which order
+/// the ones waiting for a slot start. Projects can set a lower limit of their
+/// own.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct AttemptQueueConfig {
+    /// None for no limit
+    pub max_concurrent_attempts: Option<u32>,
+    pub order: AttemptQueueOrder,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum AttemptQueueOrder {
+    /// In the order they were queued
+    #[default]
+    Fifo,
+    /// Most urgent task first, then in the order they were queued
+    Priority,
+}
+
+/// A local inference server, offered as a model backend to the executors that
+/// can talk to it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+pub struct LocalModelEndpoint {
+    /// Unique; the profile variants made for its models are named after it
+    pub name: String,
+    pub server: LocalModelServer,
+    /// Where the server listens, e.g. http://localhost:11434
+    pub url: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum LocalModelServer {
+    Ollama,
+    Vllm,
+}
+
+/// How the commits vibe-kanban makes, on an attempt's branch and when merging
+/// it, are signed, e.g. for branches that only take signed commits
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct CommitSigningConfig {
+    pub mode: CommitSigningMode,
+    pub format: SigningFormat,
+    /// GPG key ID, or the path of an SSH key; None for the git config's
+    /// `user.signingkey`
+    pub key: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum CommitSigningMode {
+    /// As the git config's `commit.gpgsign` says
+    #[default]
+    GitConfig,
+    Always,
+    Never,
+}
+
+/// Kind of the configured key, ignored when the git config's is used
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SigningFormat {
+    #[default]
+    Openpgp,
+    Ssh,
+}
+
+impl SigningFormat {
+    /// Value of the git config's `gpg.format`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Openpgp => "openpgp",
+            Self::Ssh => "ssh",
+        }
+    }
 }
 
 impl Config {
@@ -66,6 +156,9 @@ impl Config {
             showcases: old_config.showcases,
             pr_auto_description_enabled: true,
             pr_auto_description_prompt: None,
+            attempt_queue: AttemptQueueConfig::default(),
+            local_model_endpoints: Vec::new(),
+            commit_signing: CommitSigningConfig::default(),
         }
     }
 
@@ -116,6 +209,9 @@ impl Default for Config {
             showcases: ShowcaseState::default(),
             pr_auto_description_enabled: true,
             pr_auto_description_prompt: None,
+            attempt_queue: AttemptQueueConfig::default(),
+            local_model_endpoints: Vec::new(),
+            commit_signing: CommitSigningConfig::default(),
         }
     }
 }
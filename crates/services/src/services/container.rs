@@ -1,7 +1,8 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Error as AnyhowError, anyhow};
@@ -14,12 +15,18 @@ use db::{
             CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessRunReason,
             ExecutionProcessStatus,
         },
+        execution_process_entries::ExecutionProcessEntries,
+        execution_process_interruption::ExecutionProcessInterruption,
         execution_process_logs::ExecutionProcessLogs,
+        execution_process_recording::{ExecutionProcessRecording, RecordedEvent},
         execution_process_repo_state::{
             CreateExecutionProcessRepoState, ExecutionProcessRepoState,
         },
+        hook_run::HookEvent,
         project::{Project, UpdateProject},
         project_repo::{ProjectRepo, ProjectRepoWithName},
+        project_settings::{GitProvisioningSettings, ProjectSettings, PromptSettings, SharedCache},
+        queued_attempt::QueuedAttempt,
         repo::Repo,
         session::{CreateSession, Session, SessionError},
         task::{Task, TaskStatus},
@@ -33,14 +40,21 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
+    env::ExecutionEnv,
     executors::{ExecutorError, StandardCodingAgentExecutor},
-    logs::{NormalizedEntry, NormalizedEntryError, NormalizedEntryType, utils::ConversationPatch},
+    logs::{
+        NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
+        utils::{ConversationPatch, patch::normalized_entries_from_patches},
+    },
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use futures::{StreamExt, future};
+use json_patch::Patch;
+use serde::Serialize;
 use sqlx::Error as SqlxError;
 use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
+use ts_rs::TS;
 use utils::{
     log_msg::LogMsg,
     msg_store::MsgStore,
@@ -49,14 +63,89 @@ use utils::{
 use uuid::Uuid;
 
 use crate::services::{
+    attempt_queue::{self, AttemptStart, StartingAttempt},
+    config::AttemptQueueConfig,
     git::{GitService, GitServiceError},
+    lifecycle_hooks::{self, HookDetails},
     notification::NotificationService,
+    previous_attempt::PreviousAttempt,
+    process_registry::{ProcessKind, ProcessRegistry},
+    project_cleanup::DeletedProject,
     share::SharePublisher,
     workspace_manager::WorkspaceError as WorkspaceManagerError,
+    workspace_provisioning,
     worktree_manager::WorktreeError,
 };
 pub type ContainerRef = String;
 
+/// Longest a log search waits for a run's stored output to be normalized again
+const RENORMALIZE_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a recording waits after the run finishes for the normalizer to
+/// catch up with the last output
+const RECORDING_GRACE: Duration = Duration::from_secs(2);
+
+/// Environment shared by every process started in a workspace
+pub struct WorkspaceEnv {
+    pub env: ExecutionEnv,
+    /// Plaintext secret values present in `env`, to mask in captured output
+    pub secret_values: Vec<String>,
+}
+
+/// A port a process in the workspace is listening on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewTarget {
+    pub port: u16,
+    /// Host the port can be reached at from this server
+    pub host: String,
+}
+
+/// Resources currently used by the processes of an attempt
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct ResourceUsage {
+    pub memory_bytes: u64,
+    /// CPU time used so far; sample twice for a rate
+    pub cpu_usec: u64,
+    pub processes: u32,
+    /// Processes killed for running out of memory, where that is tracked
+    pub oom_kills: u64,
+}
+
+/// Where the workspace's executors run and whether that is up
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "status", rename_all = "snake_case")]
+#[ts(tag = "status", rename_all = "snake_case")]
+pub enum ContainerStatus {
+    /// Executors run directly on the host
+    Host,
+    /// Executors run over SSH, whose connection isn't probed
+    Remote {
+        host: String,
+    },
+    Running {
+        name: String,
+    },
+    /// The container exists but isn't running, `state` as reported by the
+    /// runtime (e.g. `exited`, `Pending`)
+    Stopped {
+        name: String,
+        state: String,
+    },
+    /// No container has been created, or it was removed
+    Missing,
+    /// The container runtime couldn't be asked
+    Unavailable {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SharedCacheUsage {
+    pub cache: SharedCache,
+    pub size_bytes: u64,
+    /// Whether a running execution holds the cache, so it can't be emptied
+    pub in_use: bool,
+}
+
 #[derive(Debug, Error)]
 pub enum ContainerError {
     #[error(transparent)]
@@ -101,6 +190,10 @@ pub trait ContainerService {
 
     async fn delete(&self, workspace: &Workspace) -> Result<(), ContainerError>;
 
+    /// Queue the removal of a deleted project's workspaces, containers and
+    /// caches, which runs in the background
+    fn queue_project_cleanup(&self, deleted: DeletedProject);
+
     /// Check if a task has any running execution processes
     async fn has_running_processes(&self, task_id: Uuid) -> Result<bool, ContainerError> {
         let workspaces = Workspace::fetch_all(&self.db().pool, Some(task_id)).await?;
@@ -183,6 +276,15 @@ pub trait ContainerService {
                 tracing::error!("Failed to update task status to InReview: {e}");
             }
         }
+        lifecycle_hooks::spawn_hooks(
+            self.db().pool.clone(),
+            ctx.workspace.clone(),
+            HookEvent::AfterFinish,
+            HookDetails {
+                outcome: Some(ctx.execution_process.status.clone()),
+                ..Default::default()
+            },
+        );
 
         // Skip notification if process was intentionally killed by user
         if matches!(ctx.execution_process.status, ExecutionProcessStatus::Killed) {
@@ -210,7 +312,12 @@ pub trait ContainerService {
         self.notification_service().notify(&title, &message).await;
     }
 
-    /// Cleanup executions marked as running in the db, call at startup
+    /// Cleanup executions marked as running in the db, call at startup.
+    ///
+    /// A run's output pipes close with the server that spawned it, so there is
+    /// nothing to re-attach to; what it printed was already streamed to the db.
+    /// Coding agent runs are recorded as interrupted so the attempt can be
+    /// resumed from its agent session.
     async fn cleanup_orphan_executions(&self) -> Result<(), ContainerError> {
         let running_processes = ExecutionProcess::find_running(&self.db().pool).await?;
         for process in running_processes {
@@ -235,8 +342,11 @@ pub trait ContainerService {
                 );
                 continue;
             }
+            let ctx = ExecutionProcess::load_context(&self.db().pool, process.id)
+                .await
+                .ok();
             // Capture after-head commit OID per repository
-            if let Ok(ctx) = ExecutionProcess::load_context(&self.db().pool, process.id).await
+            if let Some(ctx) = &ctx
                 && let Some(ref container_ref) = ctx.workspace.container_ref
             {
                 let workspace_root = PathBuf::from(container_ref);
@@ -262,6 +372,11 @@ pub trait ContainerService {
             }
             // Process marked as failed
             tracing::info!("Marked orphaned execution process {} as failed", process.id);
+            if let Some(ctx) = &ctx
+                && matches!(process.run_reason, ExecutionProcessRunReason::CodingAgent)
+            {
+                self.record_interruption(&process, ctx.workspace.id).await;
+            }
             // Update task status to InReview for coding agent and setup script failures
             if matches!(
                 process.run_reason,
@@ -298,6 +413,25 @@ pub trait ContainerService {
         Ok(())
     }
 
+    /// Note the interruption at the end of the run's logs and keep it open
+    /// for the attempt to be resumed from
+    async fn record_interruption(&self, process: &ExecutionProcess, workspace_id: Uuid) {
+        let pool = &self.db().pool;
+        let note = LogMsg::Stderr(
+            "The server stopped while this run was going. Resume the attempt to continue.\n"
+                .to_string(),
+        );
+        if let Ok(line) = serde_json::to_string(&note)
+            && let Err(e) =
+                ExecutionProcessLogs::append_log_line(pool, process.id, &format!("{line}\n")).await
+        {
+            tracing::warn!("Failed to note the interruption of {}: {}", process.id, e);
+        }
+        if let Err(e) = ExecutionProcessInterruption::create(pool, process.id, workspace_id).await {
+            tracing::error!("Failed to record the interruption of {}: {}", process.id, e);
+        }
+    }
+
     /// Backfill before_head_commit for legacy execution processes.
     /// Rules:
     /// - If a process has after_head_commit and missing before_head_commit,
@@ -461,6 +595,59 @@ pub trait ContainerService {
         Some(root_action)
     }
 
+    /// The coding agent's first turn on the task, followed by the cleanup
+    /// scripts
+    fn initial_agent_action(
+        &self,
+        task: &Task,
+        workspace: &Workspace,
+        repos: &[ProjectRepoWithName],
+        executor_profile_id: ExecutorProfileId,
+    ) -> ExecutorAction {
+        let working_dir = workspace
+            .agent_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+
+        ExecutorAction::new(
+            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                prompt: task.to_prompt(),
+                executor_profile_id,
+                working_dir,
+                prompt_additions: None,
+            }),
+            self.cleanup_actions_for_repos(repos).map(Box::new),
+        )
+    }
+
+    /// Submodule and Git LFS steps for the worktrees in `workspace_dir` that
+    /// need them, to run ahead of the setup scripts
+    fn provisioning_actions_for_repos(
+        &self,
+        workspace_dir: &Path,
+        repos: &[ProjectRepoWithName],
+        settings: &GitProvisioningSettings,
+    ) -> Option<ExecutorAction> {
+        let mut actions = repos.iter().filter_map(|repo| {
+            let script = workspace_provisioning::provisioning_script(
+                &workspace_dir.join(&repo.repo_name),
+                settings,
+            )?;
+            Some(ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script,
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::SetupScript,
+                    working_dir: Some(repo.repo_name.clone()),
+                }),
+                None,
+            ))
+        });
+        let first = actions.next()?;
+        Some(actions.fold(first, ExecutorAction::append_action))
+    }
+
     fn setup_actions_for_repos(&self, repos: &[ProjectRepoWithName]) -> Option<ExecutorAction> {
         let repos_with_setup: Vec<_> = repos.iter().filter(|r| r.setup_script.is_some()).collect();
 
@@ -563,6 +750,13 @@ pub trait ContainerService {
                 }
             }
         }
+
+        // Exec commands are part of the attempt too; its terminals stay open
+        ProcessRegistry::global()
+            .kill_matching(|process| {
+                process.kind == ProcessKind::Exec && process.workspace_id == Some(workspace.id)
+            })
+            .await;
     }
 
     async fn ensure_container_exists(
@@ -572,6 +766,53 @@ pub trait ContainerService {
 
     async fn is_container_clean(&self, workspace: &Workspace) -> Result<bool, ContainerError>;
 
+    /// Check out another repository in the workspace, side by side with the
+    /// ones it already has, and record it on the workspace
+    async fn add_workspace_repo(
+        &self,
+        workspace: &Workspace,
+        repo: &Repo,
+        target_branch: &str,
+    ) -> Result<(), ContainerError>;
+
+    /// VK_* and project variables for processes started in the workspace, and
+    /// the container to run them in when the project uses one.
+    async fn workspace_env(&self, workspace: &Workspace) -> Result<WorkspaceEnv, ContainerError>;
+
+    /// Ports currently being listened on by processes in the workspace, e.g.
+    /// dev servers started by an agent
+    async fn preview_targets(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Vec<PreviewTarget>, ContainerError>;
+
+    /// Current resource usage of the workspace's processes, when it can be
+    /// measured for the workspace's backend
+    async fn resource_usage(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Option<ResourceUsage>, ContainerError>;
+
+    /// State of the container the workspace's executors run in
+    async fn container_status(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<ContainerStatus, ContainerError>;
+
+    /// Size of each of the project's enabled shared caches
+    async fn shared_cache_usage(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<SharedCacheUsage>, ContainerError>;
+
+    /// Empty one of the project's shared caches. Returns `false` without
+    /// touching it while an execution is using it.
+    async fn clear_shared_cache(
+        &self,
+        project_id: Uuid,
+        cache: SharedCache,
+    ) -> Result<bool, ContainerError>;
+
     async fn start_execution_inner(
         &self,
         workspace: &Workspace,
@@ -609,6 +850,12 @@ pub trait ContainerService {
 
     async fn git_branch_prefix(&self) -> String;
 
+    async fn attempt_queue_config(&self) -> AttemptQueueConfig;
+
+    /// Passes a message to the running process, for executors that take
+    /// input mid-run. False if the process doesn't or has finished.
+    async fn send_interjection(&self, exec_id: &Uuid, message: String) -> bool;
+
     async fn git_branch_from_workspace(&self, workspace_id: &Uuid, task_title: &str) -> String {
         let task_title_id = git_branch_id(task_title);
         let prefix = self.git_branch_prefix().await;
@@ -806,6 +1053,109 @@ pub trait ContainerService {
         }
     }
 
+    /// The conversation of a coding agent run by entry index: as it stands
+    /// while the run's output is held, otherwise as recorded when the run
+    /// ended. Runs from before entries were recorded are normalized again,
+    /// which leaves their entries without times.
+    async fn normalized_entries(&self, id: &Uuid) -> Option<BTreeMap<usize, NormalizedEntry>> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            let history = store.get_history();
+            return Some(normalized_entries_from_patches(history.iter().filter_map(
+                |msg| match msg {
+                    LogMsg::JsonPatch(patch) => Some(patch),
+                    _ => None,
+                },
+            )));
+        }
+        match ExecutionProcessEntries::find_by_execution_id(&self.db().pool, *id).await {
+            Ok(Some(entries)) => return Some(entries),
+            Ok(None) => {}
+            Err(e) => tracing::error!("Failed to fetch entries of execution {}: {}", id, e),
+        }
+
+        // The stream ends once the normalizer is done with the stored output
+        let patches: Vec<Patch> = self
+            .stream_normalized_logs(id)
+            .await?
+            .take_until(tokio::time::sleep(RENORMALIZE_TIMEOUT))
+            .filter_map(|msg| {
+                future::ready(match msg {
+                    Ok(LogMsg::JsonPatch(patch)) => Some(patch),
+                    _ => None,
+                })
+            })
+            .collect()
+            .await;
+        let mut entries = normalized_entries_from_patches(&patches);
+        for entry in entries.values_mut() {
+            // Stamped now, not when the run produced them
+            entry.timestamp = None;
+        }
+        Some(entries)
+    }
+
+    /// Record the patches to the run's conversation with how long into the
+    /// run each was made, saved once the run has finished, so the run can be
+    /// replayed at its own pace
+    fn spawn_record_session(&self, execution_id: &Uuid) -> JoinHandle<()> {
+        let execution_id = *execution_id;
+        let msg_stores = self.msg_stores().clone();
+        let db = self.db().clone();
+
+        tokio::spawn(async move {
+            let store = {
+                let map = msg_stores.read().await;
+                map.get(&execution_id).cloned()
+            };
+            let Some(store) = store else {
+                return;
+            };
+
+            let started = std::time::Instant::now();
+            let elapsed_ms = || started.elapsed().as_millis() as i64;
+            let mut events = Vec::new();
+            let mut duration_ms = None;
+            let mut stream = store.history_plus_stream();
+            loop {
+                let next = match duration_ms {
+                    None => stream.next().await,
+                    Some(_) => match tokio::time::timeout(RECORDING_GRACE, stream.next()).await {
+                        Ok(next) => next,
+                        Err(_) => break,
+                    },
+                };
+                match next {
+                    Some(Ok(LogMsg::JsonPatch(patch))) => match serde_json::to_value(&patch) {
+                        Ok(patch) => events.push(RecordedEvent {
+                            at_ms: elapsed_ms(),
+                            patch,
+                        }),
+                        Err(e) => tracing::error!(
+                            "Failed to serialize patch of execution {}: {}",
+                            execution_id,
+                            e
+                        ),
+                    },
+                    Some(Ok(LogMsg::Finished)) => duration_ms = Some(elapsed_ms()),
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => break,
+                }
+            }
+
+            let duration_ms = duration_ms.unwrap_or_else(elapsed_ms);
+            if let Err(e) =
+                ExecutionProcessRecording::upsert(&db.pool, execution_id, &events, duration_ms)
+                    .await
+            {
+                tracing::error!(
+                    "Failed to save recording of execution {}: {}",
+                    execution_id,
+                    e
+                );
+            }
+        })
+    }
+
     fn spawn_stream_raw_logs_to_db(&self, execution_id: &Uuid) -> JoinHandle<()> {
         let execution_id = *execution_id;
         let msg_stores = self.msg_stores().clone();
@@ -870,6 +1220,18 @@ pub trait ContainerService {
                                 );
                             }
                         }
+                        LogMsg::TokenUsage(usage) => {
+                            if let Err(e) =
+                                ExecutionProcess::update_token_usage(&db.pool, execution_id, usage)
+                                    .await
+                            {
+                                tracing::error!(
+                                    "Failed to update token usage for execution process {}: {}",
+                                    execution_id,
+                                    e
+                                );
+                            }
+                        }
                         LogMsg::Finished => {
                             break;
                         }
@@ -880,6 +1242,145 @@ pub trait ContainerService {
         })
     }
 
+    /// Start the attempt once the limits on attempts running at once leave
+    /// room for it, which may be right away
+    async fn start_or_queue_workspace(
+        &self,
+        workspace: &Workspace,
+        executor_profile_id: ExecutorProfileId,
+    ) -> Result<AttemptStart, ContainerError> {
+        QueuedAttempt::create(&self.db().pool, workspace.id, &executor_profile_id).await?;
+        // Other attempts let in along with this one start in the background,
+        // so the caller only waits for its own
+        let mut own = None;
+        for (attempt, starting) in self.dequeue_admitted_attempts().await? {
+            if attempt.workspace_id == workspace.id {
+                own = Some(starting);
+            } else {
+                self.spawn_queued_attempt_start(attempt, starting);
+            }
+        }
+        if let Some(_starting) = own {
+            self.start_workspace(workspace, executor_profile_id).await?;
+            return Ok(AttemptStart::Started);
+        }
+        // Another caller working off the queue meanwhile may be starting it,
+        // which puts it back in the queue if that fails
+        match QueuedAttempt::find_by_workspace_id(&self.db().pool, workspace.id).await? {
+            Some(queued) => {
+                tracing::info!(
+                    "Queued attempt {} until fewer attempts are running",
+                    workspace.id
+                );
+                Ok(AttemptStart::Queued(queued))
+            }
+            None => Ok(AttemptStart::Started),
+        }
+    }
+
+    /// Start queued attempts, in queue order, while the limits leave room.
+    /// Called whenever a process ends, as that may free a slot.
+    async fn start_queued_attempts(&self) -> Result<(), ContainerError> {
+        for (attempt, starting) in self.dequeue_admitted_attempts().await? {
+            self.spawn_queued_attempt_start(attempt, starting);
+        }
+        Ok(())
+    }
+
+    /// Take the attempts the limits leave room for off the queue, in queue
+    /// order. Each counts as running until its guard is dropped.
+    async fn dequeue_admitted_attempts(
+        &self,
+    ) -> Result<Vec<(QueuedAttempt, StartingAttempt)>, ContainerError> {
+        let pool = &self.db().pool;
+        // Only choosing and dequeuing happen under the lock; the chosen attempts
+        // count as running while they start, so callers meanwhile leave their
+        // slots alone
+        let _admission = attempt_queue::ADMISSION.lock().await;
+        let queue = QueuedAttempt::find_all(pool).await?;
+        if queue.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let config = self.attempt_queue_config().await;
+        let mut project_limits = HashMap::new();
+        for attempt in &queue {
+            if !project_limits.contains_key(&attempt.project_id) {
+                let settings =
+                    ProjectSettings::find_by_project_id(pool, attempt.project_id).await?;
+                project_limits.insert(
+                    attempt.project_id,
+                    settings.concurrency.max_concurrent_attempts,
+                );
+            }
+        }
+        let running = attempt_queue::with_starting(QueuedAttempt::running_counts(pool).await?);
+        let ordered = attempt_queue::in_order(queue, config.order);
+        let admitted = attempt_queue::admit(
+            &ordered,
+            &running,
+            config.max_concurrent_attempts,
+            &project_limits,
+        );
+
+        let mut dequeued = Vec::new();
+        for attempt in ordered
+            .into_iter()
+            .filter(|attempt| admitted.contains(&attempt.workspace_id))
+        {
+            let starting = StartingAttempt::new(&attempt);
+            QueuedAttempt::delete(pool, attempt.workspace_id).await?;
+            dequeued.push((attempt, starting));
+        }
+        Ok(dequeued)
+    }
+
+    /// Run [`ContainerService::start_or_requeue_attempt`] in the background
+    fn spawn_queued_attempt_start(&self, attempt: QueuedAttempt, starting: StartingAttempt);
+
+    /// Start an attempt taken off the queue. An attempt that can't be started
+    /// goes back to its place in the queue rather than being lost: with the
+    /// error when starting it failed, so it isn't retried over and over, or
+    /// as it was when it couldn't be loaded.
+    async fn start_or_requeue_attempt(&self, attempt: QueuedAttempt, _starting: StartingAttempt) {
+        let pool = &self.db().pool;
+        let start_error = match Workspace::find_by_id(pool, attempt.workspace_id).await {
+            Ok(Some(workspace)) => {
+                match self
+                    .start_workspace(&workspace, attempt.executor_profile_id.0.clone())
+                    .await
+                {
+                    Ok(_) => return,
+                    Err(e) => {
+                        tracing::error!(
+                            "Failed to start queued attempt {}: {}",
+                            attempt.workspace_id,
+                            e
+                        );
+                        Some(e.to_string())
+                    }
+                }
+            }
+            // Deleted meanwhile
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to load queued attempt {}: {}",
+                    attempt.workspace_id,
+                    e
+                );
+                None
+            }
+        };
+        if let Err(e) = QueuedAttempt::requeue(pool, &attempt, start_error.as_deref()).await {
+            tracing::error!(
+                "Failed to put attempt {} back in the queue: {}",
+                attempt.workspace_id,
+                e
+            );
+        }
+    }
+
     async fn start_workspace(
         &self,
         workspace: &Workspace,
@@ -907,6 +1408,19 @@ pub trait ContainerService {
             .await?
             .ok_or(SqlxError::RowNotFound)?;
 
+        if let Some(hook) = lifecycle_hooks::run_hooks(
+            &self.db().pool,
+            &workspace,
+            HookEvent::BeforeStart,
+            HookDetails::default(),
+        )
+        .await
+        {
+            return Err(ContainerError::Other(anyhow!(
+                "The project's before-start hook '{hook}' failed"
+            )));
+        }
+
         // Create a session for this workspace
         let session = Session::create(
             &self.db().pool,
@@ -918,8 +1432,6 @@ pub trait ContainerService {
         )
         .await?;
 
-        let prompt = task.to_prompt();
-
         let repos_with_setup: Vec<_> = project_repos
             .iter()
             .filter(|pr| pr.setup_script.is_some())
@@ -927,24 +1439,25 @@ pub trait ContainerService {
 
         let all_parallel = repos_with_setup.iter().all(|pr| pr.parallel_setup_script);
 
-        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
-
-        let working_dir = workspace
-            .agent_working_dir
-            .as_ref()
-            .filter(|dir| !dir.is_empty())
-            .cloned();
-
-        let coding_action = ExecutorAction::new(
-            ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
-                prompt,
-                executor_profile_id: executor_profile_id.clone(),
-                working_dir,
-            }),
-            cleanup_action.map(Box::new),
-        );
+        let coding_action =
+            self.initial_agent_action(&task, &workspace, &project_repos, executor_profile_id);
+
+        // Submodules and LFS objects are in place before any setup script runs
+        let provisioning = match workspace.container_ref.as_deref() {
+            Some(workspace_dir) => {
+                let settings = ProjectSettings::find_by_project_id(&self.db().pool, project.id)
+                    .await?
+                    .git_provisioning;
+                self.provisioning_actions_for_repos(
+                    Path::new(workspace_dir),
+                    &project_repos,
+                    &settings,
+                )
+            }
+            None => None,
+        };
 
-        let execution_process = if all_parallel {
+        let execution_process = if all_parallel && provisioning.is_none() {
             // All parallel: start each setup independently, then start coding agent
             for repo in &repos_with_setup {
                 if let Some(action) = Self::setup_action_for_repo(repo)
@@ -970,6 +1483,10 @@ pub trait ContainerService {
         } else {
             // Any sequential: chain ALL setups → coding agent via next_action
             let main_action = Self::build_sequential_setup_chain(&repos_with_setup, coding_action);
+            let main_action = match provisioning {
+                Some(provisioning) => provisioning.append_action(main_action),
+                None => main_action,
+            };
             self.start_execution(
                 &workspace,
                 &session,
@@ -982,6 +1499,49 @@ pub trait ContainerService {
         Ok(execution_process)
     }
 
+    /// What the task's previous attempt left, for the first agent run of a
+    /// new attempt. Failing to gather it doesn't keep the run from starting.
+    async fn previous_attempt_context(
+        &self,
+        workspace: &Workspace,
+        executor_action: &ExecutorAction,
+        prompts: &PromptSettings,
+    ) -> Result<Option<String>, ContainerError> {
+        let starts_agent =
+            std::iter::successors(Some(executor_action), |action| action.next_action()).any(
+                |action| {
+                    matches!(
+                        action.typ(),
+                        ExecutorActionType::CodingAgentInitialRequest(_)
+                    )
+                },
+            );
+        if !starts_agent || !prompts.wants_previous_attempt() {
+            return Ok(None);
+        }
+        let agent_ran = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            &self.db().pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?
+        .is_some();
+        if agent_ran {
+            return Ok(None);
+        }
+        match PreviousAttempt::find(&self.db().pool, workspace, &prompts.previous_attempt).await {
+            Ok(previous) => Ok(previous.and_then(|previous| previous.render())),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to gather the previous attempt of workspace {}: {}",
+                    workspace.id,
+                    e
+                );
+                Ok(None)
+            }
+        }
+    }
+
     async fn start_execution(
         &self,
         workspace: &Workspace,
@@ -1009,6 +1569,18 @@ pub trait ContainerService {
                 );
             }
         }
+        // The project's prompt additions go on the stored action, so each run
+        // keeps what its agent was given
+        let prompts = ProjectSettings::find_by_project_id(&self.db().pool, task.project_id)
+            .await?
+            .prompts;
+        let previous_attempt = self
+            .previous_attempt_context(workspace, executor_action, &prompts)
+            .await?;
+        let executor_action = &executor_action.clone().with_prompt_additions(&|executor| {
+            prompts.for_executor(executor, previous_attempt.as_deref())
+        });
+
         // Create new execution process record
         // Capture current HEAD per repository as the "before" commit for this execution
         let repositories =
@@ -1151,6 +1723,7 @@ pub trait ContainerService {
                 ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id)
             {
                 executor.normalize_logs(msg_store, &working_dir);
+                self.spawn_record_session(&execution_process.id);
             } else {
                 tracing::error!(
                     "Failed to resolve profile '{:?}' for normalization",
@@ -0,0 +1,233 @@
+//! Plan-only runs of an attempt's agent. The agent works in a throwaway copy
+//! of the workspace, one detached worktree per repository under
+//! `.vk-dry-run/<id>` at the workspace root, so it can explore and even try
+//! its changes without touching the attempt. When it is done, what it changed
+//! is kept as a patch next to its plan and the copy is removed.
+//!
+//! The copy starts from each repository's working tree, uncommitted changes
+//! included; untracked files are left out.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use uuid::Uuid;
+
+use super::{git::GitCli, worktree_manager::WorktreeError};
+
+pub const DRY_RUN_DIR: &str = ".vk-dry-run";
+/// Where each copy remembers what it started from, so commits the agent makes
+/// in it still show in the patch
+const BASE_REF: &str = "refs/worktree/vk-dry-run-base";
+const MAX_PATCH_CHARS: usize = 60_000;
+
+/// The copy of a dry run, relative to the workspace root
+pub fn copy_dir(dry_run_id: &Uuid) -> PathBuf {
+    Path::new(DRY_RUN_DIR).join(dry_run_id.to_string())
+}
+
+/// The directory the agent works in, relative to the workspace root: the
+/// attempt's own working directory within the copy
+pub fn agent_working_dir(dry_run_id: &Uuid, attempt_working_dir: Option<&str>) -> String {
+    let dir = copy_dir(dry_run_id);
+    let dir = match attempt_working_dir.filter(|dir| !dir.is_empty()) {
+        Some(working_dir) => dir.join(working_dir),
+        None => dir,
+    };
+    dir.to_string_lossy().into_owned()
+}
+
+/// Copy each repository's working tree into the dry run's directory
+pub async fn create_copy(
+    workspace_root: &Path,
+    repo_names: &[String],
+    dry_run_id: &Uuid,
+) -> Result<(), WorktreeError> {
+    let copies = workspace_root.join(copy_dir(dry_run_id));
+    for repo_name in repo_names {
+        let repo = workspace_root.join(repo_name);
+        let copy = copies.join(repo_name);
+        let created = async {
+            // A commit of the working tree, without touching it; empty when
+            // there is nothing uncommitted
+            let stash = git(
+                &repo,
+                [
+                    "-c".into(),
+                    "user.name=Vibe Kanban".into(),
+                    "-c".into(),
+                    "user.email=noreply@vibekanban.com".into(),
+                    "stash".into(),
+                    "create".into(),
+                ],
+            )
+            .await?;
+            let base = match stash.trim() {
+                "" => git(&repo, ["rev-parse".into(), "HEAD".into()]).await?,
+                stash => stash.to_string(),
+            };
+            git(
+                &repo,
+                [
+                    "worktree".into(),
+                    "add".into(),
+                    "--detach".into(),
+                    copy.clone().into_os_string(),
+                    base.trim().into(),
+                ],
+            )
+            .await?;
+            git(
+                &copy,
+                ["update-ref".into(), BASE_REF.into(), base.trim().into()],
+            )
+            .await
+        }
+        .await;
+        if let Err(e) = created {
+            remove_copy(workspace_root, repo_names, dry_run_id).await;
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// What the agent changed in the copy, as one patch. Paths are prefixed with
+/// the repository's name when there is more than one.
+pub async fn capture_patch(
+    workspace_root: &Path,
+    repo_names: &[String],
+    dry_run_id: &Uuid,
+) -> Result<String, WorktreeError> {
+    let copies = workspace_root.join(copy_dir(dry_run_id));
+    let mut patch = String::new();
+    for repo_name in repo_names {
+        let copy = copies.join(repo_name);
+        git(&copy, ["add".into(), "-A".into()]).await?;
+        let (src_prefix, dst_prefix) = if repo_names.len() > 1 {
+            (format!("a/{repo_name}/"), format!("b/{repo_name}/"))
+        } else {
+            ("a/".to_string(), "b/".to_string())
+        };
+        patch.push_str(
+            &git(
+                &copy,
+                [
+                    "diff".into(),
+                    "--cached".into(),
+                    "--binary".into(),
+                    format!("--src-prefix={src_prefix}").into(),
+                    format!("--dst-prefix={dst_prefix}").into(),
+                    BASE_REF.into(),
+                ],
+            )
+            .await?,
+        );
+    }
+    Ok(patch)
+}
+
+/// Remove the copy and its worktrees; best effort
+pub async fn remove_copy(workspace_root: &Path, repo_names: &[String], dry_run_id: &Uuid) {
+    let copies = workspace_root.join(copy_dir(dry_run_id));
+    for repo_name in repo_names {
+        let repo = workspace_root.join(repo_name);
+        let copy = copies.join(repo_name);
+        if let Err(e) = git(
+            &repo,
+            [
+                "worktree".into(),
+                "remove".into(),
+                "--force".into(),
+                copy.into_os_string(),
+            ],
+        )
+        .await
+        {
+            tracing::debug!("Failed to remove dry run worktree of {}: {}", repo_name, e);
+            let _ = git(&repo, ["worktree".into(), "prune".into()]).await;
+        }
+    }
+    if let Err(e) = tokio::fs::remove_dir_all(&copies).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("Failed to remove {}: {}", copies.display(), e);
+    }
+    // Only goes once no other dry run is using it
+    let _ = tokio::fs::remove_dir(workspace_root.join(DRY_RUN_DIR)).await;
+}
+
+/// The prompt of the dry run's agent
+pub fn dry_run_prompt(prompt: &str) -> String {
+    format!(
+        "This is a dry run. You are working in a throwaway copy of the workspace and nothing you change here is kept. \
+         Work out how you would do the task below, and make the changes you propose in this copy so they can be reviewed as a diff. \
+         Don't commit, push or change anything outside this directory.\n\
+         End your last message with your plan: the approach, the files involved, and anything the user should decide before the real run.\n\n\
+         {prompt}"
+    )
+}
+
+/// The prompt of the real run started when the dry run is approved
+pub fn approved_prompt(prompt: &str, plan: Option<&str>, patch: Option<&str>) -> String {
+    let mut approved = format!(
+        "{prompt}\n\nA dry run of this task proposed the plan below and it was approved. Carry it out."
+    );
+    if let Some(plan) = plan.map(str::trim).filter(|plan| !plan.is_empty()) {
+        approved.push_str(&format!("\n\nThe plan:\n{plan}"));
+    }
+    if let Some(patch) = patch.filter(|patch| !patch.trim().is_empty()) {
+        let mut patch = patch.to_string();
+        if let Some((end, _)) = patch.char_indices().nth(MAX_PATCH_CHARS) {
+            patch.truncate(end);
+            patch.push_str("\n… the rest of the diff was left out\n");
+        }
+        if !patch.ends_with('\n') {
+            patch.push('\n');
+        }
+        approved.push_str(&format!(
+            "\n\nThe changes it proposed, to apply or redo as you see fit:\n```diff\n{patch}```"
+        ));
+    }
+    approved
+}
+
+async fn git<const N: usize>(path: &Path, args: [OsString; N]) -> Result<String, WorktreeError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || GitCli::new().git(&path, args))
+        .await
+        .map_err(|e| WorktreeError::TaskJoin(e.to_string()))?
+        .map_err(|e| WorktreeError::GitCli(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approved_prompt_carries_plan_and_patch() {
+        let id = Uuid::nil();
+        assert_eq!(
+            agent_working_dir(&id, Some("frontend")),
+            format!(".vk-dry-run/{id}/frontend")
+        );
+        assert_eq!(
+            agent_working_dir(&id, Some("")),
+            format!(".vk-dry-run/{id}")
+        );
+
+        let prompt = approved_prompt(
+            "Fix login",
+            Some("  Check the token expiry.\n"),
+            Some("--- a/auth.rs\n+++ b/auth.rs"),
+        );
+        assert_eq!(
+            prompt,
+            "Fix login\n\nA dry run of this task proposed the plan below and it was approved. Carry it out.\n\n\
+             The plan:\nCheck the token expiry.\n\n\
+             The changes it proposed, to apply or redo as you see fit:\n```diff\n--- a/auth.rs\n+++ b/auth.rs\n```"
+        );
+        assert!(!approved_prompt("Fix login", None, Some(" \n")).contains("```"));
+    }
+}
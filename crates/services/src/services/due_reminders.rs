@@ -0,0 +1,129 @@
+//! Reminders about tasks nearing their due date.
+//!
+//! The container service looks for tasks to remind of every minute and sends
+//! a desktop notification for each, once per deadline. How long before the
+//! deadline that happens is a project setting. A task given a deadline that
+//! has already passed is reminded of right away as overdue.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use db::models::{
+    project_settings::{DueReminderSettings, ProjectSettings},
+    task::Task,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::notification::NotificationService;
+
+/// Remind of every open task whose deadline has come within its project's
+/// reminder window
+pub async fn send_due(
+    pool: &SqlitePool,
+    notifications: &NotificationService,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    let mut project_settings: HashMap<Uuid, DueReminderSettings> = HashMap::new();
+
+    for task in Task::find_awaiting_due_reminder(pool).await? {
+        let Some(due_at) = task.due_at else {
+            continue;
+        };
+        let settings = match project_settings.get(&task.project_id) {
+            Some(settings) => settings,
+            None => {
+                let settings = ProjectSettings::find_by_project_id(pool, task.project_id)
+                    .await?
+                    .due_reminders;
+                project_settings.entry(task.project_id).or_insert(settings)
+            }
+        };
+        if !reminder_due(settings, due_at, now) {
+            continue;
+        }
+
+        let (title, message) = reminder_text(&task.title, due_at, now);
+        notifications.notify(&title, &message).await;
+        Task::mark_due_reminder_sent(pool, task.id).await?;
+    }
+    Ok(())
+}
+
+fn reminder_due(settings: &DueReminderSettings, due_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    settings.enabled && due_at - Duration::hours(settings.hours_before.into()) <= now
+}
+
+/// Title and message of the notification about the task
+fn reminder_text(task_title: &str, due_at: DateTime<Utc>, now: DateTime<Utc>) -> (String, String) {
+    if due_at <= now {
+        (
+            "Task overdue".to_string(),
+            format!("'{task_title}' was due {} ago", describe(now - due_at)),
+        )
+    } else {
+        (
+            "Task due soon".to_string(),
+            format!("'{task_title}' is due in {}", describe(due_at - now)),
+        )
+    }
+}
+
+/// Rough length of the duration, e.g. "3 hours"
+fn describe(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(1);
+    let (count, unit) = if minutes < 60 {
+        (minutes, "minute")
+    } else if minutes < 48 * 60 {
+        (minutes / 60, "hour")
+    } else {
+        (minutes / (24 * 60), "day")
+    };
+    if count == 1 {
+        format!("1 {unit}")
+    } else {
+        format!("{count} {unit}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reminds_within_the_window_before_the_deadline() {
+        let now = Utc::now();
+        let settings = DueReminderSettings {
+            enabled: true,
+            hours_before: 24,
+        };
+        assert!(reminder_due(&settings, now + Duration::hours(23), now));
+        assert!(reminder_due(&settings, now - Duration::hours(1), now));
+        assert!(!reminder_due(&settings, now + Duration::hours(25), now));
+        assert!(!reminder_due(
+            &DueReminderSettings {
+                enabled: false,
+                ..settings
+            },
+            now,
+            now
+        ));
+    }
+
+    #[test]
+    fn describes_how_far_the_deadline_is() {
+        let now = Utc::now();
+        assert_eq!(
+            reminder_text("Ship it", now + Duration::minutes(150), now).1,
+            "'Ship it' is due in 2 hours"
+        );
+        assert_eq!(
+            reminder_text("Ship it", now - Duration::days(3), now),
+            (
+                "Task overdue".to_string(),
+                "'Ship it' was due 3 days ago".to_string()
+            )
+        );
+        assert_eq!(describe(Duration::seconds(20)), "1 minute");
+    }
+}
@@ -0,0 +1,170 @@
+//! How far along an epic is: its tasks by status, and a burn-up chart of the
+//! work in the epic against the work done, day by day.
+//!
+//! The chart counts the epic's current tasks, each from the day it was
+//! created; a task moved into the epic later counts from its creation too.
+//! Cancelled tasks are left out of both lines.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use db::models::{
+    epic::{Epic, EpicTaskTimeline},
+    task::TaskStatus,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+/// Most points a burn-up chart has; longer epics get a point every few days
+const MAX_BURN_UP_POINTS: i64 = 120;
+
+const STATUSES: [TaskStatus; 5] = [
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::InReview,
+    TaskStatus::Done,
+    TaskStatus::Cancelled,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct StatusCount {
+    pub status: TaskStatus,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct BurnUpPoint {
+    pub day: NaiveDate,
+    /// Tasks in the epic by the end of the day
+    pub scope: u32,
+    /// Of those, tasks done by the end of the day
+    pub done: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct EpicProgress {
+    pub epic: Epic,
+    /// Every status, in board order
+    pub task_counts: Vec<StatusCount>,
+    /// Tasks that aren't cancelled
+    pub total: u32,
+    pub done: u32,
+    /// Oldest first, ending today
+    pub burn_up: Vec<BurnUpPoint>,
+}
+
+pub async fn epic_progress(pool: &SqlitePool, epic: Epic) -> Result<EpicProgress, sqlx::Error> {
+    let tasks = Epic::find_task_timelines(pool, epic.id).await?;
+    let task_counts = STATUSES
+        .into_iter()
+        .map(|status| StatusCount {
+            count: tasks.iter().filter(|task| task.status == status).count() as u32,
+            status,
+        })
+        .collect();
+    let total = tasks
+        .iter()
+        .filter(|task| task.status != TaskStatus::Cancelled)
+        .count() as u32;
+    let done = tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Done)
+        .count() as u32;
+    let burn_up = burn_up(&tasks, epic.created_at, Utc::now());
+
+    Ok(EpicProgress {
+        epic,
+        task_counts,
+        total,
+        done,
+        burn_up,
+    })
+}
+
+/// Daily points from the day the epic or its first task was created until
+/// today
+fn burn_up(
+    tasks: &[EpicTaskTimeline],
+    epic_created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<BurnUpPoint> {
+    let first_day = tasks
+        .iter()
+        .map(|task| task.created_at)
+        .chain([epic_created_at])
+        .min()
+        .unwrap_or(epic_created_at)
+        .date_naive();
+    let today = now.date_naive();
+    let days = (today - first_day).num_days().max(0);
+    let step = ((days + MAX_BURN_UP_POINTS - 1) / MAX_BURN_UP_POINTS).max(1);
+
+    let counted: Vec<&EpicTaskTimeline> = tasks
+        .iter()
+        .filter(|task| task.status != TaskStatus::Cancelled)
+        .collect();
+    let mut days_to_chart: Vec<NaiveDate> = (0..=days)
+        .step_by(step as usize)
+        .map(|offset| today - Duration::days(offset))
+        .collect();
+    days_to_chart.reverse();
+
+    days_to_chart
+        .into_iter()
+        .map(|day| BurnUpPoint {
+            day,
+            scope: counted
+                .iter()
+                .filter(|task| task.created_at.date_naive() <= day)
+                .count() as u32,
+            done: counted
+                .iter()
+                .filter(|task| {
+                    task.done_at
+                        .is_some_and(|done_at| done_at.date_naive() <= day)
+                })
+                .count() as u32,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(
+        now: DateTime<Utc>,
+        status: TaskStatus,
+        created_days_ago: i64,
+        done_days_ago: Option<i64>,
+    ) -> EpicTaskTimeline {
+        EpicTaskTimeline {
+            status,
+            created_at: now - Duration::days(created_days_ago),
+            done_at: done_days_ago.map(|days| now - Duration::days(days)),
+        }
+    }
+
+    #[test]
+    fn burn_up_counts_scope_and_done_work_per_day() {
+        let now = Utc::now();
+        let tasks = vec![
+            task(now, TaskStatus::Done, 3, Some(1)),
+            task(now, TaskStatus::InProgress, 2, None),
+            task(now, TaskStatus::Cancelled, 3, None),
+        ];
+
+        let points = burn_up(&tasks, now - Duration::days(2), now);
+
+        let lines: Vec<(u32, u32)> = points.iter().map(|p| (p.scope, p.done)).collect();
+        assert_eq!(lines, vec![(1, 0), (2, 0), (2, 1), (2, 1)]);
+        assert_eq!(points.last().map(|p| p.day), Some(now.date_naive()));
+    }
+
+    #[test]
+    fn long_epics_get_fewer_points() {
+        let now = Utc::now();
+        let points = burn_up(&[], now - Duration::days(600), now);
+        assert!(points.len() as i64 <= MAX_BURN_UP_POINTS + 1);
+        assert_eq!(points.last().map(|p| p.day), Some(now.date_naive()));
+    }
+}
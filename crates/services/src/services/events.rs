@@ -3,8 +3,8 @@ use std::{str::FromStr, sync::Arc};
 use db::{
     DBService,
     models::{
-        execution_process::ExecutionProcess, project::Project, scratch::Scratch, task::Task,
-        workspace::Workspace,
+        activity_event::ActivityEvent, execution_process::ExecutionProcess, project::Project,
+        scratch::Scratch, task::Task, task_dependency::TaskDependency, workspace::Workspace,
     },
 };
 use serde_json::json;
@@ -21,7 +21,8 @@ mod streams;
 pub mod types;
 
 pub use patches::{
-    execution_process_patch, project_patch, scratch_patch, task_patch, workspace_patch,
+    activity_patch, execution_process_patch, project_patch, scratch_patch, task_patch,
+    workspace_patch,
 };
 pub use types::{EventError, EventPatch, EventPatchInner, HookTables, RecordTypes};
 
@@ -56,12 +57,20 @@ impl EventService {
                 .find(|task_with_status| task_with_status.id == task_id)
             {
                 msg_store.push_patch(task_patch::replace(&task_with_status));
+            } else if task.archived_at.is_some() {
+                msg_store.push_patch(task_patch::remove(task_id));
             }
         }
 
         Ok(())
     }
 
+    /// Push fresh board data for a task whose state changed outside the tables
+    /// the hooks watch, like its dependencies
+    pub async fn push_task_update(&self, task_id: Uuid) -> Result<(), SqlxError> {
+        Self::push_task_update_for_task(&self.db.pool, self.msg_store.clone(), task_id).await
+    }
+
     async fn push_task_update_for_session(
         pool: &SqlitePool,
         msg_store: Arc<MsgStore>,
@@ -172,6 +181,22 @@ impl EventService {
                                     // Deletions handled in preupdate hook for reliable data capture
                                     return;
                                 }
+                                (HookTables::ActivityEvents, SqliteOperation::Insert) => {
+                                    match ActivityEvent::find_by_rowid(&db.pool, rowid).await {
+                                        Ok(Some(event)) => RecordTypes::ActivityEvent(event),
+                                        Ok(None) => return,
+                                        Err(e) => {
+                                            tracing::error!(
+                                                "Failed to fetch activity event: {:?}",
+                                                e
+                                            );
+                                            return;
+                                        }
+                                    }
+                                }
+                                // Events are written once and only go away with their
+                                // project or task
+                                (HookTables::ActivityEvents, _) => return,
                                 (HookTables::Tasks, _) => {
                                     match Task::find_by_rowid(&db.pool, rowid).await {
                                         Ok(Some(task)) => RecordTypes::Task(task),
@@ -278,6 +303,35 @@ impl EventService {
                                             _ => task_patch::replace(&task_with_status), // fallback
                                         };
                                         msg_store_for_hook.push_patch(patch);
+
+                                        // A status change can block or unblock tasks
+                                        // waiting for this one and moves the parent's
+                                        // subtask progress
+                                        let related: Vec<Uuid> =
+                                            TaskDependency::find_dependents(&db.pool, task.id)
+                                                .await
+                                                .unwrap_or_default()
+                                                .into_iter()
+                                                .map(|dependent| dependent.id)
+                                                .chain(task.parent_task_id)
+                                                .collect();
+                                        for related_id in related {
+                                            if let Err(err) = Self::push_task_update_for_task(
+                                                &db.pool,
+                                                msg_store_for_hook.clone(),
+                                                related_id,
+                                            )
+                                            .await
+                                            {
+                                                tracing::error!(
+                                                    "Failed to update related task: {:?}",
+                                                    err
+                                                );
+                                            }
+                                        }
+                                        return;
+                                    } else if task.archived_at.is_some() {
+                                        msg_store_for_hook.push_patch(task_patch::remove(task.id));
                                         return;
                                     }
                                 }
@@ -298,6 +352,10 @@ impl EventService {
                                     msg_store_for_hook.push_patch(patch);
                                     return;
                                 }
+                                RecordTypes::ActivityEvent(event) => {
+                                    msg_store_for_hook.push_patch(activity_patch::add(event));
+                                    return;
+                                }
                                 RecordTypes::Scratch(scratch) => {
                                     let patch = match hook.operation {
                                         SqliteOperation::Insert => scratch_patch::add(scratch),
@@ -1,6 +1,6 @@
 use db::models::{
-    execution_process::ExecutionProcess, project::Project, scratch::Scratch,
-    task::TaskWithAttemptStatus, workspace::Workspace,
+    activity_event::ActivityEvent, execution_process::ExecutionProcess, project::Project,
+    scratch::Scratch, task::TaskWithAttemptStatus, workspace::Workspace,
 };
 use json_patch::{AddOperation, Patch, PatchOperation, RemoveOperation, ReplaceOperation};
 use uuid::Uuid;
@@ -217,3 +217,27 @@ pub mod scratch_patch {
         })])
     }
 }
+
+/// Helper functions for creating activity feed patches. Events are only ever
+/// added; the feed keeps them until their project or task is deleted.
+pub mod activity_patch {
+    use super::*;
+
+    fn activity_path(event_id: Uuid) -> String {
+        format!(
+            "/activity/{}",
+            escape_pointer_segment(&event_id.to_string())
+        )
+    }
+
+    /// Create patch for a new activity event
+    pub fn add(event: &ActivityEvent) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: activity_path(event.id)
+                .try_into()
+                .expect("Activity path should be valid"),
+            value: serde_json::to_value(event)
+                .expect("Activity event serialization should not fail"),
+        })])
+    }
+}
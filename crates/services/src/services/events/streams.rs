@@ -1,4 +1,5 @@
 use db::models::{
+    activity_event::ActivityEvent,
     execution_process::ExecutionProcess,
     project::Project,
     scratch::Scratch,
@@ -6,6 +7,7 @@ use db::models::{
     task::{Task, TaskWithAttemptStatus},
 };
 use futures::StreamExt;
+use json_patch::Patch;
 use serde_json::json;
 use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
 use utils::log_msg::LogMsg;
@@ -13,7 +15,7 @@ use uuid::Uuid;
 
 use super::{
     EventService,
-    patches::execution_process_patch,
+    patches::{execution_process_patch, task_patch},
     types::{EventError, EventPatch, RecordTypes},
 };
 
@@ -22,6 +24,7 @@ impl EventService {
     pub async fn stream_tasks_raw(
         &self,
         project_id: Uuid,
+        label_ids: Vec<Uuid>,
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, EventError>
     {
         // Get initial snapshot of tasks
@@ -30,6 +33,7 @@ impl EventService {
         // Convert task array to object keyed by task ID
         let tasks_map: serde_json::Map<String, serde_json::Value> = tasks
             .into_iter()
+            .filter(|task| task.has_any_label(&label_ids))
             .map(|task| (task.id.to_string(), serde_json::to_value(task).unwrap()))
             .collect();
 
@@ -49,6 +53,7 @@ impl EventService {
         let filtered_stream =
             BroadcastStream::new(self.msg_store.get_receiver()).filter_map(move |msg_result| {
                 let db_pool = db_pool.clone();
+                let label_ids = label_ids.clone();
                 async move {
                     match msg_result {
                         Ok(LogMsg::JsonPatch(patch)) => {
@@ -65,7 +70,9 @@ impl EventService {
                                                 )
                                                 && task.project_id == project_id
                                             {
-                                                return Some(Ok(LogMsg::JsonPatch(patch)));
+                                                return Some(Ok(LogMsg::JsonPatch(
+                                                    filter_by_labels(patch, &task, &label_ids),
+                                                )));
                                             }
                                         }
                                         json_patch::PatchOperation::Replace(op) => {
@@ -76,7 +83,9 @@ impl EventService {
                                                 )
                                                 && task.project_id == project_id
                                             {
-                                                return Some(Ok(LogMsg::JsonPatch(patch)));
+                                                return Some(Ok(LogMsg::JsonPatch(
+                                                    filter_by_labels(patch, &task, &label_ids),
+                                                )));
                                             }
                                         }
                                         json_patch::PatchOperation::Remove(_) => {
@@ -147,6 +156,7 @@ impl EventService {
                                     // Rebuild the full snapshot like initial_msg
                                     let tasks_map: serde_json::Map<String, serde_json::Value> = tasks
                                         .into_iter()
+                                        .filter(|task| task.has_any_label(&label_ids))
                                         .map(|task| (task.id.to_string(), serde_json::to_value(task).unwrap()))
                                         .collect();
 
@@ -259,6 +269,39 @@ impl EventService {
         Ok(combined_stream)
     }
 
+    /// Stream the project's activity events as they are recorded. There is no
+    /// snapshot; earlier events are read a page at a time from the feed.
+    pub fn stream_activity_raw(
+        &self,
+        project_id: Uuid,
+    ) -> futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>> {
+        BroadcastStream::new(self.msg_store.get_receiver())
+            .filter_map(move |msg_result| async move {
+                match msg_result {
+                    Ok(LogMsg::JsonPatch(patch)) => {
+                        let patch_op = patch.0.first()?;
+                        if !patch_op.path().starts_with("/activity/") {
+                            return None;
+                        }
+                        let json_patch::PatchOperation::Add(op) = patch_op else {
+                            return None;
+                        };
+                        serde_json::from_value::<ActivityEvent>(op.value.clone())
+                            .ok()
+                            .filter(|event| event.project_id == project_id)
+                            .map(|_| Ok(LogMsg::JsonPatch(patch)))
+                    }
+                    Ok(other) => Some(Ok(other)), // Pass through non-patch messages
+                    Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                        // Missed events are still in the feed for the client to page in
+                        tracing::warn!(skipped = skipped, "activity stream lagged");
+                        None
+                    }
+                }
+            })
+            .boxed()
+    }
+
     /// Stream execution processes for a specific workspace with initial snapshot (raw LogMsg format for WebSocket)
     pub async fn stream_execution_processes_for_workspace_raw(
         &self,
@@ -560,3 +603,12 @@ impl EventService {
         Ok(combined_stream)
     }
 }
+
+/// A task that no longer carries any of the filtered labels leaves the board
+fn filter_by_labels(patch: Patch, task: &TaskWithAttemptStatus, label_ids: &[Uuid]) -> Patch {
+    if task.has_any_label(label_ids) {
+        patch
+    } else {
+        task_patch::remove(task.id)
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+use futures::{Stream, StreamExt, future};
+use notify::event::{EventKind, ModifyKind};
+use notify_debouncer_full::DebouncedEvent;
+use serde::Serialize;
+use ts_rs::TS;
+
+use super::filesystem_watcher::{self, FilesystemWatcherError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+/// A single change below a watched workspace root
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct FileChangeEvent {
+    pub kind: FileChangeKind,
+    /// Path relative to the workspace root, using forward slashes
+    pub path: String,
+}
+
+/// Watch `root` and yield batches of debounced file changes. Gitignored paths and
+/// `.git`/`node_modules` are filtered out by the underlying watcher. The watcher
+/// stops when the returned stream is dropped.
+pub fn watch_file_changes(
+    root: PathBuf,
+) -> Result<impl Stream<Item = Vec<FileChangeEvent>> + Send + 'static, FilesystemWatcherError> {
+    let (debouncer, watcher_rx, canonical_root) = filesystem_watcher::async_watcher(root)?;
+
+    Ok(watcher_rx.filter_map(move |result| {
+        // Keep the debouncer alive for as long as the stream is polled
+        let _debouncer = &debouncer;
+        let batch = match result {
+            Ok(events) => to_file_changes(&events, &canonical_root),
+            Err(errors) => {
+                for error in errors {
+                    tracing::warn!("File watcher error: {}", error);
+                }
+                Vec::new()
+            }
+        };
+        future::ready((!batch.is_empty()).then_some(batch))
+    }))
+}
+
+fn to_file_changes(events: &[DebouncedEvent], root: &Path) -> Vec<FileChangeEvent> {
+    let mut changes: Vec<FileChangeEvent> = Vec::new();
+    for event in events {
+        let kind = match event.kind {
+            EventKind::Create(_) => FileChangeKind::Created,
+            EventKind::Modify(ModifyKind::Name(_)) => FileChangeKind::Renamed,
+            EventKind::Modify(_) => FileChangeKind::Modified,
+            EventKind::Remove(_) => FileChangeKind::Removed,
+            _ => continue,
+        };
+        for path in &event.paths {
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let path = relative.to_string_lossy().replace('\\', "/");
+            if path.is_empty()
+                || changes
+                    .iter()
+                    .any(|change| change.kind == kind && change.path == path)
+            {
+                continue;
+            }
+            changes.push(FileChangeEvent { kind, path });
+        }
+    }
+    changes
+}
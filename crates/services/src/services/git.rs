@@ -1,6 +1,7 @@
 use std::{collections::HashMap, path::Path};
 
 use chrono::{DateTime, Utc};
+use db::models::merge::MergeStrategy;
 use git2::{
     BranchType, Delta, DiffFindOptions, DiffOptions, Error as GitError, Reference, Remote,
     Repository, Sort,
@@ -13,9 +14,12 @@ use utils::diff::{Diff, DiffChangeKind, FileDiffDetails, compute_line_change_cou
 mod cli;
 
 use cli::{ChangeType, StatusDiffEntry, StatusDiffOptions};
-pub use cli::{GitCli, GitCliError};
+pub use cli::{GitCli, GitCliError, WorktreeEntry};
 
-use super::file_ranker::FileStat;
+use super::{
+    config::{CommitSigningConfig, CommitSigningMode},
+    file_ranker::FileStat,
+};
 
 #[derive(Debug, Error)]
 pub enum GitServiceError {
@@ -56,6 +60,38 @@ pub enum ConflictOp {
     Revert,
 }
 
+/// Outcome of checking a commit's signature, as git's `%G?` reports it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    Good,
+    /// Good, by a key that isn't trusted
+    Untrusted,
+    /// Good, but the signature or its key expired
+    Expired,
+    /// Good, by a revoked key
+    Revoked,
+    Bad,
+    /// Signed, but it couldn't be checked, e.g. for a missing key
+    Unverifiable,
+    Unsigned,
+}
+
+impl SignatureStatus {
+    pub fn from_code(code: &str) -> Self {
+        match code.trim() {
+            "G" => Self::Good,
+            "U" => Self::Untrusted,
+            "X" | "Y" => Self::Expired,
+            "R" => Self::Revoked,
+            "B" => Self::Bad,
+            "E" => Self::Unverifiable,
+            _ => Self::Unsigned,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, TS)]
 pub struct GitBranch {
     pub name: String,
@@ -200,6 +236,16 @@ impl GitService {
         }
     }
 
+    /// URL of the repository's default remote
+    pub fn get_default_remote_url(&self, repo_path: &Path) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let remote = repo.find_remote(&self.default_remote_name(&repo))?;
+        remote
+            .url()
+            .map(str::to_string)
+            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))
+    }
+
     /// Initialize a new git repository with a main branch and initial commit
     pub fn initialize_repo_with_main_branch(
         &self,
@@ -270,6 +316,15 @@ impl GitService {
     }
 
     pub fn commit(&self, path: &Path, message: &str) -> Result<bool, GitServiceError> {
+        self.commit_with_signing(path, message, &CommitSigningConfig::default())
+    }
+
+    pub fn commit_with_signing(
+        &self,
+        path: &Path,
+        message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<bool, GitServiceError> {
         // Use Git CLI to respect sparse-checkout semantics for staging and commit
         let git = GitCli::new();
         let has_changes = git
@@ -284,7 +339,7 @@ impl GitService {
             .map_err(|e| GitServiceError::InvalidRepository(format!("git add failed: {e}")))?;
         // Only ensure identity once we know we're about to commit
         self.ensure_cli_commit_identity(path)?;
-        git.commit(path, message)
+        git.commit(path, message, signing)
             .map_err(|e| GitServiceError::InvalidRepository(format!("git commit failed: {e}")))?;
         Ok(true)
     }
@@ -788,6 +843,34 @@ impl GitService {
         task_branch_name: &str,
         base_branch_name: &str,
         commit_message: &str,
+    ) -> Result<String, GitServiceError> {
+        self.merge_changes_with_strategy(
+            base_worktree_path,
+            task_worktree_path,
+            task_branch_name,
+            base_branch_name,
+            commit_message,
+            MergeStrategy::Squash,
+            &CommitSigningConfig::default(),
+        )
+    }
+
+    /// Merge changes from a task branch into the base branch with the given
+    /// strategy, signing the commit it makes as configured. Returns the base
+    /// branch's new tip.
+    ///
+    /// The task branch has to be up to date with the base branch, so rebasing
+    /// it leaves its commits as they are and rebase-and-merge fast-forwards.
+    #[allow(clippy::too_many_arguments)]
+    pub fn merge_changes_with_strategy(
+        &self,
+        base_worktree_path: &Path,
+        task_worktree_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+        commit_message: &str,
+        strategy: MergeStrategy,
+        signing: &CommitSigningConfig,
     ) -> Result<String, GitServiceError> {
         // Open the repositories
         let task_repo = self.open_repo(task_worktree_path)?;
@@ -825,16 +908,31 @@ impl GitService {
 
                 // Use CLI merge in base context
                 self.ensure_cli_commit_identity(&base_checkout_path)?;
-                let sha = git_cli
-                    .merge_squash_commit(
+                let sha = match strategy {
+                    MergeStrategy::Squash => git_cli.merge_squash_commit(
                         &base_checkout_path,
                         base_branch_name,
                         task_branch_name,
                         commit_message,
-                    )
-                    .map_err(|e| {
-                        GitServiceError::InvalidRepository(format!("CLI merge failed: {e}"))
-                    })?;
+                        signing,
+                    ),
+                    MergeStrategy::MergeCommit => git_cli.merge_no_ff_commit(
+                        &base_checkout_path,
+                        base_branch_name,
+                        task_branch_name,
+                        commit_message,
+                        signing,
+                    ),
+                    MergeStrategy::Rebase | MergeStrategy::FastForward => git_cli
+                        .merge_fast_forward(
+                            &base_checkout_path,
+                            base_branch_name,
+                            task_branch_name,
+                        ),
+                }
+                .map_err(|e| {
+                    GitServiceError::InvalidRepository(format!("CLI merge failed: {e}"))
+                })?;
 
                 // Update task branch ref for continuity
                 let task_refname = format!("refs/heads/{task_branch_name}");
@@ -855,28 +953,45 @@ impl GitService {
                 let base_commit = base_branch.get().peel_to_commit()?;
                 let task_commit = task_branch.get().peel_to_commit()?;
 
-                // Create the squash commit in-memory (no checkout) and update the base branch ref
-                let signature = self.signature_with_fallback(&task_repo)?;
-                let squash_commit_id = self.perform_squash_merge(
-                    &task_repo,
-                    &base_commit,
-                    &task_commit,
-                    &signature,
-                    commit_message,
-                    base_branch_name,
-                )?;
+                let merged_id = if strategy.makes_commit() {
+                    // Create the commit in-memory (no checkout) and update the base branch ref
+                    let signature = self.signature_with_fallback(&task_repo)?;
+                    let commit_signing = if self.should_sign(&task_repo, signing) {
+                        self.ensure_cli_commit_identity(task_worktree_path)?;
+                        Some((task_worktree_path, signing))
+                    } else {
+                        None
+                    };
+                    self.perform_merge(
+                        &task_repo,
+                        &base_commit,
+                        &task_commit,
+                        &signature,
+                        commit_message,
+                        base_branch_name,
+                        strategy == MergeStrategy::Squash,
+                        commit_signing,
+                    )?
+                } else {
+                    self.perform_fast_forward(
+                        &task_repo,
+                        &base_commit,
+                        &task_commit,
+                        base_branch_name,
+                    )?
+                };
 
-                // Update the task branch to the new squash commit so follow-up
+                // Update the task branch to the merged commit so follow-up
                 // work can continue from the merged state without conflicts.
                 let task_refname = format!("refs/heads/{task_branch_name}");
                 base_repo.reference(
                     &task_refname,
-                    squash_commit_id,
+                    merged_id,
                     true,
-                    "Reset task branch after squash merge",
+                    "Reset task branch after merge",
                 )?;
 
-                Ok(squash_commit_id.to_string())
+                Ok(merged_id.to_string())
             }
         }
     }
@@ -1033,6 +1148,46 @@ impl GitService {
         Ok(HeadInfo { branch, oid })
     }
 
+    /// Commit a revision such as `ORIG_HEAD` resolves to in the worktree
+    pub fn resolve_commit(
+        &self,
+        worktree_path: &Path,
+        rev: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+        Ok(commit.id().to_string())
+    }
+
+    /// Signature check of the worktree's HEAD commit
+    pub fn get_head_signature(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<SignatureStatus, GitServiceError> {
+        let statuses = GitCli::new()
+            .signature_statuses(worktree_path, "-1")
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git log failed: {e}")))?;
+        Ok(statuses
+            .into_iter()
+            .next()
+            .unwrap_or(SignatureStatus::Unsigned))
+    }
+
+    /// How many of the worktree's commits not on the base branch are unsigned
+    pub fn count_unsigned_commits(
+        &self,
+        worktree_path: &Path,
+        base_branch: &str,
+    ) -> Result<usize, GitServiceError> {
+        let statuses = GitCli::new()
+            .signature_statuses(worktree_path, &format!("{base_branch}..HEAD"))
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git log failed: {e}")))?;
+        Ok(statuses
+            .into_iter()
+            .filter(|status| *status == SignatureStatus::Unsigned)
+            .count())
+    }
+
     pub fn get_current_branch(&self, repo_path: &Path) -> Result<String, git2::Error> {
         // Thin wrapper for backward compatibility
         match self.get_head_info(repo_path) {
@@ -1174,6 +1329,31 @@ impl GitService {
         Ok(())
     }
 
+    /// Add a worktree for an existing branch with only the paths matching
+    /// `patterns` checked out
+    pub fn add_sparse_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+        patterns: &[String],
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .worktree_add_sparse(repo_path, worktree_path, branch, patterns)
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))
+    }
+
+    /// Narrow or widen the worktree's checkout to the paths matching `patterns`
+    pub fn set_sparse_checkout(
+        &self,
+        worktree_path: &Path,
+        patterns: &[String],
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .sparse_checkout_set(worktree_path, patterns)
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))
+    }
+
     /// Remove a worktree
     pub fn remove_worktree(
         &self,
@@ -1207,6 +1387,30 @@ impl GitService {
         Ok(())
     }
 
+    /// The repository's worktrees, its own checkout first
+    pub fn list_worktrees(&self, repo_path: &Path) -> Result<Vec<WorktreeEntry>, GitServiceError> {
+        GitCli::new().list_worktrees(repo_path).map_err(|e| {
+            GitServiceError::InvalidRepository(format!("git worktree list failed: {e}"))
+        })
+    }
+
+    pub fn repair_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        GitCli::new()
+            .worktree_repair(repo_path, worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))
+    }
+
+    /// Whether the worktree has staged, unstaged or untracked changes
+    pub fn has_uncommitted_changes(&self, worktree_path: &Path) -> Result<bool, GitServiceError> {
+        GitCli::new()
+            .has_changes(worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git status failed: {e}")))
+    }
+
     pub fn get_all_branches(&self, repo_path: &Path) -> Result<Vec<GitBranch>, git2::Error> {
         let repo = Repository::open(repo_path)?;
         let current_branch = self.get_current_branch(repo_path).unwrap_or_default();
@@ -1271,8 +1475,24 @@ impl GitService {
         Ok(branches)
     }
 
-    /// Perform a squash merge of task branch into base branch, but fail on conflicts
-    fn perform_squash_merge(
+    /// Whether commits made through libgit2, which can't sign them itself,
+    /// have to be made through the CLI to be signed
+    fn should_sign(&self, repo: &Repository, signing: &CommitSigningConfig) -> bool {
+        match signing.mode {
+            CommitSigningMode::Always => true,
+            CommitSigningMode::Never => false,
+            CommitSigningMode::GitConfig => repo
+                .config()
+                .and_then(|cfg| cfg.get_bool("commit.gpgsign"))
+                .unwrap_or(false),
+        }
+    }
+
+    /// Merge task branch into base branch in memory, with a squash commit or a
+    /// merge commit, but fail on conflicts. The commit is signed through the
+    /// CLI of the given worktree if signing is set.
+    #[allow(clippy::too_many_arguments)]
+    fn perform_merge(
         &self,
         repo: &Repository,
         base_commit: &git2::Commit,
@@ -1280,6 +1500,8 @@ impl GitService {
         signature: &git2::Signature,
         commit_message: &str,
         base_branch_name: &str,
+        squash: bool,
+        signing: Option<(&Path, &CommitSigningConfig)>,
     ) -> Result<git2::Oid, GitServiceError> {
         // In-memory merge to detect conflicts without touching the working tree
         let mut merge_opts = git2::MergeOptions::new();
@@ -1299,21 +1521,400 @@ impl GitService {
         let tree_id = index.write_tree_to(repo)?;
         let tree = repo.find_tree(tree_id)?;
 
-        // Create a squash commit: use merged tree with base_commit as sole parent
-        let squash_commit_id = repo.commit(
-            None,           // Don't update any reference yet
-            signature,      // Author
-            signature,      // Committer
-            commit_message, // Custom message
-            &tree,          // Merged tree content
-            &[base_commit], // Single parent: base branch commit
-        )?;
+        // A squash commit has the base branch commit as its sole parent
+        let parents = if squash {
+            vec![base_commit]
+        } else {
+            vec![base_commit, task_commit]
+        };
+        let merge_commit_id = match signing {
+            Some((worktree_path, signing)) => {
+                let parent_ids: Vec<String> = parents.iter().map(|c| c.id().to_string()).collect();
+                let parent_ids: Vec<&str> = parent_ids.iter().map(String::as_str).collect();
+                let sha = GitCli::new()
+                    .commit_tree_signed(
+                        worktree_path,
+                        &tree_id.to_string(),
+                        &parent_ids,
+                        commit_message,
+                        signing,
+                    )
+                    .map_err(|e| {
+                        GitServiceError::InvalidRepository(format!("git commit-tree failed: {e}"))
+                    })?;
+                git2::Oid::from_str(&sha)?
+            }
+            None => repo.commit(
+                None,           // Don't update any reference yet
+                signature,      // Author
+                signature,      // Committer
+                commit_message, // Custom message
+                &tree,          // Merged tree content
+                &parents,
+            )?,
+        };
 
         // Update the base branch reference to point to the new commit
         let refname = format!("refs/heads/{base_branch_name}");
-        repo.reference(&refname, squash_commit_id, true, "Squash merge")?;
+        let log_message = if squash { "Squash merge" } else { "Merge" };
+        repo.reference(&refname, merge_commit_id, true, log_message)?;
+
+        Ok(merge_commit_id)
+    }
+
+    /// Move the base branch to the task branch's tip, failing unless the base
+    /// branch's tip is one of its ancestors
+    fn perform_fast_forward(
+        &self,
+        repo: &Repository,
+        base_commit: &git2::Commit,
+        task_commit: &git2::Commit,
+        base_branch_name: &str,
+    ) -> Result<git2::Oid, GitServiceError> {
+        if base_commit.id() != task_commit.id()
+            && !repo.graph_descendant_of(task_commit.id(), base_commit.id())?
+        {
+            return Err(GitServiceError::BranchesDiverged(format!(
+                "Cannot fast-forward '{base_branch_name}': it isn't an ancestor of the task branch"
+            )));
+        }
+        let refname = format!("refs/heads/{base_branch_name}");
+        repo.reference(&refname, task_commit.id(), true, "Fast-forward merge")?;
+        Ok(task_commit.id())
+    }
+
+    /// Move a local branch forward to a descendant of its tip, through the
+    /// CLI of the worktree it's checked out in if there is one
+    pub fn fast_forward_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        sha: &str,
+    ) -> Result<(), GitServiceError> {
+        if let Some(checkout_path) = self.find_checkout_path_for_branch(repo_path, branch_name)? {
+            GitCli::new()
+                .merge_fast_forward(&checkout_path, branch_name, sha)
+                .map_err(|e| {
+                    GitServiceError::InvalidRepository(format!("git merge --ff-only failed: {e}"))
+                })?;
+            return Ok(());
+        }
+        let repo = self.open_repo(repo_path)?;
+        let branch = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| GitServiceError::BranchNotFound(branch_name.to_string()))?;
+        let tip = branch.get().peel_to_commit()?;
+        let commit = repo.find_commit(git2::Oid::from_str(sha)?)?;
+        self.perform_fast_forward(&repo, &tip, &commit, branch_name)?;
+        Ok(())
+    }
+
+    /// Commits reachable from `to` but not from `from`, oldest first, without
+    /// merge commits
+    pub fn list_commits(
+        &self,
+        repo_path: &Path,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+        revwalk.push(repo.revparse_single(to)?.peel_to_commit()?.id())?;
+        revwalk.hide(repo.revparse_single(from)?.peel_to_commit()?.id())?;
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            let oid = oid?;
+            if repo.find_commit(oid)?.parent_count() <= 1 {
+                commits.push(oid.to_string());
+            }
+        }
+        Ok(commits)
+    }
+
+    /// The parents of a commit, first parent first
+    pub fn commit_parents(
+        &self,
+        repo_path: &Path,
+        sha: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let commit = repo.find_commit(git2::Oid::from_str(sha)?)?;
+        Ok(commit.parent_ids().map(|oid| oid.to_string()).collect())
+    }
+
+    /// Cherry-pick the commits onto a local branch in a new worktree detached
+    /// at its tip, which is left mid-cherry-pick if they conflict. The branch
+    /// itself isn't moved.
+    pub fn start_cherry_pick(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        target_branch: &str,
+        commits: &[String],
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tip = repo
+            .find_branch(target_branch, BranchType::Local)
+            .map_err(|_| GitServiceError::BranchNotFound(target_branch.to_string()))?
+            .get()
+            .peel_to_commit()?
+            .id()
+            .to_string();
+
+        if let Some(parent) = worktree_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let git = GitCli::new();
+        git.worktree_add_detached(repo_path, worktree_path, &tip)
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git worktree add failed: {e}"))
+            })?;
+        self.ensure_cli_commit_identity(worktree_path)?;
+
+        match git.cherry_pick(worktree_path, commits) {
+            Ok(()) => Ok(()),
+            Err(GitCliError::CommandFailed(_))
+                if !self.get_conflicted_files(worktree_path)?.is_empty() =>
+            {
+                Err(GitServiceError::MergeConflicts(format!(
+                    "Cherry-picking onto '{target_branch}' stopped on conflicts. Resolve them and then continue or abort."
+                )))
+            }
+            Err(e) => Err(GitServiceError::InvalidRepository(format!(
+                "Cherry-pick failed: {e}"
+            ))),
+        }
+    }
+
+    /// How many commits a branch is behind its base branch, and the files that
+    /// would conflict merging the two. The merge is done in memory, leaving
+    /// every worktree as it is.
+    pub fn check_against_base(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<(usize, Vec<String>), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_commit = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?;
+        let base_commit = Self::find_branch(&repo, base_branch_name)?
+            .get()
+            .peel_to_commit()?;
+        let (_, behind) = repo.graph_ahead_behind(branch_commit.id(), base_commit.id())?;
+        if behind == 0 {
+            return Ok((0, Vec::new()));
+        }
+
+        let mut merge_opts = git2::MergeOptions::new();
+        merge_opts.find_renames(true);
+        let index = repo.merge_commits(&branch_commit, &base_commit, Some(&merge_opts))?;
+        let mut conflicted = Vec::new();
+        if index.has_conflicts() {
+            for conflict in index.conflicts()? {
+                let conflict = conflict?;
+                if let Some(entry) = conflict
+                    .our
+                    .as_ref()
+                    .or(conflict.their.as_ref())
+                    .or(conflict.ancestor.as_ref())
+                {
+                    conflicted.push(String::from_utf8_lossy(&entry.path).to_string());
+                }
+            }
+        }
+        Ok((behind, conflicted))
+    }
+
+    /// Merge the base branch into the branch checked out in the worktree,
+    /// aborting the merge if it conflicts. Returns the merge commit.
+    pub fn merge_base_into_branch(
+        &self,
+        worktree_path: &Path,
+        base_branch_name: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<String, GitServiceError> {
+        let worktree_repo = Repository::open(worktree_path)?;
+        self.check_worktree_clean(&worktree_repo)?;
+        if self.detect_conflict_op(worktree_path)?.is_some() {
+            return Err(GitServiceError::InvalidRepository(
+                "Another operation is in progress in the worktree".to_string(),
+            ));
+        }
+        let branch_name = self.get_head_info(worktree_path)?.branch;
 
-        Ok(squash_commit_id)
+        self.ensure_cli_commit_identity(worktree_path)?;
+        let git = GitCli::new();
+        match git.merge_into_head(
+            worktree_path,
+            base_branch_name,
+            &format!("Merge branch '{base_branch_name}' into {branch_name}"),
+            signing,
+        ) {
+            Ok(sha) => Ok(sha),
+            Err(GitCliError::CommandFailed(_))
+                if !self.get_conflicted_files(worktree_path)?.is_empty() =>
+            {
+                git.abort_merge(worktree_path).map_err(|e| {
+                    GitServiceError::InvalidRepository(format!("git merge --abort failed: {e}"))
+                })?;
+                Err(GitServiceError::MergeConflicts(format!(
+                    "Merging '{base_branch_name}' into '{branch_name}' conflicts"
+                )))
+            }
+            Err(e) => Err(GitServiceError::InvalidRepository(format!(
+                "git merge failed: {e}"
+            ))),
+        }
+    }
+
+    /// Diff between two commits as a patch, for picking files and hunks from
+    pub fn diff_patch(
+        &self,
+        repo_path: &Path,
+        from: &str,
+        to: &str,
+    ) -> Result<String, GitServiceError> {
+        GitCli::new()
+            .diff_patch(repo_path, from, to)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git diff failed: {e}")))
+    }
+
+    /// Merge part of the task branch's diff, given as a patch, into the base
+    /// branch as a single commit. The task branch then merges that commit in,
+    /// keeping its own tree, so the changes left out stay on it and it isn't
+    /// behind the base branch. Returns the base branch's new tip.
+    #[allow(clippy::too_many_arguments)]
+    pub fn merge_partial(
+        &self,
+        repo_path: &Path,
+        task_worktree_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+        patch: &str,
+        commit_message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let task_commit = Self::find_branch(&repo, task_branch_name)?
+            .get()
+            .peel_to_commit()?;
+        let base_commit = Self::find_branch(&repo, base_branch_name)?
+            .get()
+            .peel_to_commit()?;
+        if base_commit.id() != task_commit.id()
+            && !repo.graph_descendant_of(task_commit.id(), base_commit.id())?
+        {
+            return Err(GitServiceError::BranchesDiverged(format!(
+                "Cannot merge: base branch '{base_branch_name}' has moved ahead of task branch '{task_branch_name}'. Rebase the task branch first.",
+            )));
+        }
+
+        let tree =
+            self.apply_patch_to_tree(repo_path, base_commit.id(), patch, base_branch_name)?;
+        let merged_id = self.commit_tree(
+            &repo,
+            task_worktree_path,
+            tree,
+            &[base_commit.id()],
+            commit_message,
+            signing,
+        )?;
+        self.fast_forward_branch(repo_path, base_branch_name, &merged_id.to_string())?;
+
+        let kept_id = self.commit_tree(
+            &repo,
+            task_worktree_path,
+            task_commit.tree_id(),
+            &[merged_id, task_commit.id()],
+            &format!("Merge branch '{base_branch_name}' into {task_branch_name}"),
+            signing,
+        )?;
+        repo.reference(
+            &format!("refs/heads/{task_branch_name}"),
+            kept_id,
+            true,
+            "Keep the changes left out of a partial merge",
+        )?;
+
+        Ok(merged_id.to_string())
+    }
+
+    /// Point a branch at a commit of `base_commit` with the patch applied, for
+    /// opening a pull request of part of a task branch. Returns the commit.
+    pub fn create_partial_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_commit: &str,
+        patch: &str,
+        commit_message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let base_id = repo.revparse_single(base_commit)?.peel_to_commit()?.id();
+        let tree = self.apply_patch_to_tree(repo_path, base_id, patch, base_commit)?;
+        let commit_id =
+            self.commit_tree(&repo, repo_path, tree, &[base_id], commit_message, signing)?;
+        repo.branch(branch_name, &repo.find_commit(commit_id)?, true)?;
+        Ok(commit_id.to_string())
+    }
+
+    fn apply_patch_to_tree(
+        &self,
+        repo_path: &Path,
+        commit: git2::Oid,
+        patch: &str,
+        onto: &str,
+    ) -> Result<git2::Oid, GitServiceError> {
+        let tree = GitCli::new()
+            .apply_to_tree(repo_path, &commit.to_string(), patch)
+            .map_err(|e| {
+                GitServiceError::MergeConflicts(format!(
+                    "The selected changes don't apply to '{onto}': {e}"
+                ))
+            })?;
+        Ok(git2::Oid::from_str(&tree)?)
+    }
+
+    /// Commit a tree without moving any ref, signed through the CLI of the
+    /// given worktree if signing applies
+    fn commit_tree(
+        &self,
+        repo: &Repository,
+        worktree_path: &Path,
+        tree: git2::Oid,
+        parents: &[git2::Oid],
+        message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<git2::Oid, GitServiceError> {
+        if self.should_sign(repo, signing) {
+            self.ensure_cli_commit_identity(worktree_path)?;
+            let parent_ids: Vec<String> = parents.iter().map(|oid| oid.to_string()).collect();
+            let parent_ids: Vec<&str> = parent_ids.iter().map(String::as_str).collect();
+            let sha = GitCli::new()
+                .commit_tree_signed(
+                    worktree_path,
+                    &tree.to_string(),
+                    &parent_ids,
+                    message,
+                    signing,
+                )
+                .map_err(|e| {
+                    GitServiceError::InvalidRepository(format!("git commit-tree failed: {e}"))
+                })?;
+            return Ok(git2::Oid::from_str(&sha)?);
+        }
+        let signature = self.signature_with_fallback(repo)?;
+        let tree = repo.find_tree(tree)?;
+        let parents = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid))
+            .collect::<Result<Vec<_>, _>>()?;
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        Ok(repo.commit(None, &signature, &signature, message, &tree, &parents)?)
     }
 
     /// Rebase a worktree branch onto a new base
@@ -1447,6 +2048,7 @@ impl GitService {
         &self,
         repo_path: &Path,
         branch_name: &str,
+        auth_header: Option<&str>,
     ) -> Result<bool, GitServiceError> {
         let repo = self.open_repo(repo_path)?;
         let default_remote_name = self.default_remote_name(&repo);
@@ -1467,7 +2069,7 @@ impl GitService {
 
         let git_cli = GitCli::new();
         git_cli
-            .check_remote_branch_exists(repo_path, remote_url, stripped_branch_name)
+            .check_remote_branch_exists(repo_path, remote_url, stripped_branch_name, auth_header)
             .map_err(|e| e.into())
     }
 
@@ -1631,11 +2233,15 @@ impl GitService {
         })
     }
 
-    pub fn push_to_github(
+    /// Push the branch to the default remote, authenticating with
+    /// `auth_header` when the code host's token is used instead of the
+    /// machine's credentials
+    pub fn push_to_remote(
         &self,
         worktree_path: &Path,
         branch_name: &str,
         force: bool,
+        auth_header: Option<&str>,
     ) -> Result<(), GitServiceError> {
         let repo = Repository::open(worktree_path)?;
         self.check_worktree_clean(&repo)?;
@@ -1648,8 +2254,14 @@ impl GitService {
             .url()
             .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?;
         let git_cli = GitCli::new();
-        if let Err(e) = git_cli.push(worktree_path, remote_url, branch_name, force) {
-            tracing::error!("Push to GitHub failed: {}", e);
+        if let Err(e) = git_cli.push_with_auth_header(
+            worktree_path,
+            remote_url,
+            branch_name,
+            force,
+            auth_header,
+        ) {
+            tracing::error!("Push to remote failed: {}", e);
             return Err(e.into());
         }
 
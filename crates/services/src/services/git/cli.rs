@@ -25,7 +25,11 @@ use std::{
 use thiserror::Error;
 use utils::shell::resolve_executable_path_blocking; // TODO: make GitCli async
 
-use crate::services::{filesystem_watcher::ALWAYS_SKIP_DIRS, git::Commit};
+use crate::services::{
+    config::{CommitSigningConfig, CommitSigningMode},
+    filesystem_watcher::ALWAYS_SKIP_DIRS,
+    git::{Commit, SignatureStatus},
+};
 
 #[derive(Debug, Error)]
 pub enum GitCliError {
@@ -70,6 +74,10 @@ pub struct StatusDiffEntry {
 pub struct WorktreeEntry {
     pub path: String,
     pub branch: Option<String>,
+    /// Why the worktree is locked, empty when no reason was given
+    pub locked: Option<String>,
+    /// Why git would prune the worktree, e.g. its directory is gone
+    pub prunable: Option<String>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -107,6 +115,67 @@ impl GitCli {
         Ok(())
     }
 
+    /// Add a worktree of an existing branch that only materializes the paths
+    /// matching `patterns`, gitignore-style. The worktree is added without a
+    /// checkout so the rest of the tree is never written.
+    pub fn worktree_add_sparse(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch: &str,
+        patterns: &[String],
+    ) -> Result<(), GitCliError> {
+        self.ensure_available()?;
+        let args: Vec<OsString> = vec![
+            "worktree".into(),
+            "add".into(),
+            "--no-checkout".into(),
+            worktree_path.as_os_str().into(),
+            OsString::from(branch),
+        ];
+        self.git(repo_path, args)?;
+        self.sparse_checkout_set(worktree_path, patterns)?;
+        self.git(worktree_path, ["read-tree", "-mu", "HEAD"])?;
+        Ok(())
+    }
+
+    /// Run `git sparse-checkout set --no-cone` in the worktree, which only
+    /// applies to that worktree. Files no longer matched are removed.
+    pub fn sparse_checkout_set(
+        &self,
+        worktree_path: &Path,
+        patterns: &[String],
+    ) -> Result<(), GitCliError> {
+        let mut input = patterns.join("\n");
+        input.push('\n');
+        self.git_with_stdin(
+            worktree_path,
+            ["sparse-checkout", "set", "--no-cone", "--stdin"],
+            None,
+            input.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Run `git -C <repo> worktree add --detach <path> <rev>`
+    pub fn worktree_add_detached(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        rev: &str,
+    ) -> Result<(), GitCliError> {
+        self.ensure_available()?;
+        let args: Vec<OsString> = vec![
+            "worktree".into(),
+            "add".into(),
+            "--detach".into(),
+            worktree_path.as_os_str().into(),
+            rev.into(),
+        ];
+        self.git(repo_path, args)?;
+        Ok(())
+    }
+
     /// Run `git -C <repo> worktree remove <path>`
     pub fn worktree_remove(
         &self,
@@ -154,6 +223,22 @@ impl GitCli {
         Ok(())
     }
 
+    /// Run `git -C <repo> worktree repair <path>`, relinking the worktree and
+    /// the repository after either was moved
+    pub fn worktree_repair(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitCliError> {
+        let args: Vec<OsString> = vec![
+            "worktree".into(),
+            "repair".into(),
+            worktree_path.as_os_str().into(),
+        ];
+        self.git(repo_path, args)?;
+        Ok(())
+    }
+
     /// Return true if there are any changes in the working tree (staged or unstaged).
     pub fn has_changes(&self, worktree_path: &Path) -> Result<bool, GitCliError> {
         let out = self.git(
@@ -302,6 +387,8 @@ impl GitCli {
         let mut current_path: Option<String> = None;
         let mut current_head: Option<String> = None;
         let mut current_branch: Option<String> = None;
+        let mut current_locked: Option<String> = None;
+        let mut current_prunable: Option<String> = None;
 
         for line in out.lines() {
             let line = line.trim();
@@ -312,6 +399,8 @@ impl GitCli {
                     entries.push(WorktreeEntry {
                         path,
                         branch: current_branch.take(),
+                        locked: current_locked.take(),
+                        prunable: current_prunable.take(),
                     });
                 }
             } else if let Some(path) = line.strip_prefix("worktree ") {
@@ -323,6 +412,10 @@ impl GitCli {
                 current_branch = branch_ref
                     .strip_prefix("refs/heads/")
                     .map(|name| name.to_string());
+            } else if let Some(reason) = line.strip_prefix("locked") {
+                current_locked = Some(reason.trim().to_string());
+            } else if let Some(reason) = line.strip_prefix("prunable") {
+                current_prunable = Some(reason.trim().to_string());
             }
         }
 
@@ -331,6 +424,8 @@ impl GitCli {
             entries.push(WorktreeEntry {
                 path,
                 branch: current_branch,
+                locked: current_locked,
+                prunable: current_prunable,
             });
         }
 
@@ -338,10 +433,98 @@ impl GitCli {
     }
 
     /// Commit staged changes with the given message.
-    pub fn commit(&self, worktree_path: &Path, message: &str) -> Result<(), GitCliError> {
-        self.git(worktree_path, ["commit", "-m", message])?;
+    pub fn commit(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<(), GitCliError> {
+        self.git_with_env(
+            worktree_path,
+            ["commit", "-m", message],
+            &signing_envs(signing),
+        )?;
         Ok(())
     }
+
+    /// Create a commit of a tree without touching any ref or worktree, signed
+    /// with the configured key or the git config's. Returns its sha.
+    pub fn commit_tree_signed(
+        &self,
+        repo_path: &Path,
+        tree: &str,
+        parents: &[&str],
+        message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<String, GitCliError> {
+        let mut args = vec!["commit-tree", tree];
+        for parent in parents {
+            args.extend(["-p", *parent]);
+        }
+        args.extend(["-S", "-m", message]);
+        let out = self.git_with_env(repo_path, args, &signing_envs(signing))?;
+        Ok(out.trim().to_string())
+    }
+
+    /// Signature check of each commit of the revision range, newest first
+    pub fn signature_statuses(
+        &self,
+        repo_path: &Path,
+        range: &str,
+    ) -> Result<Vec<SignatureStatus>, GitCliError> {
+        let out = self.git(repo_path, ["log", "--format=%G?", range])?;
+        Ok(out.lines().map(SignatureStatus::from_code).collect())
+    }
+
+    /// Diff between two commits as a patch `git apply` takes, binary files
+    /// included and renames as a deletion and an addition
+    pub fn diff_patch(
+        &self,
+        repo_path: &Path,
+        from: &str,
+        to: &str,
+    ) -> Result<String, GitCliError> {
+        self.git_with_env(
+            repo_path,
+            [
+                "diff",
+                "--binary",
+                "--no-renames",
+                "--no-color",
+                "--no-ext-diff",
+                "-U3",
+                from,
+                to,
+            ],
+            &config_envs(&[("core.quotePath", "false".to_string())]),
+        )
+    }
+
+    /// Tree of a commit with the patch applied, built in a temporary index so
+    /// neither the repository's index nor any worktree is touched. Returns the
+    /// tree's sha.
+    pub fn apply_to_tree(
+        &self,
+        repo_path: &Path,
+        commit: &str,
+        patch: &str,
+    ) -> Result<String, GitCliError> {
+        let tmp_dir = tempfile::TempDir::new()
+            .map_err(|e| GitCliError::CommandFailed(format!("temp dir create failed: {e}")))?;
+        let envs = [(
+            OsString::from("GIT_INDEX_FILE"),
+            tmp_dir.path().join("index").into_os_string(),
+        )];
+        self.git_with_env(repo_path, ["read-tree", commit], &envs)?;
+        self.git_with_stdin(
+            repo_path,
+            ["apply", "--cached", "--whitespace=nowarn", "-"],
+            Some(&envs),
+            patch.as_bytes(),
+        )?;
+        let out = self.git_with_env(repo_path, ["write-tree"], &envs)?;
+        Ok(out.trim().to_string())
+    }
     /// Fetch a branch to the given remote using native git authentication.
     pub fn fetch_with_refspec(
         &self,
@@ -371,13 +554,26 @@ impl GitCli {
         remote_url: &str,
         branch: &str,
         force: bool,
+    ) -> Result<(), GitCliError> {
+        self.push_with_auth_header(repo_path, remote_url, branch, force, None)
+    }
+
+    /// Push, sending `auth_header` (e.g. `Authorization: Basic ...`) to the
+    /// remote in place of the configured credentials
+    pub fn push_with_auth_header(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        branch: &str,
+        force: bool,
+        auth_header: Option<&str>,
     ) -> Result<(), GitCliError> {
         let refspec = if force {
             format!("+refs/heads/{branch}:refs/heads/{branch}")
         } else {
             format!("refs/heads/{branch}:refs/heads/{branch}")
         };
-        let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        let envs = remote_envs(auth_header);
 
         let args = [
             OsString::from("push"),
@@ -398,8 +594,9 @@ impl GitCli {
         repo_path: &Path,
         remote_url: &str,
         branch_name: &str,
+        auth_header: Option<&str>,
     ) -> Result<bool, GitCliError> {
-        let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+        let envs = remote_envs(auth_header);
 
         let args = [
             OsString::from("ls-remote"),
@@ -488,13 +685,40 @@ impl GitCli {
             .merge_base(worktree_path, old_base, task_branch)
             .unwrap_or(old_base.to_string());
 
-        self.git(
+        self.git_with_env(
             worktree_path,
             ["rebase", "--onto", new_base, &merge_base, task_branch],
+            &conflict_envs(),
         )?;
         Ok(())
     }
 
+    /// Carry on with an in-progress rebase once its conflicts are staged,
+    /// keeping the replayed commit's message.
+    pub fn continue_rebase(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        let mut envs = conflict_envs();
+        envs.push((OsString::from("GIT_EDITOR"), OsString::from("true")));
+        self.git_with_env(worktree_path, ["rebase", "--continue"], &envs)?;
+        Ok(())
+    }
+
+    /// Apply the commits on top of HEAD in order, noting each one's origin in
+    /// its message
+    pub fn cherry_pick(&self, worktree_path: &Path, commits: &[String]) -> Result<(), GitCliError> {
+        let mut args = vec!["cherry-pick", "-x"];
+        args.extend(commits.iter().map(String::as_str));
+        self.git_with_env(worktree_path, args, &conflict_envs())?;
+        Ok(())
+    }
+
+    /// Carry on with an in-progress cherry-pick once its conflicts are staged
+    pub fn continue_cherry_pick(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        let mut envs = conflict_envs();
+        envs.push((OsString::from("GIT_EDITOR"), OsString::from("true")));
+        self.git_with_env(worktree_path, ["cherry-pick", "--continue"], &envs)?;
+        Ok(())
+    }
+
     /// Return true if there is a rebase in progress in this worktree.
     /// We treat this as true when either of Git's rebase state directories exists:
     /// - rebase-merge (interactive rebase)
@@ -582,11 +806,74 @@ impl GitCli {
         base_branch: &str,
         from_branch: &str,
         message: &str,
+        signing: &CommitSigningConfig,
     ) -> Result<String, GitCliError> {
         self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
         self.git(repo_path, ["merge", "--squash", "--no-commit", from_branch])
             .map(|_| ())?;
-        self.git(repo_path, ["commit", "-m", message]).map(|_| ())?;
+        self.commit(repo_path, message, signing)?;
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
+    /// Merge `from_branch` into `base_branch` with a merge commit, even when it
+    /// could be fast-forwarded. Returns the merge commit's sha.
+    pub fn merge_no_ff_commit(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        from_branch: &str,
+        message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<String, GitCliError> {
+        self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
+        self.git_with_env(
+            repo_path,
+            ["merge", "--no-ff", "--no-edit", "-m", message, from_branch],
+            &signing_envs(signing),
+        )?;
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
+    /// Merge `from` into the branch checked out in the worktree with a merge
+    /// commit. Returns the new HEAD sha.
+    pub fn merge_into_head(
+        &self,
+        worktree_path: &Path,
+        from: &str,
+        message: &str,
+        signing: &CommitSigningConfig,
+    ) -> Result<String, GitCliError> {
+        self.git_with_env(
+            worktree_path,
+            ["merge", "--no-ff", "--no-edit", "-m", message, from],
+            &signing_envs(signing),
+        )?;
+        let sha = self
+            .git(worktree_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
+    /// Fast-forward `base_branch` to `from_branch`, failing if it can't be.
+    /// Returns the new tip's sha.
+    pub fn merge_fast_forward(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        from_branch: &str,
+    ) -> Result<String, GitCliError> {
+        self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
+        self.git(repo_path, ["merge", "--ff-only", from_branch])
+            .map(|_| ())?;
         let sha = self
             .git(repo_path, ["rev-parse", "HEAD"])?
             .trim()
@@ -627,6 +914,42 @@ impl GitCli {
         self.git(worktree_path, ["revert", "--abort"]).map(|_| ())
     }
 
+    /// Content of a conflicted path at an index stage: 1 for the common
+    /// ancestor, 2 for ours and 3 for theirs. None when that side doesn't
+    /// have the path.
+    pub fn show_index_stage(
+        &self,
+        worktree_path: &Path,
+        stage: u8,
+        path: &str,
+    ) -> Result<Option<Vec<u8>>, GitCliError> {
+        match self.git_impl(
+            worktree_path,
+            [
+                OsString::from("show"),
+                OsString::from(format!(":{stage}:{path}")),
+            ],
+            None,
+            None,
+        ) {
+            Ok(out) => Ok(Some(out)),
+            Err(GitCliError::CommandFailed(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stage a path, marking its conflict resolved.
+    pub fn add_path(&self, worktree_path: &Path, path: &str) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["add", "--", path]).map(|_| ())
+    }
+
+    /// Remove a path from the index and the worktree, resolving its conflict
+    /// as deleted.
+    pub fn remove_path(&self, worktree_path: &Path, path: &str) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["rm", "--force", "--quiet", "--", path])
+            .map(|_| ())
+    }
+
     /// List files currently in a conflicted (unmerged) state in the worktree.
     pub fn get_conflicted_files(&self, worktree_path: &Path) -> Result<Vec<String>, GitCliError> {
         // `--diff-filter=U` lists paths with unresolved conflicts
@@ -642,6 +965,61 @@ impl GitCli {
     }
 }
 
+/// Environment for commands that talk to a remote. The header goes in as
+/// config through the environment, which keeps it out of the process list.
+fn remote_envs(auth_header: Option<&str>) -> Vec<(OsString, OsString)> {
+    let mut envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
+    if let Some(header) = auth_header {
+        envs.extend(config_envs(&[("http.extraHeader", header.to_string())]));
+    }
+    envs
+}
+
+/// Environment for commands that make commits, signing them as configured
+fn signing_envs(signing: &CommitSigningConfig) -> Vec<(OsString, OsString)> {
+    let mut entries = Vec::new();
+    match signing.mode {
+        CommitSigningMode::GitConfig => {}
+        CommitSigningMode::Always => entries.push(("commit.gpgsign", "true".to_string())),
+        CommitSigningMode::Never => entries.push(("commit.gpgsign", "false".to_string())),
+    }
+    if signing.mode != CommitSigningMode::Never
+        && let Some(key) = &signing.key
+    {
+        entries.push(("gpg.format", signing.format.as_str().to_string()));
+        entries.push(("user.signingkey", key.clone()));
+    }
+    config_envs(&entries)
+}
+
+/// Environment for rebases and cherry-picks. Conflict markers include the
+/// common ancestor's lines, so each conflict shows all three sides.
+fn conflict_envs() -> Vec<(OsString, OsString)> {
+    config_envs(&[("merge.conflictStyle", "diff3".to_string())])
+}
+
+/// Config entries for a single command, set through the environment
+fn config_envs(entries: &[(&str, String)]) -> Vec<(OsString, OsString)> {
+    if entries.is_empty() {
+        return Vec::new();
+    }
+    let mut envs = vec![(
+        OsString::from("GIT_CONFIG_COUNT"),
+        OsString::from(entries.len().to_string()),
+    )];
+    for (i, (key, value)) in entries.iter().enumerate() {
+        envs.push((
+            OsString::from(format!("GIT_CONFIG_KEY_{i}")),
+            OsString::from(key),
+        ));
+        envs.push((
+            OsString::from(format!("GIT_CONFIG_VALUE_{i}")),
+            OsString::from(value),
+        ));
+    }
+    envs
+}
+
 // Private methods
 impl GitCli {
     fn classify_cli_error(&self, msg: String) -> GitCliError {
@@ -2,7 +2,10 @@ use std::{path::Path, time::Duration};
 
 use backon::{ExponentialBuilder, Retryable};
 use chrono::{DateTime, Utc};
-use db::models::merge::PullRequestInfo;
+use db::models::{
+    ci_check::NewCiCheck,
+    merge::{MergeStrategy, PullRequestInfo},
+};
 use serde::Serialize;
 use thiserror::Error;
 use tokio::task;
@@ -12,7 +15,7 @@ use ts_rs::TS;
 mod cli;
 
 use cli::{GhCli, GhCliError, PrComment, PrReviewComment};
-pub use cli::{PrCommentAuthor, ReviewCommentUser};
+pub use cli::{GitHubIssue, PrCommentAuthor, ReviewCommentUser};
 
 /// Unified PR comment that can be either a general comment or review comment
 #[derive(Debug, Clone, Serialize, TS)]
@@ -116,6 +119,15 @@ pub struct CreatePrRequest {
     pub draft: Option<bool>,
 }
 
+/// Which open issues to list
+#[derive(Debug, Clone, Default)]
+pub struct IssueFilter {
+    /// Issues must carry every one of these labels
+    pub labels: Vec<String>,
+    pub milestone: Option<String>,
+    pub limit: u32,
+}
+
 #[derive(Debug, Clone)]
 pub struct GitHubService {
     gh_cli: GhCli,
@@ -249,6 +261,55 @@ impl GitHubService {
         .await
     }
 
+    /// Merge the pull request with the strategy. GitHub has no fast-forward
+    /// merges of pull requests.
+    pub async fn merge_pr(
+        &self,
+        pr_url: &str,
+        strategy: MergeStrategy,
+    ) -> Result<PullRequestInfo, GitHubServiceError> {
+        let method_flag = match strategy {
+            MergeStrategy::MergeCommit => "--merge",
+            MergeStrategy::Squash => "--squash",
+            MergeStrategy::Rebase => "--rebase",
+            MergeStrategy::FastForward => {
+                return Err(GitHubServiceError::PullRequest(
+                    "GitHub can't fast-forward pull requests; use rebase instead".to_string(),
+                ));
+            }
+        };
+        let cli = self.gh_cli.clone();
+        let url = pr_url.to_string();
+        task::spawn_blocking(move || cli.merge_pr(&url, method_flag))
+            .await
+            .map_err(|err| {
+                GitHubServiceError::PullRequest(format!(
+                    "Failed to execute GitHub CLI for merging PR at {pr_url}: {err}"
+                ))
+            })?
+            .map_err(GitHubServiceError::from)?;
+
+        info!("Merged GitHub PR {} with {:?}", pr_url, strategy);
+        self.update_pr_status(pr_url).await
+    }
+
+    /// The pull request's head commit and its CI checks
+    pub async fn get_pr_checks(
+        &self,
+        pr_url: &str,
+    ) -> Result<(String, Vec<NewCiCheck>), GitHubServiceError> {
+        let cli = self.gh_cli.clone();
+        let url = pr_url.to_string();
+        task::spawn_blocking(move || cli.pr_checks(&url))
+            .await
+            .map_err(|err| {
+                GitHubServiceError::PullRequest(format!(
+                    "Failed to execute GitHub CLI for checks of PR at {pr_url}: {err}"
+                ))
+            })?
+            .map_err(GitHubServiceError::from)
+    }
+
     /// List all pull requests for a branch (including closed/merged)
     pub async fn list_all_prs_for_branch(
         &self,
@@ -421,4 +482,77 @@ impl GitHubService {
         })
         .await
     }
+
+    /// List open issues of the repository matching the filter
+    pub async fn list_issues(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        filter: &IssueFilter,
+    ) -> Result<Vec<GitHubIssue>, GitHubServiceError> {
+        (|| async {
+            let cli = self.gh_cli.clone();
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let filter = filter.clone();
+            task::spawn_blocking(move || {
+                cli.list_issues(
+                    &owner,
+                    &repo,
+                    &filter.labels,
+                    filter.milestone.as_deref(),
+                    filter.limit,
+                )
+            })
+            .await
+            .map_err(|err| {
+                GitHubServiceError::Repository(format!(
+                    "Failed to execute GitHub CLI for listing issues: {err}"
+                ))
+            })?
+            .map_err(GitHubServiceError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHubServiceError| e.should_retry())
+        .notify(|err: &GitHubServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    /// Close an issue with a comment explaining why
+    pub async fn close_issue(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        issue_number: i64,
+        comment: &str,
+    ) -> Result<(), GitHubServiceError> {
+        let cli = self.gh_cli.clone();
+        let owner = repo_info.owner.clone();
+        let repo = repo_info.repo_name.clone();
+        let comment = comment.to_string();
+        task::spawn_blocking(move || cli.close_issue(&owner, &repo, issue_number, &comment))
+            .await
+            .map_err(|err| {
+                GitHubServiceError::Repository(format!(
+                    "Failed to execute GitHub CLI for closing issue #{issue_number}: {err}"
+                ))
+            })?
+            .map_err(GitHubServiceError::from)?;
+
+        info!(
+            "Closed GitHub issue #{} in {}/{}",
+            issue_number, repo_info.owner, repo_info.repo_name
+        );
+        Ok(())
+    }
 }
@@ -12,7 +12,10 @@ use std::{
 };
 
 use chrono::{DateTime, Utc};
-use db::models::merge::{MergeStatus, PullRequestInfo};
+use db::models::{
+    ci_check::NewCiCheck,
+    merge::{MergeStatus, PullRequestInfo},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tempfile::NamedTempFile;
@@ -20,7 +23,10 @@ use thiserror::Error;
 use ts_rs::TS;
 use utils::shell::resolve_executable_path_blocking;
 
-use crate::services::github::{CreatePrRequest, GitHubRepoInfo};
+use crate::services::{
+    ci_checks,
+    github::{CreatePrRequest, GitHubRepoInfo},
+};
 
 /// Author information for a PR comment
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -61,6 +67,15 @@ pub struct PrReviewComment {
     pub author_association: String,
 }
 
+/// An open GitHub issue (from gh issue list)
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct GitHubIssue {
+    pub number: i64,
+    pub title: String,
+    pub body: String,
+    pub url: String,
+}
+
 /// High-level errors originating from the GitHub CLI.
 #[derive(Debug, Error)]
 pub enum GhCliError {
@@ -218,6 +233,27 @@ impl GhCli {
         Self::parse_pr_view(&raw)
     }
 
+    /// The head commit of a pull request by URL, and its CI checks.
+    pub fn pr_checks(&self, pr_url: &str) -> Result<(String, Vec<NewCiCheck>), GhCliError> {
+        let raw = self.run(
+            [
+                "pr",
+                "view",
+                pr_url,
+                "--json",
+                "headRefOid,statusCheckRollup",
+            ],
+            None,
+        )?;
+        Self::parse_pr_checks(&raw)
+    }
+
+    /// Merge a pull request by URL with `--merge`, `--squash` or `--rebase`.
+    pub fn merge_pr(&self, pr_url: &str, method_flag: &str) -> Result<(), GhCliError> {
+        self.run(["pr", "merge", pr_url, method_flag], None)?;
+        Ok(())
+    }
+
     /// List pull requests for a branch (includes closed/merged).
     pub fn list_prs_for_branch(
         &self,
@@ -281,6 +317,67 @@ impl GhCli {
         )?;
         Self::parse_pr_review_comments(&raw)
     }
+
+    /// List open issues carrying all of the labels and in the milestone, if given.
+    pub fn list_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        labels: &[String],
+        milestone: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<GitHubIssue>, GhCliError> {
+        let mut args: Vec<OsString> = vec![
+            OsString::from("issue"),
+            OsString::from("list"),
+            OsString::from("--repo"),
+            OsString::from(format!("{owner}/{repo}")),
+            OsString::from("--state"),
+            OsString::from("open"),
+            OsString::from("--limit"),
+            OsString::from(limit.to_string()),
+            OsString::from("--json"),
+            OsString::from("number,title,body,url"),
+        ];
+        for label in labels {
+            args.push(OsString::from("--label"));
+            args.push(OsString::from(label));
+        }
+        if let Some(milestone) = milestone {
+            args.push(OsString::from("--milestone"));
+            args.push(OsString::from(milestone));
+        }
+
+        let raw = self.run(args, None)?;
+        serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh issue list response: {err}; raw: {raw}"
+            ))
+        })
+    }
+
+    /// Close an issue, leaving a comment on it.
+    pub fn close_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        issue_number: i64,
+        comment: &str,
+    ) -> Result<(), GhCliError> {
+        self.run(
+            [
+                "issue",
+                "close",
+                &issue_number.to_string(),
+                "--repo",
+                &format!("{owner}/{repo}"),
+                "--comment",
+                comment,
+            ],
+            None,
+        )?;
+        Ok(())
+    }
 }
 
 impl GhCli {
@@ -383,6 +480,63 @@ impl GhCli {
             .collect()
     }
 
+    /// Check runs and commit statuses of the rollup, which GitHub lists
+    /// together
+    fn parse_pr_checks(raw: &str) -> Result<(String, Vec<NewCiCheck>), GhCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr view --json statusCheckRollup response: {err}; raw: {raw}"
+            ))
+        })?;
+        let head_commit = value
+            .get("headRefOid")
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                GhCliError::UnexpectedOutput(format!(
+                    "gh pr view response missing 'headRefOid': {value:#?}"
+                ))
+            })?
+            .to_string();
+        let str_field = |item: &Value, key: &str| {
+            item.get(key)
+                .and_then(Value::as_str)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let checks = value
+            .get("statusCheckRollup")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|item| {
+                if item.get("__typename").and_then(Value::as_str) == Some("StatusContext") {
+                    Some(NewCiCheck {
+                        name: str_field(item, "context")?,
+                        state: ci_checks::github_status_state(
+                            item.get("state")
+                                .and_then(Value::as_str)
+                                .unwrap_or_default(),
+                        ),
+                        url: str_field(item, "targetUrl"),
+                    })
+                } else {
+                    Some(NewCiCheck {
+                        name: str_field(item, "name")?,
+                        state: ci_checks::github_check_run_state(
+                            item.get("status")
+                                .and_then(Value::as_str)
+                                .unwrap_or_default(),
+                            item.get("conclusion").and_then(Value::as_str),
+                        ),
+                        url: str_field(item, "detailsUrl"),
+                    })
+                }
+            })
+            .collect();
+        Ok((head_commit, checks))
+    }
+
     fn parse_pr_review_comments(raw: &str) -> Result<Vec<PrReviewComment>, GhCliError> {
         serde_json::from_str(raw.trim()).map_err(|err| {
             GhCliError::UnexpectedOutput(format!(
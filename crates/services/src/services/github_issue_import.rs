@@ -0,0 +1,163 @@
+//! Turn open GitHub issues into tasks.
+//!
+//! Every imported task is linked to its issue, so importing again only picks
+//! up new issues. Links created with status sync close their issue once the
+//! task is done; the PR monitor checks for those every minute.
+
+use chrono::Utc;
+use db::models::{
+    github_issue_link::{CreateGitHubIssueLink, GitHubIssueLink},
+    task::{CreateTask, Task},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::github::{GitHubIssue, GitHubRepoInfo, GitHubService};
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct ImportGitHubIssues {
+    /// Project repository whose GitHub remote the issues come from
+    pub repo_id: Uuid,
+    /// Only import issues carrying all of these labels
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Only import issues in this milestone
+    pub milestone: Option<String>,
+    /// Close each issue on GitHub when its task is done
+    #[serde(default)]
+    pub sync_status: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct GitHubIssueImport {
+    pub tasks: Vec<Task>,
+    /// Matching issues that had been imported before
+    pub skipped: usize,
+}
+
+/// Create a task for every issue not yet imported into the project, keeping
+/// the order the issues were listed in
+pub async fn import_issues(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    repo_info: &GitHubRepoInfo,
+    issues: &[GitHubIssue],
+    sync_status: bool,
+) -> Result<GitHubIssueImport, sqlx::Error> {
+    let imported = GitHubIssueLink::find_issue_numbers(
+        pool,
+        project_id,
+        &repo_info.owner,
+        &repo_info.repo_name,
+    )
+    .await?;
+    let new_issues: Vec<&GitHubIssue> = issues
+        .iter()
+        .filter(|issue| !imported.contains(&issue.number))
+        .collect();
+
+    let mut tx = pool.begin().await?;
+    let mut tasks = Vec::with_capacity(new_issues.len());
+    for issue in new_issues.iter().rev() {
+        let create_task = CreateTask::from_title_description(
+            project_id,
+            issue.title.clone(),
+            Some(task_description(issue)),
+        );
+        let task = Task::create(&mut *tx, &create_task, Uuid::new_v4()).await?;
+        GitHubIssueLink::create(
+            &mut *tx,
+            &CreateGitHubIssueLink {
+                task_id: task.id,
+                project_id,
+                repo_owner: repo_info.owner.clone(),
+                repo_name: repo_info.repo_name.clone(),
+                issue_number: issue.number,
+                issue_url: issue.url.clone(),
+                sync_status,
+            },
+        )
+        .await?;
+        tasks.push(task);
+    }
+    tx.commit().await?;
+    tasks.reverse();
+
+    Ok(GitHubIssueImport {
+        tasks,
+        skipped: issues.len() - new_issues.len(),
+    })
+}
+
+/// Close the issues of done tasks that sync their status. An issue that
+/// fails to close is tried again on the next call.
+pub async fn close_done_issues(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let links = GitHubIssueLink::find_to_close(pool).await?;
+    if links.is_empty() {
+        return Ok(());
+    }
+
+    let github = match GitHubService::new() {
+        Ok(github) => github,
+        Err(e) => {
+            tracing::warn!("Can't close GitHub issues of done tasks: {}", e);
+            return Ok(());
+        }
+    };
+    for link in links {
+        let repo_info = GitHubRepoInfo {
+            owner: link.repo_owner.clone(),
+            repo_name: link.repo_name.clone(),
+        };
+        match github
+            .close_issue(
+                &repo_info,
+                link.issue_number,
+                "Closed because the task imported from this issue is done.",
+            )
+            .await
+        {
+            Ok(()) => GitHubIssueLink::mark_closed(pool, link.id, Utc::now()).await?,
+            Err(e) => tracing::warn!("Failed to close GitHub issue {}: {}", link.issue_url, e),
+        }
+    }
+    Ok(())
+}
+
+/// Issue body followed by a link back to the issue
+pub fn task_description(issue: &GitHubIssue) -> String {
+    let body = issue.body.trim();
+    if body.is_empty() {
+        format!("Imported from {}", issue.url)
+    } else {
+        format!("{body}\n\nImported from {}", issue.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(body: &str) -> GitHubIssue {
+        GitHubIssue {
+            number: 42,
+            title: "Crash on startup".to_string(),
+            body: body.to_string(),
+            url: "https://github.com/acme/app/issues/42".to_string(),
+        }
+    }
+
+    #[test]
+    fn links_back_to_the_issue() {
+        assert_eq!(
+            task_description(&issue("Steps:\n1. open the app\n")),
+            "Steps:\n1. open the app\n\nImported from https://github.com/acme/app/issues/42"
+        );
+        assert_eq!(
+            task_description(&issue("  ")),
+            "Imported from https://github.com/acme/app/issues/42"
+        );
+    }
+}
@@ -3,7 +3,12 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use db::models::image::{CreateImage, Image};
+use chrono::Utc;
+use db::models::{
+    image::{CreateImage, Image},
+    project_settings::ProjectSettings,
+    task::Task,
+};
 use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 use uuid::Uuid;
@@ -25,10 +30,19 @@ pub enum ImageError {
     #[error("Image not found")]
     NotFound,
 
+    #[error("Upload rejected by project policy: {0}")]
+    PolicyViolation(String),
+
     #[error("Failed to build response: {0}")]
     ResponseBuildError(String),
+
+    #[error("Failed to decode image: {0}")]
+    Decode(#[from] image::ImageError),
 }
 
+/// Longest edge, in pixels, of generated thumbnails.
+const THUMBNAIL_SIZE: u32 = 256;
+
 #[derive(Clone)]
 pub struct ImageService {
     cache_dir: PathBuf,
@@ -141,6 +155,76 @@ impl ImageService {
         Ok(())
     }
 
+    /// Enforce the upload policy of the project that owns `task_id`.
+    pub async fn check_upload_policy(
+        &self,
+        task_id: Uuid,
+        original_filename: &str,
+        size_bytes: i64,
+    ) -> Result<(), ImageError> {
+        let Some(task) = Task::find_by_id(&self.pool, task_id).await? else {
+            return Ok(());
+        };
+        let settings = ProjectSettings::find_by_project_id(&self.pool, task.project_id).await?;
+        let used_bytes = ProjectSettings::used_upload_bytes(&self.pool, task.project_id).await?;
+        settings
+            .upload_policy
+            .check(original_filename, size_bytes, used_bytes)
+            .map_err(ImageError::PolicyViolation)
+    }
+
+    /// Store raw clipboard bytes pasted into a task. Only PNG and JPEG pastes are
+    /// accepted, and the declared content type must match the actual image data.
+    pub async fn store_pasted_image(
+        &self,
+        task_id: Uuid,
+        data: &[u8],
+        content_type: &str,
+    ) -> Result<Image, ImageError> {
+        let declared = match content_type {
+            "image/png" => image::ImageFormat::Png,
+            "image/jpeg" | "image/jpg" => image::ImageFormat::Jpeg,
+            _ => return Err(ImageError::InvalidFormat),
+        };
+        if image::guess_format(data).ok() != Some(declared) {
+            return Err(ImageError::InvalidFormat);
+        }
+
+        let extension = declared.extensions_str()[0];
+        let filename = format!(
+            "pasted-{}.{}",
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            extension
+        );
+        self.check_upload_policy(task_id, &filename, data.len() as i64)
+            .await?;
+        let image = self.store_image(data, &filename).await?;
+        self.ensure_thumbnail(&image).await?;
+        Ok(image)
+    }
+
+    /// Return the path of the PNG thumbnail for an image, generating it on first use.
+    /// Decoding and resizing run on the blocking pool.
+    pub async fn ensure_thumbnail(&self, image: &Image) -> Result<PathBuf, ImageError> {
+        let thumbnails_dir = self.cache_dir.join("thumbnails");
+        let thumbnail_path = thumbnails_dir.join(format!("{}.png", image.id));
+        let image_path = self.get_absolute_path(image);
+        tokio::task::spawn_blocking(move || -> Result<PathBuf, ImageError> {
+            if thumbnail_path.exists() {
+                return Ok(thumbnail_path);
+            }
+
+            let data = fs::read(image_path)?;
+            let thumbnail =
+                image::load_from_memory(&data)?.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+            fs::create_dir_all(&thumbnails_dir)?;
+            thumbnail.save_with_format(&thumbnail_path, image::ImageFormat::Png)?;
+            Ok(thumbnail_path)
+        })
+        .await
+        .map_err(std::io::Error::other)?
+    }
+
     pub fn get_absolute_path(&self, image: &Image) -> PathBuf {
         self.cache_dir.join(&image.file_path)
     }
@@ -155,6 +239,13 @@ impl ImageService {
             if file_path.exists() {
                 fs::remove_file(file_path)?;
             }
+            let thumbnail_path = self
+                .cache_dir
+                .join("thumbnails")
+                .join(format!("{}.png", image.id));
+            if thumbnail_path.exists() {
+                fs::remove_file(thumbnail_path)?;
+            }
 
             Image::delete(&self.pool, id).await?;
         }
@@ -0,0 +1,343 @@
+//! The project's lifecycle hooks: commands and webhooks run when an attempt
+//! starts, finishes or is merged.
+//!
+//! Hooks run one after another and each run is recorded on the attempt. Only
+//! a blocking `before_start` hook can stop anything; a failing hook at any
+//! other point is recorded and otherwise ignored, so a broken integration
+//! never holds up the board.
+
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use db::models::{
+    execution_process::ExecutionProcessStatus,
+    hook_run::{CreateHookRun, HookEvent, HookRun, HookRunStatus},
+    project::Project,
+    project_settings::{HookAction, LifecycleHook, ProjectSettings},
+    task::Task,
+    workspace::Workspace,
+};
+use executors::{env::ExecutionEnv, test_output::strip_ansi};
+use futures::StreamExt;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    secrets::{ProjectEnv, mask_secrets},
+    workspace_exec::{ExecOutput, ExecRequest, spawn_exec},
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_OUTPUT_LINES: usize = 40;
+const MAX_RESPONSE_CHARS: usize = 2000;
+
+/// What a hook is told about the attempt, as JSON
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub workspace_id: Uuid,
+    pub branch: String,
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub details: HookDetails,
+}
+
+/// What is known only at some events
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct HookDetails {
+    /// How the attempt's last run ended; `after_finish` only
+    pub outcome: Option<ExecutionProcessStatus>,
+    /// `after_merge` only
+    pub merge_commit: Option<String>,
+    /// The pull request that was merged, if it was merged through one
+    pub pr_url: Option<String>,
+}
+
+impl HookPayload {
+    pub fn new(
+        event: HookEvent,
+        project: &Project,
+        task: &Task,
+        workspace: &Workspace,
+        details: HookDetails,
+    ) -> Self {
+        Self {
+            event,
+            project_id: project.id,
+            project_name: project.name.clone(),
+            task_id: task.id,
+            task_title: task.title.clone(),
+            workspace_id: workspace.id,
+            branch: workspace.branch.clone(),
+            details,
+        }
+    }
+
+    /// The variables a command hook gets besides the project's
+    fn env_vars(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("VK_PROJECT_NAME", self.project_name.clone()),
+            ("VK_PROJECT_ID", self.project_id.to_string()),
+            ("VK_TASK_ID", self.task_id.to_string()),
+            ("VK_WORKSPACE_ID", self.workspace_id.to_string()),
+            ("VK_WORKSPACE_BRANCH", self.branch.clone()),
+            ("VK_HOOK_EVENT", self.event.to_string()),
+            (
+                "VK_HOOK_PAYLOAD",
+                serde_json::to_string(self).unwrap_or_default(),
+            ),
+        ]
+    }
+}
+
+/// Run the project's hooks for `event` on the workspace's attempt. Returns
+/// the name of the blocking `before_start` hook that failed, if one did.
+pub async fn run_hooks(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    event: HookEvent,
+    details: HookDetails,
+) -> Option<String> {
+    let loaded = async {
+        let task = workspace
+            .parent_task(pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let project = task
+            .parent_project(pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let settings = ProjectSettings::find_by_project_id(pool, project.id).await?;
+        Ok::<_, sqlx::Error>((task, project, settings))
+    }
+    .await;
+    let (task, project, settings) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            tracing::error!("Failed to load the hooks of {}: {}", workspace.id, e);
+            return None;
+        }
+    };
+    let hooks: Vec<&LifecycleHook> = settings
+        .hooks
+        .iter()
+        .filter(|hook| hook.events.contains(&event))
+        .collect();
+    if hooks.is_empty() {
+        return None;
+    }
+    let project_env = match ProjectEnv::load(pool, project.id).await {
+        Ok(env) => env,
+        Err(e) => {
+            tracing::error!("Failed to load the variables of {}: {}", project.id, e);
+            ProjectEnv::default()
+        }
+    };
+    let payload = HookPayload::new(event, &project, &task, workspace, details);
+
+    for hook in hooks {
+        let started = Instant::now();
+        let (status, output) = match &hook.action {
+            HookAction::Command {
+                command,
+                working_dir,
+            } => {
+                run_command(
+                    workspace,
+                    &settings,
+                    &project_env,
+                    &payload,
+                    ExecRequest {
+                        command: command.clone(),
+                        working_dir: working_dir.clone(),
+                        timeout_secs: Some(hook_timeout(hook).as_secs()),
+                    },
+                )
+                .await
+            }
+            HookAction::Webhook { url, token_secret } => {
+                let token = token_secret.as_ref().and_then(|name| {
+                    project_env
+                        .vars
+                        .iter()
+                        .find(|(var, _)| var == name)
+                        .map(|(_, value)| value.as_str())
+                });
+                post_webhook(url, token, &payload, hook_timeout(hook), &project_env).await
+            }
+        };
+        if status != HookRunStatus::Succeeded {
+            tracing::warn!(
+                "Hook {} of project {} failed at {} of {}",
+                hook.name,
+                project.id,
+                event,
+                workspace.id
+            );
+        }
+        let run = CreateHookRun {
+            workspace_id: workspace.id,
+            hook_name: hook.name.clone(),
+            event,
+            status,
+            output,
+            duration_ms: started.elapsed().as_millis() as i64,
+        };
+        if let Err(e) = HookRun::create(pool, &run).await {
+            tracing::error!(
+                "Failed to record hook {} of {}: {}",
+                hook.name,
+                workspace.id,
+                e
+            );
+        }
+        if status != HookRunStatus::Succeeded && hook.blocking && event == HookEvent::BeforeStart {
+            return Some(hook.name.clone());
+        }
+    }
+    None
+}
+
+/// Run the hooks in the background, for the events nothing waits on
+pub fn spawn_hooks(pool: SqlitePool, workspace: Workspace, event: HookEvent, details: HookDetails) {
+    tokio::spawn(async move {
+        run_hooks(&pool, &workspace, event, details).await;
+    });
+}
+
+fn hook_timeout(hook: &LifecycleHook) -> Duration {
+    hook.timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TIMEOUT)
+}
+
+async fn run_command(
+    workspace: &Workspace,
+    settings: &ProjectSettings,
+    project_env: &ProjectEnv,
+    payload: &HookPayload,
+    request: ExecRequest,
+) -> (HookRunStatus, String) {
+    let Some(container_ref) = &workspace.container_ref else {
+        return (
+            HookRunStatus::Failed,
+            "The attempt's workspace doesn't exist".to_string(),
+        );
+    };
+    let mut env = ExecutionEnv::new();
+    for (name, value) in &project_env.vars {
+        env.insert(name, value);
+    }
+    for (name, value) in payload.env_vars() {
+        env.insert(name, value);
+    }
+    let mut stream = match spawn_exec(
+        workspace.id,
+        Path::new(container_ref),
+        &env,
+        &settings.exec_policy.for_configured_commands(),
+        project_env.secret_values.clone(),
+        &request,
+    ) {
+        Ok(stream) => stream,
+        Err(e) => return (HookRunStatus::Failed, e.to_string()),
+    };
+
+    let mut output = String::new();
+    let mut status = HookRunStatus::Failed;
+    while let Some(event) = stream.next().await {
+        match event {
+            ExecOutput::Stdout { data } | ExecOutput::Stderr { data } => output.push_str(&data),
+            ExecOutput::Exit { code: Some(0) } => status = HookRunStatus::Succeeded,
+            ExecOutput::Exit { .. } => {}
+            ExecOutput::TimedOut => status = HookRunStatus::TimedOut,
+        }
+    }
+    (status, output_tail(&output))
+}
+
+async fn post_webhook(
+    url: &str,
+    token: Option<&str>,
+    payload: &HookPayload,
+    timeout: Duration,
+    project_env: &ProjectEnv,
+) -> (HookRunStatus, String) {
+    let client = match reqwest::Client::builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => return (HookRunStatus::Failed, e.to_string()),
+    };
+    let mut request = client
+        .post(url)
+        .header("X-VK-Hook-Event", payload.event.to_string())
+        .json(payload);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            let body: String = body.chars().take(MAX_RESPONSE_CHARS).collect();
+            let output = mask_secrets(&format!("{status}\n{body}"), &project_env.secret_values);
+            if status.is_success() {
+                (HookRunStatus::Succeeded, output)
+            } else {
+                (HookRunStatus::Failed, output)
+            }
+        }
+        Err(e) if e.is_timeout() => (HookRunStatus::TimedOut, e.to_string()),
+        Err(e) => (HookRunStatus::Failed, e.to_string()),
+    }
+}
+
+/// The last non-blank lines of the output, without color codes
+fn output_tail(output: &str) -> String {
+    let mut tail: Vec<String> = output
+        .lines()
+        .rev()
+        .map(|line| strip_ansi(line).trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .take(MAX_OUTPUT_LINES)
+        .collect();
+    tail.reverse();
+    tail.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_is_flat_json_and_command_variables() {
+        let payload = HookPayload {
+            event: HookEvent::AfterMerge,
+            project_id: Uuid::nil(),
+            project_name: "web".to_string(),
+            task_id: Uuid::nil(),
+            task_title: "Fix login".to_string(),
+            workspace_id: Uuid::nil(),
+            branch: "vk/1a2b-fix-login".to_string(),
+            details: HookDetails {
+                merge_commit: Some("abc123".to_string()),
+                ..Default::default()
+            },
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["event"], "after_merge");
+        assert_eq!(json["merge_commit"], "abc123");
+        assert!(json["outcome"].is_null());
+
+        let vars = payload.env_vars();
+        assert!(vars.contains(&("VK_HOOK_EVENT", "after_merge".to_string())));
+        assert!(vars.contains(&("VK_WORKSPACE_BRANCH", "vk/1a2b-fix-login".to_string())));
+    }
+}
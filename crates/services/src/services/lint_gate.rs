@@ -0,0 +1,497 @@
+//! The project's lint checks, run in an attempt's workspace once its agent is
+//! done, and the problems read from what they print.
+//!
+//! Issues are read from the formats most checkers use: rustc and clippy
+//! diagnostics, `path:line[:col]: message` lines (mypy, ruff, gcc, eslint's
+//! unix format), TypeScript's `path(line,col)` and eslint's default stylish
+//! output. A check whose output isn't recognized still fails by its exit
+//! code, with the end of its output kept instead.
+
+use std::{path::Path, sync::LazyLock};
+
+use db::models::{
+    lint_run::{LintCheckResult, LintCheckStatus, LintIssue, LintSeverity},
+    project_settings::{ExecPolicy, LintCheck},
+};
+use executors::{env::ExecutionEnv, test_output::strip_ansi};
+use futures::StreamExt;
+use regex::Regex;
+use uuid::Uuid;
+
+use super::workspace_exec::{ExecOutput, ExecRequest, spawn_exec};
+
+const MAX_ISSUES: usize = 200;
+const MAX_OUTPUT_LINES: usize = 80;
+/// Issues of a check listed in the fix-up prompt
+const MAX_PROMPT_ISSUES: usize = 50;
+
+static RUSTC_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(error|warning)(?:\[([A-Za-z0-9_:]+)\])?: (.+)$").expect("valid regex")
+});
+static RUSTC_LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--> (.+?):(\d+):(\d+)$").expect("valid regex"));
+static CLIPPY_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"rust-clippy/[^#\s]+#([a-z0-9_]+)").expect("valid regex"));
+static TSC_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\S.*?)\((\d+),(\d+)\): (error|warning) (TS\d+): (.+)$").expect("valid regex")
+});
+static LOCATED_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([^\s:]+):(\d+)(?::(\d+))?:? (.+)$").expect("valid regex"));
+static SEVERITY_PREFIX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(error|warning|note|info)\s*:?\s+(.+)$").expect("valid regex")
+});
+static RULE_PREFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Z]+[0-9]+) (?:\[\*\] )?(.+)$").expect("valid regex"));
+static RULE_SUFFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?)\s+\[([^\]\s]+)\]$").expect("valid regex"));
+static STYLISH_ISSUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s+(\d+):(\d+)\s+(error|warning)\s+(.+?)(?:\s{2,}(\S+))?$").expect("valid regex")
+});
+
+/// Run the checks one after another in the workspace
+pub async fn run_checks(
+    workspace_id: Uuid,
+    workspace_root: &Path,
+    env: &ExecutionEnv,
+    policy: &ExecPolicy,
+    secrets: &[String],
+    checks: &[LintCheck],
+) -> Vec<LintCheckResult> {
+    let mut results = Vec::with_capacity(checks.len());
+    for check in checks {
+        let request = ExecRequest {
+            command: check.command.clone(),
+            working_dir: check.working_dir.clone(),
+            timeout_secs: check.timeout_secs,
+        };
+        let mut stream = match spawn_exec(
+            workspace_id,
+            workspace_root,
+            env,
+            policy,
+            secrets.to_vec(),
+            &request,
+        ) {
+            Ok(stream) => stream,
+            Err(e) => {
+                results.push(LintCheckResult {
+                    name: check.name.clone(),
+                    command: check.command.clone(),
+                    status: LintCheckStatus::NotRun,
+                    exit_code: None,
+                    issues: Vec::new(),
+                    output: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let mut output = String::new();
+        let mut exit_code = None;
+        let mut timed_out = false;
+        while let Some(event) = stream.next().await {
+            match event {
+                ExecOutput::Stdout { data } | ExecOutput::Stderr { data } => output.push_str(&data),
+                ExecOutput::Exit { code } => exit_code = code,
+                ExecOutput::TimedOut => timed_out = true,
+            }
+        }
+        let output: String = output.lines().map(|line| strip_ansi(line) + "\n").collect();
+        let status = if timed_out {
+            LintCheckStatus::TimedOut
+        } else if exit_code == Some(0) {
+            LintCheckStatus::Passed
+        } else {
+            LintCheckStatus::Failed
+        };
+        results.push(LintCheckResult {
+            name: check.name.clone(),
+            command: check.command.clone(),
+            status,
+            exit_code,
+            issues: parse_issues(&output),
+            output: output_tail(&output),
+        });
+    }
+    results
+}
+
+/// Whether every check ran and passed
+pub fn passed(results: &[LintCheckResult]) -> bool {
+    results
+        .iter()
+        .all(|result| result.status == LintCheckStatus::Passed)
+}
+
+/// Whether a check failed on something the agent can fix
+pub fn needs_fix(results: &[LintCheckResult]) -> bool {
+    results
+        .iter()
+        .any(|result| result.status == LintCheckStatus::Failed)
+}
+
+/// The prompt of the follow-up run asked to fix what the checks found
+pub fn fix_prompt(results: &[LintCheckResult]) -> String {
+    let mut prompt = String::from(
+        "The lint checks run on your changes failed. Fix what they report, keeping the changes you made, and make sure the checks pass.",
+    );
+    for result in results
+        .iter()
+        .filter(|result| result.status == LintCheckStatus::Failed)
+    {
+        prompt.push_str(&format!(
+            "\n\n{} (`{}`) failed",
+            result.name, result.command
+        ));
+        if result.issues.is_empty() {
+            prompt.push_str(&format!(
+                ". The end of its output:\n```\n{}\n```",
+                result.output.trim_end()
+            ));
+            continue;
+        }
+        prompt.push(':');
+        for issue in result.issues.iter().take(MAX_PROMPT_ISSUES) {
+            prompt.push_str(&format!("\n- {}", describe(issue)));
+        }
+        if result.issues.len() > MAX_PROMPT_ISSUES {
+            prompt.push_str(&format!(
+                "\n- and {} more",
+                result.issues.len() - MAX_PROMPT_ISSUES
+            ));
+        }
+    }
+    prompt
+}
+
+fn describe(issue: &LintIssue) -> String {
+    let mut location = issue.path.clone();
+    if let Some(line) = issue.line {
+        location.push_str(&format!(":{line}"));
+        if let Some(column) = issue.column {
+            location.push_str(&format!(":{column}"));
+        }
+    }
+    let severity = match issue.severity {
+        LintSeverity::Error => "error",
+        LintSeverity::Warning => "warning",
+        LintSeverity::Note => "note",
+    };
+    match &issue.rule {
+        Some(rule) => format!("{location}: {severity}: {} [{rule}]", issue.message),
+        None => format!("{location}: {severity}: {}", issue.message),
+    }
+}
+
+fn output_tail(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    lines[lines.len().saturating_sub(MAX_OUTPUT_LINES)..].join("\n")
+}
+
+fn severity(text: &str) -> LintSeverity {
+    match text.to_ascii_lowercase().as_str() {
+        "warning" => LintSeverity::Warning,
+        "note" | "info" => LintSeverity::Note,
+        _ => LintSeverity::Error,
+    }
+}
+
+fn number(text: Option<regex::Match>) -> Option<u32> {
+    text.and_then(|text| text.as_str().parse().ok())
+}
+
+fn push(issues: &mut Vec<LintIssue>, issue: LintIssue) {
+    if issues.len() < MAX_ISSUES && !issues.contains(&issue) {
+        issues.push(issue);
+    }
+}
+
+/// The issues the output reports, in the order printed and without
+/// duplicates
+fn parse_issues(output: &str) -> Vec<LintIssue> {
+    let mut issues: Vec<LintIssue> = Vec::new();
+    // A rustc diagnostic waiting for its location
+    let mut pending: Option<(LintSeverity, Option<String>, String)> = None;
+    // The file eslint's stylish format lists issues under
+    let mut stylish_file: Option<String> = None;
+
+    for line in output.lines() {
+        let line = line.trim_end();
+        if let Some(caps) = RUSTC_HEADER.captures(line) {
+            pending = Some((
+                severity(&caps[1]),
+                caps.get(2).map(|rule| rule.as_str().to_string()),
+                caps[3].to_string(),
+            ));
+            stylish_file = None;
+        } else if let Some(caps) = RUSTC_LOCATION.captures(line) {
+            if let Some((severity, rule, message)) = pending.take() {
+                push(
+                    &mut issues,
+                    LintIssue {
+                        path: caps[1].to_string(),
+                        line: number(caps.get(2)),
+                        column: number(caps.get(3)),
+                        severity,
+                        message,
+                        rule,
+                    },
+                );
+            }
+        } else if let Some(caps) = CLIPPY_LINK.captures(line) {
+            // Clippy names the lint in the help link after the location
+            if let Some(last) = issues.last_mut()
+                && last.rule.is_none()
+            {
+                last.rule = Some(format!("clippy::{}", &caps[1]));
+            }
+        } else if let Some(caps) = TSC_LINE.captures(line) {
+            push(
+                &mut issues,
+                LintIssue {
+                    path: caps[1].to_string(),
+                    line: number(caps.get(2)),
+                    column: number(caps.get(3)),
+                    severity: severity(&caps[4]),
+                    message: caps[6].to_string(),
+                    rule: Some(caps[5].to_string()),
+                },
+            );
+        } else if let Some(caps) = STYLISH_ISSUE.captures(line)
+            && let Some(path) = &stylish_file
+        {
+            push(
+                &mut issues,
+                LintIssue {
+                    path: path.clone(),
+                    line: number(caps.get(1)),
+                    column: number(caps.get(2)),
+                    severity: severity(&caps[3]),
+                    message: caps[4].to_string(),
+                    rule: caps.get(5).map(|rule| rule.as_str().to_string()),
+                },
+            );
+        } else if let Some(caps) = LOCATED_LINE.captures(line) {
+            let mut severity_found = LintSeverity::Error;
+            let mut rule = None;
+            let mut message = caps[4].trim().to_string();
+            if let Some(inner) = SEVERITY_PREFIX.captures(&message) {
+                severity_found = severity(&inner[1]);
+                message = inner[2].to_string();
+            } else if let Some(inner) = RULE_PREFIX.captures(&message) {
+                rule = Some(inner[1].to_string());
+                message = inner[2].to_string();
+            }
+            if let Some((text, suffix)) = RULE_SUFFIX
+                .captures(&message)
+                .map(|inner| (inner[1].to_string(), inner[2].to_string()))
+            {
+                message = text;
+                // eslint's unix format gives `[Error/rule-name]`
+                rule = Some(match suffix.split_once('/') {
+                    Some((level, name)) => {
+                        severity_found = severity(level);
+                        name.to_string()
+                    }
+                    None => suffix,
+                });
+            }
+            push(
+                &mut issues,
+                LintIssue {
+                    path: caps[1].to_string(),
+                    line: number(caps.get(2)),
+                    column: number(caps.get(3)),
+                    severity: severity_found,
+                    message,
+                    rule,
+                },
+            );
+        } else if !line.is_empty()
+            && !line.starts_with(char::is_whitespace)
+            && !line.contains(' ')
+            && line.contains(['/', '\\', '.'])
+        {
+            stylish_file = Some(line.to_string());
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(
+        path: &str,
+        line: u32,
+        column: Option<u32>,
+        severity: LintSeverity,
+        message: &str,
+        rule: Option<&str>,
+    ) -> LintIssue {
+        LintIssue {
+            path: path.to_string(),
+            line: Some(line),
+            column,
+            severity,
+            message: message.to_string(),
+            rule: rule.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn issues_are_read_from_common_formats() {
+        let clippy = "\
+warning: unneeded `return` statement
+ --> src/main.rs:3:5
+  |
+3 |     return 1;
+  |     ^^^^^^^^^
+  = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#needless_return
+error[E0308]: mismatched types
+ --> src/lib.rs:10:9
+warning: `demo` (bin \"demo\") generated 1 warning
+error: could not compile `demo` (lib) due to 1 previous error";
+        assert_eq!(
+            parse_issues(clippy),
+            vec![
+                issue(
+                    "src/main.rs",
+                    3,
+                    Some(5),
+                    LintSeverity::Warning,
+                    "unneeded `return` statement",
+                    Some("clippy::needless_return"),
+                ),
+                issue(
+                    "src/lib.rs",
+                    10,
+                    Some(9),
+                    LintSeverity::Error,
+                    "mismatched types",
+                    Some("E0308"),
+                ),
+            ]
+        );
+
+        let mypy_and_ruff = "\
+app/models.py:12: error: Incompatible return value type (got \"str\", expected \"int\")  [return-value]
+app/models.py:12: note: See https://mypy.rtfd.io
+app/views.py:1:8: F401 [*] `os` imported but unused
+Found 2 errors in 1 file (checked 3 source files)";
+        assert_eq!(
+            parse_issues(mypy_and_ruff),
+            vec![
+                issue(
+                    "app/models.py",
+                    12,
+                    None,
+                    LintSeverity::Error,
+                    "Incompatible return value type (got \"str\", expected \"int\")",
+                    Some("return-value"),
+                ),
+                issue(
+                    "app/models.py",
+                    12,
+                    None,
+                    LintSeverity::Note,
+                    "See https://mypy.rtfd.io",
+                    None,
+                ),
+                issue(
+                    "app/views.py",
+                    1,
+                    Some(8),
+                    LintSeverity::Error,
+                    "`os` imported but unused",
+                    Some("F401"),
+                ),
+            ]
+        );
+
+        let eslint = "\
+/repo/src/App.tsx
+  4:10  warning  'useMemo' is defined but never used  @typescript-eslint/no-unused-vars
+  9:1   error    Expected indentation of 2 spaces     indent
+
+src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.
+
+✖ 2 problems (1 error, 1 warning)";
+        assert_eq!(
+            parse_issues(eslint),
+            vec![
+                issue(
+                    "/repo/src/App.tsx",
+                    4,
+                    Some(10),
+                    LintSeverity::Warning,
+                    "'useMemo' is defined but never used",
+                    Some("@typescript-eslint/no-unused-vars"),
+                ),
+                issue(
+                    "/repo/src/App.tsx",
+                    9,
+                    Some(1),
+                    LintSeverity::Error,
+                    "Expected indentation of 2 spaces",
+                    Some("indent"),
+                ),
+                issue(
+                    "src/index.ts",
+                    3,
+                    Some(7),
+                    LintSeverity::Error,
+                    "Type 'string' is not assignable to type 'number'.",
+                    Some("TS2322"),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fix_prompt_lists_what_failed() {
+        let results = vec![
+            LintCheckResult {
+                name: "clippy".to_string(),
+                command: "cargo clippy -- -D warnings".to_string(),
+                status: LintCheckStatus::Failed,
+                exit_code: Some(101),
+                issues: vec![issue(
+                    "src/main.rs",
+                    3,
+                    Some(5),
+                    LintSeverity::Error,
+                    "unneeded `return` statement",
+                    Some("clippy::needless_return"),
+                )],
+                output: String::new(),
+            },
+            LintCheckResult {
+                name: "format".to_string(),
+                command: "cargo fmt --check".to_string(),
+                status: LintCheckStatus::Failed,
+                exit_code: Some(1),
+                issues: Vec::new(),
+                output: "Diff in src/main.rs at line 1:\n".to_string(),
+            },
+            LintCheckResult {
+                name: "mypy".to_string(),
+                command: "mypy .".to_string(),
+                status: LintCheckStatus::NotRun,
+                exit_code: None,
+                issues: Vec::new(),
+                output: "mypy is not allowed".to_string(),
+            },
+        ];
+        assert!(!passed(&results));
+        assert!(needs_fix(&results));
+        assert_eq!(
+            fix_prompt(&results),
+            "The lint checks run on your changes failed. Fix what they report, keeping the changes you made, and make sure the checks pass.\n\n\
+             clippy (`cargo clippy -- -D warnings`) failed:\n\
+             - src/main.rs:3:5: error: unneeded `return` statement [clippy::needless_return]\n\n\
+             format (`cargo fmt --check`) failed. The end of its output:\n```\nDiff in src/main.rs at line 1:\n```"
+        );
+    }
+}
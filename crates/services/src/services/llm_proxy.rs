@@ -0,0 +1,182 @@
+//! The parts of the LLM proxy that don't touch the network: what agents are
+//! pointed at, where a call goes, when to try the next provider, and what a
+//! call used and cost.
+//!
+//! Usage is read from the response as the agent got it, whether a JSON body
+//! or a stream of server-sent events, for both the Anthropic and the OpenAI
+//! APIs. Calls whose usage can't be read are logged as free.
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use db::models::project_settings::{LlmApi, LlmProvider};
+use serde_json::Value;
+
+use super::secrets::mask_secrets;
+
+/// Longest body kept in the log, per request and response
+const MAX_LOGGED_BODY_CHARS: usize = 64 * 1024;
+
+/// Environment pointing an agent's SDK at the proxy, with the attempt's
+/// token as the key
+pub fn agent_env(server_url: &str, token: &str) -> Vec<(&'static str, String)> {
+    let base = format!("{}/api/llm-proxy", server_url.trim_end_matches('/'));
+    vec![
+        ("ANTHROPIC_BASE_URL", format!("{base}/anthropic")),
+        ("ANTHROPIC_API_KEY", token.to_string()),
+        ("OPENAI_BASE_URL", format!("{base}/openai/v1")),
+        ("OPENAI_API_KEY", token.to_string()),
+    ]
+}
+
+/// Where a call to `path` (e.g. `v1/messages`) goes at the provider
+pub fn upstream_url(provider: &LlmProvider, path: &str, query: Option<&str>) -> String {
+    let base = provider
+        .base_url
+        .as_deref()
+        .unwrap_or(match provider.api {
+            LlmApi::Anthropic => "https://api.anthropic.com",
+            LlmApi::OpenAi => "https://api.openai.com",
+        })
+        .trim_end_matches('/');
+    let mut url = format!("{base}/{}", path.trim_start_matches('/'));
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    url
+}
+
+/// Whether a provider's answer is worth trying the next provider for: rate
+/// limits, timeouts and server errors
+pub fn fails_over(status: u16) -> bool {
+    matches!(status, 408 | 429) || status >= 500
+}
+
+/// The first instant of the month `now` is in, which the spend cap counts
+/// from
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallUsage {
+    pub model: Option<String>,
+    /// Including input written to and read from a cache
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+impl CallUsage {
+    /// Read from a response body: a JSON object, or server-sent events whose
+    /// data lines are
+    pub fn from_body(body: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(body);
+        let mut usage = Self::default();
+        match serde_json::from_str::<Value>(&text) {
+            Ok(value) => usage.add(&value),
+            Err(_) => {
+                for data in text
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("data:"))
+                    .map(str::trim)
+                {
+                    if let Ok(value) = serde_json::from_str::<Value>(data) {
+                        usage.add(&value);
+                    }
+                }
+            }
+        }
+        usage
+    }
+
+    /// Take in an event or body. Counts in a stream are running totals, so
+    /// the largest seen is kept.
+    fn add(&mut self, value: &Value) {
+        // Anthropic's stream starts with the message, OpenAI's Responses API
+        // ends with the response
+        let inner = ["message", "response"]
+            .iter()
+            .filter_map(|key| value.get(key))
+            .find(|inner| inner.is_object())
+            .unwrap_or(value);
+        for candidate in [value, inner] {
+            if let Some(model) = candidate.get("model").and_then(Value::as_str) {
+                self.model = Some(model.to_string());
+            }
+            let Some(usage) = candidate.get("usage").filter(|usage| usage.is_object()) else {
+                continue;
+            };
+            let count = |keys: &[&str]| -> i64 {
+                keys.iter()
+                    .filter_map(|key| usage.get(key).and_then(Value::as_i64))
+                    .sum()
+            };
+            let input = count(&[
+                "input_tokens",
+                "prompt_tokens",
+                "cache_creation_input_tokens",
+                "cache_read_input_tokens",
+            ]);
+            let output = count(&["output_tokens", "completion_tokens"]);
+            self.input_tokens = self.input_tokens.max(input);
+            self.output_tokens = self.output_tokens.max(output);
+        }
+    }
+
+    pub fn cost_usd(&self, provider: &LlmProvider) -> f64 {
+        (self.input_tokens as f64 * provider.input_usd_per_mtok
+            + self.output_tokens as f64 * provider.output_usd_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// A body as kept in the log: secrets masked and cut short
+pub fn logged_body(body: &[u8], secrets: &[String]) -> String {
+    let text = mask_secrets(&String::from_utf8_lossy(body), secrets);
+    match text.char_indices().nth(MAX_LOGGED_BODY_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_is_read_from_streams_and_bodies() {
+        let anthropic_stream = b"event: message_start\n\
+data: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-sonnet-4-5\",\"usage\":{\"input_tokens\":1200,\"cache_read_input_tokens\":800,\"output_tokens\":1}}}\n\n\
+event: content_block_delta\n\
+data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"Hi\"}}\n\n\
+event: message_delta\n\
+data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":350}}\n\n";
+        let usage = CallUsage::from_body(anthropic_stream);
+        assert_eq!(
+            usage,
+            CallUsage {
+                model: Some("claude-sonnet-4-5".to_string()),
+                input_tokens: 2000,
+                output_tokens: 350,
+            }
+        );
+
+        let provider = LlmProvider {
+            name: "openai".to_string(),
+            api: LlmApi::OpenAi,
+            base_url: None,
+            api_key_secret: "OPENAI_API_KEY".to_string(),
+            input_usd_per_mtok: 2.0,
+            output_usd_per_mtok: 8.0,
+        };
+        let usage = CallUsage::from_body(
+            br#"{"model":"gpt-4.1","usage":{"prompt_tokens":500000,"completion_tokens":125000}}"#,
+        );
+        assert_eq!(usage.cost_usd(&provider), 2.0);
+        assert_eq!(
+            upstream_url(&provider, "/v1/chat/completions", None),
+            "https://api.openai.com/v1/chat/completions"
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Local inference servers (Ollama, vLLM) registered as model backends, for
+//! running agents without a hosted API.
+//!
+//! An endpoint is checked by listing its models through the OpenAI-compatible
+//! API both servers serve. Each model can then be saved as a profile variant
+//! of the executors that can use it, built from the executor's default
+//! configuration; removing the endpoint leaves those variants in place.
+
+use std::time::{Duration, Instant};
+
+use executors::{
+    executors::{BaseCodingAgent, CodingAgent},
+    profile::{ExecutorConfigs, ExecutorProfileId, ProfileError, canonical_variant_key},
+};
+use serde::Serialize;
+use serde_json::Value;
+use ts_rs::TS;
+use url::Url;
+
+use super::config::{LocalModelEndpoint, LocalModelServer};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct EndpointHealth {
+    pub endpoint: LocalModelEndpoint,
+    /// The server answered with its models
+    pub available: bool,
+    /// How long listing the models took
+    pub latency_ms: Option<u32>,
+    pub models: Vec<String>,
+    pub error: Option<String>,
+    /// Each model with each executor that can use it
+    pub backends: Vec<LocalModelBackend>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LocalModelBackend {
+    pub executor: BaseCodingAgent,
+    pub model: String,
+    /// The profile variant using the model; None until it is saved
+    pub variant: Option<String>,
+}
+
+/// The OpenAI-compatible API of the server
+pub fn openai_base_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    if url.ends_with("/v1") {
+        url.to_string()
+    } else {
+        format!("{url}/v1")
+    }
+}
+
+/// Whether the endpoint can be registered next to the existing ones
+pub fn validate_endpoint(
+    endpoint: &LocalModelEndpoint,
+    existing: &[LocalModelEndpoint],
+) -> Result<(), String> {
+    if endpoint.name.trim().is_empty() {
+        return Err("Name the endpoint".to_string());
+    }
+    if existing.iter().any(|other| other.name == endpoint.name) {
+        return Err(format!("An endpoint named {} exists", endpoint.name));
+    }
+    match Url::parse(&endpoint.url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        _ => Err(format!("{} isn't an http(s) URL", endpoint.url)),
+    }
+}
+
+/// The executors that can run on models of the server
+pub fn compatible_executors(server: LocalModelServer) -> &'static [BaseCodingAgent] {
+    match server {
+        LocalModelServer::Ollama => &[
+            BaseCodingAgent::ApiAgent,
+            BaseCodingAgent::Openhands,
+            BaseCodingAgent::Goose,
+            BaseCodingAgent::Codex,
+        ],
+        LocalModelServer::Vllm => &[BaseCodingAgent::ApiAgent, BaseCodingAgent::Openhands],
+    }
+}
+
+/// The profile variant a model of the endpoint is saved as
+pub fn variant_name(endpoint: &LocalModelEndpoint, model: &str) -> String {
+    let raw: String = format!("{} {model}", endpoint.name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    canonical_variant_key(raw)
+}
+
+/// The executor's configuration pointed at a model of the endpoint; None when
+/// the executor can't use the server
+pub fn backend_config(
+    agent: CodingAgent,
+    endpoint: &LocalModelEndpoint,
+    model: &str,
+) -> Option<CodingAgent> {
+    let base_url = openai_base_url(&endpoint.url);
+    let backend = match agent {
+        CodingAgent::ApiAgent(mut agent) => {
+            agent.base_url = Some(base_url);
+            agent.model = Some(model.to_string());
+            CodingAgent::ApiAgent(agent)
+        }
+        CodingAgent::Openhands(mut agent) => {
+            agent.base_url = Some(base_url);
+            agent.model = Some(format!("openai/{model}"));
+            // LiteLLM wants a key even when the server takes none
+            agent
+                .cmd
+                .env
+                .get_or_insert_with(Default::default)
+                .entry("LLM_API_KEY".to_string())
+                .or_insert_with(|| "local".to_string());
+            CodingAgent::Openhands(agent)
+        }
+        CodingAgent::Goose(mut agent) if endpoint.server == LocalModelServer::Ollama => {
+            agent.provider = Some("ollama".to_string());
+            agent.model = Some(model.to_string());
+            agent
+                .cmd
+                .env
+                .get_or_insert_with(Default::default)
+                .insert("OLLAMA_HOST".to_string(), endpoint.url.clone());
+            CodingAgent::Goose(agent)
+        }
+        CodingAgent::Codex(mut agent) if endpoint.server == LocalModelServer::Ollama => {
+            agent.oss = Some(true);
+            agent.model = Some(model.to_string());
+            agent
+                .cmd
+                .env
+                .get_or_insert_with(Default::default)
+                .insert("CODEX_OSS_BASE_URL".to_string(), base_url);
+            CodingAgent::Codex(agent)
+        }
+        _ => return None,
+    };
+    Some(backend)
+}
+
+/// Check every endpoint at once
+pub async fn check_endpoints(endpoints: &[LocalModelEndpoint]) -> Vec<EndpointHealth> {
+    let profiles = ExecutorConfigs::get_cached();
+    futures::future::join_all(
+        endpoints
+            .iter()
+            .map(|endpoint| check_endpoint(endpoint, &profiles)),
+    )
+    .await
+}
+
+pub async fn check_endpoint(
+    endpoint: &LocalModelEndpoint,
+    profiles: &ExecutorConfigs,
+) -> EndpointHealth {
+    let started = Instant::now();
+    let listed = list_models(endpoint).await;
+    let latency_ms = started.elapsed().as_millis() as u32;
+    let (models, error) = match listed {
+        Ok(models) => (models, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    let backends = models
+        .iter()
+        .flat_map(|model| {
+            compatible_executors(endpoint.server)
+                .iter()
+                .map(move |executor| {
+                    let variant = variant_name(endpoint, model);
+                    let saved = profiles
+                        .executors
+                        .get(executor)
+                        .is_some_and(|config| config.configurations.contains_key(&variant));
+                    LocalModelBackend {
+                        executor: *executor,
+                        model: model.clone(),
+                        variant: saved.then_some(variant),
+                    }
+                })
+        })
+        .collect();
+    EndpointHealth {
+        endpoint: endpoint.clone(),
+        available: error.is_none(),
+        latency_ms: error.is_none().then_some(latency_ms),
+        models,
+        error,
+        backends,
+    }
+}
+
+async fn list_models(endpoint: &LocalModelEndpoint) -> Result<Vec<String>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .get(format!("{}/models", openai_base_url(&endpoint.url)))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("The server answered {status}"));
+    }
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    let mut models: Vec<String> = body["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| model["id"].as_str().map(str::to_string))
+        .collect();
+    models.sort();
+    Ok(models)
+}
+
+/// Save a model of the endpoint as a profile variant of the executor
+pub fn save_backend(
+    endpoint: &LocalModelEndpoint,
+    executor: BaseCodingAgent,
+    model: &str,
+) -> Result<ExecutorProfileId, ProfileError> {
+    let mut profiles = ExecutorConfigs::get_cached();
+    let config = profiles
+        .get_coding_agent(&ExecutorProfileId::new(executor))
+        .and_then(|agent| backend_config(agent, endpoint, model))
+        .ok_or_else(|| {
+            ProfileError::Validation(format!("{executor} can't use models of {}", endpoint.name))
+        })?;
+    let variant = variant_name(endpoint, model);
+    profiles
+        .executors
+        .get_mut(&executor)
+        .ok_or(ProfileError::NoAvailableExecutorProfile)?
+        .set_variant(variant.clone(), config)
+        .map_err(|e| ProfileError::Validation(e.to_string()))?;
+    profiles.save_overrides()?;
+    ExecutorConfigs::reload();
+    Ok(ExecutorProfileId::with_variant(executor, variant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_are_validated_and_named() {
+        let ollama = LocalModelEndpoint {
+            name: "Workstation".to_string(),
+            server: LocalModelServer::Ollama,
+            url: "http://localhost:11434/".to_string(),
+        };
+        assert_eq!(openai_base_url(&ollama.url), "http://localhost:11434/v1");
+        assert_eq!(
+            openai_base_url("http://gpu-box:8000/v1"),
+            "http://gpu-box:8000/v1"
+        );
+        assert!(validate_endpoint(&ollama, &[]).is_ok());
+        assert!(validate_endpoint(&ollama, std::slice::from_ref(&ollama)).is_err());
+
+        let vllm = LocalModelEndpoint {
+            name: "gpu".to_string(),
+            server: LocalModelServer::Vllm,
+            url: "gpu-box:8000".to_string(),
+        };
+        assert!(validate_endpoint(&vllm, &[]).is_err());
+        assert!(!compatible_executors(vllm.server).contains(&BaseCodingAgent::Goose));
+
+        let variant = variant_name(&ollama, "qwen3-coder:30b");
+        assert!(variant.starts_with("WORKSTATION_QWEN"));
+        assert!(
+            variant
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        );
+    }
+}
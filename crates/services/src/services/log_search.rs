@@ -0,0 +1,298 @@
+//! Querying the conversation entries of a coding agent run on the server, so
+//! the browser can page through matches of long logs instead of loading all
+//! of them.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use executors::logs::{ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+/// How much attention an entry needs, least first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum LogLevel {
+    /// Thinking, system messages and progress
+    Debug,
+    /// Messages, tool calls and their results
+    Info,
+    /// Errors, and tool calls that failed or weren't allowed
+    Error,
+}
+
+impl LogLevel {
+    pub fn of(entry: &NormalizedEntry) -> Self {
+        match &entry.entry_type {
+            NormalizedEntryType::ErrorMessage { .. } => LogLevel::Error,
+            NormalizedEntryType::ToolUse { status, .. } => match status {
+                ToolStatus::Failed | ToolStatus::Denied { .. } | ToolStatus::TimedOut => {
+                    LogLevel::Error
+                }
+                _ => LogLevel::Info,
+            },
+            NormalizedEntryType::NextAction { failed: true, .. } => LogLevel::Error,
+            NormalizedEntryType::Thinking
+            | NormalizedEntryType::SystemMessage
+            | NormalizedEntryType::Loading => LogLevel::Debug,
+            NormalizedEntryType::UserMessage
+            | NormalizedEntryType::UserFeedback { .. }
+            | NormalizedEntryType::AssistantMessage
+            | NormalizedEntryType::NextAction { .. } => LogLevel::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct LogSearchQuery {
+    /// Text the entry contains, ignoring case. Tool names and command output
+    /// are searched too.
+    pub q: Option<String>,
+    /// Least level of the entries
+    pub level: Option<LogLevel>,
+    /// Tool calls of this action, such as `command_run` or `file_edit`, or of
+    /// the tool with this name
+    pub tool: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub offset: usize,
+    /// Entries per page, 100 unless given and at most 1000
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogSearchEntry {
+    /// Position of the entry in the conversation
+    pub index: usize,
+    pub level: LogLevel,
+    pub entry: NormalizedEntry,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LogSearchPage {
+    pub entries: Vec<LogSearchEntry>,
+    /// Entries matching the query across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl LogSearchQuery {
+    fn matches(&self, entry: &NormalizedEntry) -> bool {
+        if self.level.is_some_and(|level| LogLevel::of(entry) < level) {
+            return false;
+        }
+        if let Some(tool) = &self.tool {
+            let NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type,
+                ..
+            } = &entry.entry_type
+            else {
+                return false;
+            };
+            let action = serde_json::to_value(action_type)
+                .ok()
+                .and_then(|value| value.get("action")?.as_str().map(str::to_string));
+            if !tool_name.eq_ignore_ascii_case(tool) && action.as_deref() != Some(tool.as_str()) {
+                return false;
+            }
+        }
+        if self.since.is_some() || self.until.is_some() {
+            // Entries without a time can't be placed in the range
+            let Some(at) = entry
+                .timestamp
+                .as_deref()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map(|at| at.with_timezone(&Utc))
+            else {
+                return false;
+            };
+            if self.since.is_some_and(|since| at < since)
+                || self.until.is_some_and(|until| at > until)
+            {
+                return false;
+            }
+        }
+        match self.q.as_deref().map(str::trim) {
+            Some(q) if !q.is_empty() => searchable_text(entry)
+                .iter()
+                .any(|text| contains_ignoring_case(text, q)),
+            _ => true,
+        }
+    }
+
+    /// The page of matching entries the query asks for, in conversation order
+    pub fn run(&self, entries: &BTreeMap<usize, NormalizedEntry>) -> LogSearchPage {
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let matching: Vec<_> = entries
+            .iter()
+            .filter(|(_, entry)| self.matches(entry))
+            .collect();
+        LogSearchPage {
+            total: matching.len(),
+            entries: matching
+                .into_iter()
+                .skip(self.offset)
+                .take(limit)
+                .map(|(index, entry)| LogSearchEntry {
+                    index: *index,
+                    level: LogLevel::of(entry),
+                    entry: entry.clone(),
+                })
+                .collect(),
+            offset: self.offset,
+            limit,
+        }
+    }
+}
+
+fn searchable_text(entry: &NormalizedEntry) -> Vec<&str> {
+    let mut texts = vec![entry.content.as_str()];
+    if let NormalizedEntryType::ToolUse {
+        tool_name,
+        action_type,
+        ..
+    } = &entry.entry_type
+    {
+        texts.push(tool_name);
+        if let ActionType::CommandRun { command, result } = action_type {
+            texts.push(command);
+            if let Some(output) = result.as_ref().and_then(|result| result.output.as_deref()) {
+                texts.push(output);
+            }
+        }
+    }
+    texts
+}
+
+fn contains_ignoring_case(text: &str, needle: &str) -> bool {
+    text.to_lowercase().contains(&needle.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::CommandRunResult;
+
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str, at: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: Some(at.to_string()),
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn command(command: &str, output: &str, status: ToolStatus) -> NormalizedEntryType {
+        NormalizedEntryType::ToolUse {
+            tool_name: "Bash".to_string(),
+            action_type: ActionType::CommandRun {
+                command: command.to_string(),
+                result: Some(CommandRunResult {
+                    exit_status: None,
+                    output: Some(output.to_string()),
+                }),
+            },
+            status,
+        }
+    }
+
+    #[test]
+    fn entries_are_filtered_and_paged() {
+        let entries = BTreeMap::from([
+            (
+                0,
+                entry(
+                    NormalizedEntryType::AssistantMessage,
+                    "Running the tests",
+                    "2025-12-22T09:00:00Z",
+                ),
+            ),
+            (
+                1,
+                entry(
+                    command("cargo test", "test result: FAILED", ToolStatus::Failed),
+                    "cargo test",
+                    "2025-12-22T09:01:00Z",
+                ),
+            ),
+            (
+                2,
+                entry(
+                    NormalizedEntryType::Thinking,
+                    "The test fails",
+                    "2025-12-22T09:02:00Z",
+                ),
+            ),
+            (
+                3,
+                entry(
+                    command("cargo fmt", "", ToolStatus::Success),
+                    "cargo fmt",
+                    "2025-12-22T09:03:00Z",
+                ),
+            ),
+        ]);
+        let indices = |query: LogSearchQuery| -> Vec<usize> {
+            query
+                .run(&entries)
+                .entries
+                .iter()
+                .map(|e| e.index)
+                .collect()
+        };
+
+        let text = |q: &str| LogSearchQuery {
+            q: Some(q.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(indices(text("TEST")), vec![0, 1, 2]);
+        assert_eq!(indices(text("FAILED")), vec![1]);
+
+        let level = LogSearchQuery {
+            level: Some(LogLevel::Info),
+            ..Default::default()
+        };
+        assert_eq!(indices(level), vec![0, 1, 3]);
+
+        let errors = LogSearchQuery {
+            level: Some(LogLevel::Error),
+            ..Default::default()
+        };
+        assert_eq!(indices(errors), vec![1]);
+
+        let tool = |tool: &str| LogSearchQuery {
+            tool: Some(tool.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(indices(tool("command_run")), vec![1, 3]);
+        assert_eq!(indices(tool("bash")), vec![1, 3]);
+        assert!(indices(tool("file_edit")).is_empty());
+
+        let range = LogSearchQuery {
+            since: Some("2025-12-22T09:01:00Z".parse().unwrap()),
+            until: Some("2025-12-22T09:02:30Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(indices(range), vec![1, 2]);
+
+        let page = LogSearchQuery {
+            offset: 1,
+            limit: Some(2),
+            ..Default::default()
+        }
+        .run(&entries);
+        assert_eq!(page.total, 4);
+        assert_eq!(
+            page.entries.iter().map(|e| e.index).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+}
@@ -1,25 +1,76 @@
+pub mod agent_review;
 pub mod analytics;
 pub mod approvals;
+pub mod attempt_budget;
+pub mod attempt_comparison;
+pub mod attempt_queue;
 pub mod auth;
+pub mod azure_devops;
+pub mod bitbucket;
+pub mod board_history;
+pub mod board_transfer;
+pub mod branch_sync;
+pub mod cherry_pick;
+pub mod ci_checks;
 pub mod code_server;
+pub mod commit_message;
 pub mod config;
 pub mod container;
 pub mod diff_stream;
+pub mod dry_run;
+pub mod due_reminders;
+pub mod epic_progress;
 pub mod events;
+pub mod file_events;
 pub mod file_ranker;
 pub mod file_search_cache;
 pub mod filesystem;
 pub mod filesystem_watcher;
 pub mod git;
 pub mod github;
+pub mod github_issue_import;
+pub mod gitlab;
 pub mod image;
+pub mod lifecycle_hooks;
+pub mod lint_gate;
+pub mod llm_proxy;
+pub mod local_models;
+pub mod log_search;
 pub mod notification;
 pub mod oauth_credentials;
+pub mod partial_merge;
+pub mod path_guard;
 pub mod pr_monitor;
+pub mod previous_attempt;
+pub mod process_registry;
 pub mod project;
+pub mod project_cleanup;
 pub mod queued_message;
+pub mod rebase_conflicts;
 pub mod remote_client;
 pub mod repo;
+pub mod secrets;
+pub mod session_replay;
 pub mod share;
+pub mod sparse_checkout;
+pub mod swimlanes;
+pub mod task_duplicates;
+pub mod task_plan;
+pub mod task_scheduler;
+pub mod task_scope;
+pub mod terminal;
+pub mod time_report;
+pub mod upload_scan;
+pub mod usage_report;
+pub mod variant_stats;
+pub mod workspace_archive;
+pub mod workspace_bundle;
+pub mod workspace_exec;
+pub mod workspace_files;
+pub mod workspace_janitor;
 pub mod workspace_manager;
+pub mod workspace_pool;
+pub mod workspace_provisioning;
+pub mod workspace_setup;
+pub mod worktree_maintenance;
 pub mod worktree_manager;
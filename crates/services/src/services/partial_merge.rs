@@ -0,0 +1,289 @@
+//! Partial merges: files and hunks picked from an attempt's diff are merged,
+//! or opened as a pull request, on their own while the rest of the changes
+//! stay on the attempt's branch. The picks are rebuilt into a patch of the
+//! diff they were made from, so they apply the same way each time.
+
+use std::{collections::HashSet, path::Path};
+
+use db::models::merge_selection::SelectedFile;
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+
+use super::git::{GitService, GitServiceError};
+
+#[derive(Debug, Error)]
+pub enum SelectionError {
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error("Select at least one file")]
+    Empty,
+    #[error("'{0}' isn't changed by the attempt")]
+    UnknownFile(String),
+    #[error("'{0}' is selected more than once")]
+    DuplicateFile(String),
+    #[error("'{0}' is binary and can only be selected whole")]
+    BinaryHunks(String),
+    #[error("Select at least one hunk of '{0}'")]
+    NoHunks(String),
+    #[error("'{path}' has no hunk {index}")]
+    UnknownHunk { path: String, index: usize },
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DiffHunk {
+    /// The `@@ -a,b +c,d @@` line
+    pub header: String,
+    pub body: String,
+}
+
+/// A changed file of the diff, its hunks indexed from 0 in order
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SelectableFile {
+    pub path: String,
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+    /// The file's lines before its first hunk: the `diff --git` line, modes
+    /// and the `---`/`+++` lines, or a binary patch
+    #[serde(skip)]
+    #[ts(skip)]
+    preamble: String,
+}
+
+/// The changed files between the two commits
+pub fn selectable_files(
+    git: &GitService,
+    repo_path: &Path,
+    base_commit: &str,
+    head_commit: &str,
+) -> Result<Vec<SelectableFile>, GitServiceError> {
+    Ok(parse_diff(&git.diff_patch(
+        repo_path,
+        base_commit,
+        head_commit,
+    )?))
+}
+
+/// Patch of the selected files and hunks of the diff between the two commits
+pub fn selection_patch(
+    git: &GitService,
+    repo_path: &Path,
+    base_commit: &str,
+    head_commit: &str,
+    selection: &[SelectedFile],
+) -> Result<String, SelectionError> {
+    let files = selectable_files(git, repo_path, base_commit, head_commit)?;
+    build_patch(&files, selection)
+}
+
+pub fn parse_diff(diff: &str) -> Vec<SelectableFile> {
+    let mut files: Vec<SelectableFile> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            files.push(SelectableFile {
+                path: path_of(paths.trim_end_matches('\n')),
+                binary: false,
+                hunks: Vec::new(),
+                preamble: line.to_string(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if !file.binary && line.starts_with("@@ ") {
+            file.hunks.push(DiffHunk {
+                header: line.trim_end_matches('\n').to_string(),
+                body: String::new(),
+            });
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.body.push_str(line);
+        } else {
+            if line.starts_with("GIT binary patch") || line.starts_with("Binary files ") {
+                file.binary = true;
+            }
+            file.preamble.push_str(line);
+        }
+    }
+    files
+}
+
+/// The path of a `diff --git a/<path> b/<path>` line, which is the same on
+/// both sides without renames. Both sides are quoted when the path has
+/// special characters.
+fn path_of(paths: &str) -> String {
+    let (prefix, extra) = if paths.starts_with('"') {
+        (3, 9)
+    } else {
+        (2, 5)
+    };
+    let len = paths.len().saturating_sub(extra) / 2;
+    paths.get(prefix..prefix + len).unwrap_or(paths).to_string()
+}
+
+/// Patch of the selected files and hunks. Hunks that follow a left-out one
+/// have their new line numbers shifted back by the lines it would've added.
+pub fn build_patch(
+    files: &[SelectableFile],
+    selection: &[SelectedFile],
+) -> Result<String, SelectionError> {
+    if selection.is_empty() {
+        return Err(SelectionError::Empty);
+    }
+    let mut seen = HashSet::new();
+    let mut patch = String::new();
+    for selected in selection {
+        if !seen.insert(selected.path.as_str()) {
+            return Err(SelectionError::DuplicateFile(selected.path.clone()));
+        }
+        let file = files
+            .iter()
+            .find(|f| f.path == selected.path)
+            .ok_or_else(|| SelectionError::UnknownFile(selected.path.clone()))?;
+        patch.push_str(&file.preamble);
+
+        let Some(indexes) = &selected.hunks else {
+            for hunk in &file.hunks {
+                patch.push_str(&hunk.header);
+                patch.push('\n');
+                patch.push_str(&hunk.body);
+            }
+            continue;
+        };
+        if file.binary {
+            return Err(SelectionError::BinaryHunks(file.path.clone()));
+        }
+        if indexes.is_empty() {
+            return Err(SelectionError::NoHunks(file.path.clone()));
+        }
+        if let Some(&index) = indexes.iter().find(|&&i| i >= file.hunks.len()) {
+            return Err(SelectionError::UnknownHunk {
+                path: file.path.clone(),
+                index,
+            });
+        }
+        let mut shift: i64 = 0;
+        for (i, hunk) in file.hunks.iter().enumerate() {
+            let Some(range) = HunkRange::parse(&hunk.header) else {
+                continue;
+            };
+            if !indexes.contains(&i) {
+                shift += range.new_count as i64 - range.old_count as i64;
+                continue;
+            }
+            patch.push_str(&range.header_shifted_by(shift));
+            patch.push('\n');
+            patch.push_str(&hunk.body);
+        }
+    }
+    Ok(patch)
+}
+
+struct HunkRange<'a> {
+    old_start: u64,
+    old_count: u64,
+    new_start: u64,
+    new_count: u64,
+    /// What follows the closing `@@`, usually the enclosing function
+    section: &'a str,
+}
+
+impl<'a> HunkRange<'a> {
+    fn parse(header: &'a str) -> Option<Self> {
+        let rest = header.strip_prefix("@@ -")?;
+        let (ranges, section) = rest.split_once(" @@")?;
+        let (old, new) = ranges.split_once(" +")?;
+        let (old_start, old_count) = Self::parse_range(old)?;
+        let (new_start, new_count) = Self::parse_range(new)?;
+        Some(Self {
+            old_start,
+            old_count,
+            new_start,
+            new_count,
+            section,
+        })
+    }
+
+    /// `start[,count]`, the count being 1 when left out
+    fn parse_range(range: &str) -> Option<(u64, u64)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    }
+
+    fn header_shifted_by(&self, shift: i64) -> String {
+        let new_start = (self.new_start as i64 - shift).max(0);
+        format!(
+            "@@ -{},{} +{},{} @@{}",
+            self.old_start, self.old_count, new_start, self.new_count, self.section
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ use std::fs;
++use std::io;
+
+ fn a() {}
+@@ -10,3 +11,4 @@ fn b() {
+ fn c() {}
++fn d() {}
+
+ fn e() {}
+@@ -20,3 +22,4 @@ fn f() {
+ fn g() {}
++fn h() {}
+
+ fn i() {}
+diff --git a/logo.png b/logo.png
+index 3333333..4444444 100644
+GIT binary patch
+literal 5
+McmZ?wbhEhx00BD!1ONa4
+
+";
+
+    #[test]
+    fn left_out_hunks_shift_the_ones_after_them() {
+        let files = parse_diff(DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!(files[0].hunks.len(), 3);
+        assert!(files[1].binary);
+        assert!(files[1].hunks.is_empty());
+
+        let patch = build_patch(
+            &files,
+            &[SelectedFile {
+                path: "src/lib.rs".to_string(),
+                hunks: Some(vec![2]),
+            }],
+        )
+        .unwrap();
+        assert!(patch.starts_with("diff --git a/src/lib.rs b/src/lib.rs\n"));
+        assert!(patch.contains("@@ -20,3 +20,4 @@ fn f() {\n fn g() {}\n+fn h() {}\n"));
+        assert!(!patch.contains("+use std::io;"));
+        assert!(!patch.contains("+fn d() {}"));
+
+        assert!(matches!(
+            build_patch(
+                &files,
+                &[SelectedFile {
+                    path: "logo.png".to_string(),
+                    hunks: Some(vec![0]),
+                }],
+            ),
+            Err(SelectionError::BinaryHunks(_))
+        ));
+    }
+}
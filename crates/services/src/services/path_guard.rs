@@ -0,0 +1,90 @@
+//! The project's protected paths. Changes an agent makes to them, such as to
+//! CI workflows or credentials, are left uncommitted and the attempt can't be
+//! pushed or merged until the user overrides the guard.
+//!
+//! Patterns match like a task's scope does. Paths let through once stay
+//! allowed for the rest of the attempt.
+
+use std::collections::BTreeSet;
+
+use db::models::{path_guard_block::PathGuardBlock, workspace::Workspace};
+use sqlx::SqlitePool;
+
+use super::{
+    attempt_comparison::{ChangedFile, changed_files},
+    git::GitService,
+    task_scope::PathScope,
+};
+
+/// Protected paths the attempt changed that weren't let through yet,
+/// prefixed with their repository
+pub async fn guarded_changes(
+    pool: &SqlitePool,
+    git: &GitService,
+    workspace: &Workspace,
+    patterns: &[String],
+) -> Result<Vec<String>, sqlx::Error> {
+    // Patterns are checked when the settings are saved
+    let Some(protected) = PathScope::new(patterns).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring the protected paths of {}: {}", workspace.id, e);
+        None
+    }) else {
+        return Ok(Vec::new());
+    };
+    let changed = changed_files(pool, git, workspace).await?;
+    let overridden = PathGuardBlock::find_overridden_paths(pool, workspace.id).await?;
+    Ok(guarded_paths(&protected, &changed, &overridden))
+}
+
+fn guarded_paths(
+    protected: &PathScope,
+    changed: &[ChangedFile],
+    overridden: &[String],
+) -> Vec<String> {
+    changed
+        .iter()
+        .filter(|file| protected.allows(&file.repo_name, &file.path))
+        .map(|file| format!("{}/{}", file.repo_name, file.path))
+        .filter(|path| !overridden.contains(path))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(repo_name: &str, path: &str) -> ChangedFile {
+        ChangedFile {
+            repo_name: repo_name.to_string(),
+            path: path.to_string(),
+            additions: 1,
+            deletions: 0,
+        }
+    }
+
+    #[test]
+    fn protected_changes_wait_until_overridden() {
+        let patterns = vec![".github/workflows/".to_string(), ".env*".to_string()];
+        let protected = PathScope::new(&patterns).unwrap().unwrap();
+        let changes = vec![
+            changed("app", ".github/workflows/ci.yml"),
+            changed("app", "config/.env.production"),
+            changed("app", "src/main.rs"),
+        ];
+
+        assert_eq!(
+            guarded_paths(&protected, &changes, &[]),
+            vec!["app/.github/workflows/ci.yml", "app/config/.env.production"]
+        );
+        assert_eq!(
+            guarded_paths(
+                &protected,
+                &changes,
+                &["app/.github/workflows/ci.yml".to_string()]
+            ),
+            vec!["app/config/.env.production"]
+        );
+    }
+}
@@ -3,7 +3,9 @@ use std::time::Duration;
 use db::{
     DBService,
     models::{
-        merge::{Merge, MergeStatus, PrMerge},
+        ci_check::CiCheck,
+        hook_run::HookEvent,
+        merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
         task::{Task, TaskStatus},
         workspace::{Workspace, WorkspaceError},
     },
@@ -12,11 +14,17 @@ use serde_json::json;
 use sqlx::error::Error as SqlxError;
 use thiserror::Error;
 use tokio::time::interval;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
 use crate::services::{
     analytics::AnalyticsContext,
+    azure_devops::{self, AzureDevOpsService, AzureDevOpsServiceError},
+    bitbucket::{self, BitbucketService, BitbucketServiceError},
     github::{GitHubService, GitHubServiceError},
+    github_issue_import,
+    gitlab::{self, GitLabService, GitLabServiceError},
+    lifecycle_hooks::{self, HookDetails},
     share::SharePublisher,
 };
 
@@ -25,6 +33,12 @@ enum PrMonitorError {
     #[error(transparent)]
     GitHubServiceError(#[from] GitHubServiceError),
     #[error(transparent)]
+    GitLabServiceError(#[from] GitLabServiceError),
+    #[error(transparent)]
+    BitbucketServiceError(#[from] BitbucketServiceError),
+    #[error(transparent)]
+    AzureDevOpsServiceError(#[from] AzureDevOpsServiceError),
+    #[error(transparent)]
     WorkspaceError(#[from] WorkspaceError),
     #[error(transparent)]
     Sqlx(#[from] SqlxError),
@@ -68,6 +82,9 @@ impl PrMonitorService {
             if let Err(e) = self.check_all_open_prs().await {
                 error!("Error checking open PRs: {}", e);
             }
+            if let Err(e) = github_issue_import::close_done_issues(&self.db.pool).await {
+                error!("Error closing GitHub issues of done tasks: {}", e);
+            }
         }
     }
 
@@ -95,18 +112,42 @@ impl PrMonitorService {
 
     /// Check the status of a specific PR
     async fn check_pr_status(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
-        // GitHubService now uses gh CLI, no token needed
-        let github_service = GitHubService::new()?;
-
-        let pr_status = github_service
-            .update_pr_status(&pr_merge.pr_info.url)
-            .await?;
+        let pr_status = if gitlab::is_merge_request_url(&pr_merge.pr_info.url) {
+            self.gitlab_mr_status(pr_merge).await?
+        } else if let Some((repo, id)) = bitbucket::parse_pull_request_url(&pr_merge.pr_info.url) {
+            let project_id = self.project_id(pr_merge).await?;
+            BitbucketService::for_project(&self.db.pool, project_id)
+                .await?
+                .get_pr(&repo, id)
+                .await?
+        } else if let Some((repo, id)) = azure_devops::parse_pull_request_url(&pr_merge.pr_info.url)
+        {
+            let project_id = self.project_id(pr_merge).await?;
+            let (azure, _) = AzureDevOpsService::for_project(&self.db.pool, project_id).await?;
+            azure.get_pr(&repo, id).await?
+        } else {
+            // GitHubService now uses gh CLI, no token needed
+            let github_service = GitHubService::new()?;
+            github_service
+                .update_pr_status(&pr_merge.pr_info.url)
+                .await?
+        };
 
         debug!(
             "PR #{} status: {:?} (was open)",
             pr_merge.pr_info.number, pr_status.status
         );
 
+        // The checks of a closed or merged PR no longer matter
+        if matches!(&pr_status.status, MergeStatus::Open)
+            && let Err(e) = self.poll_ci_checks(pr_merge).await
+        {
+            warn!(
+                "Error checking CI of PR #{} for workspace {}: {}",
+                pr_merge.pr_info.number, pr_merge.workspace_id, e
+            );
+        }
+
         // Update the PR status in the database
         if !matches!(&pr_status.status, MergeStatus::Open) {
             // Update merge status with the latest information from GitHub
@@ -114,7 +155,7 @@ impl PrMonitorService {
                 &self.db.pool,
                 pr_merge.id,
                 pr_status.status.clone(),
-                pr_status.merge_commit_sha,
+                pr_status.merge_commit_sha.clone(),
             )
             .await?;
 
@@ -128,6 +169,16 @@ impl PrMonitorService {
                     pr_merge.pr_info.number, workspace.task_id
                 );
                 Task::update_status(&self.db.pool, workspace.task_id, TaskStatus::Done).await?;
+                lifecycle_hooks::spawn_hooks(
+                    self.db.pool.clone(),
+                    workspace.clone(),
+                    HookEvent::AfterMerge,
+                    HookDetails {
+                        merge_commit: pr_status.merge_commit_sha,
+                        pr_url: Some(pr_merge.pr_info.url.clone()),
+                        ..Default::default()
+                    },
+                );
 
                 // Track analytics event
                 if let Some(analytics) = &self.analytics
@@ -158,4 +209,58 @@ impl PrMonitorService {
 
         Ok(())
     }
+
+    /// Record the CI checks of the PR's head commit. Only GitHub and GitLab
+    /// report them.
+    async fn poll_ci_checks(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
+        let url = &pr_merge.pr_info.url;
+        let (head_commit, checks) = if gitlab::is_merge_request_url(url) {
+            let project_id = self.project_id(pr_merge).await?;
+            let (gitlab, settings) = GitLabService::for_project(&self.db.pool, project_id).await?;
+            let (project, iid) = gitlab::parse_merge_request_url(url, settings.base_url.as_deref())
+                .ok_or_else(|| GitLabServiceError::UnsupportedRemote(url.clone()))?;
+            gitlab.get_merge_request_checks(&project, iid).await?
+        } else if bitbucket::parse_pull_request_url(url).is_some()
+            || azure_devops::parse_pull_request_url(url).is_some()
+        {
+            return Ok(());
+        } else {
+            GitHubService::new()?.get_pr_checks(url).await?
+        };
+        CiCheck::replace_for_merge(
+            &self.db.pool,
+            pr_merge.id,
+            pr_merge.workspace_id,
+            pr_merge.repo_id,
+            &head_commit,
+            &checks,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// The project whose credentials the PR is checked with
+    async fn project_id(&self, pr_merge: &PrMerge) -> Result<Uuid, PrMonitorError> {
+        let task = match Workspace::find_by_id(&self.db.pool, pr_merge.workspace_id).await? {
+            Some(workspace) => Task::find_by_id(&self.db.pool, workspace.task_id).await?,
+            None => None,
+        }
+        .ok_or(WorkspaceError::TaskNotFound)?;
+        Ok(task.project_id)
+    }
+
+    /// GitLab merge requests are checked with the token of their project
+    async fn gitlab_mr_status(
+        &self,
+        pr_merge: &PrMerge,
+    ) -> Result<PullRequestInfo, PrMonitorError> {
+        let project_id = self.project_id(pr_merge).await?;
+        let (gitlab, settings) = GitLabService::for_project(&self.db.pool, project_id).await?;
+        let (project, iid) =
+            gitlab::parse_merge_request_url(&pr_merge.pr_info.url, settings.base_url.as_deref())
+                .ok_or_else(|| {
+                    GitLabServiceError::UnsupportedRemote(pr_merge.pr_info.url.clone())
+                })?;
+        Ok(gitlab.get_merge_request(&project, iid).await?)
+    }
 }
@@ -0,0 +1,357 @@
+//! What a new attempt at a task is told of the attempt before it: where its
+//! agent said it got to, which tests it left failing and what reviewers said
+//! on its pull request.
+//!
+//! Review comments are fetched with the GitHub CLI, or from GitLab, Bitbucket
+//! or Azure DevOps, and left out when that fails or takes too long, so an attempt
+//! never waits on the code host to start.
+//! Test output is only kept for script runs; of tests the agent ran itself,
+//! the counts are all that's known.
+
+use std::time::Duration;
+
+use db::models::{
+    coding_agent_turn::CodingAgentTurn,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    execution_process_logs::ExecutionProcessLogs,
+    merge::Merge,
+    project_settings::PreviousAttemptContext,
+    repo::Repo,
+    task::Task,
+    test_run::TestRun,
+    workspace::{Workspace, WorkspaceError},
+};
+use executors::test_output::{TestRunner, strip_ansi};
+use sqlx::SqlitePool;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use super::{
+    azure_devops::{self, AzureDevOpsService},
+    bitbucket::{self, BitbucketService},
+    github::{GitHubService, UnifiedPrComment},
+    gitlab::{self, GitLabService},
+};
+
+const REVIEW_COMMENTS_TIMEOUT: Duration = Duration::from_secs(20);
+const MAX_REVIEW_COMMENTS: usize = 20;
+const MAX_COMMENT_CHARS: usize = 1000;
+const MAX_SUMMARY_CHARS: usize = 4000;
+const MAX_TEST_OUTPUT_LINES: usize = 60;
+
+#[derive(Debug, Clone, Default)]
+pub struct PreviousAttempt {
+    pub branch: String,
+    pub summary: Option<String>,
+    pub failing_tests: Option<FailingTests>,
+    /// Oldest first
+    pub review_comments: Vec<UnifiedPrComment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FailingTests {
+    pub runner: TestRunner,
+    pub passed: i64,
+    pub failed: i64,
+    /// The end of the run's output, if it was a script whose output is kept
+    pub output: Option<String>,
+}
+
+impl PreviousAttempt {
+    /// The attempt at the workspace's task made before it, with the parts
+    /// `settings` asks for. None for the task's first attempt.
+    pub async fn find(
+        pool: &SqlitePool,
+        workspace: &Workspace,
+        settings: &PreviousAttemptContext,
+    ) -> Result<Option<Self>, WorkspaceError> {
+        let Some(previous) = Workspace::fetch_all(pool, Some(workspace.task_id))
+            .await?
+            .into_iter()
+            .find(|other| other.id != workspace.id && other.created_at <= workspace.created_at)
+        else {
+            return Ok(None);
+        };
+
+        let summary = if settings.summary {
+            CodingAgentTurn::find_latest_summary_by_workspace_id(pool, previous.id).await?
+        } else {
+            None
+        };
+        let failing_tests = if settings.failing_tests {
+            failing_tests(pool, previous.id).await?
+        } else {
+            None
+        };
+        let review_comments = if settings.review_comments {
+            match tokio::time::timeout(REVIEW_COMMENTS_TIMEOUT, review_comments(pool, &previous))
+                .await
+            {
+                Ok(Ok(comments)) => comments,
+                Ok(Err(e)) => {
+                    tracing::warn!(
+                        "Failed to fetch review comments of workspace {}: {}",
+                        previous.id,
+                        e
+                    );
+                    Vec::new()
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "Timed out fetching review comments of workspace {}",
+                        previous.id
+                    );
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(Some(Self {
+            branch: previous.branch,
+            summary,
+            failing_tests,
+            review_comments,
+        }))
+    }
+
+    /// The text put before the new attempt's prompt, None if the previous
+    /// attempt left nothing worth telling
+    pub fn render(&self) -> Option<String> {
+        let summary = self
+            .summary
+            .as_deref()
+            .map(str::trim)
+            .filter(|summary| !summary.is_empty());
+        if summary.is_none() && self.failing_tests.is_none() && self.review_comments.is_empty() {
+            return None;
+        }
+
+        let mut sections = vec![format!(
+            "This task was attempted before, on branch `{}`. Continue from what that attempt found instead of starting over.",
+            self.branch
+        )];
+        if let Some(summary) = summary {
+            sections.push(format!(
+                "Where the previous attempt's agent left off:\n{}",
+                truncate(summary, MAX_SUMMARY_CHARS)
+            ));
+        }
+        if let Some(tests) = &self.failing_tests {
+            let mut section = format!(
+                "Its last test run ({}) had {} failing and {} passing tests.",
+                tests.runner, tests.failed, tests.passed
+            );
+            if let Some(output) = &tests.output {
+                section.push_str(&format!(" The end of its output:\n```\n{output}\n```"));
+            }
+            sections.push(section);
+        }
+        if !self.review_comments.is_empty() {
+            let skipped = self
+                .review_comments
+                .len()
+                .saturating_sub(MAX_REVIEW_COMMENTS);
+            let comments: Vec<String> = self.review_comments[skipped..]
+                .iter()
+                .map(render_comment)
+                .collect();
+            sections.push(format!(
+                "Review comments on its pull request:\n{}",
+                comments.join("\n")
+            ));
+        }
+        Some(sections.join("\n\n"))
+    }
+}
+
+fn render_comment(comment: &UnifiedPrComment) -> String {
+    match comment {
+        UnifiedPrComment::General { author, body, .. } => {
+            format!("- {author}: {}", truncate(body.trim(), MAX_COMMENT_CHARS))
+        }
+        UnifiedPrComment::Review {
+            author,
+            body,
+            path,
+            line,
+            ..
+        } => {
+            let location = match line {
+                Some(line) => format!("{path}:{line}"),
+                None => path.clone(),
+            };
+            format!(
+                "- {author} on {location}: {}",
+                truncate(body.trim(), MAX_COMMENT_CHARS)
+            )
+        }
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// The workspace's latest test run, if tests failed in it
+async fn failing_tests(
+    pool: &SqlitePool,
+    workspace_id: Uuid,
+) -> Result<Option<FailingTests>, sqlx::Error> {
+    let Some(run) = TestRun::find_by_workspace_id(pool, workspace_id)
+        .await?
+        .into_iter()
+        .next()
+        .filter(|run| run.failed > 0)
+    else {
+        return Ok(None);
+    };
+
+    let is_script = ExecutionProcess::find_by_id(pool, run.execution_process_id)
+        .await?
+        .is_some_and(|process| {
+            matches!(
+                process.run_reason,
+                ExecutionProcessRunReason::SetupScript | ExecutionProcessRunReason::CleanupScript
+            )
+        });
+    let output = if is_script {
+        let records =
+            ExecutionProcessLogs::find_by_execution_id(pool, run.execution_process_id).await?;
+        ExecutionProcessLogs::parse_logs(&records)
+            .ok()
+            .map(|messages| {
+                messages
+                    .into_iter()
+                    .filter_map(|msg| match msg {
+                        LogMsg::Stdout(text) | LogMsg::Stderr(text) => Some(text),
+                        _ => None,
+                    })
+                    .collect::<String>()
+            })
+            .map(|output| output_tail(&output, MAX_TEST_OUTPUT_LINES))
+            .filter(|tail| !tail.is_empty())
+    } else {
+        None
+    };
+
+    Ok(Some(FailingTests {
+        runner: run.runner,
+        passed: run.passed,
+        failed: run.failed,
+        output,
+    }))
+}
+
+/// The last `lines` non-blank lines of the output, without color codes
+fn output_tail(output: &str, lines: usize) -> String {
+    let mut tail: Vec<String> = output
+        .lines()
+        .rev()
+        .map(|line| strip_ansi(line).trim_end().to_string())
+        .filter(|line| !line.trim().is_empty())
+        .take(lines)
+        .collect();
+    tail.reverse();
+    tail.join("\n")
+}
+
+/// Comments on the pull requests opened from the workspace
+async fn review_comments(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+) -> anyhow::Result<Vec<UnifiedPrComment>> {
+    let project_id = Task::find_by_id(pool, workspace.task_id)
+        .await?
+        .ok_or(WorkspaceError::TaskNotFound)?
+        .project_id;
+    let mut comments = Vec::new();
+    for merge in Merge::find_by_workspace_id(pool, workspace.id).await? {
+        let Merge::Pr(pr) = merge else {
+            continue;
+        };
+        if gitlab::is_merge_request_url(&pr.pr_info.url) {
+            let (gitlab, settings) = GitLabService::for_project(pool, project_id).await?;
+            if let Some((project, iid)) =
+                gitlab::parse_merge_request_url(&pr.pr_info.url, settings.base_url.as_deref())
+            {
+                comments.extend(gitlab.get_mr_comments(&project, iid).await?);
+            }
+            continue;
+        }
+        if let Some((repo, id)) = bitbucket::parse_pull_request_url(&pr.pr_info.url) {
+            let bitbucket = BitbucketService::for_project(pool, project_id).await?;
+            comments.extend(bitbucket.get_pr_comments(&repo, id).await?);
+            continue;
+        }
+        if let Some((repo, id)) = azure_devops::parse_pull_request_url(&pr.pr_info.url) {
+            let (azure, _) = AzureDevOpsService::for_project(pool, project_id).await?;
+            comments.extend(azure.get_pr_comments(&repo, id).await?);
+            continue;
+        }
+        let Some(repo) = Repo::find_by_id(pool, pr.repo_id).await? else {
+            continue;
+        };
+        let github = GitHubService::new()?;
+        let repo_info = github.get_repo_info(&repo.path).await?;
+        comments.extend(
+            github
+                .get_pr_comments(&repo_info, pr.pr_info.number)
+                .await?,
+        );
+    }
+    Ok(comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn context_tells_what_the_previous_attempt_left() {
+        let attempt = PreviousAttempt {
+            branch: "vk/1a2b-fix-login".to_string(),
+            summary: Some("Added the session check; the redirect test still fails.\n".to_string()),
+            failing_tests: Some(FailingTests {
+                runner: TestRunner::Cargo,
+                passed: 41,
+                failed: 1,
+                output: Some(output_tail(
+                    "running 42 tests\n\u{1b}[31mtest login::redirects ... FAILED\u{1b}[0m\n\n",
+                    1,
+                )),
+            }),
+            review_comments: vec![UnifiedPrComment::Review {
+                id: 1,
+                author: "octocat".to_string(),
+                author_association: "MEMBER".to_string(),
+                body: "Keep the old cookie name.".to_string(),
+                created_at: Utc::now(),
+                url: String::new(),
+                path: "src/login.rs".to_string(),
+                line: Some(12),
+                diff_hunk: String::new(),
+            }],
+        };
+        assert_eq!(
+            attempt.render().unwrap(),
+            "This task was attempted before, on branch `vk/1a2b-fix-login`. Continue from what that attempt found instead of starting over.\n\n\
+             Where the previous attempt's agent left off:\nAdded the session check; the redirect test still fails.\n\n\
+             Its last test run (cargo) had 1 failing and 41 passing tests. The end of its output:\n```\ntest login::redirects ... FAILED\n```\n\n\
+             Review comments on its pull request:\n- octocat on src/login.rs:12: Keep the old cookie name."
+        );
+
+        let nothing_left = PreviousAttempt {
+            branch: "vk/1a2b-fix-login".to_string(),
+            summary: Some("  ".to_string()),
+            ..PreviousAttempt::default()
+        };
+        assert_eq!(nothing_left.render(), None);
+    }
+}
@@ -0,0 +1,446 @@
+//! Every long-running process the server spawns, so none of them outlives it.
+//!
+//! Executions, code-server, terminal shells and exec commands are each started
+//! as the leader of their own process group and registered here. Stopping one
+//! signals the whole group, and on Linux also the descendants that left it
+//! (e.g. daemons calling `setsid`), which are collected before the group goes
+//! away and their parent link with it. On Windows `taskkill /T` walks the tree.
+//!
+//! The registry is mirrored to `processes/{server_pid}.json` in the asset
+//! directory. After a crash the next server finds the file of a server that is
+//! no longer running and kills what it recorded. Entries are only trusted when
+//! the process start time still matches, which `/proc` provides on Linux; on
+//! other platforms the leftovers are logged instead of killed, as their pids
+//! may have been reused.
+
+use std::{path::PathBuf, sync::LazyLock};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::assets::asset_dir;
+use uuid::Uuid;
+
+/// How long a group gets to exit after SIGTERM before it is killed
+#[cfg(unix)]
+const GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(3);
+#[cfg(unix)]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ProcessKind {
+    Execution,
+    CodeServer,
+    Terminal,
+    Exec,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProcessInfo {
+    /// Pid of the group leader, which is also the process group id
+    pub pid: u32,
+    pub kind: ProcessKind,
+    pub label: String,
+    pub workspace_id: Option<Uuid>,
+    pub execution_process_id: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl ProcessInfo {
+    pub fn new(pid: u32, kind: ProcessKind, label: impl Into<String>) -> Self {
+        Self {
+            pid,
+            kind,
+            label: label.into(),
+            workspace_id: None,
+            execution_process_id: None,
+            started_at: Utc::now(),
+        }
+    }
+
+    pub fn with_workspace(mut self, workspace_id: Uuid) -> Self {
+        self.workspace_id = Some(workspace_id);
+        self
+    }
+
+    pub fn with_execution(mut self, execution_process_id: Uuid) -> Self {
+        self.execution_process_id = Some(execution_process_id);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Record {
+    #[serde(flatten)]
+    info: ProcessInfo,
+    /// Start time in clock ticks since boot, to tell the process apart from
+    /// a later one that was given the same pid
+    start_ticks: Option<u64>,
+}
+
+pub struct ProcessRegistry {
+    processes: DashMap<u32, Record>,
+}
+
+static REGISTRY: LazyLock<ProcessRegistry> = LazyLock::new(|| ProcessRegistry {
+    processes: DashMap::new(),
+});
+
+fn state_dir() -> PathBuf {
+    asset_dir().join("processes")
+}
+
+fn state_file(server_pid: u32) -> PathBuf {
+    state_dir().join(format!("{server_pid}.json"))
+}
+
+impl ProcessRegistry {
+    pub fn global() -> &'static ProcessRegistry {
+        &REGISTRY
+    }
+
+    pub fn register(&self, info: ProcessInfo) {
+        let pid = info.pid;
+        let record = Record {
+            info,
+            start_ticks: start_ticks(pid),
+        };
+        self.processes.insert(pid, record);
+        self.persist();
+    }
+
+    pub fn unregister(&self, pid: u32) {
+        if self.processes.remove(&pid).is_some() {
+            self.persist();
+        }
+    }
+
+    pub fn unregister_execution(&self, execution_process_id: Uuid) {
+        let before = self.processes.len();
+        self.processes
+            .retain(|_, record| record.info.execution_process_id != Some(execution_process_id));
+        if self.processes.len() != before {
+            self.persist();
+        }
+    }
+
+    /// Registered processes that are still running, oldest first
+    pub fn list(&self) -> Vec<ProcessInfo> {
+        let before = self.processes.len();
+        self.processes.retain(|_, record| is_same_process(record));
+        if self.processes.len() != before {
+            self.persist();
+        }
+        let mut processes: Vec<_> = self
+            .processes
+            .iter()
+            .map(|record| record.info.clone())
+            .collect();
+        processes.sort_by_key(|info| info.started_at);
+        processes
+    }
+
+    /// Kill the registered processes `filter` selects, with their trees
+    pub async fn kill_matching(&self, filter: impl Fn(&ProcessInfo) -> bool) {
+        let records: Vec<Record> = self
+            .processes
+            .iter()
+            .filter(|record| filter(&record.info))
+            .map(|record| record.clone())
+            .collect();
+        futures::future::join_all(records.iter().map(|record| async move {
+            if is_same_process(record) {
+                tracing::info!(
+                    "Stopping {:?} process {} ({})",
+                    record.info.kind,
+                    record.info.pid,
+                    record.info.label
+                );
+                kill_tree(record.info.pid).await;
+            }
+            self.processes.remove(&record.info.pid);
+        }))
+        .await;
+        if !records.is_empty() {
+            self.persist();
+        }
+    }
+
+    /// Kill everything still registered, on shutdown
+    pub async fn kill_all(&self) {
+        self.kill_matching(|_| true).await;
+        let _ = std::fs::remove_file(state_file(std::process::id()));
+    }
+
+    /// Kill what servers that are no longer running left behind
+    pub async fn reap_orphans(&self) {
+        let Ok(entries) = std::fs::read_dir(state_dir()) else {
+            return;
+        };
+        let own_pid = std::process::id();
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let Some(server_pid) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            // Another server may share the asset directory
+            if server_pid == own_pid || is_running(server_pid) {
+                continue;
+            }
+            let records: Vec<Record> = std::fs::read(&path)
+                .ok()
+                .and_then(|contents| serde_json::from_slice(&contents).ok())
+                .unwrap_or_default();
+            for record in records {
+                if record.start_ticks.is_none() {
+                    if is_running(record.info.pid) {
+                        tracing::warn!(
+                            "Process {} ({}) may have been left behind by a previous server",
+                            record.info.pid,
+                            record.info.label
+                        );
+                    }
+                    continue;
+                }
+                if is_same_process(&record) {
+                    tracing::info!(
+                        "Killing orphaned {:?} process {} ({})",
+                        record.info.kind,
+                        record.info.pid,
+                        record.info.label
+                    );
+                    kill_tree(record.info.pid).await;
+                }
+            }
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    fn persist(&self) {
+        let records: Vec<Record> = self.processes.iter().map(|record| record.clone()).collect();
+        let path = state_file(std::process::id());
+        let result = if records.is_empty() {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            }
+        } else {
+            std::fs::create_dir_all(state_dir()).and_then(|()| {
+                std::fs::write(&path, serde_json::to_vec(&records).unwrap_or_default())
+            })
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to record spawned processes: {}", e);
+        }
+    }
+}
+
+fn is_same_process(record: &Record) -> bool {
+    match record.start_ticks {
+        Some(ticks) => start_ticks(record.info.pid) == Some(ticks),
+        None => is_running(record.info.pid),
+    }
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    use nix::{sys::signal::kill, unistd::Pid};
+    kill(Pid::from_raw(pid as i32), None).is_ok()
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+}
+
+/// The fields of `/proc/{pid}/stat` the registry needs
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, PartialEq, Eq)]
+struct Stat {
+    state: char,
+    ppid: u32,
+    pgrp: u32,
+    start_ticks: u64,
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_stat(stat: &str) -> Option<Stat> {
+    // The command name is parenthesised and may contain spaces, so fields are
+    // counted from its closing parenthesis: state, ppid, pgrp, ..., starttime
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    Some(Stat {
+        state: fields.first()?.chars().next()?,
+        ppid: fields.get(1)?.parse().ok()?,
+        pgrp: fields.get(2)?.parse().ok()?,
+        start_ticks: fields.get(19)?.parse().ok()?,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn read_stat(pid: u32) -> Option<Stat> {
+    parse_stat(&std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)
+}
+
+/// Every process visible in `/proc`
+#[cfg(target_os = "linux")]
+fn all_stats() -> Vec<(u32, Stat)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            Some((pid, read_stat(pid)?))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn start_ticks(pid: u32) -> Option<u64> {
+    read_stat(pid).map(|stat| stat.start_ticks)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn start_ticks(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Whether a member of the group is still running. Zombies don't count: the
+/// leader is often our own child and stays one until it is waited for.
+#[cfg(target_os = "linux")]
+fn group_running(pgid: u32) -> bool {
+    all_stats()
+        .iter()
+        .any(|(_, stat)| stat.pgrp == pgid && stat.state != 'Z')
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn group_running(pgid: u32) -> bool {
+    use nix::{sys::signal::killpg, unistd::Pid};
+    killpg(Pid::from_raw(pgid as i32), None).is_ok()
+}
+
+/// Pids below `root` in `(pid, ppid)` pairs, in breadth-first order
+#[cfg(any(target_os = "linux", test))]
+fn subtree(root: u32, parents: &[(u32, u32)]) -> Vec<u32> {
+    let mut tree = vec![root];
+    let mut next = 0;
+    while next < tree.len() {
+        let parent = tree[next];
+        let children: Vec<u32> = parents
+            .iter()
+            .filter(|(pid, ppid)| *ppid == parent && !tree.contains(pid))
+            .map(|(pid, _)| *pid)
+            .collect();
+        tree.extend(children);
+        next += 1;
+    }
+    tree.remove(0);
+    tree
+}
+
+/// Descendants of `pid` with their start times. Only Linux exposes the
+/// process tree without extra tooling; elsewhere this is empty.
+pub fn descendants(pid: u32) -> Vec<(u32, u64)> {
+    #[cfg(target_os = "linux")]
+    {
+        let stats = all_stats();
+        let parents: Vec<(u32, u32)> = stats.iter().map(|(pid, stat)| (*pid, stat.ppid)).collect();
+        subtree(pid, &parents)
+            .into_iter()
+            .filter_map(|child| {
+                stats
+                    .iter()
+                    .find(|(pid, _)| *pid == child)
+                    .map(|(pid, stat)| (*pid, stat.start_ticks))
+            })
+            .collect()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Vec::new()
+    }
+}
+
+/// SIGKILL descendants collected by [`descendants`] that are still the same
+/// processes
+pub fn kill_descendants(descendants: &[(u32, u64)]) {
+    #[cfg(unix)]
+    for (pid, ticks) in descendants {
+        if start_ticks(*pid) == Some(*ticks) {
+            let _ = nix::sys::signal::kill(
+                nix::unistd::Pid::from_raw(*pid as i32),
+                nix::sys::signal::Signal::SIGKILL,
+            );
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = descendants;
+}
+
+/// Stop a process group led by `pid` and everything it spawned, politely first
+pub async fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    {
+        use nix::{
+            sys::signal::{Signal, killpg},
+            unistd::Pid,
+        };
+        let strays = descendants(pid);
+        let group = Pid::from_raw(pid as i32);
+        if killpg(group, Signal::SIGTERM).is_ok() {
+            let deadline = tokio::time::Instant::now() + GRACE_PERIOD;
+            while tokio::time::Instant::now() < deadline && group_running(pid) {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            let _ = killpg(group, Signal::SIGKILL);
+        }
+        kill_descendants(&strays);
+    }
+    #[cfg(windows)]
+    {
+        if let Err(e) = tokio::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .output()
+            .await
+        {
+            tracing::warn!("Failed to kill process tree {}: {}", pid, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_the_process_tree() {
+        let stat = "4242 (node server (dev)) S 4200 4200 4200 0 -1 4194560 1520 0 0 0 \
+                    12 3 0 0 20 0 11 0 987654 1073741824 12000";
+        assert_eq!(
+            parse_stat(stat),
+            Some(Stat {
+                state: 'S',
+                ppid: 4200,
+                pgrp: 4200,
+                start_ticks: 987654,
+            })
+        );
+
+        let parents = [(2, 1), (3, 2), (4, 2), (5, 3), (6, 1), (7, 99)];
+        assert_eq!(subtree(2, &parents), vec![3, 4, 5]);
+        assert!(subtree(7, &parents).is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Removal of what a deleted project leaves behind on disk: worktrees,
+//! containers, shared caches and editor data of its attempts.
+//!
+//! Deleting a project removes its rows right away, so everything the cleanup
+//! needs is captured beforehand in a [`DeletedProject`] and handed to the
+//! container service, which works through deleted projects one at a time in
+//! the background. Projects can keep their workspaces in a trash directory for
+//! a number of days instead, in case uncommitted work is still needed; trashed
+//! workspaces are plain copies of the files and no longer git worktrees.
+
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use db::models::{
+    project::Project, project_settings::ProjectSettings, repo::Repo, workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::workspace_manager::WorkspaceManager;
+
+/// Directory below the workspace base directory holding trashed workspaces.
+/// It is on the same filesystem, so workspaces are moved there cheaply.
+pub const TRASH_DIR: &str = ".trash";
+const MANIFEST: &str = "trash.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ProjectCleanupState {
+    Queued,
+    Running,
+    /// Everything was removed or moved to the trash
+    Completed,
+    /// Some workspaces couldn't be cleaned up, see `errors`
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectCleanupProgress {
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub state: ProjectCleanupState,
+    pub total_workspaces: u32,
+    pub cleaned_workspaces: u32,
+    /// Set when the workspaces were moved to the trash
+    pub trash_dir: Option<String>,
+    /// When trashed workspaces are removed for good
+    pub purge_after: Option<DateTime<Utc>>,
+    pub errors: Vec<String>,
+}
+
+/// What the cleanup of a project needs once its rows are gone
+#[derive(Debug)]
+pub struct DeletedProject {
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub settings: ProjectSettings,
+    /// Workspaces with the repositories their worktrees belong to
+    pub workspaces: Vec<(Workspace, Vec<Repo>)>,
+}
+
+impl DeletedProject {
+    pub async fn snapshot(pool: &SqlitePool, project: &Project) -> Result<Self, sqlx::Error> {
+        let mut workspaces = Vec::new();
+        for workspace in Workspace::find_by_project_id(pool, project.id).await? {
+            let repos = WorkspaceRepo::find_repos_for_workspace(pool, workspace.id).await?;
+            workspaces.push((workspace, repos));
+        }
+        Ok(Self {
+            project_id: project.id,
+            project_name: project.name.clone(),
+            settings: ProjectSettings::find_by_project_id(pool, project.id).await?,
+            workspaces,
+        })
+    }
+
+    /// Until when the workspaces stay in the trash, if the project keeps them
+    pub fn purge_after(&self) -> Option<DateTime<Utc>> {
+        self.settings
+            .retention
+            .trash_after_delete_days
+            .filter(|days| *days > 0)
+            .map(|days| Utc::now() + Duration::days(days))
+    }
+}
+
+static JOBS: LazyLock<DashMap<Uuid, ProjectCleanupProgress>> = LazyLock::new(DashMap::new);
+
+/// Record a deleted project as waiting for its cleanup
+pub fn queued(deleted: &DeletedProject) {
+    JOBS.insert(
+        deleted.project_id,
+        ProjectCleanupProgress {
+            project_id: deleted.project_id,
+            project_name: deleted.project_name.clone(),
+            state: ProjectCleanupState::Queued,
+            total_workspaces: deleted.workspaces.len() as u32,
+            cleaned_workspaces: 0,
+            trash_dir: None,
+            purge_after: None,
+            errors: Vec::new(),
+        },
+    );
+}
+
+pub fn update(project_id: Uuid, f: impl FnOnce(&mut ProjectCleanupProgress)) {
+    if let Some(mut progress) = JOBS.get_mut(&project_id) {
+        f(&mut progress);
+    }
+}
+
+/// Cleanups since the server started and projects still in the trash
+pub async fn list() -> Vec<ProjectCleanupProgress> {
+    let mut jobs: Vec<ProjectCleanupProgress> =
+        JOBS.iter().map(|entry| entry.value().clone()).collect();
+    for manifest in trash_manifests().await {
+        if jobs.iter().any(|job| job.project_id == manifest.project_id) {
+            continue;
+        }
+        jobs.push(ProjectCleanupProgress {
+            project_id: manifest.project_id,
+            project_name: manifest.project_name,
+            state: ProjectCleanupState::Completed,
+            total_workspaces: manifest.workspaces,
+            cleaned_workspaces: manifest.workspaces,
+            trash_dir: Some(
+                project_trash_dir(manifest.project_id)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            purge_after: Some(manifest.purge_after),
+            errors: Vec::new(),
+        });
+    }
+    jobs.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+    jobs
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrashManifest {
+    project_id: Uuid,
+    project_name: String,
+    workspaces: u32,
+    purge_after: DateTime<Utc>,
+}
+
+fn trash_root() -> PathBuf {
+    WorkspaceManager::get_workspace_base_dir().join(TRASH_DIR)
+}
+
+pub fn project_trash_dir(project_id: Uuid) -> PathBuf {
+    trash_root().join(project_id.to_string())
+}
+
+/// Start the project's trash, recording when it may be purged
+pub async fn create_trash(
+    deleted: &DeletedProject,
+    purge_after: DateTime<Utc>,
+) -> io::Result<PathBuf> {
+    let dir = project_trash_dir(deleted.project_id);
+    tokio::fs::create_dir_all(&dir).await?;
+    let manifest = TrashManifest {
+        project_id: deleted.project_id,
+        project_name: deleted.project_name.clone(),
+        workspaces: deleted.workspaces.len() as u32,
+        purge_after,
+    };
+    tokio::fs::write(dir.join(MANIFEST), serde_json::to_vec_pretty(&manifest)?).await?;
+    Ok(dir)
+}
+
+/// Move a workspace directory into the project's trash
+pub async fn move_to_trash(trash_dir: &Path, workspace_dir: &Path) -> io::Result<()> {
+    let name = workspace_dir
+        .file_name()
+        .ok_or_else(|| io::Error::other("Workspace directory has no name"))?;
+    tokio::fs::rename(workspace_dir, trash_dir.join(name)).await
+}
+
+async fn trash_manifests() -> Vec<TrashManifest> {
+    let mut manifests = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(trash_root()).await else {
+        return manifests;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(contents) = tokio::fs::read(entry.path().join(MANIFEST)).await else {
+            continue;
+        };
+        match serde_json::from_slice(&contents) {
+            Ok(manifest) => manifests.push(manifest),
+            Err(e) => tracing::warn!(
+                "Unreadable trash manifest in {}: {}",
+                entry.path().display(),
+                e
+            ),
+        }
+    }
+    manifests
+}
+
+/// Remove trashed projects whose time is up
+pub async fn purge_expired_trash() {
+    let now = Utc::now();
+    for manifest in trash_manifests().await {
+        if manifest.purge_after > now {
+            continue;
+        }
+        let dir = project_trash_dir(manifest.project_id);
+        match tokio::fs::remove_dir_all(&dir).await {
+            Ok(()) => tracing::info!(
+                "Purged trashed workspaces of deleted project {}",
+                manifest.project_name
+            ),
+            Err(e) => tracing::warn!("Failed to purge {}: {}", dir.display(), e),
+        }
+    }
+}
+
+pub fn is_trash_dir(path: &Path) -> bool {
+    path.file_name() == Some(OsStr::new(TRASH_DIR))
+}
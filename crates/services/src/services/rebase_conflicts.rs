@@ -0,0 +1,249 @@
+//! The conflicts a rebase of an attempt's branch stopped on, with all three
+//! sides of each, and their resolution file by file. In a rebase "ours" is
+//! the branch being rebased onto and "theirs" the attempt's commit being
+//! replayed.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::git::{ConflictOp, GitCli, GitCliError, GitService, GitServiceError};
+
+const MARKER_OURS: &str = "<<<<<<<";
+const MARKER_BASE: &str = "|||||||";
+const MARKER_SEPARATOR: &str = "=======";
+const MARKER_THEIRS: &str = ">>>>>>>";
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ConflictDetails {
+    /// None once the operation finished or was aborted
+    pub op: Option<ConflictOp>,
+    pub files: Vec<ConflictedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ConflictedFile {
+    pub path: String,
+    /// None when the side deleted the file, or it isn't text
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub binary: bool,
+    /// Conflicts still marked in the worktree's copy
+    pub hunks: Vec<ConflictHunk>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct ConflictHunk {
+    /// 1-based line of the worktree's copy where the conflict's markers start
+    pub start_line: usize,
+    pub base: Option<String>,
+    pub ours: String,
+    pub theirs: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Take the file as the branch being rebased onto has it
+    Ours,
+    /// Take the file as the attempt's commit has it
+    Theirs,
+    Content {
+        content: String,
+    },
+    Delete,
+}
+
+/// The worktree's in-progress operation and the files it stopped on
+pub fn conflict_details(
+    git: &GitService,
+    worktree_path: &Path,
+) -> Result<ConflictDetails, GitServiceError> {
+    let op = git.detect_conflict_op(worktree_path)?;
+    let cli = GitCli::new();
+    let files = git
+        .get_conflicted_files(worktree_path)?
+        .into_iter()
+        .map(|path| conflicted_file(&cli, worktree_path, path))
+        .collect::<Result<_, _>>()
+        .map_err(cli_error)?;
+    Ok(ConflictDetails { op, files })
+}
+
+fn conflicted_file(
+    cli: &GitCli,
+    worktree_path: &Path,
+    path: String,
+) -> Result<ConflictedFile, GitCliError> {
+    let stages = [1, 2, 3]
+        .map(|stage| cli.show_index_stage(worktree_path, stage, &path))
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    let working = std::fs::read(worktree_path.join(&path)).ok();
+    let binary = stages
+        .iter()
+        .chain([&working])
+        .flatten()
+        .any(|bytes| std::str::from_utf8(bytes).is_err());
+    let [base, ours, theirs] = [0, 1, 2].map(|i| {
+        stages[i]
+            .as_ref()
+            .and_then(|bytes| String::from_utf8(bytes.clone()).ok())
+    });
+    let hunks = working
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .map(|content| parse_hunks(&content))
+        .unwrap_or_default();
+    Ok(ConflictedFile {
+        path,
+        base,
+        ours,
+        theirs,
+        binary,
+        hunks,
+    })
+}
+
+/// Resolve one conflicted file and stage it
+pub fn resolve_file(
+    worktree_path: &Path,
+    path: &str,
+    resolution: &ConflictResolution,
+) -> Result<(), GitServiceError> {
+    let cli = GitCli::new();
+    let content = match resolution {
+        ConflictResolution::Ours => cli.show_index_stage(worktree_path, 2, path),
+        ConflictResolution::Theirs => cli.show_index_stage(worktree_path, 3, path),
+        ConflictResolution::Content { content } => Ok(Some(content.clone().into_bytes())),
+        ConflictResolution::Delete => Ok(None),
+    }
+    .map_err(cli_error)?;
+
+    match content {
+        Some(content) => {
+            let file_path = worktree_path.join(path);
+            if let Some(parent) = file_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&file_path, content)?;
+            cli.add_path(worktree_path, path)
+        }
+        // The chosen side deleted the file
+        None => cli.remove_path(worktree_path, path),
+    }
+    .map_err(cli_error)
+}
+
+/// Carry on with the rebase once every conflict is resolved. Stopping on the
+/// next commit's conflicts isn't an error; they show in the details.
+pub fn continue_rebase(git: &GitService, worktree_path: &Path) -> Result<(), GitServiceError> {
+    match GitCli::new().continue_rebase(worktree_path) {
+        Ok(()) => Ok(()),
+        Err(GitCliError::CommandFailed(_))
+            if !git.get_conflicted_files(worktree_path)?.is_empty() =>
+        {
+            Ok(())
+        }
+        Err(e) => Err(cli_error(e)),
+    }
+}
+
+fn cli_error(e: GitCliError) -> GitServiceError {
+    GitServiceError::InvalidRepository(format!("git failed: {e}"))
+}
+
+/// Conflicts marked in a file, with or without the common ancestor's section
+fn parse_hunks(content: &str) -> Vec<ConflictHunk> {
+    enum Section {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut hunks = Vec::new();
+    let mut section = Section::Outside;
+    let mut start_line = 0;
+    let (mut ours, mut base, mut theirs) = (String::new(), None::<String>, String::new());
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        section = match section {
+            Section::Outside if line.starts_with(MARKER_OURS) => {
+                start_line = i + 1;
+                ours.clear();
+                base = None;
+                theirs.clear();
+                Section::Ours
+            }
+            Section::Outside => Section::Outside,
+            Section::Ours | Section::Base if line.starts_with(MARKER_SEPARATOR) => Section::Theirs,
+            Section::Ours if line.starts_with(MARKER_BASE) => {
+                base = Some(String::new());
+                Section::Base
+            }
+            Section::Ours => {
+                ours.push_str(line);
+                Section::Ours
+            }
+            Section::Base => {
+                base.get_or_insert_default().push_str(line);
+                Section::Base
+            }
+            Section::Theirs if line.starts_with(MARKER_THEIRS) => {
+                hunks.push(ConflictHunk {
+                    start_line,
+                    base: base.take(),
+                    ours: std::mem::take(&mut ours),
+                    theirs: std::mem::take(&mut theirs),
+                });
+                Section::Outside
+            }
+            Section::Theirs => {
+                theirs.push_str(line);
+                Section::Theirs
+            }
+        };
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hunks_are_read_from_conflict_markers() {
+        let content = "fn main() {\n\
+            <<<<<<< HEAD\n\
+            \x20   run(true);\n\
+            ||||||| parent of 1a2b3c4 (Add flag)\n\
+            \x20   run();\n\
+            =======\n\
+            \x20   run(false);\n\
+            >>>>>>> 1a2b3c4 (Add flag)\n\
+            }\n\
+            <<<<<<< HEAD\n\
+            // ours\n\
+            =======\n\
+            >>>>>>> 1a2b3c4 (Add flag)\n";
+
+        assert_eq!(
+            parse_hunks(content),
+            vec![
+                ConflictHunk {
+                    start_line: 2,
+                    base: Some("    run();\n".to_string()),
+                    ours: "    run(true);\n".to_string(),
+                    theirs: "    run(false);\n".to_string(),
+                },
+                ConflictHunk {
+                    start_line: 10,
+                    base: None,
+                    ours: "// ours\n".to_string(),
+                    theirs: String::new(),
+                },
+            ]
+        );
+    }
+}
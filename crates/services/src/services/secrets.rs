@@ -0,0 +1,298 @@
+use std::{io::Write, path::Path};
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use db::models::{project_env_var::ProjectEnvVar, project_secret::ProjectSecret};
+use futures::{Stream, StreamExt, TryStreamExt, future, stream};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use utils::assets::secret_key_path;
+use uuid::Uuid;
+
+const NONCE_SIZE: usize = 12; // 96 bits for AES-256-GCM
+
+/// Secrets shorter than this are not masked in logs, since replacing every
+/// occurrence of a one or two character value would mangle unrelated output.
+const MIN_MASKED_LEN: usize = 4;
+
+const MASK: &str = "********";
+
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Secret key file is invalid")]
+    InvalidKey,
+    #[error("Failed to encrypt or decrypt secret")]
+    Crypto,
+}
+
+/// Encrypts project secrets with a key generated on first use and kept next to
+/// the local config, readable only by the current user.
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    pub fn load() -> Result<Self, SecretsError> {
+        Self::load_or_create(&secret_key_path())
+    }
+
+    pub fn load_or_create(path: &Path) -> Result<Self, SecretsError> {
+        let key = match std::fs::read_to_string(path) {
+            Ok(encoded) => {
+                let bytes = STANDARD
+                    .decode(encoded.trim())
+                    .map_err(|_| SecretsError::InvalidKey)?;
+                let bytes: [u8; 32] = bytes.try_into().map_err(|_| SecretsError::InvalidKey)?;
+                Key::<Aes256Gcm>::from(bytes)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let key = Aes256Gcm::generate_key(&mut OsRng);
+                write_key_file(path, &STANDARD.encode(key))?;
+                key
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            cipher: Aes256Gcm::new(&key),
+        })
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, SecretsError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| SecretsError::Crypto)?;
+
+        let mut combined = nonce.to_vec();
+        combined.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(combined))
+    }
+
+    pub fn decrypt(&self, encrypted: &str) -> Result<String, SecretsError> {
+        let decoded = STANDARD
+            .decode(encrypted)
+            .map_err(|_| SecretsError::Crypto)?;
+        if decoded.len() < NONCE_SIZE {
+            return Err(SecretsError::Crypto);
+        }
+        let (nonce_bytes, ciphertext) = decoded.split_at(NONCE_SIZE);
+        let nonce_bytes: [u8; NONCE_SIZE] =
+            nonce_bytes.try_into().map_err(|_| SecretsError::Crypto)?;
+        let plaintext = self
+            .cipher
+            .decrypt(&Nonce::from(nonce_bytes), ciphertext)
+            .map_err(|_| SecretsError::Crypto)?;
+        String::from_utf8(plaintext).map_err(|_| SecretsError::Crypto)
+    }
+}
+
+fn write_key_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut opts = std::fs::OpenOptions::new();
+    opts.create_new(true).write(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+
+    let mut file = opts.open(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+/// Variables configured for a project, with secrets decrypted.
+#[derive(Debug, Default)]
+pub struct ProjectEnv {
+    pub vars: Vec<(String, String)>,
+    /// Plaintext secret values, used to mask them in process output
+    pub secret_values: Vec<String>,
+}
+
+impl ProjectEnv {
+    /// Load the project's variables and secrets. Secrets win over plain variables
+    /// of the same name.
+    pub async fn load(pool: &SqlitePool, project_id: Uuid) -> Result<Self, SecretsError> {
+        let mut env = ProjectEnv {
+            vars: ProjectEnvVar::find_by_project_id(pool, project_id)
+                .await?
+                .into_iter()
+                .map(|var| (var.name, var.value))
+                .collect(),
+            secret_values: Vec::new(),
+        };
+
+        let secrets = ProjectSecret::find_by_project_id(pool, project_id).await?;
+        if secrets.is_empty() {
+            return Ok(env);
+        }
+        let cipher = SecretCipher::load()?;
+        for secret in secrets {
+            let value = cipher.decrypt(&secret.encrypted_value)?;
+            env.secret_values.push(value.clone());
+            env.vars.push((secret.name, value));
+        }
+        Ok(env)
+    }
+}
+
+/// Replace every occurrence of a secret value in `text` with a fixed mask.
+pub fn mask_secrets(text: &str, secrets: &[String]) -> String {
+    let mut masked = text.to_string();
+    for secret in secrets {
+        if secret.len() >= MIN_MASKED_LEN && masked.contains(secret.as_str()) {
+            masked = masked.replace(secret.as_str(), MASK);
+        }
+    }
+    masked
+}
+
+/// Masks secrets in output that arrives in chunks. A secret can be split
+/// across two reads, so the end of each chunk that could be the start of a
+/// secret is held back until the next chunk (or `finish`) decides it.
+pub struct SecretMasker {
+    secrets: Vec<String>,
+    pending: Vec<u8>,
+}
+
+impl SecretMasker {
+    pub fn new(secrets: Vec<String>) -> Self {
+        let secrets = secrets
+            .into_iter()
+            .filter(|secret| secret.len() >= MIN_MASKED_LEN)
+            .collect();
+        Self {
+            secrets,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Masked text of everything read so far that can no longer be part of a
+    /// secret.
+    pub fn push(&mut self, chunk: &[u8]) -> String {
+        self.pending.extend_from_slice(chunk);
+        let held = self
+            .secrets
+            .iter()
+            .map(|secret| partial_suffix_len(&self.pending, secret.as_bytes()))
+            .max()
+            .unwrap_or(0);
+        let mut split = self.pending.len() - held;
+        // Never cut through a secret that is already complete in the buffer.
+        while let Some(end) = self
+            .secrets
+            .iter()
+            .flat_map(|secret| {
+                occurrences(&self.pending, secret.as_bytes())
+                    .map(move |start| (start, start + secret.len()))
+            })
+            .filter(|&(start, end)| start < split && end > split)
+            .map(|(_, end)| end)
+            .max()
+        {
+            split = end;
+        }
+        // Keep an incomplete UTF-8 character for the next chunk too.
+        if let Err(e) = std::str::from_utf8(&self.pending[..split])
+            && e.error_len().is_none()
+        {
+            split = e.valid_up_to();
+        }
+        let rest = self.pending.split_off(split);
+        let ready = std::mem::replace(&mut self.pending, rest);
+        mask_secrets(&String::from_utf8_lossy(&ready), &self.secrets)
+    }
+
+    /// Masked text of whatever is still held back once the output has ended.
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        mask_secrets(&String::from_utf8_lossy(&rest), &self.secrets)
+    }
+}
+
+/// Mask secrets in a stream of output chunks, see [`SecretMasker`].
+pub fn mask_stream<S, B, E>(
+    stream: S,
+    secrets: Vec<String>,
+) -> impl Stream<Item = Result<String, E>>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    stream::unfold(
+        (stream, Some(SecretMasker::new(secrets))),
+        |(mut stream, masker)| async move {
+            let mut masker = masker?;
+            let text = match stream.next().await {
+                Some(Ok(chunk)) => Ok(masker.push(chunk.as_ref())),
+                Some(Err(e)) => Err(e),
+                None => return Some((Ok(masker.finish()), (stream, None))),
+            };
+            Some((text, (stream, Some(masker))))
+        },
+    )
+    .try_filter(|text| future::ready(!text.is_empty()))
+}
+
+/// Length of the longest end of `text` that is a proper prefix of `secret`.
+fn partial_suffix_len(text: &[u8], secret: &[u8]) -> usize {
+    (1..secret.len().min(text.len() + 1))
+        .rev()
+        .find(|&len| text.ends_with(&secret[..len]))
+        .unwrap_or(0)
+}
+
+fn occurrences<'a>(text: &'a [u8], secret: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    text.windows(secret.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == secret)
+        .map(|(start, _)| start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_persists_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secret.key");
+
+        let encrypted = SecretCipher::load_or_create(&path)
+            .unwrap()
+            .encrypt("hunter22")
+            .unwrap();
+        assert!(!encrypted.contains("hunter22"));
+
+        let reloaded = SecretCipher::load_or_create(&path).unwrap();
+        assert_eq!(reloaded.decrypt(&encrypted).unwrap(), "hunter22");
+    }
+
+    #[test]
+    fn masks_secret_values() {
+        let secrets = vec!["sk-abc123".to_string(), "x".to_string()];
+        assert_eq!(
+            mask_secrets("token=sk-abc123 x", &secrets),
+            "token=******** x"
+        );
+    }
+
+    #[test]
+    fn masks_secret_split_across_chunks() {
+        let mut masker = SecretMasker::new(vec!["sk-abc123".to_string()]);
+        let mut output = masker.push(b"token=sk-a");
+        assert_eq!(output, "token=");
+        output.push_str(&masker.push(b"bc123 done\n"));
+        output.push_str(&masker.push(b"sk-"));
+        output.push_str(&masker.finish());
+        assert_eq!(output, "token=******** done\nsk-");
+    }
+}
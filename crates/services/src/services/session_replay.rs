@@ -0,0 +1,118 @@
+//! Replaying a recorded coding agent run, sending the patches to its
+//! conversation at the pace the run made them, so demos, bug reports and
+//! postmortems can step through what the agent did.
+
+use std::time::Duration;
+
+use db::models::execution_process_recording::{ExecutionProcessRecording, RecordedEvent};
+use futures::{Stream, StreamExt, stream};
+use json_patch::Patch;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+
+const DEFAULT_MAX_GAP_MS: u32 = 5000;
+const MIN_SPEED: f64 = 0.1;
+const MAX_SPEED: f64 = 100.0;
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct ReplayQuery {
+    /// How many times faster than the run, 1 unless given
+    pub speed: Option<f64>,
+    /// Longest pause between two patches in milliseconds, 5000 unless given,
+    /// so the replay doesn't stall while the agent was waiting
+    pub max_gap_ms: Option<u32>,
+    /// Milliseconds into the run to start at; what came before is sent at
+    /// once
+    pub from_ms: Option<u32>,
+}
+
+impl ReplayQuery {
+    /// How long to wait before sending each event
+    fn delays(&self, events: &[RecordedEvent]) -> Vec<Duration> {
+        let speed = self.speed.unwrap_or(1.0).clamp(MIN_SPEED, MAX_SPEED);
+        let max_gap_ms = self.max_gap_ms.unwrap_or(DEFAULT_MAX_GAP_MS) as i64;
+        let from_ms = self.from_ms.unwrap_or(0) as i64;
+
+        let mut previous_ms = from_ms;
+        events
+            .iter()
+            .map(|event| {
+                if event.at_ms <= from_ms {
+                    return Duration::ZERO;
+                }
+                let gap_ms = (event.at_ms - previous_ms).clamp(0, max_gap_ms);
+                previous_ms = event.at_ms;
+                Duration::from_secs_f64(gap_ms as f64 / 1000.0 / speed)
+            })
+            .collect()
+    }
+
+    /// The recording's patches as a normalized logs stream, ending with
+    /// `Finished`
+    pub fn stream(
+        &self,
+        recording: ExecutionProcessRecording,
+    ) -> impl Stream<Item = LogMsg> + Send + 'static {
+        let delays = self.delays(&recording.events);
+        stream::iter(delays.into_iter().zip(recording.events))
+            .filter_map(|(delay, event)| async move {
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                serde_json::from_value::<Patch>(event.patch)
+                    .ok()
+                    .map(LogMsg::JsonPatch)
+            })
+            .chain(stream::once(async { LogMsg::Finished }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn events(times: &[i64]) -> Vec<RecordedEvent> {
+        times
+            .iter()
+            .map(|&at_ms| RecordedEvent {
+                at_ms,
+                patch: json!([]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gaps_are_scaled_capped_and_skipped_before_the_start() {
+        let events = events(&[0, 1000, 1500, 61500, 62000]);
+        let millis = |query: ReplayQuery| -> Vec<u128> {
+            query
+                .delays(&events)
+                .iter()
+                .map(Duration::as_millis)
+                .collect()
+        };
+
+        assert_eq!(
+            millis(ReplayQuery::default()),
+            vec![0, 1000, 500, 5000, 500]
+        );
+        assert_eq!(
+            millis(ReplayQuery {
+                speed: Some(2.0),
+                max_gap_ms: Some(60_000),
+                ..Default::default()
+            }),
+            vec![0, 500, 250, 30_000, 250]
+        );
+        assert_eq!(
+            millis(ReplayQuery {
+                from_ms: Some(1500),
+                ..Default::default()
+            }),
+            vec![0, 0, 0, 5000, 500]
+        );
+    }
+}
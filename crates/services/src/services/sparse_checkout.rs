@@ -0,0 +1,120 @@
+//! Sparse checkouts of attempt worktrees, so attempts in large monorepos only
+//! write the directories they need. Patterns come from the project's settings
+//! and, if it says so, the task's scope.
+//!
+//! Like task scopes, a pattern may start with a repository's name when the
+//! project has several; it's then only applied to that repository, relative
+//! to its root.
+
+use db::models::{project_settings::SparseCheckoutSettings, repo::Repo, task::Task};
+
+/// Patterns that check out the files at the root of the repository, which
+/// builds and tooling usually need, before any of the configured ones
+const ROOT_FILES: [&str; 2] = ["/*", "!/*/"];
+
+/// The paths to check out of a workspace's repositories
+#[derive(Debug, Clone, Default)]
+pub struct SparseCheckout {
+    patterns: Vec<String>,
+    repo_names: Vec<String>,
+}
+
+impl SparseCheckout {
+    pub fn new(settings: &SparseCheckoutSettings, task: &Task, repos: &[Repo]) -> Self {
+        let mut patterns = settings.patterns.clone();
+        if settings.include_task_scope {
+            patterns.extend(task.scope_paths.iter().cloned());
+        }
+        Self::from_patterns(
+            patterns,
+            repos.iter().map(|repo| repo.name.clone()).collect(),
+        )
+    }
+
+    pub fn from_patterns(patterns: Vec<String>, repo_names: Vec<String>) -> Self {
+        Self {
+            patterns,
+            repo_names,
+        }
+    }
+
+    /// The `git sparse-checkout` patterns of a repository's worktree; empty
+    /// to check it out whole
+    pub fn for_repo(&self, repo_name: &str) -> Vec<String> {
+        let mut patterns = Vec::new();
+        for line in &self.patterns {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negation, pattern) = match line.strip_prefix('!') {
+                Some(pattern) => ("!", pattern),
+                None => ("", line),
+            };
+            let path = pattern.trim_start_matches('/');
+            match path.split_once('/') {
+                Some((name, rest)) if name == repo_name => {
+                    let rest = if rest.is_empty() { "*" } else { rest };
+                    patterns.push(format!("{negation}/{rest}"));
+                    // The repository may also have a directory of its name
+                    patterns.push(line.to_string());
+                }
+                Some((name, _)) if self.repo_names.iter().any(|other| other == name) => {}
+                _ => patterns.push(line.to_string()),
+            }
+        }
+        if !patterns.iter().any(|pattern| !pattern.starts_with('!')) {
+            return Vec::new();
+        }
+        ROOT_FILES
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .chain(patterns)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_are_resolved_per_repository() {
+        let sparse = SparseCheckout::from_patterns(
+            vec![
+                "# services".to_string(),
+                "services/api/".to_string(),
+                "!services/api/fixtures/".to_string(),
+                "web/src/".to_string(),
+                "docs/".to_string(),
+            ],
+            vec!["services".to_string(), "web".to_string()],
+        );
+        assert_eq!(
+            sparse.for_repo("services"),
+            vec![
+                "/*",
+                "!/*/",
+                "/api/",
+                "services/api/",
+                "!/api/fixtures/",
+                "!services/api/fixtures/",
+                "docs/",
+            ]
+        );
+        assert_eq!(
+            sparse.for_repo("web"),
+            vec!["/*", "!/*/", "/src/", "web/src/", "docs/"]
+        );
+
+        let other_repo_only =
+            SparseCheckout::from_patterns(vec!["web/src/".to_string()], sparse.repo_names.clone());
+        assert!(other_repo_only.for_repo("services").is_empty());
+        assert_eq!(
+            SparseCheckout::from_patterns(vec!["web/".to_string()], sparse.repo_names.clone())
+                .for_repo("web"),
+            vec!["/*", "!/*/", "/*", "web/"]
+        );
+        assert!(SparseCheckout::default().for_repo("services").is_empty());
+    }
+}
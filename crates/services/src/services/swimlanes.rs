@@ -0,0 +1,280 @@
+//! The board split into rows, each holding the tasks of one label, epic or
+//! parent task column by column, so that large boards can be read a row at a
+//! time.
+
+use std::collections::HashMap;
+
+use db::models::{
+    board_column::{self, BoardColumn},
+    epic::Epic,
+    label::Label,
+    project_settings::SwimlaneGrouping,
+    task::{Task, TaskWithAttemptStatus},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SwimlaneColumn {
+    pub column_id: Uuid,
+    /// In board order
+    pub tasks: Vec<TaskWithAttemptStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct Swimlane {
+    /// Label, epic or parent task of the row; None for the row of tasks
+    /// without one
+    pub key: Option<Uuid>,
+    pub name: String,
+    /// Every column of the board, left to right
+    pub columns: Vec<SwimlaneColumn>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct BoardSwimlanes {
+    pub grouping: SwimlaneGrouping,
+    pub columns: Vec<BoardColumn>,
+    /// Only rows holding tasks, the one without a key last
+    pub lanes: Vec<Swimlane>,
+}
+
+/// Split the project's board tasks into rows by the grouping
+pub async fn board_swimlanes(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    grouping: SwimlaneGrouping,
+) -> Result<BoardSwimlanes, sqlx::Error> {
+    let columns = BoardColumn::find_or_create_defaults(pool, project_id).await?;
+    let tasks = Task::find_by_project_id_with_attempt_status(pool, project_id).await?;
+    let lane_names = lane_names(pool, project_id, grouping, &tasks).await?;
+    let lanes = group_tasks(grouping, &columns, tasks, &lane_names);
+    Ok(BoardSwimlanes {
+        grouping,
+        columns,
+        lanes,
+    })
+}
+
+/// Keys of the possible rows with their names, in row order
+async fn lane_names(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    grouping: SwimlaneGrouping,
+    tasks: &[TaskWithAttemptStatus],
+) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+    match grouping {
+        SwimlaneGrouping::None => Ok(Vec::new()),
+        SwimlaneGrouping::Label => Ok(Label::find_by_project_id(pool, project_id)
+            .await?
+            .into_iter()
+            .map(|label| (label.id, label.name))
+            .collect()),
+        SwimlaneGrouping::Epic => Ok(Epic::find_by_project_id(pool, project_id)
+            .await?
+            .into_iter()
+            .map(|epic| (epic.id, epic.name))
+            .collect()),
+        SwimlaneGrouping::ParentTask => {
+            let titles: HashMap<Uuid, &str> = tasks
+                .iter()
+                .map(|task| (task.id, task.title.as_str()))
+                .collect();
+            let mut parents = Vec::new();
+            for parent_id in tasks.iter().filter_map(|task| task.parent_task_id) {
+                if parents.iter().any(|(id, _)| *id == parent_id) {
+                    continue;
+                }
+                // Archived parents are off the board but still name the row
+                let title = match titles.get(&parent_id) {
+                    Some(title) => title.to_string(),
+                    None => match Task::find_by_id(pool, parent_id).await? {
+                        Some(parent) => parent.title,
+                        None => continue,
+                    },
+                };
+                parents.push((parent_id, title));
+            }
+            parents.sort_by_key(|(_, title)| title.to_lowercase());
+            Ok(parents)
+        }
+    }
+}
+
+fn lane_key(
+    grouping: SwimlaneGrouping,
+    task: &TaskWithAttemptStatus,
+    lane_names: &[(Uuid, String)],
+) -> Option<Uuid> {
+    match grouping {
+        SwimlaneGrouping::None => None,
+        // A task with several labels goes in the row of the first of them
+        SwimlaneGrouping::Label => lane_names
+            .iter()
+            .map(|(id, _)| *id)
+            .find(|id| task.label_ids.contains(id)),
+        SwimlaneGrouping::Epic => task
+            .epic_id
+            .filter(|epic_id| lane_names.iter().any(|(id, _)| id == epic_id)),
+        SwimlaneGrouping::ParentTask => task
+            .parent_task_id
+            .filter(|parent_id| lane_names.iter().any(|(id, _)| id == parent_id)),
+    }
+}
+
+fn group_tasks(
+    grouping: SwimlaneGrouping,
+    columns: &[BoardColumn],
+    mut tasks: Vec<TaskWithAttemptStatus>,
+    lane_names: &[(Uuid, String)],
+) -> Vec<Swimlane> {
+    tasks.sort_by_key(|task| (task.position, std::cmp::Reverse(task.created_at)));
+
+    let mut lanes: Vec<Swimlane> = lane_names
+        .iter()
+        .map(|(id, name)| empty_lane(Some(*id), name.clone(), columns))
+        .collect();
+    let unassigned_name = match grouping {
+        SwimlaneGrouping::None => "All tasks",
+        SwimlaneGrouping::Label => "No label",
+        SwimlaneGrouping::Epic => "No epic",
+        SwimlaneGrouping::ParentTask => "No parent task",
+    };
+    lanes.push(empty_lane(None, unassigned_name.to_string(), columns));
+
+    for task in tasks {
+        let Some(column_id) = task
+            .column_id
+            .or_else(|| board_column::first_column_for(columns, &task.status))
+        else {
+            continue;
+        };
+        let key = lane_key(grouping, &task, lane_names);
+        let Some(lane) = lanes.iter_mut().find(|lane| lane.key == key) else {
+            continue;
+        };
+        if let Some(column) = lane
+            .columns
+            .iter_mut()
+            .find(|column| column.column_id == column_id)
+        {
+            column.tasks.push(task);
+        }
+    }
+
+    lanes.retain(|lane| lane.columns.iter().any(|column| !column.tasks.is_empty()));
+    lanes
+}
+
+fn empty_lane(key: Option<Uuid>, name: String, columns: &[BoardColumn]) -> Swimlane {
+    Swimlane {
+        key,
+        name,
+        columns: columns
+            .iter()
+            .map(|column| SwimlaneColumn {
+                column_id: column.id,
+                tasks: Vec::new(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::task::{SubtaskProgress, TaskPriority, TaskStatus};
+    use sqlx::types::Json;
+
+    use super::*;
+
+    fn column(position: i64, status: TaskStatus) -> BoardColumn {
+        BoardColumn {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            name: format!("{status}"),
+            position,
+            status,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn task(
+        title: &str,
+        status: TaskStatus,
+        position: i64,
+        label_ids: Vec<Uuid>,
+    ) -> TaskWithAttemptStatus {
+        TaskWithAttemptStatus {
+            task: Task {
+                id: Uuid::new_v4(),
+                project_id: Uuid::nil(),
+                title: title.to_string(),
+                description: None,
+                status,
+                parent_workspace_id: None,
+                parent_task_id: None,
+                priority: TaskPriority::Normal,
+                position,
+                archived_at: None,
+                due_at: None,
+                column_id: None,
+                epic_id: None,
+                executor_profile_id: None,
+                scope_paths: Json(Vec::new()),
+                shared_task_id: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            has_in_progress_attempt: false,
+            last_attempt_failed: false,
+            executor: String::new(),
+            is_blocked: false,
+            subtasks: SubtaskProgress::default(),
+            label_ids,
+            latest_test_run: None,
+        }
+    }
+
+    fn titles(column: &SwimlaneColumn) -> Vec<&str> {
+        column
+            .tasks
+            .iter()
+            .map(|task| task.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn tasks_go_in_the_row_of_their_first_label() {
+        let columns = vec![column(0, TaskStatus::Todo), column(1, TaskStatus::Done)];
+        let bug = Uuid::new_v4();
+        let ui = Uuid::new_v4();
+        let docs = Uuid::new_v4();
+        let lane_names = vec![
+            (bug, "bug".to_string()),
+            (docs, "docs".to_string()),
+            (ui, "ui".to_string()),
+        ];
+        let tasks = vec![
+            task("Fix crash", TaskStatus::Todo, 1, vec![ui, bug]),
+            task("Fix layout", TaskStatus::Todo, 0, vec![bug]),
+            task("Restyle", TaskStatus::Done, 0, vec![ui]),
+            task("Release", TaskStatus::Todo, 0, vec![]),
+        ];
+
+        let lanes = group_tasks(SwimlaneGrouping::Label, &columns, tasks, &lane_names);
+
+        let keys: Vec<_> = lanes.iter().map(|lane| lane.key).collect();
+        assert_eq!(keys, vec![Some(bug), Some(ui), None]);
+        assert_eq!(
+            titles(&lanes[0].columns[0]),
+            vec!["Fix layout", "Fix crash"]
+        );
+        assert_eq!(titles(&lanes[1].columns[1]), vec!["Restyle"]);
+        assert_eq!(lanes[2].name, "No label");
+        assert_eq!(titles(&lanes[2].columns[0]), vec!["Release"]);
+    }
+}
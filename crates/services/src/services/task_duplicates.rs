@@ -0,0 +1,168 @@
+//! Open tasks that look like the same request as a task about to be created,
+//! for boards fed by several people and bots.
+//!
+//! Texts are compared by trigram similarity: the share of three-letter
+//! sequences of their words that they have in common. It catches reworded
+//! and misspelled titles without needing an embedding model.
+
+use std::collections::HashSet;
+
+use db::models::task::{Task, TaskStatus};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Candidates scoring lower aren't reported
+const MIN_SIMILARITY: f64 = 0.4;
+const MAX_CANDIDATES: usize = 5;
+/// Share of the score given to the descriptions when both tasks have one
+const DESCRIPTION_WEIGHT: f64 = 0.3;
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DuplicateCandidate {
+    pub task: Task,
+    /// From 0 to 1, 1 for the same words
+    pub similarity: f64,
+}
+
+/// Open tasks of the project most similar to the title and description, best
+/// match first
+pub async fn find_duplicates(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    title: &str,
+    description: Option<&str>,
+    exclude_task_id: Option<Uuid>,
+) -> Result<Vec<DuplicateCandidate>, sqlx::Error> {
+    let open_tasks = Task::find_by_project_id(pool, project_id)
+        .await?
+        .into_iter()
+        .filter(|task| {
+            task.archived_at.is_none()
+                && !matches!(task.status, TaskStatus::Done | TaskStatus::Cancelled)
+                && Some(task.id) != exclude_task_id
+        });
+    Ok(rank(title, description, open_tasks))
+}
+
+fn rank(
+    title: &str,
+    description: Option<&str>,
+    tasks: impl IntoIterator<Item = Task>,
+) -> Vec<DuplicateCandidate> {
+    let title = trigrams(title);
+    let description = description.map(trigrams).filter(|set| !set.is_empty());
+
+    let mut candidates: Vec<DuplicateCandidate> = tasks
+        .into_iter()
+        .filter_map(|task| {
+            let title_similarity = similarity(&title, &trigrams(&task.title));
+            let task_description = task
+                .description
+                .as_deref()
+                .map(trigrams)
+                .filter(|set| !set.is_empty());
+            let score = match (&description, &task_description) {
+                (Some(a), Some(b)) => {
+                    title_similarity * (1.0 - DESCRIPTION_WEIGHT)
+                        + similarity(a, b) * DESCRIPTION_WEIGHT
+                }
+                _ => title_similarity,
+            };
+            (score >= MIN_SIMILARITY).then_some(DuplicateCandidate {
+                task,
+                similarity: score,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    candidates.truncate(MAX_CANDIDATES);
+    candidates
+}
+
+/// Three-character sequences of each lowercased word, padded so that word
+/// starts weigh more than their ends
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let mut set = HashSet::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let chars: Vec<char> = "  "
+            .chars()
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(" ".chars())
+            .collect();
+        set.extend(
+            chars
+                .windows(3)
+                .map(|window| [window[0], window[1], window[2]]),
+        );
+    }
+    set
+}
+
+fn similarity(a: &HashSet<[char; 3]>, b: &HashSet<[char; 3]>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::task::TaskPriority;
+    use sqlx::types::Json;
+
+    use super::*;
+
+    fn task(title: &str, description: Option<&str>) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            title: title.to_string(),
+            description: description.map(str::to_string),
+            status: TaskStatus::Todo,
+            parent_workspace_id: None,
+            parent_task_id: None,
+            priority: TaskPriority::Normal,
+            position: 0,
+            archived_at: None,
+            due_at: None,
+            column_id: None,
+            epic_id: None,
+            executor_profile_id: None,
+            scope_paths: Json(Vec::new()),
+            shared_task_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn reworded_titles_rank_above_unrelated_ones() {
+        let tasks = vec![
+            task("Add dark mode toggle to settings", None),
+            task("Fix login redirect loop", None),
+            task(
+                "Settings: dark-mode toogle",
+                Some("Users keep asking for it"),
+            ),
+        ];
+
+        let candidates = rank("Add a dark mode toggle in settings", None, tasks);
+        let titles: Vec<&str> = candidates.iter().map(|c| c.task.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Add dark mode toggle to settings",
+                "Settings: dark-mode toogle"
+            ]
+        );
+        assert!(candidates[0].similarity > candidates[1].similarity);
+        assert!(rank("", None, vec![task("Anything", None)]).is_empty());
+    }
+}
@@ -0,0 +1,443 @@
+//! Tasks from a markdown plan, such as one a coding agent wrote while planning
+//! the work.
+//!
+//! Headings and list items become tasks, nested ones subtasks of the heading
+//! or item they are under. When the plan has checkboxes, only the checkbox
+//! items are tasks and plain bullets are details of the item above them.
+//! Other text describes the task it is written under. A lone top-level
+//! heading names the plan rather than a task.
+
+use db::models::task::{CreateTask, Task, TaskStatus};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct PlannedTask {
+    pub title: String,
+    pub description: Option<String>,
+    /// Ticked in the plan; the task is created as done
+    pub done: bool,
+    pub subtasks: Vec<PlannedTask>,
+}
+
+impl PlannedTask {
+    /// The task and all tasks under it
+    pub fn count(&self) -> usize {
+        1 + self.subtasks.iter().map(PlannedTask::count).sum::<usize>()
+    }
+
+    fn status(&self) -> TaskStatus {
+        if self.done {
+            TaskStatus::Done
+        } else {
+            TaskStatus::Todo
+        }
+    }
+}
+
+/// Status of every task of the plan, subtasks included
+pub fn statuses(tasks: &[PlannedTask]) -> Vec<TaskStatus> {
+    let mut statuses = Vec::new();
+    let mut pending: Vec<&PlannedTask> = tasks.iter().collect();
+    while let Some(task) = pending.pop() {
+        statuses.push(task.status());
+        pending.extend(&task.subtasks);
+    }
+    statuses
+}
+
+/// Create the plan's tasks in the project, under `parent_task_id` if given.
+/// Tasks keep the plan's order within their column; parents come first in the
+/// returned list.
+pub async fn create_tasks(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    parent_task_id: Option<Uuid>,
+    tasks: &[PlannedTask],
+) -> Result<Vec<Task>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut created = Vec::new();
+    let mut level: Vec<(Option<Uuid>, &PlannedTask)> =
+        tasks.iter().map(|task| (parent_task_id, task)).collect();
+    while !level.is_empty() {
+        // Each new task goes to the top of its column, so last to first
+        let mut level_tasks = Vec::with_capacity(level.len());
+        for (parent_task_id, planned) in level.iter().rev() {
+            let data = CreateTask {
+                status: Some(planned.status()),
+                parent_task_id: *parent_task_id,
+                ..CreateTask::from_title_description(
+                    project_id,
+                    planned.title.trim().to_string(),
+                    planned.description.clone(),
+                )
+            };
+            level_tasks.push(Task::create(&mut *tx, &data, Uuid::new_v4()).await?);
+        }
+        level_tasks.reverse();
+
+        level = level
+            .iter()
+            .zip(&level_tasks)
+            .flat_map(|((_, planned), task)| {
+                planned
+                    .subtasks
+                    .iter()
+                    .map(move |subtask| (Some(task.id), subtask))
+            })
+            .collect();
+        created.extend(level_tasks);
+    }
+    tx.commit().await?;
+    Ok(created)
+}
+
+/// A heading or list item the parser has started a task for
+struct Open {
+    path: Vec<usize>,
+    /// Headings by level, list items by indentation after every heading
+    depth: usize,
+    /// Indentation of the item's text, for telling its details apart
+    content_indent: usize,
+    description: Vec<String>,
+}
+
+enum Line<'a> {
+    Heading {
+        level: usize,
+        text: &'a str,
+    },
+    Item {
+        indent: usize,
+        checkbox: Option<bool>,
+        text: &'a str,
+    },
+    Text,
+}
+
+pub fn parse_plan(markdown: &str) -> Vec<PlannedTask> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let classified: Vec<Line> = classify_lines(&lines);
+    let checklist = classified.iter().any(|line| {
+        matches!(
+            line,
+            Line::Item {
+                checkbox: Some(_),
+                ..
+            }
+        )
+    });
+    let top_headings: Vec<usize> = classified
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| matches!(line, Line::Heading { level: 1, .. }).then_some(i))
+        .collect();
+    let first_heading = classified
+        .iter()
+        .position(|line| matches!(line, Line::Heading { .. }));
+    let plan_title = match top_headings.as_slice() {
+        [only] if Some(*only) == first_heading => Some(*only),
+        _ => None,
+    };
+
+    let mut roots: Vec<PlannedTask> = Vec::new();
+    let mut open: Vec<Open> = Vec::new();
+    let mut after_blank = false;
+    let mut in_fence = false;
+
+    for (i, (raw, line)) in lines.iter().zip(&classified).enumerate() {
+        if in_fence || is_fence(raw) {
+            if is_fence(raw) {
+                in_fence = !in_fence;
+            }
+            if let Some(top) = open.last_mut() {
+                top.description.push(raw.to_string());
+            }
+            after_blank = false;
+            continue;
+        }
+        if raw.trim().is_empty() {
+            if let Some(top) = open.last_mut() {
+                top.description.push(String::new());
+            }
+            after_blank = true;
+            continue;
+        }
+
+        match line {
+            Line::Heading { level, text } => {
+                close_deeper(&mut roots, &mut open, *level);
+                if Some(i) == plan_title {
+                    continue;
+                }
+                start_task(&mut roots, &mut open, *level, 0, text, false);
+            }
+            Line::Item {
+                indent,
+                checkbox,
+                text,
+            } if !checklist || checkbox.is_some() => {
+                let depth = 10 + indent;
+                close_deeper(&mut roots, &mut open, depth);
+                let content_indent = raw.len() - raw.trim_start().len() + 2;
+                start_task(
+                    &mut roots,
+                    &mut open,
+                    depth,
+                    content_indent,
+                    text,
+                    checkbox.unwrap_or(false),
+                );
+            }
+            _ => {
+                let indent = raw.len() - raw.trim_start().len();
+                // Text after a blank line that isn't indented under the item
+                // belongs to the section around the list
+                if after_blank {
+                    while open
+                        .last()
+                        .is_some_and(|top| top.depth >= 10 && indent < top.content_indent)
+                    {
+                        close_last(&mut roots, &mut open);
+                    }
+                }
+                if let Some(top) = open.last_mut() {
+                    top.description.push(raw.to_string());
+                }
+            }
+        }
+        after_blank = false;
+    }
+    while !open.is_empty() {
+        close_last(&mut roots, &mut open);
+    }
+    roots
+}
+
+fn classify_lines<'a>(lines: &[&'a str]) -> Vec<Line<'a>> {
+    let mut in_fence = false;
+    lines
+        .iter()
+        .map(|line| {
+            if is_fence(line) {
+                in_fence = !in_fence;
+                return Line::Text;
+            }
+            if in_fence {
+                return Line::Text;
+            }
+            heading(line)
+                .or_else(|| list_item(line))
+                .unwrap_or(Line::Text)
+        })
+        .collect()
+}
+
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+fn heading(line: &str) -> Option<Line<'_>> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&hashes) {
+        return None;
+    }
+    let text = line[hashes..].strip_prefix(' ')?;
+    let text = text.trim().trim_end_matches('#').trim();
+    (!text.is_empty()).then_some(Line::Heading {
+        level: hashes,
+        text,
+    })
+}
+
+fn list_item(line: &str) -> Option<Line<'_>> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let rest = if let Some(rest) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+        .or_else(|| trimmed.strip_prefix("+ "))
+    {
+        rest
+    } else {
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        trimmed[digits..]
+            .strip_prefix(". ")
+            .or_else(|| trimmed[digits..].strip_prefix(") "))?
+    };
+
+    let (checkbox, text) = match rest.get(..3) {
+        Some("[ ]") => (Some(false), &rest[3..]),
+        Some("[x]") | Some("[X]") => (Some(true), &rest[3..]),
+        _ => (None, rest),
+    };
+    let text = text.trim();
+    (!text.is_empty()).then_some(Line::Item {
+        indent,
+        checkbox,
+        text,
+    })
+}
+
+fn start_task(
+    roots: &mut Vec<PlannedTask>,
+    open: &mut Vec<Open>,
+    depth: usize,
+    content_indent: usize,
+    title: &str,
+    done: bool,
+) {
+    let task = PlannedTask {
+        title: title.to_string(),
+        description: None,
+        done,
+        subtasks: Vec::new(),
+    };
+    let path = match open.last() {
+        Some(parent) => {
+            let siblings = &mut task_at(roots, &parent.path).subtasks;
+            siblings.push(task);
+            let mut path = parent.path.clone();
+            path.push(siblings.len() - 1);
+            path
+        }
+        None => {
+            roots.push(task);
+            vec![roots.len() - 1]
+        }
+    };
+    open.push(Open {
+        path,
+        depth,
+        content_indent,
+        description: Vec::new(),
+    });
+}
+
+/// Finish the open tasks at or below the depth of a new heading or item
+fn close_deeper(roots: &mut [PlannedTask], open: &mut Vec<Open>, depth: usize) {
+    while open.last().is_some_and(|top| top.depth >= depth) {
+        close_last(roots, open);
+    }
+}
+
+fn close_last(roots: &mut [PlannedTask], open: &mut Vec<Open>) {
+    let Some(closed) = open.pop() else {
+        return;
+    };
+    task_at(roots, &closed.path).description = description(&closed.description);
+}
+
+fn task_at<'a>(roots: &'a mut [PlannedTask], path: &[usize]) -> &'a mut PlannedTask {
+    let mut task = &mut roots[path[0]];
+    for index in &path[1..] {
+        task = &mut task.subtasks[*index];
+    }
+    task
+}
+
+/// The lines without their common indentation and surrounding blank lines
+fn description(lines: &[String]) -> Option<String> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()?;
+    let text = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text.trim_matches('\n');
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(tasks: &[PlannedTask]) -> Vec<&str> {
+        tasks.iter().map(|task| task.title.as_str()).collect()
+    }
+
+    #[test]
+    fn checklists_under_headings_become_subtasks() {
+        let plan = "\
+# Dark mode
+
+Plan for adding a dark theme.
+
+## Theme tokens
+
+Move colors into variables first.
+
+- [x] Collect the colors in use
+- [ ] Add CSS variables
+  - light and dark values
+  - keep the names short
+
+  ```css
+  --bg: #fff;
+  ```
+- [ ] Switch components over
+  - [ ] Buttons
+
+## Settings toggle
+- [ ] Add the toggle
+";
+        let tasks = parse_plan(plan);
+
+        assert_eq!(titles(&tasks), vec!["Theme tokens", "Settings toggle"]);
+        let tokens = &tasks[0];
+        assert_eq!(
+            tokens.description.as_deref(),
+            Some("Move colors into variables first.")
+        );
+        assert_eq!(
+            titles(&tokens.subtasks),
+            vec![
+                "Collect the colors in use",
+                "Add CSS variables",
+                "Switch components over"
+            ]
+        );
+        assert!(tokens.subtasks[0].done);
+        assert_eq!(
+            tokens.subtasks[1].description.as_deref(),
+            Some("- light and dark values\n- keep the names short\n\n```css\n--bg: #fff;\n```")
+        );
+        assert_eq!(titles(&tokens.subtasks[2].subtasks), vec!["Buttons"]);
+        assert_eq!(tasks.iter().map(PlannedTask::count).sum::<usize>(), 7);
+    }
+
+    #[test]
+    fn plain_lists_become_tasks_with_nested_subtasks() {
+        let plan = "\
+1. Write the migration
+   Add the column with a default.
+2. Backfill
+   - existing rows
+   - archived rows
+
+Some closing notes.
+";
+        let tasks = parse_plan(plan);
+
+        assert_eq!(titles(&tasks), vec!["Write the migration", "Backfill"]);
+        assert_eq!(
+            tasks[0].description.as_deref(),
+            Some("Add the column with a default.")
+        );
+        assert_eq!(
+            titles(&tasks[1].subtasks),
+            vec!["existing rows", "archived rows"]
+        );
+        assert_eq!(tasks[1].description, None);
+    }
+}
@@ -0,0 +1,165 @@
+//! Tasks created on a schedule, such as a nightly changelog update or a
+//! weekly dependency audit.
+//!
+//! Each schedule stores its cron expression and when it runs next. The
+//! container service checks for due schedules every minute. A schedule that
+//! came due several times while the server was down runs once when it's back,
+//! then continues from the current time.
+
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use db::models::{
+    project::Project,
+    task::{CreateTask, Task},
+    task_schedule::{ScheduledAttempt, TaskSchedule, TaskScheduleRun},
+    workspace::{CreateWorkspace, Workspace},
+    workspace_repo::WorkspaceRepo,
+};
+use sqlx::types::Json;
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::container::{ContainerError, ContainerService};
+
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    #[error("Invalid cron expression: {0}")]
+    InvalidCron(String),
+}
+
+/// First time after `after` that the cron expression matches
+pub fn next_run(
+    cron_expression: &str,
+    after: DateTime<Utc>,
+) -> Result<DateTime<Utc>, ScheduleError> {
+    Cron::new(cron_expression)
+        .parse()
+        .and_then(|cron| cron.find_next_occurrence(&after, false))
+        .map_err(|e| ScheduleError::InvalidCron(e.to_string()))
+}
+
+/// Run every schedule that is due
+pub async fn run_due<C>(container: &C) -> Result<(), sqlx::Error>
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let now = Utc::now();
+
+    for schedule in TaskSchedule::find_due(pool, now).await? {
+        let run = run_schedule(container, &schedule).await?;
+        match &run.error {
+            Some(error) => tracing::warn!("Schedule '{}' failed: {}", schedule.title, error),
+            None => tracing::info!("Schedule '{}' created a task", schedule.title),
+        }
+
+        match next_run(&schedule.cron_expression, now) {
+            Ok(next_run_at) => TaskSchedule::mark_run(pool, schedule.id, now, next_run_at).await?,
+            Err(e) => {
+                tracing::error!("Pausing schedule '{}': {}", schedule.title, e);
+                TaskSchedule::set_paused(pool, schedule.id, true, schedule.next_run_at).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Create the schedule's task and start its attempt, recording what was
+/// created and where it stopped
+async fn run_schedule<C>(
+    container: &C,
+    schedule: &TaskSchedule,
+) -> Result<TaskScheduleRun, sqlx::Error>
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let record = |task_id, workspace_id, error| {
+        TaskScheduleRun::create(
+            pool,
+            schedule.id,
+            schedule.next_run_at,
+            task_id,
+            workspace_id,
+            error,
+        )
+    };
+
+    let create_task = CreateTask::from_title_description(
+        schedule.project_id,
+        schedule.title.clone(),
+        schedule.description.clone(),
+    );
+    let task = match Task::create(pool, &create_task, Uuid::new_v4()).await {
+        Ok(task) => task,
+        Err(e) => return record(None, None, Some(e.to_string())).await,
+    };
+
+    let Some(Json(attempt)) = &schedule.attempt else {
+        return record(Some(task.id), None, None).await;
+    };
+    match start_attempt(container, &task, attempt).await {
+        Ok(workspace) => record(Some(task.id), Some(workspace.id), None).await,
+        Err(e) => record(Some(task.id), None, Some(e.to_string())).await,
+    }
+}
+
+async fn start_attempt<C>(
+    container: &C,
+    task: &Task,
+    attempt: &ScheduledAttempt,
+) -> Result<Workspace, ContainerError>
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let agent_working_dir = Project::find_by_id(pool, task.project_id)
+        .await?
+        .and_then(|project| project.default_agent_working_dir)
+        .filter(|dir| !dir.is_empty());
+
+    let workspace_id = Uuid::new_v4();
+    let branch = container
+        .git_branch_from_workspace(&workspace_id, &task.title)
+        .await;
+    let workspace = Workspace::create(
+        pool,
+        &CreateWorkspace {
+            branch,
+            agent_working_dir,
+            executor_profile_id: Some(attempt.executor_profile_id.clone()),
+        },
+        workspace_id,
+        task.id,
+    )
+    .await?;
+    WorkspaceRepo::create_many(pool, workspace.id, &attempt.repos).await?;
+
+    container
+        .start_or_queue_workspace(&workspace, attempt.executor_profile_id.clone())
+        .await?;
+    Ok(workspace)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn finds_the_next_matching_time() {
+        let after = Utc.with_ymd_and_hms(2025, 12, 22, 3, 30, 0).unwrap();
+
+        assert_eq!(
+            next_run("0 3 * * *", after).unwrap(),
+            Utc.with_ymd_and_hms(2025, 12, 23, 3, 0, 0).unwrap()
+        );
+        // Mondays at 09:00; 2025-12-22 is a Monday
+        assert_eq!(
+            next_run("0 9 * * 1", after).unwrap(),
+            Utc.with_ymd_and_hms(2025, 12, 22, 9, 0, 0).unwrap()
+        );
+        assert!(next_run("every night", after).is_err());
+    }
+}
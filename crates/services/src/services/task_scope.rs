@@ -0,0 +1,124 @@
+//! The paths a task's attempts are meant to change, and the changes an
+//! attempt made outside them.
+//!
+//! Scopes are gitignore-style patterns matched against paths relative to a
+//! repository root, or prefixed with the repository name when a task spans
+//! several repositories. A `!` pattern carves a path back out of the scope.
+
+use std::collections::BTreeSet;
+
+use db::models::{task::Task, workspace::Workspace};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+use super::{
+    attempt_comparison::{ChangedFile, changed_files},
+    git::GitService,
+};
+
+pub struct PathScope {
+    matcher: Gitignore,
+}
+
+impl PathScope {
+    /// None for an empty list, which leaves the whole repository in scope
+    pub fn new(patterns: &[String]) -> Result<Option<Self>, ignore::Error> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        let mut builder = GitignoreBuilder::new("");
+        for pattern in patterns {
+            builder.add_line(None, pattern)?;
+        }
+        Ok(Some(Self {
+            matcher: builder.build()?,
+        }))
+    }
+
+    pub fn allows(&self, repo_name: &str, path: &str) -> bool {
+        [path.to_string(), format!("{repo_name}/{path}")]
+            .iter()
+            .any(|path| {
+                self.matcher
+                    .matched_path_or_any_parents(path, false)
+                    .is_ignore()
+            })
+    }
+
+    /// Changed paths the scope doesn't cover, prefixed with their repository
+    pub(crate) fn out_of_scope(&self, changed: &[ChangedFile]) -> Vec<String> {
+        changed
+            .iter()
+            .filter(|file| !self.allows(&file.repo_name, &file.path))
+            .map(|file| format!("{}/{}", file.repo_name, file.path))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The task's scope, if it has one. Patterns are checked when they are set,
+/// so one that no longer parses only drops the check.
+pub fn task_scope(task: &Task) -> Option<PathScope> {
+    PathScope::new(&task.scope_paths).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring the scope of task {}: {}", task.id, e);
+        None
+    })
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ScopeCheck {
+    pub scope_paths: Vec<String>,
+    pub files_changed: usize,
+    /// Changed paths outside the scope, prefixed with the repository name
+    pub out_of_scope: Vec<String>,
+}
+
+/// Compare what the attempt changed with its task's scope
+pub async fn check_attempt(
+    pool: &SqlitePool,
+    git: &GitService,
+    task: &Task,
+    workspace: &Workspace,
+) -> Result<ScopeCheck, sqlx::Error> {
+    let changed = changed_files(pool, git, workspace).await?;
+    let out_of_scope = task_scope(task)
+        .map(|scope| scope.out_of_scope(&changed))
+        .unwrap_or_default();
+    Ok(ScopeCheck {
+        scope_paths: task.scope_paths.0.clone(),
+        files_changed: changed
+            .iter()
+            .map(|file| (&file.repo_name, &file.path))
+            .collect::<BTreeSet<_>>()
+            .len(),
+        out_of_scope,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(patterns: &[&str]) -> PathScope {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        PathScope::new(&patterns).unwrap().unwrap()
+    }
+
+    #[test]
+    fn matches_like_gitignore() {
+        let server = scope(&["crates/server/", "*.md", "!crates/server/generated/"]);
+        assert!(server.allows("app", "crates/server/src/main.rs"));
+        assert!(server.allows("app", "docs/guide.md"));
+        assert!(!server.allows("app", "crates/server/generated/types.rs"));
+        assert!(!server.allows("app", "crates/db/src/lib.rs"));
+
+        let frontend = scope(&["frontend/src"]);
+        assert!(frontend.allows("frontend", "src/App.tsx"));
+        assert!(!frontend.allows("backend", "src/main.rs"));
+
+        assert!(PathScope::new(&[]).unwrap().is_none());
+    }
+}
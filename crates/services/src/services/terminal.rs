@@ -0,0 +1,528 @@
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use db::models::project_settings::ExecPolicy;
+use executors::env::{ContainerExec, ContainerRuntime, ExecutionEnv};
+use portable_pty::{ChildKiller, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    process_registry::{ProcessInfo, ProcessKind, ProcessRegistry},
+    secrets::SecretMasker,
+};
+
+/// Output kept per session and replayed to clients that reattach, e.g. after a
+/// page reload
+const SCROLLBACK_BYTES: usize = 256 * 1024;
+/// Sessions without an attached client are closed after this long without input
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const MAX_SESSIONS_PER_WORKSPACE: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum TerminalError {
+    #[error("{0}")]
+    NotAllowed(String),
+    #[error("Terminal session not found")]
+    NotFound,
+    #[error("Too many terminal sessions are open for this workspace")]
+    TooManySessions,
+    #[error("Terminal session has exited")]
+    Exited,
+    #[error(transparent)]
+    Pty(#[from] anyhow::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS)]
+pub struct TerminalSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl From<TerminalSize> for PtySize {
+    fn from(size: TerminalSize) -> Self {
+        PtySize {
+            rows: size.rows.max(1),
+            cols: size.cols.max(1),
+            pixel_width: 0,
+            pixel_height: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct OpenTerminalRequest {
+    pub cols: u16,
+    pub rows: u16,
+    /// Directory relative to the workspace root; the workspace root when omitted
+    pub working_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TerminalSessionInfo {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub size: TerminalSize,
+    pub created_at: DateTime<Utc>,
+    /// Exit code once the shell has exited
+    pub exit_code: Option<u32>,
+    pub exited: bool,
+}
+
+/// Messages sent by the client over a terminal WebSocket as JSON text frames
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum TerminalClientMessage {
+    Input { data: String },
+    Resize { cols: u16, rows: u16 },
+}
+
+#[derive(Debug, Clone)]
+pub enum TerminalEvent {
+    Output(Vec<u8>),
+    Exit(Option<u32>),
+}
+
+struct SessionState {
+    size: TerminalSize,
+    exit_code: Option<u32>,
+    exited: bool,
+    last_activity: Instant,
+    attached: usize,
+}
+
+pub struct TerminalSession {
+    id: Uuid,
+    workspace_id: Uuid,
+    created_at: DateTime<Utc>,
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    killer: Mutex<Box<dyn ChildKiller + Send + Sync>>,
+    /// Also held while broadcasting so attaching clients see every byte once
+    scrollback: Mutex<VecDeque<u8>>,
+    events: broadcast::Sender<TerminalEvent>,
+    state: Mutex<SessionState>,
+}
+
+impl TerminalSession {
+    pub fn info(&self) -> TerminalSessionInfo {
+        let state = self.state.lock().unwrap();
+        TerminalSessionInfo {
+            id: self.id,
+            workspace_id: self.workspace_id,
+            size: state.size,
+            created_at: self.created_at,
+            exit_code: state.exit_code,
+            exited: state.exited,
+        }
+    }
+
+    /// Attach a client, returning the scrollback to replay and a receiver for
+    /// everything after it. Call [`TerminalSession::detach`] when the client
+    /// goes away.
+    pub fn attach(&self) -> (Vec<u8>, broadcast::Receiver<TerminalEvent>) {
+        let scrollback = self.scrollback.lock().unwrap();
+        let receiver = self.events.subscribe();
+        let mut state = self.state.lock().unwrap();
+        state.attached += 1;
+        state.last_activity = Instant::now();
+        (scrollback.iter().copied().collect(), receiver)
+    }
+
+    pub fn detach(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.attached = state.attached.saturating_sub(1);
+        state.last_activity = Instant::now();
+    }
+
+    pub fn exit_code(&self) -> Option<Option<u32>> {
+        let state = self.state.lock().unwrap();
+        state.exited.then_some(state.exit_code)
+    }
+
+    pub fn write(&self, data: &[u8]) -> Result<(), TerminalError> {
+        if self.state.lock().unwrap().exited {
+            return Err(TerminalError::Exited);
+        }
+        self.state.lock().unwrap().last_activity = Instant::now();
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(data)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn resize(&self, size: TerminalSize) -> Result<(), TerminalError> {
+        self.master.lock().unwrap().resize(size.into())?;
+        self.state.lock().unwrap().size = size;
+        Ok(())
+    }
+
+    fn kill(&self) {
+        if let Err(e) = self.killer.lock().unwrap().kill() {
+            tracing::debug!("Failed to kill terminal {}: {}", self.id, e);
+        }
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        let state = self.state.lock().unwrap();
+        state.attached == 0 && now.duration_since(state.last_activity) > IDLE_TIMEOUT
+    }
+
+    fn push_output(&self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        let mut scrollback = self.scrollback.lock().unwrap();
+        scrollback.extend(data);
+        let overflow = scrollback.len().saturating_sub(SCROLLBACK_BYTES);
+        scrollback.drain(..overflow);
+        let _ = self.events.send(TerminalEvent::Output(data.to_vec()));
+    }
+
+    fn mark_exited(&self, exit_code: Option<u32>) {
+        let _scrollback = self.scrollback.lock().unwrap();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.exited = true;
+            state.exit_code = exit_code;
+            state.last_activity = Instant::now();
+        }
+        let _ = self.events.send(TerminalEvent::Exit(exit_code));
+    }
+}
+
+/// Interactive shells running in workspaces. Sessions outlive the WebSocket
+/// that drives them so a reloaded page can reattach, and are closed once they
+/// have been left unattached and idle for a while.
+#[derive(Clone, Default)]
+pub struct TerminalService {
+    sessions: Arc<DashMap<Uuid, Arc<TerminalSession>>>,
+}
+
+impl TerminalService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a shell in `working_dir`, inside the workspace's container when
+    /// `env` has one. A shell runs anything, so the project's policy has to
+    /// allow running the shell itself. Output is kept with `secrets` masked.
+    pub fn open(
+        &self,
+        workspace_id: Uuid,
+        working_dir: &Path,
+        env: &ExecutionEnv,
+        policy: &ExecPolicy,
+        secrets: Vec<String>,
+        size: TerminalSize,
+    ) -> Result<TerminalSessionInfo, TerminalError> {
+        for shell in shell_programs(env) {
+            policy.check(&shell).map_err(TerminalError::NotAllowed)?;
+        }
+        let open_sessions = self
+            .sessions
+            .iter()
+            .filter(|session| session.workspace_id == workspace_id && !session.info().exited)
+            .count();
+        if open_sessions >= MAX_SESSIONS_PER_WORKSPACE {
+            return Err(TerminalError::TooManySessions);
+        }
+
+        let pair = native_pty_system().openpty(size.into())?;
+        let mut child = pair.slave.spawn_command(shell_command(working_dir, env))?;
+        drop(pair.slave);
+        // The PTY spawns the shell itself, so it is moved into the attempt's
+        // cgroup right after it starts
+        if let (Some(cgroup), Some(pid)) = (&env.cgroup, child.process_id())
+            && let Err(e) = std::fs::write(cgroup.join("cgroup.procs"), pid.to_string())
+        {
+            tracing::warn!("Failed to apply resource limits to terminal: {}", e);
+        }
+        // portable-pty starts the shell in a session of its own, so its pid
+        // is also its process group
+        let pid = child.process_id();
+        if let Some(pid) = pid {
+            ProcessRegistry::global().register(
+                ProcessInfo::new(pid, ProcessKind::Terminal, working_dir.to_string_lossy())
+                    .with_workspace(workspace_id),
+            );
+        }
+        let mut reader = pair.master.try_clone_reader()?;
+        let writer = pair.master.take_writer()?;
+
+        let (events, _) = broadcast::channel(256);
+        let session = Arc::new(TerminalSession {
+            id: Uuid::new_v4(),
+            workspace_id,
+            created_at: Utc::now(),
+            master: Mutex::new(pair.master),
+            writer: Mutex::new(writer),
+            killer: Mutex::new(child.clone_killer()),
+            scrollback: Mutex::new(VecDeque::new()),
+            events,
+            state: Mutex::new(SessionState {
+                size,
+                exit_code: None,
+                exited: false,
+                last_activity: Instant::now(),
+                attached: 0,
+            }),
+        });
+
+        // PTY reads block, so each session gets its own reader thread
+        let reader_session = session.clone();
+        std::thread::spawn(move || {
+            let mut masker = SecretMasker::new(secrets);
+            let mut buf = [0u8; 8192];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => reader_session.push_output(masker.push(&buf[..n]).as_bytes()),
+                }
+            }
+            reader_session.push_output(masker.finish().as_bytes());
+            let exit_code = child.wait().ok().map(|status| status.exit_code());
+            if let Some(pid) = pid {
+                ProcessRegistry::global().unregister(pid);
+            }
+            reader_session.mark_exited(exit_code);
+        });
+
+        let info = session.info();
+        self.sessions.insert(session.id, session);
+        Ok(info)
+    }
+
+    /// Look up a session, making sure it belongs to `workspace_id`
+    pub fn get(
+        &self,
+        workspace_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<Arc<TerminalSession>, TerminalError> {
+        self.sessions
+            .get(&session_id)
+            .filter(|session| session.workspace_id == workspace_id)
+            .map(|session| session.clone())
+            .ok_or(TerminalError::NotFound)
+    }
+
+    pub fn list(&self, workspace_id: Uuid) -> Vec<TerminalSessionInfo> {
+        let mut sessions: Vec<_> = self
+            .sessions
+            .iter()
+            .filter(|session| session.workspace_id == workspace_id)
+            .map(|session| session.info())
+            .collect();
+        sessions.sort_by_key(|info| info.created_at);
+        sessions
+    }
+
+    pub fn close(&self, workspace_id: Uuid, session_id: Uuid) -> Result<(), TerminalError> {
+        self.get(workspace_id, session_id)?;
+        if let Some((_, session)) = self.sessions.remove(&session_id) {
+            session.kill();
+        }
+        Ok(())
+    }
+
+    /// Close every session of a workspace, e.g. before its directory is removed
+    pub fn close_workspace(&self, workspace_id: Uuid) {
+        self.sessions.retain(|_, session| {
+            if session.workspace_id == workspace_id {
+                session.kill();
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Periodically close sessions that nobody has used for [`IDLE_TIMEOUT`]
+    pub fn spawn_idle_reaper(&self) -> tokio::task::JoinHandle<()> {
+        let sessions = self.sessions.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let now = Instant::now();
+                sessions.retain(|id, session| {
+                    if !session.is_idle(now) {
+                        return true;
+                    }
+                    tracing::info!("Closing idle terminal session {}", id);
+                    session.kill();
+                    false
+                });
+            }
+        })
+    }
+}
+
+const LOGIN_SHELL: &str =
+    "if command -v bash >/dev/null 2>&1; then exec bash -l; else exec sh -l; fi";
+
+/// Programs a session may end up running as its shell: the user's shell
+/// locally, bash or sh in a container
+fn shell_programs(env: &ExecutionEnv) -> Vec<String> {
+    match env.container {
+        None => vec![std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string())],
+        Some(_) => vec!["bash".to_string(), "sh".to_string()],
+    }
+}
+
+fn shell_command(working_dir: &Path, env: &ExecutionEnv) -> CommandBuilder {
+    let mut command = match &env.container {
+        None => {
+            let mut command = CommandBuilder::new_default_prog();
+            command.cwd(working_dir);
+            command
+        }
+        Some(ContainerExec {
+            name,
+            runtime:
+                ContainerRuntime::Kubernetes {
+                    namespace,
+                    env_file,
+                    ..
+                },
+            ..
+        }) => {
+            // The workspace variables are in the pod's env file
+            let mut command = CommandBuilder::new("kubectl");
+            command.args(["exec", "-it", "-n", namespace, name, "--", "sh", "-c"]);
+            command.arg(format!(
+                "export TERM=xterm-256color; set -a; [ -f {env_file} ] && . {env_file}; set +a; cd \"$0\" && {LOGIN_SHELL}"
+            ));
+            command.arg(working_dir);
+            command
+        }
+        Some(ContainerExec {
+            name,
+            runtime: runtime @ ContainerRuntime::Ssh { env_file, .. },
+            ..
+        }) => {
+            let quote = |word: &str| {
+                shlex::try_quote(word)
+                    .map(|quoted| quoted.into_owned())
+                    .unwrap_or_default()
+            };
+            let mut command = CommandBuilder::new("ssh");
+            command.args(["-t", "-o", "BatchMode=yes", name, "--"]);
+            command.arg(format!(
+                "export TERM=xterm-256color; set -a; [ -f {env_file} ] && . {env_file}; set +a; cd {} && {LOGIN_SHELL}",
+                quote(&runtime.path_for(working_dir)),
+                env_file = quote(env_file),
+            ));
+            command
+        }
+        Some(container) => {
+            let mut command = CommandBuilder::new("docker");
+            command.args(["exec", "-it", "-w"]);
+            command.arg(working_dir);
+            if let Some(user) = &container.user {
+                command.args(["-u", user]);
+            }
+            command.args(["-e", "TERM"]);
+            for key in env.vars.keys() {
+                command.args(["-e", key]);
+            }
+            command.arg(&container.name);
+            command.args(["sh", "-c", LOGIN_SHELL]);
+            command
+        }
+    };
+    command.env("TERM", "xterm-256color");
+    for (key, value) in &env.vars {
+        command.env(key, value);
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container_shell_passes_variables_by_name() {
+        let mut env = ExecutionEnv::new();
+        env.insert("API_TOKEN", "secret-value");
+        env.container = Some(ContainerExec {
+            name: "vk-test".to_string(),
+            user: Some("dev".to_string()),
+            runtime: ContainerRuntime::Docker,
+        });
+
+        let command = shell_command(Path::new("/work/ws"), &env);
+        let argv: Vec<String> = command
+            .get_argv()
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            argv[..9],
+            [
+                "docker", "exec", "-it", "-w", "/work/ws", "-u", "dev", "-e", "TERM"
+            ]
+        );
+        assert!(argv.contains(&"API_TOKEN".to_string()));
+        assert!(!argv.iter().any(|arg| arg.contains("secret-value")));
+        assert_eq!(
+            command.get_env("API_TOKEN").and_then(|v| v.to_str()),
+            Some("secret-value")
+        );
+    }
+
+    #[test]
+    fn terminals_follow_the_exec_policy() {
+        let mut env = ExecutionEnv::new();
+        env.container = Some(ContainerExec {
+            name: "vk-test".to_string(),
+            user: None,
+            runtime: ContainerRuntime::Docker,
+        });
+        let service = TerminalService::new();
+        let open = |policy: &ExecPolicy| {
+            service.open(
+                Uuid::new_v4(),
+                Path::new("/work/ws"),
+                &env,
+                policy,
+                Vec::new(),
+                TerminalSize { cols: 80, rows: 24 },
+            )
+        };
+
+        assert!(matches!(
+            open(&ExecPolicy::default()),
+            Err(TerminalError::NotAllowed(_))
+        ));
+        let deny_list = ExecPolicy {
+            enabled: true,
+            allowed_programs: Vec::new(),
+            denied_programs: vec!["rm".to_string()],
+        };
+        assert!(matches!(
+            open(&deny_list),
+            Err(TerminalError::NotAllowed(_))
+        ));
+        let only_sh = ExecPolicy {
+            enabled: true,
+            allowed_programs: vec!["sh".to_string()],
+            denied_programs: Vec::new(),
+        };
+        assert!(matches!(open(&only_sh), Err(TerminalError::NotAllowed(_))));
+    }
+}
@@ -0,0 +1,323 @@
+//! Where the time on tasks goes: how long their attempts ran from the first
+//! process to the last, how much of it the coding agents spent working, and
+//! time people logged by hand.
+//!
+//! Nothing is recorded for this beyond the manual entries; attempt times are
+//! derived from the execution processes. Dev servers don't count, and a
+//! process that is still running counts up to now.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ProcessTiming},
+    task::Task,
+    task_time_entry::TaskTimeEntry,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct TimeTotals {
+    /// Wall-clock time of the attempts
+    pub attempt_seconds: i64,
+    /// Part of the attempt time the coding agents were running
+    pub agent_seconds: i64,
+    /// Time logged by hand
+    pub manual_seconds: i64,
+}
+
+impl TimeTotals {
+    fn add(&mut self, other: &TimeTotals) {
+        self.attempt_seconds += other.attempt_seconds;
+        self.agent_seconds += other.agent_seconds;
+        self.manual_seconds += other.manual_seconds;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptTime {
+    pub workspace_id: Uuid,
+    /// Executor of the attempt's first coding agent run
+    pub executor: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// None while a process of the attempt is running
+    pub ended_at: Option<DateTime<Utc>>,
+    pub wall_clock_seconds: i64,
+    pub agent_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskTime {
+    pub task_id: Uuid,
+    pub title: String,
+    /// Oldest first
+    pub attempts: Vec<AttemptTime>,
+    pub totals: TimeTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct ExecutorTime {
+    pub executor: String,
+    /// Attempts in which the executor ran
+    pub attempt_count: usize,
+    pub agent_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TimeReport {
+    /// Tasks that time was spent on, most time first
+    pub tasks: Vec<TaskTime>,
+    /// Coding agent time per executor, most time first
+    pub executors: Vec<ExecutorTime>,
+    pub totals: TimeTotals,
+}
+
+/// Time spent on the task so far
+pub async fn task_time(pool: &SqlitePool, task: &Task) -> Result<TaskTime, sqlx::Error> {
+    let timings = ExecutionProcess::find_timings_by_task_id(pool, task.id).await?;
+    let entries = TaskTimeEntry::find_by_task_id(pool, task.id).await?;
+    Ok(build_task_time(
+        task,
+        attempt_times(&timings, Utc::now()),
+        &entries,
+    ))
+}
+
+/// Time spent on the project's tasks within the range, each end optional.
+/// Attempt processes count when they started within the range and manual
+/// entries when the work was done within it.
+pub async fn project_report(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<TimeReport, sqlx::Error> {
+    let timings = ExecutionProcess::find_timings_by_project_id(pool, project_id, from, to).await?;
+    let entries = TaskTimeEntry::find_by_project_id(pool, project_id, from, to).await?;
+    let tasks = Task::find_by_project_id(pool, project_id).await?;
+    let now = Utc::now();
+
+    let mut timings_by_task: HashMap<Uuid, Vec<ProcessTiming>> = HashMap::new();
+    for timing in &timings {
+        timings_by_task
+            .entry(timing.task_id)
+            .or_default()
+            .push(timing.clone());
+    }
+
+    let mut report_tasks = Vec::new();
+    let mut totals = TimeTotals::default();
+    for task in &tasks {
+        let attempts = timings_by_task
+            .get(&task.id)
+            .map(|task_timings| attempt_times(task_timings, now))
+            .unwrap_or_default();
+        let task_entries: Vec<TaskTimeEntry> = entries
+            .iter()
+            .filter(|entry| entry.task_id == task.id)
+            .cloned()
+            .collect();
+        if attempts.is_empty() && task_entries.is_empty() {
+            continue;
+        }
+        let task_time = build_task_time(task, attempts, &task_entries);
+        totals.add(&task_time.totals);
+        report_tasks.push(task_time);
+    }
+    report_tasks.sort_by_key(|task| {
+        std::cmp::Reverse(task.totals.attempt_seconds + task.totals.manual_seconds)
+    });
+
+    Ok(TimeReport {
+        tasks: report_tasks,
+        executors: executor_times(&timings, now),
+        totals,
+    })
+}
+
+fn build_task_time(task: &Task, attempts: Vec<AttemptTime>, entries: &[TaskTimeEntry]) -> TaskTime {
+    let totals = TimeTotals {
+        attempt_seconds: attempts.iter().map(|a| a.wall_clock_seconds).sum(),
+        agent_seconds: attempts.iter().map(|a| a.agent_seconds).sum(),
+        manual_seconds: entries.iter().map(|entry| entry.seconds).sum(),
+    };
+    TaskTime {
+        task_id: task.id,
+        title: task.title.clone(),
+        attempts,
+        totals,
+    }
+}
+
+/// Group process timings into attempts, oldest first
+fn attempt_times(timings: &[ProcessTiming], now: DateTime<Utc>) -> Vec<AttemptTime> {
+    let mut order = Vec::new();
+    let mut by_workspace: HashMap<Uuid, Vec<&ProcessTiming>> = HashMap::new();
+    for timing in timings {
+        by_workspace
+            .entry(timing.workspace_id)
+            .or_insert_with(|| {
+                order.push(timing.workspace_id);
+                Vec::new()
+            })
+            .push(timing);
+    }
+
+    let mut attempts: Vec<AttemptTime> = order
+        .into_iter()
+        .filter_map(|workspace_id| {
+            let processes = by_workspace.remove(&workspace_id)?;
+            let started_at = processes.iter().map(|p| p.started_at).min()?;
+            let running = processes.iter().any(|p| p.completed_at.is_none());
+            let ended_at = processes.iter().filter_map(|p| p.completed_at).max();
+            let end = if running {
+                now
+            } else {
+                ended_at.unwrap_or(now)
+            };
+            let agent_processes = || {
+                processes
+                    .iter()
+                    .filter(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent)
+            };
+            Some(AttemptTime {
+                workspace_id,
+                executor: agent_processes()
+                    .min_by_key(|p| p.started_at)
+                    .and_then(|p| p.executor.clone()),
+                started_at,
+                ended_at: if running { None } else { ended_at },
+                wall_clock_seconds: seconds_between(started_at, end),
+                agent_seconds: agent_processes().map(|p| process_seconds(p, now)).sum(),
+            })
+        })
+        .collect();
+    attempts.sort_by_key(|attempt| attempt.started_at);
+    attempts
+}
+
+/// Coding agent time per executor, most time first
+fn executor_times(timings: &[ProcessTiming], now: DateTime<Utc>) -> Vec<ExecutorTime> {
+    let mut by_executor: HashMap<&str, (HashSet<Uuid>, i64)> = HashMap::new();
+    for timing in timings {
+        if timing.run_reason != ExecutionProcessRunReason::CodingAgent {
+            continue;
+        }
+        let executor = timing.executor.as_deref().unwrap_or("unknown");
+        let (workspaces, seconds) = by_executor.entry(executor).or_default();
+        workspaces.insert(timing.workspace_id);
+        *seconds += process_seconds(timing, now);
+    }
+
+    let mut executors: Vec<ExecutorTime> = by_executor
+        .into_iter()
+        .map(|(executor, (workspaces, agent_seconds))| ExecutorTime {
+            executor: executor.to_string(),
+            attempt_count: workspaces.len(),
+            agent_seconds,
+        })
+        .collect();
+    executors.sort_by(|a, b| {
+        b.agent_seconds
+            .cmp(&a.agent_seconds)
+            .then_with(|| a.executor.cmp(&b.executor))
+    });
+    executors
+}
+
+fn process_seconds(timing: &ProcessTiming, now: DateTime<Utc>) -> i64 {
+    seconds_between(timing.started_at, timing.completed_at.unwrap_or(now))
+}
+
+fn seconds_between(start: DateTime<Utc>, end: DateTime<Utc>) -> i64 {
+    (end - start).num_seconds().max(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn timing(
+        workspace_id: Uuid,
+        executor: &str,
+        run_reason: ExecutionProcessRunReason,
+        start: DateTime<Utc>,
+        minutes: Option<i64>,
+    ) -> ProcessTiming {
+        ProcessTiming {
+            workspace_id,
+            task_id: Uuid::nil(),
+            executor: Some(executor.to_string()),
+            run_reason,
+            started_at: start,
+            completed_at: minutes.map(|m| start + Duration::minutes(m)),
+        }
+    }
+
+    #[test]
+    fn attempts_span_their_first_to_last_process() {
+        let start = Utc::now() - Duration::hours(5);
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let timings = vec![
+            timing(
+                first,
+                "CLAUDE_CODE",
+                ExecutionProcessRunReason::SetupScript,
+                start,
+                Some(5),
+            ),
+            timing(
+                first,
+                "CLAUDE_CODE",
+                ExecutionProcessRunReason::CodingAgent,
+                start + Duration::minutes(5),
+                Some(20),
+            ),
+            timing(
+                first,
+                "CLAUDE_CODE",
+                ExecutionProcessRunReason::CodingAgent,
+                start + Duration::minutes(60),
+                Some(10),
+            ),
+            timing(
+                second,
+                "CODEX",
+                ExecutionProcessRunReason::CodingAgent,
+                start + Duration::hours(4),
+                None,
+            ),
+        ];
+        let now = start + Duration::hours(5);
+
+        let attempts = attempt_times(&timings, now);
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].wall_clock_seconds, 70 * 60);
+        assert_eq!(attempts[0].agent_seconds, 30 * 60);
+        assert_eq!(attempts[0].executor.as_deref(), Some("CLAUDE_CODE"));
+        assert!(attempts[1].ended_at.is_none());
+        assert_eq!(attempts[1].wall_clock_seconds, 60 * 60);
+
+        assert_eq!(
+            executor_times(&timings, now),
+            vec![
+                ExecutorTime {
+                    executor: "CODEX".to_string(),
+                    attempt_count: 1,
+                    agent_seconds: 60 * 60,
+                },
+                ExecutorTime {
+                    executor: "CLAUDE_CODE".to_string(),
+                    attempt_count: 1,
+                    agent_seconds: 30 * 60,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::{process::Stdio, time::Duration};
+
+use db::models::project_settings::{UploadScanSettings, UploadScanner};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    process::Command,
+};
+use utils::shell::get_shell_command;
+
+use super::workspace_files::FileUploadError;
+
+const SCAN_TIMEOUT: Duration = Duration::from_secs(60);
+const CLAMD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Run the project's configured scanner over an upload before it is written.
+/// Scanner failures reject the upload rather than letting unscanned files through.
+pub async fn scan_upload(
+    settings: &UploadScanSettings,
+    file_name: &str,
+    data: &[u8],
+) -> Result<(), FileUploadError> {
+    let Some(scanner) = settings.scanner.as_ref().filter(|_| settings.enabled) else {
+        return Ok(());
+    };
+
+    let scan = async {
+        match scanner {
+            UploadScanner::Command { command } => scan_with_command(command, file_name, data).await,
+            UploadScanner::Clamd { address } => scan_with_clamd(address, data).await,
+        }
+    };
+    tokio::time::timeout(SCAN_TIMEOUT, scan)
+        .await
+        .map_err(|_| FileUploadError::ScanFailed("scanner timed out".to_string()))?
+}
+
+async fn scan_with_command(
+    command: &str,
+    file_name: &str,
+    data: &[u8],
+) -> Result<(), FileUploadError> {
+    let (shell, shell_arg) = get_shell_command();
+    let mut child = Command::new(shell)
+        .arg(shell_arg)
+        .arg(command)
+        .env("VK_UPLOAD_FILENAME", file_name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| FileUploadError::ScanFailed(format!("failed to start scanner: {e}")))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A scanner may exit before reading everything; its exit code is what counts
+        let _ = stdin.write_all(data).await;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| FileUploadError::ScanFailed(e.to_string()))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+    let report = [output.stdout, output.stderr]
+        .iter()
+        .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+        .find(|text| !text.is_empty())
+        .unwrap_or_else(|| format!("scanner exited with {}", output.status));
+    Err(FileUploadError::ScanRejected(report))
+}
+
+async fn scan_with_clamd(address: &str, data: &[u8]) -> Result<(), FileUploadError> {
+    let failed = |e: std::io::Error| FileUploadError::ScanFailed(format!("clamd: {e}"));
+
+    #[cfg(unix)]
+    if !address.contains(':') {
+        let stream = tokio::net::UnixStream::connect(address)
+            .await
+            .map_err(failed)?;
+        return clamd_instream(stream, data).await.map_err(failed)?;
+    }
+
+    let stream = TcpStream::connect(address).await.map_err(failed)?;
+    clamd_instream(stream, data).await.map_err(failed)?
+}
+
+/// Send `data` with clamd's INSTREAM command and interpret the reply.
+async fn clamd_instream<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    data: &[u8],
+) -> std::io::Result<Result<(), FileUploadError>> {
+    stream.write_all(b"zINSTREAM\0").await?;
+    for chunk in data.chunks(CLAMD_CHUNK_SIZE) {
+        stream
+            .write_all(&(chunk.len() as u32).to_be_bytes())
+            .await?;
+        stream.write_all(chunk).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+    stream.flush().await?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    Ok(parse_clamd_reply(&String::from_utf8_lossy(&reply)))
+}
+
+fn parse_clamd_reply(reply: &str) -> Result<(), FileUploadError> {
+    let reply = reply.trim_end_matches(['\0', '\n']).trim();
+    let verdict = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if verdict == "OK" {
+        Ok(())
+    } else if let Some(signature) = verdict.strip_suffix("FOUND") {
+        Err(FileUploadError::ScanRejected(format!(
+            "malware detected: {}",
+            signature.trim()
+        )))
+    } else {
+        Err(FileUploadError::ScanFailed(format!(
+            "unexpected clamd reply: {verdict}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_clamd_replies() {
+        assert!(parse_clamd_reply("stream: OK\0").is_ok());
+        assert!(matches!(
+            parse_clamd_reply("stream: Eicar-Test-Signature FOUND\0"),
+            Err(FileUploadError::ScanRejected(reason)) if reason.contains("Eicar-Test-Signature")
+        ));
+        assert!(matches!(
+            parse_clamd_reply("INSTREAM size limit exceeded. ERROR\0"),
+            Err(FileUploadError::ScanFailed(_))
+        ));
+    }
+}
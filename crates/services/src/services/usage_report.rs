@@ -0,0 +1,278 @@
+//! Tokens and money the coding agents spent on tasks, per attempt, per task
+//! and per project.
+//!
+//! Each coding agent process keeps the last usage its executor reported.
+//! Claude Code reports cost as well as tokens; other executors only report
+//! tokens, so their runs add to the token counts but not to the cost, and are
+//! counted separately so a total cost isn't mistaken for a complete one.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::{ExecutionProcess, ProcessUsage},
+    task::Task,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct UsageTotals {
+    /// Input tokens not read from a cache
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Sum of the costs the executors reported
+    pub cost_usd: f64,
+    /// Coding agent runs counted
+    pub runs: u32,
+    /// Runs that reported tokens but no cost
+    pub runs_without_cost: u32,
+    /// Runs that reported nothing
+    pub runs_without_usage: u32,
+}
+
+impl UsageTotals {
+    fn add(&mut self, other: &UsageTotals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.cost_usd += other.cost_usd;
+        self.runs += other.runs;
+        self.runs_without_cost += other.runs_without_cost;
+        self.runs_without_usage += other.runs_without_usage;
+    }
+
+    fn add_process(&mut self, process: &ProcessUsage) {
+        let tokens = |count: Option<i64>| count.unwrap_or(0).max(0) as u64;
+        self.runs += 1;
+        self.input_tokens += tokens(process.input_tokens);
+        self.output_tokens += tokens(process.output_tokens);
+        self.cache_read_tokens += tokens(process.cache_read_tokens);
+        self.cache_write_tokens += tokens(process.cache_write_tokens);
+        match process.cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None if process.input_tokens.is_none() => self.runs_without_usage += 1,
+            None => self.runs_without_cost += 1,
+        }
+    }
+
+    fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptUsage {
+    pub workspace_id: Uuid,
+    /// Executor of the attempt's first coding agent run
+    pub executor: Option<String>,
+    /// None if no coding agent has run in the attempt
+    pub started_at: Option<DateTime<Utc>>,
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskUsage {
+    pub task_id: Uuid,
+    pub title: String,
+    /// Oldest first
+    pub attempts: Vec<AttemptUsage>,
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct ExecutorUsage {
+    pub executor: String,
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct UsageReport {
+    /// Tasks agents ran on, most expensive first
+    pub tasks: Vec<TaskUsage>,
+    /// Most expensive first
+    pub executors: Vec<ExecutorUsage>,
+    pub totals: UsageTotals,
+}
+
+/// Usage of one attempt so far
+pub async fn attempt_usage(
+    pool: &SqlitePool,
+    workspace_id: Uuid,
+) -> Result<AttemptUsage, sqlx::Error> {
+    let processes = ExecutionProcess::find_usage_by_workspace_id(pool, workspace_id).await?;
+    Ok(attempt_usages(&processes)
+        .pop()
+        .unwrap_or_else(|| AttemptUsage {
+            workspace_id,
+            executor: None,
+            started_at: None,
+            totals: UsageTotals::default(),
+        }))
+}
+
+/// Usage of the task's attempts so far
+pub async fn task_usage(pool: &SqlitePool, task: &Task) -> Result<TaskUsage, sqlx::Error> {
+    let processes = ExecutionProcess::find_usage_by_task_id(pool, task.id).await?;
+    Ok(build_task_usage(task, attempt_usages(&processes)))
+}
+
+/// Usage of the project's tasks by coding agent runs that started within the
+/// range, each end optional
+pub async fn project_report(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<UsageReport, sqlx::Error> {
+    let processes = ExecutionProcess::find_usage_by_project_id(pool, project_id, from, to).await?;
+    let tasks = Task::find_by_project_id(pool, project_id).await?;
+
+    let mut processes_by_task: HashMap<Uuid, Vec<ProcessUsage>> = HashMap::new();
+    for process in &processes {
+        processes_by_task
+            .entry(process.task_id)
+            .or_default()
+            .push(process.clone());
+    }
+
+    let mut report_tasks = Vec::new();
+    let mut totals = UsageTotals::default();
+    for task in &tasks {
+        let Some(task_processes) = processes_by_task.get(&task.id) else {
+            continue;
+        };
+        let task_usage = build_task_usage(task, attempt_usages(task_processes));
+        totals.add(&task_usage.totals);
+        report_tasks.push(task_usage);
+    }
+    report_tasks.sort_by(|a, b| most_spent_first(&a.totals, &b.totals));
+
+    Ok(UsageReport {
+        tasks: report_tasks,
+        executors: executor_usages(&processes),
+        totals,
+    })
+}
+
+fn build_task_usage(task: &Task, attempts: Vec<AttemptUsage>) -> TaskUsage {
+    let mut totals = UsageTotals::default();
+    for attempt in &attempts {
+        totals.add(&attempt.totals);
+    }
+    TaskUsage {
+        task_id: task.id,
+        title: task.title.clone(),
+        attempts,
+        totals,
+    }
+}
+
+/// Group process usage into attempts, oldest first, given processes sorted
+/// by start
+fn attempt_usages(processes: &[ProcessUsage]) -> Vec<AttemptUsage> {
+    let mut attempts: Vec<AttemptUsage> = Vec::new();
+    let mut index_by_workspace: HashMap<Uuid, usize> = HashMap::new();
+    for process in processes {
+        let index = *index_by_workspace
+            .entry(process.workspace_id)
+            .or_insert_with(|| {
+                attempts.push(AttemptUsage {
+                    workspace_id: process.workspace_id,
+                    executor: process.executor.clone(),
+                    started_at: Some(process.started_at),
+                    totals: UsageTotals::default(),
+                });
+                attempts.len() - 1
+            });
+        attempts[index].totals.add_process(process);
+    }
+    attempts
+}
+
+fn executor_usages(processes: &[ProcessUsage]) -> Vec<ExecutorUsage> {
+    let mut by_executor: HashMap<&str, UsageTotals> = HashMap::new();
+    for process in processes {
+        let executor = process.executor.as_deref().unwrap_or("unknown");
+        by_executor
+            .entry(executor)
+            .or_default()
+            .add_process(process);
+    }
+
+    let mut executors: Vec<ExecutorUsage> = by_executor
+        .into_iter()
+        .map(|(executor, totals)| ExecutorUsage {
+            executor: executor.to_string(),
+            totals,
+        })
+        .collect();
+    executors.sort_by(|a, b| {
+        most_spent_first(&a.totals, &b.totals).then_with(|| a.executor.cmp(&b.executor))
+    });
+    executors
+}
+
+/// By cost, then by tokens for executors that don't report cost
+fn most_spent_first(a: &UsageTotals, b: &UsageTotals) -> std::cmp::Ordering {
+    b.cost_usd
+        .total_cmp(&a.cost_usd)
+        .then_with(|| b.tokens().cmp(&a.tokens()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn process(
+        workspace_id: Uuid,
+        executor: &str,
+        minutes: i64,
+        tokens: Option<(i64, i64)>,
+        cost_usd: Option<f64>,
+    ) -> ProcessUsage {
+        ProcessUsage {
+            workspace_id,
+            task_id: Uuid::nil(),
+            executor: Some(executor.to_string()),
+            started_at: Utc::now() + Duration::minutes(minutes),
+            input_tokens: tokens.map(|(input, _)| input),
+            output_tokens: tokens.map(|(_, output)| output),
+            cache_read_tokens: tokens.map(|_| 0),
+            cache_write_tokens: tokens.map(|_| 0),
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn usage_rolls_up_by_attempt_and_executor() {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let processes = vec![
+            process(first, "CLAUDE_CODE", 0, Some((1000, 200)), Some(0.5)),
+            process(first, "CLAUDE_CODE", 10, Some((500, 100)), Some(0.25)),
+            process(second, "CODEX", 20, Some((4000, 800)), None),
+            process(second, "CODEX", 30, None, None),
+        ];
+
+        let attempts = attempt_usages(&processes);
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].totals.input_tokens, 1500);
+        assert_eq!(attempts[0].totals.cost_usd, 0.75);
+        assert_eq!(attempts[0].totals.runs, 2);
+        assert_eq!(attempts[1].totals.output_tokens, 800);
+        assert_eq!(attempts[1].totals.runs_without_cost, 1);
+        assert_eq!(attempts[1].totals.runs_without_usage, 1);
+
+        let executors = executor_usages(&processes);
+        let names: Vec<&str> = executors.iter().map(|e| e.executor.as_str()).collect();
+        assert_eq!(names, vec!["CLAUDE_CODE", "CODEX"]);
+    }
+}
@@ -0,0 +1,190 @@
+//! How attempts went per executor profile variant, so variants of a profile
+//! (another model, other settings or prompt) can be compared on real tasks.
+//!
+//! An attempt counts for the variant it was started with. It is merged once
+//! merged directly or through a merged PR, and abandoned when it wasn't but
+//! its task was finished, cancelled or attempted again; anything else is still
+//! open and left out of the rates.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    task::TaskStatus,
+    workspace::{Workspace, WorkspaceOutcome},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+const DEFAULT_VARIANT: &str = "DEFAULT";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct VariantStats {
+    pub executor: String,
+    /// `DEFAULT` for the executor's default configuration
+    pub variant: String,
+    pub attempts: u32,
+    pub merged: u32,
+    pub abandoned: u32,
+    pub open: u32,
+    /// Share of the merged and abandoned attempts that were merged; None
+    /// while there are none
+    pub merge_rate: Option<f64>,
+    /// Coding agent runs after the first in a merged attempt, on average:
+    /// the rounds of feedback a merge took
+    pub avg_follow_ups_to_merge: Option<f64>,
+    /// Sum of the costs the attempts' coding agent runs reported
+    pub cost_usd: f64,
+    /// All the cost, abandoned attempts' included, per merged attempt
+    pub cost_per_merge_usd: Option<f64>,
+    /// Runs that reported no cost, so `cost_usd` leaves them out
+    pub runs_without_cost: u32,
+}
+
+/// Stats of the variants the project's attempts created within the range
+/// were started with, by executor and best merge rate first
+pub async fn project_variant_stats(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<VariantStats>, sqlx::Error> {
+    let outcomes = Workspace::find_outcomes_by_project_id(pool, project_id, from, to).await?;
+    Ok(variant_stats(&outcomes))
+}
+
+fn variant_stats(outcomes: &[WorkspaceOutcome]) -> Vec<VariantStats> {
+    let mut by_variant: BTreeMap<(String, String), (VariantStats, i64)> = BTreeMap::new();
+    for outcome in outcomes {
+        let (executor, variant) = match &outcome.executor_profile_id {
+            Some(profile) => (
+                profile.executor.to_string(),
+                profile
+                    .variant
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_VARIANT.to_string()),
+            ),
+            None => (
+                outcome
+                    .session_executor
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string()),
+                DEFAULT_VARIANT.to_string(),
+            ),
+        };
+        let (stats, follow_ups) = by_variant
+            .entry((executor.clone(), variant.clone()))
+            .or_insert_with(|| {
+                (
+                    VariantStats {
+                        executor,
+                        variant,
+                        ..Default::default()
+                    },
+                    0,
+                )
+            });
+        stats.attempts += 1;
+        if outcome.merged {
+            stats.merged += 1;
+            *follow_ups += (outcome.coding_agent_runs - 1).max(0);
+        } else if outcome.superseded
+            || matches!(
+                outcome.task_status,
+                TaskStatus::Done | TaskStatus::Cancelled
+            )
+        {
+            stats.abandoned += 1;
+        } else {
+            stats.open += 1;
+        }
+        stats.cost_usd += outcome.cost_usd.unwrap_or(0.0);
+        stats.runs_without_cost += outcome.runs_without_cost.max(0) as u32;
+    }
+
+    let mut variants: Vec<VariantStats> = by_variant
+        .into_values()
+        .map(|(mut stats, follow_ups)| {
+            let decided = stats.merged + stats.abandoned;
+            if decided > 0 {
+                stats.merge_rate = Some(f64::from(stats.merged) / f64::from(decided));
+            }
+            if stats.merged > 0 {
+                let merged = f64::from(stats.merged);
+                stats.avg_follow_ups_to_merge = Some(follow_ups as f64 / merged);
+                stats.cost_per_merge_usd = Some(stats.cost_usd / merged);
+            }
+            stats
+        })
+        .collect();
+    variants.sort_by(|a, b| {
+        a.executor.cmp(&b.executor).then_with(|| {
+            b.merge_rate
+                .unwrap_or(-1.0)
+                .total_cmp(&a.merge_rate.unwrap_or(-1.0))
+                .then_with(|| a.variant.cmp(&b.variant))
+        })
+    });
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+    use sqlx::types::Json;
+
+    use super::*;
+
+    fn outcome(
+        variant: Option<&str>,
+        task_status: TaskStatus,
+        merged: bool,
+        superseded: bool,
+        runs: i64,
+        cost_usd: f64,
+    ) -> WorkspaceOutcome {
+        let profile = ExecutorProfileId {
+            executor: BaseCodingAgent::ClaudeCode,
+            variant: variant.map(str::to_string),
+        };
+        WorkspaceOutcome {
+            workspace_id: Uuid::new_v4(),
+            task_status,
+            executor_profile_id: Some(Json(profile)),
+            session_executor: None,
+            merged,
+            superseded,
+            coding_agent_runs: runs,
+            cost_usd: Some(cost_usd),
+            runs_without_cost: 0,
+        }
+    }
+
+    #[test]
+    fn attempts_roll_up_by_variant() {
+        let outcomes = vec![
+            outcome(None, TaskStatus::Done, true, false, 3, 1.0),
+            outcome(None, TaskStatus::Done, false, true, 1, 0.5),
+            outcome(Some("OPUS"), TaskStatus::Done, true, false, 1, 2.0),
+            outcome(Some("OPUS"), TaskStatus::InReview, false, false, 2, 1.0),
+        ];
+
+        let stats = variant_stats(&outcomes);
+        let variants: Vec<&str> = stats.iter().map(|s| s.variant.as_str()).collect();
+        assert_eq!(variants, vec!["OPUS", "DEFAULT"]);
+
+        let opus = &stats[0];
+        assert_eq!((opus.merged, opus.abandoned, opus.open), (1, 0, 1));
+        assert_eq!(opus.merge_rate, Some(1.0));
+        assert_eq!(opus.cost_per_merge_usd, Some(3.0));
+
+        let default = &stats[1];
+        assert_eq!(default.executor, "CLAUDE_CODE");
+        assert_eq!((default.merged, default.abandoned), (1, 1));
+        assert_eq!(default.merge_rate, Some(0.5));
+        assert_eq!(default.avg_follow_ups_to_merge, Some(2.0));
+        assert_eq!(default.cost_usd, 1.5);
+    }
+}
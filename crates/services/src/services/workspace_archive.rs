@@ -0,0 +1,183 @@
+use std::{
+    fs::File,
+    io::{self, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use flate2::{Compression, write::GzEncoder};
+use ignore::WalkBuilder;
+use serde::Deserialize;
+use thiserror::Error;
+use ts_rs::TS;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use super::workspace_files::SCRATCH_DIR;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    Walk(#[from] ignore::Error),
+    #[error("Invalid archive path: {0}")]
+    InvalidPath(String),
+    #[error("Path not found: {0}")]
+    NotFound(String),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    #[ts(rename = "zip")]
+    Zip,
+    #[default]
+    #[serde(rename = "tar.gz")]
+    #[ts(rename = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "application/zip",
+            ArchiveFormat::TarGz => "application/gzip",
+        }
+    }
+}
+
+/// Resolve the directory `relative` (empty for the whole workspace) against the
+/// workspace root, refusing anything that escapes it.
+pub fn resolve_archive_root(
+    workspace_root: &Path,
+    relative: &str,
+) -> Result<PathBuf, ArchiveError> {
+    let relative = relative.trim_matches('/');
+    if Path::new(relative).is_absolute() {
+        return Err(ArchiveError::InvalidPath(relative.to_string()));
+    }
+
+    let canonical_root = dunce::canonicalize(workspace_root)?;
+    let target = dunce::canonicalize(canonical_root.join(relative))
+        .map_err(|_| ArchiveError::NotFound(relative.to_string()))?;
+    if !target.starts_with(&canonical_root) || !target.is_dir() {
+        return Err(ArchiveError::InvalidPath(relative.to_string()));
+    }
+    Ok(target)
+}
+
+/// Build an archive of every file below `root` in an anonymous temporary file and
+/// return it rewound to the start. Entries are prefixed with `root`'s directory
+/// name. `.git` is always skipped; gitignored files and the attempt's scratch
+/// directory only when `respect_gitignore` is set.
+pub fn build_archive(
+    root: &Path,
+    format: ArchiveFormat,
+    respect_gitignore: bool,
+) -> Result<File, ArchiveError> {
+    build_archive_with_files(root, format, respect_gitignore, &[])
+}
+
+/// Like [`build_archive`], with `generated` files added at their relative
+/// paths. They replace workspace files at the same path.
+pub fn build_archive_with_files(
+    root: &Path,
+    format: ArchiveFormat,
+    respect_gitignore: bool,
+    generated: &[(PathBuf, Vec<u8>)],
+) -> Result<File, ArchiveError> {
+    let prefix = root
+        .file_name()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("workspace"));
+    let mut files = collect_files(root, respect_gitignore)?;
+    files.retain(|relative| !generated.iter().any(|(path, _)| path == relative));
+    let out = tempfile::tempfile()?;
+
+    let mut out = match format {
+        ArchiveFormat::TarGz => {
+            let encoder = GzEncoder::new(out, Compression::default());
+            let mut builder = tar::Builder::new(encoder);
+            builder.follow_symlinks(false);
+            for relative in &files {
+                builder.append_path_with_name(root.join(relative), prefix.join(relative))?;
+            }
+            for (relative, contents) in generated {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map_or(0, |elapsed| elapsed.as_secs()),
+                );
+                builder.append_data(&mut header, prefix.join(relative), contents.as_slice())?;
+            }
+            builder.into_inner()?.finish()?
+        }
+        ArchiveFormat::Zip => write_zip(root, &prefix, &files, generated, out)?,
+    };
+    out.rewind()?;
+    Ok(out)
+}
+
+fn collect_files(root: &Path, respect_gitignore: bool) -> Result<Vec<PathBuf>, ArchiveError> {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .parents(respect_gitignore)
+        .ignore(respect_gitignore)
+        .git_ignore(respect_gitignore)
+        .git_global(respect_gitignore)
+        .git_exclude(respect_gitignore)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            entry.file_name() != ".git"
+                && !(respect_gitignore && entry.depth() == 1 && entry.file_name() == SCRATCH_DIR)
+        })
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry?;
+        if entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+            && let Ok(relative) = entry.path().strip_prefix(root)
+        {
+            files.push(relative.to_path_buf());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn write_zip<W: Write + Seek>(
+    root: &Path,
+    prefix: &Path,
+    files: &[PathBuf],
+    generated: &[(PathBuf, Vec<u8>)],
+    out: W,
+) -> Result<W, ArchiveError> {
+    let mut zip = ZipWriter::new(out);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    let name = |relative: &Path| prefix.join(relative).to_string_lossy().replace('\\', "/");
+    for relative in files {
+        zip.start_file(name(relative), options)?;
+        io::copy(&mut File::open(root.join(relative))?, &mut zip)?;
+    }
+    for (relative, contents) in generated {
+        zip.start_file(name(relative), options)?;
+        zip.write_all(contents)?;
+    }
+    Ok(zip.finish()?)
+}
@@ -0,0 +1,175 @@
+//! Export of a task attempt's workspace as a bundle that opens as a dev
+//! container, for taking over from the agent on one's own machine.
+//!
+//! The bundle holds the workspace files as they are, uncommitted changes
+//! included, and a generated `.devcontainer/devcontainer.json` at its root with
+//! the project's image, plain variables, resource limits, GPUs and the
+//! repositories' setup scripts. Secret values are never exported: the
+//! configuration reads them from variables of the same name on the machine
+//! opening the bundle. Projects using the devcontainer backend already ship their configuration in
+//! the repository, so nothing is generated for them.
+
+use std::path::PathBuf;
+
+use db::models::{
+    project_env_var::ProjectEnvVar,
+    project_repo::ProjectRepo,
+    project_secret::ProjectSecret,
+    project_settings::{ContainerBackend, ProjectSettings, ResourceLimits},
+    task::Task,
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use serde_json::{Map, Value, json};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Image for projects whose executors don't run in a container
+const DEFAULT_IMAGE: &str = "mcr.microsoft.com/devcontainers/base:ubuntu";
+const CONFIG_PATH: &str = ".devcontainer/devcontainer.json";
+
+/// Files to add to the workspace's bundle, relative to the workspace root
+pub async fn generated_files(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    task: &Task,
+) -> Result<Vec<(PathBuf, Vec<u8>)>, sqlx::Error> {
+    let settings = ProjectSettings::find_by_project_id(pool, task.project_id).await?;
+    let image = match &settings.container_backend {
+        ContainerBackend::Devcontainer => return Ok(Vec::new()),
+        ContainerBackend::Docker { image } => image.as_str(),
+        ContainerBackend::Local | ContainerBackend::Ssh { .. } => DEFAULT_IMAGE,
+    };
+
+    let vars: Vec<(String, String)> = ProjectEnvVar::find_by_project_id(pool, task.project_id)
+        .await?
+        .into_iter()
+        .map(|var| (var.name, var.value))
+        .collect();
+    let secrets: Vec<String> = ProjectSecret::find_by_project_id(pool, task.project_id)
+        .await?
+        .into_iter()
+        .map(|secret| secret.name)
+        .collect();
+    let repo_ids: Vec<Uuid> = WorkspaceRepo::find_by_workspace_id(pool, workspace.id)
+        .await?
+        .into_iter()
+        .map(|repo| repo.repo_id)
+        .collect();
+    let setup_scripts: Vec<(String, String)> =
+        ProjectRepo::find_by_project_id_with_names(pool, task.project_id)
+            .await?
+            .into_iter()
+            .filter(|repo| repo_ids.contains(&repo.repo_id))
+            .filter_map(|repo| {
+                let script = repo
+                    .setup_script
+                    .filter(|script| !script.trim().is_empty())?;
+                Some((repo.repo_name, script))
+            })
+            .collect();
+
+    let config = devcontainer_config(
+        &task.title,
+        image,
+        &vars,
+        &secrets,
+        &settings.resource_limits,
+        settings.gpus.count,
+        &setup_scripts,
+    );
+    let contents = serde_json::to_vec_pretty(&config).unwrap_or_default();
+    Ok(vec![(PathBuf::from(CONFIG_PATH), contents)])
+}
+
+fn devcontainer_config(
+    name: &str,
+    image: &str,
+    vars: &[(String, String)],
+    secrets: &[String],
+    limits: &ResourceLimits,
+    gpus: u32,
+    setup_scripts: &[(String, String)],
+) -> Value {
+    let mut config = json!({ "name": name, "image": image });
+
+    // Secrets win over plain variables of the same name, as in the workspace
+    let container_env: Map<String, Value> = vars
+        .iter()
+        .filter(|(key, _)| !secrets.contains(key))
+        .map(|(key, value)| (key.clone(), Value::from(value.as_str())))
+        .collect();
+    if !container_env.is_empty() {
+        config["containerEnv"] = Value::Object(container_env);
+    }
+    if !secrets.is_empty() {
+        config["remoteEnv"] = secrets
+            .iter()
+            .map(|name| (name.clone(), Value::from(format!("${{localEnv:{name}}}"))))
+            .collect::<Map<String, Value>>()
+            .into();
+    }
+
+    let mut run_args = Vec::new();
+    if let Some(cores) = limits.cpu_cores {
+        run_args.push(format!("--cpus={cores}"));
+    }
+    if let Some(mb) = limits.memory_mb {
+        run_args.push(format!("--memory={mb}m"));
+    }
+    if let Some(max) = limits.max_processes {
+        run_args.push(format!("--pids-limit={max}"));
+    }
+    if gpus > 0 {
+        run_args.push(format!("--gpus={gpus}"));
+    }
+    if !run_args.is_empty() {
+        config["runArgs"] = json!(run_args);
+    }
+
+    // Each script runs from its repository, like the attempt's setup scripts
+    if !setup_scripts.is_empty() {
+        let command = setup_scripts
+            .iter()
+            .map(|(repo, script)| {
+                let dir = shlex::try_quote(repo).unwrap_or_else(|_| repo.as_str().into());
+                format!("(cd {dir} && {script})")
+            })
+            .collect::<Vec<_>>()
+            .join(" && ");
+        config["postCreateCommand"] = Value::from(command);
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn devcontainer_config_keeps_secrets_out() {
+        let config = devcontainer_config(
+            "Fix login",
+            "node:22",
+            &[
+                ("API_URL".to_string(), "http://localhost".to_string()),
+                ("TOKEN".to_string(), "plain".to_string()),
+            ],
+            &["TOKEN".to_string()],
+            &ResourceLimits {
+                memory_mb: Some(2048),
+                ..Default::default()
+            },
+            1,
+            &[("web app".to_string(), "npm ci".to_string())],
+        );
+        assert_eq!(config["image"], "node:22");
+        assert_eq!(
+            config["containerEnv"],
+            json!({ "API_URL": "http://localhost" })
+        );
+        assert_eq!(config["remoteEnv"], json!({ "TOKEN": "${localEnv:TOKEN}" }));
+        assert_eq!(config["runArgs"], json!(["--memory=2048m", "--gpus=1"]));
+        assert_eq!(config["postCreateCommand"], "(cd 'web app' && npm ci)");
+    }
+}
@@ -0,0 +1,196 @@
+use std::{path::Path, process::Stdio, time::Duration};
+
+use db::models::project_settings::ExecPolicy;
+use executors::env::ExecutionEnv;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    process::Child,
+    sync::mpsc,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    process_registry::{self, ProcessInfo, ProcessKind, ProcessRegistry},
+    secrets::SecretMasker,
+    workspace_files::{self, FileUploadError},
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const MAX_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Error)]
+pub enum ExecError {
+    #[error("{0}")]
+    NotAllowed(String),
+    #[error("Invalid command: {0}")]
+    InvalidCommand(String),
+    #[error(transparent)]
+    InvalidWorkingDir(#[from] FileUploadError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct ExecRequest {
+    /// Command line, split into arguments like a POSIX shell would but run
+    /// without one, so pipes, redirects and variable expansion are not available
+    pub command: String,
+    /// Directory relative to the workspace root; the workspace root when omitted
+    pub working_dir: Option<String>,
+    /// Defaults to 10 minutes, capped at an hour
+    pub timeout_secs: Option<u64>,
+}
+
+/// One event of a running command's output stream
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum ExecOutput {
+    Stdout { data: String },
+    Stderr { data: String },
+    Exit { code: Option<i32> },
+    TimedOut,
+}
+
+/// Start `request` in the workspace after checking it against the project's
+/// policy. Output is streamed with `secrets` masked; the command is killed when
+/// it times out or the returned stream is dropped.
+pub fn spawn_exec(
+    workspace_id: Uuid,
+    workspace_root: &Path,
+    env: &ExecutionEnv,
+    policy: &ExecPolicy,
+    secrets: Vec<String>,
+    request: &ExecRequest,
+) -> Result<ReceiverStream<ExecOutput>, ExecError> {
+    let args = shlex::split(&request.command)
+        .ok_or_else(|| ExecError::InvalidCommand("unbalanced quotes".to_string()))?;
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| ExecError::InvalidCommand("empty command".to_string()))?;
+    policy.check(program).map_err(ExecError::NotAllowed)?;
+
+    let working_dir = match request
+        .working_dir
+        .as_deref()
+        .filter(|dir| !dir.trim_matches('/').is_empty())
+    {
+        Some(dir) => workspace_files::validate_target_path(workspace_root, dir)?,
+        None => workspace_root.to_path_buf(),
+    };
+    if !working_dir.is_dir() {
+        return Err(ExecError::InvalidCommand(format!(
+            "working directory does not exist: {}",
+            request.working_dir.as_deref().unwrap_or_default()
+        )));
+    }
+    let timeout = request
+        .timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TIMEOUT)
+        .min(MAX_TIMEOUT);
+
+    let mut command = env.command(program, args, &working_dir);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Its own group, so whatever the command starts is stopped with it
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    if let Some(pid) = pid {
+        ProcessRegistry::global().register(
+            ProcessInfo::new(pid, ProcessKind::Exec, &request.command).with_workspace(workspace_id),
+        );
+    }
+
+    let (tx, rx) = mpsc::channel(64);
+    let stdout = child.stdout.take().map(|out| {
+        forward_output(out, tx.clone(), secrets.clone(), |data| {
+            ExecOutput::Stdout { data }
+        })
+    });
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| forward_output(err, tx.clone(), secrets, |data| ExecOutput::Stderr { data }));
+
+    tokio::spawn(async move {
+        let finished = tokio::select! {
+            status = child.wait() => Some(status),
+            _ = tokio::time::sleep(timeout) => None,
+            // The client went away
+            _ = tx.closed() => {
+                stop(&mut child, pid).await;
+                return;
+            }
+        };
+        let last = match finished {
+            Some(status) => {
+                // Drain what is left of the output before reporting the exit
+                for task in [stdout, stderr].into_iter().flatten() {
+                    let _ = task.await;
+                }
+                ExecOutput::Exit {
+                    code: status.ok().and_then(|status| status.code()),
+                }
+            }
+            None => {
+                stop(&mut child, pid).await;
+                ExecOutput::TimedOut
+            }
+        };
+        if let Some(pid) = pid {
+            ProcessRegistry::global().unregister(pid);
+        }
+        let _ = tx.send(last).await;
+    });
+
+    Ok(ReceiverStream::new(rx))
+}
+
+async fn stop(child: &mut Child, pid: Option<u32>) {
+    match pid {
+        Some(pid) => {
+            process_registry::kill_tree(pid).await;
+            ProcessRegistry::global().unregister(pid);
+        }
+        None => {
+            let _ = child.kill().await;
+        }
+    }
+}
+
+fn forward_output(
+    mut reader: impl AsyncRead + Unpin + Send + 'static,
+    tx: mpsc::Sender<ExecOutput>,
+    secrets: Vec<String>,
+    wrap: fn(String) -> ExecOutput,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; 8192];
+        let mut masker = SecretMasker::new(secrets);
+        loop {
+            match reader.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let data = masker.push(&buf[..n]);
+                    if !data.is_empty() && tx.send(wrap(data)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+        let rest = masker.finish();
+        if !rest.is_empty() {
+            let _ = tx.send(wrap(rest)).await;
+        }
+    })
+}
@@ -0,0 +1,577 @@
+use std::path::{Component, Path, PathBuf};
+
+use db::models::{
+    project_settings::{GitignoredUploadPolicy, ProjectSettings},
+    workspace_artifact::CreateWorkspaceArtifact,
+};
+use ignore::gitignore::GitignoreBuilder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use ts_rs::TS;
+
+use super::upload_scan;
+
+#[derive(Debug, Error)]
+pub enum FileUploadError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Invalid target path: {0}")]
+    InvalidPath(String),
+    #[error("Writing into .git is not allowed")]
+    GitDirectory,
+    #[error("Path resolves outside the workspace: {0}")]
+    OutsideWorkspace(String),
+    #[error("Path is ignored by .gitignore: {0}")]
+    Gitignored(String),
+    #[error("Upload rejected by project policy: {0}")]
+    PolicyViolation(String),
+    #[error("Upload rejected by scanner: {0}")]
+    ScanRejected(String),
+    #[error("Upload scan failed: {0}")]
+    ScanFailed(String),
+    #[error("File not found: {0}")]
+    NotFound(String),
+    #[error("File is not valid UTF-8 text: {0}")]
+    NotText(String),
+    #[error("File changed since it was loaded (expected {expected:?}, found {actual:?})")]
+    HashMismatch {
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+    #[error("Failed to apply patch: {0}")]
+    PatchFailed(String),
+}
+
+/// Result of writing a single uploaded file into a workspace
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct UploadedFile {
+    /// Path relative to the workspace root, using forward slashes
+    pub path: String,
+    pub size_bytes: i64,
+    /// SHA256 of the uploaded contents
+    pub hash: String,
+    /// True when the path matches a .gitignore rule, so the agent's commits won't include it
+    pub gitignored: bool,
+}
+
+/// Text contents of a workspace file together with the hash used for
+/// optimistic concurrency on later edits.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct FileContent {
+    pub path: String,
+    pub content: String,
+    /// SHA256 of the contents; pass it back as `base_hash` when editing
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateFileContent {
+    /// Path relative to the workspace root
+    pub path: String,
+    /// Hash of the contents the edit is based on, or null to create a new file
+    pub base_hash: Option<String>,
+    pub edit: FileEdit,
+}
+
+/// Either the complete new contents or a unified diff against the base contents.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum FileEdit {
+    Replace { content: String },
+    Patch { diff: String },
+}
+
+/// Directory in the workspace root, outside every repository, where an
+/// attempt keeps build output and other intermediate files. Executors find it
+/// in `$VK_SCRATCH`.
+pub const SCRATCH_DIR: &str = ".vk-scratch";
+
+pub fn scratch_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(SCRATCH_DIR)
+}
+
+/// Directory in the scratch directory where agents publish files for review
+/// beside the diff, such as reports, screenshots or coverage pages. Executors
+/// find it in `$VK_ARTIFACTS`; every file below it is an artifact named by its
+/// path there.
+pub const ARTIFACTS_DIR: &str = "artifacts";
+
+/// Most files recorded as artifacts of a workspace
+const MAX_ARTIFACTS: usize = 1000;
+
+pub fn artifacts_dir(workspace_dir: &Path) -> PathBuf {
+    scratch_dir(workspace_dir).join(ARTIFACTS_DIR)
+}
+
+/// Part of a workspace that file paths are relative to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum FileScope {
+    #[default]
+    Workspace,
+    Scratch,
+    Artifacts,
+}
+
+impl FileScope {
+    /// Root directory of the scope, created if it isn't the workspace itself
+    pub async fn root(self, workspace_dir: &Path) -> std::io::Result<PathBuf> {
+        let dir = match self {
+            FileScope::Workspace => return Ok(workspace_dir.to_path_buf()),
+            FileScope::Scratch => scratch_dir(workspace_dir),
+            FileScope::Artifacts => artifacts_dir(workspace_dir),
+        };
+        tokio::fs::create_dir_all(&dir).await?;
+        Ok(dir)
+    }
+
+    /// Where the scope's root is, relative to the workspace root
+    pub fn prefix(self) -> Option<String> {
+        match self {
+            FileScope::Workspace => None,
+            FileScope::Scratch => Some(SCRATCH_DIR.to_string()),
+            FileScope::Artifacts => Some(format!("{SCRATCH_DIR}/{ARTIFACTS_DIR}")),
+        }
+    }
+}
+
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Validate a client-supplied path relative to the workspace root and return the
+/// joined absolute path. Absolute paths, `..` components and anything inside a
+/// `.git` directory are rejected, as are paths that leave the workspace through
+/// a symlink.
+pub fn validate_target_path(root: &Path, relative: &str) -> Result<PathBuf, FileUploadError> {
+    let relative = relative.trim_start_matches('/');
+    if relative.is_empty() {
+        return Err(FileUploadError::InvalidPath(relative.to_string()));
+    }
+
+    for component in Path::new(relative).components() {
+        match component {
+            Component::Normal(name) if name == ".git" => {
+                return Err(FileUploadError::GitDirectory);
+            }
+            Component::Normal(_) | Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(FileUploadError::InvalidPath(relative.to_string()));
+            }
+        }
+    }
+
+    let target = root.join(relative);
+    ensure_within_root(root, &target)?;
+    Ok(target)
+}
+
+/// Resolve symlinks on the way to `target` and make sure it still lives under
+/// `root`. Parts of the path that don't exist yet are checked through their
+/// deepest existing ancestor.
+pub fn ensure_within_root(root: &Path, target: &Path) -> Result<(), FileUploadError> {
+    let canonical_root = dunce::canonicalize(root)?;
+    let outside = || {
+        let shown = target.strip_prefix(root).unwrap_or(target);
+        FileUploadError::OutsideWorkspace(shown.display().to_string())
+    };
+
+    let mut existing = target;
+    loop {
+        match dunce::canonicalize(existing) {
+            Ok(canonical) if canonical.starts_with(&canonical_root) => return Ok(()),
+            Ok(_) => return Err(outside()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // A dangling symlink would be followed on write, wherever it points
+                if existing.symlink_metadata().is_ok() {
+                    return Err(outside());
+                }
+                existing = existing.parent().ok_or_else(outside)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Check whether `relative` is matched by a .gitignore (or `.git/info/exclude`) in
+/// any directory between the workspace root and the target. Rules in deeper
+/// directories take precedence, mirroring git.
+pub fn is_gitignored(root: &Path, relative: &Path, is_dir: bool) -> bool {
+    let mut dirs = vec![root.to_path_buf()];
+    let mut dir = root.to_path_buf();
+    for component in relative.parent().into_iter().flat_map(Path::components) {
+        dir.push(component);
+        dirs.push(dir.clone());
+    }
+
+    let target = root.join(relative);
+    for dir in dirs.iter().rev() {
+        let mut builder = GitignoreBuilder::new(dir);
+        for candidate in [dir.join(".gitignore"), dir.join(".git/info/exclude")] {
+            if candidate.is_file()
+                && let Some(e) = builder.add(&candidate)
+            {
+                tracing::warn!("Failed to parse {}: {}", candidate.display(), e);
+            }
+        }
+        let gitignore = match builder.build() {
+            Ok(gitignore) => gitignore,
+            Err(e) => {
+                tracing::warn!("Failed to build gitignore for {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        let matched = gitignore.matched_path_or_any_parents(&target, is_dir);
+        if matched.is_ignore() {
+            return true;
+        }
+        if matched.is_whitelist() {
+            return false;
+        }
+    }
+    false
+}
+
+/// Write `data` to `relative` below the workspace root, creating parent directories.
+/// Gitignored targets are written and flagged unless the project refuses them, and
+/// the project's upload scanner (if enabled) must accept the file first.
+pub async fn upload_file(
+    root: &Path,
+    relative: &str,
+    data: &[u8],
+    settings: &ProjectSettings,
+) -> Result<UploadedFile, FileUploadError> {
+    let target = validate_target_path(root, relative)?;
+    let relative_path = target
+        .strip_prefix(root)
+        .map_err(|_| FileUploadError::InvalidPath(relative.to_string()))?
+        .to_path_buf();
+    let display_path = relative_path.to_string_lossy().replace('\\', "/");
+
+    let gitignored = is_gitignored(root, &relative_path, false);
+    if gitignored && settings.gitignored_uploads == GitignoredUploadPolicy::Refuse {
+        return Err(FileUploadError::Gitignored(display_path));
+    }
+    upload_scan::scan_upload(&settings.upload_scan, &display_path, data).await?;
+
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&target, data).await?;
+
+    Ok(UploadedFile {
+        path: display_path,
+        size_bytes: data.len() as i64,
+        hash: content_hash(data),
+        gitignored,
+    })
+}
+
+pub async fn read_text_file(root: &Path, relative: &str) -> Result<FileContent, FileUploadError> {
+    let target = validate_target_path(root, relative)?;
+    let data = match tokio::fs::read(&target).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(FileUploadError::NotFound(relative.to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let hash = content_hash(&data);
+    let content =
+        String::from_utf8(data).map_err(|_| FileUploadError::NotText(relative.to_string()))?;
+    Ok(FileContent {
+        path: relative.to_string(),
+        content,
+        hash,
+    })
+}
+
+/// Apply an edit to a text file in the workspace. The edit only goes through if
+/// the file still hashes to `base_hash` (or is still absent when creating), so
+/// concurrent changes by the agent are never silently overwritten. The new
+/// contents pass the same project checks as an upload.
+pub async fn update_text_file(
+    root: &Path,
+    update: &UpdateFileContent,
+    settings: &ProjectSettings,
+) -> Result<FileContent, FileUploadError> {
+    let target = validate_target_path(root, &update.path)?;
+    let relative_path = target
+        .strip_prefix(root)
+        .map_err(|_| FileUploadError::InvalidPath(update.path.clone()))?
+        .to_path_buf();
+    let display_path = relative_path.to_string_lossy().replace('\\', "/");
+    let current = match tokio::fs::read(&target).await {
+        Ok(data) => Some(data),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    let current_hash = current.as_deref().map(content_hash);
+    if current_hash != update.base_hash {
+        return Err(FileUploadError::HashMismatch {
+            expected: update.base_hash.clone(),
+            actual: current_hash,
+        });
+    }
+
+    let content = match &update.edit {
+        FileEdit::Replace { content } => content.clone(),
+        FileEdit::Patch { diff } => {
+            let base = match &current {
+                Some(data) => std::str::from_utf8(data)
+                    .map_err(|_| FileUploadError::NotText(update.path.clone()))?,
+                None => return Err(FileUploadError::NotFound(update.path.clone())),
+            };
+            apply_patch(base, diff)?
+        }
+    };
+
+    // The total quota covers files uploaded through the API; edits are checked
+    // per file only.
+    settings
+        .upload_policy
+        .check(&display_path, content.len() as i64, 0)
+        .map_err(FileUploadError::PolicyViolation)?;
+    if settings.gitignored_uploads == GitignoredUploadPolicy::Refuse
+        && is_gitignored(root, &relative_path, false)
+    {
+        return Err(FileUploadError::Gitignored(display_path));
+    }
+    upload_scan::scan_upload(&settings.upload_scan, &display_path, content.as_bytes()).await?;
+
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&target, &content).await?;
+
+    Ok(FileContent {
+        path: update.path.clone(),
+        hash: content_hash(content.as_bytes()),
+        content,
+    })
+}
+
+/// A file of the workspace for download, with its size
+pub async fn open_file(
+    root: &Path,
+    relative: &str,
+) -> Result<(tokio::fs::File, u64), FileUploadError> {
+    let target = validate_target_path(root, relative)?;
+    let file = match tokio::fs::File::open(&target).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(FileUploadError::NotFound(relative.to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let metadata = file.metadata().await?;
+    if !metadata.is_file() {
+        return Err(FileUploadError::NotFound(relative.to_string()));
+    }
+    Ok((file, metadata.len()))
+}
+
+/// Files in the workspace's artifacts directory, by name. Links are not
+/// followed, so nothing outside the directory gets published.
+pub async fn collect_artifacts(
+    workspace_dir: &Path,
+) -> std::io::Result<Vec<CreateWorkspaceArtifact>> {
+    let root = artifacts_dir(workspace_dir);
+    tokio::task::spawn_blocking(move || {
+        let mut artifacts = Vec::new();
+        let mut dirs = vec![root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            for entry in entries {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    dirs.push(entry.path());
+                } else if file_type.is_file() && artifacts.len() < MAX_ARTIFACTS {
+                    let path = entry.path();
+                    let name = path
+                        .strip_prefix(&root)
+                        .map_err(std::io::Error::other)?
+                        .to_string_lossy()
+                        .replace('\\', "/");
+                    let mut hasher = Sha256::new();
+                    let size_bytes = std::io::copy(&mut std::fs::File::open(&path)?, &mut hasher)?;
+                    artifacts.push(CreateWorkspaceArtifact {
+                        name,
+                        size_bytes: size_bytes as i64,
+                        hash: format!("{:x}", hasher.finalize()),
+                    });
+                }
+            }
+        }
+        artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(artifacts)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+fn apply_patch(base: &str, diff: &str) -> Result<String, FileUploadError> {
+    let patch =
+        diffy::Patch::from_str(diff).map_err(|e| FileUploadError::PatchFailed(e.to_string()))?;
+    diffy::apply(base, &patch).map_err(|e| FileUploadError::PatchFailed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::project_settings::UploadPolicy;
+
+    use super::*;
+
+    #[test]
+    fn rejects_traversal_and_git_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert!(validate_target_path(root, "src/main.rs").is_ok());
+        assert!(matches!(
+            validate_target_path(root, "../etc/passwd"),
+            Err(FileUploadError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            validate_target_path(root, "repo/.git/hooks/pre-commit"),
+            Err(FileUploadError::GitDirectory)
+        ));
+        assert!(validate_target_path(root, "").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_escaping_the_workspace() {
+        let workspace = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let root = workspace.path();
+        std::os::unix::fs::symlink(outside.path(), root.join("escape")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("missing"), root.join("dangling")).unwrap();
+        std::fs::create_dir(root.join("inside")).unwrap();
+        std::os::unix::fs::symlink(root.join("inside"), root.join("alias")).unwrap();
+
+        assert!(matches!(
+            validate_target_path(root, "escape/new/file.txt"),
+            Err(FileUploadError::OutsideWorkspace(_))
+        ));
+        assert!(matches!(
+            validate_target_path(root, "dangling"),
+            Err(FileUploadError::OutsideWorkspace(_))
+        ));
+        assert!(validate_target_path(root, "alias/file.txt").is_ok());
+    }
+
+    #[tokio::test]
+    async fn collects_artifacts_by_name() {
+        let workspace = tempfile::tempdir().unwrap();
+        assert!(
+            collect_artifacts(workspace.path())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let dir = artifacts_dir(workspace.path());
+        std::fs::create_dir_all(dir.join("coverage")).unwrap();
+        std::fs::write(dir.join("report.md"), "# Report\n").unwrap();
+        std::fs::write(dir.join("coverage/index.html"), "<html></html>").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(workspace.path().join("secret"), dir.join("link")).unwrap();
+
+        let artifacts = collect_artifacts(workspace.path()).await.unwrap();
+        let names: Vec<_> = artifacts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["coverage/index.html", "report.md"]);
+        assert_eq!(artifacts[1].size_bytes, 9);
+        assert_eq!(artifacts[1].hash, content_hash(b"# Report\n"));
+    }
+
+    #[tokio::test]
+    async fn edits_follow_upload_rules() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        let edit = |path: &str, content: &str| UpdateFileContent {
+            path: path.to_string(),
+            base_hash: None,
+            edit: FileEdit::Replace {
+                content: content.to_string(),
+            },
+        };
+        let settings = ProjectSettings {
+            upload_policy: UploadPolicy {
+                max_file_bytes: Some(8),
+                denied_extensions: vec!["sh".to_string()],
+                ..Default::default()
+            },
+            gitignored_uploads: GitignoredUploadPolicy::Refuse,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            update_text_file(root, &edit("notes.md", "too long for it"), &settings).await,
+            Err(FileUploadError::PolicyViolation(_))
+        ));
+        assert!(matches!(
+            update_text_file(root, &edit("run.sh", "ls"), &settings).await,
+            Err(FileUploadError::PolicyViolation(_))
+        ));
+        assert!(matches!(
+            update_text_file(root, &edit("debug.log", "x"), &settings).await,
+            Err(FileUploadError::Gitignored(_))
+        ));
+        assert!(!root.join("debug.log").exists());
+        assert!(
+            update_text_file(root, &edit("notes.md", "short"), &settings)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn applies_unified_diff() {
+        let base = "fn main() {\n    println!(\"hi\");\n}\n";
+        let diff = "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    println!(\"hi\");\n+    println!(\"hello\");\n }\n";
+        assert_eq!(
+            apply_patch(base, diff).unwrap(),
+            "fn main() {\n    println!(\"hello\");\n}\n"
+        );
+        assert!(matches!(
+            apply_patch("unrelated\n", diff),
+            Err(FileUploadError::PatchFailed(_))
+        ));
+    }
+
+    #[test]
+    fn detects_nested_gitignore_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("repo")).unwrap();
+        std::fs::write(dir.path().join("repo/.gitignore"), "/dist\n!keep.log\n").unwrap();
+
+        assert!(is_gitignored(dir.path(), Path::new("debug.log"), false));
+        assert!(is_gitignored(
+            dir.path(),
+            Path::new("repo/dist/out.js"),
+            false
+        ));
+        assert!(!is_gitignored(dir.path(), Path::new("dist/out.js"), false));
+        assert!(!is_gitignored(
+            dir.path(),
+            Path::new("repo/keep.log"),
+            false
+        ));
+        assert!(!is_gitignored(
+            dir.path(),
+            Path::new("repo/src/lib.rs"),
+            false
+        ));
+    }
+}
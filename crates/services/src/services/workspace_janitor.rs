@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use db::models::{
+    project_settings::{ProjectSettings, RetentionPolicy},
+    task::TaskStatus,
+    workspace::{Workspace, WorkspaceActivity},
+    workspace_disk_usage::WorkspaceDiskUsage,
+};
+use ignore::WalkBuilder;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::workspace_files::scratch_dir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ReclaimReason {
+    Merged,
+    Abandoned,
+}
+
+/// A workspace the project's retention policy would remove
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ReclaimCandidate {
+    pub workspace_id: Uuid,
+    pub reason: ReclaimReason,
+    /// Merge time for merged workspaces, last activity otherwise
+    pub since: DateTime<Utc>,
+    /// Last measured size, if the workspace has been measured yet
+    pub size_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CleanupPreview {
+    pub candidates: Vec<ReclaimCandidate>,
+    pub reclaimable_bytes: i64,
+}
+
+/// Decide whether `policy` makes a workspace eligible for removal at `now`.
+/// Workspaces with running processes are never eligible.
+pub fn reclaim_reason(
+    policy: &RetentionPolicy,
+    activity: &WorkspaceActivity,
+    now: DateTime<Utc>,
+) -> Option<(ReclaimReason, DateTime<Utc>)> {
+    if activity.has_running_processes {
+        return None;
+    }
+    let older_than = |since: DateTime<Utc>, days: i64| now - since > Duration::days(days);
+
+    match activity.merged_at {
+        Some(merged_at) => policy
+            .merged_after_days
+            .filter(|days| older_than(merged_at, *days))
+            .map(|_| (ReclaimReason::Merged, merged_at)),
+        None => {
+            let finished = matches!(
+                activity.task_status,
+                TaskStatus::Done | TaskStatus::Cancelled
+            );
+            policy
+                .abandoned_after_days
+                .filter(|days| finished && older_than(activity.last_activity_at, *days))
+                .map(|_| (ReclaimReason::Abandoned, activity.last_activity_at))
+        }
+    }
+}
+
+/// Whether `policy` has the workspace's scratch directory emptied at `now`
+pub fn scratch_expired(
+    policy: &RetentionPolicy,
+    activity: &WorkspaceActivity,
+    now: DateTime<Utc>,
+) -> bool {
+    !activity.has_running_processes
+        && policy
+            .scratch_after_days
+            .is_some_and(|days| now - activity.last_activity_at > Duration::days(days))
+}
+
+/// Scratch directories that the retention policies would empty now
+pub async fn find_stale_scratch(pool: &SqlitePool) -> Result<Vec<PathBuf>, sqlx::Error> {
+    let now = Utc::now();
+    let mut policies: HashMap<Uuid, RetentionPolicy> = HashMap::new();
+    let mut dirs = Vec::new();
+
+    for activity in Workspace::find_activity_with_container(pool).await? {
+        if !policies.contains_key(&activity.project_id) {
+            let settings = ProjectSettings::find_by_project_id(pool, activity.project_id).await?;
+            policies.insert(activity.project_id, settings.retention);
+        }
+        let dir = scratch_dir(Path::new(&activity.container_ref));
+        if scratch_expired(&policies[&activity.project_id], &activity, now) && dir.exists() {
+            dirs.push(dir);
+        }
+    }
+    Ok(dirs)
+}
+
+/// Workspaces that the retention policies would remove now, optionally limited
+/// to one project.
+pub async fn find_reclaimable(
+    pool: &SqlitePool,
+    project_id: Option<Uuid>,
+) -> Result<Vec<ReclaimCandidate>, sqlx::Error> {
+    let now = Utc::now();
+    let mut policies: HashMap<Uuid, RetentionPolicy> = HashMap::new();
+    let mut candidates = Vec::new();
+
+    for activity in Workspace::find_activity_with_container(pool).await? {
+        if project_id.is_some_and(|id| id != activity.project_id) {
+            continue;
+        }
+        if !policies.contains_key(&activity.project_id) {
+            let settings = ProjectSettings::find_by_project_id(pool, activity.project_id).await?;
+            policies.insert(activity.project_id, settings.retention);
+        }
+        let policy = &policies[&activity.project_id];
+        if let Some((reason, since)) = reclaim_reason(policy, &activity, now) {
+            let size_bytes = WorkspaceDiskUsage::find_by_workspace_id(pool, activity.workspace_id)
+                .await?
+                .map(|usage| usage.size_bytes);
+            candidates.push(ReclaimCandidate {
+                workspace_id: activity.workspace_id,
+                reason,
+                since,
+                size_bytes,
+            });
+        }
+    }
+    Ok(candidates)
+}
+
+pub async fn preview_cleanup(
+    pool: &SqlitePool,
+    project_id: Uuid,
+) -> Result<CleanupPreview, sqlx::Error> {
+    let candidates = find_reclaimable(pool, Some(project_id)).await?;
+    let reclaimable_bytes = candidates.iter().filter_map(|c| c.size_bytes).sum();
+    Ok(CleanupPreview {
+        candidates,
+        reclaimable_bytes,
+    })
+}
+
+/// Total size of the regular files below `path`. Symlinks are not followed.
+pub fn directory_size(path: &Path) -> u64 {
+    WalkBuilder::new(path)
+        .standard_filters(false)
+        .follow_links(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Measure every workspace that still exists on disk and record its size.
+pub async fn refresh_disk_usage(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    for activity in Workspace::find_activity_with_container(pool).await? {
+        let dir = PathBuf::from(&activity.container_ref);
+        if !dir.exists() {
+            continue;
+        }
+        let size = tokio::task::spawn_blocking(move || directory_size(&dir))
+            .await
+            .unwrap_or_default();
+        WorkspaceDiskUsage::upsert(pool, activity.workspace_id, size as i64).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(
+        task_status: TaskStatus,
+        merged_days_ago: Option<i64>,
+        idle_days: i64,
+    ) -> WorkspaceActivity {
+        let now = Utc::now();
+        WorkspaceActivity {
+            workspace_id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            container_ref: "/tmp/ws".to_string(),
+            task_status,
+            merged_at: merged_days_ago.map(|days| now - Duration::days(days)),
+            last_activity_at: now - Duration::days(idle_days),
+            has_running_processes: false,
+        }
+    }
+
+    #[test]
+    fn applies_merged_and_abandoned_rules() {
+        let policy = RetentionPolicy {
+            merged_after_days: Some(7),
+            abandoned_after_days: Some(30),
+            ..Default::default()
+        };
+        let now = Utc::now();
+
+        let merged = activity(TaskStatus::Done, Some(8), 8);
+        assert_eq!(
+            reclaim_reason(&policy, &merged, now).map(|(r, _)| r),
+            Some(ReclaimReason::Merged)
+        );
+        let recently_merged = activity(TaskStatus::Done, Some(2), 2);
+        assert!(reclaim_reason(&policy, &recently_merged, now).is_none());
+
+        let abandoned = activity(TaskStatus::Cancelled, None, 31);
+        assert_eq!(
+            reclaim_reason(&policy, &abandoned, now).map(|(r, _)| r),
+            Some(ReclaimReason::Abandoned)
+        );
+        let in_progress = activity(TaskStatus::InProgress, None, 90);
+        assert!(reclaim_reason(&policy, &in_progress, now).is_none());
+
+        let mut running = activity(TaskStatus::Cancelled, None, 31);
+        running.has_running_processes = true;
+        assert!(reclaim_reason(&policy, &running, now).is_none());
+
+        assert!(reclaim_reason(&RetentionPolicy::default(), &merged, now).is_none());
+    }
+
+    #[test]
+    fn empties_scratch_of_idle_workspaces() {
+        let policy = RetentionPolicy {
+            scratch_after_days: Some(3),
+            ..Default::default()
+        };
+        let now = Utc::now();
+
+        assert!(scratch_expired(
+            &policy,
+            &activity(TaskStatus::InProgress, None, 4),
+            now
+        ));
+        assert!(!scratch_expired(
+            &policy,
+            &activity(TaskStatus::InProgress, None, 1),
+            now
+        ));
+        let mut running = activity(TaskStatus::InProgress, None, 4);
+        running.has_running_processes = true;
+        assert!(!scratch_expired(&policy, &running, now));
+    }
+}
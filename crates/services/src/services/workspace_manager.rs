@@ -6,7 +6,11 @@ use thiserror::Error;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager};
+use super::{
+    project_cleanup,
+    sparse_checkout::SparseCheckout,
+    worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager},
+};
 
 #[derive(Debug, Clone)]
 pub struct RepoWorkspaceInput {
@@ -33,6 +37,8 @@ pub enum WorkspaceError {
     NoRepositories,
     #[error("Partial workspace creation failed: {0}")]
     PartialCreation(String),
+    #[error("'{0}' already exists in the workspace")]
+    AlreadyExists(String),
 }
 
 /// Info about a single repo's worktree within a workspace
@@ -60,6 +66,7 @@ impl WorkspaceManager {
         workspace_dir: &Path,
         repos: &[RepoWorkspaceInput],
         branch_name: &str,
+        sparse: &SparseCheckout,
     ) -> Result<WorktreeContainer, WorkspaceError> {
         if repos.is_empty() {
             return Err(WorkspaceError::NoRepositories);
@@ -90,6 +97,7 @@ impl WorkspaceManager {
                 &worktree_path,
                 &input.target_branch,
                 true,
+                &sparse.for_repo(&input.repo.name),
             )
             .await
             {
@@ -137,11 +145,48 @@ impl WorkspaceManager {
         })
     }
 
+    /// Check out one more repository next to the workspace's existing ones, on
+    /// a new branch of the workspace's name
+    pub async fn add_repo(
+        workspace_dir: &Path,
+        input: &RepoWorkspaceInput,
+        branch_name: &str,
+        sparse: &SparseCheckout,
+    ) -> Result<RepoWorktree, WorkspaceError> {
+        let worktree_path = workspace_dir.join(&input.repo.name);
+        if tokio::fs::try_exists(&worktree_path).await? {
+            return Err(WorkspaceError::AlreadyExists(input.repo.name.clone()));
+        }
+
+        info!(
+            "Adding repo '{}' to workspace at {}",
+            input.repo.name,
+            workspace_dir.display()
+        );
+        WorktreeManager::create_worktree(
+            &input.repo.path,
+            branch_name,
+            &worktree_path,
+            &input.target_branch,
+            true,
+            &sparse.for_repo(&input.repo.name),
+        )
+        .await?;
+
+        Ok(RepoWorktree {
+            repo_id: input.repo.id,
+            repo_name: input.repo.name.clone(),
+            source_repo_path: input.repo.path.clone(),
+            worktree_path,
+        })
+    }
+
     /// Ensure all worktrees in a workspace exist (for cold restart scenarios)
     pub async fn ensure_workspace_exists(
         workspace_dir: &Path,
         repos: &[Repo],
         branch_name: &str,
+        sparse: &SparseCheckout,
     ) -> Result<(), WorkspaceError> {
         if repos.is_empty() {
             return Err(WorkspaceError::NoRepositories);
@@ -166,8 +211,13 @@ impl WorkspaceManager {
                 worktree_path.display()
             );
 
-            WorktreeManager::ensure_worktree_exists(&repo.path, branch_name, &worktree_path)
-                .await?;
+            WorktreeManager::ensure_worktree_exists(
+                &repo.path,
+                branch_name,
+                &worktree_path,
+                &sparse.for_repo(&repo.name),
+            )
+            .await?;
         }
 
         Ok(())
@@ -323,7 +373,7 @@ impl WorkspaceManager {
             };
 
             let path = entry.path();
-            if !path.is_dir() {
+            if !path.is_dir() || project_cleanup::is_trash_dir(&path) {
                 continue;
             }
 
@@ -345,7 +395,9 @@ impl WorkspaceManager {
         }
     }
 
-    async fn cleanup_workspace_without_repos(workspace_dir: &Path) -> Result<(), WorkspaceError> {
+    pub(crate) async fn cleanup_workspace_without_repos(
+        workspace_dir: &Path,
+    ) -> Result<(), WorkspaceError> {
         info!(
             "Cleaning up orphaned workspace at {}",
             workspace_dir.display()
@@ -0,0 +1,483 @@
+//! Pre-provisioned workspaces handed to new task attempts, so large
+//! repositories don't have to be checked out while the user waits.
+//!
+//! Each project keeps up to `workspace_pool.size` workspaces holding a detached
+//! worktree of every project repository. Claiming one moves its worktrees into
+//! the attempt's workspace directory and checks out the attempt branch from the
+//! target branch, which only rewrites files that differ from the pooled
+//! checkout. Attempts that use a subset of the project's repositories are
+//! created from scratch as before.
+//!
+//! Pooled workspaces are only tracked in memory: the pool directory is cleared
+//! when the replenisher starts and refilled from there.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use dashmap::DashMap;
+use db::models::{
+    project::Project, project_repo::ProjectRepo, project_settings::ProjectSettings, repo::Repo,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tokio::sync::Notify;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    git::{GitCli, GitService},
+    sparse_checkout::SparseCheckout,
+    workspace_manager::{
+        RepoWorkspaceInput, RepoWorktree, WorkspaceError, WorkspaceManager, WorktreeContainer,
+    },
+    worktree_manager::{WorktreeError, WorktreeManager},
+};
+
+const REPLENISH_INTERVAL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspacePoolStatus {
+    /// Configured number of pooled workspaces
+    pub size: u32,
+    /// Pooled workspaces ready to be claimed
+    pub ready: u32,
+}
+
+#[derive(Debug)]
+struct PooledWorkspace {
+    dir: PathBuf,
+    /// Sorted by id
+    repos: Vec<Repo>,
+}
+
+impl PooledWorkspace {
+    fn has_repos(&self, repo_ids: &[Uuid]) -> bool {
+        self.repos
+            .iter()
+            .map(|repo| repo.id)
+            .eq(repo_ids.iter().copied())
+    }
+}
+
+/// Split a project's pooled workspaces into those to keep and those to drop:
+/// workspaces for a different set of repositories or beyond the configured size
+fn split_stale(
+    entries: Vec<PooledWorkspace>,
+    repo_ids: &[Uuid],
+    size: usize,
+) -> (Vec<PooledWorkspace>, Vec<PooledWorkspace>) {
+    let (mut keep, mut stale): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .partition(|entry| entry.has_repos(repo_ids));
+    if keep.len() > size {
+        stale.extend(keep.split_off(size));
+    }
+    (keep, stale)
+}
+
+#[derive(Clone, Default)]
+pub struct WorkspacePool {
+    entries: Arc<DashMap<Uuid, Vec<PooledWorkspace>>>,
+    replenish: Arc<Notify>,
+}
+
+impl WorkspacePool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn base_dir() -> PathBuf {
+        utils::path::get_vibe_kanban_temp_dir().join("workspace-pool")
+    }
+
+    pub async fn status(
+        &self,
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<WorkspacePoolStatus, sqlx::Error> {
+        let size = ProjectSettings::find_by_project_id(pool, project_id)
+            .await?
+            .workspace_pool
+            .size;
+        let ready = self
+            .entries
+            .get(&project_id)
+            .map_or(0, |entries| entries.len() as u32);
+        Ok(WorkspacePoolStatus { size, ready })
+    }
+
+    /// Start a replenish pass, e.g. after a project's pool size changed
+    pub fn replenish_now(&self) {
+        self.replenish.notify_one();
+    }
+
+    /// Turn a pooled workspace of the project into `workspace_dir`, with each
+    /// worktree on `branch` created from its target branch. Returns `None` when
+    /// no pooled workspace has exactly these repositories or it could not be
+    /// adopted, in which case the caller creates the workspace itself. Pooled
+    /// worktrees are narrowed to `sparse`'s paths when they're claimed.
+    pub async fn claim(
+        &self,
+        project_id: Uuid,
+        workspace_dir: &Path,
+        inputs: &[RepoWorkspaceInput],
+        branch: &str,
+        sparse: &SparseCheckout,
+    ) -> Option<WorktreeContainer> {
+        let mut repo_ids: Vec<Uuid> = inputs.iter().map(|input| input.repo.id).collect();
+        repo_ids.sort();
+        let entry = {
+            let mut entries = self.entries.get_mut(&project_id)?;
+            let index = entries
+                .iter()
+                .position(|entry| entry.has_repos(&repo_ids))?;
+            entries.remove(index)
+        };
+        self.replenish_now();
+
+        match Self::adopt(&entry, workspace_dir, inputs, branch, sparse).await {
+            Ok(container) => {
+                tracing::info!(
+                    "Created workspace {} from the pool",
+                    workspace_dir.display()
+                );
+                Some(container)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to use pooled workspace, creating a new one: {}", e);
+                if let Err(e) =
+                    WorkspaceManager::cleanup_workspace(workspace_dir, &entry.repos).await
+                {
+                    tracing::warn!("Failed to clean up partially adopted workspace: {}", e);
+                }
+                Self::discard(entry).await;
+                None
+            }
+        }
+    }
+
+    async fn adopt(
+        entry: &PooledWorkspace,
+        workspace_dir: &Path,
+        inputs: &[RepoWorkspaceInput],
+        branch: &str,
+        sparse: &SparseCheckout,
+    ) -> Result<WorktreeContainer, WorkspaceError> {
+        tokio::fs::create_dir_all(workspace_dir).await?;
+        let mut worktrees = Vec::new();
+        for input in inputs {
+            let worktree_path = workspace_dir.join(&input.repo.name);
+            WorktreeManager::move_worktree(
+                &input.repo.path,
+                &entry.dir.join(&input.repo.name),
+                &worktree_path,
+            )
+            .await?;
+            let patterns = sparse.for_repo(&input.repo.name);
+            if !patterns.is_empty() {
+                let path = worktree_path.clone();
+                tokio::task::spawn_blocking(move || {
+                    GitService::new().set_sparse_checkout(&path, &patterns)
+                })
+                .await
+                .map_err(|e| WorktreeError::TaskJoin(e.to_string()))?
+                .map_err(WorktreeError::GitService)?;
+            }
+            git(
+                &worktree_path,
+                [
+                    "checkout".into(),
+                    "--no-track".into(),
+                    "-B".into(),
+                    branch.into(),
+                    input.target_branch.clone().into(),
+                ],
+            )
+            .await?;
+            worktrees.push(RepoWorktree {
+                repo_id: input.repo.id,
+                repo_name: input.repo.name.clone(),
+                source_repo_path: input.repo.path.clone(),
+                worktree_path,
+            });
+        }
+        let _ = tokio::fs::remove_dir(&entry.dir).await;
+
+        Ok(WorktreeContainer {
+            workspace_dir: workspace_dir.to_path_buf(),
+            worktrees,
+        })
+    }
+
+    /// Keep the pools filled in the background. Claims trigger a pass right
+    /// away; otherwise pools are topped up and refreshed periodically.
+    pub fn spawn_replenisher(&self, db: SqlitePool) {
+        let pool = self.clone();
+        tokio::spawn(async move {
+            Self::clear_base_dir().await;
+            loop {
+                if let Err(e) = pool.replenish(&db).await {
+                    tracing::error!("Failed to replenish workspace pools: {}", e);
+                }
+                let _ = tokio::time::timeout(REPLENISH_INTERVAL, pool.replenish.notified()).await;
+            }
+        });
+    }
+
+    async fn replenish(&self, db: &SqlitePool) -> Result<(), sqlx::Error> {
+        let projects = Project::find_all(db).await?;
+
+        // Projects that were deleted
+        let gone: Vec<Uuid> = self
+            .entries
+            .iter()
+            .map(|entries| *entries.key())
+            .filter(|id| !projects.iter().any(|project| project.id == *id))
+            .collect();
+        for project_id in gone {
+            if let Some((_, entries)) = self.entries.remove(&project_id) {
+                for entry in entries {
+                    Self::discard(entry).await;
+                }
+            }
+        }
+
+        for project in projects {
+            let size = ProjectSettings::find_by_project_id(db, project.id)
+                .await?
+                .workspace_pool
+                .size as usize;
+            let mut repos = ProjectRepo::find_repos_for_project(db, project.id).await?;
+            repos.sort_by_key(|repo| repo.id);
+            let repo_ids: Vec<Uuid> = repos.iter().map(|repo| repo.id).collect();
+
+            let stale = {
+                let mut entries = self.entries.entry(project.id).or_default();
+                let (keep, stale) = split_stale(std::mem::take(&mut *entries), &repo_ids, size);
+                *entries = keep;
+                stale
+            };
+            for entry in stale {
+                Self::discard(entry).await;
+            }
+            if repos.is_empty() {
+                continue;
+            }
+
+            self.refresh(project.id).await;
+            let ready = self.entries.get(&project.id).map_or(0, |e| e.len());
+            for _ in ready..size {
+                match Self::provision(project.id, &repos).await {
+                    Ok(entry) => self.entries.entry(project.id).or_default().push(entry),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to provision pooled workspace for project {}: {}",
+                            project.id,
+                            e
+                        );
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the project's pooled worktrees to the current HEAD of their
+    /// repositories, so claiming them stays cheap as the repositories move on
+    async fn refresh(&self, project_id: Uuid) {
+        let dirs: Vec<(PathBuf, Vec<Repo>)> = self
+            .entries
+            .get(&project_id)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| (entry.dir.clone(), entry.repos.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for (dir, repos) in dirs {
+            for repo in repos {
+                let Ok(head) = GitService::new().get_head_info(&repo.path) else {
+                    continue;
+                };
+                let worktree_path = dir.join(&repo.name);
+                let args = ["checkout".into(), "--detach".into(), head.oid.into()];
+                if let Err(e) = git(&worktree_path, args).await {
+                    tracing::debug!(
+                        "Failed to refresh pooled worktree {}: {}",
+                        worktree_path.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    async fn provision(
+        project_id: Uuid,
+        repos: &[Repo],
+    ) -> Result<PooledWorkspace, WorkspaceError> {
+        let dir = Self::base_dir()
+            .join(project_id.to_string())
+            .join(Uuid::new_v4().to_string());
+        tokio::fs::create_dir_all(&dir).await?;
+        for repo in repos {
+            let args = [
+                "worktree".into(),
+                "add".into(),
+                "--detach".into(),
+                dir.join(&repo.name).into_os_string(),
+                "HEAD".into(),
+            ];
+            if let Err(e) = git(&repo.path, args).await {
+                if let Err(e) = WorkspaceManager::cleanup_workspace(&dir, repos).await {
+                    tracing::warn!("Failed to clean up pooled workspace: {}", e);
+                }
+                return Err(e.into());
+            }
+        }
+        Ok(PooledWorkspace {
+            dir,
+            repos: repos.to_vec(),
+        })
+    }
+
+    async fn discard(entry: PooledWorkspace) {
+        if let Err(e) = WorkspaceManager::cleanup_workspace(&entry.dir, &entry.repos).await {
+            tracing::warn!(
+                "Failed to remove pooled workspace {}: {}",
+                entry.dir.display(),
+                e
+            );
+        }
+    }
+
+    /// Remove pooled workspaces left behind by a previous run
+    async fn clear_base_dir() {
+        let base_dir = Self::base_dir();
+        let Ok(projects) = std::fs::read_dir(&base_dir) else {
+            return;
+        };
+        for dir in projects
+            .filter_map(Result::ok)
+            .filter_map(|project| std::fs::read_dir(project.path()).ok())
+            .flatten()
+            .filter_map(Result::ok)
+        {
+            if let Err(e) = WorkspaceManager::cleanup_workspace_without_repos(&dir.path()).await {
+                tracing::warn!("Failed to remove stale pooled workspace: {}", e);
+            }
+        }
+        let _ = tokio::fs::remove_dir_all(&base_dir).await;
+    }
+}
+
+async fn git<const N: usize>(path: &Path, args: [OsString; N]) -> Result<String, WorktreeError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || GitCli::new().git(&path, args))
+        .await
+        .map_err(|e| WorktreeError::TaskJoin(e.to_string()))?
+        .map_err(|e| WorktreeError::GitCli(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn repo(name: &str) -> Repo {
+        Repo {
+            id: Uuid::new_v4(),
+            path: PathBuf::from("/repos").join(name),
+            name: name.to_string(),
+            display_name: name.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn pooled(repos: &[&Repo]) -> PooledWorkspace {
+        let mut repos: Vec<Repo> = repos.iter().map(|repo| (*repo).clone()).collect();
+        repos.sort_by_key(|repo| repo.id);
+        PooledWorkspace {
+            dir: PathBuf::from("/pool").join(Uuid::new_v4().to_string()),
+            repos,
+        }
+    }
+
+    fn sorted_ids(repos: &[&Repo]) -> Vec<Uuid> {
+        let mut ids: Vec<Uuid> = repos.iter().map(|repo| repo.id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn pooled_workspaces_match_exact_repo_sets() {
+        let (web, api) = (repo("web"), repo("api"));
+        let entry = pooled(&[&web, &api]);
+
+        assert!(entry.has_repos(&sorted_ids(&[&api, &web])));
+        assert!(!entry.has_repos(&sorted_ids(&[&web])));
+        assert!(!entry.has_repos(&sorted_ids(&[&web, &api, &repo("docs")])));
+        assert!(!entry.has_repos(&[]));
+    }
+
+    #[test]
+    fn replenish_drops_other_repo_sets_and_extra_workspaces() {
+        let (web, api) = (repo("web"), repo("api"));
+        let repo_ids = sorted_ids(&[&web, &api]);
+        let entries = vec![
+            pooled(&[&web, &api]),
+            pooled(&[&web]),
+            pooled(&[&web, &api]),
+            pooled(&[&web, &api]),
+        ];
+        let dirs: Vec<PathBuf> = entries.iter().map(|entry| entry.dir.clone()).collect();
+
+        let (keep, stale) = split_stale(entries, &repo_ids, 2);
+        let kept: Vec<_> = keep.iter().map(|entry| &entry.dir).collect();
+        let dropped: Vec<_> = stale.iter().map(|entry| &entry.dir).collect();
+        assert_eq!(kept, [&dirs[0], &dirs[2]]);
+        assert_eq!(dropped, [&dirs[1], &dirs[3]]);
+
+        let (keep, stale) = split_stale(keep, &repo_ids, 0);
+        assert!(keep.is_empty());
+        assert_eq!(stale.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn claim_falls_back_when_repo_sets_differ() {
+        let (web, api) = (repo("web"), repo("api"));
+        let project_id = Uuid::new_v4();
+        let pool = WorkspacePool::new();
+        pool.entries.insert(project_id, vec![pooled(&[&web, &api])]);
+        let workspace_dir = PathBuf::from("/workspaces/attempt");
+        let sparse = SparseCheckout::from_patterns(Vec::new(), Vec::new());
+
+        let inputs = [RepoWorkspaceInput::new(web.clone(), "main".to_string())];
+        assert!(
+            pool.claim(project_id, &workspace_dir, &inputs, "vk/attempt", &sparse)
+                .await
+                .is_none()
+        );
+        assert!(
+            pool.claim(
+                Uuid::new_v4(),
+                &workspace_dir,
+                &inputs,
+                "vk/attempt",
+                &sparse
+            )
+            .await
+            .is_none()
+        );
+        // The pooled workspace stays for an attempt using all repositories
+        assert_eq!(pool.entries.get(&project_id).unwrap().len(), 1);
+    }
+}
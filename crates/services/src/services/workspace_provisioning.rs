@@ -0,0 +1,100 @@
+//! Submodules and Git LFS objects of new attempts' worktrees. `git worktree
+//! add` checks out neither, so agents in repositories that need them fail on
+//! empty submodule directories and LFS pointer files. Repositories that use
+//! them get a script run ahead of the setup scripts instead.
+
+use std::path::Path;
+
+use db::models::project_settings::GitProvisioningSettings;
+
+const SUBMODULES_SCRIPT: &str = r#"echo "Initializing submodules"
+git submodule sync --recursive
+git submodule update --init --recursive --progress
+"#;
+
+const LFS_SCRIPT: &str = r#"if ! git lfs version >/dev/null 2>&1; then
+  echo "The repository stores files in Git LFS, but git-lfs isn't installed. Install it from https://git-lfs.com or turn LFS off in the project's settings." >&2
+  exit 1
+fi
+echo "Fetching Git LFS objects"
+git lfs pull
+"#;
+
+pub fn uses_submodules(worktree_path: &Path) -> bool {
+    worktree_path.join(".gitmodules").is_file()
+}
+
+/// Whether the root `.gitattributes` routes any path through the LFS filter
+pub fn uses_lfs(worktree_path: &Path) -> bool {
+    std::fs::read_to_string(worktree_path.join(".gitattributes")).is_ok_and(|attributes| {
+        attributes
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .any(|line| line.split_whitespace().any(|attr| attr == "filter=lfs"))
+    })
+}
+
+/// Script that brings the worktree's submodules and LFS objects in, as the
+/// settings allow; None when the repository uses neither
+pub fn provisioning_script(
+    worktree_path: &Path,
+    settings: &GitProvisioningSettings,
+) -> Option<String> {
+    let submodules = settings.submodules && uses_submodules(worktree_path);
+    let lfs = settings.lfs && uses_lfs(worktree_path);
+    if !submodules && !lfs {
+        return None;
+    }
+    let mut script = String::from("set -e\n");
+    if submodules {
+        script.push_str(SUBMODULES_SCRIPT);
+    }
+    if lfs {
+        script.push_str(LFS_SCRIPT);
+        if submodules {
+            script.push_str("git submodule foreach --recursive 'git lfs pull'\n");
+        }
+    }
+    Some(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn only_repositories_using_submodules_or_lfs_are_provisioned() {
+        let td = TempDir::new().unwrap();
+        let settings = GitProvisioningSettings::default();
+        assert_eq!(provisioning_script(td.path(), &settings), None);
+
+        std::fs::write(
+            td.path().join(".gitattributes"),
+            "# *.bin filter=lfs\n*.rs text eol=lf\n",
+        )
+        .unwrap();
+        assert!(!uses_lfs(td.path()));
+        std::fs::write(
+            td.path().join(".gitattributes"),
+            "*.psd filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+        let script = provisioning_script(td.path(), &settings).unwrap();
+        assert!(script.contains("git lfs pull"));
+        assert!(!script.contains("git submodule"));
+
+        std::fs::write(td.path().join(".gitmodules"), "[submodule \"vendor\"]\n").unwrap();
+        let script = provisioning_script(td.path(), &settings).unwrap();
+        assert!(script.contains("git submodule update --init --recursive"));
+        assert!(script.contains("git submodule foreach --recursive 'git lfs pull'"));
+
+        let submodules_only = GitProvisioningSettings {
+            lfs: false,
+            ..settings
+        };
+        let script = provisioning_script(td.path(), &submodules_only).unwrap();
+        assert!(!script.contains("lfs"));
+    }
+}
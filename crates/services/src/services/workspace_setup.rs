@@ -0,0 +1,182 @@
+//! Whether a task attempt's setup scripts let its coding agent start.
+//!
+//! Setup scripts run as execution processes of the attempt, so their output is
+//! stored and streamed like that of any other process. When they run before
+//! the agent, a failing script ends the chain and the agent waits until the
+//! scripts are re-run successfully or someone starts it anyway.
+
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    session::Session,
+};
+use executors::actions::ExecutorActionType;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SetupStatus {
+    NotRun,
+    Running,
+    Succeeded,
+    /// A script failed and the agent hasn't been started since
+    Failed,
+    /// A script failed but the agent was started anyway
+    Overridden,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorkspaceSetup {
+    pub status: SetupStatus,
+    /// Whether the coding agent has ever been started in the attempt
+    pub agent_started: bool,
+    /// Setup script runs, oldest first
+    pub processes: Vec<ExecutionProcess>,
+}
+
+/// What the status needs to know of a process
+struct Step<'a> {
+    run_reason: &'a ExecutionProcessRunReason,
+    status: &'a ExecutionProcessStatus,
+    /// Repository a setup script ran in
+    script_dir: Option<&'a str>,
+}
+
+pub async fn load(pool: &SqlitePool, workspace_id: Uuid) -> Result<WorkspaceSetup, sqlx::Error> {
+    let mut processes = Vec::new();
+    for session in Session::find_by_workspace_id(pool, workspace_id).await? {
+        processes.extend(ExecutionProcess::find_by_session_id(pool, session.id, false).await?);
+    }
+    processes.sort_by_key(|process| process.created_at);
+
+    let steps: Vec<Step> = processes
+        .iter()
+        .map(|process| Step {
+            run_reason: &process.run_reason,
+            status: &process.status,
+            script_dir: process
+                .executor_action()
+                .ok()
+                .and_then(|action| match action.typ() {
+                    ExecutorActionType::ScriptRequest(script) => script.working_dir.as_deref(),
+                    _ => None,
+                }),
+        })
+        .collect();
+    let status = setup_status(&steps);
+    let agent_started = steps
+        .iter()
+        .any(|step| *step.run_reason == ExecutionProcessRunReason::CodingAgent);
+
+    Ok(WorkspaceSetup {
+        status,
+        agent_started,
+        processes: processes
+            .into_iter()
+            .filter(|process| process.run_reason == ExecutionProcessRunReason::SetupScript)
+            .collect(),
+    })
+}
+
+/// Status of the latest run of each repository's script, with steps in the
+/// order they were created. Scripts run in parallel finish in any order, so a
+/// failure is only cleared by a later run of the same script.
+fn setup_status(steps: &[Step]) -> SetupStatus {
+    let mut latest: Vec<(Option<&str>, usize)> = Vec::new();
+    for (index, step) in steps.iter().enumerate() {
+        if *step.run_reason != ExecutionProcessRunReason::SetupScript {
+            continue;
+        }
+        match latest.iter_mut().find(|(dir, _)| *dir == step.script_dir) {
+            Some(entry) => entry.1 = index,
+            None => latest.push((step.script_dir, index)),
+        }
+    }
+    if latest.is_empty() {
+        return SetupStatus::NotRun;
+    }
+    if latest
+        .iter()
+        .any(|(_, index)| *steps[*index].status == ExecutionProcessStatus::Running)
+    {
+        return SetupStatus::Running;
+    }
+
+    let last_failure = latest
+        .iter()
+        .filter(|(_, index)| {
+            matches!(
+                steps[*index].status,
+                ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed
+            )
+        })
+        .map(|(_, index)| *index)
+        .max();
+    match last_failure {
+        None => SetupStatus::Succeeded,
+        Some(failure) => {
+            let agent_started_since = steps[failure..]
+                .iter()
+                .any(|step| *step.run_reason == ExecutionProcessRunReason::CodingAgent);
+            if agent_started_since {
+                SetupStatus::Overridden
+            } else {
+                SetupStatus::Failed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(
+        run_reason: &'static ExecutionProcessRunReason,
+        status: &'static ExecutionProcessStatus,
+        script_dir: Option<&'static str>,
+    ) -> Step<'static> {
+        Step {
+            run_reason,
+            status,
+            script_dir,
+        }
+    }
+
+    #[test]
+    fn setup_failures_are_cleared_by_rerunning_the_same_script() {
+        use ExecutionProcessRunReason::{CodingAgent, SetupScript};
+        use ExecutionProcessStatus::{Completed, Failed, Running};
+
+        assert_eq!(setup_status(&[]), SetupStatus::NotRun);
+
+        let failed = [
+            step(&SetupScript, &Failed, Some("api")),
+            step(&SetupScript, &Completed, Some("web")),
+        ];
+        assert_eq!(setup_status(&failed), SetupStatus::Failed);
+
+        let rerun = [
+            step(&SetupScript, &Failed, Some("api")),
+            step(&SetupScript, &Completed, Some("web")),
+            step(&SetupScript, &Completed, Some("api")),
+            step(&CodingAgent, &Completed, None),
+        ];
+        assert_eq!(setup_status(&rerun), SetupStatus::Succeeded);
+
+        let overridden = [
+            step(&SetupScript, &Failed, Some("api")),
+            step(&CodingAgent, &Running, None),
+        ];
+        assert_eq!(setup_status(&overridden), SetupStatus::Overridden);
+
+        let running = [
+            step(&SetupScript, &Failed, Some("api")),
+            step(&SetupScript, &Running, Some("api")),
+        ];
+        assert_eq!(setup_status(&running), SetupStatus::Running);
+    }
+}
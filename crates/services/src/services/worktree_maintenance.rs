@@ -0,0 +1,419 @@
+//! Maintenance of the worktrees attempts leave in their repositories. Over a
+//! long-lived project worktrees go missing or get moved, outlive the attempts
+//! they were made for, and leave attempt branches behind. These are reported
+//! per repository, and fixed where that loses no work: locked worktrees and
+//! those with uncommitted changes are left alone.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use db::models::{repo::Repo, workspace::Workspace};
+use serde::Serialize;
+use ts_rs::TS;
+use utils::path::normalize_macos_private_alias;
+use uuid::Uuid;
+
+use super::{
+    cherry_pick::CHERRY_PICK_DIR,
+    dry_run::DRY_RUN_DIR,
+    git::{GitService, GitServiceError, WorktreeEntry},
+    worktree_manager::{WorktreeCleanup, WorktreeError, WorktreeManager},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum WorktreeProblem {
+    /// The directory is gone but git still lists the worktree
+    Missing,
+    /// The directory and the repository no longer point at each other, e.g.
+    /// after either was moved
+    Broken,
+    /// An attempt's checkout in vibe-kanban's worktree directory, but no
+    /// attempt's
+    Orphaned,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorktreeStatus {
+    pub path: String,
+    pub branch: Option<String>,
+    /// The attempt the worktree belongs to, if any
+    pub workspace_id: Option<Uuid>,
+    /// Why the worktree is locked, empty when no reason was given
+    pub locked: Option<String>,
+    pub problem: Option<WorktreeProblem>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorktreeReport {
+    pub repo_id: Uuid,
+    /// Worktrees besides the repository's own checkout
+    pub worktree_count: usize,
+    pub worktrees: Vec<WorktreeStatus>,
+    /// Attempt branches that neither an attempt nor a worktree has any more
+    pub dangling_branches: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SkippedWorktree {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct WorktreeMaintenance {
+    /// Worktrees pruned, removed or repaired
+    pub fixed: Vec<String>,
+    pub skipped: Vec<SkippedWorktree>,
+    /// The repository's worktrees afterwards
+    pub report: WorktreeReport,
+}
+
+/// The repository's worktrees and what's wrong with them, given the
+/// attempts that have the repository
+pub fn inspect(
+    git: &GitService,
+    repo: &Repo,
+    workspaces: &[Workspace],
+    branch_prefix: &str,
+) -> Result<WorktreeReport, GitServiceError> {
+    let entries = git.list_worktrees(&repo.path)?;
+    let attempts: HashMap<PathBuf, &Workspace> = workspaces
+        .iter()
+        .filter_map(|workspace| {
+            let dir = workspace.container_ref.as_ref()?;
+            let path = normalize_macos_private_alias(Path::new(dir).join(&repo.name));
+            Some((path, workspace))
+        })
+        .collect();
+    let attempt_dirs: Vec<(PathBuf, &Workspace)> = workspaces
+        .iter()
+        .filter_map(|workspace| {
+            let dir = workspace.container_ref.as_ref()?;
+            Some((normalize_macos_private_alias(Path::new(dir)), workspace))
+        })
+        .collect();
+    let base_dir = normalize_macos_private_alias(WorktreeManager::get_worktree_base_dir());
+
+    // The first entry is the repository's own checkout
+    let mut worktrees: Vec<WorktreeStatus> = entries
+        .iter()
+        .skip(1)
+        .map(|entry| {
+            let path = normalize_macos_private_alias(&entry.path);
+            let workspace = attempts
+                .get(&path)
+                .copied()
+                .or_else(|| owner(&path, &attempt_dirs));
+            WorktreeStatus {
+                path: entry.path.clone(),
+                branch: entry.branch.clone(),
+                workspace_id: workspace.map(|w| w.id),
+                locked: entry.locked.clone(),
+                problem: classify(entry, workspace.is_some(), &base_dir),
+            }
+        })
+        .collect();
+    // Attempts' worktrees that are on disk but that git no longer lists
+    let listed: HashSet<PathBuf> = entries
+        .iter()
+        .map(|entry| normalize_macos_private_alias(&entry.path))
+        .collect();
+    for (path, workspace) in &attempts {
+        if !listed.contains(path) && path.join(".git").is_file() {
+            worktrees.push(WorktreeStatus {
+                path: path.to_string_lossy().to_string(),
+                branch: Some(workspace.branch.clone()),
+                workspace_id: Some(workspace.id),
+                locked: None,
+                problem: Some(WorktreeProblem::Broken),
+            });
+        }
+    }
+
+    // Without a prefix, attempt branches can't be told from the user's own
+    let prefix = branch_prefix.trim_end_matches('/');
+    let dangling_branches = if prefix.is_empty() {
+        Vec::new()
+    } else {
+        let prefix = format!("{prefix}/");
+        let attempt_branches: HashSet<&str> =
+            workspaces.iter().map(|w| w.branch.as_str()).collect();
+        let checked_out: HashSet<&str> = entries
+            .iter()
+            .filter_map(|entry| entry.branch.as_deref())
+            .collect();
+        git.get_all_branches(&repo.path)?
+            .into_iter()
+            .filter(|branch| {
+                !branch.is_remote
+                    && branch.name.starts_with(&prefix)
+                    && !attempt_branches.contains(branch.name.as_str())
+                    && !checked_out.contains(branch.name.as_str())
+            })
+            .map(|branch| branch.name)
+            .collect()
+    };
+
+    Ok(WorktreeReport {
+        repo_id: repo.id,
+        worktree_count: entries.len().saturating_sub(1),
+        worktrees,
+        dangling_branches,
+    })
+}
+
+/// The attempt whose dry run copies or cherry-picks the worktree is, which
+/// live in `<attempt dir>/.vk-dry-run/<id>/` and `<attempt dir>/.vk-cherry-picks/`
+fn owner<'a>(path: &Path, attempt_dirs: &[(PathBuf, &'a Workspace)]) -> Option<&'a Workspace> {
+    attempt_dirs.iter().find_map(|(dir, workspace)| {
+        (path.starts_with(dir.join(DRY_RUN_DIR)) || path.starts_with(dir.join(CHERRY_PICK_DIR)))
+            .then_some(*workspace)
+    })
+}
+
+/// What's wrong with a worktree git lists, if anything. Only worktrees laid
+/// out as attempts' checkouts, `<base dir>/<attempt dir>/<repo>`, can be
+/// orphans; others in the base dir belong to something else of an attempt.
+fn classify(entry: &WorktreeEntry, is_attempts: bool, base_dir: &Path) -> Option<WorktreeProblem> {
+    let path = Path::new(&entry.path);
+    let is_attempt_checkout = normalize_macos_private_alias(path)
+        .strip_prefix(base_dir)
+        .is_ok_and(|relative| relative.components().count() == 2);
+    if entry.prunable.is_some() || !path.exists() {
+        Some(WorktreeProblem::Missing)
+    } else if !links_to_repo(path) {
+        Some(WorktreeProblem::Broken)
+    } else if !is_attempts && is_attempt_checkout {
+        Some(WorktreeProblem::Orphaned)
+    } else {
+        None
+    }
+}
+
+/// Whether the worktree's `.git` file points at metadata that exists
+fn links_to_repo(worktree_path: &Path) -> bool {
+    std::fs::read_to_string(worktree_path.join(".git"))
+        .ok()
+        .and_then(|contents| {
+            let gitdir = contents.strip_prefix("gitdir:")?.trim().to_string();
+            Some(worktree_path.join(gitdir))
+        })
+        .is_some_and(|gitdir| gitdir.exists())
+}
+
+fn locked_reason(reason: &str) -> String {
+    if reason.is_empty() {
+        "The worktree is locked".to_string()
+    } else {
+        format!("The worktree is locked: {reason}")
+    }
+}
+
+/// Drop git's metadata of missing worktrees, and remove orphaned worktrees
+/// that have no uncommitted changes
+pub async fn prune(
+    git: &GitService,
+    repo: &Repo,
+    workspaces: &[Workspace],
+    branch_prefix: &str,
+) -> Result<WorktreeMaintenance, WorktreeError> {
+    let before = inspect(git, repo, workspaces, branch_prefix)?;
+    let mut fixed = Vec::new();
+    let mut skipped = Vec::new();
+
+    // git doesn't prune locked worktrees either
+    git.prune_worktrees(&repo.path)?;
+    for worktree in &before.worktrees {
+        let Some(problem @ (WorktreeProblem::Missing | WorktreeProblem::Orphaned)) =
+            worktree.problem
+        else {
+            continue;
+        };
+        if let Some(reason) = &worktree.locked {
+            skipped.push(SkippedWorktree {
+                path: worktree.path.clone(),
+                reason: locked_reason(reason),
+            });
+            continue;
+        }
+        if problem == WorktreeProblem::Missing {
+            fixed.push(worktree.path.clone());
+            continue;
+        }
+
+        let path = PathBuf::from(&worktree.path);
+        let reason = match git.has_uncommitted_changes(&path) {
+            Ok(true) => Some("The worktree has uncommitted changes".to_string()),
+            Ok(false) => WorktreeManager::cleanup_worktree(&WorktreeCleanup::new(
+                path,
+                Some(repo.path.clone()),
+            ))
+            .await
+            .err()
+            .map(|e| e.to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        match reason {
+            Some(reason) => skipped.push(SkippedWorktree {
+                path: worktree.path.clone(),
+                reason,
+            }),
+            None => fixed.push(worktree.path.clone()),
+        }
+    }
+
+    Ok(WorktreeMaintenance {
+        fixed,
+        skipped,
+        report: inspect(git, repo, workspaces, branch_prefix)?,
+    })
+}
+
+/// Relink broken worktrees with the repository. Worktrees git has no
+/// metadata of at all can't be relinked; they're recreated the next time
+/// their attempt needs them.
+pub fn repair(
+    git: &GitService,
+    repo: &Repo,
+    workspaces: &[Workspace],
+    branch_prefix: &str,
+) -> Result<WorktreeMaintenance, GitServiceError> {
+    let before = inspect(git, repo, workspaces, branch_prefix)?;
+    let mut repaired = Vec::new();
+    let mut skipped = Vec::new();
+    for worktree in &before.worktrees {
+        if worktree.problem != Some(WorktreeProblem::Broken) {
+            continue;
+        }
+        match git.repair_worktree(&repo.path, Path::new(&worktree.path)) {
+            Ok(()) => repaired.push(worktree.path.clone()),
+            Err(e) => skipped.push(SkippedWorktree {
+                path: worktree.path.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    let report = inspect(git, repo, workspaces, branch_prefix)?;
+    let still_broken: HashSet<&str> = report
+        .worktrees
+        .iter()
+        .filter(|worktree| worktree.problem == Some(WorktreeProblem::Broken))
+        .map(|worktree| worktree.path.as_str())
+        .collect();
+    let (fixed, unfixed): (Vec<String>, Vec<String>) = repaired
+        .into_iter()
+        .partition(|path| !still_broken.contains(path.as_str()));
+    skipped.extend(unfixed.into_iter().map(|path| SkippedWorktree {
+        path,
+        reason: "git couldn't relink the worktree".to_string(),
+    }));
+
+    Ok(WorktreeMaintenance {
+        fixed,
+        skipped,
+        report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn entry(path: &Path, prunable: Option<&str>) -> WorktreeEntry {
+        WorktreeEntry {
+            path: path.to_string_lossy().to_string(),
+            branch: Some("vk/1234-task".to_string()),
+            locked: None,
+            prunable: prunable.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn worktrees_are_classified_by_their_directory_and_link() {
+        let td = TempDir::new().unwrap();
+        let base_dir = td.path().join("worktrees");
+        let metadata = td.path().join("repo/.git/worktrees/wt");
+        std::fs::create_dir_all(&metadata).unwrap();
+
+        let linked = base_dir.join("attempt/repo");
+        std::fs::create_dir_all(&linked).unwrap();
+        std::fs::write(
+            linked.join(".git"),
+            format!("gitdir: {}\n", metadata.display()),
+        )
+        .unwrap();
+        assert_eq!(classify(&entry(&linked, None), true, &base_dir), None);
+        assert_eq!(
+            classify(&entry(&linked, None), false, &base_dir),
+            Some(WorktreeProblem::Orphaned)
+        );
+        assert_eq!(
+            classify(
+                &entry(&linked, Some("gitdir file points to non-existent location")),
+                true,
+                &base_dir
+            ),
+            Some(WorktreeProblem::Missing)
+        );
+
+        let moved = base_dir.join("moved/repo");
+        std::fs::create_dir_all(&moved).unwrap();
+        std::fs::write(moved.join(".git"), "gitdir: /nowhere/.git/worktrees/wt\n").unwrap();
+        assert_eq!(
+            classify(&entry(&moved, None), true, &base_dir),
+            Some(WorktreeProblem::Broken)
+        );
+        assert_eq!(
+            classify(&entry(&base_dir.join("gone"), None), true, &base_dir),
+            Some(WorktreeProblem::Missing)
+        );
+    }
+
+    #[test]
+    fn dry_run_copies_and_cherry_picks_are_not_orphans() {
+        let td = TempDir::new().unwrap();
+        let base_dir = td.path().join("worktrees");
+        let metadata = td.path().join("repo/.git/worktrees/wt");
+        std::fs::create_dir_all(&metadata).unwrap();
+        let attempt_dir = base_dir.join("1a2b-fix");
+        let copy = attempt_dir
+            .join(DRY_RUN_DIR)
+            .join(Uuid::new_v4().to_string())
+            .join("repo");
+        let cherry_pick = attempt_dir.join(CHERRY_PICK_DIR).join("release/repo");
+        for path in [&copy, &cherry_pick] {
+            std::fs::create_dir_all(path).unwrap();
+            std::fs::write(
+                path.join(".git"),
+                format!("gitdir: {}\n", metadata.display()),
+            )
+            .unwrap();
+            // Even when its attempt isn't known
+            assert_eq!(classify(&entry(path, None), false, &base_dir), None);
+        }
+
+        let workspace = Workspace {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            container_ref: Some(attempt_dir.to_string_lossy().to_string()),
+            branch: "vk/1a2b-fix".to_string(),
+            agent_working_dir: None,
+            setup_completed_at: None,
+            executor_profile_id: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let attempt_dirs = [(attempt_dir.clone(), &workspace)];
+        for path in [&copy, &cherry_pick] {
+            assert_eq!(owner(path, &attempt_dirs).map(|w| w.id), Some(workspace.id));
+        }
+        assert!(owner(&base_dir.join("1a2b-fixed/repo"), &attempt_dirs).is_none());
+    }
+}
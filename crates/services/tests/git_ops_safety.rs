@@ -4,8 +4,14 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use db::models::{merge::MergeStrategy, merge_selection::SelectedFile};
 use git2::{PushOptions, Repository, build::CheckoutBuilder};
-use services::services::git::{GitCli, GitCliError, GitService};
+use services::services::{
+    cherry_pick,
+    config::{CommitSigningConfig, CommitSigningMode},
+    git::{GitCli, GitCliError, GitService, SignatureStatus},
+    partial_merge,
+};
 use tempfile::TempDir;
 // Avoid direct git CLI usage in tests; exercise GitService instead.
 
@@ -598,6 +604,282 @@ fn merge_preserves_unstaged_changes_on_base() {
     assert_eq!(m, "merged content\n");
 }
 
+#[test]
+fn merge_without_signing_overrides_git_config() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "main");
+    // Signing as the config asks would fail without a key
+    repo.config()
+        .unwrap()
+        .set_bool("commit.gpgsign", true)
+        .unwrap();
+    write_file(&worktree_path, "merged.txt", "merged content\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "feature merged");
+
+    let signing = CommitSigningConfig {
+        mode: CommitSigningMode::Never,
+        ..Default::default()
+    };
+    s.merge_changes_with_strategy(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "main",
+        "squash",
+        MergeStrategy::Squash,
+        &signing,
+    )
+    .unwrap();
+    assert_eq!(
+        s.get_head_signature(&repo_path).unwrap(),
+        SignatureStatus::Unsigned
+    );
+}
+
+#[test]
+fn merge_commit_keeps_task_commits_as_second_parent() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "main");
+    let base_tip = repo.head().unwrap().peel_to_commit().unwrap().id();
+    write_file(&worktree_path, "merged.txt", "merged content\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "feature merged");
+    let task_tip = wt_repo.head().unwrap().peel_to_commit().unwrap().id();
+
+    let sha = s
+        .merge_changes_with_strategy(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "Merge feature",
+            MergeStrategy::MergeCommit,
+            &CommitSigningConfig::default(),
+        )
+        .unwrap();
+    let merge = repo
+        .find_commit(git2::Oid::from_str(&sha).unwrap())
+        .unwrap();
+    assert_eq!(merge.parent_ids().collect::<Vec<_>>(), [base_tip, task_tip]);
+    assert_eq!(merge.summary(), Some("Merge feature"));
+}
+
+#[test]
+fn fast_forward_moves_base_to_task_tip() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "main");
+    write_file(&worktree_path, "merged.txt", "merged content\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "feature merged");
+    let task_tip = wt_repo.head().unwrap().peel_to_commit().unwrap().id();
+
+    let sha = s
+        .merge_changes_with_strategy(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "unused",
+            MergeStrategy::FastForward,
+            &CommitSigningConfig::default(),
+        )
+        .unwrap();
+    assert_eq!(sha, task_tip.to_string());
+    assert_eq!(
+        repo.head().unwrap().peel_to_commit().unwrap().id(),
+        task_tip
+    );
+}
+
+#[test]
+fn cherry_pick_moves_target_branch_and_removes_its_worktree() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    write_file(&worktree_path, "hotfix.txt", "fixed\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "hotfix");
+
+    let commits =
+        cherry_pick::attempt_commits(&s, &repo_path, "feature", "old-base", None).unwrap();
+    assert_eq!(commits.len(), 2);
+    let attempt_dir = td.path().join("attempt");
+    let pick_path = cherry_pick::worktree_path(&attempt_dir, "repo", "new-base");
+    let status = cherry_pick::start(&s, &repo_path, &pick_path, "new-base", &commits).unwrap();
+
+    let new_tip = s.get_branch_oid(&repo_path, "new-base").unwrap();
+    assert_eq!(status.target_commit.as_deref(), Some(new_tip.as_str()));
+    let repo = Repository::open(&repo_path).unwrap();
+    let tip = repo
+        .find_commit(git2::Oid::from_str(&new_tip).unwrap())
+        .unwrap();
+    assert!(tip.tree().unwrap().get_name("hotfix.txt").is_some());
+    assert!(tip.message().unwrap().contains("cherry picked from commit"));
+    assert!(!pick_path.exists());
+    assert!(!attempt_dir.join(cherry_pick::CHERRY_PICK_DIR).exists());
+}
+
+#[test]
+fn stopped_cherry_pick_stays_inside_the_attempt_dir() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    // new-base wrote base.txt differently, so this commit conflicts there
+    write_file(&worktree_path, "base.txt", "hotfix version\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "hotfix");
+
+    let base_dir = td.path().join("worktrees");
+    let attempt_dir = base_dir.join("1a2b-fix");
+    fs::create_dir_all(&attempt_dir).unwrap();
+    let commits =
+        cherry_pick::attempt_commits(&s, &repo_path, "feature", "old-base", None).unwrap();
+    let pick_path = cherry_pick::worktree_path(&attempt_dir, "repo", "new-base");
+    let status = cherry_pick::start(&s, &repo_path, &pick_path, "new-base", &commits).unwrap();
+    assert!(status.target_commit.is_none());
+    assert!(pick_path.exists());
+
+    // The orphan sweep removes whatever in the base dir isn't an attempt's
+    // directory; the stopped cherry-pick adds nothing there
+    let entries: Vec<PathBuf> = fs::read_dir(&base_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(entries, vec![attempt_dir.clone()]);
+
+    cherry_pick::abort(&s, &repo_path, &pick_path).unwrap();
+    assert!(!attempt_dir.join(cherry_pick::CHERRY_PICK_DIR).exists());
+    assert!(attempt_dir.exists());
+}
+
+#[test]
+fn partial_merge_leaves_unselected_changes_on_the_branch() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    write_file(&worktree_path, "extra.txt", "not yet\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "extra");
+
+    let base = s
+        .get_base_commit(&repo_path, "feature", "old-base")
+        .unwrap()
+        .to_string();
+    let head = s.get_branch_oid(&repo_path, "feature").unwrap();
+    let patch = partial_merge::selection_patch(
+        &s,
+        &repo_path,
+        &base,
+        &head,
+        &[SelectedFile {
+            path: "feat.txt".to_string(),
+            hunks: None,
+        }],
+    )
+    .unwrap();
+    let sha = s
+        .merge_partial(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "old-base",
+            &patch,
+            "partial",
+            &CommitSigningConfig::default(),
+        )
+        .unwrap();
+
+    assert_eq!(s.get_branch_oid(&repo_path, "old-base").unwrap(), sha);
+    let repo = Repository::open(&repo_path).unwrap();
+    let merged = repo
+        .find_commit(git2::Oid::from_str(&sha).unwrap())
+        .unwrap();
+    assert!(merged.tree().unwrap().get_name("feat.txt").is_some());
+    assert!(merged.tree().unwrap().get_name("extra.txt").is_none());
+
+    // The branch keeps the rest and isn't behind its base
+    let (_, behind) = s
+        .get_branch_status(&repo_path, "feature", "old-base")
+        .unwrap();
+    assert_eq!(behind, 0);
+    let head = s.get_branch_oid(&repo_path, "feature").unwrap();
+    let remaining = partial_merge::selectable_files(&s, &repo_path, &sha, &head).unwrap();
+    let paths: Vec<&str> = remaining.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["extra.txt"]);
+    assert!(worktree_path.join("extra.txt").exists());
+}
+
+#[test]
+fn check_against_base_reports_conflicts_and_merge_brings_branch_up_to_date() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+
+    // new-base writes base.txt differently from the feature's old-base
+    let (behind, conflicts) = s
+        .check_against_base(&repo_path, "feature", "new-base")
+        .unwrap();
+    assert_eq!(behind, 1);
+    assert_eq!(conflicts, vec!["base.txt".to_string()]);
+
+    let repo = Repository::open(&repo_path).unwrap();
+    write_file(&repo_path, "later.txt", "later\n");
+    commit_all(&repo, "later old-base commit");
+    let (behind, conflicts) = s
+        .check_against_base(&repo_path, "feature", "old-base")
+        .unwrap();
+    assert_eq!(behind, 1);
+    assert!(conflicts.is_empty());
+
+    s.merge_base_into_branch(&worktree_path, "old-base", &CommitSigningConfig::default())
+        .unwrap();
+    let (behind, _) = s
+        .check_against_base(&repo_path, "feature", "old-base")
+        .unwrap();
+    assert_eq!(behind, 0);
+    assert!(worktree_path.join("later.txt").exists());
+    assert!(worktree_path.join("feat.txt").exists());
+}
+
+#[test]
+fn worktree_list_reports_locks_and_repair_relinks_a_moved_worktree() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+
+    let repo = Repository::open(&repo_path).unwrap();
+    repo.find_worktree("wt-feature")
+        .unwrap()
+        .lock(Some("on a usb drive"))
+        .unwrap();
+    let entries = s.list_worktrees(&repo_path).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].branch.as_deref(), Some("feature"));
+    assert_eq!(entries[1].locked.as_deref(), Some("on a usb drive"));
+    repo.find_worktree("wt-feature").unwrap().unlock().unwrap();
+
+    let moved_path = td.path().join("wt-moved");
+    std::fs::rename(&worktree_path, &moved_path).unwrap();
+    s.repair_worktree(&repo_path, &moved_path).unwrap();
+    let entries = s.list_worktrees(&repo_path).unwrap();
+    assert_eq!(
+        std::fs::canonicalize(&entries[1].path).unwrap(),
+        std::fs::canonicalize(&moved_path).unwrap()
+    );
+    assert!(entries[1].prunable.is_none());
+    assert!(!s.has_uncommitted_changes(&moved_path).unwrap());
+}
+
 #[test]
 fn update_ref_does_not_destroy_feature_worktree_dirty_state() {
     let td = TempDir::new().unwrap();
@@ -769,6 +1051,75 @@ fn rebase_applies_multiple_commits_onto_ahead_base() {
     assert_eq!(feat2, "second change\n");
 }
 
+#[test]
+fn stacked_branch_is_rebased_onto_the_rebased_parent() {
+    let td = TempDir::new().unwrap();
+    let repo_path = td.path().join("repo_stack");
+    let g = GitService::new();
+    g.initialize_repo_with_main_branch(&repo_path).unwrap();
+    let repo = Repository::open(&repo_path).unwrap();
+    configure_user(&repo);
+    checkout_branch(&repo, "main");
+    write_file(&repo_path, "common.txt", "base\n");
+    commit_all(&repo, "initial main commit");
+    let main_before = g.get_branch_oid(&repo_path, "main").unwrap();
+
+    // A parent attempt on main, and a child attempt stacked on it
+    create_branch_from_head(&repo, "parent");
+    let parent_wt = td.path().join("wt-parent");
+    g.add_worktree(&repo_path, &parent_wt, "parent", false)
+        .unwrap();
+    let parent_repo = Repository::open(&parent_wt).unwrap();
+    write_file(&parent_wt, "parent.txt", "parent v1\n");
+    commit_all(&parent_repo, "parent commit");
+
+    create_branch_from_head(&parent_repo, "child");
+    let child_wt = td.path().join("wt-child");
+    g.add_worktree(&repo_path, &child_wt, "child", false)
+        .unwrap();
+    let child_repo = Repository::open(&child_wt).unwrap();
+    write_file(&child_wt, "child.txt", "child\n");
+    commit_all(&child_repo, "child commit");
+
+    // Main moves on and the parent is rebased onto it, its commit reworked
+    // along the way so that the child's copy of it no longer applies
+    write_file(&repo_path, "main.txt", "main moved\n");
+    commit_all(&repo, "advance main");
+    let parent_old_head = g.get_head_info(&parent_wt).unwrap().oid;
+    g.rebase_branch(&repo_path, &parent_wt, "main", &main_before, "parent")
+        .expect("parent rebase should succeed");
+    write_file(&parent_wt, "parent.txt", "parent v2\n");
+    let mut index = parent_repo.index().unwrap();
+    index.add_path(Path::new("parent.txt")).unwrap();
+    index.write().unwrap();
+    let tree = parent_repo.find_tree(index.write_tree().unwrap()).unwrap();
+    parent_repo
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .amend(Some("HEAD"), None, None, None, None, Some(&tree))
+        .unwrap();
+    let parent_new_head = g.get_head_info(&parent_wt).unwrap().oid;
+
+    // Restacking only replays the child's own commits onto the new parent head
+    g.rebase_branch(&repo_path, &child_wt, "parent", &parent_old_head, "child")
+        .expect("restacking the child should succeed");
+
+    let child_head = child_repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(child_head.summary(), Some("child commit"));
+    assert_eq!(
+        child_head.parent_id(0).unwrap().to_string(),
+        parent_new_head
+    );
+    assert_eq!(
+        fs::read_to_string(child_wt.join("parent.txt")).unwrap(),
+        "parent v2\n"
+    );
+    assert!(child_wt.join("main.txt").exists());
+    assert!(child_wt.join("child.txt").exists());
+}
+
 #[test]
 fn merge_when_base_ahead_and_feature_ahead_fails() {
     let td = TempDir::new().unwrap();
@@ -1025,6 +1376,40 @@ fn sparse_checkout_respected_in_worktree_diffs_and_commit() {
     );
 }
 
+#[test]
+fn sparse_worktree_only_checks_out_matching_paths() {
+    let td = TempDir::new().unwrap();
+    let repo_path = td.path().join("repo_sparse_wt");
+    let s = GitService::new();
+    s.initialize_repo_with_main_branch(&repo_path).unwrap();
+    let repo = Repository::open(&repo_path).unwrap();
+    configure_user(&repo);
+    checkout_branch(&repo, "main");
+    write_file(&repo_path, "package.json", "{}\n");
+    write_file(&repo_path, "services/api/main.rs", "api\n");
+    write_file(&repo_path, "services/api/fixtures/big.bin", "big\n");
+    write_file(&repo_path, "services/web/main.rs", "web\n");
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+
+    create_branch_from_head(&repo, "feature");
+    let wt = td.path().join("wt_sparse_only");
+    let patterns = ["/*", "!/*/", "services/api/", "!services/api/fixtures/"].map(String::from);
+    s.add_sparse_worktree(&repo_path, &wt, "feature", &patterns)
+        .unwrap();
+
+    assert!(wt.join("package.json").exists());
+    assert!(wt.join("services/api/main.rs").exists());
+    assert!(!wt.join("services/api/fixtures/big.bin").exists());
+    assert!(!wt.join("services/web/main.rs").exists());
+    // Files left out aren't changes, and the main checkout stays whole
+    assert!(!s.has_uncommitted_changes(&wt).unwrap());
+    assert!(repo_path.join("services/web/main.rs").exists());
+
+    s.set_sparse_checkout(&wt, &["/*".to_string()]).unwrap();
+    assert!(wt.join("services/web/main.rs").exists());
+    assert!(wt.join("services/api/fixtures/big.bin").exists());
+}
+
 #[test]
 fn worktree_diff_ignores_commits_where_base_branch_is_ahead() {
     let td = TempDir::new().unwrap();
@@ -141,6 +141,7 @@ const CreatePRDialogImpl = NiceModal.create<CreatePRDialogProps>(
         draft: isDraft,
         auto_generate_description: autoGenerateDescription,
         repo_id: repoId,
+        selection_id: null,
       });
 
       if (result.success) {
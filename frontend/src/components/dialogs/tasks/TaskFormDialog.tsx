@@ -173,6 +173,7 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
             description: value.description,
             status: value.status,
             parent_workspace_id: null,
+            priority: null,
             image_ids: images.length > 0 ? images.map((img) => img.id) : null,
           },
         },
@@ -188,8 +189,11 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
         status: null,
         parent_workspace_id:
           mode === 'subtask' ? props.parentTaskAttemptId : null,
+        parent_task_id: null,
+        priority: null,
         image_ids: imageIds,
         shared_task_id: null,
+        due_at: null,
       };
       const shouldAutoStart = value.autoStart && !forceCreateOnlyRef.current;
       if (shouldAutoStart) {
@@ -132,8 +132,11 @@ export function NoServerContent({
         description: COMPANION_INSTALL_TASK_DESCRIPTION,
         status: null,
         parent_workspace_id: null,
+        parent_task_id: null,
+        priority: null,
         image_ids: null,
         shared_task_id: null,
+        due_at: null,
       },
       executor_profile_id: config.executor_profile,
       repos,
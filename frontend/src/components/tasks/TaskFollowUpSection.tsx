@@ -392,6 +392,28 @@ export function TaskFollowUpSection({
     }
   }, [workspaceId, isAttemptRunning]);
 
+  // Turn the review comments into follow-up tasks instead of sending them
+  const [isCreatingReviewTasks, setIsCreatingReviewTasks] = useState(false);
+  const handleCreateReviewTasks = useCallback(async () => {
+    if (!workspaceId || comments.length === 0) return;
+    setIsCreatingReviewTasks(true);
+    try {
+      await attemptsApi.createReviewTasks(workspaceId, {
+        comments: comments.map((comment) => ({
+          file_path: comment.filePath,
+          line_number: comment.lineNumber,
+          text: comment.text,
+          code_line: comment.codeLine ?? null,
+        })),
+      });
+      clearComments();
+    } catch (error) {
+      console.error('Failed to create tasks from review comments:', error);
+    } finally {
+      setIsCreatingReviewTasks(false);
+    }
+  }, [workspaceId, comments, clearComments]);
+
   // Handler to queue the current message for execution after agent finishes
   const handleQueueMessage = useCallback(async () => {
     if (
@@ -896,6 +918,19 @@ export function TaskFollowUpSection({
                   {t('followUp.clearReviewComments')}
                 </Button>
               )}
+              {comments.length > 0 && (
+                <Button
+                  onClick={handleCreateReviewTasks}
+                  size="sm"
+                  variant="outline"
+                  disabled={!isEditable || isCreatingReviewTasks}
+                >
+                  {isCreatingReviewTasks && (
+                    <Loader2 className="animate-spin h-4 w-4 mr-2" />
+                  )}
+                  {t('followUp.createTasksFromComments')}
+                </Button>
+              )}
               <Button
                 onClick={onSendFollowUp}
                 disabled={!canSendFollowUp || !isEditable}
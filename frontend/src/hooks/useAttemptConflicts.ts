@@ -7,7 +7,10 @@ export function useAttemptConflicts(attemptId?: string, repoId?: string) {
 
   const abortConflicts = useCallback(async () => {
     if (!attemptId || !repoId) return;
-    await attemptsApi.abortConflicts(attemptId, { repo_id: repoId });
+    await attemptsApi.abortConflicts(attemptId, {
+      repo_id: repoId,
+      cherry_pick_target: null,
+    });
     await queryClient.invalidateQueries({
       queryKey: ['branchStatus', attemptId],
     });
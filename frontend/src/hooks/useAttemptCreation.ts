@@ -28,6 +28,7 @@ export function useAttemptCreation({
         task_id: taskId,
         executor_profile_id: profile,
         repos,
+        stacked_on_workspace_id: null,
       }),
     onSuccess: (newAttempt: Workspace) => {
       queryClient.setQueryData(
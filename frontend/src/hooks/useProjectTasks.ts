@@ -136,11 +136,7 @@ export const useProjectTasks = (projectId: string): UseProjectTasksResult => {
     );
 
     (Object.values(byStatus) as TaskWithAttemptStatus[][]).forEach((list) => {
-      list.sort(
-        (a, b) =>
-          new Date(b.created_at as string).getTime() -
-          new Date(a.created_at as string).getTime()
-      );
+      list.sort((a, b) => Number(a.position) - Number(b.position));
     });
 
     return { tasks: sorted, tasksById: merged, tasksByStatus: byStatus };
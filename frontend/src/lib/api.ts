@@ -7,6 +7,7 @@ import {
   CreateFollowUpAttempt,
   EditorType,
   CreateGitHubPrRequest,
+  CreateReviewTasksRequest,
   CreateTask,
   CreateAndStartTaskRequest,
   CreateTaskAttemptBody,
@@ -30,9 +31,12 @@ import {
   Tag,
   TagSearchParams,
   TaskWithAttemptStatus,
+  TaskWithLinks,
   UpdateProject,
   UpdateTask,
   UpdateTag,
+  MoveTaskRequest,
+  TaskOrder,
   UserSystemInfo,
   McpServerQuery,
   UpdateMcpServersBody,
@@ -387,9 +391,9 @@ export const projectsApi = {
 
 // Task Management APIs
 export const tasksApi = {
-  getById: async (taskId: string): Promise<Task> => {
+  getById: async (taskId: string): Promise<TaskWithLinks> => {
     const response = await makeRequest(`/api/tasks/${taskId}`);
-    return handleApiResponse<Task>(response);
+    return handleApiResponse<TaskWithLinks>(response);
   },
 
   create: async (data: CreateTask): Promise<Task> => {
@@ -418,6 +422,17 @@ export const tasksApi = {
     return handleApiResponse<Task>(response);
   },
 
+  move: async (
+    taskId: string,
+    data: MoveTaskRequest
+  ): Promise<TaskOrder> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/move`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskOrder>(response);
+  },
+
   delete: async (taskId: string): Promise<void> => {
     const response = await makeRequest(`/api/tasks/${taskId}`, {
       method: 'DELETE',
@@ -513,6 +528,20 @@ export const attemptsApi = {
     return handleApiResponse<TaskRelationships>(response);
   },
 
+  createReviewTasks: async (
+    attemptId: string,
+    data: CreateReviewTasksRequest
+  ): Promise<Task[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/review-tasks`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Task[]>(response);
+  },
+
   getAll: async (taskId: string): Promise<Workspace[]> => {
     const response = await makeRequest(`/api/task-attempts?task_id=${taskId}`);
     return handleApiResponse<Workspace[]>(response);
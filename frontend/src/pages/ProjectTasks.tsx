@@ -429,14 +429,23 @@ export function ProjectTasks() {
       });
     });
 
-    const getTimestamp = (item: KanbanColumnItem) => {
-      const createdAt =
-        item.type === 'task' ? item.task.created_at : item.task.created_at;
-      return new Date(createdAt).getTime();
+    // Local tasks keep the order stored on the server, shared-only tasks
+    // follow them newest first
+    const compareItems = (a: KanbanColumnItem, b: KanbanColumnItem) => {
+      if (a.type === 'task' && b.type === 'task') {
+        return Number(a.task.position) - Number(b.task.position);
+      }
+      if (a.type !== b.type) {
+        return a.type === 'task' ? -1 : 1;
+      }
+      return (
+        new Date(b.task.created_at).getTime() -
+        new Date(a.task.created_at).getTime()
+      );
     };
 
     TASK_STATUSES.forEach((status) => {
-      columns[status].sort((a, b) => getTimestamp(b) - getTimestamp(a));
+      columns[status].sort(compareItems);
     });
 
     return columns;
@@ -757,15 +766,13 @@ export function ProjectTasks() {
       if (!task || task.status === newStatus) return;
 
       try {
-        await tasksApi.update(draggedTaskId, {
-          title: task.title,
-          description: task.description,
+        await tasksApi.move(draggedTaskId, {
           status: newStatus,
-          parent_workspace_id: task.parent_workspace_id,
-          image_ids: null,
+          column_id: null,
+          index: 0,
         });
       } catch (err) {
-        console.error('Failed to update task status:', err);
+        console.error('Failed to move task:', err);
       }
     },
     [tasksById]
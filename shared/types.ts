@@ -30,281 +30,2173 @@ export type CreateProjectRepo = { display_name: string, git_repo_path: string, }
 
 export type UpdateProjectRepo = { setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean | null, };
 
+export type ProjectEnvVar = { id: string, project_id: string, name: string, value: string, created_at: string, updated_at: string, };
+
+export type SetProjectEnvVar = { value: string, };
+
+export type ProjectSecret = { id: string, project_id: string, name: string, created_at: string, updated_at: string, };
+
+export type ProjectSettings = { upload_policy: UploadPolicy, gitignored_uploads: GitignoredUploadPolicy, upload_scan: UploadScanSettings, container_backend: ContainerBackend, retention: RetentionPolicy, exec_policy: ExecPolicy, workspace_pool: WorkspacePoolSettings, resource_limits: ResourceLimits, network_policy: NetworkPolicy, shared_caches: SharedCacheSettings, gpus: GpuSettings, wip_limits: WipLimitSettings, due_reminders: DueReminderSettings, swimlanes: SwimlaneGrouping, definition_of_done: DefinitionOfDone, retry_policy: RetryPolicy, lint_gate: LintGate, agent_review: AgentReviewSettings, llm_proxy: LlmProxySettings, hooks: Array<LifecycleHook>, attempt_limits: AttemptLimits, command_policy: CommandPolicy, prompts: PromptSettings, concurrency: ConcurrencySettings, 
+/**
+ * Gitignore-style patterns of paths, such as CI workflows or secrets
+ * files, whose changes by an agent wait for the user's override before
+ * they are committed
+ */
+protected_paths: Array<string>, code_host: CodeHostSettings, commit_messages: CommitMessageSettings, 
+/**
+ * How attempts are merged, both here and through the code host's pull
+ * requests
+ */
+merge_strategy: MergeStrategy, branch_sync: BranchSyncSettings, 
+/**
+ * Merges, here or through the code host, wait until every CI check of
+ * the attempt's pull request has passed on the branch's tip
+ */
+require_green_checks: boolean, git_provisioning: GitProvisioningSettings, sparse_checkout: SparseCheckoutSettings, };
+
+export type UploadPolicy = { 
+/**
+ * Maximum number of bytes uploaded across all tasks of the project
+ */
+max_total_bytes: bigint | null, 
+/**
+ * Maximum size of a single uploaded file
+ */
+max_file_bytes: bigint | null, 
+/**
+ * Extensions (without the leading dot) that may be uploaded. Empty allows all.
+ */
+allowed_extensions: Array<string>, 
+/**
+ * Extensions (without the leading dot) that are always rejected, e.g. "exe"
+ */
+denied_extensions: Array<string>, };
+
+export type GitignoredUploadPolicy = "warn" | "refuse";
+
+export type UploadScanSettings = { enabled: boolean, scanner: UploadScanner | null, };
+
+export type UploadScanner = { "type": "command", command: string, } | { "type": "clamd", address: string, };
+
+export type ContainerBackend = { "type": "local" } | { "type": "docker", image: string, } | { "type": "devcontainer" } | { "type": "ssh", 
+/**
+ * Destination passed to `ssh`, e.g. `user@build-server`
+ */
+host: string, remote_root: string, };
+
+export type RetentionPolicy = { 
+/**
+ * Remove a workspace this many days after it was merged
+ */
+merged_after_days: bigint | null, 
+/**
+ * Remove an unmerged workspace of a done or cancelled task this many days
+ * after its last activity
+ */
+abandoned_after_days: bigint | null, 
+/**
+ * Empty an attempt's scratch directory this many days after its last
+ * activity, keeping the rest of the workspace
+ */
+scratch_after_days: bigint | null, 
+/**
+ * Keep the workspaces of a deleted project in the trash this many days
+ * before removing them, instead of removing them right away
+ */
+trash_after_delete_days: bigint | null, };
+
+export type ExecPolicy = { enabled: boolean, 
+/**
+ * Program names that may be run. Empty allows every program not denied.
+ */
+allowed_programs: Array<string>, 
+/**
+ * Program names that may not be run. Shells and programs that run
+ * another one, such as `env` or `xargs`, are denied along with them.
+ */
+denied_programs: Array<string>, };
+
+export type WorkspacePoolSettings = { 
+/**
+ * Number of workspaces kept ready. Zero disables the pool.
+ */
+size: number, };
+
+export type ResourceLimits = { 
+/**
+ * CPU time as a number of cores, e.g. `1.5`
+ */
+cpu_cores: number | null, memory_mb: number | null, max_processes: number | null, };
+
+export type NetworkPolicy = { "mode": "unrestricted" } | { "mode": "allowlist", 
+/**
+ * e.g. `github.com`, which also allows `api.github.com`
+ */
+domains: Array<string>, } | { "mode": "offline" };
+
+export type SharedCacheSettings = { caches: Array<SharedCache>, 
+/**
+ * Empty a cache once it grows past this size and no execution uses it
+ */
+max_size_mb: number | null, };
+
+export type SharedCache = "cargo" | "npm" | "pnpm" | "yarn" | "pip";
+
+export type GpuSettings = { 
+/**
+ * GPUs per attempt; zero requests none
+ */
+count: number, 
+/**
+ * Extended resource requested from Kubernetes, depending on the
+ * cluster's device plugin
+ */
+kubernetes_resource: string, };
+
+export type WipLimitSettings = { enforcement: WipEnforcement, limits: Array<WipLimit>, };
+
+export type WipLimit = { column_id: string, max_tasks: number, };
+
+export type WipEnforcement = "warn" | "reject";
+
+export type DueReminderSettings = { enabled: boolean, 
+/**
+ * How long before the deadline the reminder is sent
+ */
+hours_before: number, };
+
+export type SwimlaneGrouping = "none" | "label" | "epic" | "parent_task";
+
+export type DefinitionOfDone = { items: Array<string>, 
+/**
+ * Refuse to move a task to done while items of its checklist are
+ * unchecked
+ */
+block_done_while_unchecked: boolean, };
+
+export type RetryPolicy = { enabled: boolean, 
+/**
+ * Retries allowed per attempt, over all of its runs
+ */
+max_retries: number, 
+/**
+ * Wait before the attempt's first retry
+ */
+delay_seconds: number, 
+/**
+ * Failures that are retried
+ */
+retry_on: Array<FailureKind>, };
+
+export type LintGate = { checks: Array<LintCheck>, 
+/**
+ * Send the agent what the failed checks reported in a follow-up run
+ */
+auto_fix: boolean, 
+/**
+ * Fix-up runs allowed per attempt
+ */
+max_fix_runs: number, };
+
+export type LintCheck = { name: string, 
+/**
+ * Run without a shell, like workspace commands
+ */
+command: string, 
+/**
+ * Directory relative to the workspace root; the root when omitted
+ */
+working_dir: string | null, 
+/**
+ * Defaults to 10 minutes
+ */
+timeout_secs: bigint | null, };
+
+export type AgentReviewSettings = { enabled: boolean, 
+/**
+ * The executor profile reviewing; no reviews without one
+ */
+reviewer: ExecutorProfileId | null, 
+/**
+ * What the reviewer should look at besides correctness, e.g. the
+ * project's conventions
+ */
+instructions: string | null, 
+/**
+ * Start a revision run with the comments when the reviewer asks for
+ * changes
+ */
+auto_revise: boolean, 
+/**
+ * Reviews per attempt
+ */
+max_iterations: number, };
+
+export type LlmProxySettings = { enabled: boolean, 
+/**
+ * Tried in order; when one can't be reached, is rate limited or fails,
+ * the call goes to the next one with the same API
+ */
+providers: Array<LlmProvider>, 
+/**
+ * US dollars per calendar month (UTC) across the project's calls; calls
+ * are refused once it is reached
+ */
+monthly_spend_cap_usd: number | null, 
+/**
+ * Keep request and response bodies in the log, with secrets masked
+ */
+log_bodies: boolean, 
+/**
+ * How the agents reach this server, e.g. from containers;
+ * `http://127.0.0.1:<port>` when omitted
+ */
+server_url: string | null, };
+
+export type LlmProvider = { name: string, api: LlmApi, 
+/**
+ * The API's public endpoint when omitted
+ */
+base_url: string | null, 
+/**
+ * Name of the project secret holding the key
+ */
+api_key_secret: string, 
+/**
+ * Prices per million tokens, to count calls against the spend cap
+ */
+input_usd_per_mtok: number, output_usd_per_mtok: number, };
+
+export type LlmApi = "anthropic" | "openai";
+
+export type LifecycleHook = { name: string, events: Array<HookEvent>, action: HookAction, 
+/**
+ * Defaults to a minute
+ */
+timeout_secs: bigint | null, 
+/**
+ * Keep the attempt from starting when the hook fails; only applies to
+ * `before_start`
+ */
+blocking: boolean, };
+
+export type HookAction = { "type": "command", command: string, 
+/**
+ * Directory relative to the workspace root; the root when omitted
+ */
+working_dir: string | null, } | { "type": "webhook", url: string, 
+/**
+ * Name of a project secret sent as a bearer token
+ */
+token_secret: string | null, };
+
+export type AttemptLimits = { 
+/**
+ * Minutes the coding agents may run
+ */
+max_minutes: number | null, 
+/**
+ * Input and output tokens; cached input isn't counted
+ */
+max_tokens: number | null, 
+/**
+ * Cost as the executors report it; runs of executors that don't report
+ * cost aren't held to it
+ */
+max_cost_usd: number | null, max_tool_calls: number | null, };
+
+export type ConcurrencySettings = { 
+/**
+ * None for no limit of the project's own
+ */
+max_concurrent_attempts: number | null, };
+
+export type BranchSyncSettings = { mode: BranchSyncMode, 
+/**
+ * How often each open attempt is checked
+ */
+interval_minutes: number, };
+
+export type BranchSyncMode = "off" | "check" | "rebase" | "merge";
+
+export type GitProvisioningSettings = { 
+/**
+ * Initialize and update submodules, recursively
+ */
+submodules: boolean, 
+/**
+ * Download the Git LFS objects of the checkout
+ */
+lfs: boolean, };
+
+export type SparseCheckoutSettings = { 
+/**
+ * Gitignore-style patterns of the paths to check out
+ */
+patterns: Array<string>, 
+/**
+ * Also check out the paths of the task's scope
+ */
+include_task_scope: boolean, };
+
+export type CodeHostSettings = { provider: CodeHostProvider, gitlab: GitLabSettings, bitbucket: BitbucketSettings, azure_devops: AzureDevOpsSettings, };
+
+export type CodeHostProvider = "github" | "gitlab" | "bitbucket" | "azure_devops";
+
+export type GitLabSettings = { 
+/**
+ * The instance's address, e.g. https://gitlab.example.com; None to take
+ * the host of the repository's remote
+ */
+base_url: string | null, 
+/**
+ * Name of the project secret holding the token
+ */
+token_secret: string, };
+
+export type BitbucketSettings = { 
+/**
+ * The account the app password belongs to; None when the secret holds
+ * a token
+ */
+username: string | null, 
+/**
+ * Name of the project secret holding the app password or token
+ */
+token_secret: string, };
+
+export type AzureDevOpsSettings = { 
+/**
+ * Name of the project secret holding the token
+ */
+token_secret: string, 
+/**
+ * Link the work items the task mentions, as `#123` or `AB#123`, to its
+ * pull requests
+ */
+link_work_items: boolean, };
+
+export type CommitMessageSettings = { 
+/**
+ * None for the agent's summary of its turn
+ */
+attempt_template: string | null, 
+/**
+ * None for the task's title and short ID, then its description
+ */
+merge_template: string | null, conventional_commits: ConventionalCommitSettings, 
+/**
+ * Name of the trailer carrying the task's ID; None to leave it out
+ */
+task_trailer: string | null, };
+
+export type ConventionalCommitSettings = { enabled: boolean, 
+/**
+ * Types a subject may start with; empty for any
+ */
+types: Array<string>, fallback_type: string, };
+
+export type PromptSettings = { prefix: string | null, suffix: string | null, previous_attempt: PreviousAttemptContext, overrides: Array<ExecutorPromptOverride>, };
+
+export type PreviousAttemptContext = { enabled: boolean, 
+/**
+ * The last summary the previous attempt's agent gave
+ */
+summary: boolean, 
+/**
+ * Comments on the previous attempt's pull request
+ */
+review_comments: boolean, 
+/**
+ * The failing tests of the previous attempt's last test run
+ */
+failing_tests: boolean, };
+
+export type ExecutorPromptOverride = { executor: BaseCodingAgent, prefix: string | null, suffix: string | null, 
+/**
+ * Whether the executor's runs get the previous attempt's context, None
+ * to follow the project
+ */
+previous_attempt: boolean | null, };
+
+export type CommandPolicy = { enabled: boolean, allow: Array<string>, deny: Array<string>, };
+
+export type CommandDecision = { id: string, workspace_id: string, execution_process_id: string, tool_call_id: string, command: string, status: CommandDecisionStatus, decided_by: CommandDecider, 
+/**
+ * Policy patterns the decision was based on
+ */
+rule: string | null, 
+/**
+ * Why the command was denied
+ */
+reason: string | null, created_at: string, };
+
+export type CommandDecisionStatus = "approved" | "denied" | "timed_out";
+
+export type CommandDecider = "policy" | "user";
+
+export type BudgetLimit = "time" | "tokens" | "cost" | "tool_calls";
+
+export type BudgetUsage = { 
+/**
+ * Time the coding agents ran, counting running ones up to now
+ */
+agent_seconds: bigint, 
+/**
+ * Input and output tokens; cached input isn't counted
+ */
+tokens: bigint, cost_usd: number, tool_calls: bigint, };
+
+export type AttemptBudget = { 
+/**
+ * The project's limits, times the extensions granted
+ */
+limits: AttemptLimits, used: BudgetUsage, extensions: number, 
+/**
+ * Limit that stopped the attempt's coding agent since the last extension
+ */
+stopped_by: BudgetLimit | null, 
+/**
+ * Limit the attempt is at; new runs are refused until it is extended
+ */
+reached: BudgetLimit | null, };
+
 export type WorkspaceRepo = { id: string, workspace_id: string, repo_id: string, target_branch: string, created_at: Date, updated_at: Date, };
 
-export type CreateWorkspaceRepo = { repo_id: string, target_branch: string, };
+export type CreateWorkspaceRepo = { repo_id: string, target_branch: string, };
+
+export type RepoWithTargetBranch = { target_branch: string, id: string, path: string, name: string, display_name: string, created_at: Date, updated_at: Date, };
+
+export type WorkspaceUpload = { id: string, workspace_id: string, path: string, size_bytes: bigint, hash: string, uploaded_by: string | null, created_at: string, };
+
+export type WorkspaceArtifact = { id: string, workspace_id: string, 
+/**
+ * Run that last wrote the file
+ */
+execution_process_id: string, 
+/**
+ * Path below the artifacts directory, using forward slashes
+ */
+name: string, size_bytes: bigint, 
+/**
+ * SHA256 of the contents
+ */
+hash: string, created_at: string, updated_at: string, };
+
+export type WorkspaceDiskUsage = { workspace_id: string, size_bytes: bigint, measured_at: string, };
+
+export type BranchSyncStatus = { workspace_id: string, repo_id: string, target_branch: string, 
+/**
+ * Commits of the target branch that the attempt's branch doesn't have
+ */
+commits_behind: bigint, 
+/**
+ * Files that conflict between the two, empty when they merge cleanly
+ */
+conflicted_files: Array<string>, 
+/**
+ * How the check brought the branch up to date, if it did
+ */
+action: BranchSyncAction | null, 
+/**
+ * Why the branch couldn't be checked or brought up to date
+ */
+error: string | null, checked_at: string, };
+
+export type BranchSyncAction = "rebased" | "merged";
+
+export type CiCheck = { id: string, 
+/**
+ * The pull request's merge record
+ */
+merge_id: string, workspace_id: string, repo_id: string, 
+/**
+ * The pull request's head commit the check ran on
+ */
+head_commit: string, name: string, state: CiCheckState, url: string | null, updated_at: string, };
+
+export type CiCheckState = "pending" | "passed" | "failed" | "skipped";
+
+export type Tag = { id: string, tag_name: string, content: string, created_at: string, updated_at: string, };
+
+export type CreateTag = { tag_name: string, content: string, };
+
+export type UpdateTag = { tag_name: string | null, content: string | null, };
+
+export type Label = { id: string, project_id: string, name: string, 
+/**
+ * Hex color such as `#1f883d`
+ */
+color: string, created_at: string, updated_at: string, };
+
+export type CreateLabel = { name: string, color: string, };
+
+export type UpdateLabel = { name: string | null, color: string | null, };
+
+export type Epic = { id: string, project_id: string, name: string, description: string | null, 
+/**
+ * When the epic's tasks should all be done
+ */
+target_date: string | null, created_at: string, updated_at: string, };
+
+export type CreateEpic = { name: string, description: string | null, target_date: string | null, };
+
+export type UpdateEpic = { name: string, description: string | null, target_date: string | null, };
+
+export type SetTaskEpicRequest = { 
+/**
+ * None takes the task out of its epic
+ */
+epic_id: string | null, };
+
+export type BoardColumn = { id: string, project_id: string, name: string, 
+/**
+ * Place on the board, leftmost first
+ */
+position: bigint, 
+/**
+ * Status of the tasks in the column
+ */
+status: TaskStatus, created_at: string, updated_at: string, };
+
+export type CreateBoardColumn = { name: string, status: TaskStatus, };
+
+export type UpdateBoardColumn = { name: string | null, 
+/**
+ * Changing it also changes the status of the column's tasks
+ */
+status: TaskStatus | null, };
+
+export type ReorderBoardColumns = { 
+/**
+ * Every column of the project, left to right
+ */
+column_ids: Array<string>, };
+
+export type ColumnWipStatus = { column_id: string, task_count: bigint, 
+/**
+ * None when the column takes any number of tasks
+ */
+max_tasks: number | null, };
+
+export type BoardWipStatus = { enforcement: WipEnforcement, 
+/**
+ * Every column of the project, left to right
+ */
+columns: Array<ColumnWipStatus>, };
+
+export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
+
+export type TaskPriority = "urgent" | "high" | "normal" | "low";
+
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, parent_task_id: string | null, priority: TaskPriority, 
+/**
+ * Place within the board column of the task's status, lowest first
+ */
+position: bigint, 
+/**
+ * Set while the task is archived and left off the board
+ */
+archived_at: string | null, 
+/**
+ * When the task should be done by
+ */
+due_at: string | null, 
+/**
+ * Custom board column holding the task; none means the first column of
+ * its status
+ */
+column_id: string | null, 
+/**
+ * Epic the task is part of
+ */
+epic_id: string | null, 
+/**
+ * Executor profile for the task's attempts, in place of the configured
+ * default
+ */
+executor_profile_id: ExecutorProfileId | null, 
+/**
+ * Gitignore-style patterns of the paths attempts should change; empty
+ * for the whole repository
+ */
+scope_paths: Array<string>, shared_task_id: string | null, created_at: string, updated_at: string, };
+
+export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, 
+/**
+ * Whether a task this one depends on isn't done yet
+ */
+is_blocked: boolean, subtasks: SubtaskProgress, label_ids: Array<string>, 
+/**
+ * Tests the task's attempts ran last, for the red/green badge
+ */
+latest_test_run: TestRun | null, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, parent_task_id: string | null, priority: TaskPriority, 
+/**
+ * Place within the board column of the task's status, lowest first
+ */
+position: bigint, 
+/**
+ * Set while the task is archived and left off the board
+ */
+archived_at: string | null, 
+/**
+ * When the task should be done by
+ */
+due_at: string | null, 
+/**
+ * Custom board column holding the task; none means the first column of
+ * its status
+ */
+column_id: string | null, 
+/**
+ * Epic the task is part of
+ */
+epic_id: string | null, 
+/**
+ * Executor profile for the task's attempts, in place of the configured
+ * default
+ */
+executor_profile_id: ExecutorProfileId | null, 
+/**
+ * Gitignore-style patterns of the paths attempts should change; empty
+ * for the whole repository
+ */
+scope_paths: Array<string>, shared_task_id: string | null, created_at: string, updated_at: string, };
+
+export type SubtaskProgress = { total: number, done: number, };
+
+export type TaskRelationships = { parent_task: Task | null, current_workspace: Workspace, children: Array<Task>, };
+
+export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, 
+/**
+ * Create the task as a subtask of this task
+ */
+parent_task_id: string | null, priority: TaskPriority | null, image_ids: Array<string> | null, shared_task_id: string | null, due_at: string | null, };
+
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, priority: TaskPriority | null, image_ids: Array<string> | null, };
+
+export type TaskDependency = { task_id: string, depends_on_task_id: string, created_at: string, };
+
+export type TaskLinkType = "relates_to" | "duplicates" | "follows_up";
+
+export type TaskLink = { id: string, task_id: string, linked_task_id: string, link_type: TaskLinkType, created_at: string, };
+
+export type CreateTaskLink = { linked_task_id: string, link_type: TaskLinkType, };
+
+export type LinkedTask = { link_id: string, link_type: TaskLinkType, 
+/**
+ * Set when the link points at the viewed task, e.g. the other task
+ * follows up this one
+ */
+incoming: boolean, task: Task, };
+
+export type TaskWithLinks = { links: Array<LinkedTask>, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, parent_task_id: string | null, priority: TaskPriority, 
+/**
+ * Place within the board column of the task's status, lowest first
+ */
+position: bigint, 
+/**
+ * Set while the task is archived and left off the board
+ */
+archived_at: string | null, 
+/**
+ * When the task should be done by
+ */
+due_at: string | null, 
+/**
+ * Custom board column holding the task; none means the first column of
+ * its status
+ */
+column_id: string | null, 
+/**
+ * Epic the task is part of
+ */
+epic_id: string | null, 
+/**
+ * Executor profile for the task's attempts, in place of the configured
+ * default
+ */
+executor_profile_id: ExecutorProfileId | null, 
+/**
+ * Gitignore-style patterns of the paths attempts should change; empty
+ * for the whole repository
+ */
+scope_paths: Array<string>, shared_task_id: string | null, created_at: string, updated_at: string, };
+
+export type ScheduledAttempt = { executor_profile_id: ExecutorProfileId, repos: Array<CreateWorkspaceRepo>, };
+
+export type TaskSchedule = { id: string, project_id: string, title: string, description: string | null, 
+/**
+ * Five-field cron expression, evaluated in UTC
+ */
+cron_expression: string, 
+/**
+ * Attempt to start for each created task; without one the task is only created
+ */
+attempt: ScheduledAttempt | null, paused: boolean, next_run_at: string, last_run_at: string | null, created_at: string, updated_at: string, };
+
+export type CreateTaskSchedule = { title: string, description: string | null, cron_expression: string, attempt: ScheduledAttempt | null, };
+
+export type TaskScheduleRun = { id: string, schedule_id: string, 
+/**
+ * Time the run was due, which can be earlier than when it happened
+ */
+scheduled_for: string, task_id: string | null, workspace_id: string | null, error: string | null, created_at: string, };
+
+export type DraftFollowUpData = { message: string, variant: string | null, };
+
+export type ScratchPayload = { "type": "DRAFT_TASK", "data": string } | { "type": "DRAFT_FOLLOW_UP", "data": DraftFollowUpData };
+
+export enum ScratchType { DRAFT_TASK = "DRAFT_TASK", DRAFT_FOLLOW_UP = "DRAFT_FOLLOW_UP" }
+
+export type Scratch = { id: string, payload: ScratchPayload, created_at: string, updated_at: string, };
+
+export type CreateScratch = { payload: ScratchPayload, };
+
+export type UpdateScratch = { payload: ScratchPayload, };
+
+export type Image = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+
+export type CreateImage = { file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, };
+
+export type Workspace = { id: string, task_id: string, container_ref: string | null, branch: string, agent_working_dir: string | null, setup_completed_at: string | null, 
+/**
+ * Executor profile the attempt was started with
+ */
+executor_profile_id: ExecutorProfileId | null, created_at: string, updated_at: string, };
+
+export type Session = { id: string, workspace_id: string, executor: string | null, created_at: string, updated_at: string, };
+
+export type ExecutionProcess = { id: string, session_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, status: ExecutionProcessStatus, exit_code: bigint | null, 
+/**
+ * dropped: true if this process is excluded from the current
+ * history view (due to restore/trimming). Hidden from logs/timeline;
+ * still listed in the Processes tab.
+ */
+dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+
+export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
+
+export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
+
+export type ExecutionProcessRetry = { id: string, workspace_id: string, failed_process_id: string, retry_process_id: string, failure_kind: FailureKind, created_at: string, };
+
+export type QueuedAttempt = { workspace_id: string, task_id: string, project_id: string, task_title: string, priority: TaskPriority, executor_profile_id: ExecutorProfileId, enqueued_at: string, 
+/**
+ * Why the attempt failed to start when it last left the queue. It stays
+ * queued, without being started again, until it is stopped.
+ */
+start_error: string | null, };
+
+export type AttemptQueuePosition = { 
+/**
+ * 1 for the attempt that starts next
+ */
+position: number, queued: number, attempt: QueuedAttempt, };
+
+export type TestRun = { execution_process_id: string, workspace_id: string, runner: TestRunner, passed: bigint, failed: bigint, skipped: bigint, created_at: string, };
+
+export type LintSeverity = "error" | "warning" | "note";
+
+export type LintIssue = { 
+/**
+ * As the check printed it, usually relative to where it ran
+ */
+path: string, line: number | null, column: number | null, severity: LintSeverity, message: string, 
+/**
+ * The lint or error code, e.g. `clippy::needless_return`
+ */
+rule: string | null, };
+
+export type LintCheckStatus = "passed" | "failed" | "timed_out" | "not_run";
+
+export type LintCheckResult = { name: string, command: string, status: LintCheckStatus, exit_code: number | null, 
+/**
+ * The problems read from the output, for the checks whose output format
+ * is recognized
+ */
+issues: Array<LintIssue>, 
+/**
+ * The end of the output, or why the check didn't run
+ */
+output: string, };
+
+export type LintRun = { 
+/**
+ * The process whose end started the checks
+ */
+execution_process_id: string, workspace_id: string, checks: Array<LintCheckResult>, passed: boolean, 
+/**
+ * The follow-up run asked to fix what failed
+ */
+fix_execution_process_id: string | null, created_at: string, };
+
+export type AgentReviewStatus = "running" | "approved" | "changes_requested" | "failed";
+
+export type AgentReviewComment = { path: string | null, line: number | null, body: string, 
+/**
+ * Whether the change has to be made before the attempt is done, rather
+ * than being a suggestion
+ */
+blocking: boolean, };
+
+export type AgentReview = { id: string, workspace_id: string, 
+/**
+ * The run of the attempt's agent, or the cleanup script after it, whose
+ * end started the review
+ */
+reviewed_execution_process_id: string, 
+/**
+ * The reviewer's run
+ */
+review_execution_process_id: string, status: AgentReviewStatus, summary: string | null, comments: Array<AgentReviewComment>, 
+/**
+ * The follow-up run of the attempt's agent handed the comments
+ */
+revision_execution_process_id: string | null, created_at: string, updated_at: string, };
+
+export type HookEvent = "before_start" | "after_finish" | "after_merge";
+
+export type HookRunStatus = "succeeded" | "failed" | "timed_out";
+
+export type HookRun = { id: string, workspace_id: string, hook_name: string, event: HookEvent, status: HookRunStatus, 
+/**
+ * The end of a command's output, or a webhook's response status and
+ * body; why the hook didn't run if it couldn't be started
+ */
+output: string, duration_ms: bigint, created_at: string, };
+
+export type HookPayload = { event: HookEvent, project_id: string, project_name: string, task_id: string, task_title: string, workspace_id: string, branch: string, } & HookDetails;
+
+export type HookDetails = { 
+/**
+ * How the attempt's last run ended; `after_finish` only
+ */
+outcome: ExecutionProcessStatus | null, 
+/**
+ * `after_merge` only
+ */
+merge_commit: string | null, 
+/**
+ * The pull request that was merged, if it was merged through one
+ */
+pr_url: string | null, };
+
+export type DryRunStatus = "running" | "ready" | "failed" | "approved" | "discarded";
+
+export type DryRun = { id: string, workspace_id: string, execution_process_id: string, 
+/**
+ * What the agent was asked to plan
+ */
+prompt: string, status: DryRunStatus, 
+/**
+ * The agent's last message
+ */
+plan: string | null, 
+/**
+ * What the agent changed in the copy, as a diff against the workspace
+ */
+patch: string | null, 
+/**
+ * The real run started when the plan was approved
+ */
+approved_execution_process_id: string | null, created_at: string, updated_at: string, };
+
+export type StartDryRunRequest = { 
+/**
+ * What to plan; the task itself when omitted
+ */
+prompt: string | null, 
+/**
+ * The profile the attempt was started with when omitted
+ */
+executor_profile_id: ExecutorProfileId | null, };
+
+export type PathGuardBlock = { id: string, workspace_id: string, execution_process_id: string, 
+/**
+ * The protected paths changed, prefixed with their repository
+ */
+paths: Array<string>, 
+/**
+ * None while the attempt waits for the override
+ */
+overridden_at: string | null, created_at: string, };
+
+export type SelectedFile = { path: string, 
+/**
+ * Indexes of the file's hunks to take, None for the whole file
+ */
+hunks: Array<number> | null, };
+
+export type MergeSelection = { id: string, workspace_id: string, repo_id: string, 
+/**
+ * The diff the selection was made from, between these commits
+ */
+base_commit: string, head_commit: string, files: Array<SelectedFile>, 
+/**
+ * The merge or pull request the selection went into, once it's used
+ */
+merge_id: string | null, created_at: string, };
+
+export type ExecutionProcessInterruption = { execution_process_id: string, workspace_id: string, 
+/**
+ * Run that picked the work up again, once the attempt is resumed
+ */
+resumed_process_id: string | null, 
+/**
+ * When the restarted server found the run cut off
+ */
+interrupted_at: string, };
+
+export type ExecutionProcessRepoState = { id: string, execution_process_id: string, repo_id: string, before_head_commit: string | null, after_head_commit: string | null, merge_commit: string | null, created_at: Date, updated_at: Date, };
+
+export type RecordedEvent = { 
+/**
+ * Milliseconds from the start of the run
+ */
+at_ms: bigint, 
+/**
+ * JSON patch to the conversation, as sent by the normalized logs stream
+ */
+patch: JsonValue, };
+
+export type ExecutionProcessRecording = { execution_process_id: string, events: Array<RecordedEvent>, duration_ms: bigint, created_at: Date, };
+
+export type MergeStrategy = "merge_commit" | "squash" | "rebase" | "fast_forward";
+
+export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
+
+export type DirectMerge = { id: string, workspace_id: string, repo_id: string, merge_commit: string, target_branch_name: string, merge_strategy: MergeStrategy | null, created_at: string, };
+
+export type PrMerge = { id: string, workspace_id: string, repo_id: string, created_at: string, target_branch_name: string, pr_info: PullRequestInfo, 
+/**
+ * None unless vibe-kanban merged the pull request
+ */
+merge_strategy: MergeStrategy | null, };
+
+export type MergeStatus = "open" | "merged" | "closed" | "unknown";
+
+export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, };
+
+export type ApprovalStatus = { "status": "pending" } | { "status": "approved" } | { "status": "denied", reason?: string, } | { "status": "timed_out" };
+
+export type CreateApprovalRequest = { tool_name: string, tool_input: JsonValue, tool_call_id: string, };
+
+export type ApprovalResponse = { execution_process_id: string, status: ApprovalStatus, };
+
+export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, 
+/**
+ * True when file contents are intentionally omitted (e.g., too large)
+ */
+contentOmitted: boolean, 
+/**
+ * Optional precomputed stats for omitted content
+ */
+additions: number | null, deletions: number | null, };
+
+export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
+
+export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
+
+export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse, };
+
+export type ProfileResponse = { user_id: string, username: string | null, email: string, providers: Array<ProviderProfile>, };
+
+export type ProviderProfile = { provider: string, username: string | null, display_name: string | null, email: string | null, avatar_url: string | null, };
+
+export type StatusResponse = { logged_in: boolean, profile: ProfileResponse | null, degraded: boolean | null, };
+
+export enum MemberRole { ADMIN = "ADMIN", MEMBER = "MEMBER" }
+
+export enum InvitationStatus { PENDING = "PENDING", ACCEPTED = "ACCEPTED", DECLINED = "DECLINED", EXPIRED = "EXPIRED" }
+
+export type Organization = { id: string, name: string, slug: string, is_personal: boolean, created_at: string, updated_at: string, };
+
+export type OrganizationWithRole = { id: string, name: string, slug: string, is_personal: boolean, created_at: string, updated_at: string, user_role: MemberRole, };
+
+export type ListOrganizationsResponse = { organizations: Array<OrganizationWithRole>, };
+
+export type GetOrganizationResponse = { organization: Organization, user_role: string, };
+
+export type CreateOrganizationRequest = { name: string, slug: string, };
+
+export type CreateOrganizationResponse = { organization: OrganizationWithRole, };
+
+export type UpdateOrganizationRequest = { name: string, };
+
+export type Invitation = { id: string, organization_id: string, invited_by_user_id: string | null, email: string, role: MemberRole, status: InvitationStatus, token: string, created_at: string, expires_at: string, };
+
+export type CreateInvitationRequest = { email: string, role: MemberRole, };
+
+export type CreateInvitationResponse = { invitation: Invitation, };
+
+export type ListInvitationsResponse = { invitations: Array<Invitation>, };
+
+export type GetInvitationResponse = { id: string, organization_slug: string, role: MemberRole, expires_at: string, };
+
+export type AcceptInvitationResponse = { organization_id: string, organization_slug: string, role: MemberRole, };
+
+export type RevokeInvitationRequest = { invitation_id: string, };
+
+export type OrganizationMember = { user_id: string, role: MemberRole, joined_at: string, };
+
+export type OrganizationMemberWithProfile = { user_id: string, role: MemberRole, joined_at: string, first_name: string | null, last_name: string | null, username: string | null, email: string | null, avatar_url: string | null, };
+
+export type ListMembersResponse = { members: Array<OrganizationMemberWithProfile>, };
+
+export type UpdateMemberRoleRequest = { role: MemberRole, };
+
+export type UpdateMemberRoleResponse = { user_id: string, role: MemberRole, };
+
+export type RemoteProject = { id: string, organization_id: string, name: string, metadata: Record<string, unknown>, created_at: string, };
+
+export type ListProjectsResponse = { projects: Array<RemoteProject>, };
+
+export type RemoteProjectMembersResponse = { organization_id: string, members: Array<OrganizationMemberWithProfile>, };
+
+export type CreateRemoteProjectRequest = { organization_id: string, name: string, };
+
+export type LinkToExistingRequest = { remote_project_id: string, };
+
+export type UploadQuota = { used_bytes: bigint, max_total_bytes: bigint | null, max_file_bytes: bigint | null, remaining_bytes: bigint | null, };
+
+export type RegisterRepoRequest = { path: string, display_name: string | null, };
+
+export type InitRepoRequest = { parent_path: string, folder_name: string, };
+
+export type TagSearchParams = { search: string | null, };
+
+export type SearchParams = { q: string, project_id: string | null, status: TaskStatus | null, 
+/**
+ * Only match tasks and agent turns created at or after this time
+ */
+from: string | null, 
+/**
+ * Only match tasks and agent turns created at or before this time
+ */
+to: string | null, limit: number | null, };
+
+export type SearchHitKind = "task" | "agent_turn";
+
+export type SnippetPart = { text: string, highlighted: boolean, };
+
+export type SearchHit = { kind: SearchHitKind, task_id: string, task_title: string, project_id: string, status: TaskStatus, 
+/**
+ * Attempt the agent turn belongs to
+ */
+workspace_id: string | null, snippet: Array<SnippetPart>, 
+/**
+ * When the task or agent turn was created
+ */
+created_at: string, };
+
+export type TokenResponse = { access_token: string, expires_at: string | null, };
+
+export type UserSystemInfo = { config: Config, analytics_user_id: string, login_status: LoginStatus, environment: Environment, 
+/**
+ * Capabilities supported per executor (e.g., { "CLAUDE_CODE": ["SESSION_FORK"] })
+ */
+capabilities: { [key in string]?: Array<BaseAgentCapability> }, executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
+
+export type Environment = { os_type: string, os_version: string, os_architecture: string, bitness: string, };
+
+export type McpServerQuery = { executor: BaseCodingAgent, };
+
+export type UpdateMcpServersBody = { servers: { [key in string]?: JsonValue }, };
+
+export type GetMcpServerResponse = { mcp_config: McpConfig, config_path: string, };
+
+export type CheckEditorAvailabilityQuery = { editor_type: EditorType, };
+
+export type CheckEditorAvailabilityResponse = { available: boolean, };
+
+export type CheckAgentAvailabilityQuery = { executor: BaseCodingAgent, };
+
+export type CurrentUserResponse = { user_id: string, };
+
+export type CreateFollowUpAttempt = { prompt: string, variant: string | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
+
+export type ChangeTargetBranchRequest = { repo_id: string, new_target_branch: string, };
+
+export type ChangeTargetBranchResponse = { repo_id: string, new_target_branch: string, status: [number, number], };
+
+export type MergeTaskAttemptRequest = { repo_id: string, 
+/**
+ * Merge only the files and hunks of this selection, as a single commit,
+ * leaving the rest of the changes on the attempt's branch
+ */
+selection_id: string | null, };
+
+export type PushTaskAttemptRequest = { repo_id: string, };
+
+export type RenameBranchRequest = { new_branch_name: string, };
+
+export type RenameBranchResponse = { branch: string, };
+
+export type OpenEditorRequest = { editor_type: string | null, file_path: string | null, };
+
+export type OpenEditorResponse = { url: string | null, };
+
+export type PreviewPort = { port: number, 
+/**
+ * Path of the preview proxy for this port
+ */
+url: string, };
+
+export type ResourceUsage = { memory_bytes: bigint, 
+/**
+ * CPU time used so far; sample twice for a rate
+ */
+cpu_usec: bigint, processes: number, 
+/**
+ * Processes killed for running out of memory, where that is tracked
+ */
+oom_kills: bigint, };
+
+export type LogLevel = "debug" | "info" | "error";
+
+export type LogSearchQuery = { 
+/**
+ * Text the entry contains, ignoring case. Tool names and command output
+ * are searched too.
+ */
+q: string | null, 
+/**
+ * Least level of the entries
+ */
+level: LogLevel | null, 
+/**
+ * Tool calls of this action, such as `command_run` or `file_edit`, or of
+ * the tool with this name
+ */
+tool: string | null, since: string | null, until: string | null, offset: number, 
+/**
+ * Entries per page, 100 unless given and at most 1000
+ */
+limit: number | null, };
+
+export type LogSearchEntry = { 
+/**
+ * Position of the entry in the conversation
+ */
+index: number, level: LogLevel, entry: NormalizedEntry, };
+
+export type LogSearchPage = { entries: Array<LogSearchEntry>, 
+/**
+ * Entries matching the query across all pages
+ */
+total: number, offset: number, limit: number, };
+
+export type ReplayQuery = { 
+/**
+ * How many times faster than the run, 1 unless given
+ */
+speed: number | null, 
+/**
+ * Longest pause between two patches in milliseconds, 5000 unless given,
+ * so the replay doesn't stall while the agent was waiting
+ */
+max_gap_ms: number | null, 
+/**
+ * Milliseconds into the run to start at; what came before is sent at
+ * once
+ */
+from_ms: number | null, };
+
+
+export type AttemptMetrics = { limits: ResourceLimits, 
+/**
+ * Absent when usage can't be measured for the project's backend
+ */
+usage: ResourceUsage | null, };
+
+export type ScopeCheck = { scope_paths: Array<string>, files_changed: number, 
+/**
+ * Changed paths outside the scope, prefixed with the repository name
+ */
+out_of_scope: Array<string>, };
+
+export type AssignSharedTaskRequest = { new_assignee_user_id: string | null, };
+
+export type ShareTaskResponse = { shared_task_id: string, };
+
+export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
+
+export type FindDuplicatesRequest = { project_id: string, title: string, description: string | null, 
+/**
+ * Leave this task out, when checking a task that already exists
+ */
+exclude_task_id: string | null, };
+
+export type DuplicateCandidate = { task: Task, 
+/**
+ * From 0 to 1, 1 for the same words
+ */
+similarity: number, };
+
+export type TaskDependencies = { 
+/**
+ * Tasks that have to be done before this one can start
+ */
+blocked_by: Array<Task>, 
+/**
+ * Tasks waiting for this one
+ */
+blocking: Array<Task>, };
+
+export type TaskDependencyRequest = { depends_on_task_id: string, };
+
+export type SetTaskLabelsRequest = { label_ids: Array<string>, };
+
+export type BulkUpdateTasksRequest = { task_ids: Array<string>, status: TaskStatus | null, priority: TaskPriority | null, 
+/**
+ * Labels added to every task
+ */
+add_label_ids: Array<string>, 
+/**
+ * Labels removed from every task
+ */
+remove_label_ids: Array<string>, 
+/**
+ * Archive the tasks, or bring archived tasks back to the board
+ */
+archived: boolean | null, };
+
+export type BulkCreateTasksRequest = { tasks: Array<CreateTask>, };
+
+export type ArchivedTaskPage = { 
+/**
+ * Most recently archived first
+ */
+tasks: Array<Task>, 
+/**
+ * Archived tasks matching the search, across all pages
+ */
+total: bigint, };
+
+export type TaskSort = "position" | "due_at";
+
+export type DueTasks = { 
+/**
+ * Most overdue first
+ */
+overdue: Array<Task>, 
+/**
+ * Tasks due within the requested window, soonest first
+ */
+due_soon: Array<Task>, };
+
+export type SetTaskDueDateRequest = { 
+/**
+ * None clears the deadline
+ */
+due_at: string | null, };
+
+export type SetTaskExecutorProfileRequest = { 
+/**
+ * None goes back to the configured default
+ */
+executor_profile_id: ExecutorProfileId | null, };
+
+export type SetTaskScopeRequest = { 
+/**
+ * Gitignore-style patterns; an empty list puts the whole repository back
+ * in scope
+ */
+paths: Array<string>, };
+
+export type MoveTaskRequest = { 
+/**
+ * Status to move the task to; defaults to its current status
+ */
+status: TaskStatus | null, 
+/**
+ * Custom column to move the task to, which also sets its status
+ */
+column_id: string | null, 
+/**
+ * Place within the column, 0 being the top
+ */
+index: number, };
+
+export type TaskOrder = { status: TaskStatus, 
+/**
+ * None for the first column of the status
+ */
+column_id: string | null, 
+/**
+ * Tasks of the column from top to bottom
+ */
+task_ids: Array<string>, };
+
+export type Subtasks = { progress: SubtaskProgress, subtasks: Array<Task>, };
+
+export type StartSubtasksRequest = { 
+/**
+ * Profile for every started subtask; without one each subtask uses its
+ * own or the configured default
+ */
+executor_profile_id: ExecutorProfileId | null, repos: Array<WorkspaceRepoInput>, };
+
+export type SkippedSubtask = { task_id: string, reason: string, };
+
+export type StartSubtasksResponse = { 
+/**
+ * Attempts created, one per started subtask
+ */
+started: Array<Workspace>, 
+/**
+ * Subtasks to do that couldn't be started, e.g. because they are blocked
+ */
+skipped: Array<SkippedSubtask>, };
+
+export type CreateGitHubPrRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, 
+/**
+ * Open the pull request with only the files and hunks of this selection,
+ * from a branch of their own, leaving the rest on the attempt's branch
+ */
+selection_id: string | null, };
+
+export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+
+export type ImageMetadata = { exists: boolean, file_name: string | null, path: string | null, size_bytes: bigint | null, format: string | null, proxy_url: string | null, };
+
+export type PastedImageResponse = { image: ImageResponse, 
+/**
+ * Markdown image link relative to the worktree root, ready to insert into a description
+ */
+markdown: string, thumbnail_url: string, };
+
+export type CreateTaskAttemptBody = { task_id: string, 
+/**
+ * Without one the task's own profile is used, or else the configured
+ * default
+ */
+executor_profile_id: ExecutorProfileId | null, repos: Array<WorkspaceRepoInput>, 
+/**
+ * Attempt whose branch this one is based on, in place of the repos'
+ * target branches
+ */
+stacked_on_workspace_id: string | null, };
+
+export type StackedAttempt = { workspace_id: string, task_id: string, task_title: string, branch: string, };
+
+export type AttemptStack = { 
+/**
+ * The attempts this one is stacked on, the bottom of the stack first
+ */
+ancestors: Array<StackedAttempt>, 
+/**
+ * The attempts stacked directly on this one
+ */
+children: Array<StackedAttempt>, };
+
+export type AttemptGroup = { id: string, task_id: string, created_at: string, };
 
-export type RepoWithTargetBranch = { target_branch: string, id: string, path: string, name: string, display_name: string, created_at: Date, updated_at: Date, };
+export type AttemptGroupMember = { workspace_id: string, executor_profile_id: ExecutorProfileId | null, 
+/**
+ * None until the coding agent has started
+ */
+status: ExecutionProcessStatus | null, completed_at: string | null, };
 
-export type Tag = { id: string, tag_name: string, content: string, created_at: string, updated_at: string, };
+export type FanOutAttemptsRequest = { 
+/**
+ * One attempt is started per profile; a profile can be listed twice
+ */
+executor_profile_ids: Array<ExecutorProfileId>, repos: Array<WorkspaceRepoInput>, 
+/**
+ * Attempts set up at the same time, 2 if not given
+ */
+max_concurrent: number | null, };
 
-export type CreateTag = { tag_name: string, content: string, };
+export type AttemptGroupStatus = { group: AttemptGroup, attempts: Array<AttemptGroupMember>, 
+/**
+ * Attempts whose agent hasn't started yet
+ */
+pending: number, running: number, completed: number, 
+/**
+ * Failed or stopped
+ */
+failed: number, 
+/**
+ * Every agent of the group has stopped
+ */
+finished: boolean, 
+/**
+ * Attempt whose agent completed first
+ */
+first_completed: string | null, };
 
-export type UpdateTag = { tag_name: string | null, content: string | null, };
+export type TaskChecklistItem = { id: string, task_id: string, label: string, checked: boolean, position: bigint, checked_at: string | null, created_at: string, };
 
-export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
+export type CreateTaskChecklistItem = { label: string, };
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
+export type TaskRevisionAuthor = "user" | "agent";
 
-export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, shared_task_id: string | null, created_at: string, updated_at: string, };
+export type TaskRevision = { id: string, task_id: string, title: string, description: string | null, 
+/**
+ * None for the version the task had before its first recorded edit
+ */
+author: TaskRevisionAuthor | null, 
+/**
+ * Signed-in user who made the edit
+ */
+author_name: string | null, created_at: string, };
 
-export type TaskRelationships = { parent_task: Task | null, current_workspace: Workspace, children: Array<Task>, };
+export type TaskRevisionEntry = { 
+/**
+ * Title before this revision, when the revision changed it
+ */
+previous_title: string | null, 
+/**
+ * Unified diff of the description against the revision before, when it
+ * changed
+ */
+description_diff: string | null, id: string, task_id: string, title: string, description: string | null, 
+/**
+ * None for the version the task had before its first recorded edit
+ */
+author: TaskRevisionAuthor | null, 
+/**
+ * Signed-in user who made the edit
+ */
+author_name: string | null, created_at: string, };
 
-export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, shared_task_id: string | null, };
+export type Activity = { "type": "task_created", status: TaskStatus, } | { "type": "task_moved", from: TaskStatus, to: TaskStatus, } | { "type": "attempt_started", branch: string, } | { "type": "attempt_finished", status: ExecutionProcessStatus, executor: string | null, } | { "type": "pr_opened", pr_number: bigint, pr_url: string, };
 
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, };
+export type LlmProxyRequest = { id: string, project_id: string, 
+/**
+ * None once the workspace is removed
+ */
+workspace_id: string | null, 
+/**
+ * The provider's name in the project settings
+ */
+provider: string, method: string, path: string, model: string | null, 
+/**
+ * None if the provider couldn't be reached
+ */
+status_code: bigint | null, input_tokens: bigint, output_tokens: bigint, cost_usd: number, duration_ms: bigint, error: string | null, 
+/**
+ * Only kept when the project logs bodies
+ */
+request_body: string | null, response_body: string | null, created_at: string, };
 
-export type DraftFollowUpData = { message: string, variant: string | null, };
+export type LlmProxyLog = { month_spend_usd: number, monthly_spend_cap_usd: number | null, 
+/**
+ * Newest first
+ */
+requests: Array<LlmProxyRequest>, };
 
-export type ScratchPayload = { "type": "DRAFT_TASK", "data": string } | { "type": "DRAFT_FOLLOW_UP", "data": DraftFollowUpData };
+export type ActivityEvent = { id: string, project_id: string, task_id: string | null, workspace_id: string | null, 
+/**
+ * Current title of the task
+ */
+task_title: string | null, activity: Activity, created_at: string, };
 
-export enum ScratchType { DRAFT_TASK = "DRAFT_TASK", DRAFT_FOLLOW_UP = "DRAFT_FOLLOW_UP" }
+export type ActivityPage = { 
+/**
+ * Newest first
+ */
+events: Array<ActivityEvent>, 
+/**
+ * Pass as `before` to get the next page; none on the last page
+ */
+next_before: string | null, };
 
-export type Scratch = { id: string, payload: ScratchPayload, created_at: string, updated_at: string, };
+export type WorkspaceRepoInput = { repo_id: string, target_branch: string, };
 
-export type CreateScratch = { payload: ScratchPayload, };
+export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId, };
 
-export type UpdateScratch = { payload: ScratchPayload, };
+export type RunAgentSetupResponse = Record<string, never>;
 
-export type Image = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+export type InterjectRequest = { message: string, };
 
-export type CreateImage = { file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, };
+export type GhCliSetupError = "BREW_MISSING" | "SETUP_HELPER_NOT_SUPPORTED" | { "OTHER": { message: string, } };
 
-export type Workspace = { id: string, task_id: string, container_ref: string | null, branch: string, agent_working_dir: string | null, setup_completed_at: string | null, created_at: string, updated_at: string, };
+export type RebaseTaskAttemptRequest = { repo_id: string, old_base_branch: string | null, new_base_branch: string | null, };
 
-export type Session = { id: string, workspace_id: string, executor: string | null, created_at: string, updated_at: string, };
+export type AbortConflictsRequest = { repo_id: string, 
+/**
+ * Target branch of a cherry-pick of the attempt's commits to abort, in
+ * place of the attempt's own operation
+ */
+cherry_pick_target: string | null, };
 
-export type ExecutionProcess = { id: string, session_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, status: ExecutionProcessStatus, exit_code: bigint | null, 
+export type ConflictsQuery = { repo_id: string, 
 /**
- * dropped: true if this process is excluded from the current
- * history view (due to restore/trimming). Hidden from logs/timeline;
- * still listed in the Processes tab.
+ * Target branch of a cherry-pick of the attempt's commits, for its
+ * conflicts rather than the attempt's own
  */
-dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+cherry_pick_target: string | null, };
 
-export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
+export type ResolveConflictRequest = { repo_id: string, path: string, resolution: ConflictResolution, cherry_pick_target: string | null, };
 
-export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
+export type ContinueRebaseRequest = { repo_id: string, cherry_pick_target: string | null, };
 
-export type ExecutionProcessRepoState = { id: string, execution_process_id: string, repo_id: string, before_head_commit: string | null, after_head_commit: string | null, merge_commit: string | null, created_at: Date, updated_at: Date, };
+export type ResolveConflictsWithAgentRequest = { repo_id: string, };
 
-export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
+export type CherryPickRequest = { repo_id: string, 
+/**
+ * Local branch the attempt's commits are picked onto
+ */
+target_branch: string, };
 
-export type DirectMerge = { id: string, workspace_id: string, repo_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
+export type SelectableChangesQuery = { repo_id: string, };
 
-export type PrMerge = { id: string, workspace_id: string, repo_id: string, created_at: string, target_branch_name: string, pr_info: PullRequestInfo, };
+export type SelectableChanges = { 
+/**
+ * Where the attempt's branch leaves its target branch
+ */
+base_commit: string, 
+/**
+ * The attempt branch's tip
+ */
+head_commit: string, files: Array<SelectableFile>, };
 
-export type MergeStatus = "open" | "merged" | "closed" | "unknown";
+export type CreateMergeSelectionRequest = { repo_id: string, 
+/**
+ * The branch tip the files and hunks were picked at, from the
+ * selectable changes
+ */
+head_commit: string, files: Array<SelectedFile>, };
 
-export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, };
+export type GitOperationError = { "type": "merge_conflicts", message: string, op: ConflictOp, } | { "type": "rebase_in_progress" };
 
-export type ApprovalStatus = { "status": "pending" } | { "status": "approved" } | { "status": "denied", reason?: string, } | { "status": "timed_out" };
+export type PushError = { "type": "force_push_required" };
 
-export type CreateApprovalRequest = { tool_name: string, tool_input: JsonValue, tool_call_id: string, };
+export type CreatePrError = { "type": "github_cli_not_installed" } | { "type": "github_cli_not_logged_in" } | { "type": "git_cli_not_logged_in" } | { "type": "git_cli_not_installed" } | { "type": "target_branch_not_found", branch: string, } | { "type": "gitlab_not_logged_in" } | { "type": "bitbucket_not_logged_in" } | { "type": "azure_devops_not_logged_in" };
 
-export type ApprovalResponse = { execution_process_id: string, status: ApprovalStatus, };
+export type BranchStatus = { commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
+/**
+ * True if a `git rebase` is currently in progress in this worktree
+ */
+is_rebase_in_progress: boolean, 
+/**
+ * Current conflict operation if any
+ */
+conflict_op: ConflictOp | null, 
+/**
+ * List of files currently in conflicted (unmerged) state
+ */
+conflicted_files: Array<string>, 
+/**
+ * Signature check of the branch's latest commit
+ */
+head_signature: SignatureStatus | null, 
+/**
+ * Commits on the branch but not the target that aren't signed
+ */
+unsigned_commits: number | null, };
 
-export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, 
+export type RunScriptError = { "type": "no_script_configured" } | { "type": "process_already_running" };
+
+export type RetrySetupRequest = { 
 /**
- * True when file contents are intentionally omitted (e.g., too large)
+ * Agent to start once the scripts succeed, if it hasn't been started yet
  */
-contentOmitted: boolean, 
+executor_profile_id: ExecutorProfileId | null, };
+
+export type StartAgentAnywayRequest = { executor_profile_id: ExecutorProfileId, };
+
+export type AttachPrResponse = { pr_attached: boolean, pr_url: string | null, pr_number: bigint | null, pr_status: MergeStatus | null, };
+
+export type AttachExistingPrRequest = { repo_id: string, };
+
+export type MergePrRequest = { repo_id: string, };
+
+export type PrCommentsResponse = { comments: Array<UnifiedPrComment>, };
+
+export type GetPrCommentsError = { "type": "no_pr_attached" } | { "type": "github_cli_not_installed" } | { "type": "github_cli_not_logged_in" } | { "type": "gitlab_not_logged_in" } | { "type": "bitbucket_not_logged_in" } | { "type": "azure_devops_not_logged_in" };
+
+export type GetPrCommentsQuery = { repo_id: string, };
+
+export type ReviewCommentTask = { file_path: string, line_number: number, text: string, 
 /**
- * Optional precomputed stats for omitted content
+ * The commented line as shown in the diff
  */
-additions: number | null, deletions: number | null, };
+code_line: string | null, };
 
-export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
+export type CreateReviewTasksRequest = { 
+/**
+ * The comments to turn into tasks, one task each
+ */
+comments: Array<ReviewCommentTask>, };
 
-export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
+export type UnifiedPrComment = { "comment_type": "general", id: string, author: string, author_association: string, body: string, created_at: string, url: string, } | { "comment_type": "review", id: bigint, author: string, author_association: string, body: string, created_at: string, url: string, path: string, line: bigint | null, diff_hunk: string, };
 
-export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse, };
+export type GitHubIssueLink = { id: string, task_id: string, project_id: string, repo_owner: string, repo_name: string, issue_number: bigint, issue_url: string, 
+/**
+ * Close the issue once the task is done
+ */
+sync_status: boolean, 
+/**
+ * When the issue was closed from here
+ */
+closed_at: string | null, created_at: string, };
 
-export type ProfileResponse = { user_id: string, username: string | null, email: string, providers: Array<ProviderProfile>, };
+export type ImportGitHubIssues = { 
+/**
+ * Project repository whose GitHub remote the issues come from
+ */
+repo_id: string, 
+/**
+ * Only import issues carrying all of these labels
+ */
+labels: Array<string>, 
+/**
+ * Only import issues in this milestone
+ */
+milestone: string | null, 
+/**
+ * Close each issue on GitHub when its task is done
+ */
+sync_status: boolean, };
 
-export type ProviderProfile = { provider: string, username: string | null, display_name: string | null, email: string | null, avatar_url: string | null, };
+export type GitHubIssueImport = { tasks: Array<Task>, 
+/**
+ * Matching issues that had been imported before
+ */
+skipped: number, };
 
-export type StatusResponse = { logged_in: boolean, profile: ProfileResponse | null, degraded: boolean | null, };
+export type BoardFormat = "json" | "csv";
 
-export enum MemberRole { ADMIN = "ADMIN", MEMBER = "MEMBER" }
+export type BoardExport = { project_name: string, exported_at: string, tasks: Array<ExportedTask>, };
 
-export enum InvitationStatus { PENDING = "PENDING", ACCEPTED = "ACCEPTED", DECLINED = "DECLINED", EXPIRED = "EXPIRED" }
+export type ExportedTask = { 
+/**
+ * Id on the instance the board was exported from
+ */
+id: string | null, title: string, description: string | null, status: TaskStatus, priority: TaskPriority, 
+/**
+ * Label names
+ */
+labels: Array<string>, archived: boolean, 
+/**
+ * Newest first; not imported
+ */
+attempts: Array<ExportedAttempt>, created_at: string | null, updated_at: string | null, };
 
-export type Organization = { id: string, name: string, slug: string, is_personal: boolean, created_at: string, updated_at: string, };
+export type ExportedAttempt = { id: string, branch: string, executor: string | null, created_at: string, };
 
-export type OrganizationWithRole = { id: string, name: string, slug: string, is_personal: boolean, created_at: string, updated_at: string, user_role: MemberRole, };
+export type DuplicateStrategy = "skip" | "merge" | "create";
 
-export type ListOrganizationsResponse = { organizations: Array<OrganizationWithRole>, };
+export type ImportBoard = { format: BoardFormat, 
+/**
+ * Contents of an exported file
+ */
+content: string, on_duplicate: DuplicateStrategy, };
 
-export type GetOrganizationResponse = { organization: Organization, user_role: string, };
+export type BoardImportResult = { created: Array<string>, updated: Array<string>, skipped: number, };
 
-export type CreateOrganizationRequest = { name: string, slug: string, };
+export type TaskTimeEntry = { id: string, task_id: string, seconds: bigint, note: string | null, 
+/**
+ * When the work was done
+ */
+spent_at: string, created_at: string, };
 
-export type CreateOrganizationResponse = { organization: OrganizationWithRole, };
+export type CreateTaskTimeEntry = { seconds: bigint, note: string | null, 
+/**
+ * Defaults to now
+ */
+spent_at: string | null, };
 
-export type UpdateOrganizationRequest = { name: string, };
+export type TimeReportQuery = { from: string | null, to: string | null, };
 
-export type Invitation = { id: string, organization_id: string, invited_by_user_id: string | null, email: string, role: MemberRole, status: InvitationStatus, token: string, created_at: string, expires_at: string, };
+export type TimeTotals = { 
+/**
+ * Wall-clock time of the attempts
+ */
+attempt_seconds: bigint, 
+/**
+ * Part of the attempt time the coding agents were running
+ */
+agent_seconds: bigint, 
+/**
+ * Time logged by hand
+ */
+manual_seconds: bigint, };
 
-export type CreateInvitationRequest = { email: string, role: MemberRole, };
+export type AttemptTime = { workspace_id: string, 
+/**
+ * Executor of the attempt's first coding agent run
+ */
+executor: string | null, started_at: string, 
+/**
+ * None while a process of the attempt is running
+ */
+ended_at: string | null, wall_clock_seconds: bigint, agent_seconds: bigint, };
 
-export type CreateInvitationResponse = { invitation: Invitation, };
+export type TaskTime = { task_id: string, title: string, 
+/**
+ * Oldest first
+ */
+attempts: Array<AttemptTime>, totals: TimeTotals, };
 
-export type ListInvitationsResponse = { invitations: Array<Invitation>, };
+export type ExecutorTime = { executor: string, 
+/**
+ * Attempts in which the executor ran
+ */
+attempt_count: number, agent_seconds: bigint, };
 
-export type GetInvitationResponse = { id: string, organization_slug: string, role: MemberRole, expires_at: string, };
+export type TimeReport = { 
+/**
+ * Tasks that time was spent on, most time first
+ */
+tasks: Array<TaskTime>, 
+/**
+ * Coding agent time per executor, most time first
+ */
+executors: Array<ExecutorTime>, totals: TimeTotals, };
 
-export type AcceptInvitationResponse = { organization_id: string, organization_slug: string, role: MemberRole, };
+export type UsageReportQuery = { from: string | null, to: string | null, };
 
-export type RevokeInvitationRequest = { invitation_id: string, };
+export type UsageTotals = { 
+/**
+ * Input tokens not read from a cache
+ */
+input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_write_tokens: bigint, 
+/**
+ * Sum of the costs the executors reported
+ */
+cost_usd: number, 
+/**
+ * Coding agent runs counted
+ */
+runs: number, 
+/**
+ * Runs that reported tokens but no cost
+ */
+runs_without_cost: number, 
+/**
+ * Runs that reported nothing
+ */
+runs_without_usage: number, };
 
-export type OrganizationMember = { user_id: string, role: MemberRole, joined_at: string, };
+export type AttemptUsage = { workspace_id: string, 
+/**
+ * Executor of the attempt's first coding agent run
+ */
+executor: string | null, 
+/**
+ * None if no coding agent has run in the attempt
+ */
+started_at: string | null, totals: UsageTotals, };
 
-export type OrganizationMemberWithProfile = { user_id: string, role: MemberRole, joined_at: string, first_name: string | null, last_name: string | null, username: string | null, email: string | null, avatar_url: string | null, };
+export type TaskUsage = { task_id: string, title: string, 
+/**
+ * Oldest first
+ */
+attempts: Array<AttemptUsage>, totals: UsageTotals, };
 
-export type ListMembersResponse = { members: Array<OrganizationMemberWithProfile>, };
+export type ExecutorUsage = { executor: string, totals: UsageTotals, };
 
-export type UpdateMemberRoleRequest = { role: MemberRole, };
+export type UsageReport = { 
+/**
+ * Tasks agents ran on, most expensive first
+ */
+tasks: Array<TaskUsage>, 
+/**
+ * Most expensive first
+ */
+executors: Array<ExecutorUsage>, totals: UsageTotals, };
 
-export type UpdateMemberRoleResponse = { user_id: string, role: MemberRole, };
+export type VariantStats = { executor: string, 
+/**
+ * `DEFAULT` for the executor's default configuration
+ */
+variant: string, attempts: number, merged: number, abandoned: number, open: number, 
+/**
+ * Share of the merged and abandoned attempts that were merged; None
+ * while there are none
+ */
+merge_rate: number | null, 
+/**
+ * Coding agent runs after the first in a merged attempt, on average:
+ * the rounds of feedback a merge took
+ */
+avg_follow_ups_to_merge: number | null, 
+/**
+ * Sum of the costs the attempts' coding agent runs reported
+ */
+cost_usd: number, 
+/**
+ * All the cost, abandoned attempts' included, per merged attempt
+ */
+cost_per_merge_usd: number | null, 
+/**
+ * Runs that reported no cost, so `cost_usd` leaves them out
+ */
+runs_without_cost: number, };
 
-export type RemoteProject = { id: string, organization_id: string, name: string, metadata: Record<string, unknown>, created_at: string, };
+export type SwimlaneColumn = { column_id: string, 
+/**
+ * In board order
+ */
+tasks: Array<TaskWithAttemptStatus>, };
 
-export type ListProjectsResponse = { projects: Array<RemoteProject>, };
+export type Swimlane = { 
+/**
+ * Label, epic or parent task of the row; None for the row of tasks
+ * without one
+ */
+key: string | null, name: string, 
+/**
+ * Every column of the board, left to right
+ */
+columns: Array<SwimlaneColumn>, };
 
-export type RemoteProjectMembersResponse = { organization_id: string, members: Array<OrganizationMemberWithProfile>, };
+export type BoardSwimlanes = { grouping: SwimlaneGrouping, columns: Array<BoardColumn>, 
+/**
+ * Only rows holding tasks, the one without a key last
+ */
+lanes: Array<Swimlane>, };
 
-export type CreateRemoteProjectRequest = { organization_id: string, name: string, };
+export type StatusCount = { status: TaskStatus, count: number, };
 
-export type LinkToExistingRequest = { remote_project_id: string, };
+export type BurnUpPoint = { day: string, 
+/**
+ * Tasks in the epic by the end of the day
+ */
+scope: number, 
+/**
+ * Of those, tasks done by the end of the day
+ */
+done: number, };
+
+export type EpicProgress = { epic: Epic, 
+/**
+ * Every status, in board order
+ */
+task_counts: Array<StatusCount>, 
+/**
+ * Tasks that aren't cancelled
+ */
+total: number, done: number, 
+/**
+ * Oldest first, ending today
+ */
+burn_up: Array<BurnUpPoint>, };
 
-export type RegisterRepoRequest = { path: string, display_name: string | null, };
+export type BoardHistoryQuery = { 
+/**
+ * 30 days before `to` if not given
+ */
+from: string | null, 
+/**
+ * Today if not given
+ */
+to: string | null, };
 
-export type InitRepoRequest = { parent_path: string, folder_name: string, };
+export type BurndownPoint = { day: string, 
+/**
+ * Tasks left to do: neither done nor cancelled
+ */
+remaining: number, done: number, };
 
-export type TagSearchParams = { search: string | null, };
+export type FlowColumn = { column_id: string, 
+/**
+ * Latest name of the column in the range
+ */
+name: string, status: TaskStatus, };
 
-export type TokenResponse = { access_token: string, expires_at: string | null, };
+export type FlowDay = { day: string, 
+/**
+ * Tasks in each column, in the order of the chart's columns
+ */
+counts: Array<number>, };
 
-export type UserSystemInfo = { config: Config, analytics_user_id: string, login_status: LoginStatus, environment: Environment, 
+export type CumulativeFlow = { 
 /**
- * Capabilities supported per executor (e.g., { "CLAUDE_CODE": ["SESSION_FORK"] })
+ * Every column seen in the range, in their latest board order
  */
-capabilities: { [key in string]?: Array<BaseAgentCapability> }, executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
+columns: Array<FlowColumn>, 
+/**
+ * Oldest first
+ */
+days: Array<FlowDay>, };
 
-export type Environment = { os_type: string, os_version: string, os_architecture: string, bitness: string, };
+export type DiffStats = { files_changed: number, additions: number, deletions: number, };
 
-export type McpServerQuery = { executor: BaseCodingAgent, };
+export type TestRun = { status: ExecutionProcessStatus, exit_code: bigint | null, };
 
-export type UpdateMcpServersBody = { servers: { [key in string]?: JsonValue }, };
+export type TokenUsage = { 
+/**
+ * Cache writes and reads included
+ */
+input_tokens: bigint, output_tokens: bigint, 
+/**
+ * Only set when the agent reported a cost for every run
+ */
+cost_usd: number | null, };
 
-export type GetMcpServerResponse = { mcp_config: McpConfig, config_path: string, };
+export type AttemptComparison = { workspace: Workspace, 
+/**
+ * Executor of the attempt's first coding agent run
+ */
+executor: string | null, 
+/**
+ * Against the base of the attempt's branch, uncommitted changes included
+ */
+diff: DiffStats, 
+/**
+ * Paths changed, prefixed with the repository name
+ */
+files: Array<string>, 
+/**
+ * Changed paths outside the task's scope
+ */
+out_of_scope: Array<string>, 
+/**
+ * Last cleanup script run, if there was one
+ */
+test_run: TestRun | null, wall_clock_seconds: bigint, agent_seconds: bigint, 
+/**
+ * None when the agent doesn't report token usage
+ */
+tokens: TokenUsage | null, };
 
-export type CheckEditorAvailabilityQuery = { editor_type: EditorType, };
+export type PlannedTask = { title: string, description: string | null, 
+/**
+ * Ticked in the plan; the task is created as done
+ */
+done: boolean, subtasks: Array<PlannedTask>, };
 
-export type CheckEditorAvailabilityResponse = { available: boolean, };
+export type PreviewPlanRequest = { markdown: string, };
 
-export type CheckAgentAvailabilityQuery = { executor: BaseCodingAgent, };
+export type PlanPreview = { tasks: Array<PlannedTask>, 
+/**
+ * Tasks that would be created, subtasks included
+ */
+task_count: number, };
 
-export type CurrentUserResponse = { user_id: string, };
+export type CreatePlanTasksRequest = { 
+/**
+ * The previewed tasks, edited as needed
+ */
+tasks: Array<PlannedTask>, 
+/**
+ * Create the plan's top-level tasks as subtasks of this task
+ */
+parent_task_id: string | null, };
 
-export type CreateFollowUpAttempt = { prompt: string, variant: string | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
+export type RepoBranchStatus = { repo_id: string, repo_name: string, commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
+/**
+ * True if a `git rebase` is currently in progress in this worktree
+ */
+is_rebase_in_progress: boolean, 
+/**
+ * Current conflict operation if any
+ */
+conflict_op: ConflictOp | null, 
+/**
+ * List of files currently in conflicted (unmerged) state
+ */
+conflicted_files: Array<string>, };
 
-export type ChangeTargetBranchRequest = { repo_id: string, new_target_branch: string, };
+export type DirectoryEntry = { name: string, path: string, is_directory: boolean, is_git_repo: boolean, last_modified: bigint | null, };
 
-export type ChangeTargetBranchResponse = { repo_id: string, new_target_branch: string, status: [number, number], };
+export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
-export type MergeTaskAttemptRequest = { repo_id: string, };
+export type FileChangeKind = "created" | "modified" | "removed" | "renamed";
 
-export type PushTaskAttemptRequest = { repo_id: string, };
+export type FileChangeEvent = { kind: FileChangeKind, 
+/**
+ * Path relative to the workspace root, using forward slashes
+ */
+path: string, };
 
-export type RenameBranchRequest = { new_branch_name: string, };
+export type ArchiveFormat = "zip" | "tar.gz";
 
-export type RenameBranchResponse = { branch: string, };
+export type UploadedFile = { 
+/**
+ * Path relative to the workspace root, using forward slashes
+ */
+path: string, size_bytes: bigint, 
+/**
+ * SHA256 of the uploaded contents
+ */
+hash: string, 
+/**
+ * True when the path matches a .gitignore rule, so the agent's commits won't include it
+ */
+gitignored: boolean, };
 
-export type OpenEditorRequest = { editor_type: string | null, file_path: string | null, };
+export type FileContent = { path: string, content: string, 
+/**
+ * SHA256 of the contents; pass it back as `base_hash` when editing
+ */
+hash: string, };
 
-export type OpenEditorResponse = { url: string | null, };
+export type UpdateFileContent = { 
+/**
+ * Path relative to the workspace root
+ */
+path: string, 
+/**
+ * Hash of the contents the edit is based on, or null to create a new file
+ */
+base_hash: string | null, edit: FileEdit, };
 
-export type AssignSharedTaskRequest = { new_assignee_user_id: string | null, };
+export type FileEdit = { "type": "replace", content: string, } | { "type": "patch", diff: string, };
 
-export type ShareTaskResponse = { shared_task_id: string, };
+export type FileScope = "workspace" | "scratch" | "artifacts";
 
-export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
+export type ReclaimReason = "merged" | "abandoned";
 
-export type CreateGitHubPrRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, };
+export type ReclaimCandidate = { workspace_id: string, reason: ReclaimReason, 
+/**
+ * Merge time for merged workspaces, last activity otherwise
+ */
+since: string, 
+/**
+ * Last measured size, if the workspace has been measured yet
+ */
+size_bytes: bigint | null, };
 
-export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+export type CleanupPreview = { candidates: Array<ReclaimCandidate>, reclaimable_bytes: bigint, };
 
-export type ImageMetadata = { exists: boolean, file_name: string | null, path: string | null, size_bytes: bigint | null, format: string | null, proxy_url: string | null, };
+export type WorktreeProblem = "missing" | "broken" | "orphaned";
 
-export type CreateTaskAttemptBody = { task_id: string, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
+export type WorktreeStatus = { path: string, branch: string | null, 
+/**
+ * The attempt the worktree belongs to, if any
+ */
+workspace_id: string | null, 
+/**
+ * Why the worktree is locked, empty when no reason was given
+ */
+locked: string | null, problem: WorktreeProblem | null, };
 
-export type WorkspaceRepoInput = { repo_id: string, target_branch: string, };
+export type WorktreeReport = { repo_id: string, 
+/**
+ * Worktrees besides the repository's own checkout
+ */
+worktree_count: number, worktrees: Array<WorktreeStatus>, 
+/**
+ * Attempt branches that neither an attempt nor a worktree has any more
+ */
+dangling_branches: Array<string>, };
 
-export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId, };
+export type SkippedWorktree = { path: string, reason: string, };
 
-export type RunAgentSetupResponse = Record<string, never>;
+export type WorktreeMaintenance = { 
+/**
+ * Worktrees pruned, removed or repaired
+ */
+fixed: Array<string>, skipped: Array<SkippedWorktree>, 
+/**
+ * The repository's worktrees afterwards
+ */
+report: WorktreeReport, };
 
-export type GhCliSetupError = "BREW_MISSING" | "SETUP_HELPER_NOT_SUPPORTED" | { "OTHER": { message: string, } };
+export type ProjectCleanupState = "queued" | "running" | "completed" | "failed";
 
-export type RebaseTaskAttemptRequest = { repo_id: string, old_base_branch: string | null, new_base_branch: string | null, };
+export type ProjectCleanupProgress = { project_id: string, project_name: string, state: ProjectCleanupState, total_workspaces: number, cleaned_workspaces: number, 
+/**
+ * Set when the workspaces were moved to the trash
+ */
+trash_dir: string | null, 
+/**
+ * When trashed workspaces are removed for good
+ */
+purge_after: string | null, errors: Array<string>, };
 
-export type AbortConflictsRequest = { repo_id: string, };
+export type WorkspacePoolStatus = { 
+/**
+ * Configured number of pooled workspaces
+ */
+size: number, 
+/**
+ * Pooled workspaces ready to be claimed
+ */
+ready: number, };
 
-export type GitOperationError = { "type": "merge_conflicts", message: string, op: ConflictOp, } | { "type": "rebase_in_progress" };
+export type SetupStatus = "not_run" | "running" | "succeeded" | "failed" | "overridden";
 
-export type PushError = { "type": "force_push_required" };
+export type WorkspaceSetup = { status: SetupStatus, 
+/**
+ * Whether the coding agent has ever been started in the attempt
+ */
+agent_started: boolean, 
+/**
+ * Setup script runs, oldest first
+ */
+processes: Array<ExecutionProcess>, };
+
+export type SharedCacheUsage = { cache: SharedCache, size_bytes: bigint, 
+/**
+ * Whether a running execution holds the cache, so it can't be emptied
+ */
+in_use: boolean, };
 
-export type CreatePrError = { "type": "github_cli_not_installed" } | { "type": "github_cli_not_logged_in" } | { "type": "git_cli_not_logged_in" } | { "type": "git_cli_not_installed" } | { "type": "target_branch_not_found", branch: string, };
+export type ContainerStatus = { "status": "host" } | { "status": "remote", host: string, } | { "status": "running", name: string, } | { "status": "stopped", name: string, state: string, } | { "status": "missing" } | { "status": "unavailable", reason: string, };
 
-export type BranchStatus = { commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
+export type ExecRequest = { 
 /**
- * True if a `git rebase` is currently in progress in this worktree
+ * Command line, split into arguments like a POSIX shell would but run
+ * without one, so pipes, redirects and variable expansion are not available
  */
-is_rebase_in_progress: boolean, 
+command: string, 
 /**
- * Current conflict operation if any
+ * Directory relative to the workspace root; the workspace root when omitted
  */
-conflict_op: ConflictOp | null, 
+working_dir: string | null, 
 /**
- * List of files currently in conflicted (unmerged) state
+ * Defaults to 10 minutes, capped at an hour
  */
-conflicted_files: Array<string>, };
+timeout_secs: bigint | null, };
 
-export type RunScriptError = { "type": "no_script_configured" } | { "type": "process_already_running" };
+export type ExecOutput = { "type": "stdout", data: string, } | { "type": "stderr", data: string, } | { "type": "exit", code: number | null, } | { "type": "timed_out" };
 
-export type AttachPrResponse = { pr_attached: boolean, pr_url: string | null, pr_number: bigint | null, pr_status: MergeStatus | null, };
+export type TerminalSize = { cols: number, rows: number, };
 
-export type AttachExistingPrRequest = { repo_id: string, };
+export type OpenTerminalRequest = { cols: number, rows: number, 
+/**
+ * Directory relative to the workspace root; the workspace root when omitted
+ */
+working_dir: string | null, };
 
-export type PrCommentsResponse = { comments: Array<UnifiedPrComment>, };
+export type TerminalSessionInfo = { id: string, workspace_id: string, size: TerminalSize, created_at: string, 
+/**
+ * Exit code once the shell has exited
+ */
+exit_code: number | null, exited: boolean, };
 
-export type GetPrCommentsError = { "type": "no_pr_attached" } | { "type": "github_cli_not_installed" } | { "type": "github_cli_not_logged_in" };
+export type TerminalClientMessage = { "type": "input", data: string, } | { "type": "resize", cols: number, rows: number, };
 
-export type GetPrCommentsQuery = { repo_id: string, };
+export type ProcessKind = "execution" | "code_server" | "terminal" | "exec";
 
-export type UnifiedPrComment = { "comment_type": "general", id: string, author: string, author_association: string, body: string, created_at: string, url: string, } | { "comment_type": "review", id: bigint, author: string, author_association: string, body: string, created_at: string, url: string, path: string, line: bigint | null, diff_hunk: string, };
+export type ProcessInfo = { 
+/**
+ * Pid of the group leader, which is also the process group id
+ */
+pid: number, kind: ProcessKind, label: string, workspace_id: string | null, execution_process_id: string | null, started_at: string, };
 
-export type RepoBranchStatus = { repo_id: string, repo_name: string, commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
+export type WorktreeHealth = { repo_id: string, repo_name: string, exists: boolean, 
 /**
- * True if a `git rebase` is currently in progress in this worktree
+ * Whether the worktree has no uncommitted changes; absent when it is
+ * missing or git can't read it
  */
-is_rebase_in_progress: boolean, 
+clean: boolean | null, };
+
+export type WorkspaceHealth = { 
 /**
- * Current conflict operation if any
+ * Absent until the workspace is first created
  */
-conflict_op: ConflictOp | null, 
+workspace_dir: string | null, workspace_dir_exists: boolean, worktrees: Array<WorktreeHealth>, 
 /**
- * List of files currently in conflicted (unmerged) state
+ * Space left on the filesystem holding the workspace, where it can be
+ * measured
  */
-conflicted_files: Array<string>, };
-
-export type DirectoryEntry = { name: string, path: string, is_directory: boolean, is_git_repo: boolean, last_modified: bigint | null, };
-
-export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
+disk_free_bytes: bigint | null, container: ContainerStatus, 
+/**
+ * Processes the server runs for the attempt: executions, terminals,
+ * editors and exec commands
+ */
+processes: Array<ProcessInfo>, };
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, attempt_queue: AttemptQueueConfig, local_model_endpoints: Array<LocalModelEndpoint>, commit_signing: CommitSigningConfig, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -324,6 +2216,59 @@ export type UiLanguage = "BROWSER" | "EN" | "JA" | "ES" | "KO" | "ZH_HANS" | "ZH
 
 export type ShowcaseState = { seen_features: Array<string>, };
 
+export type AttemptQueueConfig = { 
+/**
+ * None for no limit
+ */
+max_concurrent_attempts: number | null, order: AttemptQueueOrder, };
+
+export type AttemptQueueOrder = "fifo" | "priority";
+
+export type LocalModelEndpoint = { 
+/**
+ * Unique; the profile variants made for its models are named after it
+ */
+name: string, server: LocalModelServer, 
+/**
+ * Where the server listens, e.g. http://localhost:11434
+ */
+url: string, };
+
+export type LocalModelServer = "ollama" | "vllm";
+
+export type CommitSigningConfig = { mode: CommitSigningMode, format: SigningFormat, 
+/**
+ * GPG key ID, or the path of an SSH key; None for the git config's
+ * `user.signingkey`
+ */
+key: string | null, };
+
+export type CommitSigningMode = "git_config" | "always" | "never";
+
+export type SigningFormat = "openpgp" | "ssh";
+
+export type EndpointHealth = { endpoint: LocalModelEndpoint, 
+/**
+ * The server answered with its models
+ */
+available: boolean, 
+/**
+ * How long listing the models took
+ */
+latency_ms: number | null, models: Array<string>, error: string | null, 
+/**
+ * Each model with each executor that can use it
+ */
+backends: Array<LocalModelBackend>, };
+
+export type LocalModelBackend = { executor: BaseCodingAgent, model: string, 
+/**
+ * The profile variant using the model; None until it is saved
+ */
+variant: string | null, };
+
+export type SaveLocalModelBackend = { executor: BaseCodingAgent, model: string, };
+
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
 export type SharedTaskDetails = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, };
@@ -346,6 +2291,47 @@ export type QueueStatus = { "status": "empty" } | { "status": "queued", message:
 
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
+export type SignatureStatus = "good" | "untrusted" | "expired" | "revoked" | "bad" | "unverifiable" | "unsigned";
+
+export type ConflictDetails = { 
+/**
+ * None once the operation finished or was aborted
+ */
+op: ConflictOp | null, files: Array<ConflictedFile>, };
+
+export type ConflictedFile = { path: string, 
+/**
+ * None when the side deleted the file, or it isn't text
+ */
+base: string | null, ours: string | null, theirs: string | null, binary: boolean, 
+/**
+ * Conflicts still marked in the worktree's copy
+ */
+hunks: Array<ConflictHunk>, };
+
+export type ConflictHunk = { 
+/**
+ * 1-based line of the worktree's copy where the conflict's markers start
+ */
+start_line: number, base: string | null, ours: string, theirs: string, };
+
+export type CherryPickStatus = { target_branch: string, 
+/**
+ * The target's new tip once every commit is picked, None while the
+ * cherry-pick is stopped on conflicts
+ */
+target_commit: string | null, conflicts: ConflictDetails, };
+
+export type SelectableFile = { path: string, binary: boolean, hunks: Array<DiffHunk>, };
+
+export type DiffHunk = { 
+/**
+ * The `@@ -a,b +c,d @@` line
+ */
+header: string, body: string, };
+
+export type ConflictResolution = { "type": "ours" } | { "type": "theirs" } | { "type": "content", content: string, } | { "type": "delete" };
+
 export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, };
 
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, preconfigured: JsonValue, is_toml_config: boolean, };
@@ -363,9 +2349,9 @@ working_dir: string | null, };
 
 export type ScriptRequestLanguage = "Bash";
 
-export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", DROID = "DROID" }
+export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", DROID = "DROID", OPENHANDS = "OPENHANDS", API_AGENT = "API_AGENT", GOOSE = "GOOSE", PLUGIN = "PLUGIN" }
 
-export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid };
+export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } | { "OPENHANDS": Openhands } | { "API_AGENT": ApiAgent } | { "GOOSE": Goose } | { "PLUGIN": Plugin };
 
 export type AvailabilityInfo = { "type": "LOGIN_DETECTED", last_auth_timestamp: bigint, } | { "type": "INSTALLATION_FOUND" } | { "type": "NOT_FOUND" };
 
@@ -389,12 +2375,14 @@ executor: BaseCodingAgent,
  */
 variant: string | null, };
 
-export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } };
+export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } | { "OPENHANDS": Openhands } | { "API_AGENT": ApiAgent } | { "GOOSE": Goose } | { "PLUGIN": Plugin } };
 
 export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
 
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER" }
 
+export type FailureKind = "rate_limit" | "network" | "crash" | "other";
+
 export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type Gemini = { append_prompt: AppendPrompt, model?: string | null, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
@@ -431,8 +2419,81 @@ export type Autonomy = "normal" | "low" | "medium" | "high" | "skip-permissions-
 
 export type DroidReasoningEffort = "none" | "dynamic" | "off" | "low" | "medium" | "high";
 
+export type Openhands = { append_prompt: AppendPrompt, model?: string | null, base_url?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+export type ApiAgent = { append_prompt: AppendPrompt, base_url?: string | null, model?: string | null, api_key_env?: string | null, max_turns?: number | null, temperature?: number | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+export type Goose = { append_prompt: AppendPrompt, provider?: string | null, model?: string | null, max_turns?: number | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+export type Plugin = { append_prompt: AppendPrompt, plugin: string, options?: JsonValue | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+export type PluginManifest = { 
+/**
+ * Lowercase letters, digits, `-` and `_`; the profile variant is this
+ * name in SCREAMING_SNAKE_CASE
+ */
+name: string, description?: string | null, version?: string | null, 
+/**
+ * Version of the stdio protocol the plugin speaks
+ */
+protocol: number, 
+/**
+ * Command line starting the plugin. A program starting with `./` is
+ * relative to the plugin's directory.
+ */
+command: string, 
+/**
+ * Whether the plugin can continue a session it reported
+ */
+follow_ups: boolean, 
+/**
+ * Environment variables the plugin takes an API key from
+ */
+api_key_env: Array<string>, };
+
+export type DiscoveredPlugin = { 
+/**
+ * Directory holding the manifest
+ */
+dir: string, manifest: PluginManifest | null, 
+/**
+ * Why the plugin can't be used
+ */
+error: string | null, };
+
 export type AppendPrompt = string | null;
 
+export type PromptAdditions = { prefix: string | null, 
+/**
+ * What earlier attempts at the task left, put between the prefix and the
+ * prompt
+ */
+context: string | null, suffix: string | null, };
+
+export type TestRunner = "cargo" | "pytest" | "jest";
+
+export type TestSummary = { runner: TestRunner, passed: number, 
+/**
+ * Failed tests, and for pytest also errors in setup or collection
+ */
+failed: number, 
+/**
+ * Skipped or ignored tests, and for pytest expected failures
+ */
+skipped: number, };
+
+export type ExecutorHealth = { executor: BaseCodingAgent, cli: CliCheck, auth: AuthCheck, mcp_config: McpConfigCheck, 
+/**
+ * Nothing found that keeps the executor from starting
+ */
+ready: boolean, };
+
+export type CliCheck = { "status": "ok", version: string | null, } | { "status": "not_installed", program: string, } | { "status": "failed", error: string, } | { "status": "timed_out" } | { "status": "not_needed" };
+
+export type AuthCheck = { "status": "logged_in", last_auth_timestamp: bigint, } | { "status": "api_key", variable: string, } | { "status": "config_found" } | { "status": "missing" } | { "status": "not_needed" };
+
+export type McpConfigCheck = { "status": "valid", path: string, servers: number, } | { "status": "missing", path: string, } | { "status": "invalid", path: string, error: string, } | { "status": "unsupported" };
+
 export type CodingAgentInitialRequest = { prompt: string, 
 /**
  * Executor profile specification
@@ -442,7 +2503,11 @@ executor_profile_id: ExecutorProfileId,
  * Optional relative path to execute the agent in (relative to container_ref).
  * If None, uses the container_ref directory directly.
  */
-working_dir: string | null, };
+working_dir: string | null, 
+/**
+ * The project's prompt additions as they were when the run started
+ */
+prompt_additions: PromptAdditions | null, };
 
 export type CodingAgentFollowUpRequest = { prompt: string, session_id: string, 
 /**
@@ -453,7 +2518,11 @@ executor_profile_id: ExecutorProfileId,
  * Optional relative path to execute the agent in (relative to container_ref).
  * If None, uses the container_ref directory directly.
  */
-working_dir: string | null, };
+working_dir: string | null, 
+/**
+ * The project's prompt additions as they were when the run started
+ */
+prompt_additions: PromptAdditions | null, };
 
 export type CommandExitStatus = { "type": "exit_code", code: number, } | { "type": "success", success: boolean, };
 